@@ -0,0 +1,147 @@
+//! One-shot timer-heap deadline on the BSP's local APIC.
+//!
+//! The BSP keeps its 100 Hz PIT tick for preemption and housekeeping, and
+//! that tick alone would leave every [`crate::process::timer`] deadline
+//! waiting for the next 10 ms edge. The BSP's LAPIC timer is otherwise
+//! unused, so it is armed for the earliest heap deadline: in TSC-deadline
+//! mode where the CPU has it, else as a one-shot countdown converted from
+//! nanoseconds. Another CPU that arms an earlier deadline than the one
+//! programmed sends the BSP [`super::lapic::CLOCKEVENT_VECTOR`], the vector
+//! the timer fires on, and the BSP reprograms.
+//!
+//! A fired deadline only wakes the timer service. Reprogramming a deadline
+//! that is still due would fire again at once, so the handler leaves the
+//! timer disarmed in that case and the service asks for a reprogram
+//! ([`reprogram_soon`]) after each pass.
+
+use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use super::interrupt_guard::InterruptGuard;
+
+const MODE_OFF: u8 = 0;
+const MODE_TSC_DEADLINE: u8 = 1;
+const MODE_ONESHOT: u8 = 2;
+/// How long the one-shot countdown is timed against the TSC.
+const CALIBRATION_NS: u64 = 1_000_000;
+const NOT_PROGRAMMED: u64 = u64::MAX;
+
+static MODE: AtomicU8 = AtomicU8::new(MODE_OFF);
+/// LAPIC timer counts per nanosecond as 32.32 fixed point, for one-shot mode.
+static COUNTS_PER_NS: AtomicU64 = AtomicU64::new(0);
+/// Deadline the hardware is armed for.
+static PROGRAMMED_NS: AtomicU64 = AtomicU64::new(NOT_PROGRAMMED);
+
+/// Take over the BSP's LAPIC timer. Without a LAPIC or a calibrated TSC the
+/// heap stays on the PIT tick.
+pub fn init() {
+    if !super::lapic::available() || !super::tsc::calibrated() {
+        crate::debug_info!("clockevent: timer deadlines follow the PIT tick");
+        return;
+    }
+    debug_assert_eq!(super::percpu::cpu_id(), 0);
+    {
+        let _interrupts = InterruptGuard::disable();
+        if super::tsc::deadline_timer_supported() {
+            super::lapic::configure_tsc_deadline_timer();
+            MODE.store(MODE_TSC_DEADLINE, Ordering::Release);
+        } else {
+            super::lapic::start_timer_calibration();
+            let start = crate::time::monotonic_ns();
+            let mut elapsed = 0;
+            while elapsed < CALIBRATION_NS {
+                core::hint::spin_loop();
+                elapsed = crate::time::monotonic_ns() - start;
+            }
+            let counts = u32::MAX - super::lapic::timer_current_count();
+            COUNTS_PER_NS.store(
+                ((u128::from(counts) << 32) / u128::from(elapsed)) as u64,
+                Ordering::Relaxed,
+            );
+            super::lapic::configure_oneshot_timer();
+            MODE.store(MODE_ONESHOT, Ordering::Release);
+        }
+    }
+    crate::debug_info!(
+        "clockevent: {} on CPU 0",
+        if MODE.load(Ordering::Relaxed) == MODE_TSC_DEADLINE {
+            "TSC deadline"
+        } else {
+            "LAPIC one-shot"
+        }
+    );
+    reprogram_soon();
+}
+
+/// Make sure the hardware fires by `deadline_ns`, the heap's new earliest
+/// deadline. Callable on any CPU.
+pub fn deadline_lowered(deadline_ns: u64) {
+    // Pairs with the store in `reprogram`: either this CPU sees the newer
+    // programmed deadline, or the BSP's recheck sees this deadline.
+    if deadline_ns < PROGRAMMED_NS.load(Ordering::SeqCst) {
+        reprogram_soon();
+    }
+}
+
+/// Reprogram for the heap's current earliest deadline, directly on the BSP
+/// and by IPI from anywhere else.
+pub fn reprogram_soon() {
+    if MODE.load(Ordering::Acquire) == MODE_OFF {
+        return;
+    }
+    if super::percpu::cpu_id() == 0 {
+        reprogram();
+    } else {
+        super::lapic::send_fixed(
+            super::acpi::topology().bsp_lapic_id,
+            super::lapic::CLOCKEVENT_VECTOR,
+        );
+    }
+}
+
+/// BSP interrupt on [`super::lapic::CLOCKEVENT_VECTOR`]: a deadline fired or
+/// another CPU asked for a reprogram.
+pub fn on_interrupt() {
+    if MODE.load(Ordering::Acquire) == MODE_OFF {
+        return;
+    }
+    PROGRAMMED_NS.store(NOT_PROGRAMMED, Ordering::SeqCst);
+    let now = crate::time::monotonic_ns();
+    crate::process::timer::on_tick(now);
+    if !crate::process::timer::deadline_due(now) {
+        reprogram();
+    }
+}
+
+fn reprogram() {
+    let _interrupts = InterruptGuard::disable();
+    loop {
+        let deadline = crate::process::timer::earliest_deadline_ns();
+        program(deadline);
+        PROGRAMMED_NS.store(deadline, Ordering::SeqCst);
+        if crate::process::timer::earliest_deadline_ns() == deadline {
+            break;
+        }
+    }
+}
+
+fn program(deadline_ns: u64) {
+    match MODE.load(Ordering::Acquire) {
+        MODE_TSC_DEADLINE if deadline_ns == NOT_PROGRAMMED => {
+            super::lapic::arm_tsc_deadline(0);
+        }
+        MODE_TSC_DEADLINE => {
+            super::lapic::arm_tsc_deadline(super::tsc::tsc_at(deadline_ns).max(1));
+        }
+        MODE_ONESHOT if deadline_ns == NOT_PROGRAMMED => {
+            super::lapic::arm_oneshot_timer(0);
+        }
+        MODE_ONESHOT => {
+            // A deadline past the counter's range fires early and reprograms.
+            let delay = deadline_ns.saturating_sub(crate::time::monotonic_ns());
+            let counts =
+                (u128::from(delay) * u128::from(COUNTS_PER_NS.load(Ordering::Relaxed))) >> 32;
+            super::lapic::arm_oneshot_timer(u32::try_from(counts).unwrap_or(u32::MAX).max(1));
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,83 @@
+//! Debug exception (#DB) entry.
+//!
+//! The only source of #DB is RFLAGS.TF, which `ptrace(PTRACE_SINGLESTEP)`
+//! sets in a tracee's saved state. Stopping the tracee needs its full
+//! register file, so the entry saves the general-purpose registers in the
+//! timer handler's [`InterruptStackFrame`] layout and hands the frame to
+//! `ptrace`, which may park the task and never return here.
+
+use core::arch::naked_asm;
+
+use crate::arch::x86_64::preemption::InterruptStackFrame;
+
+/// RFLAGS.TF.
+pub const TRAP_FLAG: u64 = 1 << 8;
+
+/// #DB entry. The CPU pushes no error code for this vector.
+#[unsafe(naked)]
+#[no_mangle]
+pub unsafe extern "C" fn debug_exception_handler() {
+    naked_asm!(
+        // Same push order as the timer handler: InterruptStackFrame.
+        "push rax",
+        "push rbx",
+        "push rcx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push rbp",
+        "push r8",
+        "push r9",
+        "push r10",
+        "push r11",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+        "mov rdi, rsp",
+        "call {debug_exception_inner}",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop r11",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rbp",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "pop rbx",
+        "pop rax",
+        "iretq",
+        debug_exception_inner = sym debug_exception_inner,
+    );
+}
+
+#[no_mangle]
+extern "C" fn debug_exception_inner(stack_frame: *mut InterruptStackFrame) {
+    let frame = unsafe { &mut *stack_frame };
+    let previous_cpl = (frame.cs & 3) as u8;
+    crate::diagnostics::trace::record_interrupt_boundary(
+        crate::diagnostics::trace::EventKind::InterruptEntry,
+        1,
+        previous_cpl,
+        false,
+        crate::diagnostics::trace::InterruptOutcome::Return,
+    );
+    if previous_cpl == 3 {
+        crate::userland::ptrace::single_step_trap(frame);
+    } else {
+        // SFMASK clears TF on SYSCALL, so a kernel-mode trap is stray.
+        frame.rflags &= !TRAP_FLAG;
+    }
+    crate::diagnostics::trace::record_interrupt_boundary(
+        crate::diagnostics::trace::EventKind::InterruptExit,
+        1,
+        previous_cpl,
+        true,
+        crate::diagnostics::trace::InterruptOutcome::Return,
+    );
+}
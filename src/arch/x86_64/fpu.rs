@@ -111,9 +111,9 @@ impl FpuState {
         s
     }
 
-    /// Raw byte view — used by the test-only roundtrip check that
-    /// asserts XMM register state survives a save/restore pair.
-    #[cfg(feature = "test")]
+    /// Raw byte view — the `NT_PRFPREG` note of a core dump, and the
+    /// test-only roundtrip check that asserts XMM register state
+    /// survives a save/restore pair.
     pub fn bytes(&self) -> &[u8; 512] {
         &self.bytes
     }
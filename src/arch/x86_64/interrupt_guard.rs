@@ -68,7 +68,17 @@ impl<T> InterruptMutex<T> {
             crate::diagnostics::shadow::locks::LockKind::Interrupt,
             site,
         );
-        let inner = self.inner.lock();
+        // The owner may be waiting for this CPU to acknowledge a TLB
+        // shootdown, which the masked IPI cannot deliver while we spin.
+        let inner = loop {
+            if let Some(inner) = self.inner.try_lock() {
+                break inner;
+            }
+            while self.inner.is_locked() {
+                super::shootdown::service_pending();
+                core::hint::spin_loop();
+            }
+        };
         crate::diagnostics::shadow::locks::acquired(
             self.class,
             crate::diagnostics::shadow::locks::LockKind::Interrupt,
@@ -24,12 +24,12 @@ fn route_user_fault_or_panic(
         crate::diagnostics::trace::InterruptOutcome::Return,
     );
     if frame_is_user(stack_frame.code_segment as u64) {
-        cleanup_user_process(AbnormalExit {
+        cleanup_user_process(AbnormalExit::from_user_frame(
             vector,
             error_code,
             fault_addr,
-            fault_rip: stack_frame.instruction_pointer,
-        });
+            stack_frame,
+        ));
     }
     crate::diagnostics::crash::begin_trap(
         panic_msg,
@@ -81,6 +81,13 @@ lazy_static! {
 
         // Set up exception handlers
         idt.breakpoint.set_handler_fn(breakpoint_handler);
+        // #DB needs the full user register file for ptrace single-step, so it
+        // takes the timer's naked-entry shape rather than `x86-interrupt`.
+        unsafe {
+            use crate::arch::x86_64::debug_trap::debug_exception_handler;
+            idt.debug
+                .set_handler_addr(x86_64::VirtAddr::new(debug_exception_handler as usize as u64));
+        }
         unsafe {
             idt.non_maskable_interrupt
                 .set_handler_fn(crash_nmi_handler)
@@ -140,6 +147,10 @@ lazy_static! {
             .set_handler_fn(reschedule_interrupt_handler);
         idt[crate::arch::x86_64::lapic::HALT_VECTOR as usize]
             .set_handler_fn(halt_interrupt_handler);
+        idt[crate::arch::x86_64::lapic::TLB_SHOOTDOWN_VECTOR as usize]
+            .set_handler_fn(tlb_shootdown_interrupt_handler);
+        idt[crate::arch::x86_64::lapic::CLOCKEVENT_VECTOR as usize]
+            .set_handler_fn(clockevent_interrupt_handler);
         idt[crate::arch::x86_64::lapic::ERROR_VECTOR as usize]
             .set_handler_fn(lapic_error_interrupt_handler);
         idt[crate::arch::x86_64::lapic::SPURIOUS_VECTOR as usize]
@@ -507,8 +518,8 @@ extern "x86-interrupt" fn page_fault_handler(
         // demand page after the CPU raised a non-present fault but before we
         // acquired the process/mapper locks. If the shared page tables now
         // contain a leaf, discard the local stale translation and retry.
-        // This is the narrow same-address-space counterpart to a future
-        // cross-CPU user TLB shootdown and is required by pthread startup.
+        // Not-present entries are never shot down remotely, so this retry is
+        // what lets the second task see a leaf its sibling just installed.
         if !error_code.contains(x86_64::structures::idt::PageFaultErrorCode::PROTECTION_VIOLATION) {
             let l4 = crate::userland::lifecycle::with_current_group(|process| {
                 process.address_space.as_ref().map(|space| space.l4_frame())
@@ -559,12 +570,12 @@ extern "x86-interrupt" fn page_fault_handler(
             accessed_addr,
             error_code,
         );
-        cleanup_user_process(AbnormalExit {
-            vector: 14,
-            error_code: Some(error_code.bits()),
-            fault_addr: Some(accessed_addr),
-            fault_rip: stack_frame.instruction_pointer,
-        });
+        cleanup_user_process(AbnormalExit::from_user_frame(
+            14,
+            Some(error_code.bits()),
+            Some(accessed_addr),
+            &stack_frame,
+        ));
     }
 
     if (addr >= HEAP_START && addr < HEAP_END)
@@ -698,12 +709,17 @@ extern "x86-interrupt" fn alignment_check_handler(
 
 // Hardware Interrupt Handlers
 
-/// Timer tick counter (atomic for safe access)
+/// PIT interrupts taken on the BSP (atomic for safe access)
 pub static TIMER_TICKS: AtomicU64 = AtomicU64::new(0);
 
-/// Get the current timer tick count
+/// Get the current timer tick count: whole 10 ms periods since boot. Once
+/// the TSC is calibrated this is read from it, so it agrees with
+/// [`crate::time::monotonic_ns`] even if PIT interrupts were lost.
 pub fn get_timer_ticks() -> u64 {
-    TIMER_TICKS.load(Ordering::Relaxed)
+    match crate::arch::x86_64::tsc::now_ns() {
+        Some(ns) => ns / crate::time::PIT_NANOSECONDS_PER_TICK,
+        None => TIMER_TICKS.load(Ordering::Relaxed),
+    }
 }
 
 extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStackFrame) {
@@ -764,6 +780,31 @@ extern "x86-interrupt" fn reschedule_interrupt_handler(stack_frame: InterruptSta
     );
 }
 
+extern "x86-interrupt" fn tlb_shootdown_interrupt_handler(_stack_frame: InterruptStackFrame) {
+    crate::arch::x86_64::shootdown::service_pending();
+    crate::arch::x86_64::lapic::eoi();
+}
+
+extern "x86-interrupt" fn clockevent_interrupt_handler(stack_frame: InterruptStackFrame) {
+    let previous_cpl = (stack_frame.code_segment & 3) as u8;
+    crate::diagnostics::trace::record_interrupt_boundary(
+        crate::diagnostics::trace::EventKind::InterruptEntry,
+        crate::arch::x86_64::lapic::CLOCKEVENT_VECTOR,
+        previous_cpl,
+        false,
+        crate::diagnostics::trace::InterruptOutcome::Return,
+    );
+    crate::arch::x86_64::clockevent::on_interrupt();
+    crate::arch::x86_64::lapic::eoi();
+    crate::diagnostics::trace::record_interrupt_boundary(
+        crate::diagnostics::trace::EventKind::InterruptExit,
+        crate::arch::x86_64::lapic::CLOCKEVENT_VECTOR,
+        previous_cpl,
+        true,
+        crate::diagnostics::trace::InterruptOutcome::Return,
+    );
+}
+
 extern "x86-interrupt" fn halt_interrupt_handler(_stack_frame: InterruptStackFrame) {
     crate::arch::x86_64::lapic::eoi();
     x86_64::instructions::interrupts::disable();
@@ -9,6 +9,8 @@ use crate::{debug_info, debug_warn};
 
 pub const RESCHEDULE_VECTOR: u8 = 0xf0;
 pub const HALT_VECTOR: u8 = 0xf1;
+pub const TLB_SHOOTDOWN_VECTOR: u8 = 0xf2;
+pub const CLOCKEVENT_VECTOR: u8 = 0xf3;
 pub const ERROR_VECTOR: u8 = 0xfe;
 pub const SPURIOUS_VECTOR: u8 = 0xff;
 pub const LAPIC_TIMER_VECTOR: u8 = 0xef;
@@ -16,6 +18,7 @@ pub const LAPIC_TIMER_VECTOR: u8 = 0xef;
 pub const LAPIC_VIRT_BASE: u64 = 0x0000_5580_0000_0000;
 
 const APIC_BASE_MSR: u32 = 0x1b;
+const TSC_DEADLINE_MSR: u32 = 0x6e0;
 const APIC_BASE_ENABLE: u64 = 1 << 11;
 const REG_ID: u32 = 0x020;
 const REG_TPR: u32 = 0x080;
@@ -34,6 +37,7 @@ const REG_TIMER_DIVIDE: u32 = 0x3e0;
 
 const LVT_MASKED: u32 = 1 << 16;
 const TIMER_PERIODIC: u32 = 1 << 17;
+const TIMER_TSC_DEADLINE: u32 = 2 << 17;
 const ICR_DELIVERY_PENDING: u32 = 1 << 12;
 const ICR_ALL_EXCLUDING_SELF: u32 = 3 << 18;
 const ICR_DELIVERY_NMI: u32 = 4 << 8;
@@ -146,6 +150,56 @@ pub fn configure_periodic_timer(initial_count: u32) {
     }
 }
 
+/// Mask or unmask the timer without disturbing its count, so a periodic
+/// timer resumes in phase.
+pub fn set_timer_masked(masked: bool) {
+    unsafe {
+        let lvt = read(REG_LVT_TIMER);
+        write(
+            REG_LVT_TIMER,
+            if masked {
+                lvt | LVT_MASKED
+            } else {
+                lvt & !LVT_MASKED
+            },
+        );
+    }
+}
+
+/// Disarmed one-shot countdown on [`CLOCKEVENT_VECTOR`]; see
+/// [`arm_oneshot_timer`].
+pub fn configure_oneshot_timer() {
+    unsafe {
+        write(REG_TIMER_DIVIDE, 0x3);
+        write(REG_LVT_TIMER, u32::from(CLOCKEVENT_VECTOR));
+        write(REG_TIMER_INITIAL, 0);
+    }
+}
+
+/// Fire once after `count` divided bus clocks; 0 disarms.
+pub fn arm_oneshot_timer(count: u32) {
+    unsafe { write(REG_TIMER_INITIAL, count) }
+}
+
+/// Disarmed TSC-deadline timer on [`CLOCKEVENT_VECTOR`]; see
+/// [`arm_tsc_deadline`].
+pub fn configure_tsc_deadline_timer() {
+    unsafe {
+        write(
+            REG_LVT_TIMER,
+            TIMER_TSC_DEADLINE | u32::from(CLOCKEVENT_VECTOR),
+        );
+    }
+    // The mode switch must land before the deadline MSR write (SDM 10.5.4.1).
+    core::sync::atomic::fence(Ordering::SeqCst);
+    arm_tsc_deadline(0);
+}
+
+/// Fire once the TSC reaches `tsc`, at once if it already has; 0 disarms.
+pub fn arm_tsc_deadline(tsc: u64) {
+    unsafe { Msr::new(TSC_DEADLINE_MSR).write(tsc) }
+}
+
 pub fn start_timer_calibration() {
     unsafe {
         write(REG_TIMER_DIVIDE, 0x3);
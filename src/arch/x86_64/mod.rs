@@ -1,5 +1,7 @@
 pub mod acpi;
+pub mod clockevent;
 pub mod context_switch;
+pub mod debug_trap;
 pub mod fpu;
 pub mod gdt;
 pub mod interrupt_guard;
@@ -12,5 +14,7 @@ pub mod preemption;
 pub mod preemption_guard;
 pub mod random;
 pub mod rtc;
+pub mod shootdown;
 pub mod smp;
 pub mod syscall;
+pub mod tsc;
@@ -56,7 +56,14 @@ pub fn program_syscall_msrs(
     Star::write(cs_user, ss_user, cs_kernel, ss_kernel)
         .expect("STAR selectors violate SYSRET +8/+16 invariant");
     LStar::write(lstar);
-    SFMask::write(RFlags::INTERRUPT_FLAG | RFlags::DIRECTION_FLAG | RFlags::ALIGNMENT_CHECK);
+    // TF too: a single-stepped SYSCALL must not trap on the kernel's first
+    // instruction, before the stack switch.
+    SFMask::write(
+        RFlags::INTERRUPT_FLAG
+            | RFlags::DIRECTION_FLAG
+            | RFlags::ALIGNMENT_CHECK
+            | RFlags::TRAP_FLAG,
+    );
 }
 
 /// Set `IA32_GS_BASE` and `IA32_KERNEL_GS_BASE` to the same per-CPU pointer.
@@ -28,8 +28,13 @@ pub struct CpuLocal {
     user_ticks: AtomicU64,
     system_ticks: AtomicU64,
     idle_ticks: AtomicU64,
+    /// Monotonic ns at which the tick was stopped for idle; 0 while ticking.
+    tickless_since_ns: AtomicU64,
     in_mapper: AtomicBool,
     pending_context_publish: AtomicU64,
+    active_user_l4: AtomicU64,
+    shootdown_requested: AtomicU64,
+    shootdown_completed: AtomicU64,
 }
 
 unsafe impl Sync for CpuLocal {}
@@ -52,8 +57,12 @@ impl CpuLocal {
             user_ticks: AtomicU64::new(0),
             system_ticks: AtomicU64::new(0),
             idle_ticks: AtomicU64::new(0),
+            tickless_since_ns: AtomicU64::new(0),
             in_mapper: AtomicBool::new(false),
             pending_context_publish: AtomicU64::new(0),
+            active_user_l4: AtomicU64::new(0),
+            shootdown_requested: AtomicU64::new(0),
+            shootdown_completed: AtomicU64::new(0),
         }
     }
 }
@@ -107,7 +116,9 @@ pub unsafe fn init_cpu(logical_id: usize, lapic_id: u8, kernel_rsp_top: u64) ->
     (*local).user_ticks.store(0, Ordering::Release);
     (*local).system_ticks.store(0, Ordering::Release);
     (*local).idle_ticks.store(0, Ordering::Release);
+    (*local).tickless_since_ns.store(0, Ordering::Release);
     super::msr::init_gs_base(local as u64);
+    super::tsc::set_cpu_number(logical_id);
     INITIALIZED_CPUS.fetch_add(1, Ordering::AcqRel);
     local
 }
@@ -196,6 +207,20 @@ pub fn set_idle_interruptible(value: bool) {
     local().idle_interruptible.store(value, Ordering::Release);
 }
 
+/// PID of the ring-3 task loaded on `cpu`, if any.
+pub fn user_pid_on(cpu: usize) -> Option<u32> {
+    if cpu >= MAX_CPUS {
+        return None;
+    }
+    match unsafe { &*core::ptr::addr_of!(CPU_LOCALS[cpu]) }
+        .current_user_pid
+        .load(Ordering::SeqCst)
+    {
+        NO_PID => None,
+        pid => Some(pid),
+    }
+}
+
 pub fn idle_interruptible(cpu: usize) -> bool {
     cpu < MAX_CPUS
         && unsafe { &*core::ptr::addr_of!(CPU_LOCALS[cpu]) }
@@ -227,16 +252,50 @@ pub fn record_cpu_time(kind: CpuTimeKind) {
     counter.fetch_add(1, Ordering::Relaxed);
 }
 
+/// The scheduling timer on this CPU stops for idle at `now_ns`; the idle
+/// samples it misses are charged by [`end_tickless_idle`].
+pub fn begin_tickless_idle(now_ns: u64) {
+    local()
+        .tickless_since_ns
+        .store(now_ns.max(1), Ordering::Release);
+}
+
+/// The scheduling timer on this CPU is running again. Charge one idle
+/// sample per tick boundary crossed while it was stopped.
+pub fn end_tickless_idle(now_ns: u64) {
+    let since = local().tickless_since_ns.swap(0, Ordering::AcqRel);
+    if since != 0 {
+        local()
+            .idle_ticks
+            .fetch_add(ticks_crossed(since, now_ns), Ordering::Relaxed);
+    }
+}
+
+fn ticks_crossed(since_ns: u64, now_ns: u64) -> u64 {
+    let tick = crate::time::PIT_NANOSECONDS_PER_TICK;
+    (now_ns / tick).saturating_sub(since_ns / tick)
+}
+
 /// Snapshot the monotonic scheduling-timer counters for one logical CPU.
+/// A CPU in tickless idle reports the idle samples accrued so far.
 pub fn cpu_time_snapshot(cpu: usize) -> Option<CpuTimeSnapshot> {
     if cpu >= initialized_cpu_count() || cpu >= MAX_CPUS {
         return None;
     }
     let local = unsafe { &*core::ptr::addr_of!(CPU_LOCALS[cpu]) };
+    let since = local.tickless_since_ns.load(Ordering::Acquire);
+    let pending_idle = if since == 0 {
+        0
+    } else {
+        ticks_crossed(since, crate::time::monotonic_ns())
+    };
     Some(CpuTimeSnapshot {
         user: local.user_ticks.load(Ordering::Relaxed),
         system: local.system_ticks.load(Ordering::Relaxed),
-        idle: local.idle_ticks.load(Ordering::Relaxed),
+        idle: local
+            .idle_ticks
+            .load(Ordering::Relaxed)
+            .saturating_add(pending_idle),
     })
 }
 
@@ -315,3 +374,54 @@ pub fn take_pending_context_publish() -> Option<crate::process::entity::EntityId
     }
     entity
 }
+
+/// Publish the user L4 (physical address) this CPU is about to load, or 0
+/// once it runs on the kernel L4. Set before a user CR3 write and cleared
+/// after the switch away, so a TLB shootdown never misses a CPU that can
+/// still cache the address space's translations.
+pub fn set_active_user_l4(l4: u64) {
+    local().active_user_l4.store(l4, Ordering::SeqCst);
+}
+
+pub fn active_user_l4() -> u64 {
+    local().active_user_l4.load(Ordering::SeqCst)
+}
+
+pub fn active_user_l4_on(cpu: usize) -> u64 {
+    if cpu >= MAX_CPUS {
+        return 0;
+    }
+    unsafe { &*core::ptr::addr_of!(CPU_LOCALS[cpu]) }
+        .active_user_l4
+        .load(Ordering::SeqCst)
+}
+
+/// Queue one TLB shootdown on `cpu` and return the ticket its
+/// acknowledgement must reach.
+pub fn request_shootdown(cpu: usize) -> u64 {
+    assert!(cpu < MAX_CPUS);
+    unsafe { &*core::ptr::addr_of!(CPU_LOCALS[cpu]) }
+        .shootdown_requested
+        .fetch_add(1, Ordering::AcqRel)
+        + 1
+}
+
+pub fn shootdown_completed(cpu: usize) -> u64 {
+    assert!(cpu < MAX_CPUS);
+    unsafe { &*core::ptr::addr_of!(CPU_LOCALS[cpu]) }
+        .shootdown_completed
+        .load(Ordering::Acquire)
+}
+
+/// Newest shootdown ticket queued on this CPU that it has not yet
+/// acknowledged. Only the owning CPU acknowledges, so the answer stays
+/// valid until [`complete_shootdown`].
+pub fn pending_shootdown() -> Option<u64> {
+    let local = local();
+    let requested = local.shootdown_requested.load(Ordering::Acquire);
+    (requested != local.shootdown_completed.load(Ordering::Relaxed)).then_some(requested)
+}
+
+pub fn complete_shootdown(ticket: u64) {
+    local().shootdown_completed.store(ticket, Ordering::Release);
+}
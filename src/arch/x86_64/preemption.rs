@@ -140,8 +140,9 @@ extern "C" fn timer_handler_inner(stack_frame: *mut InterruptStackFrame) {
     use crate::arch::x86_64::interrupts::{eoi, InterruptIndex, TIMER_TICKS};
     use core::sync::atomic::Ordering;
 
-    // The BSP PIT is the sole wall-clock/timer-heap driver. AP LAPIC timers
-    // provide local preemption only.
+    // The BSP PIT is the timer heap's backstop tick; the BSP clockevent
+    // fires deadlines between ticks. AP LAPIC timers provide local
+    // preemption only.
     let cpu = crate::arch::x86_64::percpu::cpu_id();
     let frame = unsafe { &*stack_frame };
     let previous_cpl = (frame.cs & 3) as u8;
@@ -157,15 +158,15 @@ extern "C" fn timer_handler_inner(stack_frame: *mut InterruptStackFrame) {
         false,
         crate::diagnostics::trace::InterruptOutcome::Return,
     );
-    let ticks = if cpu == 0 {
-        let ticks = TIMER_TICKS.fetch_add(1, Ordering::Relaxed) + 1;
+    if cpu == 0 {
+        TIMER_TICKS.fetch_add(1, Ordering::Relaxed);
         let _ = crate::process::drain_kernel_io_wakes();
-        crate::process::timer::on_tick(ticks);
-        ticks
+        crate::process::timer::on_tick(crate::time::monotonic_ns());
+        crate::time::on_tick();
     } else {
         crate::arch::x86_64::interrupts::record_lapic_timer_tick(cpu);
-        TIMER_TICKS.load(Ordering::Relaxed)
-    };
+    }
+    let ticks = crate::arch::x86_64::interrupts::get_timer_ticks();
 
     let time_kind = if (frame.cs & 3) == 3 {
         crate::arch::x86_64::percpu::CpuTimeKind::User
@@ -195,13 +196,17 @@ extern "C" fn timer_handler_inner(stack_frame: *mut InterruptStackFrame) {
         // (resume_ring3's atomic swap invariant). try_lock only: with
         // IF cleared here no holder can be preempted mid-hold, but a
         // dropped sample under contention is harmless for accounting.
+        // RLIMIT_CPU signals go out once the table lock is released.
+        let mut cpu_limit_signal = None;
         if let Some(mut table) = crate::userland::lifecycle::PROCESS_TABLE.try_lock() {
             if let Some(pid) = crate::arch::x86_64::percpu::current_user_pid() {
-                if let Some(p) = table.by_pid.get_mut(&pid) {
-                    p.utime_ticks = p.utime_ticks.saturating_add(1);
-                }
+                cpu_limit_signal = crate::userland::rlimit::charge_cpu_tick(&mut table, pid, true);
             }
         }
+        if let Some((tgid, sig)) = cpu_limit_signal {
+            crate::userland::job_control::send_signal(tgid, sig);
+            crate::userland::syscalls::maybe_terminate_pending_fatal_signal();
+        }
         if let Some(mut sched) = crate::process::scheduler::SCHEDULER.try_lock() {
             if let Some(current_pid) = sched.current() {
                 if let Some(pcb) = sched.get_process_mut(current_pid) {
@@ -237,6 +242,11 @@ extern "C" fn timer_handler_inner(stack_frame: *mut InterruptStackFrame) {
             );
             return;
         }
+        // A traced process stops for its tracer on a pending signal.
+        crate::userland::ptrace::signal_stop_preempted();
+        // A stop signal sent while this process ran in ring 3 takes
+        // effect here; the saved state resumes it on SIGCONT.
+        crate::userland::job_control::stop_if_pending_preempted();
         let current = crate::process::entity::EntityId::UserProcess(current_pid);
         let next = crate::process::scheduler::SCHEDULER
             .lock()
@@ -280,6 +290,21 @@ extern "C" fn timer_handler_inner(stack_frame: *mut InterruptStackFrame) {
         return;
     }
 
+    // A tick inside a ring-3 process's syscall is its system time. Only the
+    // pending bit is set for an RLIMIT_CPU signal: the interrupted kernel
+    // code may hold locks that sending would take, and the syscall exit
+    // delivers it.
+    if let Some(pid) = crate::arch::x86_64::percpu::current_user_pid() {
+        if let Some(mut table) = crate::userland::lifecycle::PROCESS_TABLE.try_lock() {
+            if let Some((_, sig)) = crate::userland::rlimit::charge_cpu_tick(&mut table, pid, false)
+            {
+                if let Some(task) = table.by_pid.get_mut(&pid) {
+                    task.signal_state.raise(sig);
+                }
+            }
+        }
+    }
+
     // A protected kernel critical section receives the clock edge but defers
     // the scheduling decision. Timer expiry itself is always deferred to the
     // bounded timer-service worker.
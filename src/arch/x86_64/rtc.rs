@@ -1,8 +1,9 @@
 //! PC CMOS real-time clock access.
 //!
 //! The RTC is sampled once during boot. Consumers use the generic `crate::time`
-//! clock, which advances that sample with PIT ticks; rendering and syscalls do
-//! not perform CMOS port I/O.
+//! clock, which advances that sample with the TSC; rendering and clock reads
+//! do not perform CMOS port I/O. Setting the clock writes it back here, in
+//! whatever BCD or binary and 12- or 24-hour encoding the RTC already uses.
 
 use x86_64::instructions::{interrupts, port::Port};
 
@@ -23,6 +24,8 @@ const REG_CENTURY: u8 = 0x32;
 
 const STATUS_A_UPDATE_IN_PROGRESS: u8 = 1 << 7;
 const STATUS_B_24_HOUR: u8 = 1 << 1;
+/// Halts updates so the time registers can be written as one value.
+const STATUS_B_SET: u8 = 1 << 7;
 const STATUS_B_BINARY: u8 = 1 << 2;
 const HOUR_PM: u8 = 1 << 7;
 
@@ -69,6 +72,32 @@ pub fn read_datetime() -> Result<DateTime, RtcError> {
     })
 }
 
+/// Write `value` into the RTC. The SET bit holds the update cycle off for
+/// the duration, so the RTC never ticks a half-written time.
+pub fn write_datetime(value: DateTime) -> Result<(), RtcError> {
+    interrupts::without_interrupts(|| {
+        let result = unsafe {
+            let status_b = read_register(REG_STATUS_B);
+            encode_snapshot(value, status_b).map(|raw| {
+                write_register(REG_STATUS_B, status_b | STATUS_B_SET);
+                write_register(REG_SECONDS, raw.seconds);
+                write_register(REG_MINUTES, raw.minutes);
+                write_register(REG_HOURS, raw.hours);
+                write_register(REG_DAY, raw.day);
+                write_register(REG_MONTH, raw.month);
+                write_register(REG_YEAR, raw.year);
+                write_register(REG_CENTURY, raw.century);
+                write_register(REG_STATUS_B, status_b & !STATUS_B_SET);
+            })
+        };
+        unsafe {
+            let mut index = Port::<u8>::new(CMOS_INDEX_PORT);
+            index.write(REG_SECONDS);
+        }
+        result
+    })
+}
+
 fn read_stable_snapshot() -> Result<RawRtcSnapshot, RtcError> {
     for _ in 0..SNAPSHOT_RETRIES {
         wait_until_not_updating()?;
@@ -114,6 +143,14 @@ unsafe fn read_register(register: u8) -> u8 {
     data.read()
 }
 
+/// Write one CMOS register, masking NMI like [`read_register`].
+unsafe fn write_register(register: u8, value: u8) {
+    let mut index = Port::<u8>::new(CMOS_INDEX_PORT);
+    let mut data = Port::<u8>::new(CMOS_DATA_PORT);
+    index.write(register | 0x80);
+    data.write(value);
+}
+
 pub(crate) fn decode_snapshot(raw: RawRtcSnapshot) -> Result<DateTime, RtcError> {
     let binary = raw.status_b & STATUS_B_BINARY != 0;
     let decode_required = |value| decode_value(value, binary).ok_or(RtcError::InvalidEncoding);
@@ -160,6 +197,42 @@ pub(crate) fn decode_snapshot(raw: RawRtcSnapshot) -> Result<DateTime, RtcError>
     }
 }
 
+/// Inverse of [`decode_snapshot`] for an RTC configured as `status_b`.
+pub(crate) fn encode_snapshot(value: DateTime, status_b: u8) -> Result<RawRtcSnapshot, RtcError> {
+    if !value.is_valid() || value.year > 9999 {
+        return Err(RtcError::InvalidDateTime);
+    }
+    let binary = status_b & STATUS_B_BINARY != 0;
+    let hours = if status_b & STATUS_B_24_HOUR != 0 {
+        encode_value(value.hour, binary)
+    } else {
+        let twelve = match value.hour % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        let pm = if value.hour >= 12 { HOUR_PM } else { 0 };
+        encode_value(twelve, binary) | pm
+    };
+    Ok(RawRtcSnapshot {
+        seconds: encode_value(value.second, binary),
+        minutes: encode_value(value.minute, binary),
+        hours,
+        day: encode_value(value.day, binary),
+        month: encode_value(value.month, binary),
+        year: encode_value((value.year % 100) as u8, binary),
+        century: encode_value((value.year / 100) as u8, binary),
+        status_b,
+    })
+}
+
+fn encode_value(value: u8, binary: bool) -> u8 {
+    if binary {
+        value
+    } else {
+        (value / 10) << 4 | (value % 10)
+    }
+}
+
 fn decode_value(value: u8, binary: bool) -> Option<u8> {
     if binary {
         return Some(value);
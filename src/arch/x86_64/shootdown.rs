@@ -0,0 +1,128 @@
+//! Cross-CPU user TLB shootdown.
+//!
+//! Every CPU publishes the user L4 it has loaded (see
+//! [`super::percpu::set_active_user_l4`]). A mapper transaction that
+//! downgrades or removes user translations flushes its own TLB, records the
+//! address space as [`StaleTranslations`], and before the mapper lock is
+//! released asks every other CPU running that L4 to flush as well. The
+//! initiator waits for each acknowledgement, so a frame released or copied
+//! under the lock can never be reached through a stale remote entry once
+//! another CPU may reallocate it.
+//!
+//! Requests carry no payload: a target reloads CR3, discarding every
+//! non-global translation, and one reload answers every ticket queued so
+//! far. Kernel locks spin with interrupts masked, so a target blocked on a
+//! lock the initiator holds would never take the IPI; [`service_pending`] is
+//! therefore also polled from the `InterruptMutex` spin loop and while an
+//! initiator waits for its own acknowledgements.
+
+use core::sync::atomic::{fence, AtomicUsize, Ordering};
+
+use super::acpi::MAX_CPUS;
+use super::percpu;
+
+/// Shootdowns whose initiator is still waiting. Lets lock spinners skip the
+/// per-CPU mailbox entirely in the common case.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// User translations a mapper transaction may have left cached on other
+/// CPUs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StaleTranslations {
+    #[default]
+    None,
+    /// Only CPUs running this user L4 (physical address) can hold them.
+    AddressSpace(u64),
+    /// Several address spaces were touched; flush every CPU running one.
+    AnyAddressSpace,
+}
+
+impl StaleTranslations {
+    pub fn note(&mut self, l4: u64) {
+        *self = match *self {
+            Self::None => Self::AddressSpace(l4),
+            Self::AddressSpace(noted) if noted == l4 => Self::AddressSpace(l4),
+            _ => Self::AnyAddressSpace,
+        };
+    }
+
+    /// Shoot down everything noted so far and reset to [`Self::None`].
+    pub fn flush(&mut self) {
+        match core::mem::take(self) {
+            Self::None => {}
+            Self::AddressSpace(l4) => flush_address_space(l4),
+            Self::AnyAddressSpace => shoot(|active| active != 0),
+        }
+    }
+}
+
+/// Make every other CPU running `l4` discard its user translations, and
+/// wait until all of them have. The IPI round trip also serializes each
+/// target, which is what private-expedited `membarrier` relies on.
+pub fn flush_address_space(l4: u64) {
+    shoot(|active| active == l4);
+}
+
+/// Wait until no other CPU still has `l4` loaded. Called before an address
+/// space's page tables are freed; every task leaving an address space
+/// switches to another L4 on its own, so this only covers stragglers still
+/// on their way off a CPU.
+pub fn retire_address_space(l4: u64) {
+    fence(Ordering::SeqCst);
+    let me = percpu::cpu_id();
+    for cpu in 0..MAX_CPUS {
+        while cpu != me && percpu::active_user_l4_on(cpu) == l4 {
+            service_pending();
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Acknowledge any shootdown queued on this CPU.
+pub fn service_pending() {
+    if IN_FLIGHT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let Some(ticket) = percpu::pending_shootdown() else {
+        return;
+    };
+    // A CPU on the kernel L4 flushed every user translation when it
+    // switched there.
+    if percpu::active_user_l4() != 0 {
+        x86_64::instructions::tlb::flush_all();
+    }
+    percpu::complete_shootdown(ticket);
+}
+
+fn shoot(targets: impl Fn(u64) -> bool) {
+    // Order the caller's page-table writes before sampling which CPUs run
+    // the address space. A CPU that publishes its L4 after this point loads
+    // CR3 afterwards and so walks the updated tables.
+    fence(Ordering::SeqCst);
+    if !super::lapic::available() {
+        return;
+    }
+    let _interrupts = super::interrupt_guard::InterruptGuard::disable();
+    let me = percpu::cpu_id();
+    let topology = super::acpi::topology();
+    let mut tickets = [0u64; MAX_CPUS];
+    IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    for (cpu, ticket) in tickets.iter_mut().enumerate().take(topology.cpu_count) {
+        if cpu == me || !targets(percpu::active_user_l4_on(cpu)) {
+            continue;
+        }
+        *ticket = percpu::request_shootdown(cpu);
+        super::lapic::send_fixed(
+            topology.cpus[cpu].lapic_id,
+            super::lapic::TLB_SHOOTDOWN_VECTOR,
+        );
+    }
+    for (cpu, &ticket) in tickets.iter().enumerate() {
+        while ticket != 0 && percpu::shootdown_completed(cpu) < ticket {
+            // Two initiators may be waiting on each other.
+            service_pending();
+            core::hint::spin_loop();
+        }
+    }
+    IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+}
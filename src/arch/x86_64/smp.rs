@@ -323,16 +323,13 @@ fn idle_loop() -> ! {
 
         // PCI IRQs are routed to the BSP. Their handlers publish bounded I/O
         // wake records and kick halted APs; consume those records here in
-        // normal context before selecting from the shared run queue.
+        // normal context before selecting from the run queues.
         let _ = crate::process::drain_kernel_io_wakes();
         crate::process::try_run_scheduled_processes();
 
         x86_64::instructions::interrupts::disable();
         let io_woke = crate::process::drain_kernel_io_wakes();
-        let scheduler_ready = crate::process::scheduler::SCHEDULER
-            .try_lock()
-            .map(|scheduler| scheduler.ready_entity_count() != 0)
-            .unwrap_or(true);
+        let scheduler_ready = crate::process::scheduler::work_queued();
         if io_woke || scheduler_ready {
             x86_64::instructions::interrupts::enable();
             continue;
@@ -343,17 +340,22 @@ fn idle_loop() -> ! {
         // interrupts and halting.
         super::percpu::set_idle_interruptible(true);
         let io_woke = crate::process::drain_kernel_io_wakes();
-        let scheduler_ready = crate::process::scheduler::SCHEDULER
-            .try_lock()
-            .map(|scheduler| scheduler.ready_entity_count() != 0)
-            .unwrap_or(true);
+        let scheduler_ready = crate::process::scheduler::work_queued();
         if io_woke || scheduler_ready {
             super::percpu::set_idle_interruptible(false);
             x86_64::instructions::interrupts::enable();
             continue;
         }
+        // Tickless idle: nothing here needs the preemption tick until an
+        // interrupt brings work, and the BSP fires timer deadlines.
+        super::lapic::set_timer_masked(true);
+        super::percpu::begin_tickless_idle(crate::time::monotonic_ns());
         x86_64::instructions::interrupts::enable_and_hlt();
+        x86_64::instructions::interrupts::disable();
+        super::percpu::end_tickless_idle(crate::time::monotonic_ns());
+        super::lapic::set_timer_masked(false);
         super::percpu::set_idle_interruptible(false);
+        x86_64::instructions::interrupts::enable();
     }
 }
 
@@ -0,0 +1,177 @@
+//! TSC clocksource.
+//!
+//! Boot times the TSC across [`CALIBRATION_TICKS`] PIT periods and from then
+//! on reads monotonic time from it with nanosecond resolution. The scale is
+//! anchored at the last calibration edge to the PIT tick count at that edge,
+//! so the clock continues exactly where the PIT count left off. Every CPU
+//! reads its own TSC against the same anchor, and every reading passes
+//! through a kernel-wide high-water mark, so a TSC that lags on one CPU or
+//! slows in a power state holds time still rather than running it
+//! backwards. The vDSO reads the TSC without that mark, so it is only
+//! given the scale when the TSC is invariant; otherwise user clock reads
+//! fall back to the syscall. Until calibration finishes, and on a CPU
+//! whose TSC does not count, time falls back to the PIT tick count.
+
+use core::arch::x86_64::{__cpuid, _rdtsc};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use x86_64::registers::model_specific::Msr;
+
+use crate::time::PIT_NANOSECONDS_PER_TICK;
+
+const CALIBRATION_TICKS: u64 = 10;
+const NANOSECONDS_PER_SECOND: u128 = 1_000_000_000;
+/// IA32_TSC_AUX, the value RDTSCP returns in ECX.
+const TSC_AUX_MSR: u32 = 0xc000_0103;
+
+/// TSC reading at the calibration end edge.
+static BASE_TSC: AtomicU64 = AtomicU64::new(0);
+/// Nanoseconds since boot at `BASE_TSC`.
+static BASE_NS: AtomicU64 = AtomicU64::new(0);
+/// Nanoseconds per cycle as 32.32 fixed point; 0 until calibrated.
+static NS_PER_CYCLE: AtomicU64 = AtomicU64::new(0);
+/// Cycles per nanosecond as 32.32 fixed point.
+static CYCLES_PER_NS: AtomicU64 = AtomicU64::new(0);
+/// The TSC rate survives P- and C-state changes.
+static INVARIANT: AtomicBool = AtomicBool::new(false);
+/// Latest time [`now_ns`] returned on any CPU.
+static LAST_NS: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+fn rdtsc() -> u64 {
+    unsafe { _rdtsc() }
+}
+
+/// Time the TSC against the PIT. Needs the PIT interrupt running on this
+/// CPU; spins for about [`CALIBRATION_TICKS`] ticks.
+pub fn calibrate() {
+    use super::interrupts::TIMER_TICKS;
+
+    let start_tick = wait_for_tick_edge(TIMER_TICKS.load(Ordering::Acquire));
+    let start_tsc = rdtsc();
+    let end_tick = wait_for_tick_edge(start_tick + CALIBRATION_TICKS - 1);
+    let end_tsc = rdtsc();
+    if end_tsc <= start_tsc {
+        crate::debug_warn!("tsc: not counting; clocks stay on the PIT");
+        return;
+    }
+    let cycles = end_tsc - start_tsc;
+    let elapsed_ns = (end_tick - start_tick) * PIT_NANOSECONDS_PER_TICK;
+
+    let frequency = (u128::from(cycles) * NANOSECONDS_PER_SECOND / u128::from(elapsed_ns)) as u64;
+    let ns_per_cycle = ((u128::from(elapsed_ns) << 32) / u128::from(cycles)) as u64;
+    let cycles_per_ns = ((u128::from(cycles) << 32) / u128::from(elapsed_ns)) as u64;
+    BASE_TSC.store(end_tsc, Ordering::Relaxed);
+    BASE_NS.store(
+        end_tick.saturating_mul(PIT_NANOSECONDS_PER_TICK),
+        Ordering::Relaxed,
+    );
+    CYCLES_PER_NS.store(cycles_per_ns, Ordering::Relaxed);
+    INVARIANT.store(invariant(), Ordering::Relaxed);
+    NS_PER_CYCLE.store(ns_per_cycle, Ordering::Release);
+    crate::debug_info!(
+        "tsc: {} kHz{}{}",
+        frequency / 1_000,
+        if INVARIANT.load(Ordering::Relaxed) {
+            ", invariant"
+        } else {
+            ", not invariant; vDSO clocks use the syscall"
+        },
+        if deadline_timer_supported() {
+            ", TSC-deadline timer"
+        } else {
+            ""
+        }
+    );
+}
+
+/// Spin until the PIT count passes `tick`, returning the new count.
+fn wait_for_tick_edge(tick: u64) -> u64 {
+    loop {
+        let now = super::interrupts::TIMER_TICKS.load(Ordering::Acquire);
+        if now > tick {
+            return now;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// The mapping [`now_ns`] applies: `base_ns + ((tsc - base_tsc) *
+/// ns_per_cycle >> 32)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Scale {
+    pub base_tsc: u64,
+    pub base_ns: u64,
+    pub ns_per_cycle: u64,
+}
+
+/// The calibrated scale for the vDSO, or `None` before calibration or when
+/// the TSC is not invariant.
+pub fn scale() -> Option<Scale> {
+    let ns_per_cycle = NS_PER_CYCLE.load(Ordering::Acquire);
+    (ns_per_cycle != 0 && INVARIANT.load(Ordering::Relaxed)).then(|| Scale {
+        base_tsc: BASE_TSC.load(Ordering::Relaxed),
+        base_ns: BASE_NS.load(Ordering::Relaxed),
+        ns_per_cycle,
+    })
+}
+
+pub fn calibrated() -> bool {
+    NS_PER_CYCLE.load(Ordering::Acquire) != 0
+}
+
+/// Monotonic nanoseconds since boot, or `None` before calibration.
+#[inline]
+pub fn now_ns() -> Option<u64> {
+    let ns_per_cycle = NS_PER_CYCLE.load(Ordering::Acquire);
+    if ns_per_cycle == 0 {
+        return None;
+    }
+    // A CPU whose TSC reads just behind the anchor's clamps to it.
+    let cycles = rdtsc().saturating_sub(BASE_TSC.load(Ordering::Relaxed));
+    let elapsed = (u128::from(cycles) * u128::from(ns_per_cycle)) >> 32;
+    let ns = BASE_NS
+        .load(Ordering::Relaxed)
+        .saturating_add(elapsed as u64);
+    Some(ns.max(LAST_NS.fetch_max(ns, Ordering::Relaxed)))
+}
+
+/// The TSC value at which [`now_ns`] reaches `ns`. Only meaningful once
+/// calibrated.
+pub fn tsc_at(ns: u64) -> u64 {
+    let after_base = ns.saturating_sub(BASE_NS.load(Ordering::Relaxed));
+    let cycles = (u128::from(after_base) * u128::from(CYCLES_PER_NS.load(Ordering::Relaxed))) >> 32;
+    BASE_TSC
+        .load(Ordering::Relaxed)
+        .saturating_add(u64::try_from(cycles).unwrap_or(u64::MAX))
+}
+
+/// CPUID.01H:ECX.TSC_Deadline\[bit 24\].
+pub fn deadline_timer_supported() -> bool {
+    unsafe { __cpuid(1) }.ecx & (1 << 24) != 0
+}
+
+/// CPUID.80000001H:EDX.RDTSCP\[bit 27\].
+pub fn rdtscp_supported() -> bool {
+    if unsafe { __cpuid(0x8000_0000) }.eax < 0x8000_0001 {
+        return false;
+    }
+    unsafe { __cpuid(0x8000_0001) }.edx & (1 << 27) != 0
+}
+
+/// Store this CPU's logical id in IA32_TSC_AUX, where ring 3 reads it with
+/// RDTSCP.
+pub fn set_cpu_number(logical_id: usize) {
+    if rdtscp_supported() {
+        unsafe { Msr::new(TSC_AUX_MSR).write(logical_id as u64) };
+    }
+}
+
+/// CPUID.80000007H:EDX.InvariantTSC\[bit 8\]: the rate survives P- and
+/// C-state changes.
+fn invariant() -> bool {
+    if unsafe { __cpuid(0x8000_0000) }.eax < 0x8000_0007 {
+        return false;
+    }
+    unsafe { __cpuid(0x8000_0007) }.edx & (1 << 8) != 0
+}
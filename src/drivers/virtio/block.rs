@@ -191,6 +191,12 @@ impl VirtioBlockDevice {
         Some(Self::from_driver(index, drivers.get(index)?))
     }
 
+    /// Linux-style node name for this disk: `/dev/vda` for index 0, in PCI
+    /// discovery order.
+    pub fn dev_path(&self) -> String {
+        alloc::format!("/dev/vd{}", (b'a' + self.index as u8) as char)
+    }
+
     fn from_driver(index: usize, driver: &Driver) -> Self {
         Self {
             index,
@@ -59,12 +59,17 @@ struct Driver {
     irq: u8,
     requests: BTreeMap<u16, Request>,
     quarantined: bool,
+    tag: String,
 }
 
 lazy_static! {
     static ref DRIVERS: InterruptMutex<Vec<Driver>> = InterruptMutex::new(Vec::new());
 }
 
+/// PCI probing runs once; later lookups reuse the live transports, since
+/// re-initializing a device would reset queues a mounted share still owns.
+static PROBED: spin::Once<()> = spin::Once::new();
+
 // Keep 9p tokens disjoint from the block driver's low-half sequence. The
 // scheduler's I/O wait reason is shared by both transports.
 static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1 << 63);
@@ -103,10 +108,16 @@ impl P9Transport {
     /// Find the virtio-9p device carrying `tag` and bring it up. Absence is
     /// normal (share disabled, or a QEMU launch without the device).
     pub fn discover_by_tag(tag: &str) -> Option<Self> {
-        pci::find_virtio_9p_devices()
-            .into_iter()
-            .filter_map(Self::new)
-            .find(|transport| transport.tag == tag)
+        PROBED.call_once(|| {
+            for pci_device in pci::find_virtio_9p_devices() {
+                Self::new(pci_device);
+            }
+        });
+        let index = DRIVERS.lock().iter().position(|driver| driver.tag == tag)?;
+        Some(Self {
+            index,
+            tag: String::from(tag),
+        })
     }
 
     /// Initialize one transport and publish it to the interrupt handler before
@@ -135,6 +146,7 @@ impl P9Transport {
                 irq,
                 requests: BTreeMap::new(),
                 quarantined: false,
+                tag: tag.clone(),
             });
             index
         };
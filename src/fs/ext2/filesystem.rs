@@ -105,6 +105,22 @@ impl Inode {
     fn set_dtime(&mut self, now: u32) {
         put32(&mut self.raw, 20, now);
     }
+    /// Owner ids combine the classic 16-bit fields with the Linux
+    /// `l_i_uid_high`/`l_i_gid_high` halves in the OS-dependent area.
+    fn uid(&self) -> u32 {
+        le16(&self.raw, 2) as u32 | (le16(&self.raw, 120) as u32) << 16
+    }
+    fn set_uid(&mut self, uid: u32) {
+        put16(&mut self.raw, 2, uid as u16);
+        put16(&mut self.raw, 120, (uid >> 16) as u16);
+    }
+    fn gid(&self) -> u32 {
+        le16(&self.raw, 24) as u32 | (le16(&self.raw, 122) as u32) << 16
+    }
+    fn set_gid(&mut self, gid: u32) {
+        put16(&mut self.raw, 24, gid as u16);
+        put16(&mut self.raw, 122, (gid >> 16) as u16);
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -1129,8 +1145,8 @@ impl<'a> Ext2Filesystem<'a> {
         crate::fs::filesystem::UnixMetadata {
             inode: inode.number as u64,
             mode: inode.mode() as u32,
-            uid: le16(&inode.raw, 2) as u32,
-            gid: le16(&inode.raw, 24) as u32,
+            uid: inode.uid(),
+            gid: inode.gid(),
             links: inode.links() as u64,
             size: inode.size(),
             blocks_512: inode.sectors() as u64,
@@ -1492,9 +1508,13 @@ impl Filesystem for Ext2Filesystem<'_> {
 
     fn stats(&self) -> Result<FilesystemStats, FilesystemError> {
         let state = self.state.lock();
+        let free_blocks = le32(&state.super_raw, 12) as u64;
+        // s_r_blocks_count: blocks held back for the superuser.
+        let reserved_blocks = le32(&state.super_raw, 8) as u64;
         Ok(FilesystemStats {
             total_blocks: self.geometry.blocks_count as u64,
-            free_blocks: le32(&state.super_raw, 12) as u64,
+            free_blocks,
+            available_blocks: free_blocks.saturating_sub(reserved_blocks),
             block_size: self.geometry.block_size,
             total_inodes: self.geometry.inodes_count as u64,
             free_inodes: le32(&state.super_raw, 16) as u64,
@@ -1712,6 +1732,36 @@ impl Filesystem for Ext2Filesystem<'_> {
         self.write_inode(&inode, &state.groups)
     }
 
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), FilesystemError> {
+        let mut state = self.state.lock();
+        self.mark_dirty(&mut state)?;
+        let number = self.resolve_with_groups(path, &state.groups)?;
+        let mut inode = self.read_inode_with_groups(number, &state.groups)?;
+        inode.set_mode((inode.mode() & MODE_TYPE_MASK) | (mode & 0o7777) as u16);
+        inode.set_changed(Self::now());
+        self.write_inode(&inode, &state.groups)
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> Result<(), FilesystemError> {
+        let mut state = self.state.lock();
+        self.mark_dirty(&mut state)?;
+        let number = self.resolve_with_groups(path, &state.groups)?;
+        let mut inode = self.read_inode_with_groups(number, &state.groups)?;
+        if let Some(uid) = uid {
+            inode.set_uid(uid);
+        }
+        if let Some(gid) = gid {
+            inode.set_gid(gid);
+        }
+        inode.set_changed(Self::now());
+        self.write_inode(&inode, &state.groups)
+    }
+
     fn sync_handle(&self, _handle: &FileHandle, _data_only: bool) -> Result<(), FilesystemError> {
         self.sync()
     }
@@ -92,12 +92,13 @@ impl<'a> Filesystem for FatFilesystemWrapper<'a> {
     }
 
     fn stats(&self) -> Result<FilesystemStats, FilesystemError> {
-        // This would require additional methods on FatFilesystem
-        // For now, return dummy values
+        // FAT has no inode table; like Linux vfat, report zero inodes.
+        let free_clusters = self.inner.free_clusters().map_err(map_fat_err)? as u64;
         Ok(FilesystemStats {
-            total_blocks: 0,
-            free_blocks: 0,
-            block_size: 512,
+            total_blocks: self.inner.total_clusters() as u64,
+            free_blocks: free_clusters,
+            available_blocks: free_clusters,
+            block_size: self.inner.cluster_size(),
             total_inodes: 0,
             free_inodes: 0,
         })
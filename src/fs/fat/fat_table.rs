@@ -458,6 +458,22 @@ impl<'a> FatTable<'a> {
         Err(FatError::DiskFull)
     }
 
+    /// Count the free entries among clusters `2..=max_cluster` in one
+    /// sequential pass over the FAT.
+    pub fn count_free_clusters(&self, max_cluster: u32) -> Result<u32, FatError> {
+        let mut cached_sector = u32::MAX;
+        let mut buffer = [0u8; 512];
+        let mut free = 0;
+        for cluster in 2..=max_cluster {
+            let entry =
+                self.read_entry_cached(ClusterId(cluster), &mut cached_sector, &mut buffer)?;
+            if entry.0 == 0 {
+                free += 1;
+            }
+        }
+        Ok(free)
+    }
+
     pub fn follow_chain(
         &self,
         start_cluster: ClusterId,
@@ -55,6 +55,9 @@ struct MutableState {
     /// an optimization: a miss (different file, or a lower offset) just
     /// restarts from the first cluster. Any write invalidates it.
     chain_hint: Option<ChainHint>,
+    /// Free cluster count, filled by the first `free_clusters` scan and
+    /// then adjusted by every allocation and free on this mount.
+    free_clusters: Option<u32>,
 }
 
 /// Last cluster position reached while walking a chain in `read_file_at`.
@@ -277,6 +280,7 @@ impl<'a> FatFilesystem<'a> {
                 sn_cache: BTreeMap::new(),
                 writable: false,
                 chain_hint: None,
+                free_clusters: None,
             }),
         })
     }
@@ -340,11 +344,36 @@ impl<'a> FatFilesystem<'a> {
         self.state.lock().writable
     }
 
-    #[expect(dead_code, reason = "intentional kernel API surface")]
     pub fn total_clusters(&self) -> u32 {
         self.total_clusters
     }
 
+    pub fn cluster_size(&self) -> u32 {
+        self.sectors_per_cluster as u32 * self.bytes_per_sector as u32
+    }
+
+    /// Number of unallocated data clusters. The first call scans the
+    /// whole FAT; later calls use the count kept by allocate/free.
+    pub fn free_clusters(&self) -> Result<u32, FatError> {
+        if let Some(free) = self.state.lock().free_clusters {
+            return Ok(free);
+        }
+        let free = self
+            .fresh_fat_table()?
+            .count_free_clusters(self.total_clusters + 1)?;
+        Ok(*self.state.lock().free_clusters.get_or_insert(free))
+    }
+
+    /// Advance the allocation hint past `cluster` and charge it to the
+    /// free count.
+    fn record_allocation(&self, cluster: ClusterId) {
+        let mut state = self.state.lock();
+        state.alloc_hint = cluster.0 + 1;
+        if let Some(free) = state.free_clusters.as_mut() {
+            *free = free.saturating_sub(1);
+        }
+    }
+
     pub fn fat_type(&self) -> FatType {
         self.fat_type
     }
@@ -1100,7 +1129,7 @@ impl<'a> FatFilesystem<'a> {
                 )
                 .map_err(|_| FatError::BlockDeviceError)?;
         }
-        self.state.lock().alloc_hint = new.0 + 1;
+        self.record_allocation(new);
         Ok(new)
     }
 
@@ -1122,7 +1151,7 @@ impl<'a> FatFilesystem<'a> {
             FatType::Fat32 => ClusterId(0x0FFFFFFF),
         };
         table.write_entry(new, eoc)?;
-        self.state.lock().alloc_hint = new.0 + 1;
+        self.record_allocation(new);
         Ok(new)
     }
 
@@ -1318,6 +1347,9 @@ impl<'a> FatFilesystem<'a> {
         })?;
         for cl in to_free {
             table.write_entry(cl, ClusterId(0))?;
+            if let Some(free) = self.state.lock().free_clusters.as_mut() {
+                *free += 1;
+            }
         }
         Ok(())
     }
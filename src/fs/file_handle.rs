@@ -59,6 +59,8 @@ pub type FileResult<T> = Result<T, FileError>;
 struct FileHandleInner {
     path: String,
     filesystem: &'static dyn crate::fs::filesystem::Filesystem,
+    /// Mount namespace whose mount record this handle pins.
+    namespace: crate::fs::vfs::NamespaceId,
     mode: FileMode,
     position: u64,
     size: u64,
@@ -78,17 +80,37 @@ pub struct File {
 impl File {
     /// Open a file at the given path with the specified mode
     pub fn open(path: &str, mode: FileMode) -> FileResult<Arc<File>> {
-        let (filesystem, rel_path) = get_vfs().find_filesystem(path).ok_or(FileError::NotFound)?;
-        let fs_handle = filesystem
-            .open(rel_path, mode)
-            .map_err(FileError::FilesystemError)?;
-
-        // Get file metadata
-        let metadata = crate::fs::vfs::vfs_stat(path).map_err(|e| FileError::FilesystemError(e))?;
+        let writes = mode.write || mode.append || mode.create || mode.truncate;
+        let mut vfs = get_vfs();
+        let namespace = vfs.namespace();
+        let (filesystem, rel_path) = vfs.pin(path, writes).map_err(|error| match error {
+            FilesystemError::NotFound => FileError::NotFound,
+            error => FileError::FilesystemError(error),
+        })?;
+        drop(vfs);
+        // Only creating or truncating opens change anything inotify reports.
+        let existed = (mode.create || mode.truncate) && crate::fs::vfs::vfs_stat(path).is_ok();
+        let opened = filesystem.open(rel_path, mode).and_then(|handle| {
+            // Get file metadata
+            crate::fs::vfs::vfs_stat(path).map(|metadata| (handle, metadata))
+        });
+        let (fs_handle, metadata) = match opened {
+            Ok(opened) => opened,
+            Err(error) => {
+                crate::fs::vfs::unpin(namespace, filesystem);
+                return Err(FileError::FilesystemError(error));
+            }
+        };
+        if mode.create && !existed {
+            crate::userland::inotify::created(path, false);
+        } else if mode.truncate && existed && mode.write {
+            crate::userland::inotify::modified(path);
+        }
 
         let inner = FileHandleInner {
             path: String::from(path),
             filesystem,
+            namespace,
             mode,
             position: 0,
             size: metadata.size,
@@ -228,10 +250,56 @@ impl File {
         if inner.position > inner.size {
             inner.size = inner.position;
         }
+        if bytes_written != 0 {
+            crate::userland::inotify::modified(&inner.path);
+        }
 
         Ok(bytes_written)
     }
 
+    /// Write `buffer` at an absolute offset without moving the shared file
+    /// position. Shared file mappings write dirty pages back through this.
+    pub fn write_at(&self, offset: u64, buffer: &[u8]) -> FileResult<usize> {
+        let (filesystem, mut fs_handle) = {
+            let inner = self.inner.lock();
+            if !inner.mode.write {
+                return Err(FileError::AccessDenied);
+            }
+            if !inner.is_open {
+                return Err(FileError::HandleClosed);
+            }
+            if offset > inner.size {
+                return Err(FileError::SeekOutOfBounds);
+            }
+            let handle = inner.fs_handle.as_ref().ok_or(FileError::HandleClosed)?;
+            (
+                inner.filesystem,
+                crate::fs::filesystem::FileHandle {
+                    inode: handle.inode,
+                    position: offset,
+                    size: handle.size,
+                    mode: handle.mode,
+                },
+            )
+        };
+        filesystem
+            .seek(&mut fs_handle, offset)
+            .map_err(FileError::FilesystemError)?;
+        let bytes_written = filesystem
+            .write(&mut fs_handle, buffer)
+            .map_err(FileError::FilesystemError)?;
+        let end = offset + bytes_written as u64;
+        let mut inner = self.inner.lock();
+        if let Some(handle) = inner.fs_handle.as_mut() {
+            handle.size = handle.size.max(end);
+        }
+        inner.size = inner.size.max(end);
+        if bytes_written != 0 {
+            crate::userland::inotify::modified(&inner.path);
+        }
+        Ok(bytes_written)
+    }
+
     /// Read the entire file contents into a `Vec<u8>`.
     ///
     /// Reads directly into the freshly allocated `Vec`'s spare capacity —
@@ -323,6 +391,13 @@ impl File {
         self.inner.lock().size
     }
 
+    /// The file's identity across opens, when its filesystem has inode
+    /// numbers.
+    pub fn identity(&self) -> Option<crate::fs::filesystem::FileIdentity> {
+        let inner = self.inner.lock();
+        inner.filesystem.file_identity(inner.fs_handle.as_ref()?)
+    }
+
     pub fn metadata(&self) -> FileResult<crate::fs::filesystem::UnixMetadata> {
         let inner = self.inner.lock();
         let handle = inner.fs_handle.as_ref().ok_or(FileError::HandleClosed)?;
@@ -355,6 +430,7 @@ impl File {
         if inner.position > size {
             inner.position = size;
         }
+        crate::userland::inotify::modified(&inner.path);
         Ok(())
     }
 
@@ -390,6 +466,7 @@ impl File {
                     .map_err(FileError::FilesystemError)?;
             }
             inner.is_open = false;
+            crate::fs::vfs::unpin(inner.namespace, inner.filesystem);
         }
 
         Ok(())
@@ -457,9 +534,8 @@ impl Directory {
     }
 
     fn open_impl(path: &str, names_only: bool) -> FileResult<Arc<Directory>> {
-        let (filesystem, rel_path) = crate::fs::vfs::get_vfs()
-            .find_filesystem(path)
-            .ok_or(FileError::NotFound)?;
+        let (filesystem, rel_path) =
+            crate::fs::vfs::resolve_mount(path).map_err(|_| FileError::NotFound)?;
 
         // Directory streams only expose names and types. Let remote
         // filesystems avoid fetching size/timestamps for every entry; tools
@@ -486,7 +562,7 @@ impl Directory {
                     // entries only when `rel_path == "/"`, so any non-root
                     // path that reached here is genuinely unknown.
                     let mut es = Vec::new();
-                    Self::collect_filesystem_entries(filesystem, rel_path, &mut es);
+                    Self::collect_filesystem_entries(&*filesystem, rel_path, &mut es);
                     if es.is_empty() {
                         return Err(FileError::NotFound);
                     }
@@ -19,6 +19,7 @@ pub enum FilesystemError {
     IsADirectory,
     NotEmpty,
     BufferTooSmall,
+    Busy,
 }
 
 impl fmt::Display for FilesystemError {
@@ -39,6 +40,7 @@ impl fmt::Display for FilesystemError {
             FilesystemError::IsADirectory => write!(f, "Is a directory"),
             FilesystemError::NotEmpty => write!(f, "Directory not empty"),
             FilesystemError::BufferTooSmall => write!(f, "Buffer too small"),
+            FilesystemError::Busy => write!(f, "Device or resource busy"),
         }
     }
 }
@@ -126,16 +128,17 @@ impl FileMode {
 
 /// Filesystem statistics
 #[derive(Debug, Clone, Copy)]
-#[expect(dead_code, reason = "intentional kernel API surface")]
 pub struct FilesystemStats {
     pub total_blocks: u64,
     pub free_blocks: u64,
+    /// Free blocks usable by unprivileged callers (`f_bavail`).
+    pub available_blocks: u64,
     pub block_size: u32,
     pub total_inodes: u64,
     pub free_inodes: u64,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct UnixTimestamp {
     pub seconds: u64,
     pub nanoseconds: u32,
@@ -177,6 +180,14 @@ pub struct UnixMetadata {
     pub changed: UnixTimestamp,
 }
 
+/// Names one file across opens: the filesystem instance that owns it and
+/// its inode number there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileIdentity {
+    pub filesystem: usize,
+    pub inode: u64,
+}
+
 /// Main filesystem trait that all filesystem implementations must implement
 pub trait Filesystem: Sync {
     /// Get the name/type of this filesystem
@@ -222,13 +233,16 @@ pub trait Filesystem: Sync {
     /// Get file/directory metadata
     fn stat(&self, path: &str) -> Result<DirectoryEntry, FilesystemError>;
 
+    /// POSIX view of `path`. The default synthesizes root-owned modes for
+    /// filesystems without permission metadata; regular files are
+    /// executable, like Linux vfat's default `fmask`.
     fn unix_metadata(&self, path: &str) -> Result<UnixMetadata, FilesystemError> {
         let entry = self.stat(path)?;
         let mode = match entry.file_type {
             FileType::Directory => 0o040755,
             FileType::Symlink => 0o120777,
             FileType::Device => 0o060600,
-            FileType::File | FileType::Other => 0o100644,
+            FileType::File | FileType::Other => 0o100755,
         };
         Ok(UnixMetadata {
             inode: 0,
@@ -259,6 +273,18 @@ pub trait Filesystem: Sync {
         Err(FilesystemError::UnsupportedOperation)
     }
 
+    /// Identity of the file behind `handle`: the same for every open of
+    /// that file and different from every other live file. The default
+    /// pairs this instance with the inode number from `handle_metadata`,
+    /// and gives `None` without one.
+    fn file_identity(&self, handle: &FileHandle) -> Option<FileIdentity> {
+        let inode = self.handle_metadata(handle).ok()?.inode;
+        (inode != 0).then_some(FileIdentity {
+            filesystem: self as *const Self as *const u8 as usize,
+            inode,
+        })
+    }
+
     /// Open a file
     fn open(&self, path: &str, mode: FileMode) -> Result<FileHandle, FilesystemError>;
 
@@ -290,6 +316,22 @@ pub trait Filesystem: Sync {
         Err(FilesystemError::UnsupportedOperation)
     }
 
+    /// Replace the permission bits (`0o7777`) of a path, keeping its type.
+    fn set_mode(&self, _path: &str, _mode: u32) -> Result<(), FilesystemError> {
+        Err(FilesystemError::UnsupportedOperation)
+    }
+
+    /// Change the owner and/or group of a path. `None` keeps the
+    /// existing value.
+    fn set_owner(
+        &self,
+        _path: &str,
+        _uid: Option<u32>,
+        _gid: Option<u32>,
+    ) -> Result<(), FilesystemError> {
+        Err(FilesystemError::UnsupportedOperation)
+    }
+
     fn sync_handle(&self, _handle: &FileHandle, _data_only: bool) -> Result<(), FilesystemError> {
         self.sync()
     }
@@ -197,7 +197,7 @@ impl Overlay {
     /// `BufferTooSmall` for oversized files.
     fn copy_up(&self, path: &str) -> Result<(), FilesystemError> {
         let meta = self.lower.stat(path)?;
-        let lower_times = self.lower.unix_metadata(path).ok();
+        let lower_meta = self.lower.unix_metadata(path).ok();
         if meta.file_type == FileType::Directory {
             return Err(FilesystemError::IsADirectory);
         }
@@ -243,13 +243,32 @@ impl Overlay {
             written += n;
         }
         let _ = self.upper.close(&mut upper_handle);
-        if let Some(lower_times) = lower_times {
+        if let Some(lower_meta) = lower_meta {
             self.upper
-                .set_times(path, Some(lower_times.accessed), Some(lower_times.modified))?;
+                .set_times(path, Some(lower_meta.accessed), Some(lower_meta.modified))?;
+            let _ = self.upper.set_mode(path, lower_meta.mode & 0o7777);
+            let _ = self
+                .upper
+                .set_owner(path, Some(lower_meta.uid), Some(lower_meta.gid));
         }
         Ok(())
     }
 
+    /// Make `path` present in upper so its attributes can change.
+    /// Directories are recreated empty; the merged readdir still shows
+    /// the lower children.
+    fn copy_up_metadata(&self, path: &str) -> Result<(), FilesystemError> {
+        if self.upper.stat(path).is_ok() {
+            return Ok(());
+        }
+        let (layer, entry) = self.locate(path)?;
+        if matches!(layer, Layer::Lower) && entry.file_type == FileType::Directory {
+            self.mkdir_p_upper(path)
+        } else {
+            self.copy_up(path)
+        }
+    }
+
     /// Look up `path`, returning which layer answers and the
     /// underlying entry. Returns `NotFound` if upper whiteouts hide
     /// the lower entry.
@@ -284,16 +303,9 @@ impl Filesystem for Overlay {
     }
 
     fn stats(&self) -> Result<FilesystemStats, FilesystemError> {
-        // Combine; tmpfs stats are mostly zero. Surface lower's
-        // block_size as a reasonable hint.
-        let lower_stats = self.lower.stats().unwrap_or(FilesystemStats {
-            total_blocks: 0,
-            free_blocks: 0,
-            block_size: 1,
-            total_inodes: 0,
-            free_inodes: 0,
-        });
-        Ok(lower_stats)
+        // Like Linux overlayfs, report the upper layer: that is where
+        // every new byte lands, so it is the space callers can use.
+        self.upper.stats()
     }
 
     fn read_dir(&self, _path: &str) -> Result<DirectoryIterator<'_>, FilesystemError> {
@@ -389,6 +401,13 @@ impl Filesystem for Overlay {
         }
     }
 
+    fn read_link(&self, path: &str) -> Result<Vec<u8>, FilesystemError> {
+        match self.locate(path)?.0 {
+            Layer::Upper => self.upper.read_link(path),
+            Layer::Lower => self.lower.read_link(path),
+        }
+    }
+
     fn open(&self, path: &str, mode: FileMode) -> Result<FileHandle, FilesystemError> {
         let want_write = mode.write || mode.create || mode.truncate || mode.append;
 
@@ -550,6 +569,21 @@ impl Filesystem for Overlay {
         self.upper.set_times(path, accessed, modified)
     }
 
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), FilesystemError> {
+        self.copy_up_metadata(path)?;
+        self.upper.set_mode(path, mode)
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> Result<(), FilesystemError> {
+        self.copy_up_metadata(path)?;
+        self.upper.set_owner(path, uid, gid)
+    }
+
     fn handle_metadata(
         &self,
         handle: &FileHandle,
@@ -567,6 +601,22 @@ impl Filesystem for Overlay {
         }
     }
 
+    /// The identity of the layer's file, so an upper and a lower file with
+    /// the same inode number stay distinct.
+    fn file_identity(&self, handle: &FileHandle) -> Option<crate::fs::filesystem::FileIdentity> {
+        let inner = FileHandle {
+            inode: raw_id(handle.inode),
+            position: handle.position,
+            size: handle.size,
+            mode: handle.mode,
+        };
+        if is_upper(handle.inode) {
+            self.upper.file_identity(&inner)
+        } else {
+            self.lower.file_identity(&inner)
+        }
+    }
+
     fn sync_handle(&self, handle: &FileHandle, data_only: bool) -> Result<(), FilesystemError> {
         let inner = FileHandle {
             inode: raw_id(handle.inode),
@@ -28,7 +28,7 @@
 //! Format:
 //! ```text
 //!   [magic   4 bytes = b"AGOV"]
-//!   [version 1 byte  = 2]
+//!   [version 1 byte  = 3]
 //!   [crc32   4 bytes over everything that follows]
 //!   [entry_count u32 LE]
 //!   foreach entry:
@@ -39,21 +39,25 @@
 //!     if file/dir: [atime sec u64 + nsec u32]
 //!                  [mtime sec u64 + nsec u32]
 //!                  [ctime sec u64 + nsec u32]
+//!                  [mode u32 LE][uid u32 LE][gid u32 LE]   (version 3)
 //!     if file: [data_len u32 LE][data bytes]
 //! ```
-//! Version 1 blobs remain readable; their entries simply have no stored
-//! timestamps and acquire restore-time metadata during the one-time upgrade.
+//! Older blobs remain readable. Version 1 entries have no stored timestamps
+//! and acquire restore-time metadata during the one-time upgrade; version 1
+//! and 2 entries have no stored owner and come back root-owned with the
+//! default permission bits.
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 use crate::fs::filesystem::{FileMode, Filesystem, FilesystemError};
-use crate::fs::tmpfs::filesystem::{DirBody, NodeTimes, TmpNode, Tmpfs};
+use crate::fs::tmpfs::filesystem::{DirBody, NodeAccess, NodeTimes, TmpNode, Tmpfs};
 use spin::Mutex;
 
 const MAGIC: &[u8; 4] = b"AGOV";
 const LEGACY_VERSION: u8 = 1;
-const VERSION: u8 = 2;
+const TIMES_VERSION: u8 = 2;
+const VERSION: u8 = 3;
 const KIND_FILE: u8 = 0;
 const KIND_WHITEOUT: u8 = 1;
 const KIND_OPAQUE: u8 = 2;
@@ -100,10 +104,12 @@ pub enum Entry {
         path: String,
         data: Vec<u8>,
         times: Option<NodeTimes>,
+        access: Option<NodeAccess>,
     },
     Directory {
         path: String,
         times: Option<NodeTimes>,
+        access: Option<NodeAccess>,
     },
     Whiteout {
         path: String,
@@ -124,6 +130,12 @@ fn push_times(out: &mut Vec<u8>, times: NodeTimes) {
     push_timestamp(out, times.changed);
 }
 
+fn push_access(out: &mut Vec<u8>, access: NodeAccess) {
+    out.extend_from_slice(&access.mode.to_le_bytes());
+    out.extend_from_slice(&access.uid.to_le_bytes());
+    out.extend_from_slice(&access.gid.to_le_bytes());
+}
+
 fn read_timestamp(
     data: &[u8],
     offset: &mut usize,
@@ -151,6 +163,23 @@ fn read_times(data: &[u8], offset: &mut usize) -> Result<NodeTimes, &'static str
     })
 }
 
+fn read_access(data: &[u8], offset: &mut usize) -> Result<NodeAccess, &'static str> {
+    if *offset + 12 > data.len() {
+        return Err("truncated access");
+    }
+    let word = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    let access = NodeAccess {
+        mode: word(*offset),
+        uid: word(*offset + 4),
+        gid: word(*offset + 8),
+    };
+    *offset += 12;
+    if access.mode & !0o7777 != 0 {
+        return Err("invalid mode bits");
+    }
+    Ok(access)
+}
+
 /// Walk a tmpfs subtree rooted at `dir` (with the given path
 /// prefix), pushing entries into `out`. Whiteout / opaque sentinels
 /// (`.wh.*` / `.wh..wh..opq`) are emitted as their semantic Entry
@@ -161,6 +190,7 @@ fn walk_tmpfs_dir(dir: &DirBody, prefix: &str, out: &mut Vec<Entry>) {
     out.push(Entry::Directory {
         path: prefix.to_string(),
         times: Some(children.times),
+        access: Some(children.access),
     });
     for (name, node) in children.children.iter() {
         let mut full_path = String::with_capacity(prefix.len() + 1 + name.len());
@@ -195,6 +225,7 @@ fn walk_tmpfs_dir(dir: &DirBody, prefix: &str, out: &mut Vec<Entry>) {
                     path: full_path,
                     data: file.data.clone(),
                     times: Some(file.times),
+                    access: Some(file.access),
                 });
             }
             TmpNode::Dir(sub) => {
@@ -219,7 +250,12 @@ pub fn serialize_upper(upper: &Tmpfs) -> Vec<u8> {
     inner.extend_from_slice(&(entries.len() as u32).to_le_bytes());
     for entry in &entries {
         match entry {
-            Entry::File { path, data, times } => {
+            Entry::File {
+                path,
+                data,
+                times,
+                access,
+            } => {
                 inner.push(KIND_FILE);
                 inner.extend_from_slice(&(path.len() as u16).to_le_bytes());
                 inner.extend_from_slice(path.as_bytes());
@@ -227,10 +263,18 @@ pub fn serialize_upper(upper: &Tmpfs) -> Vec<u8> {
                     &mut inner,
                     times.expect("serialized tmpfs file has timestamps"),
                 );
+                push_access(
+                    &mut inner,
+                    access.expect("serialized tmpfs file has an owner"),
+                );
                 inner.extend_from_slice(&(data.len() as u32).to_le_bytes());
                 inner.extend_from_slice(data);
             }
-            Entry::Directory { path, times } => {
+            Entry::Directory {
+                path,
+                times,
+                access,
+            } => {
                 inner.push(KIND_DIRECTORY);
                 inner.extend_from_slice(&(path.len() as u16).to_le_bytes());
                 inner.extend_from_slice(path.as_bytes());
@@ -238,6 +282,10 @@ pub fn serialize_upper(upper: &Tmpfs) -> Vec<u8> {
                     &mut inner,
                     times.expect("serialized tmpfs directory has timestamps"),
                 );
+                push_access(
+                    &mut inner,
+                    access.expect("serialized tmpfs directory has an owner"),
+                );
             }
             Entry::Whiteout { path } => {
                 inner.push(KIND_WHITEOUT);
@@ -271,7 +319,7 @@ pub fn deserialize_blob(blob: &[u8]) -> Result<Vec<Entry>, &'static str> {
         return Err("bad magic");
     }
     let version = blob[4];
-    if version != VERSION && version != LEGACY_VERSION {
+    if !(LEGACY_VERSION..=VERSION).contains(&version) {
         return Err("unsupported version");
     }
     let expected_crc = u32::from_le_bytes([blob[5], blob[6], blob[7], blob[8]]);
@@ -305,11 +353,16 @@ pub fn deserialize_blob(blob: &[u8]) -> Result<Vec<Entry>, &'static str> {
         p += path_len;
         match kind {
             KIND_FILE => {
-                let times = if version >= VERSION {
+                let times = if version >= TIMES_VERSION {
                     Some(read_times(inner, &mut p)?)
                 } else {
                     None
                 };
+                let access = if version >= VERSION {
+                    Some(read_access(inner, &mut p)?)
+                } else {
+                    None
+                };
                 if p + 4 > inner.len() {
                     return Err("truncated data_len");
                 }
@@ -322,12 +375,26 @@ pub fn deserialize_blob(blob: &[u8]) -> Result<Vec<Entry>, &'static str> {
                 }
                 let data = inner[p..p + data_len].to_vec();
                 p += data_len;
-                entries.push(Entry::File { path, data, times });
+                entries.push(Entry::File {
+                    path,
+                    data,
+                    times,
+                    access,
+                });
+            }
+            KIND_DIRECTORY if version >= TIMES_VERSION => {
+                let times = Some(read_times(inner, &mut p)?);
+                let access = if version >= VERSION {
+                    Some(read_access(inner, &mut p)?)
+                } else {
+                    None
+                };
+                entries.push(Entry::Directory {
+                    path,
+                    times,
+                    access,
+                });
             }
-            KIND_DIRECTORY if version >= VERSION => entries.push(Entry::Directory {
-                path,
-                times: Some(read_times(inner, &mut p)?),
-            }),
             KIND_WHITEOUT => entries.push(Entry::Whiteout { path }),
             KIND_OPAQUE => entries.push(Entry::Opaque { dir_path: path }),
             _ => return Err("unknown entry kind"),
@@ -450,7 +517,12 @@ fn apply_entries(upper: &Tmpfs, entries: Vec<Entry>) {
     let mut restored_times: Vec<(String, NodeTimes)> = Vec::new();
     for entry in entries {
         match entry {
-            Entry::File { path, data, times } => {
+            Entry::File {
+                path,
+                data,
+                times,
+                access,
+            } => {
                 ensure_parents(upper, &path);
                 if let Ok(mut handle) = upper.open(
                     &path,
@@ -465,15 +537,26 @@ fn apply_entries(upper: &Tmpfs, entries: Vec<Entry>) {
                     let _ = upper.write(&mut handle, &data);
                     let _ = upper.close(&mut handle);
                 }
+                if let Some(access) = access {
+                    let _ = upper.restore_node_access(&path, access);
+                }
                 if let Some(times) = times {
                     restored_times.push((path, times));
                 }
             }
-            Entry::Directory { path, times } => {
+            Entry::Directory {
+                path,
+                times,
+                access,
+            } => {
                 if !path.is_empty() && path != "/" {
                     ensure_parents(upper, &path);
                     let _ = upper.mkdir(&path);
                 }
+                if let Some(access) = access {
+                    let node = if path.is_empty() { "/" } else { &path };
+                    let _ = upper.restore_node_access(node, access);
+                }
                 if let Some(times) = times {
                     restored_times.push((path, times));
                 }
@@ -56,10 +56,23 @@ pub struct P9Client {
     poisoned: bool,
 }
 
+/// Tsetattr payload. `valid` is a mask of `setattr_valid` bits.
+#[derive(Default)]
+pub struct SetAttr {
+    pub valid: u32,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u64,
+    pub atime: UnixTimestamp,
+    pub mtime: UnixTimestamp,
+}
+
 pub struct Statfs {
     pub bsize: u32,
     pub blocks: u64,
     pub bfree: u64,
+    pub bavail: u64,
     pub files: u64,
     pub ffree: u64,
 }
@@ -349,27 +362,20 @@ impl P9Client {
         }
     }
 
-    /// Tsetattr limited to the fields the kernel mutates: size, atime, mtime.
-    pub fn setattr(
-        &mut self,
-        fid: u32,
-        valid: u32,
-        size: u64,
-        atime: UnixTimestamp,
-        mtime: UnixTimestamp,
-    ) -> Result<(), FilesystemError> {
+    /// Tsetattr. Only the fields selected by `attr.valid` are applied.
+    pub fn setattr(&mut self, fid: u32, attr: &SetAttr) -> Result<(), FilesystemError> {
         let mut writer = WireWriter::request(msg::TSETATTR, self.tag);
         writer
             .u32(fid)
-            .u32(valid)
-            .u32(0) // mode
-            .u32(0) // uid
-            .u32(0) // gid
-            .u64(size)
-            .u64(atime.seconds)
-            .u64(atime.nanoseconds as u64)
-            .u64(mtime.seconds)
-            .u64(mtime.nanoseconds as u64);
+            .u32(attr.valid)
+            .u32(attr.mode)
+            .u32(attr.uid)
+            .u32(attr.gid)
+            .u64(attr.size)
+            .u64(attr.atime.seconds)
+            .u64(attr.atime.nanoseconds as u64)
+            .u64(attr.mtime.seconds)
+            .u64(attr.mtime.nanoseconds as u64);
         self.rpc(writer.finish(), msg::RSETATTR, self.tag)
             .map(|_| ())
     }
@@ -537,13 +543,14 @@ impl P9Client {
         let bsize = reader.u32()?;
         let blocks = reader.u64()?;
         let bfree = reader.u64()?;
-        let _bavail = reader.u64()?;
+        let bavail = reader.u64()?;
         let files = reader.u64()?;
         let ffree = reader.u64()?;
         Ok(Statfs {
             bsize,
             blocks,
             bfree,
+            bavail,
             files,
             ffree,
         })
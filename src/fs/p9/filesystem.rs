@@ -13,7 +13,7 @@ use crate::fs::filesystem::{
     DirectoryEntry, DirectoryIterator, FileAttributes, FileHandle, FileMode, FileType, Filesystem,
     FilesystemError, FilesystemStats, UnixMetadata, UnixTimestamp,
 };
-use crate::fs::p9::client::{P9Client, MAX_SYMLINK_DEPTH};
+use crate::fs::p9::client::{P9Client, SetAttr, MAX_SYMLINK_DEPTH};
 use crate::fs::p9::protocol::{open_flags, setattr_valid, P9Stat, AT_REMOVEDIR};
 use alloc::collections::VecDeque;
 use alloc::format;
@@ -274,6 +274,15 @@ impl P9Filesystem {
         }
     }
 
+    /// Tsetattr on the object `path` resolves to (symlinks followed).
+    fn setattr_path(&self, path: &str, attr: &SetAttr) -> Result<(), FilesystemError> {
+        let (_, mut client) = self.lock_any();
+        let (fid, _stat) = walk_resolved(&mut client, path, MAX_SYMLINK_DEPTH)?;
+        let result = client.setattr(fid, attr);
+        clunk_quiet(&mut client, fid);
+        result
+    }
+
     fn lock_handle(&self, handle: &FileHandle) -> Result<P9ClientGuard<'_>, FilesystemError> {
         let lane = (handle.inode >> 32) as usize;
         self.clients
@@ -511,6 +520,7 @@ impl Filesystem for P9Filesystem {
         Ok(FilesystemStats {
             total_blocks: statfs.blocks,
             free_blocks: statfs.bfree,
+            available_blocks: statfs.bavail,
             block_size: statfs.bsize.max(512),
             total_inodes: statfs.files,
             free_inodes: statfs.ffree,
@@ -741,10 +751,11 @@ impl Filesystem for P9Filesystem {
         let mut client = self.lock_handle(handle)?;
         client.setattr(
             decode_handle_fid(handle),
-            setattr_valid::SIZE,
-            size,
-            UnixTimestamp::ZERO,
-            UnixTimestamp::ZERO,
+            &SetAttr {
+                valid: setattr_valid::SIZE,
+                size,
+                ..SetAttr::default()
+            },
         )?;
         handle.size = size;
         Ok(())
@@ -759,8 +770,6 @@ impl Filesystem for P9Filesystem {
         if accessed.is_none() && modified.is_none() {
             return Ok(());
         }
-        let (_, mut client) = self.lock_any();
-        let (fid, _stat) = walk_resolved(&mut client, path, MAX_SYMLINK_DEPTH)?;
         let mut valid = 0u32;
         if accessed.is_some() {
             valid |= setattr_valid::ATIME | setattr_valid::ATIME_SET;
@@ -768,15 +777,47 @@ impl Filesystem for P9Filesystem {
         if modified.is_some() {
             valid |= setattr_valid::MTIME | setattr_valid::MTIME_SET;
         }
-        let result = client.setattr(
-            fid,
-            valid,
-            0,
-            accessed.unwrap_or(UnixTimestamp::ZERO),
-            modified.unwrap_or(UnixTimestamp::ZERO),
-        );
-        clunk_quiet(&mut client, fid);
-        result
+        self.setattr_path(
+            path,
+            &SetAttr {
+                valid,
+                atime: accessed.unwrap_or(UnixTimestamp::ZERO),
+                mtime: modified.unwrap_or(UnixTimestamp::ZERO),
+                ..SetAttr::default()
+            },
+        )
+    }
+
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), FilesystemError> {
+        self.setattr_path(
+            path,
+            &SetAttr {
+                valid: setattr_valid::MODE,
+                mode: mode & 0o7777,
+                ..SetAttr::default()
+            },
+        )
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> Result<(), FilesystemError> {
+        let mut attr = SetAttr::default();
+        if let Some(uid) = uid {
+            attr.valid |= setattr_valid::UID;
+            attr.uid = uid;
+        }
+        if let Some(gid) = gid {
+            attr.valid |= setattr_valid::GID;
+            attr.gid = gid;
+        }
+        if attr.valid == 0 {
+            return Ok(());
+        }
+        self.setattr_path(path, &attr)
     }
 
     fn sync_handle(&self, handle: &FileHandle, data_only: bool) -> Result<(), FilesystemError> {
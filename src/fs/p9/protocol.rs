@@ -71,6 +71,9 @@ pub mod open_flags {
 
 /// Tsetattr `valid` mask bits.
 pub mod setattr_valid {
+    pub const MODE: u32 = 0x1;
+    pub const UID: u32 = 0x2;
+    pub const GID: u32 = 0x4;
     pub const SIZE: u32 = 0x8;
     pub const ATIME: u32 = 0x10;
     pub const MTIME: u32 = 0x20;
@@ -46,31 +46,69 @@ impl NodeTimes {
     }
 }
 
+/// Permission bits (`0o7777`) and owner of a node. New nodes belong to
+/// root; the syscall layer re-owns them for unprivileged creators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeAccess {
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl NodeAccess {
+    pub const FILE: Self = Self {
+        mode: 0o644,
+        uid: 0,
+        gid: 0,
+    };
+    pub const DIRECTORY: Self = Self {
+        mode: 0o755,
+        uid: 0,
+        gid: 0,
+    };
+}
+
 pub struct TmpFile {
+    /// Inode number, stable for the life of the node.
+    pub(crate) ino: u64,
     pub(crate) data: Vec<u8>,
     pub(crate) times: NodeTimes,
+    pub(crate) access: NodeAccess,
 }
 
 pub struct TmpDirectory {
+    pub(crate) ino: u64,
     pub(crate) children: BTreeMap<String, TmpNode>,
     pub(crate) times: NodeTimes,
+    pub(crate) access: NodeAccess,
 }
 
 fn current_time() -> UnixTimestamp {
     UnixTimestamp::from_nanoseconds(crate::time::realtime_ns())
 }
 
+/// Inode numbers are never reused, across every tmpfs instance.
+static NEXT_INO: AtomicU64 = AtomicU64::new(1);
+
+fn next_ino() -> u64 {
+    NEXT_INO.fetch_add(1, Ordering::Relaxed)
+}
+
 fn new_file_body() -> FileBody {
     Arc::new(Mutex::new(TmpFile {
+        ino: next_ino(),
         data: Vec::new(),
         times: NodeTimes::now(),
+        access: NodeAccess::FILE,
     }))
 }
 
 fn new_dir_body() -> DirBody {
     Arc::new(Mutex::new(TmpDirectory {
+        ino: next_ino(),
         children: BTreeMap::new(),
         times: NodeTimes::now(),
+        access: NodeAccess::DIRECTORY,
     }))
 }
 
@@ -103,6 +141,41 @@ impl Tmpfs {
         }
         Ok(())
     }
+
+    pub(crate) fn restore_node_access(
+        &self,
+        path: &str,
+        access: NodeAccess,
+    ) -> Result<(), FilesystemError> {
+        match self.resolve(path).ok_or(FilesystemError::NotFound)? {
+            TmpNode::File(body) => body.lock().access = access,
+            TmpNode::Dir(body) => body.lock().access = access,
+        }
+        Ok(())
+    }
+
+    /// Apply `update` to the node's access record and bump its ctime.
+    fn update_access(
+        &self,
+        path: &str,
+        update: impl FnOnce(&mut NodeAccess),
+    ) -> Result<(), FilesystemError> {
+        let node = self.resolve(path).ok_or(FilesystemError::NotFound)?;
+        let now = current_time();
+        match node {
+            TmpNode::File(body) => {
+                let mut file = body.lock();
+                update(&mut file.access);
+                file.times.changed = now;
+            }
+            TmpNode::Dir(body) => {
+                let mut dir = body.lock();
+                update(&mut dir.access);
+                dir.times.changed = now;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl TmpNode {
@@ -116,6 +189,13 @@ impl TmpNode {
         }
     }
 
+    fn ino(&self) -> u64 {
+        match self {
+            TmpNode::File(body) => body.lock().ino,
+            TmpNode::Dir(body) => body.lock().ino,
+        }
+    }
+
     fn times(&self) -> NodeTimes {
         match self {
             TmpNode::File(body) => body.lock().times,
@@ -123,6 +203,13 @@ impl TmpNode {
         }
     }
 
+    fn access(&self) -> NodeAccess {
+        match self {
+            TmpNode::File(body) => body.lock().access,
+            TmpNode::Dir(body) => body.lock().access,
+        }
+    }
+
     fn touch_changed(&self, now: UnixTimestamp) {
         match self {
             TmpNode::File(body) => body.lock().times.changed = now,
@@ -218,6 +305,30 @@ impl Tmpfs {
     }
 }
 
+/// Block size reported by `stats`; usage is counted in whole pages.
+const PAGE_SIZE: usize = 4096;
+const HEAP_PAGES: u64 = (crate::mm::heap::HEAP_SIZE / PAGE_SIZE) as u64;
+
+/// Count the nodes (including `dir` itself) and data pages below `dir`.
+fn usage(dir: &DirBody) -> (u64, u64) {
+    let children: Vec<TmpNode> = dir.lock().children.values().cloned().collect();
+    let (mut nodes, mut pages) = (1, 0);
+    for child in children {
+        match child {
+            TmpNode::File(file) => {
+                nodes += 1;
+                pages += file.lock().data.len().div_ceil(PAGE_SIZE) as u64;
+            }
+            TmpNode::Dir(sub) => {
+                let (sub_nodes, sub_pages) = usage(&sub);
+                nodes += sub_nodes;
+                pages += sub_pages;
+            }
+        }
+    }
+    (nodes, pages)
+}
+
 /// Validate that a single path component is non-empty and contains no
 /// path separator. Caller is responsible for higher-level checks.
 fn valid_component(c: &str) -> bool {
@@ -234,12 +345,24 @@ impl Filesystem for Tmpfs {
     }
 
     fn stats(&self) -> Result<FilesystemStats, FilesystemError> {
+        let (nodes, used_pages) = usage(&self.root);
+        let frames = crate::mm::memory::with_memory_mapper(|m| m.frame_stats());
+        let heap_pages = crate::mm::heap::stats().map_or(0, |h| (h.free / PAGE_SIZE) as u64);
+        // Unsized tmpfs gets half of RAM for both blocks and inodes, as on
+        // Linux, but file bodies live on the kernel heap so that arena
+        // bounds both the size and what is actually free.
+        let limit = frames.map_or(0, |f| f.total_usable / 2).min(HEAP_PAGES);
+        let free_blocks = limit
+            .saturating_sub(used_pages)
+            .min(heap_pages)
+            .min(frames.map_or(0, |f| f.free));
         Ok(FilesystemStats {
-            total_blocks: 0,
-            free_blocks: 0,
-            block_size: 1,
-            total_inodes: 0,
-            free_inodes: 0,
+            total_blocks: limit,
+            free_blocks,
+            available_blocks: free_blocks,
+            block_size: PAGE_SIZE as u32,
+            total_inodes: limit,
+            free_inodes: limit.saturating_sub(nodes),
         })
     }
 
@@ -320,12 +443,13 @@ impl Filesystem for Tmpfs {
     fn unix_metadata(&self, path: &str) -> Result<UnixMetadata, FilesystemError> {
         let node = self.resolve(path).ok_or(FilesystemError::NotFound)?;
         let times = node.times();
+        let access = node.access();
         let size = node.size();
         Ok(UnixMetadata {
-            inode: 0,
-            mode: if node.is_dir() { 0o040755 } else { 0o100644 },
-            uid: 0,
-            gid: 0,
+            inode: node.ino(),
+            mode: if node.is_dir() { 0o040000 } else { 0o100000 } | access.mode,
+            uid: access.uid,
+            gid: access.gid,
             links: if node.is_dir() { 2 } else { 1 },
             size,
             blocks_512: size.div_ceil(512),
@@ -349,10 +473,10 @@ impl Filesystem for Tmpfs {
         let file = body.lock();
         let size = file.data.len() as u64;
         Ok(UnixMetadata {
-            inode: handle.inode,
-            mode: 0o100644,
-            uid: 0,
-            gid: 0,
+            inode: file.ino,
+            mode: 0o100000 | file.access.mode,
+            uid: file.access.uid,
+            gid: file.access.gid,
             links: 1,
             size,
             blocks_512: size.div_ceil(512),
@@ -513,6 +637,26 @@ impl Filesystem for Tmpfs {
         Ok(())
     }
 
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), FilesystemError> {
+        self.update_access(path, |access| access.mode = mode & 0o7777)
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> Result<(), FilesystemError> {
+        self.update_access(path, |access| {
+            if let Some(uid) = uid {
+                access.uid = uid;
+            }
+            if let Some(gid) = gid {
+                access.gid = gid;
+            }
+        })
+    }
+
     fn mkdir(&self, path: &str) -> Result<(), FilesystemError> {
         let (parent, leaf) = self
             .resolve_parent(path)
@@ -1063,6 +1207,22 @@ mod tests {
         fs.close(&mut moved).unwrap();
     }
 
+    fn test_tmpfs_stats_track_usage() {
+        let fs = Tmpfs::new();
+        let empty = fs.stats().expect("stats");
+        assert_eq!(empty.block_size, 4096);
+        assert!(empty.total_blocks > 0);
+        assert_eq!(empty.total_inodes - empty.free_inodes, 1);
+
+        fs.mkdir("/dir").expect("mkdir");
+        open_write_read(&fs, "/dir/big", &alloc::vec![7u8; 4097]);
+        let used = fs.stats().expect("stats");
+        assert_eq!(used.total_inodes - used.free_inodes, 3);
+        assert_eq!(used.total_blocks, empty.total_blocks);
+        assert!(used.free_blocks <= used.total_blocks - 2);
+        assert_eq!(used.available_blocks, used.free_blocks);
+    }
+
     pub fn get_tests() -> &'static [&'static dyn Testable] {
         &[
             &test_tmpfs_write_then_read,
@@ -1080,6 +1240,7 @@ mod tests {
             &test_tmpfs_set_times_roundtrip_and_omit,
             &test_tmpfs_mutations_update_file_times,
             &test_tmpfs_namespace_mutations_update_parent_times,
+            &test_tmpfs_stats_track_usage,
         ]
     }
 }
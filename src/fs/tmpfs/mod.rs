@@ -1,11 +1,12 @@
 //! In-RAM `tmpfs` filesystem.
 //!
 //! Backed entirely by the kernel heap. Files and directories are inode-like
-//! bodies wrapped in `Arc<Mutex<…>>`; each body owns its content/children and
-//! atime/mtime/ctime metadata.
+//! bodies wrapped in `Arc<Mutex<…>>`; each body owns its content/children,
+//! atime/mtime/ctime metadata and an inode number that is never reused.
 //!
 //! Open handles are anchored in a per-FS side table keyed by a unique
-//! handle id. The `FileHandle.inode` field carries that id, so the
+//! handle id. The `FileHandle.inode` field carries that id (not the
+//! body's inode number, which `stat` and `fstat` report), so the
 //! POD `Filesystem::FileHandle` shape stays unchanged while still
 //! supporting POSIX unlink-while-open semantics: `unlink` drops the
 //! directory-tree reference but the open-handle table keeps the
@@ -1,173 +1,633 @@
 use crate::arch::x86_64::preemption_guard::{PreemptionMutex, PreemptionMutexGuard};
 use crate::drivers::block::BlockDevice;
 use crate::fs::fat::FatFilesystem;
-use crate::fs::filesystem::{detect_filesystem, Filesystem, FilesystemError, FilesystemType};
+use crate::fs::filesystem::{
+    detect_filesystem, Filesystem, FilesystemError, FilesystemStats, FilesystemType,
+};
 use crate::{debug_error, debug_info};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
 
-// Static storage for mounted FAT filesystem wrappers. The wrapper owns the
-// inner FatFilesystem, so we only need one array. Slot count matches the
-// kernel's other static-slot conventions (cf. PARTITION_DEVICES). Bumping
-// this is cheap if more than two simultaneous FAT mounts ever land.
-const MAX_FAT_MOUNTS: usize = 4;
-static mut MOUNTED_FAT_WRAPPERS: [Option<
-    crate::fs::fat::fat_filesystem::FatFilesystemWrapper<'static>,
->; MAX_FAT_MOUNTS] = [None, None, None, None];
-
-const MAX_EXT2_MOUNTS: usize = 2;
-static mut MOUNTED_EXT2: [Option<crate::fs::ext2::Ext2Filesystem<'static>>; MAX_EXT2_MOUNTS] =
-    [None, None];
-
-/// Static slot for the boot-root tmpfs upper layer. There's only one
-/// overlay at `/` for now; a second slot is cheap if other writable
-/// mounts (e.g. `/tmp` as a separate tmpfs) ever land.
-static mut MOUNTED_TMPFS: [Option<crate::fs::tmpfs::Tmpfs>; 2] = [None, None];
-
-/// Static slot for the boot-root overlay. Holds the merged
-/// (tmpfs over FAT) view that gets mounted at `/`.
-static mut MOUNTED_OVERLAY: [Option<crate::fs::overlay::Overlay>; 2] = [None, None];
-
-/// Static slot for the 9p-backed host share mounted at `/shared`. One
-/// device/tag today; widen alongside a second `-fsdev` if one ever lands.
-static mut MOUNTED_P9: [Option<crate::fs::p9::P9Filesystem>; 1] = [None];
-
-/// A sentinel block device for filesystems with no underlying disk
-/// (tmpfs, overlay). The VFS mount table requires a `&'static dyn
-/// BlockDevice`; for non-disk-backed FSes we point at this no-op
-/// implementation. Reads/writes return errors so any kernel code that
-/// accidentally calls through it surfaces a loud failure rather than
-/// silent zero-fill.
-pub struct NullBlockDevice;
+/// One entry of the mount table, as reported by `/proc/mounts`.
+pub struct MountPoint {
+    pub path: String,
+    /// Device path, 9p tag, or pseudo-source (`tmpfs`, `overlay`).
+    pub source: String,
+    /// Linux filesystem type name (`vfat`, `ext2`, `tmpfs`, `overlay`, `9p`).
+    pub fstype: &'static str,
+    pub filesystem: &'static dyn Filesystem,
+    /// Mount-level `ro`, independent of whether the driver itself writes.
+    read_only: bool,
+    /// Open `File`s and in-flight operations pinning this mount; unmounting
+    /// refuses while non-zero.
+    users: usize,
+    /// Lazily unmounted: hidden from lookups, and dropped from the table
+    /// once its last user unpins.
+    detached: bool,
+}
 
-impl crate::drivers::block::BlockDevice for NullBlockDevice {
-    fn read_blocks(&self, _start: u64, _count: u32, _buf: &mut [u8]) -> Result<(), &'static str> {
-        Err("NullBlockDevice: no underlying disk")
-    }
-    fn write_blocks(&self, _start: u64, _count: u32, _buf: &[u8]) -> Result<(), &'static str> {
-        Err("NullBlockDevice: no underlying disk")
-    }
-    fn block_size(&self) -> u32 {
-        1
-    }
-    fn total_blocks(&self) -> u64 {
-        0
+impl MountPoint {
+    /// The same mount as seen from a freshly unshared namespace, which
+    /// has no open files of its own yet.
+    fn copy_for_namespace(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            source: self.source.clone(),
+            fstype: self.fstype,
+            filesystem: self.filesystem,
+            read_only: self.read_only,
+            users: 0,
+            detached: false,
+        }
     }
-    fn name(&self) -> &str {
-        "null"
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || self.filesystem.is_read_only()
     }
-}
 
-pub static NULL_BLOCK_DEVICE: NullBlockDevice = NullBlockDevice;
+    fn matches(&self, path: &str) -> bool {
+        if self.path == "/" {
+            path.starts_with('/')
+        } else {
+            path == self.path
+                || path
+                    .strip_prefix(self.path.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }
+    }
 
-/// Mount point information
-#[derive(Clone, Copy)]
-pub struct MountPoint {
-    pub path: &'static str,
-    pub filesystem: &'static dyn Filesystem,
-    #[expect(dead_code, reason = "intentional kernel API surface")]
-    pub device: &'static dyn BlockDevice,
+    fn relative<'a>(&self, path: &'a str) -> &'a str {
+        let len = self.path.len();
+        if self.path == "/" {
+            path
+        } else if path.len() > len && path.as_bytes()[len] == b'/' {
+            &path[len + 1..]
+        } else if path.len() == len {
+            "/"
+        } else {
+            &path[len..]
+        }
+    }
 }
 
 /// Virtual Filesystem Manager
 pub struct VirtualFilesystem {
-    mounts: [Option<MountPoint>; 16], // Support up to 16 mount points
-    mount_count: usize,
+    mounts: Vec<MountPoint>,
 }
 
 impl VirtualFilesystem {
     pub const fn new() -> Self {
-        Self {
-            mounts: [None; 16],
-            mount_count: 0,
-        }
+        Self { mounts: Vec::new() }
+    }
+
+    /// Entries still attached to the tree.
+    fn attached(&self) -> impl Iterator<Item = &MountPoint> {
+        self.mounts.iter().filter(|mount| !mount.detached)
     }
 
     /// Mount a filesystem at the given path
     pub fn mount(
         &mut self,
-        path: &'static str,
+        path: &str,
+        source: &str,
+        fstype: &'static str,
         filesystem: &'static dyn Filesystem,
-        device: &'static dyn BlockDevice,
+        read_only: bool,
     ) -> Result<(), FilesystemError> {
-        if self.mount_count >= self.mounts.len() {
-            return Err(FilesystemError::DiskFull); // No more mount points available
-        }
-
-        // Check if path is already mounted
-        for mount in self.mounts.iter().flatten() {
-            if mount.path == path {
-                return Err(FilesystemError::AlreadyExists);
-            }
+        if self.attached().any(|mount| mount.path == path) {
+            return Err(FilesystemError::AlreadyExists);
         }
-
-        self.mounts[self.mount_count] = Some(MountPoint {
-            path,
+        self.mounts.push(MountPoint {
+            path: String::from(path),
+            source: String::from(source),
+            fstype,
             filesystem,
-            device,
+            read_only,
+            users: 0,
+            detached: false,
         });
-        self.mount_count += 1;
-
         debug_info!("Mounted {} filesystem at {}", filesystem.name(), path);
         Ok(())
     }
 
-    /// Unmount a filesystem
+    /// Take the mount at exactly `path` out of the tree. Refuses `/`, and
+    /// mounts with users unless `detach` asks for a lazy unmount. A lazily
+    /// unmounted entry with users stays in the table, hidden, until
+    /// [`unpin`](Self::unpin) drops the last of them. Returns the
+    /// filesystem and whether its entry was removed.
+    pub fn unmount(
+        &mut self,
+        path: &str,
+        detach: bool,
+    ) -> Result<(&'static dyn Filesystem, bool), FilesystemError> {
+        let index = self
+            .mounts
+            .iter()
+            .position(|mount| !mount.detached && mount.path == path)
+            .ok_or(FilesystemError::InvalidPath)?;
+        let target = &self.mounts[index];
+        let nested = self
+            .attached()
+            .any(|mount| mount.path.len() > path.len() && target.matches(&mount.path));
+        if path == "/" || nested || (!detach && target.users > 0) {
+            return Err(FilesystemError::Busy);
+        }
+        if target.users > 0 {
+            let mount = &mut self.mounts[index];
+            mount.detached = true;
+            debug_info!("Detached {} from {}", mount.fstype, mount.path);
+            return Ok((mount.filesystem, false));
+        }
+        let mount = self.mounts.remove(index);
+        debug_info!("Unmounted {} from {}", mount.fstype, mount.path);
+        Ok((mount.filesystem, true))
+    }
+
+    /// Flip the mount-level read-only flag (`MS_REMOUNT`). A driver that is
+    /// itself read-only cannot be remounted writable.
+    pub fn remount(&mut self, path: &str, read_only: bool) -> Result<(), FilesystemError> {
+        let mount = self
+            .mounts
+            .iter_mut()
+            .find(|mount| !mount.detached && mount.path == path)
+            .ok_or(FilesystemError::InvalidPath)?;
+        if !read_only && mount.filesystem.is_read_only() {
+            return Err(FilesystemError::ReadOnly);
+        }
+        mount.read_only = read_only;
+        Ok(())
+    }
+
+    /// Find the longest mount point covering `path`.
+    fn find_mount<'a>(&self, path: &'a str) -> Option<(&MountPoint, &'a str)> {
+        self.attached()
+            .filter(|mount| mount.matches(path))
+            .max_by_key(|mount| mount.path.len())
+            .map(|mount| (mount, mount.relative(path)))
+    }
 
     /// Find the filesystem for a given path
     pub fn find_filesystem<'a>(&self, path: &'a str) -> Option<(&'static dyn Filesystem, &'a str)> {
-        let mut best_match: Option<(&MountPoint, usize)> = None;
-
-        // Find the longest matching mount point
-        for mount in self.mounts.iter().flatten() {
-            let matches = if mount.path == "/" {
-                path.starts_with('/')
-            } else {
-                path == mount.path
-                    || path
-                        .strip_prefix(mount.path)
-                        .is_some_and(|rest| rest.starts_with('/'))
-            };
-            if matches {
-                let mount_len = mount.path.len();
-                if best_match.is_none() || mount_len > best_match.unwrap().1 {
-                    best_match = Some((mount, mount_len));
-                }
-            }
+        self.find_mount(path)
+            .map(|(mount, relative)| (mount.filesystem, relative))
+    }
+
+    /// Resolve `path` like `find_filesystem` and count the caller as a user
+    /// of the mount until the matching `unpin`. Writable opens fail with
+    /// `ReadOnly` on an `ro` mount. [`VfsGuard::pin`] also keeps the
+    /// filesystem alive.
+    fn pin<'a>(
+        &mut self,
+        path: &'a str,
+        write: bool,
+    ) -> Result<(&'static dyn Filesystem, &'a str), FilesystemError> {
+        let (mount, relative) = self.find_mount(path).ok_or(FilesystemError::NotFound)?;
+        if write && mount.read_only {
+            return Err(FilesystemError::ReadOnly);
+        }
+        let filesystem = mount.filesystem;
+        if let Some(mount) = self
+            .mounts
+            .iter_mut()
+            .find(|mount| !mount.detached && same_filesystem(mount.filesystem, filesystem))
+        {
+            mount.users += 1;
         }
+        Ok((filesystem, relative))
+    }
 
-        best_match.map(|(mount, len)| {
-            let relative_path = if mount.path == "/" {
-                path
-            } else if path.len() > len && path.as_bytes()[len] == b'/' {
-                &path[len + 1..]
-            } else if path.len() == len {
-                "/"
-            } else {
-                &path[len..]
-            };
-            (mount.filesystem, relative_path)
-        })
+    /// Drop one `pin` reference. Returns true when that was the last user
+    /// of a lazily detached entry, which is then removed.
+    fn unpin(&mut self, filesystem: &'static dyn Filesystem) -> bool {
+        let Some(index) = self
+            .mounts
+            .iter()
+            .position(|mount| mount.users > 0 && same_filesystem(mount.filesystem, filesystem))
+        else {
+            return false;
+        };
+        let mount = &mut self.mounts[index];
+        mount.users -= 1;
+        if mount.detached && mount.users == 0 {
+            let mount = self.mounts.remove(index);
+            debug_info!("Released detached {} from {}", mount.fstype, mount.path);
+            return true;
+        }
+        false
     }
 
     /// List all mount points
     pub fn list_mounts(&self) -> impl Iterator<Item = &MountPoint> {
-        self.mounts.iter().flatten()
+        self.attached()
+    }
+
+    /// `pivot_root`: make the mount at `new_root` the root of this table and
+    /// move everything else below `put_old`, which must lie strictly inside
+    /// `new_root`. Stacking the old root on the new one (`put_old` equal to
+    /// `new_root`) is not supported.
+    pub fn pivot(&mut self, new_root: &str, put_old: &str) -> Result<(), FilesystemError> {
+        if new_root == "/"
+            || !self.attached().any(|mount| mount.path == new_root)
+            || !matches!(below(put_old, new_root), Some(rest) if rest != "/")
+        {
+            return Err(FilesystemError::InvalidPath);
+        }
+        let paths: Vec<String> = self
+            .attached()
+            .map(|mount| pivoted_path(new_root, put_old, &mount.path))
+            .collect();
+        if paths
+            .iter()
+            .enumerate()
+            .any(|(index, path)| paths[..index].contains(path))
+        {
+            return Err(FilesystemError::Busy);
+        }
+        let attached = self.mounts.iter_mut().filter(|mount| !mount.detached);
+        for (mount, path) in attached.zip(paths) {
+            mount.path = path;
+        }
+        Ok(())
+    }
+}
+
+/// Where `path` ends up after `pivot_root(new_root, put_old)`: paths inside
+/// `new_root` lose that prefix, everything else moves below `put_old`.
+/// `put_old` itself is given before the pivot, so it lies inside
+/// `new_root`.
+pub fn pivoted_path(new_root: &str, put_old: &str, path: &str) -> String {
+    if let Some(moved) = below(path, new_root) {
+        return String::from(moved);
+    }
+    match (below(put_old, new_root).unwrap_or(put_old), path) {
+        ("/", path) => String::from(path),
+        (old_root, "/") => String::from(old_root),
+        (old_root, path) => alloc::format!("{old_root}{path}"),
+    }
+}
+
+/// `path` relative to `dir` (as an absolute path) when it is `dir` or lies
+/// below it.
+fn below<'a>(path: &'a str, dir: &str) -> Option<&'a str> {
+    if dir == "/" {
+        return Some(path);
+    }
+    match path.strip_prefix(dir) {
+        Some("") => Some("/"),
+        Some(rest) if rest.starts_with('/') => Some(rest),
+        _ => None,
+    }
+}
+
+fn same_filesystem(a: &dyn Filesystem, b: &dyn Filesystem) -> bool {
+    core::ptr::addr_eq(a as *const dyn Filesystem, b as *const dyn Filesystem)
+}
+
+/// Identifies one mount table. Every task starts in
+/// [`INITIAL_NAMESPACE`]; `unshare(CLONE_NEWNS)` moves it to a private copy
+/// that later mounts and unmounts change without affecting anyone else.
+pub type NamespaceId = u64;
+
+pub const INITIAL_NAMESPACE: NamespaceId = 0;
+
+/// Frees what a mounted filesystem was built on, such as the block device
+/// handles under it, after the filesystem itself is gone.
+pub type Release = Box<dyn FnOnce() + Send>;
+
+/// One installed filesystem, however many tables list it.
+struct Instance {
+    filesystem: &'static dyn Filesystem,
+    source: String,
+    /// Table entries naming it, detached ones included, plus pins, across
+    /// every namespace. The filesystem is freed when this reaches zero.
+    references: usize,
+    release: Option<Release>,
+}
+
+/// Every mount table, plus which one each task resolves paths in.
+struct Namespaces {
+    initial: VirtualFilesystem,
+    unshared: BTreeMap<NamespaceId, VirtualFilesystem>,
+    instances: Vec<Instance>,
+    /// Tasks outside the initial namespace, by tid. Kernel contexts and
+    /// tasks not listed use [`INITIAL_NAMESPACE`].
+    members: BTreeMap<u32, NamespaceId>,
+    next_id: NamespaceId,
+}
+
+impl Namespaces {
+    const fn new() -> Self {
+        Self {
+            initial: VirtualFilesystem::new(),
+            unshared: BTreeMap::new(),
+            instances: Vec::new(),
+            members: BTreeMap::new(),
+            next_id: INITIAL_NAMESPACE + 1,
+        }
+    }
+
+    fn namespace_of(&self, tid: Option<u32>) -> NamespaceId {
+        tid.and_then(|tid| self.members.get(&tid).copied())
+            .unwrap_or(INITIAL_NAMESPACE)
+    }
+
+    fn table(&self, id: NamespaceId) -> Option<&VirtualFilesystem> {
+        match id {
+            INITIAL_NAMESPACE => Some(&self.initial),
+            id => self.unshared.get(&id),
+        }
+    }
+
+    fn table_mut(&mut self, id: NamespaceId) -> Option<&mut VirtualFilesystem> {
+        match id {
+            INITIAL_NAMESPACE => Some(&mut self.initial),
+            id => self.unshared.get_mut(&id),
+        }
+    }
+
+    fn retain(&mut self, filesystem: &'static dyn Filesystem) {
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| same_filesystem(instance.filesystem, filesystem))
+        {
+            instance.references += 1;
+        }
+    }
+
+    /// Drop one reference to `filesystem`. Returns its instance, for the
+    /// caller to [`destroy`] once the lock is dropped, if that was the last.
+    fn release(&mut self, filesystem: &'static dyn Filesystem) -> Option<Instance> {
+        let index = self
+            .instances
+            .iter()
+            .position(|instance| same_filesystem(instance.filesystem, filesystem))?;
+        let instance = &mut self.instances[index];
+        instance.references -= 1;
+        (instance.references == 0).then(|| self.instances.remove(index))
+    }
+
+    /// Take `tid` out of its namespace. Returns the table if that was the
+    /// last member, for the caller to sync once the lock is dropped.
+    fn leave(&mut self, tid: u32) -> Option<VirtualFilesystem> {
+        let id = self.members.remove(&tid)?;
+        if self.members.values().any(|&member| member == id) {
+            return None;
+        }
+        self.unshared.remove(&id)
+    }
+}
+
+/// Global mount tables. Filesystems are leaked on mount and freed once no
+/// table lists them and nothing pins them, so a `&'static dyn Filesystem`
+/// stays valid only while its holder keeps a pin; runtime mount
+/// lookup/mutation is serialized here.
+static VFS: PreemptionMutex<Namespaces> = PreemptionMutex::new(Namespaces::new());
+
+/// The locked mount table of one namespace.
+pub struct VfsGuard {
+    namespaces: PreemptionMutexGuard<'static, Namespaces>,
+    id: NamespaceId,
+}
+
+impl VfsGuard {
+    pub fn namespace(&self) -> NamespaceId {
+        self.id
+    }
+
+    /// Resolve `path` and pin its mount until the matching [`unpin`]: the
+    /// mount counts as busy, and its filesystem outlives any unmount.
+    /// Writable opens fail with `ReadOnly` on an `ro` mount.
+    pub fn pin<'a>(
+        &mut self,
+        path: &'a str,
+        write: bool,
+    ) -> Result<(&'static dyn Filesystem, &'a str), FilesystemError> {
+        let (filesystem, relative) = self.deref_mut().pin(path, write)?;
+        self.namespaces.retain(filesystem);
+        Ok((filesystem, relative))
+    }
+}
+
+impl Deref for VfsGuard {
+    type Target = VirtualFilesystem;
+
+    fn deref(&self) -> &VirtualFilesystem {
+        self.namespaces
+            .table(self.id)
+            .expect("namespace checked on lock")
+    }
+}
+
+impl DerefMut for VfsGuard {
+    fn deref_mut(&mut self) -> &mut VirtualFilesystem {
+        let id = self.id;
+        self.namespaces
+            .table_mut(id)
+            .expect("namespace checked on lock")
     }
 }
 
-/// Global mount table. The backing mount-slot arrays above are populated only
-/// during BSP boot while APs remain parked, then become read-only; runtime
-/// mount lookup/mutation is serialized here.
-static VFS: PreemptionMutex<VirtualFilesystem> = PreemptionMutex::new(VirtualFilesystem::new());
+/// The mount table the current task resolves paths in.
+pub fn get_vfs() -> VfsGuard {
+    get_vfs_of(crate::arch::x86_64::percpu::current_user_pid())
+}
 
-/// Get the global VFS instance
-pub fn get_vfs() -> PreemptionMutexGuard<'static, VirtualFilesystem> {
+/// The mount table task `tid` resolves paths in; the initial one for
+/// `None`.
+pub fn get_vfs_of(tid: Option<u32>) -> VfsGuard {
+    let namespaces = VFS.lock();
+    let id = namespaces.namespace_of(tid);
+    VfsGuard { namespaces, id }
+}
+
+/// Drop a `pin` taken in namespace `id`. The last pin on a lazily
+/// unmounted filesystem syncs and frees it.
+pub fn unpin(id: NamespaceId, filesystem: &'static dyn Filesystem) {
+    let destroyed = {
+        let mut namespaces = VFS.lock();
+        let removed = namespaces
+            .table_mut(id)
+            .is_some_and(|table| table.unpin(filesystem));
+        let pin = namespaces.release(filesystem);
+        if removed {
+            namespaces.release(filesystem)
+        } else {
+            pin
+        }
+    };
+    if let Some(instance) = destroyed {
+        if let Err(error) = instance.filesystem.sync() {
+            debug_error!("Failed to sync detached {}: {:?}", instance.source, error);
+        }
+        destroy(instance);
+    }
+}
+
+/// Free an instance nothing refers to any more: the filesystem first, then
+/// whatever it was built on.
+fn destroy(instance: Instance) {
+    debug_info!(
+        "Freed {} from {}",
+        instance.filesystem.name(),
+        instance.source
+    );
+    // SAFETY: `install` leaked this box, and with no table entry and no pin
+    // left nothing can reach it any more.
+    drop(unsafe {
+        Box::from_raw(instance.filesystem as *const dyn Filesystem as *mut dyn Filesystem)
+    });
+    if let Some(release) = instance.release {
+        release();
+    }
+}
+
+/// True while a filesystem mounted from `source` is in any mount table,
+/// lazily detached ones included, or still pinned.
+pub fn source_in_use(source: &str) -> bool {
+    VFS.lock()
+        .instances
+        .iter()
+        .any(|instance| instance.source == source)
+}
+
+/// Hand `release` to the filesystem just mounted from `source`, to run when
+/// it is freed. Runs it at once if that filesystem is already gone.
+pub fn attach_release(source: &str, release: Release) {
+    let mut namespaces = VFS.lock();
+    let Some(instance) = namespaces
+        .instances
+        .iter_mut()
+        .rev()
+        .find(|instance| instance.source == source && instance.release.is_none())
+    else {
+        drop(namespaces);
+        release();
+        return;
+    };
+    instance.release = Some(release);
+}
+
+/// Move `tid` into a private copy of its current mount table.
+pub fn unshare_namespace(tid: u32) -> NamespaceId {
+    let (id, retired) = {
+        let mut namespaces = VFS.lock();
+        let current = namespaces.namespace_of(Some(tid));
+        let mounts: Vec<MountPoint> = namespaces
+            .table(current)
+            .map(|table| {
+                table
+                    .attached()
+                    .map(MountPoint::copy_for_namespace)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for mount in &mounts {
+            namespaces.retain(mount.filesystem);
+        }
+        let id = namespaces.next_id;
+        namespaces.next_id += 1;
+        namespaces.unshared.insert(id, VirtualFilesystem { mounts });
+        let retired = namespaces.leave(tid);
+        namespaces.members.insert(tid, id);
+        (id, retired)
+    };
+    sync_retired(retired);
+    id
+}
+
+/// Put a new task `child` in the namespace of `parent` (fork and thread
+/// creation).
+pub fn inherit_namespace(parent: u32, child: u32) {
+    let mut namespaces = VFS.lock();
+    if let Some(&id) = namespaces.members.get(&parent) {
+        namespaces.members.insert(child, id);
+    }
+}
+
+/// Forget an exiting task. The last task out of an unshared namespace
+/// takes its mount table with it.
+pub fn leave_namespace(tid: u32) {
+    let retired = VFS.lock().leave(tid);
+    sync_retired(retired);
+}
+
+/// Tasks whose namespace is `id`. Empty for the initial namespace, whose
+/// members are not tracked.
+pub fn namespace_members(id: NamespaceId) -> Vec<u32> {
     VFS.lock()
+        .members
+        .iter()
+        .filter_map(|(&tid, &member)| (member == id).then_some(tid))
+        .collect()
+}
+
+fn sync_retired(table: Option<VirtualFilesystem>) {
+    let Some(table) = table else {
+        return;
+    };
+    for mount in &table.mounts {
+        if let Err(error) = mount.filesystem.sync() {
+            debug_error!(
+                "Failed to sync {} on namespace exit: {:?}",
+                mount.path,
+                error
+            );
+        }
+    }
+    let destroyed: Vec<Instance> = {
+        let mut namespaces = VFS.lock();
+        table
+            .mounts
+            .iter()
+            .filter_map(|mount| namespaces.release(mount.filesystem))
+            .collect()
+    };
+    destroyed.into_iter().for_each(destroy);
+}
+
+/// Leak `filesystem` and enter it into the mount table, where it lives
+/// until [`destroy`]. If the mount point is already taken the filesystem was
+/// never published and is freed again.
+fn install(
+    mount_path: &str,
+    source: &str,
+    fstype: &'static str,
+    filesystem: Box<dyn Filesystem>,
+    read_only: bool,
+) -> Result<&'static dyn Filesystem, FilesystemError> {
+    let filesystem: &'static dyn Filesystem = Box::leak(filesystem);
+    let mut vfs = get_vfs();
+    match vfs.mount(mount_path, source, fstype, filesystem, read_only) {
+        Ok(()) => {
+            vfs.namespaces.instances.push(Instance {
+                filesystem,
+                source: String::from(source),
+                references: 1,
+                release: None,
+            });
+            Ok(filesystem)
+        }
+        Err(error) => {
+            // SAFETY: the mount was refused, so the leaked box was never
+            // published and this is the only reference to it.
+            drop(unsafe {
+                Box::from_raw(filesystem as *const dyn Filesystem as *mut dyn Filesystem)
+            });
+            Err(error)
+        }
+    }
 }
 
-/// Auto-mount a block device by detecting its filesystem type
+/// Auto-mount a block device read-only by detecting its filesystem type.
+/// `source` is the device name reported by `/proc/mounts`.
 pub fn auto_mount(
     device: &'static dyn BlockDevice,
-    mount_path: &'static str,
+    source: &str,
+    mount_path: &str,
 ) -> Result<FilesystemType, FilesystemError> {
     let fs_type = detect_filesystem(device)?;
 
@@ -175,66 +635,25 @@ pub fn auto_mount(
 
     match fs_type {
         FilesystemType::Fat12 | FilesystemType::Fat16 | FilesystemType::Fat32 => {
-            unsafe {
-                // Find the first free slot in the static wrapper array.
-                let wrappers_ptr = &raw mut MOUNTED_FAT_WRAPPERS;
-                let slot = (0..MAX_FAT_MOUNTS).find(|&i| (*wrappers_ptr)[i].is_none());
-                let slot = match slot {
-                    Some(i) => i,
-                    None => {
-                        debug_error!("No free FAT mount slots (max {})", MAX_FAT_MOUNTS);
-                        return Err(FilesystemError::DiskFull);
-                    }
-                };
-
-                // Try to create FAT filesystem with 'static lifetime
-                match FatFilesystem::new(device) {
-                    Ok(fat_fs) => {
-                        // Transmute to 'static lifetime - safe because the device is 'static
-                        let fat_fs_static: FatFilesystem<'static> = core::mem::transmute(fat_fs);
-                        let wrapper = crate::fs::fat::fat_filesystem::FatFilesystemWrapper::new(
-                            fat_fs_static,
-                        );
-                        (*wrappers_ptr)[slot] = Some(wrapper);
-
-                        // Take a 'static reference to the wrapper now living in the slot.
-                        if let Some(wrapper_ref) = (*&raw const MOUNTED_FAT_WRAPPERS)[slot].as_ref()
-                        {
-                            let mut vfs = get_vfs();
-                            match vfs.mount(mount_path, wrapper_ref as &dyn Filesystem, device) {
-                                Ok(_) => {
-                                    debug_info!(
-                                        "Successfully mounted FAT filesystem at {} (slot {})",
-                                        mount_path,
-                                        slot
-                                    );
-                                    Ok(fs_type)
-                                }
-                                Err(e) => {
-                                    debug_error!(
-                                        "Failed to mount FAT filesystem at {}: {:?}",
-                                        mount_path,
-                                        e
-                                    );
-                                    // Free the slot so a later attempt can reuse it.
-                                    (*wrappers_ptr)[slot] = None;
-                                    Err(e)
-                                }
-                            }
-                        } else {
-                            debug_error!("Failed to create wrapper reference");
-                            (*wrappers_ptr)[slot] = None;
-                            Err(FilesystemError::InvalidFilesystem)
-                        }
-                    }
-                    Err(_) => {
-                        debug_error!("Failed to initialize FAT filesystem");
-                        Err(FilesystemError::InvalidFilesystem)
-                    }
+            let fat_fs = FatFilesystem::new(device).map_err(|_| {
+                debug_error!("Failed to initialize FAT filesystem");
+                FilesystemError::InvalidFilesystem
+            })?;
+            let wrapper = crate::fs::fat::fat_filesystem::FatFilesystemWrapper::new(fat_fs);
+            match install(mount_path, source, "vfat", Box::new(wrapper), false) {
+                Ok(_) => {
+                    debug_info!("Successfully mounted FAT filesystem at {}", mount_path);
+                    Ok(fs_type)
+                }
+                Err(e) => {
+                    debug_error!("Failed to mount FAT filesystem at {}: {:?}", mount_path, e);
+                    Err(e)
                 }
             }
         }
-        FilesystemType::Ext2 => mount_ext2(device, mount_path, false, false).map(|_| fs_type),
+        FilesystemType::Ext2 => {
+            mount_ext2(device, source, mount_path, false, false).map(|_| fs_type)
+        }
         FilesystemType::Ext3 | FilesystemType::Ext4 => {
             debug_info!("Ext3/ext4 features are not supported by the ext2 driver");
             Err(FilesystemError::UnsupportedFeature)
@@ -256,12 +675,13 @@ pub fn auto_mount(
 /// to a read-only mount).
 pub fn auto_mount_writable(
     device: &'static dyn BlockDevice,
-    mount_path: &'static str,
+    source: &str,
+    mount_path: &str,
     force_dirty_mount: bool,
 ) -> Result<FilesystemType, FilesystemError> {
     let fs_type = detect_filesystem(device)?;
     if fs_type == FilesystemType::Ext2 {
-        mount_ext2(device, mount_path, true, force_dirty_mount)?;
+        mount_ext2(device, source, mount_path, true, force_dirty_mount)?;
         return Ok(fs_type);
     }
     if !matches!(
@@ -270,67 +690,34 @@ pub fn auto_mount_writable(
     ) {
         return Err(FilesystemError::UnsupportedFeature);
     }
-    unsafe {
-        let wrappers_ptr = &raw mut MOUNTED_FAT_WRAPPERS;
-        let slot = (0..MAX_FAT_MOUNTS)
-            .find(|&i| (*wrappers_ptr)[i].is_none())
-            .ok_or(FilesystemError::DiskFull)?;
-        let fat_fs = FatFilesystem::new(device).map_err(|_| FilesystemError::InvalidFilesystem)?;
-        // C-2 dirty-bit gate. Errors here propagate as ReadOnly so
-        // the caller can choose to retry as a normal (read-only)
-        // mount with `auto_mount(...)`.
-        fat_fs
-            .enable_writes(force_dirty_mount)
-            .map_err(|_| FilesystemError::ReadOnly)?;
-        let fat_fs_static: FatFilesystem<'static> = core::mem::transmute(fat_fs);
-        let wrapper =
-            crate::fs::fat::fat_filesystem::FatFilesystemWrapper::new_writable(fat_fs_static);
-        (*wrappers_ptr)[slot] = Some(wrapper);
-        if let Some(wrapper_ref) = (*&raw const MOUNTED_FAT_WRAPPERS)[slot].as_ref() {
-            let mut vfs = get_vfs();
-            vfs.mount(mount_path, wrapper_ref as &dyn Filesystem, device)?;
-            debug_info!(
-                "Mounted {} as WRITABLE at {} (slot {})",
-                wrapper_ref.name(),
-                mount_path,
-                slot
-            );
-            Ok(fs_type)
-        } else {
-            (*wrappers_ptr)[slot] = None;
-            Err(FilesystemError::InvalidFilesystem)
-        }
-    }
+    let fat_fs = FatFilesystem::new(device).map_err(|_| FilesystemError::InvalidFilesystem)?;
+    // C-2 dirty-bit gate. Errors here propagate as ReadOnly so
+    // the caller can choose to retry as a normal (read-only)
+    // mount with `auto_mount(...)`.
+    fat_fs
+        .enable_writes(force_dirty_mount)
+        .map_err(|_| FilesystemError::ReadOnly)?;
+    let wrapper = crate::fs::fat::fat_filesystem::FatFilesystemWrapper::new_writable(fat_fs);
+    let filesystem = install(mount_path, source, "vfat", Box::new(wrapper), false)?;
+    debug_info!(
+        "Mounted {} as WRITABLE at {}",
+        filesystem.name(),
+        mount_path
+    );
+    Ok(fs_type)
 }
 
 fn mount_ext2(
     device: &'static dyn BlockDevice,
-    mount_path: &'static str,
+    source: &str,
+    mount_path: &str,
     writable: bool,
     force_dirty: bool,
 ) -> Result<(), FilesystemError> {
-    unsafe {
-        let slots = &raw mut MOUNTED_EXT2;
-        let slot = (0..MAX_EXT2_MOUNTS)
-            .find(|&index| (*slots)[index].is_none())
-            .ok_or(FilesystemError::DiskFull)?;
-        let filesystem = crate::fs::ext2::Ext2Filesystem::new(device, writable, force_dirty)?;
-        (*slots)[slot] = Some(filesystem);
-        let filesystem_ref = (*&raw const MOUNTED_EXT2)[slot]
-            .as_ref()
-            .ok_or(FilesystemError::InvalidFilesystem)?;
-        if let Err(error) = get_vfs().mount(mount_path, filesystem_ref, device) {
-            (*slots)[slot] = None;
-            return Err(error);
-        }
-        debug_info!(
-            "Mounted ext2 at {} (slot {}, writable={})",
-            mount_path,
-            slot,
-            writable
-        );
-        Ok(())
-    }
+    let filesystem = crate::fs::ext2::Ext2Filesystem::new(device, writable, force_dirty)?;
+    install(mount_path, source, "ext2", Box::new(filesystem), false)?;
+    debug_info!("Mounted ext2 at {} (writable={})", mount_path, writable);
+    Ok(())
 }
 
 /// Mount the boot-root FAT as the LOWER layer of an overlay, with a
@@ -346,78 +733,132 @@ pub fn mount_overlay_root(device: &'static dyn BlockDevice) -> Result<(), Filesy
     use crate::fs::overlay::Overlay;
     use crate::fs::tmpfs::Tmpfs;
 
-    // 1. Construct the FAT lower layer in slot 0.
+    // 1. The FAT lower layer and tmpfs upper layer live as long as the
+    //    overlay that references them, i.e. forever.
     let fat_fs = FatFilesystem::new(device).map_err(|_| FilesystemError::InvalidFilesystem)?;
-    let fat_fs_static: FatFilesystem<'static> = unsafe { core::mem::transmute(fat_fs) };
-    let wrapper = crate::fs::fat::fat_filesystem::FatFilesystemWrapper::new(fat_fs_static);
-    unsafe {
-        let slot = (0..MAX_FAT_MOUNTS)
-            .find(|&i| MOUNTED_FAT_WRAPPERS[i].is_none())
-            .ok_or(FilesystemError::DiskFull)?;
-        MOUNTED_FAT_WRAPPERS[slot] = Some(wrapper);
-    }
-    let lower_ref: &'static dyn Filesystem = unsafe {
-        MOUNTED_FAT_WRAPPERS[0]
-            .as_ref()
-            .ok_or(FilesystemError::InvalidFilesystem)? as &dyn Filesystem
-    };
-
-    // 2. Construct the tmpfs upper in slot 0.
-    unsafe {
-        MOUNTED_TMPFS[0] = Some(Tmpfs::new());
-    }
-    let upper_ref: &'static dyn Filesystem = unsafe {
-        MOUNTED_TMPFS[0]
-            .as_ref()
-            .ok_or(FilesystemError::InvalidFilesystem)? as &dyn Filesystem
-    };
-
-    // 3. Construct the overlay in slot 0 and mount at `/`.
-    unsafe {
-        MOUNTED_OVERLAY[0] = Some(Overlay::new(upper_ref, lower_ref));
-    }
-    let overlay_ref: &'static dyn Filesystem = unsafe {
-        MOUNTED_OVERLAY[0]
-            .as_ref()
-            .ok_or(FilesystemError::InvalidFilesystem)? as &dyn Filesystem
-    };
+    let lower_ref: &'static dyn Filesystem = Box::leak(Box::new(
+        crate::fs::fat::fat_filesystem::FatFilesystemWrapper::new(fat_fs),
+    ));
+    let upper_ref: &'static dyn Filesystem = Box::leak(Box::new(Tmpfs::new()));
 
-    let mut vfs = get_vfs();
-    vfs.mount("/", overlay_ref, &NULL_BLOCK_DEVICE)?;
+    // 2. Construct the overlay and mount at `/`.
+    let overlay = Overlay::new(upper_ref, lower_ref);
+    install("/", "overlay", "overlay", Box::new(overlay), false)?;
     debug_info!("Mounted overlay(tmpfs over FAT) at /");
     Ok(())
 }
 
+/// Mount a fresh tmpfs at `/dev/shm` for POSIX shared memory. Unlike the
+/// overlay's upper layer it is never persisted, so segments vanish on
+/// reboot as they do on Linux.
+pub fn mount_dev_shm() -> Result<(), FilesystemError> {
+    mount_tmpfs("tmpfs", crate::userland::devfs::SHM_DIR, false).map(|_| ())
+}
+
+/// Mount a fresh, empty tmpfs at `mount_path`.
+pub fn mount_tmpfs(
+    source: &str,
+    mount_path: &str,
+    read_only: bool,
+) -> Result<&'static dyn Filesystem, FilesystemError> {
+    install(
+        mount_path,
+        source,
+        "tmpfs",
+        Box::new(crate::fs::tmpfs::Tmpfs::new()),
+        read_only,
+    )
+}
+
 /// Mount a 9p share (no backing block device) at `mount_path`. The
-/// filesystem must arrive with its version/attach handshake already done.
+/// filesystem must arrive with its version/attach handshake already done;
+/// `tag` is the virtio mount tag reported as the source.
 pub fn mount_p9(
     filesystem: crate::fs::p9::P9Filesystem,
-    mount_path: &'static str,
+    tag: &str,
+    mount_path: &str,
+    read_only: bool,
 ) -> Result<(), FilesystemError> {
-    unsafe {
-        let slot_ptr = &raw mut MOUNTED_P9;
-        if (*slot_ptr)[0].is_some() {
-            return Err(FilesystemError::AlreadyExists);
-        }
-        (*slot_ptr)[0] = Some(filesystem);
-        let fs_ref = (*&raw const MOUNTED_P9)[0]
-            .as_ref()
-            .ok_or(FilesystemError::InvalidFilesystem)?;
+    install(mount_path, tag, "9p", Box::new(filesystem), read_only).map(|_| ())
+}
+
+/// Sync and detach the mount at `mount_path`, freeing its filesystem if
+/// nothing else refers to it. See [`VirtualFilesystem::unmount`] for the
+/// refusal rules.
+pub fn unmount(mount_path: &str, detach: bool) -> Result<(), FilesystemError> {
+    let filesystem = {
         let mut vfs = get_vfs();
-        vfs.mount(mount_path, fs_ref as &dyn Filesystem, &NULL_BLOCK_DEVICE)
+        let (filesystem, removed) = vfs.unmount(mount_path, detach)?;
+        // Hold a reference across the sync, in place of the entry if it went.
+        if !removed {
+            vfs.namespaces.retain(filesystem);
+        }
+        filesystem
+    };
+    let synced = filesystem.sync();
+    if let Some(instance) = VFS.lock().release(filesystem) {
+        destroy(instance);
     }
+    synced
 }
 
 /// Convenience functions that operate on the global VFS
 
+/// A mount pinned for one operation, released when dropped.
+pub(crate) struct Pinned {
+    namespace: NamespaceId,
+    filesystem: &'static dyn Filesystem,
+}
+
+impl Deref for Pinned {
+    type Target = dyn Filesystem;
+
+    fn deref(&self) -> &(dyn Filesystem + 'static) {
+        self.filesystem
+    }
+}
+
+impl Drop for Pinned {
+    fn drop(&mut self) {
+        unpin(self.namespace, self.filesystem);
+    }
+}
+
 /// Resolve a path while holding the mount-table lock, then return only the
-/// selected static filesystem pointer and the caller-owned relative path.
-/// Filesystem operations may sleep on block I/O, so none may run while the
-/// global VFS lock is held.
-fn resolve_mount(path: &str) -> Result<(&'static dyn Filesystem, &str), FilesystemError> {
-    get_vfs()
-        .find_filesystem(path)
-        .ok_or(FilesystemError::NotFound)
+/// pinned filesystem and the caller-owned relative path. Filesystem
+/// operations may sleep on block I/O, so none may run while the global VFS
+/// lock is held; the pin keeps an unmount from freeing the filesystem
+/// under them.
+pub(crate) fn resolve_mount(path: &str) -> Result<(Pinned, &str), FilesystemError> {
+    let mut vfs = get_vfs();
+    let namespace = vfs.namespace();
+    let (filesystem, relative) = vfs.pin(path, false)?;
+    Ok((
+        Pinned {
+            namespace,
+            filesystem,
+        },
+        relative,
+    ))
+}
+
+/// `resolve_mount` for mutating operations: fails with `ReadOnly` when
+/// either the mount or its driver is read-only.
+fn resolve_writable(path: &str) -> Result<(Pinned, &str), FilesystemError> {
+    let mut vfs = get_vfs();
+    let (mount, _) = vfs.find_mount(path).ok_or(FilesystemError::NotFound)?;
+    if mount.is_read_only() {
+        return Err(FilesystemError::ReadOnly);
+    }
+    let namespace = vfs.namespace();
+    let (filesystem, relative) = vfs.pin(path, false)?;
+    Ok((
+        Pinned {
+            namespace,
+            filesystem,
+        },
+        relative,
+    ))
 }
 
 #[expect(
@@ -436,6 +877,8 @@ pub fn vfs_read_dir(
     path: &str,
 ) -> Result<crate::fs::filesystem::DirectoryIterator<'_>, FilesystemError> {
     let (filesystem, relative) = resolve_mount(path)?;
+    // The iterator reads every entry up front, so it outlives the pin.
+    let filesystem: &'static dyn Filesystem = filesystem.filesystem;
     filesystem.read_dir(relative)
 }
 
@@ -463,11 +906,41 @@ pub fn vfs_set_times(
     accessed: Option<crate::fs::filesystem::UnixTimestamp>,
     modified: Option<crate::fs::filesystem::UnixTimestamp>,
 ) -> Result<(), FilesystemError> {
-    let (filesystem, relative) = resolve_mount(path)?;
-    if filesystem.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
+    let (filesystem, relative) = resolve_writable(path)?;
+    filesystem.set_times(relative, accessed, modified)?;
+    crate::userland::inotify::attrib(path);
+    Ok(())
+}
+
+pub fn vfs_set_mode(path: &str, mode: u32) -> Result<(), FilesystemError> {
+    let (filesystem, relative) = resolve_writable(path)?;
+    filesystem.set_mode(relative, mode)?;
+    crate::userland::inotify::attrib(path);
+    Ok(())
+}
+
+pub fn vfs_set_owner(
+    path: &str,
+    uid: Option<u32>,
+    gid: Option<u32>,
+) -> Result<(), FilesystemError> {
+    let (filesystem, relative) = resolve_writable(path)?;
+    filesystem.set_owner(relative, uid, gid)?;
+    crate::userland::inotify::attrib(path);
+    Ok(())
+}
+
+/// Apply a new node's creation mode and owner. Unlike `vfs_set_mode` this
+/// raises no inotify event (the creation itself already did), and
+/// filesystems without permission metadata silently keep their defaults.
+pub fn vfs_init_attributes(path: &str, mode: u32, owner: Option<(u32, u32)>) {
+    let Ok((filesystem, relative)) = resolve_mount(path) else {
+        return;
+    };
+    let _ = filesystem.set_mode(relative, mode);
+    if let Some((uid, gid)) = owner {
+        let _ = filesystem.set_owner(relative, Some(uid), Some(gid));
     }
-    filesystem.set_times(relative, accessed, modified)
 }
 
 pub fn vfs_read_link(path: &str) -> Result<alloc::vec::Vec<u8>, FilesystemError> {
@@ -476,94 +949,108 @@ pub fn vfs_read_link(path: &str) -> Result<alloc::vec::Vec<u8>, FilesystemError>
 }
 
 pub fn vfs_symlink(target: &str, link_path: &str) -> Result<(), FilesystemError> {
-    let (filesystem, relative) = resolve_mount(link_path)?;
-    if filesystem.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
-    }
-    filesystem.symlink(target, relative)
+    let (filesystem, relative) = resolve_writable(link_path)?;
+    filesystem.symlink(target, relative)?;
+    crate::userland::inotify::created(link_path, false);
+    Ok(())
 }
 
 pub fn vfs_link(old_path: &str, new_path: &str) -> Result<(), FilesystemError> {
-    let (old_fs, old_relative) = resolve_mount(old_path)?;
+    let (old_fs, old_relative) = resolve_writable(old_path)?;
     let (new_fs, new_relative) = resolve_mount(new_path)?;
-    if (old_fs as *const dyn Filesystem as *const ())
-        != (new_fs as *const dyn Filesystem as *const ())
-    {
+    if !same_filesystem(&*old_fs, &*new_fs) {
         return Err(FilesystemError::UnsupportedOperation);
     }
-    if old_fs.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
-    }
-    old_fs.link(old_relative, new_relative)
+    old_fs.link(old_relative, new_relative)?;
+    crate::userland::inotify::attrib(old_path);
+    crate::userland::inotify::created(new_path, false);
+    Ok(())
 }
 
 pub fn vfs_mkdir(path: &str) -> Result<(), FilesystemError> {
-    let (fs, rel) = resolve_mount(path)?;
-    if fs.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
-    }
-    fs.mkdir(rel)
+    let (fs, rel) = resolve_writable(path)?;
+    fs.mkdir(rel)?;
+    crate::userland::inotify::created(path, true);
+    Ok(())
 }
 
 pub fn vfs_unlink(path: &str) -> Result<(), FilesystemError> {
-    let (fs, rel) = resolve_mount(path)?;
-    if fs.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
-    }
-    fs.unlink(rel)
+    let (fs, rel) = resolve_writable(path)?;
+    fs.unlink(rel)?;
+    crate::userland::inotify::deleted(path, false);
+    Ok(())
 }
 
 pub fn vfs_rmdir(path: &str) -> Result<(), FilesystemError> {
-    let (fs, rel) = resolve_mount(path)?;
-    if fs.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
-    }
-    fs.rmdir(rel)
+    let (fs, rel) = resolve_writable(path)?;
+    fs.rmdir(rel)?;
+    crate::userland::inotify::deleted(path, true);
+    Ok(())
 }
 
 /// Rename `old_path` to `new_path`. Both paths must resolve to the
 /// same mount; cross-mount renames return `ReadOnly` (mapped to EXDEV
 /// at the syscall boundary).
 pub fn vfs_rename(old_path: &str, new_path: &str) -> Result<(), FilesystemError> {
-    let (fs_old, rel_old) = resolve_mount(old_path)?;
+    let (fs_old, rel_old) = resolve_writable(old_path)?;
     let (fs_new, rel_new) = resolve_mount(new_path)?;
     // Use trait-object pointer identity to enforce same-mount.
-    if (fs_old as *const dyn Filesystem as *const ())
-        != (fs_new as *const dyn Filesystem as *const ())
-    {
+    if !same_filesystem(&*fs_old, &*fs_new) {
         return Err(FilesystemError::UnsupportedOperation);
     }
-    if fs_old.is_read_only() {
-        return Err(FilesystemError::ReadOnly);
-    }
-    fs_old.rename(rel_old, rel_new)
+    fs_old.rename(rel_old, rel_new)?;
+    crate::userland::inotify::moved(old_path, new_path);
+    Ok(())
 }
 
 pub fn vfs_sync_all() -> Result<(), FilesystemError> {
-    let mut filesystems: [Option<&'static dyn Filesystem>; 16] = [None; 16];
-    let count = {
-        let vfs = get_vfs();
-        let mut count = 0usize;
-        for mount in vfs.list_mounts() {
-            filesystems[count] = Some(mount.filesystem);
-            count += 1;
+    let filesystems: Vec<&'static dyn Filesystem> = {
+        let mut vfs = get_vfs();
+        let filesystems: Vec<&'static dyn Filesystem> =
+            vfs.list_mounts().map(|mount| mount.filesystem).collect();
+        for filesystem in &filesystems {
+            vfs.namespaces.retain(*filesystem);
         }
-        count
+        filesystems
     };
     let mut last = Ok(());
-    for filesystem in filesystems[..count].iter().flatten() {
+    for filesystem in filesystems {
         if let Err(e) = filesystem.sync() {
             last = Err(e);
         }
+        if let Some(instance) = VFS.lock().release(filesystem) {
+            destroy(instance);
+        }
     }
     last
 }
 
+/// What `statfs(2)` reports for one mount.
+pub struct MountStats {
+    pub fstype: &'static str,
+    pub read_only: bool,
+    pub stats: FilesystemStats,
+}
+
+/// Usage of the mount covering `path`. The driver is queried after the
+/// table lock is dropped: 9p answers with a round trip to the host.
+pub fn vfs_statfs(path: &str) -> Result<MountStats, FilesystemError> {
+    let (fstype, read_only) = {
+        let vfs = get_vfs();
+        let (mount, _) = vfs.find_mount(path).ok_or(FilesystemError::NotFound)?;
+        (mount.fstype, mount.is_read_only())
+    };
+    let (filesystem, _) = resolve_mount(path)?;
+    Ok(MountStats {
+        fstype,
+        read_only,
+        stats: filesystem.stats()?,
+    })
+}
+
 /// True iff `path` resolves to a writable mount. Caller still has to
 /// handle the case where the mount itself rejects a specific
 /// operation (e.g., `/bin` namespace shielding).
 pub fn vfs_is_writable(path: &str) -> bool {
-    resolve_mount(path)
-        .map(|(fs, _)| !fs.is_read_only())
-        .unwrap_or(false)
+    resolve_writable(path).is_ok()
 }
@@ -86,6 +86,8 @@ pub fn init(boot_info: &'static mut BootInfo) {
     crate::diagnostics::shadow_init();
     crate::process::timer::init();
     crate::process::timer::start_service();
+    crate::arch::x86_64::clockevent::init();
+    crate::userland::vdso::init();
     crate::arch::x86_64::smp::init();
     #[cfg(feature = "test")]
     crate::diagnostics::maybe_inject_crash();
@@ -137,12 +139,32 @@ pub fn init(boot_info: &'static mut BootInfo) {
 
     // POSIX temp-file directory. GCC's driver (and anything else relying
     // on mkstemp/choose_tmpdir defaults) writes scratch files here with
-    // no TMPDIR convention. Overlay-backed like /work.
+    // no TMPDIR convention. Overlay-backed like /work, world-writable
+    // and sticky so unprivileged users share it safely.
     match crate::fs::vfs::vfs_mkdir("/tmp") {
         Ok(()) => {}
         Err(crate::fs::filesystem::FilesystemError::AlreadyExists) => {}
         Err(e) => debug_info!("[boot] /tmp provisioning failed: {:?}", e),
     }
+    let _ = crate::fs::vfs::vfs_set_mode("/tmp", 0o1777);
+
+    // Search root of musl's dynamic linker (`/lib/ld-musl-x86_64.so.1`)
+    // and of the shared objects it loads. Overlay-backed so libraries can
+    // be installed at runtime.
+    match crate::fs::vfs::vfs_mkdir("/lib") {
+        Ok(()) => {}
+        Err(crate::fs::filesystem::FilesystemError::AlreadyExists) => {}
+        Err(e) => debug_info!("[boot] /lib provisioning failed: {:?}", e),
+    }
+
+    // POSIX shared memory (`shm_open`) and `memfd_create` segments. A
+    // separate RAM-only tmpfs, outside the persisted overlay.
+    match crate::fs::vfs::mount_dev_shm() {
+        Ok(()) => {
+            let _ = crate::fs::vfs::vfs_set_mode("/dev/shm", 0o1777);
+        }
+        Err(e) => debug_info!("[boot] /dev/shm mount failed: {:?}", e),
+    }
 
     debug_info!("[boot] managed /etc");
     crate::userland::etc::init();
@@ -552,7 +574,8 @@ fn try_mount_host_disk() {
                     i + 1,
                     fs_type
                 );
-                match auto_mount(part_device, "/host") {
+                let source = alloc::format!("{}{}", host_disk.dev_path(), i + 1);
+                match auto_mount(part_device, &source, "/host") {
                     Ok(_) => {
                         debug_info!("Host folder mounted at /host");
                         return;
@@ -638,7 +661,8 @@ fn try_mount_data_disk() {
             if force_dirty {
                 debug_warn!("Data disk: forced dirty writable mount override is active");
             }
-            match auto_mount_writable(data_disk, "/data", force_dirty) {
+            let source = data_disk.dev_path();
+            match auto_mount_writable(data_disk, &source, "/data", force_dirty) {
                 Ok(_) => {
                     debug_info!("Data disk mounted writable at /data");
                 }
@@ -646,7 +670,7 @@ fn try_mount_data_disk() {
                     debug_warn!(
                         "Data disk: dirty-bit gate refused writable mount; falling back to read-only"
                     );
-                    if let Err(e) = auto_mount(data_disk, "/data") {
+                    if let Err(e) = auto_mount(data_disk, &source, "/data") {
                         debug_warn!("Read-only fallback also failed: {:?}", e);
                     }
                 }
@@ -675,10 +699,12 @@ fn try_mount_shared() {
         return;
     };
     match crate::fs::p9::P9Filesystem::new(transport) {
-        Ok(filesystem) => match crate::fs::vfs::mount_p9(filesystem, "/shared") {
-            Ok(()) => debug_info!("Mounted 9p host share at /shared"),
-            Err(error) => debug_warn!("shared: mount failed: {:?}", error),
-        },
+        Ok(filesystem) => {
+            match crate::fs::vfs::mount_p9(filesystem, "agenticos-shared", "/shared", false) {
+                Ok(()) => debug_info!("Mounted 9p host share at /shared"),
+                Err(error) => debug_warn!("shared: mount failed: {:?}", error),
+            }
+        }
         Err(error) => debug_warn!("shared: 9p handshake failed: {:?}", error),
     }
 }
@@ -709,7 +735,7 @@ fn try_mount_legacy_data_disk() {
                 FilesystemType::Fat12 | FilesystemType::Fat16 | FilesystemType::Fat32
             ) =>
         {
-            match auto_mount(disk, "/legacy-data") {
+            match auto_mount(disk, &disk.dev_path(), "/legacy-data") {
                 Ok(_) => debug_info!(
                     "Mounted legacy FAT data image read-only at /legacy-data ({} MB)",
                     (sectors * 512) / (1024 * 1024)
@@ -733,11 +759,7 @@ fn restore_overlay_upper_from_data() {
 
     // Confirm /data is writable; otherwise restoring is moot since
     // future syncs won't be able to write either.
-    if !get_vfs()
-        .find_filesystem("/data")
-        .map(|(fs, _)| !fs.is_read_only())
-        .unwrap_or(false)
-    {
+    if !crate::fs::vfs::vfs_is_writable("/data") {
         debug_info!("overlay restore: /data not writable; skipping persistence restore");
         return;
     }
@@ -892,10 +914,7 @@ pub fn run() -> ! {
         // before committing to STI+HLT — otherwise a parked signal-pending
         // process could sleep until the next unrelated interrupt.
         let signal_woke = crate::userland::lifecycle::retry_dropped_signal_wakes();
-        let scheduler_ready = crate::process::scheduler::SCHEDULER
-            .try_lock()
-            .map(|scheduler| scheduler.ready_entity_count() != 0)
-            .unwrap_or(true);
+        let scheduler_ready = crate::process::scheduler::work_queued();
         if io_woke || signal_woke || scheduler_ready {
             x86_64::instructions::interrupts::enable();
             continue;
@@ -906,11 +925,7 @@ pub fn run() -> ! {
         // by the recheck before STI+HLT. The timer accounting path also uses
         // the flag to distinguish real idle time from kernel housekeeping.
         crate::arch::x86_64::percpu::set_idle_interruptible(true);
-        if crate::process::scheduler::SCHEDULER
-            .lock()
-            .ready_entity_count()
-            != 0
-        {
+        if crate::process::scheduler::work_queued() {
             crate::arch::x86_64::percpu::set_idle_interruptible(false);
             x86_64::instructions::interrupts::enable();
             continue;
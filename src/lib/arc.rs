@@ -119,7 +119,6 @@ impl<T> Arc<T> {
     }
 
     /// Creates a new `Weak` pointer to this allocation.
-    #[cfg_attr(not(feature = "test"), expect(dead_code, reason = "QEMU test API"))]
     pub fn downgrade(this: &Self) -> Weak<T> {
         this.inner().weak.fetch_add(1, Ordering::Relaxed);
         Weak {
@@ -217,7 +216,6 @@ impl<T> Weak<T> {
     }
 
     /// Attempts to upgrade the `Weak` pointer to an `Arc`.
-    #[cfg_attr(not(feature = "test"), expect(dead_code, reason = "QEMU test API"))]
     pub fn upgrade(&self) -> Option<Arc<T>> {
         if self.ptr.as_ptr() as *const u8 as usize == WEAK_SENTINEL {
             return None;
@@ -252,7 +250,6 @@ impl<T> Weak<T> {
 
 impl<T: ?Sized> Weak<T> {
     /// Gets the number of strong (`Arc`) pointers to this allocation.
-    #[cfg_attr(not(feature = "test"), expect(dead_code, reason = "QEMU test API"))]
     pub fn strong_count(&self) -> usize {
         if self.ptr.as_ptr() as *const u8 as usize == WEAK_SENTINEL {
             0
@@ -240,7 +240,11 @@ pub fn with_memory_mapper<R>(
     crate::arch::x86_64::percpu::mapper_enter();
     let result = {
         let mut mapper = MAPPER.lock();
-        mapper.as_mut().map(f)
+        mapper.as_mut().map(|mapper| {
+            let result = f(mapper);
+            mapper.flush_stale_translations();
+            result
+        })
     };
     crate::arch::x86_64::percpu::mapper_exit();
     result
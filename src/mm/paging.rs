@@ -17,6 +17,20 @@ use x86_64::structures::paging::page_table::PageTableLevel;
 /// Base virtual address where a static non-PIE user binary is loaded.
 pub const USER_LOAD_BASE: u64 = 0x0000_0000_0040_0000;
 
+/// Where the lowest PT_LOAD page of an `ET_DYN` (PIE) program lands. PML4
+/// slot 172, clear of the kernel-reserved slots and of the low mmap arena.
+pub const USER_PIE_LOAD_BASE: u64 = 0x0000_5600_0000_0000;
+
+/// Where the lowest PT_LOAD page of a `PT_INTERP` dynamic linker lands.
+/// PML4 slot 254, well below the deepest the user stack may grow.
+pub const USER_INTERP_BASE: u64 = 0x0000_7f00_0000_0000;
+
+/// The vDSO's read-only clock data page, mapped into every process. The
+/// vDSO image itself occupies the page above. PML4 slot 255, far below the
+/// stack reservation.
+pub const USER_VVAR_BASE: u64 = 0x0000_7ff0_0000_0000;
+pub const USER_VDSO_BASE: u64 = USER_VVAR_BASE + 0x1000;
+
 /// Exclusive ceiling of canonical lower-half user virtual memory.
 pub const USER_CANONICAL_END: u64 = 0x0000_8000_0000_0000;
 
@@ -194,6 +208,7 @@ pub unsafe fn activate_kernel_l4() {
     use x86_64::registers::control::{Cr3, Cr3Flags};
     let frame = kernel_l4_frame().expect("kernel L4 not captured at boot");
     Cr3::write(frame, Cr3Flags::empty());
+    crate::arch::x86_64::percpu::set_active_user_l4(0);
     crate::diagnostics::shadow::address_space::deactivate_cpu();
 }
 
@@ -230,6 +245,7 @@ pub struct MemoryMapper {
     mapper: OffsetPageTable<'static>,
     frame_allocator: BootInfoFrameAllocator,
     physical_memory_offset: VirtAddr,
+    stale_translations: crate::arch::x86_64::shootdown::StaleTranslations,
 }
 
 struct TypedFrameAllocator<'a> {
@@ -267,6 +283,7 @@ impl MemoryMapper {
             mapper,
             frame_allocator,
             physical_memory_offset,
+            stale_translations: Default::default(),
         }
     }
 
@@ -404,6 +421,30 @@ impl MemoryMapper {
         Cr3::read().0
     }
 
+    /// Drop `addr`'s translation in `l4_frame` from this CPU's TLB and note
+    /// that other CPUs running the address space must follow before the
+    /// mapper lock is released.
+    fn flush_user_page(&mut self, l4_frame: PhysFrame<Size4KiB>, addr: VirtAddr) {
+        if self.active_l4_frame() == l4_frame {
+            x86_64::instructions::tlb::flush(addr);
+        }
+        self.note_stale_translations(l4_frame);
+    }
+
+    /// Record that other CPUs may cache translations of `l4_frame` that no
+    /// longer match its page tables.
+    pub fn note_stale_translations(&mut self, l4_frame: PhysFrame<Size4KiB>) {
+        self.stale_translations
+            .note(l4_frame.start_address().as_u64());
+    }
+
+    /// Shoot down every translation noted since the last call. Runs before
+    /// the mapper lock is released, so no frame freed under it can be reused
+    /// while a remote TLB still reaches it.
+    pub fn flush_stale_translations(&mut self) {
+        self.stale_translations.flush();
+    }
+
     fn leaf_entry_ptr(
         &self,
         l4_frame: PhysFrame<Size4KiB>,
@@ -455,9 +496,7 @@ impl MemoryMapper {
             addr.as_u64() & !0xfff,
             flags.bits(),
         );
-        if self.active_l4_frame() == l4_frame {
-            x86_64::instructions::tlb::flush(addr);
-        }
+        self.flush_user_page(l4_frame, addr);
         Ok(())
     }
 
@@ -474,12 +513,50 @@ impl MemoryMapper {
             let _ = self.set_leaf_flags(l4_frame, addr, flags);
             return CowOutcome::Upgraded;
         }
-        let Some(new_frame) = self
+        match self.replace_leaf_with_copy(l4_frame, addr, old_frame, flags) {
+            Some(_) => CowOutcome::Copied,
+            None => CowOutcome::OutOfFrames,
+        }
+    }
+
+    /// Frame backing the present leaf at `addr`, first giving the leaf a
+    /// private copy if any other mapping shares its frame. Permissions are
+    /// unchanged except that a COW leaf is resolved as for a write fault.
+    /// `ptrace` writes through the returned frame to poke read-only text.
+    /// Callers must not use this on `MAP_SHARED` mappings, whose frames are
+    /// shared by design.
+    pub fn unshare_leaf(
+        &mut self,
+        l4_frame: PhysFrame<Size4KiB>,
+        addr: VirtAddr,
+    ) -> Option<PhysFrame<Size4KiB>> {
+        let (frame, flags) = self.leaf_info(l4_frame, addr)?;
+        if flags.contains(PageTableFlags::BIT_9) {
+            return match self.resolve_cow(l4_frame, addr) {
+                CowOutcome::Upgraded | CowOutcome::Copied => {
+                    self.leaf_info(l4_frame, addr).map(|(frame, _)| frame)
+                }
+                CowOutcome::NotCow | CowOutcome::OutOfFrames => None,
+            };
+        }
+        if self.frame_allocator.refcount(frame) == Some(1) {
+            return Some(frame);
+        }
+        self.replace_leaf_with_copy(l4_frame, addr, frame, flags)
+    }
+
+    /// Point the leaf at `addr` at a fresh copy of `old_frame` with
+    /// `flags`, dropping the leaf's reference to `old_frame`.
+    fn replace_leaf_with_copy(
+        &mut self,
+        l4_frame: PhysFrame<Size4KiB>,
+        addr: VirtAddr,
+        old_frame: PhysFrame<Size4KiB>,
+        flags: PageTableFlags,
+    ) -> Option<PhysFrame<Size4KiB>> {
+        let new_frame = self
             .frame_allocator
-            .allocate_frame_reason(FrameRefReason::LeafMapping, 0x1101)
-        else {
-            return CowOutcome::OutOfFrames;
-        };
+            .allocate_frame_reason(FrameRefReason::LeafMapping, 0x1101)?;
         unsafe {
             let source = (self.physical_memory_offset.as_u64() + old_frame.start_address().as_u64())
                 as *const u8;
@@ -504,10 +581,8 @@ impl MemoryMapper {
             FrameRefReason::LeafMapping,
             0x1102,
         );
-        if self.active_l4_frame() == l4_frame {
-            x86_64::instructions::tlb::flush(addr);
-        }
-        CowOutcome::Copied
+        self.flush_user_page(l4_frame, addr);
+        Some(new_frame)
     }
 
     /// Allocate and zero a user-leaf frame without exposing it in any page
@@ -529,6 +604,50 @@ impl MemoryMapper {
         addr: VirtAddr,
         frame: PhysFrame<Size4KiB>,
         flags: PageTableFlags,
+    ) -> Result<(), UserMapError> {
+        self.install_leaf(l4_frame, addr, frame, flags, true)
+    }
+
+    /// Map one page owned by a shared-memory object. The leaf takes its own
+    /// frame reference, so the object and every mapping may drop in any
+    /// order. Shared leaves are never marked COW: all mappers write the same
+    /// frame, so `flags` should carry `WRITABLE` directly.
+    pub fn map_shared_frame_into(
+        &mut self,
+        l4_frame: PhysFrame<Size4KiB>,
+        addr: VirtAddr,
+        frame: PhysFrame<Size4KiB>,
+        flags: PageTableFlags,
+    ) -> Result<(), UserMapError> {
+        self.frame_allocator
+            .retain_frame_reason(frame, FrameRefReason::LeafMapping, 0x110d)
+            .map_err(|_| UserMapError::OutOfFrames)?;
+        let result = self.install_leaf(l4_frame, addr, frame, flags, false);
+        if result.is_err() {
+            let _ = self.frame_allocator.release_frame_reason(
+                frame,
+                FrameRefReason::LeafMapping,
+                0x110e,
+            );
+        }
+        result
+    }
+
+    /// Drop a shared-memory object's own reference to one of its pages.
+    pub fn release_shared_frame(&mut self, frame: PhysFrame<Size4KiB>) {
+        let released =
+            self.frame_allocator
+                .release_frame_reason(frame, FrameRefReason::Transient, 0x110f);
+        debug_assert!(released.is_ok(), "shared page must be allocator-owned");
+    }
+
+    fn install_leaf(
+        &mut self,
+        l4_frame: PhysFrame<Size4KiB>,
+        addr: VirtAddr,
+        frame: PhysFrame<Size4KiB>,
+        flags: PageTableFlags,
+        adopt_private: bool,
     ) -> Result<(), UserMapError> {
         let page_addr = VirtAddr::new(addr.as_u64() & !0xfff);
         let page = Page::<Size4KiB>::containing_address(page_addr);
@@ -552,12 +671,14 @@ impl MemoryMapper {
                     flush.ignore();
                 }
                 if let Some((frame_index, _)) = self.frame_allocator.shadow_identity(frame) {
-                    crate::diagnostics::shadow::memory::transfer(
-                        frame_index,
-                        FrameRefReason::Transient,
-                        FrameRefReason::LeafMapping,
-                        0x1105,
-                    );
+                    if adopt_private {
+                        crate::diagnostics::shadow::memory::transfer(
+                            frame_index,
+                            FrameRefReason::Transient,
+                            FrameRefReason::LeafMapping,
+                            0x1105,
+                        );
+                    }
                     crate::diagnostics::shadow::memory::map_leaf(
                         self.address_space_generation(l4_frame),
                         page_addr.as_u64(),
@@ -631,9 +752,7 @@ impl MemoryMapper {
         );
         debug_assert!(released.is_ok(), "user leaf must be allocator-owned");
         self.prune_empty_path(l4_frame, addr);
-        if self.active_l4_frame() == l4_frame {
-            x86_64::instructions::tlb::flush(addr);
-        }
+        self.flush_user_page(l4_frame, addr);
         Ok(leaf_frame)
     }
 
@@ -704,9 +823,7 @@ impl MemoryMapper {
             destination.as_u64(),
         );
         self.prune_empty_path(l4_frame, source);
-        if self.active_l4_frame() == l4_frame {
-            x86_64::instructions::tlb::flush(source);
-        }
+        self.flush_user_page(l4_frame, source);
         Ok(true)
     }
 
@@ -6,4 +6,7 @@ pub struct NetworkConfig {
     pub router: Option<[u8; 4]>,
     pub dns_servers: [[u8; 4]; 3],
     pub dns_server_count: u8,
+    /// DHCP option 42 servers, in offer order.
+    pub ntp_servers: [[u8; 4]; 3],
+    pub ntp_server_count: u8,
 }
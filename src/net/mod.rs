@@ -7,12 +7,15 @@
 pub mod abi;
 mod config;
 mod resolver_config;
+mod sntp;
 pub mod socket;
 mod stack;
 
 pub use config::NetworkConfig;
 #[cfg(feature = "test")]
 pub use resolver_config::resolver_tests;
+#[cfg(feature = "test")]
+pub use sntp::sntp_tests;
 
 use alloc::string::String;
 use lazy_static::lazy_static;
@@ -34,6 +37,7 @@ pub fn init() {
         *NETWORK.lock() = Some(stack);
     }
     crate::process::spawn_process(String::from("net-rx-tx"), None, network_worker);
+    sntp::start();
 }
 
 fn network_worker() {
@@ -66,7 +70,6 @@ pub fn is_available() -> bool {
     with_stack_mut(|_| ()).is_some()
 }
 
-#[cfg(feature = "test")]
 pub fn config() -> NetworkConfig {
     with_stack_mut(|stack| stack.config()).unwrap_or_default()
 }
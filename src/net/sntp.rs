@@ -0,0 +1,342 @@
+//! SNTP client (RFC 4330) that disciplines the wall clock.
+//!
+//! A kernel process asks the server named by the `ntp_server` setting, or
+//! the first one DHCP offered, for the time once the network is configured
+//! and then every [`POLL_INTERVAL_TICKS`]. An offset beyond
+//! [`STEP_THRESHOLD_NS`] steps realtime; anything smaller is slewed. Each
+//! successful exchange writes the RTC back.
+//!
+//! The packet exchange is a parameter of [`query`], which keeps the
+//! timestamp arithmetic testable against a stand-in server.
+
+use alloc::string::String;
+
+use crate::net::abi::SockAddrV4;
+use crate::net::socket::{self, SocketError, SocketType};
+use crate::net::NetworkConfig;
+use crate::system_control::NtpServer;
+
+const NTP_PORT: u16 = 123;
+pub(crate) const PACKET_BYTES: usize = 48;
+/// Seconds from the NTP era 0 epoch (1900) to the Unix epoch.
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
+const VERSION: u8 = 4;
+const MODE_CLIENT: u8 = 3;
+const MODE_SERVER: u8 = 4;
+/// Leap indicator of a server whose own clock is unsynchronized.
+const LEAP_ALARM: u8 = 3;
+const ORIGINATE_OFFSET: usize = 24;
+const RECEIVE_OFFSET: usize = 32;
+const TRANSMIT_OFFSET: usize = 40;
+/// Offsets larger than this step the clock, as ntpd's 128 ms.
+const STEP_THRESHOLD_NS: i64 = 128_000_000;
+const POLL_INTERVAL_TICKS: u64 = 1024 * 100;
+const RETRY_INTERVAL_TICKS: u64 = 64 * 100;
+const REPLY_TIMEOUT_TICKS: u64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SntpError {
+    Socket(SocketError),
+    Timeout,
+    BadReply,
+}
+
+/// One exchange's result, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Sample {
+    /// What to add to realtime to match the server.
+    pub offset_ns: i64,
+    /// Round trip, less the server's processing time.
+    pub delay_ns: i64,
+}
+
+pub fn start() {
+    crate::process::spawn_process(String::from("sntp"), None, worker);
+}
+
+fn worker() {
+    loop {
+        let config = crate::net::config();
+        let delay = match server(crate::system_control::ntp_server(), config) {
+            Some(address) if config.configured => match query(|request| exchange(address, request))
+            {
+                Ok(sample) => {
+                    apply(sample);
+                    POLL_INTERVAL_TICKS
+                }
+                Err(error) => {
+                    crate::debug_warn!(
+                        "sntp: {}.{}.{}.{}: {:?}",
+                        address[0],
+                        address[1],
+                        address[2],
+                        address[3],
+                        error
+                    );
+                    RETRY_INTERVAL_TICKS
+                }
+            },
+            _ => RETRY_INTERVAL_TICKS,
+        };
+        crate::process::sleep_ticks(delay);
+    }
+}
+
+/// The server the setting selects, given the current DHCP lease.
+pub(crate) fn server(setting: NtpServer, config: NetworkConfig) -> Option<[u8; 4]> {
+    match setting {
+        NtpServer::Disabled => None,
+        NtpServer::Address(address) => Some(address),
+        NtpServer::Dhcp => (config.ntp_server_count > 0).then_some(config.ntp_servers[0]),
+    }
+}
+
+/// Run one client exchange through `exchange` and measure the result
+/// against realtime.
+pub(crate) fn query<F>(exchange: F) -> Result<Sample, SntpError>
+where
+    F: FnOnce(&[u8; PACKET_BYTES]) -> Result<[u8; PACKET_BYTES], SntpError>,
+{
+    let mut request = [0u8; PACKET_BYTES];
+    request[0] = (VERSION << 3) | MODE_CLIENT;
+    let sent = crate::time::realtime_ns();
+    request[TRANSMIT_OFFSET..].copy_from_slice(&to_ntp(sent).to_be_bytes());
+    let reply = exchange(&request)?;
+    let received = crate::time::realtime_ns();
+    sample(&request, &reply, sent, received)
+}
+
+/// Validate `reply` against `request` and compute the offset and delay from
+/// the four RFC 4330 timestamps.
+pub(crate) fn sample(
+    request: &[u8; PACKET_BYTES],
+    reply: &[u8; PACKET_BYTES],
+    sent_ns: u64,
+    received_ns: u64,
+) -> Result<Sample, SntpError> {
+    let leap = reply[0] >> 6;
+    let version = (reply[0] >> 3) & 7;
+    let mode = reply[0] & 7;
+    let stratum = reply[1];
+    // Stratum 0 is a kiss-of-death; the originate timestamp must echo ours.
+    if leap == LEAP_ALARM
+        || !(1..=VERSION).contains(&version)
+        || mode != MODE_SERVER
+        || !(1..=15).contains(&stratum)
+        || reply[ORIGINATE_OFFSET..RECEIVE_OFFSET] != request[TRANSMIT_OFFSET..]
+    {
+        return Err(SntpError::BadReply);
+    }
+    let server_received = timestamp(reply, RECEIVE_OFFSET);
+    let server_sent = timestamp(reply, TRANSMIT_OFFSET);
+    if server_received == 0 || server_sent == 0 {
+        return Err(SntpError::BadReply);
+    }
+    let (Some(server_received), Some(server_sent)) =
+        (from_ntp(server_received), from_ntp(server_sent))
+    else {
+        return Err(SntpError::BadReply);
+    };
+    let (t1, t2, t3, t4) = (
+        i128::from(sent_ns),
+        i128::from(server_received),
+        i128::from(server_sent),
+        i128::from(received_ns),
+    );
+    let offset = ((t2 - t1) + (t3 - t4)) / 2;
+    let delay = (t4 - t1) - (t3 - t2);
+    Ok(Sample {
+        offset_ns: i64::try_from(offset).map_err(|_| SntpError::BadReply)?,
+        delay_ns: i64::try_from(delay).map_err(|_| SntpError::BadReply)?,
+    })
+}
+
+fn apply(sample: Sample) {
+    if sample.offset_ns.abs() > STEP_THRESHOLD_NS {
+        crate::time::step_realtime_ns(sample.offset_ns);
+        crate::debug_info!("sntp: stepped {} ns", sample.offset_ns);
+    } else {
+        crate::time::set_slew_ns(sample.offset_ns);
+    }
+    crate::time::mark_synchronized();
+    crate::time::sync_rtc();
+}
+
+/// Send `request` to `address` over UDP and wait for the server's answer.
+fn exchange(
+    address: [u8; 4],
+    request: &[u8; PACKET_BYTES],
+) -> Result<[u8; PACKET_BYTES], SntpError> {
+    let remote = SockAddrV4 {
+        address,
+        port: NTP_PORT,
+    };
+    let handle = socket::create(SocketType::Datagram, true).map_err(SntpError::Socket)?;
+    socket::connect(handle.id(), remote).map_err(SntpError::Socket)?;
+    socket::send(handle.id(), request, None).map_err(SntpError::Socket)?;
+    crate::net::poll_once();
+    // Room for extension fields, which SNTP ignores.
+    let mut buffer = [0u8; 128];
+    for _ in 0..REPLY_TIMEOUT_TICKS {
+        match socket::recv(handle.id(), &mut buffer) {
+            Ok((len, Some(source))) if source == remote && len >= PACKET_BYTES => {
+                let mut reply = [0u8; PACKET_BYTES];
+                reply.copy_from_slice(&buffer[..PACKET_BYTES]);
+                return Ok(reply);
+            }
+            Ok(_) | Err(SocketError::MessageTooLarge) => continue,
+            Err(SocketError::WouldBlock) => {
+                crate::process::sleep_ticks(1);
+                crate::net::poll_once();
+            }
+            Err(error) => return Err(SntpError::Socket(error)),
+        }
+    }
+    Err(SntpError::Timeout)
+}
+
+fn timestamp(packet: &[u8; PACKET_BYTES], offset: usize) -> u64 {
+    u64::from_be_bytes(packet[offset..offset + 8].try_into().unwrap())
+}
+
+/// Unix nanoseconds as a 32.32 NTP timestamp, wrapping into era 1 in 2036.
+pub(crate) fn to_ntp(unix_ns: u64) -> u64 {
+    let seconds = (unix_ns / NANOSECONDS_PER_SECOND + NTP_UNIX_OFFSET) as u32;
+    let fraction = ((unix_ns % NANOSECONDS_PER_SECOND) << 32) / NANOSECONDS_PER_SECOND;
+    (u64::from(seconds) << 32) | fraction
+}
+
+/// The Unix nanoseconds of an NTP timestamp. Seconds with the top bit
+/// clear are taken to be in era 1, per RFC 4330's 1968–2104 window; `None`
+/// for the part of that window before the Unix epoch.
+pub(crate) fn from_ntp(timestamp: u64) -> Option<u64> {
+    let mut seconds = timestamp >> 32;
+    if seconds & 0x8000_0000 == 0 {
+        seconds += 1 << 32;
+    }
+    let fraction = ((timestamp & 0xffff_ffff) * NANOSECONDS_PER_SECOND) >> 32;
+    seconds
+        .checked_sub(NTP_UNIX_OFFSET)?
+        .checked_mul(NANOSECONDS_PER_SECOND)?
+        .checked_add(fraction)
+}
+
+#[cfg(feature = "test")]
+mod tests {
+    use super::*;
+
+    const SERVER_AHEAD_NS: u64 = 5 * NANOSECONDS_PER_SECOND;
+
+    /// Stand-in server whose clock runs [`SERVER_AHEAD_NS`] ahead of ours
+    /// and answers instantly.
+    fn stand_in(request: &[u8; PACKET_BYTES]) -> Result<[u8; PACKET_BYTES], SntpError> {
+        let mut reply = [0u8; PACKET_BYTES];
+        reply[0] = (VERSION << 3) | MODE_SERVER;
+        reply[1] = 2;
+        reply[ORIGINATE_OFFSET..RECEIVE_OFFSET].copy_from_slice(&request[TRANSMIT_OFFSET..]);
+        let now = to_ntp(crate::time::realtime_ns() + SERVER_AHEAD_NS).to_be_bytes();
+        reply[RECEIVE_OFFSET..TRANSMIT_OFFSET].copy_from_slice(&now);
+        reply[TRANSMIT_OFFSET..].copy_from_slice(&now);
+        Ok(reply)
+    }
+
+    fn test_timestamps_round_trip_across_the_era() {
+        for unix_ns in [
+            1_700_000_000_123_456_789,
+            // 2036-02-07T06:28:16Z, where era 0 ends.
+            2_085_978_496 * NANOSECONDS_PER_SECOND,
+            2_100_000_000 * NANOSECONDS_PER_SECOND + 500_000_000,
+        ] {
+            let back = from_ntp(to_ntp(unix_ns)).unwrap();
+            assert!(
+                back <= unix_ns && unix_ns - back <= 1,
+                "{unix_ns} -> {back}"
+            );
+        }
+        assert_eq!(to_ntp(0) >> 32, NTP_UNIX_OFFSET);
+    }
+
+    fn test_stand_in_server_offset_and_delay() {
+        let sample = query(stand_in).unwrap();
+        let error = sample.offset_ns - SERVER_AHEAD_NS as i64;
+        assert!(error.abs() < 1_000_000, "offset {}", sample.offset_ns);
+        assert!((0..10_000_000).contains(&sample.delay_ns));
+    }
+
+    fn test_offset_arithmetic_cancels_symmetric_delay() {
+        let mut request = [0u8; PACKET_BYTES];
+        request[TRANSMIT_OFFSET..].copy_from_slice(&to_ntp(1_000).to_be_bytes());
+        let mut reply = stand_in(&request).unwrap();
+        // 10 ms out, 1 ms at the server, 10 ms back; server 2 s behind.
+        let sent = 1_700_000_000 * NANOSECONDS_PER_SECOND;
+        let server_received = sent + 10_000_000 - 2 * NANOSECONDS_PER_SECOND;
+        reply[RECEIVE_OFFSET..TRANSMIT_OFFSET]
+            .copy_from_slice(&to_ntp(server_received).to_be_bytes());
+        reply[TRANSMIT_OFFSET..]
+            .copy_from_slice(&to_ntp(server_received + 1_000_000).to_be_bytes());
+        let sample = sample(&request, &reply, sent, sent + 21_000_000).unwrap();
+        assert!((sample.offset_ns + 2_000_000_000).abs() <= 1);
+        assert!((sample.delay_ns - 20_000_000).abs() <= 1);
+    }
+
+    fn test_rejects_unusable_replies() {
+        let mut request = [0u8; PACKET_BYTES];
+        request[TRANSMIT_OFFSET..].copy_from_slice(&to_ntp(1_000).to_be_bytes());
+        let good = stand_in(&request).unwrap();
+        assert!(sample(&request, &good, 0, 0).is_ok());
+        let mut kiss = good;
+        kiss[1] = 0;
+        let mut alarm = good;
+        alarm[0] |= LEAP_ALARM << 6;
+        let mut client = good;
+        client[0] = (VERSION << 3) | MODE_CLIENT;
+        let mut stale = good;
+        stale[ORIGINATE_OFFSET] ^= 1;
+        for reply in [kiss, alarm, client, stale] {
+            assert_eq!(sample(&request, &reply, 0, 0), Err(SntpError::BadReply));
+        }
+        assert_eq!(query(|_| Err(SntpError::Timeout)), Err(SntpError::Timeout));
+    }
+
+    fn test_rejects_timestamps_before_the_unix_epoch() {
+        // 1968-01-20, inside RFC 4330's window but before 1970.
+        let before_epoch = 0x8000_0000u64 << 32;
+        assert_eq!(from_ntp(before_epoch), None);
+        let mut request = [0u8; PACKET_BYTES];
+        request[TRANSMIT_OFFSET..].copy_from_slice(&to_ntp(1_000).to_be_bytes());
+        let mut reply = stand_in(&request).unwrap();
+        reply[RECEIVE_OFFSET..TRANSMIT_OFFSET].copy_from_slice(&before_epoch.to_be_bytes());
+        assert_eq!(sample(&request, &reply, 0, 0), Err(SntpError::BadReply));
+    }
+
+    fn test_server_follows_setting_then_dhcp() {
+        let mut config = NetworkConfig {
+            configured: true,
+            ..NetworkConfig::default()
+        };
+        assert_eq!(server(NtpServer::Dhcp, config), None);
+        config.ntp_servers[0] = [10, 0, 2, 3];
+        config.ntp_server_count = 1;
+        assert_eq!(server(NtpServer::Dhcp, config), Some([10, 0, 2, 3]));
+        assert_eq!(
+            server(NtpServer::Address([192, 0, 2, 1]), config),
+            Some([192, 0, 2, 1])
+        );
+        assert_eq!(server(NtpServer::Disabled, config), None);
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_timestamps_round_trip_across_the_era,
+            &test_stand_in_server_offset_and_delay,
+            &test_offset_arithmetic_cancels_symmetric_delay,
+            &test_rejects_unusable_replies,
+            &test_rejects_timestamps_before_the_unix_epoch,
+            &test_server_follows_setting_then_dhcp,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests::get_tests as sntp_tests;
@@ -11,6 +11,13 @@ use crate::net::socket::SocketRegistry;
 use crate::net::NetworkConfig;
 use crate::{debug_info, debug_warn};
 
+/// Subnet mask, router, DNS servers and NTP servers.
+const DHCP_PARAMETER_REQUEST_LIST: &[u8] = &[1, 3, 6, OPT_NTP_SERVERS];
+const OPT_NTP_SERVERS: u8 = 42;
+/// Room for the acknowledgement the socket keeps so options it does not
+/// parse itself can be read.
+const DHCP_PACKET_BYTES: usize = 1500;
+
 pub(super) struct NetworkStack {
     pub(super) device: VirtioNet,
     pub(super) interface: Interface,
@@ -40,7 +47,11 @@ impl NetworkStack {
         let now = now();
         let interface = Interface::new(iface_config, &mut device, now);
         let mut sockets = SocketSet::new(Vec::new());
-        let dhcp = sockets.add(dhcpv4::Socket::new());
+        let mut dhcp = dhcpv4::Socket::new();
+        dhcp.set_parameter_request_list(DHCP_PARAMETER_REQUEST_LIST);
+        // One stack per boot, so the buffer is leaked for the `'static` set.
+        dhcp.set_receive_packet_buffer(Vec::leak(alloc::vec![0; DHCP_PACKET_BYTES]));
+        let dhcp = sockets.add(dhcp);
         Some(Self {
             device,
             interface,
@@ -90,6 +101,16 @@ impl NetworkStack {
                     snapshot.dns_servers[index] = address.octets();
                     snapshot.dns_server_count += 1;
                 }
+                let ntp_servers = config
+                    .packet
+                    .iter()
+                    .flat_map(|packet| packet.options())
+                    .filter(|option| option.kind == OPT_NTP_SERVERS)
+                    .flat_map(|option| option.data.chunks_exact(4));
+                for (index, address) in ntp_servers.take(3).enumerate() {
+                    snapshot.ntp_servers[index].copy_from_slice(address);
+                    snapshot.ntp_server_count += 1;
+                }
                 if snapshot != self.config {
                     debug_info!(
                         "DHCP configured {}.{}.{}.{}/{} router={:?}",
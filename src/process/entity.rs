@@ -31,3 +31,53 @@ impl LatencyContract {
         Self { max_dispatch_ticks }
     }
 }
+
+/// Range of `setpriority(2)` nice values; lower runs more.
+pub const NICE_MIN: i8 = -20;
+pub const NICE_MAX: i8 = 19;
+
+/// Load weight of a nice-0 entity.
+pub const NICE_0_WEIGHT: u32 = 1024;
+
+/// Linux's `sched_prio_to_weight`: each nice step is worth about 10% CPU
+/// against an entity one step away.
+const NICE_TO_WEIGHT: [u32; 40] = [
+    88761, 71755, 56483, 46273, 36291, 29154, 23254, 18705, 14949, 11916, 9548, 7620, 6100, 4904,
+    3906, 3121, 2501, 1991, 1586, 1277, 1024, 820, 655, 526, 423, 335, 272, 215, 172, 137, 110, 87,
+    70, 56, 45, 36, 29, 23, 18, 15,
+];
+
+/// Load weight for `nice`, clamped to [`NICE_MIN`]..=[`NICE_MAX`].
+pub const fn nice_to_weight(nice: i8) -> u32 {
+    let nice = if nice < NICE_MIN {
+        NICE_MIN
+    } else if nice > NICE_MAX {
+        NICE_MAX
+    } else {
+        nice
+    };
+    NICE_TO_WEIGHT[(nice - NICE_MIN) as usize]
+}
+
+/// Scheduling class. Real-time entities run ahead of every normal one,
+/// highest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedPolicy {
+    /// Weighted fair sharing by nice value.
+    Normal,
+    /// Runs until it blocks, yields or a higher priority becomes ready.
+    Fifo,
+    /// As [`SchedPolicy::Fifo`], but rotates among equal priorities every
+    /// time slice.
+    RoundRobin,
+}
+
+impl SchedPolicy {
+    pub const fn is_realtime(self) -> bool {
+        !matches!(self, Self::Normal)
+    }
+}
+
+/// Range of real-time priorities; higher runs first.
+pub const RT_PRIORITY_MIN: u8 = 1;
+pub const RT_PRIORITY_MAX: u8 = 99;
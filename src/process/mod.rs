@@ -684,7 +684,7 @@ pub fn sleep_ticks_with_contract(
         return;
     };
 
-    crate::process::timer::arm(
+    crate::process::timer::arm_at_tick(
         crate::process::timer::TimerKey {
             entity: crate::process::entity::EntityId::KernelThread(current_pid),
             kind: crate::process::timer::TimerKind::KernelSleep,
@@ -1,4 +1,4 @@
-//! The scheduler's single privilege-neutral ready queue.
+//! One CPU's privilege-neutral ready queue.
 
 use alloc::collections::VecDeque;
 
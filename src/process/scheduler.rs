@@ -1,16 +1,42 @@
 //! Privilege-neutral fair scheduler.
 //!
-//! Kernel threads and ring-3 processes are tagged entities in one queue. Each
-//! running entity gets one PIT tick before it is requeued at the tail; overdue
-//! one-shot latency contracts may override FIFO order.
-
-use crate::arch::x86_64::interrupt_guard::InterruptMutex;
+//! Kernel threads and ring-3 processes are tagged entities sharing a set of
+//! per-CPU ready queues. An entity waits on the queue of the CPU it is bound
+//! to, else the one it last ran on, so it tends to come back to a warm cache.
+//! A CPU with nothing eligible of its own steals from the busiest queue, and
+//! every [`BALANCE_INTERVAL_TICKS`] each CPU pulls entities from the busiest
+//! queue until the two differ by at most one. Each running entity gets one
+//! PIT tick before it is requeued. Selection is
+//! CFS-like: every tick charges the running entity virtual runtime inversely
+//! proportional to its nice weight, and the ready entity with the least
+//! virtual runtime runs next, queue order breaking ties. Overdue one-shot
+//! latency contracts may override that order.
+//!
+//! Real-time entities (`SCHED_FIFO`, `SCHED_RR`) run ahead of all of that,
+//! highest priority first across every queue, and keep the CPU across ticks until they block,
+//! yield, meet a higher priority or, for round-robin, use up their slice
+//! with an equal-priority peer waiting. A newly ready real-time entity
+//! preempts a normal one at the next tick. A per-CPU throttle lets them
+//! use at most [`RT_RUNTIME_TICKS`] of every [`RT_PERIOD_TICKS`]; past
+//! that they compete as normal entities until the period ends.
+//!
+//! Each queue in [`RUN_QUEUES`] has its own lock. Idle CPUs polling for
+//! work and `/proc/stat` read queue depths through those locks alone, never
+//! touching [`SCHEDULER`]. A queue changes only together with the entity
+//! states it mirrors, so changes still happen inside a [`SCHEDULER`]
+//! transaction, which takes queue locks after its own; stealing and
+//! balancing hold two queue locks at once, the lower-numbered CPU's first.
+
+use crate::arch::x86_64::acpi::MAX_CPUS;
+use crate::arch::x86_64::interrupt_guard::{InterruptMutex, InterruptMutexGuard};
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
 
 use super::context::CpuContext;
-use super::entity::{EntityId, LatencyContract, RunState};
+use super::entity::{
+    nice_to_weight, EntityId, LatencyContract, RunState, SchedPolicy, NICE_0_WEIGHT,
+};
 use super::pcb::{BlockReason, ProcessControlBlock, ProcessState, WakeEvents};
 use super::process::ProcessId;
 use super::run_queue::RunQueue;
@@ -21,6 +47,27 @@ use super::stack::free_stack;
 /// This provides smooth multitasking where processes appear to run simultaneously
 pub const DEFAULT_TIME_SLICE: u64 = 1;
 
+/// Virtual runtime a nice-0 entity is charged per tick. Scaled so the
+/// lightest weight still advances by a whole number of units.
+const TICK_VRUNTIME: u64 = 1 << 20;
+
+/// How far behind the queue's minimum virtual runtime a waking sleeper may
+/// be placed, so interactive tasks run promptly without banking credit.
+const SLEEPER_CREDIT: u64 = TICK_VRUNTIME;
+
+/// `SCHED_RR` time slice in ticks (100 ms).
+pub const RR_TIMESLICE_TICKS: u64 = 10;
+
+/// Length of a real-time throttle period in ticks (1 s).
+pub const RT_PERIOD_TICKS: u64 = 100;
+
+/// Ticks per period real-time entities may use on one CPU, as Linux's
+/// default `sched_rt_runtime_us` of 95%.
+pub const RT_RUNTIME_TICKS: u64 = 95;
+
+/// Ticks between a CPU's load-balancing passes.
+const BALANCE_INTERVAL_TICKS: u64 = 4;
+
 /// Lightweight process info for display purposes (e.g., task manager)
 #[derive(Debug, Clone)]
 pub struct ProcessInfo {
@@ -52,13 +99,28 @@ pub struct SchedEntity {
     pub latency_contract: Option<LatencyContract>,
     /// Full architecture context is safe for another CPU to restore.
     pub context_published: bool,
-    /// Optional logical CPU constraint. User tasks that share an address
-    /// space are pinned together until remote user-TLB shootdown exists.
+    /// Optional logical CPU constraint. Threads sharing an address space
+    /// run anywhere; remote TLB shootdown keeps their translations coherent.
     pub cpu_affinity: Option<usize>,
+    /// CPU this entity last ran on; its queue is where the entity waits.
+    pub last_cpu: Option<usize>,
+    pub nice: i8,
+    /// Load weight derived from `nice`.
+    pub weight: u32,
+    /// Weighted runtime; the fair queue runs the smallest first.
+    pub vruntime: u64,
+    pub policy: SchedPolicy,
+    /// Real-time priority; 0 for [`SchedPolicy::Normal`].
+    pub rt_priority: u8,
+    /// `SCHED_RESET_ON_FORK`: children start normal with a nice of at
+    /// least 0.
+    pub reset_on_fork: bool,
+    /// Ticks left in the current round-robin slice.
+    rr_ticks_left: u64,
 }
 
 impl SchedEntity {
-    const fn new(_id: EntityId) -> Self {
+    const fn new(_id: EntityId, vruntime: u64) -> Self {
         Self {
             state: RunState::Blocked,
             runtime_ticks: 0,
@@ -67,26 +129,117 @@ impl SchedEntity {
             latency_contract: None,
             context_published: true,
             cpu_affinity: None,
+            last_cpu: None,
+            nice: 0,
+            weight: NICE_0_WEIGHT,
+            vruntime,
+            policy: SchedPolicy::Normal,
+            rt_priority: 0,
+            reset_on_fork: false,
+            rr_ticks_left: RR_TIMESLICE_TICKS,
+        }
+    }
+
+    /// Account one tick of execution.
+    fn charge_tick(&mut self) {
+        self.runtime_ticks = self.runtime_ticks.saturating_add(1);
+        self.vruntime = self
+            .vruntime
+            .saturating_add(TICK_VRUNTIME * u64::from(NICE_0_WEIGHT) / u64::from(self.weight));
+        if self.policy == SchedPolicy::RoundRobin {
+            self.rr_ticks_left = self.rr_ticks_left.saturating_sub(1);
         }
     }
 }
 
+/// Real-time ticks used on one CPU in the current throttle period.
+#[derive(Clone, Copy)]
+struct RtBandwidth {
+    period_start: u64,
+    used: u64,
+}
+
+impl RtBandwidth {
+    const fn new() -> Self {
+        Self {
+            period_start: 0,
+            used: 0,
+        }
+    }
+
+    fn throttled(&self, now: u64) -> bool {
+        now < self.period_start.saturating_add(RT_PERIOD_TICKS) && self.used >= RT_RUNTIME_TICKS
+    }
+
+    fn charge(&mut self, now: u64) {
+        if now >= self.period_start.saturating_add(RT_PERIOD_TICKS) {
+            self.period_start = now;
+            self.used = 0;
+        }
+        self.used = self.used.saturating_add(1);
+    }
+}
+
+/// Position of a queued entity: the CPU whose queue holds it, and where.
+#[derive(Clone, Copy)]
+struct QueuedAt {
+    cpu: usize,
+    index: usize,
+}
+
+/// Ready queue of each logical CPU, used by [`SCHEDULER`].
+static RUN_QUEUES: RunQueues = [const { InterruptMutex::new(RunQueue::new()) }; MAX_CPUS];
+
+type RunQueues = [InterruptMutex<RunQueue>; MAX_CPUS];
+
 /// Global scheduler instance
 pub static SCHEDULER: InterruptMutex<Scheduler> = InterruptMutex::new_tracked(
-    Scheduler::new(),
+    Scheduler::with_queues(&RUN_QUEUES),
     crate::diagnostics::shadow::locks::LockClassId::Scheduler,
 );
 
+/// Whether any CPU's ready queue holds an entity. Takes only the queue
+/// locks, so polling idle CPUs do not contend on [`SCHEDULER`].
+pub fn work_queued() -> bool {
+    RUN_QUEUES.iter().any(|queue| !queue.lock().is_empty())
+}
+
+/// Number of entities waiting on `cpu`'s ready queue.
+pub fn queue_depth(cpu: usize) -> usize {
+    RUN_QUEUES.get(cpu).map_or(0, |queue| queue.lock().len())
+}
+
+/// Lock the queues of CPUs `a` and `b`, which differ, the lower-numbered
+/// first, and return the guards in argument order.
+fn lock_pair(
+    queues: &RunQueues,
+    a: usize,
+    b: usize,
+) -> (
+    InterruptMutexGuard<'_, RunQueue>,
+    InterruptMutexGuard<'_, RunQueue>,
+) {
+    debug_assert_ne!(a, b, "a queue lock is not reentrant");
+    if a < b {
+        let first = queues[a].lock();
+        (first, queues[b].lock())
+    } else {
+        let second = queues[b].lock();
+        (queues[a].lock(), second)
+    }
+}
+
 /// Fair scheduler for every runnable entity on the CPU.
 pub struct Scheduler {
     /// All processes indexed by PID
     processes: BTreeMap<ProcessId, ProcessControlBlock>,
     /// Privilege-neutral scheduling state indexed by stable tagged identity.
     entities: BTreeMap<EntityId, SchedEntity>,
-    /// The one ready queue shared by kernel threads and user processes.
-    run_queue: RunQueue,
+    /// One ready queue per logical CPU, shared by kernel threads and user
+    /// processes.
+    run_queues: &'static RunQueues,
     /// Entity whose execution state is loaded on each logical CPU.
-    current: [Option<EntityId>; crate::arch::x86_64::acpi::MAX_CPUS],
+    current: [Option<EntityId>; MAX_CPUS],
     /// The idle process PID (runs when nothing else is ready)
     pub idle_pid: Option<ProcessId>,
     /// Whether scheduler is initialized
@@ -95,6 +248,12 @@ pub struct Scheduler {
     signal_waiters: Vec<ProcessId>,
     /// Number of contracted dispatches selected after their ceiling.
     latency_misses: u64,
+    /// Monotonic floor of the ready and running entities' virtual runtime.
+    /// New entities start here and waking sleepers are pulled up to it.
+    min_vruntime: u64,
+    rt_bandwidth: [RtBandwidth; MAX_CPUS],
+    /// Tick of each CPU's last load-balancing pass.
+    last_balance: [u64; MAX_CPUS],
     /// Whether mutations belong to the singleton production scheduler and
     /// therefore participate in the global crash-readable shadow.
     shadow_observed: bool,
@@ -142,17 +301,29 @@ impl Scheduler {
         );
     }
 
-    /// Create a new scheduler (const for static initialization)
-    pub const fn new() -> Self {
+    /// Create a scheduler with ready queues of its own, apart from the
+    /// global instance's.
+    #[cfg(feature = "test")]
+    pub fn new() -> Self {
+        Self::with_queues(alloc::boxed::Box::leak(alloc::boxed::Box::new(
+            [const { InterruptMutex::new(RunQueue::new()) }; MAX_CPUS],
+        )))
+    }
+
+    /// Create a scheduler over `run_queues` (const for static initialization)
+    const fn with_queues(run_queues: &'static RunQueues) -> Self {
         Scheduler {
             processes: BTreeMap::new(),
             entities: BTreeMap::new(),
-            run_queue: RunQueue::new(),
-            current: [None; crate::arch::x86_64::acpi::MAX_CPUS],
+            run_queues,
+            current: [None; MAX_CPUS],
             idle_pid: None,
             initialized: false,
             signal_waiters: Vec::new(),
             latency_misses: 0,
+            min_vruntime: 0,
+            rt_bandwidth: [RtBandwidth::new(); MAX_CPUS],
+            last_balance: [0; MAX_CPUS],
             shadow_observed: false,
         }
     }
@@ -171,9 +342,12 @@ impl Scheduler {
             return;
         }
 
-        self.run_queue
-            .reserve()
-            .expect("scheduler run-queue reservation failed");
+        for queue in self.run_queues {
+            queue
+                .lock()
+                .reserve()
+                .expect("scheduler run-queue reservation failed");
+        }
 
         // Create idle process - it is never inserted in the normal run queue.
         let idle_pid = super::process::allocate_pid();
@@ -185,7 +359,7 @@ impl Scheduler {
         self.processes.insert(idle_pid, idle_pcb);
         self.entities.insert(
             EntityId::KernelThread(idle_pid),
-            SchedEntity::new(EntityId::KernelThread(idle_pid)),
+            SchedEntity::new(EntityId::KernelThread(idle_pid), self.min_vruntime),
         );
         if self.shadow_observed {
             crate::diagnostics::shadow::scheduler::register(EntityId::KernelThread(idle_pid));
@@ -217,7 +391,8 @@ impl Scheduler {
 
         self.processes.insert(pid, pcb);
         let id = EntityId::KernelThread(pid);
-        self.entities.insert(id, SchedEntity::new(id));
+        self.entities
+            .insert(id, SchedEntity::new(id, self.min_vruntime));
         if self.shadow_observed {
             crate::diagnostics::shadow::scheduler::register(id);
         }
@@ -272,7 +447,7 @@ impl Scheduler {
     pub fn register_user(&mut self, pid: u32) -> Result<(), ()> {
         let id = EntityId::UserProcess(pid);
         if let alloc::collections::btree_map::Entry::Vacant(entry) = self.entities.entry(id) {
-            entry.insert(SchedEntity::new(id));
+            entry.insert(SchedEntity::new(id, self.min_vruntime));
             if self.shadow_observed {
                 crate::diagnostics::shadow::scheduler::register(id);
             }
@@ -314,7 +489,7 @@ impl Scheduler {
                 }
                 return Ok(false);
             }
-            let result = self.run_queue.enqueue(id);
+            let result = self.enqueue(id);
             if self.shadow_observed {
                 crate::diagnostics::shadow::scheduler::make_ready(id, true);
             }
@@ -329,12 +504,14 @@ impl Scheduler {
                 pcb.block_reason = None;
             }
         }
+        let floor = self.min_vruntime.saturating_sub(SLEEPER_CREDIT);
         let entity = self
             .entities
             .get_mut(&id)
             .expect("scheduler entity vanished");
         entity.state = RunState::Ready;
         entity.ready_since_tick = now;
+        entity.vruntime = entity.vruntime.max(floor);
         entity.latency_contract = contract;
         entity.must_run_by_tick = contract.map(|c| now.saturating_add(c.max_dispatch_ticks as u64));
         if !entity.context_published {
@@ -343,7 +520,7 @@ impl Scheduler {
             }
             return Ok(false);
         }
-        let result = self.run_queue.enqueue(id);
+        let result = self.enqueue(id);
         if self.shadow_observed {
             crate::diagnostics::shadow::scheduler::make_ready(id, true);
         }
@@ -354,7 +531,7 @@ impl Scheduler {
     }
 
     pub fn block_entity(&mut self, id: EntityId) {
-        self.run_queue.remove(id);
+        self.dequeue(id);
         let existed = if let Some(entity) = self.entities.get_mut(&id) {
             entity.state = RunState::Blocked;
             entity.must_run_by_tick = None;
@@ -375,7 +552,7 @@ impl Scheduler {
     pub fn unregister_entity(&mut self, id: EntityId) {
         let existed = self.entities.contains_key(&id);
         let was_current = self.current.contains(&Some(id));
-        self.run_queue.remove(id);
+        self.dequeue(id);
         for current in &mut self.current {
             if *current == Some(id) {
                 *current = None;
@@ -394,6 +571,10 @@ impl Scheduler {
         self.entities.get(&id).map(|entity| entity.state)
     }
 
+    #[cfg_attr(
+        not(feature = "test"),
+        expect(dead_code, reason = "no production caller binds an entity to a CPU")
+    )]
     pub fn set_cpu_affinity(&mut self, id: EntityId, cpu: Option<usize>) -> Result<(), ()> {
         if cpu.is_some_and(|cpu| cpu >= crate::arch::x86_64::smp::online_cpu_count()) {
             return Err(());
@@ -430,7 +611,6 @@ impl Scheduler {
         };
         let enqueued = if ready {
             let enqueued = self
-                .run_queue
                 .enqueue(id)
                 .expect("scheduler entity capacity exceeded while publishing context");
             if enqueued {
@@ -475,35 +655,309 @@ impl Scheduler {
             .is_some_and(|entity| entity.cpu_affinity.is_none_or(|bound| bound == cpu))
     }
 
-    fn pick_ready_index(&self, now: u64, cpu: usize) -> Option<usize> {
-        let mut due: Option<(usize, u64)> = None;
-        for (index, id) in self.run_queue.iter().enumerate() {
-            if !self.eligible_on_cpu(*id, cpu) {
-                continue;
+    /// Queue an entity waits on: the CPU it is bound to, else the one it
+    /// last ran on, else this one.
+    fn home_queue(&self, id: EntityId) -> usize {
+        self.entities
+            .get(&id)
+            .and_then(|entity| entity.cpu_affinity.or(entity.last_cpu))
+            .filter(|cpu| *cpu < MAX_CPUS)
+            .unwrap_or_else(crate::arch::x86_64::percpu::cpu_id)
+    }
+
+    fn enqueue(&mut self, id: EntityId) -> Result<bool, ()> {
+        if self
+            .run_queues
+            .iter()
+            .any(|queue| queue.lock().contains(id))
+        {
+            return Ok(false);
+        }
+        let cpu = self.home_queue(id);
+        self.run_queues[cpu].lock().enqueue(id)
+    }
+
+    fn dequeue(&mut self, id: EntityId) -> bool {
+        self.run_queues.iter().any(|queue| queue.lock().remove(id))
+    }
+
+    /// Fold `f` over every queued entity, holding one queue lock at a time.
+    fn fold_queued<B>(&self, init: B, mut f: impl FnMut(B, EntityId) -> B) -> B {
+        self.run_queues.iter().fold(init, |acc, queue| {
+            queue.lock().iter().fold(acc, |acc, id| f(acc, *id))
+        })
+    }
+
+    /// Position of the queued entity with the least `key`, `cpu`'s own
+    /// queue first and then queue order breaking ties. Entities whose key
+    /// is `None` are passed over.
+    fn least_queued<K: Ord>(
+        &self,
+        cpu: usize,
+        key: impl Fn(EntityId) -> Option<K>,
+    ) -> Option<QueuedAt> {
+        let mut best: Option<(QueuedAt, K)> = None;
+        for queue in (0..MAX_CPUS).map(|offset| (cpu + offset) % MAX_CPUS) {
+            for (index, id) in self.run_queues[queue].lock().iter().enumerate() {
+                let Some(k) = key(*id) else {
+                    continue;
+                };
+                if best.as_ref().is_none_or(|(_, least)| k < *least) {
+                    best = Some((QueuedAt { cpu: queue, index }, k));
+                }
             }
-            let Some(deadline) = self
+        }
+        best.map(|(at, _)| at)
+    }
+
+    fn take_queued(&mut self, at: QueuedAt) -> Option<EntityId> {
+        self.run_queues[at.cpu].lock().remove_at(at.index)
+    }
+
+    fn pick_ready_index(&self, now: u64, cpu: usize) -> Option<QueuedAt> {
+        let accept = |id| self.eligible_on_cpu(id, cpu);
+        let due = self.least_queued(cpu, |id| {
+            let deadline = self.entities.get(&id)?.must_run_by_tick?;
+            (accept(id) && deadline <= now).then_some(deadline)
+        });
+        self.realtime_index(now, cpu, accept)
+            .or(due)
+            .or_else(|| self.fairest_index(cpu, accept))
+            .or_else(|| self.steal_index(cpu, accept))
+    }
+
+    /// Position of the accepted real-time entity to run next: highest
+    /// priority, `cpu`'s own queue then earliest queued on a tie. `None`
+    /// while `cpu` is throttled.
+    fn realtime_index(
+        &self,
+        now: u64,
+        cpu: usize,
+        accept: impl Fn(EntityId) -> bool,
+    ) -> Option<QueuedAt> {
+        if self.rt_bandwidth[cpu].throttled(now) {
+            return None;
+        }
+        self.least_queued(cpu, |id| {
+            let entity = self.entities.get(&id)?;
+            (accept(id) && entity.policy.is_realtime())
+                .then_some(core::cmp::Reverse(entity.rt_priority))
+        })
+    }
+
+    /// Position of the user entity [`Self::pop_next_user`] takes.
+    fn next_user_index(&self, now: u64, cpu: usize) -> Option<QueuedAt> {
+        let accept = |id| matches!(id, EntityId::UserProcess(_)) && self.eligible_on_cpu(id, cpu);
+        self.realtime_index(now, cpu, accept)
+            .or_else(|| self.fairest_index(cpu, accept))
+            .or_else(|| self.steal_index(cpu, accept))
+    }
+
+    /// Whether real-time `current` keeps the CPU at a tick: nothing of
+    /// higher priority is ready, and a round-robin slice has time left or
+    /// no equal-priority peer to rotate to.
+    fn keeps_cpu(&self, current: EntityId, now: u64, cpu: usize) -> bool {
+        let Some(entity) = self.entities.get(&current) else {
+            return false;
+        };
+        if !entity.policy.is_realtime() || self.rt_bandwidth[cpu].throttled(now) {
+            return false;
+        }
+        let waiting = self.fold_queued(None, |highest: Option<u8>, id| {
+            let priority = self
                 .entities
-                .get(id)
-                .and_then(|entity| entity.must_run_by_tick)
-            else {
-                continue;
-            };
-            if deadline <= now && due.map(|(_, earliest)| deadline < earliest).unwrap_or(true) {
-                due = Some((index, deadline));
+                .get(&id)
+                .filter(|waiting| waiting.policy.is_realtime())
+                .filter(|_| self.eligible_on_cpu(id, cpu))
+                .map(|waiting| waiting.rt_priority);
+            highest.max(priority)
+        });
+        match waiting {
+            Some(priority) if priority > entity.rt_priority => false,
+            Some(priority) if priority == entity.rt_priority => {
+                entity.policy == SchedPolicy::Fifo || entity.rr_ticks_left > 0
             }
+            _ => true,
         }
-        due.map(|(index, _)| index).or_else(|| {
-            self.run_queue
-                .iter()
-                .position(|id| self.eligible_on_cpu(*id, cpu))
+    }
+
+    /// Position on `queue` of the accepted entity with the least virtual
+    /// runtime, the earliest queued on a tie.
+    fn fairest_index(&self, queue: usize, accept: impl Fn(EntityId) -> bool) -> Option<QueuedAt> {
+        self.fairest_in(&self.run_queues[queue].lock(), queue, accept)
+    }
+
+    /// [`Self::fairest_index`] on a queue whose lock the caller holds.
+    fn fairest_in(
+        &self,
+        queue: &RunQueue,
+        cpu: usize,
+        accept: impl Fn(EntityId) -> bool,
+    ) -> Option<QueuedAt> {
+        queue
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| accept(**id))
+            .min_by_key(|(index, id)| {
+                let vruntime = self.entities.get(*id).map_or(u64::MAX, |e| e.vruntime);
+                (vruntime, *index)
+            })
+            .map(|(index, _)| QueuedAt { cpu, index })
+    }
+
+    /// Work stealing for a CPU with nothing of its own to run: move the
+    /// fairest accepted entity on the busiest queue that has one onto
+    /// `cpu`'s queue, holding both queue locks, and return its position.
+    fn steal_index(&self, cpu: usize, accept: impl Fn(EntityId) -> bool) -> Option<QueuedAt> {
+        let (victim, _) = (0..MAX_CPUS)
+            .filter(|queue| *queue != cpu)
+            .filter_map(|queue| {
+                let guard = self.run_queues[queue].lock();
+                self.fairest_in(&guard, queue, &accept)
+                    .map(|_| (queue, guard.len()))
+            })
+            .max_by_key(|(_, len)| *len)?;
+        let (mut own, mut from) = lock_pair(self.run_queues, cpu, victim);
+        if own.len() >= super::run_queue::MAX_ENTITIES {
+            return None;
+        }
+        let at = self.fairest_in(&from, victim, &accept)?;
+        let id = from.remove_at(at.index)?;
+        own.enqueue(id).ok()?;
+        Some(QueuedAt {
+            cpu,
+            index: own.len() - 1,
         })
     }
 
+    /// Periodic load balancing from `cpu`'s tick: pull entities from the
+    /// busiest queue until it is at most one longer than `cpu`'s own. The
+    /// most recently queued move first, their caches being the coldest.
+    fn balance(&mut self, now: u64, cpu: usize) {
+        if now < self.last_balance[cpu].saturating_add(BALANCE_INTERVAL_TICKS) {
+            return;
+        }
+        self.last_balance[cpu] = now;
+        let Some(busiest) = (0..MAX_CPUS)
+            .filter(|queue| *queue != cpu)
+            .max_by_key(|queue| self.run_queues[*queue].lock().len())
+        else {
+            return;
+        };
+        let (mut from, mut own) = lock_pair(self.run_queues, busiest, cpu);
+        while from.len() > own.len() + 1 {
+            let Some(index) = from
+                .iter()
+                .enumerate()
+                .filter(|(_, id)| self.eligible_on_cpu(**id, cpu))
+                .map(|(index, _)| index)
+                .last()
+            else {
+                break;
+            };
+            let id = from.remove_at(index).expect("balanced entity vanished");
+            own.enqueue(id)
+                .expect("balancing target is shorter than its source");
+        }
+    }
+
+    /// Advance [`Self::min_vruntime`] to the least virtual runtime among the
+    /// queued and running entities. It never moves backwards.
+    fn update_min_vruntime(&mut self) {
+        let vruntime = |id: &EntityId| self.entities.get(id).map(|entity| entity.vruntime);
+        let running = self.current.iter().flatten().filter_map(vruntime).min();
+        let least = self.fold_queued(running, |least, id| match (least, vruntime(&id)) {
+            (Some(least), Some(v)) => Some(least.min(v)),
+            (least, v) => least.or(v),
+        });
+        if let Some(least) = least {
+            self.min_vruntime = self.min_vruntime.max(least);
+        }
+    }
+
+    pub fn nice(&self, id: EntityId) -> Option<i8> {
+        self.entities.get(&id).map(|entity| entity.nice)
+    }
+
+    /// Set an entity's nice value, clamped to the valid range. Its virtual
+    /// runtime so far is kept, so the new weight applies from the next tick.
+    pub fn set_nice(&mut self, id: EntityId, nice: i8) -> Result<(), ()> {
+        let entity = self.entities.get_mut(&id).ok_or(())?;
+        entity.nice = nice.clamp(super::entity::NICE_MIN, super::entity::NICE_MAX);
+        entity.weight = nice_to_weight(entity.nice);
+        Ok(())
+    }
+
+    /// Scheduling class and real-time priority of `id`.
+    pub fn policy(&self, id: EntityId) -> Option<(SchedPolicy, u8)> {
+        self.entities
+            .get(&id)
+            .map(|entity| (entity.policy, entity.rt_priority))
+    }
+
+    pub fn reset_on_fork(&self, id: EntityId) -> Option<bool> {
+        self.entities.get(&id).map(|entity| entity.reset_on_fork)
+    }
+
+    /// Set an entity's scheduling class. Callers validate `rt_priority`
+    /// against the class. A round-robin entity starts a fresh slice.
+    pub fn set_policy(
+        &mut self,
+        id: EntityId,
+        policy: SchedPolicy,
+        rt_priority: u8,
+        reset_on_fork: bool,
+    ) -> Result<(), ()> {
+        let entity = self.entities.get_mut(&id).ok_or(())?;
+        entity.policy = policy;
+        entity.rt_priority = rt_priority;
+        entity.reset_on_fork = reset_on_fork;
+        entity.rr_ticks_left = RR_TIMESLICE_TICKS;
+        Ok(())
+    }
+
+    /// Register user entity `child` with the nice value and scheduling
+    /// class of `parent`, as `fork` and `clone` inherit them. A parent with
+    /// `reset_on_fork` passes on neither real-time class nor negative nice.
+    /// Cannot fail: the child entity is created here if it does not exist.
+    pub fn register_user_child(&mut self, parent: EntityId, child: u32) {
+        let child = EntityId::UserProcess(child);
+        let inherited = self.entities.get(&parent).map(|parent| {
+            if parent.reset_on_fork {
+                (parent.nice.max(0), SchedPolicy::Normal, 0, false)
+            } else {
+                (
+                    parent.nice,
+                    parent.policy,
+                    parent.rt_priority,
+                    parent.reset_on_fork,
+                )
+            }
+        });
+        let min_vruntime = self.min_vruntime;
+        let shadow_observed = self.shadow_observed;
+        let entity = self.entities.entry(child).or_insert_with(|| {
+            if shadow_observed {
+                crate::diagnostics::shadow::scheduler::register(child);
+            }
+            SchedEntity::new(child, min_vruntime)
+        });
+        let Some((nice, policy, rt_priority, reset_on_fork)) = inherited else {
+            return;
+        };
+        entity.nice = nice.clamp(super::entity::NICE_MIN, super::entity::NICE_MAX);
+        entity.weight = nice_to_weight(entity.nice);
+        entity.policy = policy;
+        entity.rt_priority = rt_priority;
+        entity.reset_on_fork = reset_on_fork;
+        entity.rr_ticks_left = RR_TIMESLICE_TICKS;
+    }
+
     pub fn schedule_entity(&mut self) -> Option<EntityId> {
         let now = crate::arch::x86_64::interrupts::get_timer_ticks();
         let cpu = crate::arch::x86_64::percpu::cpu_id();
-        let index = self.pick_ready_index(now, cpu)?;
-        let next = self.run_queue.remove_at(index)?;
+        let at = self.pick_ready_index(now, cpu)?;
+        let next = self.take_queued(at)?;
         let missed = self
             .entities
             .get(&next)
@@ -516,6 +970,7 @@ impl Scheduler {
             entity.state = RunState::Running;
             entity.must_run_by_tick = None;
             entity.latency_contract = None;
+            entity.last_cpu = Some(cpu);
         }
         if let EntityId::KernelThread(pid) = next {
             if let Some(pcb) = self.processes.get_mut(&pid) {
@@ -524,7 +979,8 @@ impl Scheduler {
                 pcb.last_activity_tick = now;
             }
         }
-        self.current[crate::arch::x86_64::percpu::cpu_id()] = Some(next);
+        self.current[cpu] = Some(next);
+        self.update_min_vruntime();
         if self.shadow_observed {
             crate::diagnostics::shadow::scheduler::dispatch(next);
         }
@@ -537,11 +993,23 @@ impl Scheduler {
         Some(next)
     }
 
-    /// Requeue an interrupted running entity and select once from the shared
-    /// fair queue. Context saving is the caller's architecture responsibility.
+    /// Requeue an interrupted running entity and select once from the ready
+    /// queues. Context saving is the caller's architecture responsibility.
     pub fn preempt_and_pick(&mut self, current: EntityId) -> Option<EntityId> {
+        let now = crate::arch::x86_64::interrupts::get_timer_ticks();
+        let cpu = crate::arch::x86_64::percpu::cpu_id();
         if let Some(entity) = self.entities.get_mut(&current) {
-            entity.runtime_ticks = entity.runtime_ticks.saturating_add(1);
+            entity.charge_tick();
+            if entity.policy.is_realtime() {
+                self.rt_bandwidth[cpu].charge(now);
+            }
+        }
+        self.balance(now, cpu);
+        let keep = self.keeps_cpu(current, now, cpu);
+        if let Some(entity) = self.entities.get_mut(&current) {
+            if entity.rr_ticks_left == 0 {
+                entity.rr_ticks_left = RR_TIMESLICE_TICKS;
+            }
         }
         if let EntityId::KernelThread(pid) = current {
             if let Some(pcb) = self.processes.get_mut(&pid) {
@@ -550,20 +1018,20 @@ impl Scheduler {
         }
         // The interrupt frame and entity stack remain live until the
         // architecture handoff switches stacks. Keep the saved context out of
-        // the shared queue until that handoff publishes it.
+        // the ready queues until that handoff publishes it.
         self.mark_context_saving(current);
         if let Some(entity) = self.entities.get_mut(&current) {
             entity.state = RunState::Ready;
             entity.must_run_by_tick = None;
         }
-        let cpu = crate::arch::x86_64::percpu::cpu_id();
         if self.current[cpu] == Some(current) {
             self.current[cpu] = None;
         }
 
-        let Some(next) = self.schedule_entity() else {
-            // Nothing else can run, so keep executing the current interrupt
-            // frame without publishing it to another CPU.
+        let next = if keep { None } else { self.schedule_entity() };
+        let Some(next) = next else {
+            // Nothing else can (or may) run, so keep executing the current
+            // interrupt frame without publishing it to another CPU.
             if let Some(entity) = self.entities.get_mut(&current) {
                 entity.state = RunState::Running;
                 entity.context_published = true;
@@ -596,19 +1064,20 @@ impl Scheduler {
     }
 
     pub fn pop_next_user(&mut self) -> Option<u32> {
+        let now = crate::arch::x86_64::interrupts::get_timer_ticks();
         let cpu = crate::arch::x86_64::percpu::cpu_id();
-        let index = self.run_queue.iter().position(|id| {
-            matches!(id, EntityId::UserProcess(_)) && self.eligible_on_cpu(*id, cpu)
-        })?;
-        let EntityId::UserProcess(pid) = self.run_queue.remove_at(index)? else {
+        let at = self.next_user_index(now, cpu)?;
+        let EntityId::UserProcess(pid) = self.take_queued(at)? else {
             return None;
         };
         let id = EntityId::UserProcess(pid);
         if let Some(entity) = self.entities.get_mut(&id) {
             entity.state = RunState::Running;
             entity.must_run_by_tick = None;
+            entity.last_cpu = Some(cpu);
         }
-        self.current[crate::arch::x86_64::percpu::cpu_id()] = Some(id);
+        self.current[cpu] = Some(id);
+        self.update_min_vruntime();
         if self.shadow_observed {
             crate::diagnostics::shadow::scheduler::dispatch(id);
         }
@@ -621,12 +1090,13 @@ impl Scheduler {
     }
 
     pub fn peek_next_user(&self) -> Option<u32> {
+        let now = crate::arch::x86_64::interrupts::get_timer_ticks();
         let cpu = crate::arch::x86_64::percpu::cpu_id();
-        self.run_queue.iter().find_map(|id| match id {
-            EntityId::UserProcess(pid) if self.eligible_on_cpu(*id, cpu) => Some(*pid),
+        let at = self.next_user_index(now, cpu)?;
+        match self.run_queues[at.cpu].lock().iter().nth(at.index)? {
+            EntityId::UserProcess(pid) => Some(*pid),
             EntityId::KernelThread(_) => None,
-            EntityId::UserProcess(_) => None,
-        })
+        }
     }
 
     pub fn clear_current_user(&mut self, pid: u32) {
@@ -659,7 +1129,7 @@ impl Scheduler {
         }
         let inserted =
             if let alloc::collections::btree_map::Entry::Vacant(entry) = self.entities.entry(id) {
-                entry.insert(SchedEntity::new(id));
+                entry.insert(SchedEntity::new(id, self.min_vruntime));
                 true
             } else {
                 false
@@ -667,10 +1137,11 @@ impl Scheduler {
         if inserted && self.shadow_observed {
             crate::diagnostics::shadow::scheduler::register(id);
         }
-        self.run_queue.remove(id);
+        self.dequeue(id);
         if let Some(entity) = self.entities.get_mut(&id) {
             entity.state = RunState::Running;
             entity.must_run_by_tick = None;
+            entity.last_cpu = Some(cpu);
         }
         self.current[cpu] = Some(id);
         if self.shadow_observed {
@@ -693,8 +1164,7 @@ impl Scheduler {
         if self.current[cpu] == Some(id) {
             self.current[cpu] = None;
         }
-        self.run_queue
-            .enqueue(id)
+        self.enqueue(id)
             .expect("scheduler entity capacity exceeded while yielding");
         if self.shadow_observed {
             crate::diagnostics::shadow::scheduler::yielded(id);
@@ -869,7 +1339,7 @@ impl Scheduler {
         }
 
         // Remove from ready queue if present
-        self.run_queue.remove(id);
+        self.dequeue(id);
 
         if let Some(pcb) = self.processes.get_mut(&pid) {
             pcb.state = ProcessState::Terminated;
@@ -906,7 +1376,7 @@ impl Scheduler {
 
                 // Don't preempt idle process
                 if self.idle_pid == Some(current_pid) {
-                    return !self.run_queue.is_empty();
+                    return self.run_queues.iter().any(|queue| !queue.lock().is_empty());
                 }
 
                 // Decrement time slice
@@ -951,14 +1421,25 @@ impl Scheduler {
     /// Get the number of ready processes
     #[expect(dead_code, reason = "legacy diagnostics API")]
     pub fn ready_count(&self) -> usize {
-        self.run_queue
-            .iter()
-            .filter(|id| matches!(id, EntityId::KernelThread(pid) if Some(*pid) != self.idle_pid))
-            .count()
+        self.fold_queued(0, |count, id| match id {
+            EntityId::KernelThread(pid) if Some(pid) != self.idle_pid => count + 1,
+            _ => count,
+        })
     }
 
     pub fn ready_entity_count(&self) -> usize {
-        self.run_queue.len()
+        self.run_queues.iter().map(|queue| queue.lock().len()).sum()
+    }
+
+    /// Number of entities waiting on `cpu`'s ready queue.
+    #[cfg_attr(
+        not(feature = "test"),
+        expect(dead_code, reason = "production reads go through the free function")
+    )]
+    pub fn queue_depth(&self, cpu: usize) -> usize {
+        self.run_queues
+            .get(cpu)
+            .map_or(0, |queue| queue.lock().len())
     }
 
     #[cfg(feature = "test")]
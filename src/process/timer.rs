@@ -1,4 +1,8 @@
 //! Allocation-free-after-init deadline heap and bounded deferred delivery.
+//!
+//! Deadlines are monotonic nanoseconds. The BSP's PIT tick checks the
+//! earliest one as a backstop; [`crate::arch::x86_64::clockevent`] fires it
+//! on time.
 
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -20,6 +24,9 @@ pub enum TimerKind {
     UserNetworkTimeout,
     UserRealTimer,
     UserFutex,
+    /// A `timerfd`. The key's `entity` carries the timerfd id rather than
+    /// a process: the timer belongs to an open-file description.
+    UserTimerFd,
     NetworkPoll,
     CompositorFrame,
 }
@@ -40,12 +47,13 @@ pub enum TimerAction {
     UserNetworkTimeout(u32),
     UserRealTimer(u32),
     UserFutex(u32),
+    UserTimerFd(u32),
 }
 
 #[derive(Debug, Clone, Copy)]
 struct TimerEntry {
     key: TimerKey,
-    deadline_tick: u64,
+    deadline_ns: u64,
     sequence: u64,
     generation: u64,
     action: TimerAction,
@@ -101,6 +109,7 @@ impl TimerQueue {
             TimerKind::NetworkPoll => 5,
             TimerKind::CompositorFrame => 6,
             TimerKind::UserFutex => 7,
+            TimerKind::UserTimerFd => 8,
         };
         value
             .wrapping_mul(tag)
@@ -159,7 +168,7 @@ impl TimerQueue {
     }
 
     fn less(a: &TimerEntry, b: &TimerEntry) -> bool {
-        (a.deadline_tick, a.sequence) < (b.deadline_tick, b.sequence)
+        (a.deadline_ns, a.sequence) < (b.deadline_ns, b.sequence)
     }
 
     fn swap_heap(&mut self, a: usize, b: usize) {
@@ -210,11 +219,11 @@ impl TimerQueue {
         }
     }
 
-    fn arm(&mut self, key: TimerKey, deadline_tick: u64, action: TimerAction) -> Result<u64, ()> {
+    fn arm(&mut self, key: TimerKey, deadline_ns: u64, action: TimerAction) -> Result<u64, ()> {
         self.init()?;
         if let Some(index) = self.heap_index(key) {
             let generation = self.heap[index].generation.wrapping_add(1);
-            self.heap[index].deadline_tick = deadline_tick;
+            self.heap[index].deadline_ns = deadline_ns;
             self.heap[index].generation = generation;
             self.heap[index].action = action;
             self.repair(index);
@@ -230,7 +239,7 @@ impl TimerQueue {
         self.next_sequence = self.next_sequence.wrapping_add(1);
         self.heap.push(TimerEntry {
             key,
-            deadline_tick,
+            deadline_ns,
             sequence,
             generation: 1,
             action,
@@ -265,7 +274,7 @@ impl TimerQueue {
     }
 
     fn pop_due(&mut self, now: u64) -> Option<TimerEntry> {
-        if self.heap.first()?.deadline_tick > now {
+        if self.heap.first()?.deadline_ns > now {
             return None;
         }
         Some(self.remove_at(0))
@@ -274,7 +283,7 @@ impl TimerQueue {
     fn earliest(&self) -> u64 {
         self.heap
             .first()
-            .map(|entry| entry.deadline_tick)
+            .map(|entry| entry.deadline_ns)
             .unwrap_or(NO_DEADLINE)
     }
 
@@ -282,10 +291,10 @@ impl TimerQueue {
     pub fn arm_for_test(
         &mut self,
         key: TimerKey,
-        deadline_tick: u64,
+        deadline_ns: u64,
         action: TimerAction,
     ) -> Result<u64, ()> {
-        self.arm(key, deadline_tick, action)
+        self.arm(key, deadline_ns, action)
     }
 
     #[cfg(feature = "test")]
@@ -296,7 +305,7 @@ impl TimerQueue {
     #[cfg(feature = "test")]
     pub fn pop_due_for_test(&mut self, now: u64) -> Option<(TimerKey, u64)> {
         self.pop_due(now)
-            .map(|entry| (entry.key, entry.deadline_tick))
+            .map(|entry| (entry.key, entry.deadline_ns))
     }
 
     #[cfg(feature = "test")]
@@ -326,7 +335,7 @@ fn service_main() {
     loop {
         if take_work_pending() {
             crate::userland::readiness::retry_pending_wake();
-            let now = crate::arch::x86_64::interrupts::get_timer_ticks();
+            let now = crate::time::monotonic_ns();
             let _ = process_due(now);
             if deadline_due(now) {
                 crate::process::yield_current();
@@ -337,21 +346,41 @@ fn service_main() {
     }
 }
 
-fn publish_earliest(queue: &TimerQueue) {
-    EARLIEST_DEADLINE.store(queue.earliest(), Ordering::Release);
+fn publish_earliest(queue: &TimerQueue) -> u64 {
+    let earliest = queue.earliest();
+    EARLIEST_DEADLINE.store(earliest, Ordering::SeqCst);
+    earliest
 }
 
-pub fn arm(key: TimerKey, deadline_tick: u64, action: TimerAction) -> Result<u64, ()> {
-    let mut queue = TIMERS.lock();
-    let generation = queue.arm(key, deadline_tick, action)?;
-    publish_earliest(&queue);
+pub fn earliest_deadline_ns() -> u64 {
+    EARLIEST_DEADLINE.load(Ordering::SeqCst)
+}
+
+pub fn arm(key: TimerKey, deadline_ns: u64, action: TimerAction) -> Result<u64, ()> {
+    let (generation, earliest) = {
+        let mut queue = TIMERS.lock();
+        let generation = queue.arm(key, deadline_ns, action)?;
+        (generation, publish_earliest(&queue))
+    };
+    if earliest == deadline_ns {
+        crate::arch::x86_64::clockevent::deadline_lowered(deadline_ns);
+    }
     Ok(generation)
 }
 
+/// Arm for the instant PIT tick `tick` begins.
+pub fn arm_at_tick(key: TimerKey, tick: u64, action: TimerAction) -> Result<u64, ()> {
+    arm(
+        key,
+        tick.saturating_mul(crate::time::PIT_NANOSECONDS_PER_TICK),
+        action,
+    )
+}
+
 pub fn cancel(key: TimerKey) -> bool {
     let mut queue = TIMERS.lock();
     let cancelled = queue.cancel(key);
-    publish_earliest(&queue);
+    let _ = publish_earliest(&queue);
     cancelled
 }
 
@@ -373,8 +402,9 @@ pub fn deadline_due(now: u64) -> bool {
     now >= EARLIEST_DEADLINE.load(Ordering::Acquire)
 }
 
-/// PIT-side notification: no heap lock or allocation. The due flag is atomic;
-/// waking the service performs one bounded scheduler-ready operation.
+/// PIT- and clockevent-side notification: no heap lock or allocation. The
+/// due flag is atomic; waking the service performs one bounded
+/// scheduler-ready operation.
 pub fn on_tick(now: u64) {
     if deadline_due(now) || crate::userland::readiness::wake_pending() {
         WORK_PENDING.store(true, Ordering::Release);
@@ -402,7 +432,7 @@ pub fn process_due(now: u64) -> usize {
         let entry = {
             let mut queue = TIMERS.lock();
             let entry = queue.pop_due(now);
-            publish_earliest(&queue);
+            let _ = publish_earliest(&queue);
             entry
         };
         let Some(entry) = entry else {
@@ -415,10 +445,14 @@ pub fn process_due(now: u64) -> usize {
     if deadline_due(now) {
         WORK_PENDING.store(true, Ordering::Release);
     }
+    crate::arch::x86_64::clockevent::reprogram_soon();
     delivered
 }
 
+/// Run one expiry. User sleeps and timerfds keep nanosecond deadlines; the
+/// other user timers count PIT ticks.
 fn deliver(action: TimerAction, now: u64) {
+    let now_tick = now / crate::time::PIT_NANOSECONDS_PER_TICK;
     match action {
         TimerAction::Wake { entity, latency } => {
             let _ = crate::process::scheduler::SCHEDULER
@@ -429,14 +463,16 @@ fn deliver(action: TimerAction, now: u64) {
             crate::userland::lifecycle::expire_user_sleep(pid, now);
         }
         TimerAction::UserNetworkTimeout(pid) => {
-            crate::userland::lifecycle::expire_network_wait(pid, now);
+            crate::userland::lifecycle::expire_network_wait(pid, now_tick);
         }
         TimerAction::UserRealTimer(pid) => {
-            crate::userland::lifecycle::expire_real_timer(pid, now);
+            crate::userland::lifecycle::expire_real_timer(pid, now_tick);
         }
         TimerAction::UserFutex(pid) => {
-            let _ = now;
             crate::userland::futex::expire_wait(pid);
         }
+        TimerAction::UserTimerFd(id) => {
+            crate::userland::timerfd::expire(id, now);
+        }
     }
 }
@@ -7,7 +7,7 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec;
 use core::mem;
-use core::sync::atomic::{AtomicU8, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use crate::arch::x86_64::preemption_guard::PreemptionMutex;
 use crate::arch::x86_64::syscall::SyscallArgs;
@@ -22,12 +22,22 @@ const SETTINGS_PATH: &str = "/data/agenticos/settings.conf";
 const SETTINGS_TEMP_PATH: &str = "/data/agenticos/.settings.new";
 const MAX_SETTINGS_BYTES: usize = 4096;
 const MAX_WALLPAPER_PATH_BYTES: usize = 1024;
+/// Where core dumps go unless the `core_dir` setting says otherwise.
+pub const DEFAULT_CORE_DIR: &str = "/data/cores";
+/// Zone published as `/etc/localtime` unless the `time_zone` setting says
+/// otherwise.
+pub const DEFAULT_TIME_ZONE: &str = "UTC";
+const MAX_TIME_ZONE_BYTES: usize = 64;
 
 pub const COMMAND_GET_SNAPSHOT: u64 = 0;
 pub const COMMAND_GET_WALLPAPER_PATH: u64 = 1;
 pub const COMMAND_SET_THEME: u64 = 2;
 pub const COMMAND_SET_WALLPAPER_PATH: u64 = 3;
 pub const COMMAND_RESET_WALLPAPER: u64 = 4;
+pub const COMMAND_GET_CORE_DIR: u64 = 5;
+pub const COMMAND_SET_CORE_DIR: u64 = 6;
+pub const COMMAND_GET_TIME_ZONE: u64 = 7;
+pub const COMMAND_SET_TIME_ZONE: u64 = 8;
 
 pub const THEME_AVAILABLE_CLASSIC: u32 = 1 << 0;
 pub const THEME_AVAILABLE_AERO: u32 = 1 << 1;
@@ -111,10 +121,65 @@ pub struct SystemControlSnapshotV1 {
 
 const _: [(); 64] = [(); mem::size_of::<SystemControlSnapshotV1>()];
 
+/// Core-dump destination. Spelled `default`, `none` or an absolute
+/// directory in the settings file and in `COMMAND_SET_CORE_DIR`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CoreDir {
+    Default,
+    Disabled,
+    Custom(String),
+}
+
+impl CoreDir {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "default" => Some(Self::Default),
+            "none" => Some(Self::Disabled),
+            path if valid_absolute_path(path) => Some(Self::Custom(
+                crate::userland::path::normalize_path("/", path),
+            )),
+            _ => None,
+        }
+    }
+
+    fn path(&self) -> Option<&str> {
+        match self {
+            Self::Default => Some(DEFAULT_CORE_DIR),
+            Self::Disabled => None,
+            Self::Custom(path) => Some(path),
+        }
+    }
+}
+
+/// Where the SNTP client gets its time. Spelled `dhcp`, `none` or a dotted
+/// IPv4 address in the settings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NtpServer {
+    Dhcp,
+    Disabled,
+    Address([u8; 4]),
+}
+
+impl NtpServer {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "dhcp" => Some(Self::Dhcp),
+            "none" => Some(Self::Disabled),
+            address => address
+                .parse::<core::net::Ipv4Addr>()
+                .ok()
+                .map(|address| Self::Address(address.octets())),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct SettingsState {
     theme: ThemePreference,
     wallpaper_path: Option<String>,
+    core_dir: CoreDir,
+    ntp_server: NtpServer,
+    time_zone: Option<String>,
     wallpaper_fallback: bool,
     persistence_available: bool,
     boot_theme_override: bool,
@@ -125,6 +190,9 @@ impl SettingsState {
         Self {
             theme: ThemePreference::Auto,
             wallpaper_path: None,
+            core_dir: CoreDir::Default,
+            ntp_server: NtpServer::Dhcp,
+            time_zone: None,
             wallpaper_fallback: false,
             persistence_available: false,
             boot_theme_override: false,
@@ -134,6 +202,9 @@ impl SettingsState {
 
 static SETTINGS: PreemptionMutex<SettingsState> = PreemptionMutex::new(SettingsState::defaults());
 static PENDING_THEME_PUBLICATION: AtomicU8 = AtomicU8::new(0);
+/// Mirror of `core_dir != none` for the ring-3 fault path, which must not
+/// take [`SETTINGS`].
+static CORE_DUMPS_ENABLED: AtomicBool = AtomicBool::new(true);
 
 pub fn init() {
     let persistent = ensure_settings_dir();
@@ -161,6 +232,7 @@ pub fn init() {
         loaded.wallpaper_path.as_deref().unwrap_or("default"),
         loaded.persistence_available,
     );
+    CORE_DUMPS_ENABLED.store(loaded.core_dir != CoreDir::Disabled, Ordering::Release);
     *SETTINGS.lock() = loaded;
 }
 
@@ -183,6 +255,24 @@ fn parse_config_into(text: &str, state: &mut SettingsState) {
                     state.wallpaper_path = Some(crate::userland::path::normalize_path("/", value));
                 }
             }
+            "core_dir" => {
+                if let Some(core_dir) = CoreDir::parse(value) {
+                    state.core_dir = core_dir;
+                }
+            }
+            "ntp_server" => {
+                if let Some(server) = NtpServer::parse(value) {
+                    state.ntp_server = server;
+                }
+            }
+            "time_zone" => {
+                let value = value.trim();
+                if value == DEFAULT_TIME_ZONE {
+                    state.time_zone = None;
+                } else if valid_time_zone_name(value) {
+                    state.time_zone = Some(value.to_string());
+                }
+            }
             _ => {}
         }
     }
@@ -202,11 +292,25 @@ fn ensure_settings_dir() -> bool {
 }
 
 fn serialize(state: &SettingsState) -> String {
-    format!(
+    let mut text = format!(
         "theme={}\nwallpaper={}\n",
         state.theme.as_str(),
         state.wallpaper_path.as_deref().unwrap_or("default"),
-    )
+    );
+    match &state.core_dir {
+        CoreDir::Default => {}
+        CoreDir::Disabled => text.push_str("core_dir=none\n"),
+        CoreDir::Custom(path) => text.push_str(&format!("core_dir={path}\n")),
+    }
+    match state.ntp_server {
+        NtpServer::Dhcp => {}
+        NtpServer::Disabled => text.push_str("ntp_server=none\n"),
+        NtpServer::Address([a, b, c, d]) => text.push_str(&format!("ntp_server={a}.{b}.{c}.{d}\n")),
+    }
+    if let Some(zone) = &state.time_zone {
+        text.push_str(&format!("time_zone={zone}\n"));
+    }
+    text
 }
 
 fn persist_current() -> bool {
@@ -285,6 +389,45 @@ pub fn load_configured_wallpaper() -> Option<alloc::vec::Vec<u8>> {
     crate::window::load_default_wallpaper()
 }
 
+/// Directory core dumps are written to, or `None` when they are disabled.
+pub fn core_dump_dir() -> Option<String> {
+    SETTINGS.lock().core_dir.path().map(String::from)
+}
+
+/// Whether core dumps are enabled. Lock-free, so the ring-3 fault path may
+/// ask before it spends time building an image.
+pub fn core_dumps_enabled() -> bool {
+    CORE_DUMPS_ENABLED.load(Ordering::Acquire)
+}
+
+/// The SNTP server setting.
+pub fn ntp_server() -> NtpServer {
+    SETTINGS.lock().ntp_server
+}
+
+/// The configured time zone, an IANA name such as `Europe/London`.
+pub fn time_zone() -> String {
+    SETTINGS
+        .lock()
+        .time_zone
+        .clone()
+        .unwrap_or_else(|| DEFAULT_TIME_ZONE.to_string())
+}
+
+/// Whether `name` can name a file under `/etc/zoneinfo`: relative,
+/// `/`-separated components of letters, digits, `_`, `-` and `+`.
+pub fn valid_time_zone_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_TIME_ZONE_BYTES
+        && name.split('/').all(|component| {
+            !component.is_empty()
+                && !component.starts_with('-')
+                && component
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"_-+".contains(&byte))
+        })
+}
+
 fn snapshot() -> SystemControlSnapshotV1 {
     let state = SETTINGS.lock().clone();
     let (renderer, width, height) = crate::window::with_window_manager(|wm| {
@@ -422,6 +565,44 @@ fn reset_wallpaper() -> i64 {
     }
 }
 
+fn set_core_dir(value: &str) -> i64 {
+    let Some(core_dir) = CoreDir::parse(value) else {
+        return EINVAL;
+    };
+    CORE_DUMPS_ENABLED.store(core_dir != CoreDir::Disabled, Ordering::Release);
+    let shown = String::from(core_dir.path().unwrap_or("none"));
+    SETTINGS.lock().core_dir = core_dir;
+    let persisted = persist_current();
+    crate::debug_info!(
+        "system core dump directory set to {} persistent={}",
+        shown,
+        persisted,
+    );
+    if persisted {
+        0
+    } else {
+        1
+    }
+}
+
+fn set_time_zone(name: &str) -> i64 {
+    if !valid_time_zone_name(name) {
+        return EINVAL;
+    }
+    if !crate::userland::etc::publish_time_zone(name) {
+        return ENOENT;
+    }
+    SETTINGS.lock().time_zone = (name != DEFAULT_TIME_ZONE).then(|| name.to_string());
+    let persisted = persist_current();
+    crate::userland::gui::broadcast_settings_changed();
+    crate::debug_info!("system time zone set to {} persistent={}", name, persisted,);
+    if persisted {
+        0
+    } else {
+        1
+    }
+}
+
 pub fn syscall_handler(args: &mut SyscallArgs) -> i64 {
     if args.r8 != 0 {
         return EINVAL;
@@ -479,6 +660,59 @@ pub fn syscall_handler(args: &mut SyscallArgs) -> i64 {
                 reset_wallpaper()
             }
         }
+        COMMAND_GET_CORE_DIR => {
+            if args.rsi != 0 {
+                return EINVAL;
+            }
+            // An empty result means core dumps are disabled.
+            let path = core_dump_dir().unwrap_or_default();
+            if args.r10 < path.len() as u64 {
+                return ERANGE;
+            }
+            match crate::userland::usercopy::copy_to_user(args.rdx, path.as_bytes()) {
+                Ok(()) => path.len() as i64,
+                Err(_) => EFAULT,
+            }
+        }
+        COMMAND_SET_CORE_DIR => {
+            if args.rsi != 0 || args.r10 == 0 || args.r10 as usize > MAX_WALLPAPER_PATH_BYTES {
+                return EINVAL;
+            }
+            let mut bytes = vec![0u8; args.r10 as usize];
+            if crate::userland::usercopy::copy_from_user(&mut bytes, args.rdx).is_err() {
+                return EFAULT;
+            }
+            match String::from_utf8(bytes) {
+                Ok(value) => set_core_dir(&value),
+                Err(_) => EINVAL,
+            }
+        }
+        COMMAND_GET_TIME_ZONE => {
+            if args.rsi != 0 {
+                return EINVAL;
+            }
+            let zone = time_zone();
+            if args.r10 < zone.len() as u64 {
+                return ERANGE;
+            }
+            match crate::userland::usercopy::copy_to_user(args.rdx, zone.as_bytes()) {
+                Ok(()) => zone.len() as i64,
+                Err(_) => EFAULT,
+            }
+        }
+        COMMAND_SET_TIME_ZONE => {
+            if args.rsi != 0 || args.r10 == 0 || args.r10 as usize > MAX_TIME_ZONE_BYTES {
+                return EINVAL;
+            }
+            let mut bytes = vec![0u8; args.r10 as usize];
+            if crate::userland::usercopy::copy_from_user(&mut bytes, args.rdx).is_err() {
+                return EFAULT;
+            }
+            match String::from_utf8(bytes) {
+                Ok(name) => set_time_zone(&name),
+                Err(_) => EINVAL,
+            }
+        }
         _ => EINVAL,
     }
 }
@@ -504,6 +738,67 @@ mod tests {
         assert!(state.wallpaper_path.is_none());
     }
 
+    fn test_core_dir_setting_round_trip() {
+        let mut state = SettingsState::defaults();
+        assert_eq!(state.core_dir.path(), Some(DEFAULT_CORE_DIR));
+        parse_config_into("core_dir=/data/crash/../dumps\n", &mut state);
+        assert_eq!(state.core_dir.path(), Some("/data/dumps"));
+        assert!(serialize(&state).ends_with("core_dir=/data/dumps\n"));
+        parse_config_into("core_dir=none\n", &mut state);
+        assert_eq!(state.core_dir.path(), None);
+        assert!(serialize(&state).ends_with("core_dir=none\n"));
+        // A bad value keeps the previous setting.
+        parse_config_into("core_dir=cores\n", &mut state);
+        assert_eq!(state.core_dir, CoreDir::Disabled);
+        parse_config_into("core_dir=default\n", &mut state);
+        assert!(!serialize(&state).contains("core_dir"));
+    }
+
+    fn test_ntp_server_setting_round_trip() {
+        let mut state = SettingsState::defaults();
+        assert_eq!(state.ntp_server, NtpServer::Dhcp);
+        parse_config_into("ntp_server=192.0.2.123\n", &mut state);
+        assert_eq!(state.ntp_server, NtpServer::Address([192, 0, 2, 123]));
+        assert!(serialize(&state).ends_with("ntp_server=192.0.2.123\n"));
+        // A bad value keeps the previous setting.
+        parse_config_into("ntp_server=pool.ntp.org\n", &mut state);
+        assert_eq!(state.ntp_server, NtpServer::Address([192, 0, 2, 123]));
+        parse_config_into("ntp_server=none\n", &mut state);
+        assert!(serialize(&state).ends_with("ntp_server=none\n"));
+        parse_config_into("ntp_server=dhcp\n", &mut state);
+        assert!(!serialize(&state).contains("ntp_server"));
+    }
+
+    fn test_time_zone_setting_round_trip() {
+        let mut state = SettingsState::defaults();
+        assert_eq!(state.time_zone, None);
+        parse_config_into("time_zone=America/Argentina/Buenos_Aires\n", &mut state);
+        assert_eq!(
+            state.time_zone.as_deref(),
+            Some("America/Argentina/Buenos_Aires")
+        );
+        assert!(serialize(&state).ends_with("time_zone=America/Argentina/Buenos_Aires\n"));
+        // A bad value keeps the previous setting.
+        parse_config_into("time_zone=../../data/settings.conf\n", &mut state);
+        assert_eq!(
+            state.time_zone.as_deref(),
+            Some("America/Argentina/Buenos_Aires")
+        );
+        parse_config_into("time_zone=UTC\n", &mut state);
+        assert!(!serialize(&state).contains("time_zone"));
+    }
+
+    fn test_time_zone_names_stay_under_zoneinfo() {
+        assert!(valid_time_zone_name("UTC"));
+        assert!(valid_time_zone_name("Etc/GMT+5"));
+        assert!(valid_time_zone_name("America/Port-au-Prince"));
+        assert!(!valid_time_zone_name(""));
+        assert!(!valid_time_zone_name("/etc/passwd"));
+        assert!(!valid_time_zone_name("Europe/../../passwd"));
+        assert!(!valid_time_zone_name("Europe//London"));
+        assert!(!valid_time_zone_name("Europe/London\n"));
+    }
+
     fn test_snapshot_layout_is_stable() {
         assert_eq!(mem::size_of::<SystemControlSnapshotV1>(), 64);
     }
@@ -547,6 +842,10 @@ mod tests {
         &[
             &test_config_parser_is_forward_compatible,
             &test_bad_individual_values_keep_defaults,
+            &test_core_dir_setting_round_trip,
+            &test_ntp_server_setting_round_trip,
+            &test_time_zone_setting_round_trip,
+            &test_time_zone_names_stay_under_zoneinfo,
             &test_snapshot_layout_is_stable,
             &test_futurism_preference_round_trip,
             &test_snapshot_syscall_writes_versioned_payload,
@@ -4,20 +4,30 @@
 //! [`Termios`], and [`Winsize`]. The ring-3 `TERMINAL.ELF` holds the master as
 //! an fd; zsh and its children resolve the slave through `Process.terminal_id`.
 //!
-//! A pty pair is registered per `WindowId`. Processes find their pty
-//! through their existing `Process.terminal_id` field — the model is
-//! the same as before, just with everything that used to live in three
-//! mutexes consolidated under one `Arc<Mutex<PtyInner>>`. Multi-process
-//! per-pty (fork) is handled the same way the WindowId queues handled
-//! it: every process with the same `terminal_id` shares the pty.
+//! Every pty is registered under its [`TerminalId`], the `N` of
+//! `/dev/pts/N`. Processes find their pty through `Process.terminal_id`,
+//! with everything consolidated under one `Arc<Mutex<PtyInner>>`.
+//! Multi-process per-pty (fork) works by inheritance: every process with
+//! the same `terminal_id` shares the pty.
+//!
+//! A pty comes from one of two places. `pty_open` binds one to a ring-3
+//! terminal window, which keeps it until the window goes away; opening
+//! `/dev/ptmx` allocates one that lives until its last master descriptor
+//! closes. Either way, tearing the pty down hangs up the slave: reads see
+//! end-of-file and writes fail.
 //!
 //! Line discipline lives here. [`PtyInner::push_slave_input`] applies
 //! canonical-mode editing (echo, VERASE/VKILL erase, a `MAX_CANON` line
 //! cap), VEOF end-of-file delivery, and ISIG signal generation for the
 //! ring-3 emulator's master-fd writes.
+//!
+//! Each pty also records the session it is the controlling terminal of
+//! and that session's foreground process group. The job-control rules
+//! that set them live in [`crate::userland::job_control`].
 
 use alloc::collections::{BTreeMap, VecDeque};
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
 use spin::Mutex;
 
 use crate::lib::arc::Arc;
@@ -128,6 +138,17 @@ impl Winsize {
 // PtyInner
 // ---------------------------------------------------------------------
 
+/// Index of a pty under `/dev/pts`. Names the pty in the registry and in
+/// `Process.terminal_id`, independently of any GUI window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TerminalId(pub u32);
+
+/// Placeholder id of the legacy pty, which has no `/dev/pts` entry.
+const LEGACY_ID: TerminalId = TerminalId(u32::MAX);
+
+/// Linux's default `/proc/sys/kernel/pty/max`.
+const MAX_PTYS: u32 = 4096;
+
 /// Soft cap on queued bytes (each direction). Bounds a runaway producer
 /// from holding the kernel heap hostage.
 const MAX_QUEUED_BYTES: usize = 64 * 1024;
@@ -148,8 +169,8 @@ pub struct SlaveInput {
     /// Data (or an EOF) became available to the slave — wake any process
     /// blocked in `read(0)` on this terminal.
     pub wake_reader: bool,
-    /// ISIG line-discipline signal (SIGINT/SIGQUIT) to raise on every
-    /// process bound to this terminal.
+    /// ISIG line-discipline signal (SIGINT/SIGQUIT/SIGTSTP) to raise on
+    /// the foreground process group.
     pub signal: Option<i32>,
 }
 
@@ -176,13 +197,29 @@ pub struct PtyInner {
     /// returns 0 — POSIX end-of-file.
     pending_eof: bool,
 
-    /// `WindowId` of the ring-3 terminal's GUI surface. Held for
-    /// SIGWINCH delivery and process-waker keys.
-    terminal_id: WindowId,
+    /// This pty's `/dev/pts` index. Held for SIGWINCH delivery and
+    /// process-waker keys.
+    terminal_id: TerminalId,
+
+    /// A `/dev/ptmx` pty starts locked: its slave cannot be opened until
+    /// the master clears this with `TIOCSPTLCK` (`unlockpt`).
+    locked: bool,
+
+    /// The master side is gone. Slave reads return end-of-file and writes
+    /// fail with `EIO`.
+    hung_up: bool,
+
+    /// Session whose controlling terminal this is, once claimed.
+    session: Option<u32>,
+
+    /// Foreground process group of `session`. Keyboard signals and
+    /// SIGWINCH go to this group; other groups in the session are in
+    /// the background.
+    foreground_pgrp: Option<u32>,
 }
 
 impl PtyInner {
-    fn new(terminal_id: WindowId, rows: u16, cols: u16) -> Self {
+    fn new(terminal_id: TerminalId, rows: u16, cols: u16) -> Self {
         Self {
             slave_queue: VecDeque::new(),
             master_queue: VecDeque::new(),
@@ -191,6 +228,10 @@ impl PtyInner {
             canon_line: Vec::new(),
             pending_eof: false,
             terminal_id,
+            locked: false,
+            hung_up: false,
+            session: None,
+            foreground_pgrp: None,
         }
     }
 
@@ -231,8 +272,8 @@ impl PtyInner {
     ///   ICRNL) flushes the line — terminator included — to the slave's
     ///   read queue. VEOF flushes a partial line without a terminator; on
     ///   an empty line it marks EOF so the slave's `read` returns 0. With
-    ///   ISIG set, VINTR/VQUIT discard the partial line and report the
-    ///   signal for the caller to raise once the pty lock is released.
+    ///   ISIG set, VINTR/VQUIT/VSUSP discard the partial line and report
+    ///   the signal for the caller to raise once the pty lock is released.
     pub fn push_slave_input(&mut self, bytes: &[u8]) -> SlaveInput {
         if !self.termios.is_canonical() {
             let consumed = self.push_slave_raw(bytes);
@@ -249,21 +290,19 @@ impl PtyInner {
         let mut result = SlaveInput::default();
         for &raw in bytes {
             let b = if icrnl && raw == b'\r' { b'\n' } else { raw };
-            if isig && raw != 0 && (raw == cc[VINTR] || raw == cc[VQUIT]) {
+            if isig && raw != 0 && (raw == cc[VINTR] || raw == cc[VQUIT] || raw == cc[VSUSP]) {
                 self.canon_line.clear();
+                let (caret, signal): (&[u8], i32) = if raw == cc[VINTR] {
+                    (b"^C\r\n", crate::userland::signal::SIGINT)
+                } else if raw == cc[VQUIT] {
+                    (b"^\\\r\n", crate::userland::signal::SIGQUIT)
+                } else {
+                    (b"^Z\r\n", crate::userland::signal::SIGTSTP)
+                };
                 if echo {
-                    let caret: &[u8] = if raw == cc[VINTR] {
-                        b"^C\r\n"
-                    } else {
-                        b"^\\\r\n"
-                    };
                     self.echo(caret);
                 }
-                result.signal = Some(if raw == cc[VINTR] {
-                    crate::userland::signal::SIGINT
-                } else {
-                    crate::userland::signal::SIGQUIT
-                });
+                result.signal = Some(signal);
             } else if b == b'\n' {
                 if echo {
                     self.echo(b"\r\n");
@@ -316,11 +355,43 @@ impl PtyInner {
         }
     }
 
-    /// The `WindowId` this pty is keyed on.
-    pub fn terminal_id(&self) -> WindowId {
+    /// The `/dev/pts` index this pty is keyed on.
+    pub fn terminal_id(&self) -> TerminalId {
         self.terminal_id
     }
 
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    pub fn hung_up(&self) -> bool {
+        self.hung_up
+    }
+
+    /// Session this pty is the controlling terminal of.
+    pub fn session(&self) -> Option<u32> {
+        self.session
+    }
+
+    pub fn foreground_pgrp(&self) -> Option<u32> {
+        self.foreground_pgrp
+    }
+
+    /// Make this the controlling terminal of `session` with `pgrp` in
+    /// the foreground.
+    pub fn set_controlling(&mut self, session: u32, pgrp: u32) {
+        self.session = Some(session);
+        self.foreground_pgrp = Some(pgrp);
+    }
+
+    pub fn set_foreground_pgrp(&mut self, pgrp: u32) {
+        self.foreground_pgrp = Some(pgrp);
+    }
+
     /// Drain bytes from the slave's input queue into `dst`. Returns
     /// the number copied.
     pub fn slave_read(&mut self, dst: &mut [u8]) -> usize {
@@ -402,18 +473,55 @@ impl PtyInner {
 // Master / Slave handles
 // ---------------------------------------------------------------------
 
-/// The master end. Exposed to `TERMINAL.ELF` through `FdSlot::PtyMaster`.
-/// All operations require briefly locking the inner mutex.
+/// The master end. Exposed to `TERMINAL.ELF` and `/dev/ptmx` openers
+/// through `FdSlot::PtyMaster`. All operations require briefly locking the
+/// inner mutex.
 #[derive(Clone)]
 pub struct PtyMaster {
     inner: Arc<Mutex<PtyInner>>,
+    /// Present for a `/dev/ptmx` master, whose last close hangs the pty up.
+    open: Option<Arc<PtmxOpen>>,
+}
+
+/// Open-file description of a `/dev/ptmx` master, shared by dup and fork.
+struct PtmxOpen {
+    terminal_id: TerminalId,
+    nonblocking: AtomicBool,
+}
+
+impl Drop for PtmxOpen {
+    fn drop(&mut self) {
+        clear_for_terminal(self.terminal_id);
+        crate::userland::lifecycle::wake_ring3_blocked_on_input(Some(self.terminal_id));
+    }
 }
 
 impl PtyMaster {
-    pub fn terminal_id(&self) -> WindowId {
+    pub fn terminal_id(&self) -> TerminalId {
         self.inner.lock().terminal_id
     }
 
+    /// A window master never blocks: `TERMINAL.ELF` polls it alongside its
+    /// GUI event fd. A `/dev/ptmx` master follows `O_NONBLOCK`.
+    pub fn nonblocking(&self) -> bool {
+        self.open
+            .as_ref()
+            .is_none_or(|open| open.nonblocking.load(Ordering::Acquire))
+    }
+
+    pub fn set_nonblocking(&self, value: bool) {
+        if let Some(open) = &self.open {
+            open.nonblocking.store(value, Ordering::Release);
+        }
+    }
+
+    /// The slave end of the same pty.
+    pub fn slave(&self) -> PtySlave {
+        PtySlave {
+            inner: self.inner.clone(),
+        }
+    }
+
     pub fn with<R>(&self, f: impl FnOnce(&mut PtyInner) -> R) -> R {
         f(&mut self.inner.lock())
     }
@@ -444,7 +552,20 @@ impl PtyMaster {
     /// Bounded drain of the slave's output into `dst` for a ring-3
     /// `read(master_fd)`. Returns the number of bytes copied.
     pub fn read_output(&self, dst: &mut [u8]) -> usize {
-        self.with(|p| p.read_master_output(dst))
+        let (n, was_full) = self.with(|p| {
+            let was_full = p.master_queue.len() >= MAX_QUEUED_BYTES;
+            (p.read_master_output(dst), was_full)
+        });
+        if was_full && n > 0 {
+            // A slave writer may be parked on the full queue.
+            crate::userland::readiness::notify_changed();
+        }
+        n
+    }
+
+    /// Free space in the slave's input queue, for master-fd write readiness.
+    pub fn input_room(&self) -> usize {
+        self.with(|p| MAX_QUEUED_BYTES.saturating_sub(p.slave_queue.len()))
     }
 
     /// True iff both handles reference the same underlying pty (dup/fork
@@ -493,12 +614,24 @@ pub struct PtySlave {
 }
 
 impl PtySlave {
+    pub fn terminal_id(&self) -> TerminalId {
+        self.with(|p| p.terminal_id)
+    }
+
     pub fn with<R>(&self, f: impl FnOnce(&mut PtyInner) -> R) -> R {
         f(&mut self.inner.lock())
     }
 
     pub fn read(&self, dst: &mut [u8]) -> usize {
-        self.with(|p| p.slave_read(dst))
+        let (n, was_full) = self.with(|p| {
+            let was_full = p.slave_queue.len() >= MAX_QUEUED_BYTES;
+            (p.slave_read(dst), was_full)
+        });
+        if was_full && n > 0 {
+            // A blocking master writer may be parked on the full queue.
+            crate::userland::readiness::notify_changed();
+        }
+        n
     }
 
     /// Consume a pending canonical-mode EOF (VEOF typed on an empty
@@ -512,6 +645,11 @@ impl PtySlave {
         self.with(|p| p.slave_readable())
     }
 
+    /// Free space in the master's output queue, for slave-fd write readiness.
+    pub fn output_room(&self) -> usize {
+        self.with(|p| MAX_QUEUED_BYTES.saturating_sub(p.master_queue.len()))
+    }
+
     pub fn write(&self, src: &[u8]) -> usize {
         let consumed = self.with(|p| p.slave_write(src));
         if consumed > 0 {
@@ -537,67 +675,148 @@ impl PtySlave {
 }
 
 // ---------------------------------------------------------------------
-// Registry — per-WindowId pty
+// Registry — ptys by /dev/pts index
 // ---------------------------------------------------------------------
 
-static REGISTRY: Mutex<BTreeMap<Option<WindowId>, Arc<Mutex<PtyInner>>>> =
-    Mutex::new(BTreeMap::new());
+struct Registry {
+    /// Live ptys. The `None` key is the legacy pty.
+    ptys: BTreeMap<Option<TerminalId>, Arc<Mutex<PtyInner>>>,
+    /// Ptys `pty_open` bound to a ring-3 terminal window.
+    windows: BTreeMap<WindowId, TerminalId>,
+}
+
+impl Registry {
+    /// Lowest free `/dev/pts` index, as Linux allocates them.
+    fn free_id(&self) -> Option<TerminalId> {
+        let mut next = 0;
+        for id in self.ptys.keys().filter_map(|key| *key) {
+            if id.0 != next {
+                break;
+            }
+            next += 1;
+        }
+        (next < MAX_PTYS).then_some(TerminalId(next))
+    }
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    ptys: BTreeMap::new(),
+    windows: BTreeMap::new(),
+});
 
-/// Allocate a pty for `terminal_id` with the given grid size. Returns
-/// the master end. Idempotent: re-registering returns the existing
-/// pty.
-pub fn install_for_terminal(terminal_id: WindowId, rows: u16, cols: u16) -> PtyMaster {
+/// Allocate a pty for the terminal window `window` with the given grid
+/// size. Returns the master end. Idempotent: re-registering returns the
+/// existing pty.
+///
+/// # Panics
+///
+/// If all `MAX_PTYS` indices are taken.
+pub fn install_for_window(window: WindowId, rows: u16, cols: u16) -> PtyMaster {
     let mut reg = REGISTRY.lock();
-    let inner = reg
-        .entry(Some(terminal_id))
-        .or_insert_with(|| Arc::new(Mutex::new(PtyInner::new(terminal_id, rows, cols))));
-    PtyMaster {
-        inner: inner.clone(),
+    if let Some(inner) = reg
+        .windows
+        .get(&window)
+        .and_then(|id| reg.ptys.get(&Some(*id)))
+    {
+        return PtyMaster {
+            inner: inner.clone(),
+            open: None,
+        };
     }
+    let id = reg.free_id().expect("pty indices exhausted");
+    let inner = Arc::new(Mutex::new(PtyInner::new(id, rows, cols)));
+    reg.ptys.insert(Some(id), inner.clone());
+    reg.windows.insert(window, id);
+    PtyMaster { inner, open: None }
 }
 
-/// Tear down the pty for `terminal_id`. Slaves with cached handles
-/// continue to operate on a now-orphaned inner; they'll simply have
-/// nothing waking them and their reads will return 0.
-pub fn clear_for_terminal(terminal_id: WindowId) {
-    REGISTRY.lock().remove(&Some(terminal_id));
+/// The pty bound to the terminal window `window`, if any.
+pub fn terminal_for_window(window: WindowId) -> Option<TerminalId> {
+    REGISTRY.lock().windows.get(&window).copied()
+}
+
+/// Allocate a locked pty for a `/dev/ptmx` open. `None` when every index
+/// is taken.
+pub fn open_ptmx() -> Option<PtyMaster> {
+    let mut reg = REGISTRY.lock();
+    let id = reg.free_id()?;
+    let mut pty = PtyInner::new(id, DEFAULT_ROWS, DEFAULT_COLS);
+    pty.locked = true;
+    let inner = Arc::new(Mutex::new(pty));
+    reg.ptys.insert(Some(id), inner.clone());
+    Some(PtyMaster {
+        inner,
+        open: Some(Arc::new(PtmxOpen {
+            terminal_id: id,
+            nonblocking: AtomicBool::new(false),
+        })),
+    })
+}
+
+/// Tear down the pty `terminal_id` and hang up its slave. Slaves with
+/// cached handles continue to operate on the now-orphaned inner; their
+/// reads return 0.
+pub fn clear_for_terminal(terminal_id: TerminalId) {
+    let removed = {
+        let mut reg = REGISTRY.lock();
+        reg.windows.retain(|_, id| *id != terminal_id);
+        reg.ptys.remove(&Some(terminal_id))
+    };
+    if let Some(inner) = removed {
+        inner.lock().hung_up = true;
+        crate::userland::readiness::notify_changed();
+    }
+}
+
+/// Tear down the pty bound to `window`, returning its id. No-op for a
+/// window without a pty.
+pub fn clear_for_window(window: WindowId) -> Option<TerminalId> {
+    let id = terminal_for_window(window)?;
+    clear_for_terminal(id);
+    Some(id)
 }
 
 /// True iff a pty exists for `terminal_id`.
-pub fn is_active_for_terminal(terminal_id: WindowId) -> bool {
-    REGISTRY.lock().contains_key(&Some(terminal_id))
+pub fn is_active_for_terminal(terminal_id: TerminalId) -> bool {
+    REGISTRY.lock().ptys.contains_key(&Some(terminal_id))
 }
 
 /// Look up the slave handle for a terminal id. Same Arc as the master.
-pub fn slave_for_terminal(terminal_id: WindowId) -> Option<PtySlave> {
+pub fn slave_for_terminal(terminal_id: TerminalId) -> Option<PtySlave> {
     REGISTRY
         .lock()
+        .ptys
         .get(&Some(terminal_id))
         .map(|inner| PtySlave {
             inner: inner.clone(),
         })
 }
 
+/// The `/dev/pts` indices in use, ascending.
+pub fn terminal_ids() -> Vec<TerminalId> {
+    REGISTRY.lock().ptys.keys().filter_map(|key| *key).collect()
+}
+
 /// Write process output to the slave side of `terminal_id`'s pty.
 ///
 /// This is the byte-preserving stdout/stderr route used by the Linux syscall
 /// layer. `None` means the process carries a stale/unbound terminal id;
 /// `Some(n)` reports the source bytes consumed (which may be short at the
 /// bounded output-queue cap).
-pub fn write_slave_for_terminal(terminal_id: WindowId, bytes: &[u8]) -> Option<usize> {
+pub fn write_slave_for_terminal(terminal_id: TerminalId, bytes: &[u8]) -> Option<usize> {
     slave_for_terminal(terminal_id).map(|slave| slave.write(bytes))
 }
 
 /// Install the `None`-keyed legacy queue, used by tests and by ring-3
 /// processes that don't have a terminal yet. The defaults are 80×24.
 pub fn install_legacy() {
-    REGISTRY.lock().entry(None).or_insert_with(|| {
+    REGISTRY.lock().ptys.entry(None).or_insert_with(|| {
         Arc::new(Mutex::new(PtyInner::new(
-            // The None-keyed pty isn't tied to any window — store a
+            // The None-keyed pty has no `/dev/pts` entry — store a
             // placeholder id (which the keyboard waker path won't
             // ever fire on, since input only flows through a real
             // terminal).
-            crate::window::WindowId(0),
+            LEGACY_ID,
             DEFAULT_ROWS,
             DEFAULT_COLS,
         )))
@@ -605,24 +824,25 @@ pub fn install_legacy() {
 }
 
 pub fn clear_legacy() {
-    REGISTRY.lock().remove(&None);
+    REGISTRY.lock().ptys.remove(&None);
 }
 
 pub fn is_active_legacy() -> bool {
-    REGISTRY.lock().contains_key(&None)
+    REGISTRY.lock().ptys.contains_key(&None)
 }
 
 /// Slave handle for the legacy `None`-keyed pty. Used by tests.
 pub fn legacy_slave() -> Option<PtySlave> {
-    REGISTRY.lock().get(&None).map(|inner| PtySlave {
+    REGISTRY.lock().ptys.get(&None).map(|inner| PtySlave {
         inner: inner.clone(),
     })
 }
 
 /// Slave handle for the legacy `None`-keyed pty. Used by tests.
 pub fn legacy_master() -> Option<PtyMaster> {
-    REGISTRY.lock().get(&None).map(|inner| PtyMaster {
+    REGISTRY.lock().ptys.get(&None).map(|inner| PtyMaster {
         inner: inner.clone(),
+        open: None,
     })
 }
 
@@ -640,6 +860,7 @@ pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
         &tests::test_slave_write_drains_via_master,
         &tests::test_set_winsize_returns_change_flag,
         &tests::test_registry_install_and_clear,
+        &tests::test_ptmx_allocates_lowest_free_index,
         &tests::test_queue_caps_at_max,
         &tests::test_per_pty_termios_independent,
         &tests::test_slave_write_translates_lf_to_crlf_under_onlcr,
@@ -651,6 +872,7 @@ pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
         &tests::test_canonical_line_caps_at_max_canon,
         &tests::test_veof_flushes_partial_line_and_signals_eof,
         &tests::test_vintr_discards_line_and_reports_sigint,
+        &tests::test_vsusp_reports_sigtstp,
         &tests::test_vkill_erases_whole_line,
     ]
 }
@@ -660,8 +882,7 @@ mod tests {
     use super::*;
 
     fn fresh_master() -> PtyMaster {
-        let id = WindowId::new();
-        install_for_terminal(id, 24, 80)
+        install_for_window(WindowId::new(), 24, 80)
     }
 
     // Line discipline: the ring-3 emulator's `push_slave_input` applies
@@ -779,6 +1000,21 @@ mod tests {
         clear_for_terminal(id);
     }
 
+    pub(super) fn test_vsusp_reports_sigtstp() {
+        let m = fresh_master();
+        let id = m.terminal_id();
+        let r = m.with(|p| p.push_slave_input(b"sleep\x1a"));
+        assert_eq!(r.signal, Some(crate::userland::signal::SIGTSTP));
+        assert_eq!(&m.drain_output()[..], b"sleep^Z\r\n");
+        // Without ISIG, ^Z is an ordinary character.
+        let mut t = m.termios();
+        t.c_lflag &= !ISIG;
+        m.set_termios(t);
+        let r = m.with(|p| p.push_slave_input(b"\x1a"));
+        assert_eq!(r.signal, None);
+        clear_for_terminal(id);
+    }
+
     pub(super) fn test_vkill_erases_whole_line() {
         let m = fresh_master();
         let id = m.terminal_id();
@@ -811,8 +1047,8 @@ mod tests {
     }
 
     pub(super) fn test_master_slave_share_state() {
-        let id = WindowId::new();
-        let m = install_for_terminal(id, 24, 80);
+        let m = install_for_window(WindowId::new(), 24, 80);
+        let id = m.terminal_id();
         let s = slave_for_terminal(id).unwrap();
         let mut t = m.termios();
         t.c_lflag &= !ICANON;
@@ -859,12 +1095,38 @@ mod tests {
     }
 
     pub(super) fn test_registry_install_and_clear() {
-        let id = WindowId::new();
-        assert!(!is_active_for_terminal(id));
-        install_for_terminal(id, 24, 80);
+        let window = WindowId::new();
+        assert_eq!(terminal_for_window(window), None);
+        let id = install_for_window(window, 24, 80).terminal_id();
         assert!(is_active_for_terminal(id));
-        clear_for_terminal(id);
+        assert_eq!(install_for_window(window, 24, 80).terminal_id(), id);
+        let s = slave_for_terminal(id).unwrap();
+        assert_eq!(clear_for_window(window), Some(id));
         assert!(!is_active_for_terminal(id));
+        assert_eq!(terminal_for_window(window), None);
+        assert!(s.with(|p| p.hung_up()), "teardown hangs up the slave");
+    }
+
+    pub(super) fn test_ptmx_allocates_lowest_free_index() {
+        let first = open_ptmx().unwrap();
+        let second = open_ptmx().unwrap();
+        let (a, b) = (first.terminal_id(), second.terminal_id());
+        assert!(a < b);
+        assert!(first.with(|p| p.locked()), "ptmx ptys start locked");
+        let slave = slave_for_terminal(a).unwrap();
+        // Closing the only master description frees the index.
+        drop(first);
+        assert!(slave.with(|p| p.hung_up()));
+        assert!(!is_active_for_terminal(a));
+        let third = open_ptmx().unwrap();
+        assert_eq!(third.terminal_id(), a, "the freed index is reused");
+        // A dup'd master keeps the pty alive.
+        let dup = second.clone();
+        drop(second);
+        assert!(is_active_for_terminal(b));
+        drop(dup);
+        assert!(!is_active_for_terminal(b));
+        drop(third);
     }
 
     pub(super) fn test_queue_caps_at_max() {
@@ -874,6 +1136,7 @@ mod tests {
         // Push 2x the cap; only MAX_QUEUED_BYTES should land.
         let blob = alloc::vec![b'x'; MAX_QUEUED_BYTES * 2];
         m.push_input(&blob);
+        assert_eq!(m.input_room(), 0, "a full queue reports no room");
         // Drain in chunks to count.
         let mut total = 0usize;
         let mut buf = [0u8; 4096];
@@ -885,6 +1148,7 @@ mod tests {
             total += n;
         }
         assert_eq!(total, MAX_QUEUED_BYTES);
+        assert_eq!(m.input_room(), MAX_QUEUED_BYTES);
         clear_for_terminal(id);
     }
 
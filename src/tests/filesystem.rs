@@ -1209,6 +1209,37 @@ fn test_tmp_directory_provisioned_and_writable() {
     crate::fs::vfs::vfs_unlink(path).expect("cleanup temp probe");
 }
 
+/// A lazy unmount with an open file keeps the filesystem, and its source,
+/// in use until the file closes; only then is the source free again.
+fn test_detached_mount_lives_until_last_close() {
+    use crate::fs::filesystem::FilesystemError;
+    use crate::fs::vfs;
+
+    const TARGET: &str = "/tmp/detach-probe";
+    const SOURCE: &str = "detach-probe";
+    vfs::vfs_mkdir(TARGET).expect("create mount point");
+    vfs::mount_tmpfs(SOURCE, TARGET, false).expect("mount tmpfs");
+    let file = crate::fs::File::create("/tmp/detach-probe/open").expect("create inside mount");
+
+    assert_eq!(vfs::unmount(TARGET, false), Err(FilesystemError::Busy));
+    vfs::unmount(TARGET, true).expect("lazy unmount");
+    assert!(!vfs::get_vfs()
+        .list_mounts()
+        .any(|mount| mount.path == TARGET));
+    assert!(
+        vfs::source_in_use(SOURCE),
+        "the detached filesystem still holds its source"
+    );
+    assert_eq!(file.write(b"still here").expect("write after detach"), 10);
+
+    drop(file);
+    assert!(
+        !vfs::source_in_use(SOURCE),
+        "the last close frees the detached filesystem"
+    );
+    vfs::vfs_rmdir(TARGET).expect("cleanup mount point");
+}
+
 fn wait_for_filesystem_tick() {
     let start = crate::arch::x86_64::interrupts::get_timer_ticks();
     while crate::arch::x86_64::interrupts::get_timer_ticks() == start {
@@ -1270,6 +1301,7 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_work_directory_provisioned_and_writable,
         &test_root_home_provisioned_and_writable,
         &test_tmp_directory_provisioned_and_writable,
+        &test_detached_mount_lives_until_last_close,
         &test_work_timestamps_support_incremental_build_ordering,
         &test_seek_past_eof_tmpfs_zero_fill,
         &test_seek_past_eof_data_zero_fill,
@@ -345,12 +345,12 @@ fn test_gui_cleanup_releases_pid_state() {
         },
     )
     .expect("register");
-    let _master = crate::terminal::pty::install_for_terminal(surface_id, 24, 80);
-    assert!(crate::terminal::pty::is_active_for_terminal(surface_id));
+    let _master = crate::terminal::pty::install_for_window(surface_id, 24, 80);
+    assert!(crate::terminal::pty::terminal_for_window(surface_id).is_some());
     assert_eq!(gui::window_count_for_test(pid), 1);
     gui::cleanup_process(pid);
     assert_eq!(gui::window_count_for_test(pid), 0);
-    assert!(!crate::terminal::pty::is_active_for_terminal(surface_id));
+    assert_eq!(crate::terminal::pty::terminal_for_window(surface_id), None);
 }
 
 fn test_retired_terminal_spawn_syscall_is_enosys() {
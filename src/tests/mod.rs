@@ -136,6 +136,7 @@ static MODULES: &[(&str, GetTestsFn)] = &[
     ("memory", memory::get_tests),
     ("network", network::get_tests),
     ("resolver", crate::net::resolver_tests),
+    ("sntp", crate::net::sntp_tests),
     ("network_userland", network_userland::get_tests),
     ("git_userland", git_userland::get_tests),
     ("entropy", entropy::get_tests),
@@ -179,6 +180,33 @@ static MODULES: &[(&str, GetTestsFn)] = &[
         "bin_namespace",
         crate::userland::bin_namespace::bin_namespace_tests,
     ),
+    ("shebang", crate::userland::shebang::shebang_tests),
+    ("memfd", crate::userland::memfd::memfd_tests),
+    (
+        "job_control",
+        crate::userland::job_control::job_control_tests,
+    ),
+    ("rlimit", crate::userland::rlimit::rlimit_tests),
+    ("coredump", crate::userland::coredump::coredump_tests),
+    ("ptrace", crate::userland::ptrace::ptrace_tests),
+    ("timerfd", crate::userland::timerfd::timerfd_tests),
+    ("vdso", crate::userland::vdso::vdso_tests),
+    ("signalfd", crate::userland::signalfd::signalfd_tests),
+    ("inotify", crate::userland::inotify::inotify_tests),
+    (
+        "unix_socket",
+        crate::userland::unix_socket::unix_socket_tests,
+    ),
+    ("pts", crate::userland::pts::pts_tests),
+    (
+        "credentials",
+        crate::userland::credentials::credentials_tests,
+    ),
+    ("mount", crate::userland::mount::mount_tests),
+    ("statfs", crate::userland::statfs::statfs_tests),
+    ("namespace", crate::userland::namespace::namespace_tests),
+    ("priority", crate::userland::priority::priority_tests),
+    ("seccomp", crate::userland::seccomp::seccomp_tests),
     ("clipboard", clipboard::get_tests),
     (
         "gui_launch_table",
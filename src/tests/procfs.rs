@@ -21,6 +21,7 @@ fn synthetic_process(pid: u32) -> Process {
     Process {
         pid,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(pid),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -29,11 +30,15 @@ fn synthetic_process(pid: u32) -> Process {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         sleep_deadline: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         pending_syscall_interrupt: false,
         cwd: String::from("/"),
+        root: String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -42,6 +47,7 @@ fn synthetic_process(pid: u32) -> Process {
         exe_path: Some(String::from("/host/FAKE.ELF")),
         cmdline: alloc::vec![String::from("fake"), String::from("--flag")],
         utime_ticks: 123,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -199,7 +205,42 @@ fn test_proc_meminfo_shape() {
     assert!(text.contains("KernelHeapUsed:"));
 }
 
-/// `/proc/stat` has one row per online CPU and an exact aggregate.
+/// `/proc/mounts` lists the overlay root in `getmntent` format, and
+/// `/proc/filesystems` names every type `mount(2)` takes.
+fn test_proc_mounts_shape() {
+    let content = read_proc_file(b"/proc/mounts\0");
+    let text = core::str::from_utf8(&content).expect("mounts is ASCII");
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split(' ').collect();
+        assert_eq!(fields.len(), 6, "bad line: {}", line);
+        assert!(fields[3] == "rw" || fields[3] == "ro", "bad line: {}", line);
+        assert_eq!(&fields[4..], ["0", "0"]);
+    }
+    assert!(
+        text.lines()
+            .any(|line| line.starts_with("overlay / overlay ")),
+        "got: {}",
+        text
+    );
+    assert_eq!(read_proc_file(b"/proc/self/mounts\0"), content);
+    assert_eq!(
+        procfs::mount_line("my disk", "/mnt/a b", "vfat", true),
+        "my\\040disk /mnt/a\\040b vfat ro 0 0\n"
+    );
+    assert_eq!(
+        procfs::mount_line("", "/mnt", "tmpfs", false),
+        "none /mnt tmpfs rw 0 0\n"
+    );
+
+    let filesystems = read_proc_file(b"/proc/filesystems\0");
+    let filesystems = core::str::from_utf8(&filesystems).unwrap();
+    for kind in ["\tvfat\n", "\text2\n", "nodev\ttmpfs\n", "nodev\t9p\n"] {
+        assert!(filesystems.contains(kind), "missing {:?}", kind);
+    }
+}
+
+/// `/proc/stat` has one row per online CPU, an exact aggregate and one
+/// run-queue depth per CPU.
 fn test_proc_stat_shape() {
     let content = read_proc_file(b"/proc/stat\0");
     let text = core::str::from_utf8(&content).expect("stat is ASCII");
@@ -241,6 +282,19 @@ fn test_proc_stat_shape() {
         },
     );
     assert_eq!(aggregate.expect("aggregate cpu row"), sum);
+
+    let depths = text
+        .lines()
+        .find_map(|line| line.strip_prefix("runqueue "))
+        .expect("per-CPU run-queue depths");
+    assert_eq!(
+        depths
+            .split_whitespace()
+            .map(|depth| depth.parse::<u64>().expect("queue depth"))
+            .count(),
+        per_cpu.len(),
+        "one run-queue depth per cpuN row"
+    );
 }
 
 /// getdents64 on /proc enumerates the static files, subdirs, and live
@@ -285,6 +339,7 @@ fn test_proc_getdents_root() {
     for expected in [
         "uptime",
         "meminfo",
+        "mounts",
         "stat",
         "agenticos",
         "net",
@@ -480,11 +535,14 @@ fn test_sleeper_wake_at_deadline() {
     use crate::userland::lifecycle::{process_expired_sleeps, Ring3BlockReason};
     const DUE: u32 = 700006;
     const NOT_DUE: u32 = 700007;
-    let now = crate::arch::x86_64::interrupts::get_timer_ticks();
+    let now = crate::time::monotonic_ns();
 
     insert_synthetic(DUE);
     insert_synthetic(NOT_DUE);
-    for (pid, deadline) in [(DUE, now.saturating_sub(1)), (NOT_DUE, now + 10_000)] {
+    for (pid, deadline) in [
+        (DUE, now.saturating_sub(1)),
+        (NOT_DUE, now + 100_000_000_000),
+    ] {
         {
             let mut g = PROCESS_TABLE.lock();
             g.by_pid.get_mut(&pid).unwrap().sleep_deadline = Some(deadline);
@@ -496,7 +554,7 @@ fn test_sleeper_wake_at_deadline() {
         crate::userland::lifecycle::mark_ring3_blocked(
             pid,
             Ring3BlockReason::Sleeping {
-                deadline_tick: deadline,
+                deadline_ns: deadline,
             },
         );
     }
@@ -600,6 +658,7 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_proc_pid_classify_liveness,
         &test_proc_uptime_read,
         &test_proc_meminfo_shape,
+        &test_proc_mounts_shape,
         &test_proc_stat_shape,
         &test_proc_getdents_root,
         &test_proc_pid_files,
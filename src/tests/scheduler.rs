@@ -1,5 +1,7 @@
+use alloc::vec::Vec;
+
 use crate::lib::test_utils::Testable;
-use crate::process::entity::{EntityId, LatencyContract, RunState};
+use crate::process::entity::{EntityId, LatencyContract, RunState, SchedPolicy};
 use crate::process::run_queue::RunQueue;
 use crate::process::scheduler::Scheduler;
 
@@ -117,6 +119,118 @@ fn test_preemption_without_alternative_keeps_current_private() {
     assert_eq!(scheduler.ready_entity_count(), 0);
 }
 
+fn test_nice_weight_scales_cpu_share() {
+    let mut scheduler = Scheduler::new();
+    scheduler.init();
+    for pid in [221, 222, 223] {
+        scheduler.register_user(pid).unwrap();
+        scheduler
+            .make_ready(EntityId::UserProcess(pid), None)
+            .unwrap();
+    }
+    scheduler.set_nice(EntityId::UserProcess(223), 10).unwrap();
+    assert_eq!(scheduler.nice(EntityId::UserProcess(223)), Some(10));
+    scheduler.set_nice(EntityId::UserProcess(222), 40).unwrap();
+    assert_eq!(scheduler.nice(EntityId::UserProcess(222)), Some(19));
+    scheduler.set_nice(EntityId::UserProcess(222), 0).unwrap();
+
+    let mut picks = [0u32; 3];
+    let mut current = scheduler.schedule_entity().unwrap();
+    for _ in 0..60 {
+        let next = scheduler.preempt_and_pick(current).unwrap();
+        scheduler.publish_context(current);
+        current = next;
+        let EntityId::UserProcess(pid) = current else {
+            panic!("idle picked over ready user entities");
+        };
+        picks[(pid - 221) as usize] += 1;
+    }
+    assert!(
+        picks[2] * 4 < picks[0] && picks[2] * 4 < picks[1],
+        "nice 10 must get a fraction of nice 0's ticks: {picks:?}"
+    );
+    assert!(picks[0].abs_diff(picks[1]) <= 2);
+}
+
+/// Tick `current` `ticks` times, returning the entity picked after each.
+fn run_ticks(scheduler: &mut Scheduler, mut current: EntityId, ticks: u64) -> Vec<EntityId> {
+    let mut picks = Vec::new();
+    for _ in 0..ticks {
+        let next = scheduler.preempt_and_pick(current).unwrap();
+        if next != current {
+            scheduler.publish_context(current);
+        }
+        current = next;
+        picks.push(current);
+    }
+    picks
+}
+
+fn test_fifo_runs_ahead_until_throttled() {
+    use crate::process::scheduler::RT_RUNTIME_TICKS;
+
+    let mut scheduler = Scheduler::new();
+    scheduler.init();
+    for pid in [231, 232] {
+        scheduler.register_user(pid).unwrap();
+        scheduler
+            .make_ready(EntityId::UserProcess(pid), None)
+            .unwrap();
+    }
+    let (normal, fifo) = (EntityId::UserProcess(231), EntityId::UserProcess(232));
+    scheduler
+        .set_policy(fifo, SchedPolicy::Fifo, 50, false)
+        .unwrap();
+    assert_eq!(scheduler.policy(fifo), Some((SchedPolicy::Fifo, 50)));
+
+    let current = scheduler.schedule_entity().unwrap();
+    assert_eq!(current, fifo, "real time must run ahead of the queue head");
+    let picks = run_ticks(&mut scheduler, current, RT_RUNTIME_TICKS + 2);
+    let budget = RT_RUNTIME_TICKS as usize - 1;
+    assert!(
+        picks[..budget].iter().all(|id| *id == fifo),
+        "a FIFO task keeps the CPU over normal tasks"
+    );
+    assert!(
+        picks[budget..].contains(&normal),
+        "the throttle must hand normal tasks the rest of the period"
+    );
+}
+
+fn test_round_robin_rotates_equal_priorities() {
+    use crate::process::scheduler::RR_TIMESLICE_TICKS;
+
+    let mut scheduler = Scheduler::new();
+    scheduler.init();
+    for pid in [233, 234, 235] {
+        scheduler.register_user(pid).unwrap();
+        scheduler
+            .make_ready(EntityId::UserProcess(pid), None)
+            .unwrap();
+    }
+    for pid in [233, 234] {
+        scheduler
+            .set_policy(
+                EntityId::UserProcess(pid),
+                SchedPolicy::RoundRobin,
+                10,
+                false,
+            )
+            .unwrap();
+    }
+
+    let current = scheduler.schedule_entity().unwrap();
+    assert_eq!(current, EntityId::UserProcess(233));
+    let picks = run_ticks(&mut scheduler, current, 3 * RR_TIMESLICE_TICKS);
+    let slice = RR_TIMESLICE_TICKS as usize;
+    assert!(picks[..slice - 1]
+        .iter()
+        .all(|id| *id == EntityId::UserProcess(233)));
+    assert_eq!(picks[slice - 1], EntityId::UserProcess(234));
+    assert_eq!(picks[2 * slice - 1], EntityId::UserProcess(233));
+    assert!(!picks.contains(&EntityId::UserProcess(235)));
+}
+
 fn test_user_affinity_skips_wrong_cpu_without_losing_queue_entry() {
     let mut scheduler = Scheduler::new();
     scheduler.init();
@@ -133,6 +247,69 @@ fn test_user_affinity_skips_wrong_cpu_without_losing_queue_entry() {
     assert_eq!(scheduler.pop_next_user(), Some(tid));
 }
 
+/// Queue `pids` on `cpu`'s ready queue while leaving them free to run
+/// anywhere.
+fn queue_on(scheduler: &mut Scheduler, cpu: usize, pids: core::ops::RangeInclusive<u32>) {
+    for pid in pids {
+        let entity = EntityId::UserProcess(pid);
+        scheduler.register_user(pid).unwrap();
+        scheduler.set_cpu_affinity(entity, Some(cpu)).unwrap();
+        scheduler.make_ready(entity, None).unwrap();
+        scheduler.set_cpu_affinity(entity, None).unwrap();
+    }
+}
+
+fn test_idle_cpu_steals_from_busiest_queue() {
+    if crate::arch::x86_64::smp::online_cpu_count() == 1 {
+        return;
+    }
+    let mut scheduler = Scheduler::new();
+    scheduler.init();
+    let cpu = crate::arch::x86_64::percpu::cpu_id();
+    let other = (cpu + 1) % crate::arch::x86_64::smp::online_cpu_count();
+    queue_on(&mut scheduler, other, 241..=242);
+    assert_eq!(scheduler.queue_depth(other), 2);
+    assert_eq!(scheduler.queue_depth(cpu), 0);
+
+    let stolen = scheduler.schedule_entity();
+    assert_eq!(stolen, Some(EntityId::UserProcess(241)));
+    assert_eq!(scheduler.queue_depth(other), 1);
+    scheduler.yield_entity(EntityId::UserProcess(241));
+    assert_eq!(
+        scheduler.queue_depth(cpu),
+        1,
+        "a stolen entity waits on the CPU it last ran on"
+    );
+}
+
+fn test_tick_balance_pulls_from_busiest_queue() {
+    if crate::arch::x86_64::smp::online_cpu_count() == 1 {
+        return;
+    }
+    let mut scheduler = Scheduler::new();
+    scheduler.init();
+    let cpu = crate::arch::x86_64::percpu::cpu_id();
+    let other = (cpu + 1) % crate::arch::x86_64::smp::online_cpu_count();
+    scheduler.register_user(251).unwrap();
+    scheduler
+        .make_ready(EntityId::UserProcess(251), None)
+        .unwrap();
+    let current = scheduler.schedule_entity().unwrap();
+    queue_on(&mut scheduler, other, 252..=255);
+
+    let next = scheduler.preempt_and_pick(current);
+    assert_eq!(
+        scheduler.queue_depth(other),
+        2,
+        "balancing evens out the two queues"
+    );
+    assert_eq!(scheduler.queue_depth(cpu), 1);
+    assert!(
+        matches!(next, Some(EntityId::UserProcess(254 | 255))),
+        "the most recently queued entities move: {next:?}"
+    );
+}
+
 fn test_timer_arm_update_and_cancel() {
     use crate::process::timer::{TimerAction, TimerKey, TimerKind, TimerQueue};
 
@@ -192,7 +369,12 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_latency_contract_is_one_shot_override,
         &test_preemption_defers_source_until_context_publish,
         &test_preemption_without_alternative_keeps_current_private,
+        &test_nice_weight_scales_cpu_share,
+        &test_fifo_runs_ahead_until_throttled,
+        &test_round_robin_rotates_equal_priorities,
         &test_user_affinity_skips_wrong_cpu_without_losing_queue_entry,
+        &test_idle_cpu_steals_from_busiest_queue,
+        &test_tick_balance_pulls_from_busiest_queue,
         &test_timer_arm_update_and_cancel,
         &test_timer_heap_orders_and_bounds_a_deferred_pass,
     ]
@@ -10,6 +10,9 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_cross_cpu_kernel_termination_retires_stack,
         &test_cross_cpu_ring3_setup_keeps_cr3_owned,
         &test_cross_cpu_ring3_run_and_exit,
+        &test_stale_translations_merge_per_address_space,
+        &test_cross_cpu_tlb_shootdown_round_trip,
+        &test_tickless_idle_charges_missed_ticks,
     ]
 }
 
@@ -477,3 +480,113 @@ fn test_cross_cpu_ring3_run_and_exit() {
     );
     assert_ne!(RUN_CPU.load(Ordering::Acquire), 0);
 }
+
+fn test_stale_translations_merge_per_address_space() {
+    use crate::arch::x86_64::shootdown::StaleTranslations;
+
+    let mut stale = StaleTranslations::default();
+    stale.note(0x1000);
+    stale.note(0x1000);
+    assert_eq!(stale, StaleTranslations::AddressSpace(0x1000));
+    stale.note(0x2000);
+    assert_eq!(stale, StaleTranslations::AnyAddressSpace);
+    stale.note(0x1000);
+    assert_eq!(stale, StaleTranslations::AnyAddressSpace);
+    stale.flush();
+    assert_eq!(stale, StaleTranslations::None);
+}
+
+fn test_cross_cpu_tlb_shootdown_round_trip() {
+    use crate::arch::x86_64::percpu;
+    use crate::arch::x86_64::shootdown::{self, StaleTranslations};
+    use alloc::string::String;
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    // Page-aligned but above any physical frame, so never a real L4.
+    const FAKE_L4: u64 = 0x000f_ffff_ffff_f000;
+    static READY: AtomicBool = AtomicBool::new(false);
+    static RELEASE: AtomicBool = AtomicBool::new(false);
+    static DONE: AtomicBool = AtomicBool::new(false);
+    static RUN_CPU: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+    if crate::arch::x86_64::smp::online_cpu_count() == 1 {
+        return;
+    }
+
+    READY.store(false, Ordering::Release);
+    RELEASE.store(false, Ordering::Release);
+    DONE.store(false, Ordering::Release);
+    RUN_CPU.store(usize::MAX, Ordering::Release);
+    crate::arch::x86_64::smp::set_test_ap_dispatch_enabled(true);
+    crate::process::spawn_process(String::from("smp-tlb-target"), None, || {
+        RUN_CPU.store(percpu::cpu_id(), Ordering::Release);
+        // Pose as a CPU running a user address space; the shootdown IPI
+        // lands while this spins with interrupts enabled.
+        percpu::set_active_user_l4(FAKE_L4);
+        READY.store(true, Ordering::Release);
+        while !RELEASE.load(Ordering::Acquire) {
+            core::hint::spin_loop();
+        }
+        percpu::set_active_user_l4(0);
+        DONE.store(true, Ordering::Release);
+    });
+
+    let start_timeout = crate::arch::x86_64::interrupts::get_timer_ticks().saturating_add(100);
+    while !READY.load(Ordering::Acquire)
+        && crate::arch::x86_64::interrupts::get_timer_ticks() < start_timeout
+    {
+        x86_64::instructions::hlt();
+    }
+    assert!(READY.load(Ordering::Acquire), "shootdown target never ran");
+    let cpu = RUN_CPU.load(Ordering::Acquire);
+    assert_ne!(cpu, 0);
+
+    let before = percpu::shootdown_completed(cpu);
+    shootdown::flush_address_space(FAKE_L4);
+    let acknowledged = percpu::shootdown_completed(cpu);
+    assert!(acknowledged > before, "target CPU did not acknowledge");
+
+    // A mapper transaction's deferred flush reaches the same CPU.
+    let mut stale = StaleTranslations::default();
+    stale.note(FAKE_L4);
+    stale.flush();
+    assert!(percpu::shootdown_completed(cpu) > acknowledged);
+    // Unrelated address spaces leave the target alone.
+    let untouched = percpu::shootdown_completed(cpu);
+    shootdown::flush_address_space(FAKE_L4 - 0x1000);
+    assert_eq!(percpu::shootdown_completed(cpu), untouched);
+
+    RELEASE.store(true, Ordering::Release);
+    let exit_timeout = crate::arch::x86_64::interrupts::get_timer_ticks().saturating_add(100);
+    while !DONE.load(Ordering::Acquire)
+        && crate::arch::x86_64::interrupts::get_timer_ticks() < exit_timeout
+    {
+        x86_64::instructions::hlt();
+    }
+    crate::arch::x86_64::smp::set_test_ap_dispatch_enabled(false);
+    assert!(DONE.load(Ordering::Acquire));
+    // The target has switched back off the fake address space.
+    shootdown::retire_address_space(FAKE_L4);
+}
+
+fn test_tickless_idle_charges_missed_ticks() {
+    use crate::arch::x86_64::percpu;
+
+    let cpu = percpu::cpu_id();
+    let tick = crate::time::PIT_NANOSECONDS_PER_TICK;
+    let now = crate::time::monotonic_ns();
+    let before = percpu::cpu_time_snapshot(cpu).expect("current CPU counters");
+    let _interrupts = crate::arch::x86_64::interrupt_guard::InterruptGuard::disable();
+    percpu::begin_tickless_idle(now - 3 * tick);
+    let pending = percpu::cpu_time_snapshot(cpu).expect("current CPU counters");
+    assert!(pending.idle >= before.idle + 3);
+    percpu::end_tickless_idle(now);
+    let after = percpu::cpu_time_snapshot(cpu).expect("current CPU counters");
+    assert!(after.idle >= before.idle + 3);
+    percpu::end_tickless_idle(now + 10 * tick);
+    assert_eq!(
+        percpu::cpu_time_snapshot(cpu).unwrap().idle,
+        after.idle,
+        "a CPU already ticking has nothing to charge"
+    );
+}
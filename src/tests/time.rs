@@ -1,4 +1,4 @@
-use crate::arch::x86_64::rtc::{decode_snapshot, RawRtcSnapshot};
+use crate::arch::x86_64::rtc::{decode_snapshot, encode_snapshot, RawRtcSnapshot};
 use crate::lib::test_utils::Testable;
 use crate::time::{datetime_from_unix_seconds, unix_seconds_from_datetime, DateTime};
 
@@ -57,6 +57,36 @@ fn test_century_fallback_and_invalid_encoding() {
     assert!(decode_snapshot(value).is_err());
 }
 
+fn test_encode_inverts_decode() {
+    assert_eq!(
+        encode_snapshot(decode_snapshot(raw(0x15, 0x02)).unwrap(), 0x02),
+        Ok(raw(0x15, 0x02))
+    );
+    for hours in [0x12, 0x92, 0x83, 0x01] {
+        let value = decode_snapshot(raw(hours, 0)).unwrap();
+        assert_eq!(encode_snapshot(value, 0), Ok(raw(hours, 0)));
+    }
+    let binary = DateTime {
+        year: 2026,
+        month: 7,
+        day: 18,
+        hour: 15,
+        minute: 34,
+        second: 56,
+    };
+    let encoded = encode_snapshot(binary, 0x06).unwrap();
+    assert_eq!((encoded.hours, encoded.year, encoded.century), (15, 26, 20));
+    assert_eq!(decode_snapshot(encoded), Ok(binary));
+    assert!(encode_snapshot(
+        DateTime {
+            month: 13,
+            ..binary
+        },
+        0x02
+    )
+    .is_err());
+}
+
 fn test_calendar_validation_and_leap_years() {
     assert!(DateTime {
         year: 2000,
@@ -151,15 +181,156 @@ fn test_live_qemu_rtc_is_plausible() {
     assert!(crate::time::realtime_ns() > 1_500_000_000_000_000_000);
 }
 
+fn test_tsc_clock_has_sub_tick_resolution() {
+    assert!(crate::arch::x86_64::tsc::calibrated());
+    let start = crate::time::monotonic_ns();
+    let mut next = start;
+    while next == start {
+        core::hint::spin_loop();
+        next = crate::time::monotonic_ns();
+    }
+    assert!(next > start);
+    assert!(next - start < crate::time::PIT_NANOSECONDS_PER_TICK);
+}
+
+fn test_jiffies_follow_monotonic_clock() {
+    let ns = crate::time::monotonic_ns();
+    let ticks = crate::arch::x86_64::interrupts::get_timer_ticks();
+    let after = crate::time::monotonic_ns();
+    let tick = crate::time::PIT_NANOSECONDS_PER_TICK;
+    assert!((ns / tick..=after / tick).contains(&ticks));
+}
+
+fn test_set_realtime_steps_the_wall_clock() {
+    const HOUR_NS: u64 = 3_600 * 1_000_000_000;
+    let before = crate::time::realtime_ns();
+    crate::time::set_realtime_ns(before + HOUR_NS);
+    let stepped = crate::time::realtime_ns();
+    assert!(stepped >= before + HOUR_NS && stepped - (before + HOUR_NS) < 1_000_000_000);
+    let anchor = crate::time::realtime_anchor();
+    assert_eq!(anchor.rate, crate::time::REALTIME_RATE_UNIT);
+    let now = crate::time::monotonic_ns();
+    assert_eq!(
+        anchor.realtime_at(now).wrapping_sub(now),
+        anchor.realtime_ns.wrapping_sub(anchor.monotonic_ns)
+    );
+    crate::time::step_realtime_ns(-(HOUR_NS as i64));
+    let restored = crate::time::realtime_ns();
+    assert!(restored >= before && restored - before < 1_000_000_000);
+}
+
+fn test_slew_is_bounded_and_replaceable() {
+    let max = crate::time::MAX_SLEW_NS_PER_TICK;
+    assert_eq!(crate::time::slew_step(1_000_000), max);
+    assert_eq!(crate::time::slew_step(-1_000_000), -max);
+    assert_eq!(crate::time::slew_step(max - 1), max - 1);
+
+    let previous = crate::time::set_slew_ns(1_000_000);
+    let start = crate::arch::x86_64::interrupts::get_timer_ticks();
+    while crate::arch::x86_64::interrupts::get_timer_ticks() < start + 3 {
+        core::hint::spin_loop();
+    }
+    let remaining = crate::time::slew_remaining_ns();
+    let ticks = (crate::arch::x86_64::interrupts::get_timer_ticks() - start + 1) as i64;
+    assert!(remaining < 1_000_000 && remaining >= 1_000_000 - ticks * max);
+    assert_eq!(crate::time::set_slew_ns(previous), remaining);
+}
+
+fn test_negative_slew_keeps_realtime_moving_forward() {
+    let previous = crate::time::set_slew_ns(-1_000_000);
+    let anchor = crate::time::realtime_anchor();
+    assert!(anchor.rate < crate::time::REALTIME_RATE_UNIT);
+    assert!(anchor.rate > 0);
+
+    let start = crate::arch::x86_64::interrupts::get_timer_ticks();
+    let mut last = crate::time::realtime_ns();
+    while crate::arch::x86_64::interrupts::get_timer_ticks() < start + 3 {
+        let now = crate::time::realtime_ns();
+        assert!(now >= last, "realtime went back from {last} to {now}");
+        last = now;
+    }
+    assert!(crate::time::slew_remaining_ns() > -1_000_000);
+    crate::time::set_slew_ns(previous);
+}
+
+fn test_adjtimex_reads_clock_state() {
+    use crate::arch::x86_64::syscall::SyscallArgs;
+    use crate::userland::abi::{self, EINVAL};
+
+    let mut timex = [0u8; 208];
+    let pointer = timex.as_mut_ptr() as u64;
+    abi::set_user_va_bounds(abi::UserVaBounds {
+        start: pointer,
+        end: pointer + timex.len() as u64,
+    });
+    // ADJ_OFFSET_SS_READ needs no privilege.
+    timex[..4].copy_from_slice(&0xa001u32.to_ne_bytes());
+    let mut args = SyscallArgs::default();
+    args.rdi = pointer;
+    let state = crate::userland::syscalls::adjtimex_handler(&mut args);
+    let tick = i64::from_ne_bytes(timex[88..96].try_into().unwrap());
+    let seconds = i64::from_ne_bytes(timex[72..80].try_into().unwrap());
+    // ADJ_FREQUENCY is refused: there is no PLL.
+    timex[..4].copy_from_slice(&0x0002u32.to_ne_bytes());
+    let refused = crate::userland::syscalls::adjtimex_handler(&mut args);
+    abi::clear_user_va_bounds();
+    assert!(state == 0 || state == 5);
+    assert_eq!(tick, 10_000);
+    assert!(seconds > 1_500_000_000);
+    assert_eq!(refused, EINVAL);
+}
+
+fn test_settime_rejects_unrepresentable_times() {
+    use crate::arch::x86_64::syscall::SyscallArgs;
+    use crate::userland::abi::{self, EINVAL};
+
+    // Just past u64::MAX nanoseconds, and the i64 extremes.
+    let first_overflow = (u64::MAX / 1_000_000_000 + 1) as i64;
+    let mut time = [0i64; 2];
+    let pointer = time.as_mut_ptr() as u64;
+    abi::set_user_va_bounds(abi::UserVaBounds {
+        start: pointer,
+        end: pointer + core::mem::size_of_val(&time) as u64,
+    });
+    let before = crate::time::realtime_ns();
+    let mut results = alloc::vec::Vec::new();
+    for seconds in [first_overflow, i64::MAX, -1, i64::MIN] {
+        // SAFETY: `pointer` addresses `time`, which outlives the loop.
+        unsafe { core::ptr::write_volatile(pointer as *mut [i64; 2], [seconds, 999_999]) };
+        let mut args = SyscallArgs::default();
+        args.rdi = 0; // CLOCK_REALTIME
+        args.rsi = pointer;
+        results.push(crate::userland::syscalls::clock_settime_handler(&mut args));
+        let mut args = SyscallArgs::default();
+        args.rdi = pointer;
+        results.push(crate::userland::syscalls::settimeofday_handler(&mut args));
+    }
+    abi::clear_user_va_bounds();
+    let after = crate::time::realtime_ns();
+    assert!(
+        results.iter().all(|&result| result == EINVAL),
+        "{results:?}"
+    );
+    assert!(after >= before && after - before < 1_000_000_000);
+}
+
 pub fn get_tests() -> &'static [&'static dyn Testable] {
     &[
         &test_bcd_24_hour_decode,
         &test_binary_24_hour_decode,
         &test_12_hour_decode_edges,
+        &test_encode_inverts_decode,
         &test_century_fallback_and_invalid_encoding,
         &test_calendar_validation_and_leap_years,
         &test_unix_epoch_and_known_timestamp,
         &test_calendar_round_trips_boundaries,
         &test_live_qemu_rtc_is_plausible,
+        &test_tsc_clock_has_sub_tick_resolution,
+        &test_jiffies_follow_monotonic_clock,
+        &test_set_realtime_steps_the_wall_clock,
+        &test_slew_is_bounded_and_replaceable,
+        &test_negative_slew_keeps_realtime_moving_forward,
+        &test_adjtimex_reads_clock_state,
+        &test_settime_rejects_unrepresentable_times,
     ]
 }
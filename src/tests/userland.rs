@@ -6,7 +6,7 @@ use crate::mm::paging::{
 use crate::tests::userland_fixtures as fix;
 use crate::userland::abi::{
     self, nr, syscall_dispatch, validate_user_slice, UserVaBounds, EAGAIN, EBADF, EFAULT, EINVAL,
-    ENOENT, ENOSYS, ENOTTY, EPERM, ERANGE, EROFS, LAST_EXIT_CODE,
+    ENOENT, ENOSYS, ENOTTY, EPERM, ERANGE, EROFS, ESRCH, LAST_EXIT_CODE,
 };
 use crate::userland::error::LoaderError;
 use crate::userland::fdtable::{FdSlot, FdTable};
@@ -832,6 +832,142 @@ fn test_dispatch_prlimit64_null_old_returns_zero() {
     assert_eq!(ret, 0);
 }
 
+/// Set the soft and hard `resource` limit of the active user through
+/// `setrlimit`.
+fn dispatch_setrlimit(resource: usize, value: u64) {
+    let limit = crate::userland::rlimit::Rlimit {
+        rlim_cur: value,
+        rlim_max: value,
+    };
+    let ptr = &limit as *const _ as u64;
+    abi::set_user_va_bounds(UserVaBounds {
+        start: ptr,
+        end: ptr + core::mem::size_of_val(&limit) as u64,
+    });
+    let mut args = SyscallArgs::default();
+    args.rax = nr::SETRLIMIT;
+    args.rdi = resource as u64;
+    args.rsi = ptr;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+    abi::clear_user_va_bounds();
+}
+
+/// `RLIMIT_NOFILE` bounds the descriptor a `dup` may take: `EMFILE` past
+/// it.
+fn test_rlimit_nofile_fails_dup_with_emfile() {
+    use crate::userland::abi::EMFILE;
+    use crate::userland::rlimit::{RLIMIT_NOFILE, RLIM_INFINITY};
+    setup_phase2_active_user();
+    dispatch_setrlimit(RLIMIT_NOFILE, 4);
+    let dup = || {
+        let mut args = SyscallArgs::default();
+        args.rax = nr::DUP;
+        args.rdi = 0;
+        syscall_dispatch(&mut args)
+    };
+    assert_eq!(dup(), 3);
+    assert_eq!(dup(), EMFILE);
+    dispatch_setrlimit(RLIMIT_NOFILE, RLIM_INFINITY);
+    teardown_phase2_active_user();
+}
+
+/// `RLIMIT_AS` fails an anonymous `mmap` that would cross it with
+/// `ENOMEM`.
+fn test_rlimit_as_fails_mmap_with_enomem() {
+    use crate::userland::abi::ENOMEM;
+    use crate::userland::rlimit::{RLIMIT_AS, RLIM_INFINITY};
+    const PROT_READ: u64 = 0x1;
+    const PROT_WRITE: u64 = 0x2;
+    const MAP_PRIVATE: u64 = 0x2;
+    const MAP_ANONYMOUS: u64 = 0x20;
+
+    setup_phase2_active_user();
+    let space = crate::userland::address_space::AddressSpace::new().expect("AddressSpace::new");
+    let previous_space = crate::userland::lifecycle::with_active_user(|au| {
+        core::mem::replace(&mut au.address_space, Some(space))
+    });
+    dispatch_setrlimit(RLIMIT_AS, 0x4000);
+    let mmap = |len: u64| {
+        let mut args = SyscallArgs::default();
+        args.rax = nr::MMAP;
+        args.rsi = len;
+        args.rdx = PROT_READ | PROT_WRITE;
+        args.r10 = MAP_PRIVATE | MAP_ANONYMOUS;
+        args.r8 = u64::MAX;
+        syscall_dispatch(&mut args)
+    };
+    let mapping = mmap(0x1000);
+    assert!(mapping > 0, "mmap under RLIMIT_AS failed: {}", mapping);
+    assert_eq!(mmap(0x4000), ENOMEM);
+
+    dispatch_setrlimit(RLIMIT_AS, RLIM_INFINITY);
+    let space = crate::userland::lifecycle::with_active_user(|au| {
+        core::mem::replace(&mut au.address_space, previous_space)
+    });
+    drop(space);
+    teardown_phase2_active_user();
+}
+
+/// `RLIMIT_FSIZE` shortens a `write` that crosses it; one that starts at
+/// the limit fails with `EFBIG` and leaves `SIGXFSZ` pending on the
+/// writer.
+fn test_rlimit_fsize_fails_write_with_efbig_and_sigxfsz() {
+    use crate::userland::abi::EFBIG;
+    use crate::userland::rlimit::{RLIMIT_FSIZE, RLIM_INFINITY};
+    use crate::userland::signal::SIGXFSZ;
+    let xfsz_pending =
+        || crate::userland::lifecycle::with_current_process(|p| p.signal_state.is_pending(SIGXFSZ));
+
+    setup_phase2_active_user();
+    dispatch_setrlimit(RLIMIT_FSIZE, 10);
+    let path = b"/fsize.tmp\0";
+    let payload = [b'x'; 16];
+    let path_ptr = path.as_ptr() as u64;
+    let payload_ptr = payload.as_ptr() as u64;
+    abi::set_user_va_bounds(UserVaBounds {
+        start: core::cmp::min(path_ptr, payload_ptr),
+        end: core::cmp::max(
+            path_ptr + path.len() as u64,
+            payload_ptr + payload.len() as u64,
+        ),
+    });
+
+    let mut open = SyscallArgs::default();
+    open.rax = nr::OPEN;
+    open.rdi = path_ptr;
+    open.rsi = 0x241; // O_WRONLY|O_CREAT|O_TRUNC
+    let fd = syscall_dispatch(&mut open);
+    assert!(fd >= 0, "open for write failed: {}", fd);
+    let write = || {
+        let mut args = SyscallArgs::default();
+        args.rax = nr::WRITE;
+        args.rdi = fd as u64;
+        args.rsi = payload_ptr;
+        args.rdx = payload.len() as u64;
+        syscall_dispatch(&mut args)
+    };
+    assert_eq!(write(), 10, "a write crossing the limit is shortened");
+    assert!(!xfsz_pending());
+    assert_eq!(write(), EFBIG);
+    assert!(xfsz_pending(), "a write past the limit raises SIGXFSZ");
+    crate::userland::lifecycle::with_current_process(|p| {
+        p.signal_state.pending &= !(1 << (SIGXFSZ - 1));
+    });
+
+    let mut close = SyscallArgs::default();
+    close.rax = nr::CLOSE;
+    close.rdi = fd as u64;
+    assert_eq!(syscall_dispatch(&mut close), 0);
+    let mut unlink = SyscallArgs::default();
+    unlink.rax = nr::UNLINK;
+    unlink.rdi = path_ptr;
+    assert_eq!(syscall_dispatch(&mut unlink), 0);
+
+    abi::clear_user_va_bounds();
+    dispatch_setrlimit(RLIMIT_FSIZE, RLIM_INFINITY);
+    teardown_phase2_active_user();
+}
+
 /// `getrusage(RUSAGE_SELF, &usage)` zero-fills the 144-byte rusage struct
 /// and returns 0; `getrusage(99, ...)` rejects an unknown `who` with EINVAL.
 fn test_dispatch_getrusage_self_zero_fills_and_rejects_unknown_who() {
@@ -958,7 +1094,7 @@ fn test_dispatch_nanosleep_validation_and_synthetic_return() {
     // Restart machinery: a re-fired sleep whose absolute deadline has
     // already elapsed observes `now >= sleep_deadline`, reports done,
     // and clears the per-process deadline.
-    let now = crate::arch::x86_64::interrupts::get_timer_ticks();
+    let now = crate::time::monotonic_ns();
     crate::userland::lifecycle::with_current_process(|process| {
         process.sleep_deadline = Some(now);
     });
@@ -1019,8 +1155,8 @@ fn test_write_stdout_to_pty_is_byte_preserving() {
         end: ptr + payload.len() as u64,
     });
 
-    let terminal_id = crate::window::WindowId::new();
-    let master = crate::terminal::pty::install_for_terminal(terminal_id, 24, 80);
+    let master = crate::terminal::pty::install_for_window(crate::window::WindowId::new(), 24, 80);
+    let terminal_id = master.terminal_id();
     let prior_terminal = crate::userland::lifecycle::with_active_user(|process| {
         let prior = process.terminal_id;
         process.terminal_id = Some(terminal_id);
@@ -1319,6 +1455,249 @@ fn test_pwrite_large_and_pread_short() {
     teardown_phase2_active_user();
 }
 
+/// A shared mapping of a read-only descriptor cannot be made writable:
+/// `mprotect(PROT_WRITE)` fails with `EACCES` just as a writable `mmap`
+/// does, so the caller cannot store to a file it could not open for
+/// writing.
+fn test_mprotect_refuses_write_on_read_only_shared_file() {
+    use crate::userland::abi::EACCES;
+    const PROT_READ: u64 = 0x1;
+    const PROT_WRITE: u64 = 0x2;
+    const MAP_SHARED: u64 = 0x1;
+
+    let file = crate::fs::File::create("/tmp/read-only-shared.bin").expect("create fixture");
+    assert_eq!(file.write_at(0, &[b'r'; 0x1000]).expect("fill"), 0x1000);
+    drop(file);
+
+    setup_phase2_active_user();
+    let space = crate::userland::address_space::AddressSpace::new().expect("AddressSpace::new");
+    let previous_space = crate::userland::lifecycle::with_active_user(|au| {
+        core::mem::replace(&mut au.address_space, Some(space))
+    });
+    let path = b"/tmp/read-only-shared.bin\0";
+    let path_ptr = path.as_ptr() as u64;
+    abi::set_user_va_bounds(UserVaBounds {
+        start: path_ptr,
+        end: path_ptr + path.len() as u64,
+    });
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::OPEN;
+    args.rdi = path_ptr;
+    args.rsi = 0; // O_RDONLY
+    let fd = syscall_dispatch(&mut args);
+    assert!(fd >= 0, "open read-only failed: {}", fd);
+    let fd = fd as u64;
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MMAP;
+    args.rsi = 0x1000;
+    args.rdx = PROT_READ | PROT_WRITE;
+    args.r10 = MAP_SHARED;
+    args.r8 = fd;
+    assert_eq!(syscall_dispatch(&mut args), EACCES);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MMAP;
+    args.rsi = 0x1000;
+    args.rdx = PROT_READ;
+    args.r10 = MAP_SHARED;
+    args.r8 = fd;
+    let mapping = syscall_dispatch(&mut args);
+    assert!(mapping > 0, "read-only shared mmap failed: {}", mapping);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MPROTECT;
+    args.rdi = mapping as u64;
+    args.rsi = 0x1000;
+    args.rdx = PROT_READ | PROT_WRITE;
+    assert_eq!(syscall_dispatch(&mut args), EACCES);
+    let prot = crate::userland::lifecycle::with_active_user(|au| {
+        au.address_space
+            .as_ref()
+            .and_then(|space| space.vmas().find(mapping as u64).map(|vma| vma.prot))
+    });
+    assert_eq!(prot, Some(crate::userland::vm::VmProt::READ));
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MPROTECT;
+    args.rdi = mapping as u64;
+    args.rsi = 0x1000;
+    args.rdx = 0;
+    assert_eq!(
+        syscall_dispatch(&mut args),
+        0,
+        "dropping access stays allowed"
+    );
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MUNMAP;
+    args.rdi = mapping as u64;
+    args.rsi = 0x1000;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::CLOSE;
+    args.rdi = fd;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    abi::clear_user_va_bounds();
+    let space = crate::userland::lifecycle::with_active_user(|au| {
+        core::mem::replace(&mut au.address_space, previous_space)
+    });
+    drop(space);
+    teardown_phase2_active_user();
+    crate::fs::vfs::vfs_unlink("/tmp/read-only-shared.bin").expect("cleanup fixture");
+}
+
+/// Seals on a memfd hold at the syscall boundary: `F_SEAL_WRITE` is refused
+/// with `EBUSY` while a writable shared mapping exists, and once added it
+/// turns `write`, `pwrite64`, `ftruncate` and writable `mmap(MAP_SHARED)`
+/// into `EPERM`.
+fn test_memfd_seals_enforced_by_syscalls() {
+    use crate::userland::abi::EBUSY;
+    use crate::userland::memfd::{F_SEAL_SHRINK, F_SEAL_WRITE, MFD_ALLOW_SEALING};
+    const F_ADD_SEALS: u64 = 1033;
+    const F_GET_SEALS: u64 = 1034;
+    const PROT_READ: u64 = 0x1;
+    const PROT_WRITE: u64 = 0x2;
+    const MAP_SHARED: u64 = 0x1;
+
+    setup_phase2_active_user();
+    let space = crate::userland::address_space::AddressSpace::new().expect("AddressSpace::new");
+    let previous_space = crate::userland::lifecycle::with_active_user(|au| {
+        core::mem::replace(&mut au.address_space, Some(space))
+    });
+    let name = b"sealed\0";
+    let payload = [b'x'; 16];
+    let name_ptr = name.as_ptr() as u64;
+    let payload_ptr = payload.as_ptr() as u64;
+    abi::set_user_va_bounds(UserVaBounds {
+        start: core::cmp::min(name_ptr, payload_ptr),
+        end: core::cmp::max(
+            name_ptr + name.len() as u64,
+            payload_ptr + payload.len() as u64,
+        ),
+    });
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MEMFD_CREATE;
+    args.rdi = name_ptr;
+    args.rsi = u64::from(MFD_ALLOW_SEALING);
+    let fd = syscall_dispatch(&mut args);
+    assert!(fd >= 0, "memfd_create failed: {}", fd);
+    let fd = fd as u64;
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::WRITE;
+    args.rdi = fd;
+    args.rsi = payload_ptr;
+    args.rdx = payload.len() as u64;
+    assert_eq!(syscall_dispatch(&mut args), payload.len() as i64);
+
+    // A writable shared mapping blocks F_SEAL_WRITE and leaves no seal.
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MMAP;
+    args.rsi = 0x1000;
+    args.rdx = PROT_READ | PROT_WRITE;
+    args.r10 = MAP_SHARED;
+    args.r8 = fd;
+    let mapping = syscall_dispatch(&mut args);
+    assert!(mapping > 0, "writable shared mmap failed: {}", mapping);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::FCNTL;
+    args.rdi = fd;
+    args.rsi = F_ADD_SEALS;
+    args.rdx = u64::from(F_SEAL_WRITE);
+    assert_eq!(syscall_dispatch(&mut args), EBUSY);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::FCNTL;
+    args.rdi = fd;
+    args.rsi = F_GET_SEALS;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MUNMAP;
+    args.rdi = mapping as u64;
+    args.rsi = 0x1000;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::FCNTL;
+    args.rdi = fd;
+    args.rsi = F_ADD_SEALS;
+    args.rdx = u64::from(F_SEAL_WRITE | F_SEAL_SHRINK);
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::FCNTL;
+    args.rdi = fd;
+    args.rsi = F_GET_SEALS;
+    assert_eq!(
+        syscall_dispatch(&mut args),
+        i64::from(F_SEAL_WRITE | F_SEAL_SHRINK)
+    );
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::WRITE;
+    args.rdi = fd;
+    args.rsi = payload_ptr;
+    args.rdx = payload.len() as u64;
+    assert_eq!(syscall_dispatch(&mut args), EPERM);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::PWRITE64;
+    args.rdi = fd;
+    args.rsi = payload_ptr;
+    args.rdx = 1;
+    args.r10 = 0;
+    assert_eq!(syscall_dispatch(&mut args), EPERM);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::FTRUNCATE;
+    args.rdi = fd;
+    args.rsi = 0;
+    assert_eq!(syscall_dispatch(&mut args), EPERM);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MMAP;
+    args.rsi = 0x1000;
+    args.rdx = PROT_READ | PROT_WRITE;
+    args.r10 = MAP_SHARED;
+    args.r8 = fd;
+    assert_eq!(syscall_dispatch(&mut args), EPERM);
+
+    // A read-only shared mapping is still allowed.
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MMAP;
+    args.rsi = 0x1000;
+    args.rdx = PROT_READ;
+    args.r10 = MAP_SHARED;
+    args.r8 = fd;
+    let mapping = syscall_dispatch(&mut args);
+    assert!(mapping > 0, "read-only shared mmap failed: {}", mapping);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::MUNMAP;
+    args.rdi = mapping as u64;
+    args.rsi = 0x1000;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::CLOSE;
+    args.rdi = fd;
+    assert_eq!(syscall_dispatch(&mut args), 0);
+
+    abi::clear_user_va_bounds();
+    let space = crate::userland::lifecycle::with_active_user(|au| {
+        core::mem::replace(&mut au.address_space, previous_space)
+    });
+    drop(space);
+    teardown_phase2_active_user();
+}
+
 /// BusyBox `cat` copies regular files to stdout with `sendfile(2)`. The
 /// kernel must route that stdout through the issuing process's PTY just like
 /// `write(2)`/`writev(2)`; the legacy `crate::print!` path draws outside the
@@ -1335,8 +1714,8 @@ fn test_sendfile_stdout_routes_to_current_terminal() {
     );
     drop(writer);
 
-    let terminal_id = crate::window::WindowId::new();
-    let master = crate::terminal::pty::install_for_terminal(terminal_id, 24, 80);
+    let master = crate::terminal::pty::install_for_window(crate::window::WindowId::new(), 24, 80);
+    let terminal_id = master.terminal_id();
     let prior_terminal = crate::userland::lifecycle::with_active_user(|process| {
         let prior = process.terminal_id;
         process.terminal_id = Some(terminal_id);
@@ -1371,9 +1750,9 @@ fn test_sendfile_stdout_routes_to_current_terminal() {
     crate::fs::vfs::vfs_unlink(PATH).expect("unlink sendfile fixture");
 }
 
-/// `chmod`/`fchmod` are validated success no-ops: FAT and tmpfs carry no
-/// permission bits and execve performs no +x check. TinyCC chmods its
-/// output executable after writing it.
+/// `chmod`/`fchmod` succeed for root on existing files, including
+/// filesystems without permission bits. TinyCC chmods its output
+/// executable after writing it.
 fn test_dispatch_chmod_fchmod_noops() {
     setup_phase2_active_user();
 
@@ -1750,6 +2129,77 @@ fn test_loader_pt_interp_rejected() {
     );
 }
 
+fn test_loader_interp_path_parsed() {
+    let interp = b"/lib/ld-musl-x86_64.so.1\0";
+    let interp_offset = 0x2000u64;
+    let bytes = fix::Fixture {
+        e_type: fix::ET_EXEC,
+        e_machine: fix::EM_X86_64,
+        ei_class: fix::ELFCLASS64,
+        ei_data: fix::ELFDATA2LSB,
+        e_entry: 0x40_0000,
+        phdrs: vec![
+            fix::PhdrSpec {
+                p_type: fix::PT_LOAD,
+                p_flags: fix::PF_R | fix::PF_X,
+                p_offset: 0x1000,
+                p_vaddr: 0x40_0000,
+                p_filesz: 4,
+                p_memsz: 4,
+                p_align: 0x1000,
+            },
+            fix::PhdrSpec {
+                p_type: fix::PT_INTERP,
+                p_flags: fix::PF_R,
+                p_offset: interp_offset,
+                p_vaddr: 0,
+                p_filesz: interp.len() as u64,
+                p_memsz: interp.len() as u64,
+                p_align: 1,
+            },
+        ],
+        payloads: vec![(0x1000, vec![0x90u8; 4]), (interp_offset, interp.to_vec())],
+        truncate_to: None,
+    }
+    .build();
+    assert_eq!(
+        crate::userland::loader::interp_path(&bytes)
+            .unwrap()
+            .as_deref(),
+        Some("/lib/ld-musl-x86_64.so.1")
+    );
+    assert_eq!(
+        crate::userland::loader::interp_path(&fix::happy_path_elf()).unwrap(),
+        None
+    );
+    // The eager loader has no file to back the interpreter with.
+    assert_eq!(
+        load_elf(&bytes).unwrap_err(),
+        LoaderError::InterpUnsupported
+    );
+}
+
+fn test_loader_pie_loads_at_bias() {
+    let mut bytes = fix::happy_path_elf();
+    fix::write_u16(&mut bytes, 16, fix::ET_DYN);
+    fix::write_u64(&mut bytes, 24, 0x8); // e_entry
+    fix::write_u64(&mut bytes, 64 + 16, 0); // p_vaddr
+    let image = load_elf(&bytes).expect("load_elf PIE");
+    let base = crate::mm::paging::USER_PIE_LOAD_BASE;
+
+    assert_eq!(image.entry.as_u64(), base + 0x8);
+    assert_eq!(image.program_entry, image.entry);
+    assert_eq!(image.interp_base, None);
+    // The fixture's phdrs sit outside every PT_LOAD.
+    assert_eq!(image.phdr_va, None);
+    unsafe {
+        let p = base as *const u8;
+        for i in 0..16u8 {
+            assert_eq!(*p.add(i as usize), i);
+        }
+    }
+}
+
 fn test_loader_segment_overflow() {
     let mut bytes = fix::happy_path_elf();
     fix::write_u64(&mut bytes, 64 + 8, u64::MAX - 4);
@@ -2266,13 +2716,13 @@ fn test_dispatch_read_returns_queued_bytes() {
         end: ptr + 32,
     });
 
-    let terminal_id = crate::window::WindowId::new();
+    let master = crate::terminal::pty::install_for_window(crate::window::WindowId::new(), 24, 80);
+    let terminal_id = master.terminal_id();
     let prior_terminal = crate::userland::lifecycle::with_active_user(|p| {
         let prior = p.terminal_id;
         p.terminal_id = Some(terminal_id);
         prior
     });
-    let master = crate::terminal::pty::install_for_terminal(terminal_id, 24, 80);
     let pushed = master.with(|pty| pty.push_slave_input(b"echo me\n"));
     assert!(pushed.wake_reader);
 
@@ -2594,6 +3044,84 @@ fn test_dispatch_chdir_nonexistent_returns_enoent() {
     teardown_phase2_active_user();
 }
 
+/// A symlink inside a `chroot` resolves against the process root: neither
+/// an absolute target nor a `..` chain climbs out of the jail, while the
+/// link itself still reads back unchanged.
+fn test_chroot_symlink_cannot_escape_root() {
+    use crate::fs::vfs::{vfs_mkdir, vfs_rmdir, vfs_symlink, vfs_unlink};
+    use crate::userland::lifecycle::with_active_user;
+    const LINKS: [&str; 3] = [
+        "/data/jail/tmp/root",
+        "/data/jail/tmp/up",
+        "/data/jail/tmp/file",
+    ];
+    let cleanup = || {
+        for path in LINKS {
+            let _ = vfs_unlink(path);
+        }
+        let _ = vfs_unlink("/data/jail/secret.txt");
+        let _ = vfs_unlink("/data/secret.txt");
+        let _ = vfs_rmdir("/data/jail/tmp");
+        let _ = vfs_rmdir("/data/jail");
+    };
+    cleanup();
+    vfs_mkdir("/data/jail").expect("mkdir jail");
+    vfs_mkdir("/data/jail/tmp").expect("mkdir jail/tmp");
+    for (path, contents) in [
+        ("/data/secret.txt", b"outside"),
+        ("/data/jail/secret.txt", b"inside!"),
+    ] {
+        let file = crate::fs::File::create(path).expect("create secret");
+        file.write(contents).expect("write secret");
+    }
+    vfs_symlink("/", LINKS[0]).expect("symlink to /");
+    vfs_symlink("../../..", LINKS[1]).expect("symlink to ../../..");
+    vfs_symlink("/secret.txt", LINKS[2]).expect("symlink to /secret.txt");
+
+    setup_phase2_active_user();
+    let old_root = with_active_user(|au| core::mem::replace(&mut au.root, "/data/jail".into()));
+    let mut user = [0u8; 128];
+    let user_ptr = user.as_mut_ptr() as u64;
+    abi::set_user_va_bounds(UserVaBounds {
+        start: user_ptr,
+        end: user_ptr + user.len() as u64,
+    });
+    for path in ["/tmp/root/secret.txt", "/tmp/up/secret.txt", "/tmp/file"] {
+        user.fill(0);
+        user[..path.len()].copy_from_slice(path.as_bytes());
+        let mut open = SyscallArgs::default();
+        open.rax = nr::OPEN;
+        open.rdi = user_ptr;
+        let fd = syscall_dispatch(&mut open);
+        assert!(fd >= 0, "open {path} failed: {fd}");
+        let mut read = SyscallArgs::default();
+        read.rax = nr::READ;
+        read.rdi = fd as u64;
+        read.rsi = user_ptr + 64;
+        read.rdx = 32;
+        assert_eq!(syscall_dispatch(&mut read), 7);
+        assert_eq!(&user[64..71], b"inside!", "{path} left the jail");
+        let mut close = SyscallArgs::default();
+        close.rax = nr::CLOSE;
+        close.rdi = fd as u64;
+        assert_eq!(syscall_dispatch(&mut close), 0);
+    }
+    user.fill(0);
+    user[..9].copy_from_slice(b"/tmp/file");
+    let mut readlink = SyscallArgs::default();
+    readlink.rax = nr::READLINK;
+    readlink.rdi = user_ptr;
+    readlink.rsi = user_ptr + 64;
+    readlink.rdx = 32;
+    assert_eq!(syscall_dispatch(&mut readlink), 11);
+    assert_eq!(&user[64..75], b"/secret.txt");
+
+    abi::clear_user_va_bounds();
+    with_active_user(|au| au.root = old_root);
+    teardown_phase2_active_user();
+    cleanup();
+}
+
 fn test_dispatch_open_nonexistent_returns_enoent() {
     setup_phase2_active_user();
     let path = b"/host/NEVER_EXISTS_XYZ.TXT\0";
@@ -3820,6 +4348,7 @@ fn test_notify_parent_of_exit_files_zombie_and_raises_sigchld() {
     let mut parent = Process {
         pid: 100,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(100),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -3828,11 +4357,15 @@ fn test_notify_parent_of_exit_files_zombie_and_raises_sigchld() {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -3841,6 +4374,7 @@ fn test_notify_parent_of_exit_files_zombie_and_raises_sigchld() {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -4682,6 +5216,85 @@ fn test_fork_execve_badpath_returns_to_parent() {
     );
 }
 
+fn write_exec_fixture(path: &str, bytes: &[u8]) {
+    let file = crate::fs::File::create(path).expect("create exec fixture");
+    assert_eq!(file.write(bytes).expect("write exec fixture"), bytes.len());
+}
+
+/// End-to-end `execve` of a dynamically linked program: the kernel maps
+/// its `PT_INTERP` interpreter and enters it, so the interpreter's exit
+/// code 42 wins over the program's own 1.
+fn test_execve_enters_pt_interp_interpreter() {
+    use crate::userland::lifecycle::ExitKind;
+    const INTERP: &str = "/tmp/exec-interp.so";
+    const PROGRAM: &str = "/tmp/exec-dynamic.elf";
+    write_exec_fixture(INTERP, &fix::interp_exit_elf(42));
+    write_exec_fixture(PROGRAM, &fix::dynamic_exit1_elf(b"/tmp/exec-interp.so\0"));
+    reset_active_user();
+
+    let aspace = crate::userland::address_space::AddressSpace::new().expect("AddressSpace::new");
+    unsafe {
+        aspace.activate();
+    }
+    let bytes = fix::execve_elf(b"/tmp/exec-dynamic.elf\0");
+    let image = load_elf(&bytes).expect("load_elf");
+    let result =
+        crate::userland::enter_user_mode_with_aspace(image, &["agenticos-app"], &[], Some(aspace))
+            .expect("enter_user_mode_with_aspace");
+    let _ = crate::userland::release_active_image();
+
+    assert!(matches!(result.0, ExitKind::Cooperative));
+    assert_eq!(
+        result.1, 42,
+        "the interpreter must run in place of the program's entry"
+    );
+    crate::fs::vfs::vfs_unlink(PROGRAM).expect("cleanup program");
+    crate::fs::vfs::vfs_unlink(INTERP).expect("cleanup interpreter");
+}
+
+/// `execve` of a dynamic program fails cleanly when its interpreter is
+/// missing (`ENOENT`) or not an ELF image (`ELIBBAD`), or when its
+/// `PT_INTERP` is malformed (`ENOEXEC`).
+fn test_execve_interpreter_errors() {
+    use crate::userland::abi::{ELIBBAD, ENOEXEC};
+    const FIXTURES: [&str; 4] = [
+        "/tmp/exec-bad-interp.so",
+        "/tmp/exec-bad.elf",
+        "/tmp/exec-missing.elf",
+        "/tmp/exec-relative.elf",
+    ];
+    write_exec_fixture(FIXTURES[0], b"#not an ELF image");
+    write_exec_fixture(
+        FIXTURES[1],
+        &fix::dynamic_exit1_elf(b"/tmp/exec-bad-interp.so\0"),
+    );
+    write_exec_fixture(FIXTURES[2], &fix::dynamic_exit1_elf(b"/tmp/exec-none.so\0"));
+    write_exec_fixture(FIXTURES[3], &fix::dynamic_exit1_elf(b"lib/ld.so\0"));
+
+    setup_phase2_active_user();
+    let execve = |path: &[u8]| {
+        let ptr = path.as_ptr() as u64;
+        abi::set_user_va_bounds(UserVaBounds {
+            start: ptr,
+            end: ptr + path.len() as u64,
+        });
+        let mut args = SyscallArgs::default();
+        args.rax = nr::EXECVE;
+        args.rdi = ptr;
+        let ret = syscall_dispatch(&mut args);
+        abi::clear_user_va_bounds();
+        ret
+    };
+    assert_eq!(execve(b"/tmp/exec-missing.elf\0"), ENOENT);
+    assert_eq!(execve(b"/tmp/exec-bad.elf\0"), ELIBBAD);
+    assert_eq!(execve(b"/tmp/exec-relative.elf\0"), ENOEXEC);
+    teardown_phase2_active_user();
+
+    for path in FIXTURES {
+        crate::fs::vfs::vfs_unlink(path).expect("cleanup exec fixture");
+    }
+}
+
 // ---------- Phase 4 PR-C2: fork + wait4 round trip ----------
 
 /// End-to-end fork test: a hand-rolled binary forks, child exits with
@@ -4738,6 +5351,7 @@ fn test_fork_then_wait_returns_to_parent() {
 fn test_address_space_clone_for_child_uses_cow() {
     use crate::mm::paging::{CowOutcome, UserPerms, USER_LOAD_BASE};
     use crate::userland::address_space::AddressSpace;
+    use crate::userland::vm::VmaSet;
     use x86_64::registers::control::Cr3;
     use x86_64::structures::paging::PageTableFlags;
     use x86_64::VirtAddr;
@@ -4761,7 +5375,8 @@ fn test_address_space_clone_for_child_uses_cow() {
 
     // Build the child by cloning the parent's L4. Stay on parent's L4
     // for the clone walk — `clone_for_child` reads parent's tables.
-    let child = AddressSpace::clone_for_child(parent.l4_frame()).expect("clone_for_child");
+    let child =
+        AddressSpace::clone_for_child(parent.l4_frame(), &VmaSet::new()).expect("clone_for_child");
     assert_ne!(child.l4_frame(), parent.l4_frame());
     assert_ne!(child.l4_frame(), kernel_frame);
 
@@ -4823,6 +5438,181 @@ fn test_address_space_clone_for_child_uses_cow() {
     assert_eq!(final_cr3, kernel_frame);
 }
 
+/// Leaves inside a `MAP_SHARED` VMA stay writable in both processes after
+/// fork and keep one frame, so a store in the child is seen by the parent.
+fn test_address_space_clone_for_child_keeps_shared_writable() {
+    use crate::mm::paging::{UserPerms, USER_LOAD_BASE};
+    use crate::userland::address_space::AddressSpace;
+    use crate::userland::shared_memory::SharedObject;
+    use crate::userland::vm::{VmProt, Vma, VmaBacking, VmaSet};
+    use x86_64::registers::control::Cr3;
+    use x86_64::structures::paging::PageTableFlags;
+    use x86_64::VirtAddr;
+
+    let kernel_frame = crate::mm::paging::kernel_l4_frame().expect("kernel L4 captured at boot");
+    let parent = AddressSpace::new().expect("parent AddressSpace::new");
+    unsafe {
+        parent.activate();
+    }
+    crate::mm::memory::map_user_region(VirtAddr::new(USER_LOAD_BASE), 1, UserPerms::ReadWrite)
+        .expect("memory mapper")
+        .expect("parent map");
+    let va = USER_LOAD_BASE as *mut u32;
+    unsafe {
+        core::ptr::write_volatile(va, 0x5151_5151);
+    }
+    let mut vmas = VmaSet::new();
+    vmas.insert(
+        Vma::new(
+            USER_LOAD_BASE,
+            USER_LOAD_BASE + 0x1000,
+            VmProt::READ.union(VmProt::WRITE),
+            VmaBacking::Shared {
+                object: SharedObject::anonymous(),
+                offset: 0,
+            },
+        )
+        .expect("shared vma"),
+    )
+    .expect("insert shared vma");
+
+    let child = AddressSpace::clone_for_child(parent.l4_frame(), &vmas).expect("clone_for_child");
+    let leaf = |l4| {
+        crate::mm::memory::with_memory_mapper(|m| {
+            m.leaf_info(l4, VirtAddr::new(USER_LOAD_BASE))
+                .expect("leaf")
+        })
+        .expect("memory mapper")
+    };
+    let (parent_frame, parent_flags) = leaf(parent.l4_frame());
+    let (child_frame, child_flags) = leaf(child.l4_frame());
+    assert_eq!(parent_frame, child_frame);
+    for flags in [parent_flags, child_flags] {
+        assert!(flags.contains(PageTableFlags::WRITABLE));
+        assert!(!flags.contains(PageTableFlags::BIT_9));
+    }
+
+    unsafe {
+        child.activate();
+        core::ptr::write_volatile(va, 0x7373_7373);
+        parent.activate();
+    }
+    assert_eq!(unsafe { core::ptr::read_volatile(va) }, 0x7373_7373);
+
+    drop(child);
+    drop(parent);
+    let (final_cr3, _) = Cr3::read();
+    assert_eq!(final_cr3, kernel_frame);
+}
+
+/// Shared file objects follow the file, not its path: two opens of one
+/// file share an object, and a file created at the same path after an
+/// unlink gets a new one instead of the old file's pages.
+fn test_shared_file_object_follows_identity_not_path() {
+    use crate::userland::shared_memory::SharedObject;
+
+    const PATH: &str = "/tmp/shared-identity.bin";
+    let first = crate::fs::File::create(PATH).expect("create first file");
+    let again = crate::fs::File::open(PATH, crate::fs::filesystem::FileMode::READ)
+        .expect("reopen first file");
+    let inode = first.metadata().expect("metadata").inode;
+    assert_ne!(inode, 0, "tmpfs reports an inode number");
+    assert_eq!(again.metadata().expect("metadata").inode, inode);
+    let object = SharedObject::for_file(&first, true);
+    assert!(crate::lib::arc::Arc::ptr_eq(
+        &object,
+        &SharedObject::for_file(&again, false)
+    ));
+
+    crate::fs::vfs::vfs_unlink(PATH).expect("unlink first file");
+    let second = crate::fs::File::create(PATH).expect("create second file");
+    assert_ne!(second.metadata().expect("metadata").inode, inode);
+    let replacement = SharedObject::for_file(&second, true);
+    assert!(!crate::lib::arc::Arc::ptr_eq(&object, &replacement));
+    assert!(replacement.is_backed_by(&second));
+
+    drop(replacement);
+    drop(object);
+    drop((first, again, second));
+    crate::fs::vfs::vfs_unlink(PATH).expect("cleanup second file");
+}
+
+/// A shared file page is written back only while a leaf that maps it has
+/// its dirty bit set: after one writeback the page is clean, so a later
+/// sync does not overwrite the file with the mapped copy.
+fn test_shared_file_writeback_follows_dirty_bit() {
+    use crate::mm::paging::USER_LOAD_BASE;
+    use crate::userland::address_space::AddressSpace;
+    use crate::userland::shared_memory::{harvest_dirty, SharedObject};
+    use crate::userland::vm::{VmProt, Vma, VmaBacking};
+    use x86_64::structures::paging::PageTableFlags;
+    use x86_64::VirtAddr;
+
+    const PATH: &str = "/tmp/shared-writeback.bin";
+    let file = crate::fs::File::create(PATH).expect("create shared fixture");
+    assert_eq!(file.write_at(0, &[b'a'; 0x1000]).expect("fill"), 0x1000);
+    let object = SharedObject::for_file(&file, true);
+    let frame = crate::mm::memory::with_memory_mapper(|m| m.allocate_private_zeroed_frame())
+        .expect("memory mapper")
+        .expect("frame");
+    let (frame, adopted) = object.install(0, frame);
+    assert!(adopted);
+    let vma = Vma::new(
+        USER_LOAD_BASE,
+        USER_LOAD_BASE + 0x1000,
+        VmProt::READ.union(VmProt::WRITE),
+        VmaBacking::Shared {
+            object: object.clone(),
+            offset: 0,
+        },
+    )
+    .expect("shared vma");
+
+    let space = AddressSpace::new().expect("AddressSpace::new");
+    let flags = PageTableFlags::PRESENT
+        | PageTableFlags::USER_ACCESSIBLE
+        | PageTableFlags::WRITABLE
+        | PageTableFlags::NO_EXECUTE;
+    crate::mm::memory::with_memory_mapper(|m| {
+        m.map_shared_frame_into(
+            space.l4_frame(),
+            VirtAddr::new(USER_LOAD_BASE),
+            frame,
+            flags,
+        )
+    })
+    .expect("memory mapper")
+    .expect("map shared page");
+    unsafe {
+        space.activate();
+        core::ptr::write_volatile(USER_LOAD_BASE as *mut u8, b'b');
+    }
+
+    harvest_dirty(space.l4_frame(), &vma, vma.start, vma.end);
+    let (_, flags) = crate::mm::memory::with_memory_mapper(|m| {
+        m.leaf_info(space.l4_frame(), VirtAddr::new(USER_LOAD_BASE))
+    })
+    .expect("memory mapper")
+    .expect("leaf");
+    assert!(!flags.contains(PageTableFlags::DIRTY));
+    object.writeback(0, 0x1000).expect("first writeback");
+    let mut byte = [0u8; 1];
+    file.read_at(0, &mut byte).expect("read back");
+    assert_eq!(byte, *b"b");
+
+    file.write_at(0, b"c").expect("write through the file");
+    harvest_dirty(space.l4_frame(), &vma, vma.start, vma.end);
+    object.writeback(0, 0x1000).expect("second writeback");
+    file.read_at(0, &mut byte).expect("read back");
+    assert_eq!(byte, *b"c", "a clean page must not be written back");
+
+    drop(space);
+    drop(vma);
+    drop(object);
+    drop(file);
+    crate::fs::vfs::vfs_unlink(PATH).expect("cleanup shared fixture");
+}
+
 // ---------- Phase 4 PR-A: Process table ----------
 
 /// `getpid()` returns the kernel sentinel (0) when no user process is
@@ -5039,11 +5829,10 @@ fn test_dispatch_ioctl_tiocgwinsz_returns_80x24() {
     teardown_phase2_active_user();
 }
 
-/// U5: `ioctl(stdin, TIOCGPGRP, ...)` returns -ENOTTY. Zsh's
-/// `acquire_pgrp` reads this as "no controlling tty" and clears the
-/// MONITOR option, suppressing every subsequent setpgid/tcsetpgrp call.
-/// Critical: the arm sits inside the request match — the non-tty
-/// short-circuit only fires on file fds, not on stdin.
+/// `ioctl(stdin, TIOCGPGRP, ...)` returns -ENOTTY for a process with no
+/// pty: there is no terminal its session could claim. The arm sits inside
+/// the request match — the non-tty short-circuit only fires on file fds,
+/// not on stdin.
 fn test_dispatch_ioctl_tiocgpgrp_returns_enotty() {
     setup_phase2_active_user();
     let mut args = SyscallArgs::default();
@@ -5055,20 +5844,45 @@ fn test_dispatch_ioctl_tiocgpgrp_returns_enotty() {
     teardown_phase2_active_user();
 }
 
-/// U5: `ioctl(stdout, TIOCSPGRP, ...)` returns 0 (silent success).
-/// Defensive — zsh shouldn't reach this path with MONITOR cleared,
-/// but the stub avoids surprises if a build configuration somehow does.
-fn test_dispatch_ioctl_tiocspgrp_returns_zero() {
+/// `ioctl(stdout, TIOCSPGRP, ...)` without a controlling terminal is
+/// -ENOTTY, checked before the argument is read.
+fn test_dispatch_ioctl_tiocspgrp_without_terminal_is_enotty() {
     setup_phase2_active_user();
     let mut args = SyscallArgs::default();
     args.rax = nr::IOCTL;
     args.rdi = 1; // stdout
     args.rsi = 0x5410; // TIOCSPGRP
     args.rdx = 0;
-    assert_eq!(syscall_dispatch(&mut args), 0);
+    assert_eq!(syscall_dispatch(&mut args), ENOTTY);
     teardown_phase2_active_user();
 }
 
+/// Process-group syscalls reject malformed arguments before looking
+/// anything up.
+fn test_dispatch_process_group_argument_errors() {
+    let mut args = SyscallArgs::default();
+    args.rax = nr::SETPGID;
+    args.rdi = 0;
+    args.rsi = (-1i64) as u64;
+    assert_eq!(syscall_dispatch(&mut args), EINVAL);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::SETPGID;
+    args.rdi = (-1i64) as u64;
+    assert_eq!(syscall_dispatch(&mut args), ESRCH);
+
+    let mut args = SyscallArgs::default();
+    args.rax = nr::GETSID;
+    args.rdi = (-5i64) as u64;
+    assert_eq!(syscall_dispatch(&mut args), ESRCH);
+
+    // No such process.
+    let mut args = SyscallArgs::default();
+    args.rax = nr::GETPGID;
+    args.rdi = 0x7fff_0000;
+    assert_eq!(syscall_dispatch(&mut args), ESRCH);
+}
+
 // ---------- Phase 2 PR-4: directories + getdents64 ----------
 
 /// `open("/host")` must succeed (host folder mount root) and produce a
@@ -6082,6 +6896,7 @@ fn test_save_restore_user_cpu_state_roundtrips_fs_base() {
     let mut p = Process {
         pid: 9000,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(9000),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -6090,11 +6905,15 @@ fn test_save_restore_user_cpu_state_roundtrips_fs_base() {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -6103,6 +6922,7 @@ fn test_save_restore_user_cpu_state_roundtrips_fs_base() {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -6149,6 +6969,7 @@ fn test_has_children_sees_live_child() {
     let child = Process {
         pid: 8001,
         parent_pid: 8000,
+        job: crate::userland::job_control::JobState::leader(8001),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -6157,11 +6978,15 @@ fn test_has_children_sees_live_child() {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -6170,6 +6995,7 @@ fn test_has_children_sees_live_child() {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -6302,6 +7128,7 @@ fn test_remove_process_cleans_ring3_queues() {
     let p1 = crate::userland::lifecycle::Process {
         pid: 60,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(60),
         image: None,
         exit_kind: crate::userland::lifecycle::ExitKind::None,
         exit_code: 0,
@@ -6310,11 +7137,15 @@ fn test_remove_process_cleans_ring3_queues() {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -6323,6 +7154,7 @@ fn test_remove_process_cleans_ring3_queues() {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -6337,6 +7169,7 @@ fn test_remove_process_cleans_ring3_queues() {
     let p2 = crate::userland::lifecycle::Process {
         pid: 61,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(61),
         image: None,
         exit_kind: crate::userland::lifecycle::ExitKind::None,
         exit_code: 0,
@@ -6345,11 +7178,15 @@ fn test_remove_process_cleans_ring3_queues() {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -6358,6 +7195,7 @@ fn test_remove_process_cleans_ring3_queues() {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -6479,6 +7317,9 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_dispatch_sched_getaffinity_reports_online_cpus,
         &test_dispatch_prlimit64_old_value_writes_infinity,
         &test_dispatch_prlimit64_null_old_returns_zero,
+        &test_rlimit_nofile_fails_dup_with_emfile,
+        &test_rlimit_as_fails_mmap_with_enomem,
+        &test_rlimit_fsize_fails_write_with_efbig_and_sigxfsz,
         &test_dispatch_getrusage_self_zero_fills_and_rejects_unknown_who,
         &test_dispatch_setitimer_real_arms_queries_and_validates,
         &test_dispatch_nanosleep_validation_and_synthetic_return,
@@ -6492,6 +7333,8 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_write_file_large_chunked,
         &test_writev_file_large_iovs,
         &test_pwrite_large_and_pread_short,
+        &test_memfd_seals_enforced_by_syscalls,
+        &test_mprotect_refuses_write_on_read_only_shared_file,
         &test_sendfile_stdout_routes_to_current_terminal,
         &test_dispatch_eventfd_epoll_edge_round_trip,
         &test_dispatch_socketpair_full_duplex,
@@ -6514,6 +7357,8 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_loader_pt_tls_loads,
         &test_loader_pt_tls_oversized_rejected,
         &test_loader_pt_interp_rejected,
+        &test_loader_interp_path_parsed,
+        &test_loader_pie_loads_at_bias,
         &test_loader_segment_overflow,
         &test_loader_unsupported_reloc,
         &test_loader_glob_dat_unresolved,
@@ -6563,6 +7408,7 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_dispatch_getcwd_returns_default,
         &test_dispatch_getcwd_short_buffer_returns_erange,
         &test_dispatch_chdir_root_succeeds,
+        &test_chroot_symlink_cannot_escape_root,
         &test_dispatch_chdir_nonexistent_returns_enoent,
         &test_dispatch_open_nonexistent_returns_enoent,
         &test_dispatch_open_writable_flag_returns_erofs,
@@ -6601,7 +7447,8 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_dispatch_ioctl_on_file_returns_enotty,
         &test_dispatch_ioctl_tiocgwinsz_returns_80x24,
         &test_dispatch_ioctl_tiocgpgrp_returns_enotty,
-        &test_dispatch_ioctl_tiocspgrp_returns_zero,
+        &test_dispatch_ioctl_tiocspgrp_without_terminal_is_enotty,
+        &test_dispatch_process_group_argument_errors,
         // Phase 4 PR-A: process table + real PIDs
         &test_getpid_returns_real_pid,
         &test_pid_allocation_is_monotonic,
@@ -6612,10 +7459,15 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_address_space_drop_reclaims_leaf_and_all_table_levels,
         // Phase 4 PR-C: clone for fork
         &test_address_space_clone_for_child_uses_cow,
+        &test_address_space_clone_for_child_keeps_shared_writable,
+        &test_shared_file_writeback_follows_dirty_bit,
+        &test_shared_file_object_follows_identity_not_path,
         // Phase 4 PR-C2: fork + wait4
         &test_fork_then_wait_returns_to_parent,
         // Phase 4 PR-D: execve (negative path)
         &test_fork_execve_badpath_returns_to_parent,
+        &test_execve_enters_pt_interp_interpreter,
+        &test_execve_interpreter_errors,
         // Phase 5 PR-A: pipes
         &test_pipe_basic_write_then_read,
         &test_pipe_handle_clone_drop_tracks_counts,
@@ -40,6 +40,7 @@ pub const EV_CURRENT: u8 = 1;
 
 pub const ET_REL: u16 = 1;
 pub const ET_EXEC: u16 = 2;
+pub const ET_DYN: u16 = 3;
 pub const EM_X86_64: u16 = 62;
 pub const EM_AARCH64: u16 = 183;
 
@@ -435,6 +436,112 @@ pub fn fork_execve_badpath_elf() -> Vec<u8> {
     runnable_elf_rx(&code)
 }
 
+/// `execve(path, NULL, NULL)`; if it returns, `exit_group(11)`. `path`
+/// must be NUL-terminated.
+pub fn execve_elf(path: &[u8]) -> Vec<u8> {
+    let mut code: Vec<u8> = Vec::new();
+    // lea rdi, [rip + path]; displacement patched below
+    code.extend_from_slice(&[0x48, 0x8D, 0x3D, 0x00, 0x00, 0x00, 0x00]);
+    let lea_end = code.len();
+    // xor rsi, rsi
+    code.extend_from_slice(&[0x48, 0x31, 0xF6]);
+    // xor rdx, rdx
+    code.extend_from_slice(&[0x48, 0x31, 0xD2]);
+    // mov eax, 59 (SYS_execve)
+    code.extend_from_slice(&[0xB8, 0x3B, 0x00, 0x00, 0x00]);
+    // syscall
+    code.extend_from_slice(&[0x0F, 0x05]);
+    // mov edi, 11
+    code.extend_from_slice(&[0xBF, 0x0B, 0x00, 0x00, 0x00]);
+    // mov eax, 231 (SYS_exit_group)
+    code.extend_from_slice(&[0xB8, 0xE7, 0x00, 0x00, 0x00]);
+    // syscall
+    code.extend_from_slice(&[0x0F, 0x05]);
+    // hlt
+    code.push(0xF4);
+    let disp = (code.len() - lea_end) as i32;
+    code[lea_end - 4..lea_end].copy_from_slice(&disp.to_le_bytes());
+    code.extend_from_slice(path);
+    runnable_elf_rx(&code)
+}
+
+/// A `PT_INTERP` interpreter: an `ET_DYN` image whose entry calls
+/// `exit_group(exit_code)`.
+pub fn interp_exit_elf(exit_code: u32) -> Vec<u8> {
+    let mut code: Vec<u8> = Vec::new();
+    // mov edi, exit_code
+    code.push(0xBF);
+    code.extend_from_slice(&exit_code.to_le_bytes());
+    // mov eax, 231 (SYS_exit_group)
+    code.extend_from_slice(&[0xB8, 0xE7, 0x00, 0x00, 0x00]);
+    // syscall
+    code.extend_from_slice(&[0x0F, 0x05]);
+    // hlt
+    code.push(0xF4);
+    Fixture {
+        e_type: ET_DYN,
+        e_machine: EM_X86_64,
+        ei_class: ELFCLASS64,
+        ei_data: ELFDATA2LSB,
+        e_entry: 0,
+        phdrs: vec![PhdrSpec {
+            p_type: PT_LOAD,
+            p_flags: PF_R | PF_X,
+            p_offset: 0x1000,
+            p_vaddr: 0,
+            p_filesz: code.len() as u64,
+            p_memsz: 0x1000,
+            p_align: 0x1000,
+        }],
+        payloads: vec![(0x1000, code)],
+        truncate_to: None,
+    }
+    .build()
+}
+
+/// A dynamically linked program whose `PT_INTERP` is `interp`
+/// (NUL-terminated). Its own entry calls `exit_group(1)`, so any other
+/// exit code shows the interpreter ran instead.
+pub fn dynamic_exit1_elf(interp: &[u8]) -> Vec<u8> {
+    let code = [
+        0xBF, 0x01, 0x00, 0x00, 0x00, // mov edi, 1
+        0xB8, 0xE7, 0x00, 0x00, 0x00, // mov eax, 231 (SYS_exit_group)
+        0x0F, 0x05, // syscall
+        0xF4, // hlt
+    ];
+    let interp_offset = 0x2000u64;
+    Fixture {
+        e_type: ET_EXEC,
+        e_machine: EM_X86_64,
+        ei_class: ELFCLASS64,
+        ei_data: ELFDATA2LSB,
+        e_entry: 0x40_0000,
+        phdrs: vec![
+            PhdrSpec {
+                p_type: PT_LOAD,
+                p_flags: PF_R | PF_X,
+                p_offset: 0x1000,
+                p_vaddr: 0x40_0000,
+                p_filesz: code.len() as u64,
+                p_memsz: 0x1000,
+                p_align: 0x1000,
+            },
+            PhdrSpec {
+                p_type: PT_INTERP,
+                p_flags: PF_R,
+                p_offset: interp_offset,
+                p_vaddr: 0,
+                p_filesz: interp.len() as u64,
+                p_memsz: interp.len() as u64,
+                p_align: 1,
+            },
+        ],
+        payloads: vec![(0x1000, code.to_vec()), (interp_offset, interp.to_vec())],
+        truncate_to: None,
+    }
+    .build()
+}
+
 /// Phase 5 PR-B2 signal-delivery fixture.
 ///
 /// Layout (single PT_LOAD R-X, loaded at USER_LOAD_BASE):
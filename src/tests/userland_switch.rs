@@ -35,6 +35,7 @@ fn synthetic_process(pid: u32) -> Process {
     Process {
         pid,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(pid),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -43,11 +44,15 @@ fn synthetic_process(pid: u32) -> Process {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -56,6 +61,7 @@ fn synthetic_process(pid: u32) -> Process {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -291,6 +297,7 @@ fn insert_synthetic(pid: u32) {
     let p = Process {
         pid,
         parent_pid: 0,
+        job: crate::userland::job_control::JobState::leader(pid),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -299,11 +306,15 @@ fn insert_synthetic(pid: u32) {
         mmap_next: 0,
         fd_table: crate::userland::fdtable::FdTable::new(),
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: alloc::string::String::from("/"),
+        root: alloc::string::String::from("/"),
         address_space: None,
         signal_state: crate::userland::signal::SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -312,6 +323,7 @@ fn insert_synthetic(pid: u32) {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -765,8 +777,8 @@ fn test_expired_nanosleep_wakes_blocked_process() {
 
     let _g = PreemptTestGuard::new();
     insert_synthetic(9330);
-    let now = crate::arch::x86_64::interrupts::get_timer_ticks();
-    mark_ring3_blocked(9330, Ring3BlockReason::Sleeping { deadline_tick: now });
+    let now = crate::time::monotonic_ns();
+    mark_ring3_blocked(9330, Ring3BlockReason::Sleeping { deadline_ns: now });
 
     process_expired_sleeps();
 
@@ -784,11 +796,11 @@ fn test_unexpired_nanosleep_stays_blocked() {
 
     let _g = PreemptTestGuard::new();
     insert_synthetic(9331);
-    let now = crate::arch::x86_64::interrupts::get_timer_ticks();
+    let now = crate::time::monotonic_ns();
     mark_ring3_blocked(
         9331,
         Ring3BlockReason::Sleeping {
-            deadline_tick: now.saturating_add(1_000_000),
+            deadline_ns: now.saturating_add(10_000_000_000_000),
         },
     );
 
@@ -798,6 +810,131 @@ fn test_unexpired_nanosleep_stays_blocked() {
     remove_process(9331);
 }
 
+/// A full job-control round trip: SIGTSTP stops the process and leaves one
+/// `wait4(WUNTRACED)` report; SIGCONT wakes it and leaves one
+/// `wait4(WCONTINUED)` report. The process is a kernel child, so the waits
+/// run from the kernel sentinel.
+fn test_stop_wait_continue_wait_round_trip() {
+    use crate::userland::abi::{clear_user_va_bounds, set_user_va_bounds, UserVaBounds};
+    use crate::userland::job_control::{send_signal, take_stop};
+    use crate::userland::lifecycle::{
+        mark_ring3_blocked, pop_next_ring3, remove_process, set_current_user_pid, with_process,
+        Ring3BlockReason,
+    };
+    use crate::userland::signal::{SIGCONT, SIGTSTP};
+    const WNOHANG: u64 = 1;
+    const WUNTRACED: u64 = 2;
+    const WCONTINUED: u64 = 8;
+
+    let _g = PreemptTestGuard::new();
+    insert_synthetic(9350);
+    let mut status = 0u32;
+    let status_ptr = &mut status as *mut u32 as u64;
+    let wait = |options: u64| {
+        let mut args = crate::arch::x86_64::syscall::SyscallArgs::default();
+        args.rdi = 9350;
+        args.rsi = status_ptr;
+        args.rdx = WNOHANG | options;
+        crate::userland::syscalls::wait4_handler(&mut args)
+    };
+
+    // The stopping half of the dispatcher hook, run as the process itself.
+    set_current_user_pid(Some(9350));
+    assert!(send_signal(9350, SIGTSTP));
+    assert_eq!(take_stop(), Some(SIGTSTP));
+    mark_ring3_blocked(9350, Ring3BlockReason::Stopped);
+    set_current_user_pid(None);
+    assert_eq!(with_process(9350, |p| p.job.stopped), Some(true));
+
+    set_user_va_bounds(UserVaBounds {
+        start: status_ptr,
+        end: status_ptr + 4,
+    });
+    assert_eq!(wait(WCONTINUED), 0, "no continue report before SIGCONT");
+    assert_eq!(wait(WUNTRACED), 9350);
+    // SAFETY: `status` is live; wait4 wrote it through the raw pointer.
+    let stopped = unsafe { core::ptr::read_volatile(status_ptr as *const u32) };
+    assert_eq!(stopped, ((SIGTSTP as u32) << 8) | 0x7f);
+    assert_eq!(wait(WUNTRACED), 0, "a stop is reported once");
+
+    assert!(send_signal(9350, SIGCONT));
+    assert_eq!(
+        pop_next_ring3(),
+        Some(9350),
+        "SIGCONT must wake the process"
+    );
+    assert_eq!(with_process(9350, |p| p.job.stopped), Some(false));
+    assert_eq!(wait(WUNTRACED | WCONTINUED), 9350);
+    // SAFETY: as above.
+    let continued = unsafe { core::ptr::read_volatile(status_ptr as *const u32) };
+    assert_eq!(continued, 0xffff);
+    assert_eq!(wait(WCONTINUED), 0, "a continue is reported once");
+
+    clear_user_va_bounds();
+    remove_process(9350);
+}
+
+/// A stop taken by one thread stops and reports the whole group once;
+/// SIGCONT to the group resumes both threads.
+fn test_group_stop_parks_and_resumes_every_thread() {
+    use crate::userland::job_control::{must_stop, send_signal, take_report, take_stop};
+    use crate::userland::lifecycle::{
+        join_thread_group_for_test, mark_ring3_blocked, pop_next_ring3, remove_process,
+        set_current_user_pid, with_process, Ring3BlockReason,
+    };
+    use crate::userland::signal::{SIGCONT, SIGTSTP};
+
+    let _g = PreemptTestGuard::new();
+    insert_synthetic(9360);
+    insert_synthetic(9361);
+    join_thread_group_for_test(9361, 9360);
+    with_process(9360, |p| p.parent_pid = 9359);
+    mark_ring3_blocked(
+        9361,
+        Ring3BlockReason::Sleeping {
+            deadline_ns: u64::MAX,
+        },
+    );
+
+    set_current_user_pid(Some(9360));
+    assert!(send_signal(9360, SIGTSTP));
+    assert_eq!(take_stop(), Some(SIGTSTP));
+    mark_ring3_blocked(9360, Ring3BlockReason::Stopped);
+    assert_eq!(with_process(9360, |p| p.job.stopped), Some(true));
+    assert_eq!(
+        pop_next_ring3(),
+        Some(9361),
+        "the stop must kick the sleeping thread"
+    );
+
+    // The other thread parks at its next kernel exit without a signal of
+    // its own, and the group is reported once.
+    set_current_user_pid(Some(9361));
+    assert!(must_stop());
+    mark_ring3_blocked(9361, Ring3BlockReason::Stopped);
+    set_current_user_pid(None);
+    assert_eq!(
+        take_report(9359, -1, true, false),
+        Some((
+            9360,
+            crate::userland::job_control::JobReport::Stopped(SIGTSTP)
+        ))
+    );
+    assert_eq!(take_report(9359, -1, true, false), None);
+
+    assert!(send_signal(9360, SIGCONT));
+    let mut resumed = [pop_next_ring3(), pop_next_ring3()];
+    resumed.sort();
+    assert_eq!(
+        resumed,
+        [Some(9360), Some(9361)],
+        "SIGCONT resumes the group"
+    );
+    assert_eq!(with_process(9360, |p| p.job.stopped), Some(false));
+
+    remove_process(9360);
+}
+
 pub fn get_tests() -> &'static [&'static dyn Testable] {
     &[
         &test_save_ring3_copies_every_gpr,
@@ -819,5 +956,7 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_retry_dropped_signal_wakes_skips_block_io,
         &test_expired_nanosleep_wakes_blocked_process,
         &test_unexpired_nanosleep_stays_blocked,
+        &test_stop_wait_continue_wait_round_trip,
+        &test_group_stop_parks_and_resumes_every_thread,
     ]
 }
@@ -80,6 +80,50 @@ fn test_reserved_kernel_slots_and_wrap_are_rejected() {
     assert!(Vma::new(u64::MAX & !0xfff, 0, VmProt::READ, VmaBacking::Anonymous,).is_err());
 }
 
+fn test_shared_split_tracks_offset_and_remerges() {
+    use crate::userland::shared_memory::SharedObject;
+    let rw = VmProt::READ.union(VmProt::WRITE);
+    let object = SharedObject::anonymous();
+    let shared = |start, end, offset| {
+        Vma::new(
+            start,
+            end,
+            rw,
+            VmaBacking::Shared {
+                object: object.clone(),
+                offset,
+            },
+        )
+        .unwrap()
+    };
+    let mut set = VmaSet::new();
+    set.insert(shared(0x400000, 0x404000, 0x1000)).unwrap();
+    assert!(!set.as_slice()[0].private);
+    set.protect(0x402000, 0x403000, VmProt::READ).unwrap();
+    let middle = set.find(0x402000).unwrap();
+    assert_eq!(middle.shared_at(0x402000).unwrap().1, 0x3000);
+    set.protect(0x402000, 0x403000, rw).unwrap();
+    assert_eq!(set.as_slice().len(), 1);
+
+    // Another object, or a non-contiguous offset, never merges.
+    set.insert(shared(0x404000, 0x405000, 0x9000)).unwrap();
+    let other = SharedObject::anonymous();
+    set.insert(
+        Vma::new(
+            0x405000,
+            0x406000,
+            rw,
+            VmaBacking::Shared {
+                object: other,
+                offset: 0,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(set.as_slice().len(), 3);
+}
+
 pub fn get_tests() -> &'static [&'static dyn Testable] {
     &[
         &test_adjacent_anonymous_allocations_keep_distinct_ids,
@@ -88,5 +132,6 @@ pub fn get_tests() -> &'static [&'static dyn Testable] {
         &test_protect_splits_and_requires_full_coverage,
         &test_top_down_gap_search_and_overlap_rejection,
         &test_reserved_kernel_slots_and_wrap_are_rejected,
+        &test_shared_split_tracks_offset_and_remerges,
     ]
 }
@@ -1,14 +1,43 @@
-//! Kernel monotonic and RTC-anchored wall clocks.
+//! Kernel monotonic and wall clocks.
+//!
+//! Both read the TSC clocksource ([`crate::arch::x86_64::tsc`]) once boot
+//! has calibrated it, and the 100 Hz PIT tick count before that. Realtime
+//! runs from an anchor: the realtime value at some monotonic instant, plus
+//! the monotonic time since then scaled by a rate. Boot anchors it to the
+//! CMOS RTC. `clock_settime` and a large SNTP correction re-anchor it,
+//! stepping realtime. `adjtime` and small SNTP corrections slew it instead,
+//! running realtime at most [`MAX_SLEW_NS_PER_TICK`] per tick, 500 ppm, fast
+//! or slow; the BSP tick re-anchors and picks the next rate. A slew changes
+//! only the rate, so realtime stays continuous and never goes backwards.
+//! Every anchor is republished to the vDSO, which applies the same rate.
 
-use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use core::sync::atomic::{fence, AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
 
-const PIT_NANOSECONDS_PER_TICK: u64 = 10_000_000;
+use crate::arch::x86_64::interrupt_guard::InterruptMutex;
+
+pub const PIT_NANOSECONDS_PER_TICK: u64 = 10_000_000;
 const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
 const SECONDS_PER_DAY: u64 = 86_400;
+/// The most a slew moves realtime per PIT tick: 500 ppm, as Linux `adjtime`.
+pub const MAX_SLEW_NS_PER_TICK: i64 = (PIT_NANOSECONDS_PER_TICK / 2_000) as i64;
+/// [`RealtimeAnchor::rate`] while no slew runs: one realtime nanosecond per
+/// monotonic nanosecond, in 32.32 fixed point.
+pub const REALTIME_RATE_UNIT: u64 = 1 << 32;
 
 static WALL_CLOCK_VALID: AtomicBool = AtomicBool::new(false);
-static WALL_CLOCK_EPOCH_SECONDS: AtomicU64 = AtomicU64::new(0);
-static WALL_CLOCK_BASE_TICK: AtomicU64 = AtomicU64::new(0);
+/// Odd while the anchor below is being rewritten.
+static ANCHOR_SEQ: AtomicU32 = AtomicU32::new(0);
+static ANCHOR_MONOTONIC_NS: AtomicU64 = AtomicU64::new(0);
+/// Realtime at `ANCHOR_MONOTONIC_NS`; equal to it while the wall clock is
+/// invalid.
+static ANCHOR_REALTIME_NS: AtomicU64 = AtomicU64::new(0);
+static REALTIME_RATE: AtomicU64 = AtomicU64::new(REALTIME_RATE_UNIT);
+/// Correction a slew still owes as of the anchor, in signed nanoseconds.
+static SLEW_REMAINING_NS: AtomicI64 = AtomicI64::new(0);
+/// Set by a successful SNTP exchange, cleared by [`set_realtime_ns`].
+static SYNCHRONIZED: AtomicBool = AtomicBool::new(false);
+/// Serializes anchor and slew updates against the tick.
+static ADJUST: InterruptMutex<()> = InterruptMutex::new(());
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DateTime {
@@ -35,24 +64,52 @@ impl DateTime {
     }
 }
 
-/// Anchor realtime to a stable CMOS snapshot. Failure is deliberately
-/// non-fatal: realtime retains its historical uptime-from-zero fallback.
+/// Where realtime was at a monotonic instant and how fast it runs from
+/// there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RealtimeAnchor {
+    pub monotonic_ns: u64,
+    pub realtime_ns: u64,
+    /// Realtime nanoseconds per monotonic nanosecond, 32.32 fixed point.
+    pub rate: u64,
+}
+
+impl RealtimeAnchor {
+    /// Realtime at `monotonic_ns`. A reading from just before the anchor,
+    /// as another CPU may take, clamps to it.
+    pub fn realtime_at(&self, monotonic_ns: u64) -> u64 {
+        let elapsed = monotonic_ns.saturating_sub(self.monotonic_ns);
+        let scaled = (u128::from(elapsed) * u128::from(self.rate)) >> 32;
+        self.realtime_ns.wrapping_add(scaled as u64)
+    }
+}
+
+/// Calibrate the TSC, then anchor realtime to a stable CMOS snapshot.
+/// Failure is deliberately non-fatal: realtime retains its historical
+/// uptime-from-zero fallback.
 pub fn init() {
-    let before = crate::arch::x86_64::interrupts::get_timer_ticks();
+    crate::arch::x86_64::tsc::calibrate();
+    let before = monotonic_ns();
     match crate::arch::x86_64::rtc::read_datetime()
         .ok()
         .and_then(unix_seconds_from_datetime)
     {
         Some(epoch_seconds) => {
-            let after = crate::arch::x86_64::interrupts::get_timer_ticks();
-            let anchor_tick = before.saturating_add(after.saturating_sub(before) / 2);
-            WALL_CLOCK_EPOCH_SECONDS.store(epoch_seconds, Ordering::Relaxed);
-            WALL_CLOCK_BASE_TICK.store(anchor_tick, Ordering::Relaxed);
-            WALL_CLOCK_VALID.store(true, Ordering::Release);
+            let after = monotonic_ns();
+            let anchor_ns = before.saturating_add(after.saturating_sub(before) / 2);
+            {
+                let _adjust = ADJUST.lock();
+                store_anchor(RealtimeAnchor {
+                    monotonic_ns: anchor_ns,
+                    realtime_ns: epoch_seconds.saturating_mul(NANOSECONDS_PER_SECOND),
+                    rate: REALTIME_RATE_UNIT,
+                });
+                WALL_CLOCK_VALID.store(true, Ordering::Release);
+            }
             crate::debug_info!(
-                "wall clock: RTC anchor {} at PIT tick {}",
+                "wall clock: RTC anchor {} at {} ns",
                 epoch_seconds,
-                anchor_tick
+                anchor_ns
             );
         }
         None => {
@@ -64,40 +121,197 @@ pub fn init() {
     }
 }
 
-/// Monotonic nanoseconds since boot, derived from the 100 Hz PIT.
+/// Monotonic nanoseconds since boot.
 pub fn monotonic_ns() -> u64 {
-    crate::arch::x86_64::interrupts::get_timer_ticks().saturating_mul(PIT_NANOSECONDS_PER_TICK)
+    crate::arch::x86_64::tsc::now_ns().unwrap_or_else(|| {
+        crate::arch::x86_64::interrupts::TIMER_TICKS
+            .load(Ordering::Relaxed)
+            .saturating_mul(PIT_NANOSECONDS_PER_TICK)
+    })
 }
 
-/// RTC-anchored Unix nanoseconds, or `None` when boot could not establish a
-/// valid wall clock.
+/// Unix nanoseconds, or `None` when neither the RTC nor a clock set has
+/// established a wall clock.
 pub fn wall_clock_ns() -> Option<u64> {
     if !WALL_CLOCK_VALID.load(Ordering::Acquire) {
         return None;
     }
-    let epoch_seconds = WALL_CLOCK_EPOCH_SECONDS.load(Ordering::Relaxed);
-    let base_tick = WALL_CLOCK_BASE_TICK.load(Ordering::Relaxed);
-    let now_tick = crate::arch::x86_64::interrupts::get_timer_ticks();
-    let elapsed_ns = now_tick
-        .saturating_sub(base_tick)
-        .saturating_mul(PIT_NANOSECONDS_PER_TICK);
-    Some(
-        epoch_seconds
-            .saturating_mul(NANOSECONDS_PER_SECOND)
-            .saturating_add(elapsed_ns),
-    )
+    Some(realtime_ns())
 }
 
 /// Linux realtime clock. Preserve the pre-RTC behavior if the hardware sample
 /// was unavailable so existing userland still receives a progressing clock.
 pub fn realtime_ns() -> u64 {
-    wall_clock_ns().unwrap_or_else(monotonic_ns)
+    let now = monotonic_ns();
+    realtime_anchor().realtime_at(now)
+}
+
+/// The current anchor, read consistently against concurrent updates; the
+/// vDSO publishes the same values.
+pub fn realtime_anchor() -> RealtimeAnchor {
+    loop {
+        let seq = ANCHOR_SEQ.load(Ordering::Acquire);
+        if seq % 2 == 1 {
+            core::hint::spin_loop();
+            continue;
+        }
+        let anchor = RealtimeAnchor {
+            monotonic_ns: ANCHOR_MONOTONIC_NS.load(Ordering::Relaxed),
+            realtime_ns: ANCHOR_REALTIME_NS.load(Ordering::Relaxed),
+            rate: REALTIME_RATE.load(Ordering::Relaxed),
+        };
+        fence(Ordering::Acquire);
+        if ANCHOR_SEQ.load(Ordering::Relaxed) == seq {
+            return anchor;
+        }
+    }
+}
+
+/// Rewrite the anchor. Callers hold [`ADJUST`].
+fn store_anchor(anchor: RealtimeAnchor) {
+    let seq = ANCHOR_SEQ.load(Ordering::Relaxed);
+    ANCHOR_SEQ.store(seq.wrapping_add(1), Ordering::Relaxed);
+    fence(Ordering::Release);
+    ANCHOR_MONOTONIC_NS.store(anchor.monotonic_ns, Ordering::Relaxed);
+    ANCHOR_REALTIME_NS.store(anchor.realtime_ns, Ordering::Relaxed);
+    REALTIME_RATE.store(anchor.rate, Ordering::Relaxed);
+    ANCHOR_SEQ.store(seq.wrapping_add(2), Ordering::Release);
+}
+
+/// The anchor moved to now at the current rate, so realtime is unchanged,
+/// and how far that rate moved realtime from monotonic time since the
+/// previous anchor.
+fn advance_anchor() -> (RealtimeAnchor, i64) {
+    let now = monotonic_ns();
+    let previous = realtime_anchor();
+    let realtime = previous.realtime_at(now);
+    let elapsed = now.saturating_sub(previous.monotonic_ns);
+    let applied = realtime
+        .wrapping_sub(previous.realtime_ns)
+        .wrapping_sub(elapsed) as i64;
+    let anchor = RealtimeAnchor {
+        monotonic_ns: now,
+        realtime_ns: realtime,
+        rate: previous.rate,
+    };
+    (anchor, applied)
+}
+
+/// Step realtime to `ns` Unix nanoseconds, cancelling any slew.
+pub fn set_realtime_ns(ns: u64) {
+    {
+        let _adjust = ADJUST.lock();
+        store_anchor(RealtimeAnchor {
+            monotonic_ns: monotonic_ns(),
+            realtime_ns: ns,
+            rate: REALTIME_RATE_UNIT,
+        });
+        WALL_CLOCK_VALID.store(true, Ordering::Release);
+        SLEW_REMAINING_NS.store(0, Ordering::Relaxed);
+    }
+    SYNCHRONIZED.store(false, Ordering::Relaxed);
+    crate::userland::vdso::publish();
+}
+
+/// Step realtime by `delta_ns`, cancelling any slew.
+pub fn step_realtime_ns(delta_ns: i64) {
+    {
+        let _adjust = ADJUST.lock();
+        let now = monotonic_ns();
+        store_anchor(RealtimeAnchor {
+            monotonic_ns: now,
+            realtime_ns: realtime_anchor()
+                .realtime_at(now)
+                .wrapping_add_signed(delta_ns),
+            rate: REALTIME_RATE_UNIT,
+        });
+        WALL_CLOCK_VALID.store(true, Ordering::Release);
+        SLEW_REMAINING_NS.store(0, Ordering::Relaxed);
+    }
+    crate::userland::vdso::publish();
+}
+
+/// Replace the outstanding slew with `delta_ns` and return the part of the
+/// previous one that had not been applied as of the last tick.
+pub fn set_slew_ns(delta_ns: i64) -> i64 {
+    let previous = {
+        let _adjust = ADJUST.lock();
+        let (anchor, _) = advance_anchor();
+        store_anchor(RealtimeAnchor {
+            rate: slew_rate(delta_ns),
+            ..anchor
+        });
+        WALL_CLOCK_VALID.store(true, Ordering::Release);
+        SLEW_REMAINING_NS.swap(delta_ns, Ordering::Relaxed)
+    };
+    crate::userland::vdso::publish();
+    previous
+}
+
+/// Correction the current slew had yet to apply at the last tick.
+pub fn slew_remaining_ns() -> i64 {
+    SLEW_REMAINING_NS.load(Ordering::Relaxed)
+}
+
+/// The part of `remaining_ns` one tick applies.
+pub(crate) fn slew_step(remaining_ns: i64) -> i64 {
+    remaining_ns.clamp(-MAX_SLEW_NS_PER_TICK, MAX_SLEW_NS_PER_TICK)
+}
+
+/// The rate that applies [`slew_step`] of `remaining_ns` over one tick.
+/// The adjustment rounds away from zero so a slew of a few nanoseconds
+/// still finishes.
+pub(crate) fn slew_rate(remaining_ns: i64) -> u64 {
+    let step = slew_step(remaining_ns);
+    let adjustment = (step.unsigned_abs() << 32).div_ceil(PIT_NANOSECONDS_PER_TICK);
+    if step < 0 {
+        REALTIME_RATE_UNIT - adjustment
+    } else {
+        REALTIME_RATE_UNIT + adjustment
+    }
+}
+
+/// BSP tick: account for what the current rate applied since the last
+/// tick and pick the rate for the next one.
+pub fn on_tick() {
+    if SLEW_REMAINING_NS.load(Ordering::Relaxed) == 0
+        && REALTIME_RATE.load(Ordering::Relaxed) == REALTIME_RATE_UNIT
+    {
+        return;
+    }
+    {
+        let _adjust = ADJUST.lock();
+        let remaining = SLEW_REMAINING_NS.load(Ordering::Relaxed);
+        let (anchor, applied) = advance_anchor();
+        let remaining = remaining.saturating_sub(applied);
+        store_anchor(RealtimeAnchor {
+            rate: slew_rate(remaining),
+            ..anchor
+        });
+        SLEW_REMAINING_NS.store(remaining, Ordering::Relaxed);
+    }
+    crate::userland::vdso::publish();
+}
+
+/// Whether SNTP has disciplined the clock since the last manual step.
+pub fn synchronized() -> bool {
+    SYNCHRONIZED.load(Ordering::Relaxed)
+}
+
+pub fn mark_synchronized() {
+    SYNCHRONIZED.store(true, Ordering::Relaxed);
+}
+
+/// Write the wall clock back to the CMOS RTC, to the whole second.
+pub fn sync_rtc() {
+    let Some(now) = utc_now() else {
+        return;
+    };
+    if let Err(error) = crate::arch::x86_64::rtc::write_datetime(now) {
+        crate::debug_warn!("wall clock: RTC write-back failed: {:?}", error);
+    }
 }
 
-#[expect(
-    dead_code,
-    reason = "kernel wall-clock API retained for callers/diagnostics"
-)]
 pub fn utc_now() -> Option<DateTime> {
     let seconds = wall_clock_ns()? / NANOSECONDS_PER_SECOND;
     datetime_from_unix_seconds(seconds)
@@ -38,6 +38,9 @@ pub const EINVAL: i64 = -22;
 pub const ENOTTY: i64 = -25;
 pub const ENOENT: i64 = -2;
 pub const EIO: i64 = -5;
+pub const ENXIO: i64 = -6;
+pub const ENOEXEC: i64 = -8;
+pub const ELIBBAD: i64 = -80;
 pub const EACCES: i64 = -13;
 pub const EEXIST: i64 = -17;
 pub const ENOTDIR: i64 = -20;
@@ -47,6 +50,7 @@ pub const ESPIPE: i64 = -29;
 pub const EROFS: i64 = -30;
 pub const ERANGE: i64 = -34;
 pub const ENAMETOOLONG: i64 = -36;
+pub const ELOOP: i64 = -40;
 pub const ECHILD: i64 = -10;
 pub const EAGAIN: i64 = -11;
 pub const EPIPE: i64 = -32;
@@ -55,6 +59,8 @@ pub const EPERM: i64 = -1;
 pub const ESRCH: i64 = -3;
 pub const ENOSPC: i64 = -28;
 pub const EBUSY: i64 = -16;
+pub const ENOTBLK: i64 = -15;
+pub const ENODEV: i64 = -19;
 pub const EXDEV: i64 = -18;
 pub const EFBIG: i64 = -27;
 pub const ENOTEMPTY: i64 = -39;
@@ -65,6 +71,7 @@ pub const EAFNOSUPPORT: i64 = -97;
 pub const EPROTONOSUPPORT: i64 = -93;
 pub const EOPNOTSUPP: i64 = -95;
 pub const ENOTCONN: i64 = -107;
+pub const ETOOMANYREFS: i64 = -109;
 pub const ENOTSUP: i64 = -95;
 pub const EISCONN: i64 = -106;
 pub const EINPROGRESS: i64 = -115;
@@ -79,6 +86,7 @@ pub const EDESTADDRREQ: i64 = -89;
 pub const ENOPROTOOPT: i64 = -92;
 pub const ENETDOWN: i64 = -100;
 pub const ENETUNREACH: i64 = -101;
+pub const EPROTOTYPE: i64 = -91;
 
 /// Active user-VA bounds (inclusive lower, exclusive upper). Populated by
 /// `enter_user_mode` before `iretq`-to-ring-3, cleared on exit. Pointer
@@ -223,6 +231,7 @@ pub mod nr {
     pub const SELECT: u64 = 23;
     pub const SCHED_YIELD: u64 = 24;
     pub const MREMAP: u64 = 25;
+    pub const MSYNC: u64 = 26;
     pub const MADVISE: u64 = 28;
     pub const NANOSLEEP: u64 = 35;
     pub const SETITIMER: u64 = 38;
@@ -251,6 +260,16 @@ pub mod nr {
     pub const GETCWD: u64 = 79;
     pub const CHDIR: u64 = 80;
     pub const FCHDIR: u64 = 81;
+    pub const GETPRIORITY: u64 = 140;
+    pub const SETPRIORITY: u64 = 141;
+    pub const SCHED_SETPARAM: u64 = 142;
+    pub const SCHED_GETPARAM: u64 = 143;
+    pub const SCHED_SETSCHEDULER: u64 = 144;
+    pub const SCHED_GETSCHEDULER: u64 = 145;
+    pub const SCHED_GET_PRIORITY_MAX: u64 = 146;
+    pub const SCHED_GET_PRIORITY_MIN: u64 = 147;
+    pub const SCHED_RR_GET_INTERVAL: u64 = 148;
+    pub const PRCTL: u64 = 157;
     pub const ARCH_PRCTL: u64 = 158;
     pub const GETTID: u64 = 186;
     pub const FUTEX: u64 = 202;
@@ -259,15 +278,38 @@ pub mod nr {
     pub const GETGID: u64 = 104;
     pub const GETEUID: u64 = 107;
     pub const GETEGID: u64 = 108;
+    pub const SETUID: u64 = 105;
+    pub const SETGID: u64 = 106;
+    pub const SETREUID: u64 = 113;
+    pub const SETREGID: u64 = 114;
+    pub const GETGROUPS: u64 = 115;
+    pub const SETGROUPS: u64 = 116;
+    pub const SETRESUID: u64 = 117;
+    pub const GETRESUID: u64 = 118;
+    pub const SETRESGID: u64 = 119;
+    pub const GETRESGID: u64 = 120;
+    pub const SETFSUID: u64 = 122;
+    pub const SETFSGID: u64 = 123;
     pub const GETPPID: u64 = 110;
+    pub const SETPGID: u64 = 109;
+    pub const GETPGRP: u64 = 111;
+    pub const SETSID: u64 = 112;
+    pub const GETPGID: u64 = 121;
+    pub const GETSID: u64 = 124;
+    pub const PTRACE: u64 = 101;
     pub const GETTIMEOFDAY: u64 = 96;
     pub const UMASK: u64 = 95;
     pub const GETRLIMIT: u64 = 97;
+    pub const SETRLIMIT: u64 = 160;
     pub const GETRUSAGE: u64 = 98;
     pub const SYSINFO: u64 = 99;
     pub const READLINK: u64 = 89;
     pub const SET_TID_ADDRESS: u64 = 218;
     pub const CLOCK_GETTIME: u64 = 228;
+    pub const CLOCK_SETTIME: u64 = 227;
+    pub const SETTIMEOFDAY: u64 = 164;
+    pub const ADJTIMEX: u64 = 159;
+    pub const CLOCK_ADJTIME: u64 = 305;
     pub const EXIT_GROUP: u64 = 231;
     pub const OPENAT: u64 = 257;
     pub const NEWFSTATAT: u64 = 262;
@@ -280,6 +322,7 @@ pub mod nr {
     pub const ACCEPT4: u64 = 288;
     pub const PRLIMIT64: u64 = 302;
     pub const GETDENTS64: u64 = 217;
+    pub const SECCOMP: u64 = 317;
     pub const GETRANDOM: u64 = 318;
     // Phase B (FS writes): mutations on the now-writable namespace.
     pub const TRUNCATE: u64 = 76;
@@ -293,9 +336,20 @@ pub mod nr {
     pub const SYMLINK: u64 = 88;
     pub const CHMOD: u64 = 90;
     pub const FCHMOD: u64 = 91;
+    pub const CHOWN: u64 = 92;
+    pub const FCHOWN: u64 = 93;
+    pub const LCHOWN: u64 = 94;
+    pub const FCHOWNAT: u64 = 260;
     pub const FSYNC: u64 = 74;
     pub const FDATASYNC: u64 = 75;
     pub const SYNC: u64 = 162;
+    pub const MOUNT: u64 = 165;
+    pub const UMOUNT2: u64 = 166;
+    pub const PIVOT_ROOT: u64 = 155;
+    pub const CHROOT: u64 = 161;
+    pub const UNSHARE: u64 = 272;
+    pub const STATFS: u64 = 137;
+    pub const FSTATFS: u64 = 138;
     pub const PREAD64: u64 = 17;
     pub const PWRITE64: u64 = 18;
     pub const SENDFILE: u64 = 40;
@@ -323,10 +377,21 @@ pub mod nr {
     pub const EPOLL_CREATE: u64 = 213;
     pub const EPOLL_WAIT: u64 = 232;
     pub const EPOLL_CTL: u64 = 233;
+    pub const INOTIFY_INIT: u64 = 253;
+    pub const INOTIFY_ADD_WATCH: u64 = 254;
+    pub const INOTIFY_RM_WATCH: u64 = 255;
     pub const EPOLL_PWAIT: u64 = 281;
+    pub const SIGNALFD: u64 = 282;
+    pub const TIMERFD_CREATE: u64 = 283;
     pub const EVENTFD: u64 = 284;
+    pub const TIMERFD_SETTIME: u64 = 286;
+    pub const TIMERFD_GETTIME: u64 = 287;
+    pub const SIGNALFD4: u64 = 289;
     pub const EVENTFD2: u64 = 290;
     pub const EPOLL_CREATE1: u64 = 291;
+    pub const INOTIFY_INIT1: u64 = 294;
+    pub const GETCPU: u64 = 309;
+    pub const MEMFD_CREATE: u64 = 319;
     pub const MEMBARRIER: u64 = 324;
 
     // AgenticOS-internal syscalls. Numbers picked well above the Linux
@@ -367,6 +432,8 @@ pub mod nr {
 /// `arch::x86_64::syscall` (via `syscall_dispatch_entry`). Routes by the
 /// syscall number in `args.rax`. Unhandled numbers return `-ENOSYS` so libc
 /// feature probes and build tools can select their fallback paths.
+/// The caller's seccomp filters run first and may refuse the syscall or
+/// kill the caller (see [`crate::userland::seccomp`]).
 ///
 /// After the syscall handler returns, we check whether a signal
 /// handler should run; if so, we build a signal frame on the user
@@ -381,12 +448,24 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
     // If an asynchronous signal woke it, deliver the handler before calling
     // the syscall implementation again; otherwise recv/read would simply
     // park a second time and the pending signal could never run.
+    // A stop signal that woke it parks the process first; once continued,
+    // the syscall re-fires from the start.
     if crate::userland::lifecycle::take_pending_syscall_interrupt() {
+        crate::userland::ptrace::signal_stop_before_syscall(args);
+        crate::userland::job_control::stop_if_pending_before_syscall(args);
         let _ = syscalls::maybe_deliver_signal(args, EINTR);
     }
+    // A tracee resumed with PTRACE_SYSCALL stops here once per syscall;
+    // the syscall then re-fires with whatever registers the tracer left.
+    crate::userland::ptrace::syscall_entry_stop(args);
 
     crate::userland::lifecycle::clear_stale_network_wait(args.rax);
 
+    // A filtered syscall still gets the exit stop and signal delivery.
+    if let Some(result) = crate::userland::seccomp::check(args) {
+        return finish_syscall(args, result);
+    }
+
     let result = match args.rax {
         // Phase 1: streams + memory + signal stubs
         nr::READ => syscalls::read_handler(args),
@@ -396,6 +475,7 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         nr::MMAP => syscalls::mmap_handler(args),
         nr::MPROTECT => syscalls::mprotect_handler(args),
         nr::MUNMAP => syscalls::munmap_handler(args),
+        nr::MSYNC => syscalls::msync_handler(args),
         nr::BRK => syscalls::brk_handler(args),
         nr::RT_SIGACTION => syscalls::rt_sigaction_handler(args),
         nr::RT_SIGPROCMASK => syscalls::rt_sigprocmask_handler(args),
@@ -415,7 +495,7 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         nr::GETPEERNAME => crate::userland::network_syscalls::getpeername_handler(args),
         nr::SETSOCKOPT => crate::userland::network_syscalls::setsockopt_handler(args),
         nr::GETSOCKOPT => crate::userland::network_syscalls::getsockopt_handler(args),
-        nr::SOCKETPAIR => crate::userland::local_stream::socketpair_handler(args),
+        nr::SOCKETPAIR => crate::userland::unix_socket::socketpair_handler(args),
         // U3: musl-init / zsh-startup surface
         nr::POLL => syscalls::poll_handler(args),
         nr::SELECT => syscalls::select_handler(args),
@@ -428,20 +508,47 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         nr::EPOLL_PWAIT => crate::userland::epoll::epoll_pwait_handler(args),
         nr::EVENTFD => crate::userland::eventfd::eventfd_handler(args),
         nr::EVENTFD2 => crate::userland::eventfd::eventfd2_handler(args),
+        nr::TIMERFD_CREATE => crate::userland::timerfd::timerfd_create_handler(args),
+        nr::TIMERFD_SETTIME => crate::userland::timerfd::timerfd_settime_handler(args),
+        nr::TIMERFD_GETTIME => crate::userland::timerfd::timerfd_gettime_handler(args),
+        nr::SIGNALFD => crate::userland::signalfd::signalfd_handler(args),
+        nr::SIGNALFD4 => crate::userland::signalfd::signalfd4_handler(args),
+        nr::INOTIFY_INIT => crate::userland::inotify::inotify_init_handler(args),
+        nr::INOTIFY_INIT1 => crate::userland::inotify::inotify_init1_handler(args),
+        nr::INOTIFY_ADD_WATCH => crate::userland::inotify::inotify_add_watch_handler(args),
+        nr::INOTIFY_RM_WATCH => crate::userland::inotify::inotify_rm_watch_handler(args),
+        nr::MEMFD_CREATE => crate::userland::memfd::memfd_create_handler(args),
         nr::READLINK => syscalls::readlink_handler(args),
         nr::READLINKAT => syscalls::readlinkat_handler(args),
-        nr::GETRLIMIT => syscalls::getrlimit_handler(args),
+        nr::GETRLIMIT => crate::userland::rlimit::getrlimit_handler(args),
+        nr::SETRLIMIT => crate::userland::rlimit::setrlimit_handler(args),
         nr::GETRUSAGE => syscalls::getrusage_handler(args),
         nr::SYSINFO => syscalls::sysinfo_handler(args),
-        nr::PRLIMIT64 => syscalls::prlimit64_handler(args),
+        nr::PRLIMIT64 => crate::userland::rlimit::prlimit64_handler(args),
         nr::SETITIMER => syscalls::setitimer_handler(args),
         nr::NANOSLEEP => syscalls::nanosleep_handler(args),
+        nr::GETPRIORITY => crate::userland::priority::getpriority_handler(args),
+        nr::SETPRIORITY => crate::userland::priority::setpriority_handler(args),
+        nr::SCHED_SETPARAM => crate::userland::priority::sched_setparam_handler(args),
+        nr::SCHED_GETPARAM => crate::userland::priority::sched_getparam_handler(args),
+        nr::SCHED_SETSCHEDULER => crate::userland::priority::sched_setscheduler_handler(args),
+        nr::SCHED_GETSCHEDULER => crate::userland::priority::sched_getscheduler_handler(args),
+        nr::SCHED_GET_PRIORITY_MAX => {
+            crate::userland::priority::sched_get_priority_max_handler(args)
+        }
+        nr::SCHED_GET_PRIORITY_MIN => {
+            crate::userland::priority::sched_get_priority_min_handler(args)
+        }
+        nr::SCHED_RR_GET_INTERVAL => crate::userland::priority::sched_rr_get_interval_handler(args),
+        nr::PRCTL => crate::userland::seccomp::prctl_handler(args),
+        nr::SECCOMP => crate::userland::seccomp::seccomp_handler(args),
         nr::ARCH_PRCTL => syscalls::arch_prctl_handler(args),
         nr::SET_TID_ADDRESS => syscalls::set_tid_address_handler(args),
         nr::SET_ROBUST_LIST => syscalls::set_robust_list_handler(args),
         nr::GETTID => syscalls::gettid_handler(args),
         nr::SCHED_YIELD => syscalls::sched_yield_handler(args),
         nr::SCHED_GETAFFINITY => syscalls::sched_getaffinity_handler(args),
+        nr::GETCPU => syscalls::getcpu_handler(args),
         nr::SIGALTSTACK => syscalls::sigaltstack_handler(args),
         nr::MEMBARRIER => syscalls::membarrier_handler(args),
         nr::FUTEX => crate::userland::futex::handler(args),
@@ -468,17 +575,39 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         // Phase 2: time / random / uname
         nr::CLOCK_GETTIME => syscalls::clock_gettime_handler(args),
         nr::GETTIMEOFDAY => syscalls::gettimeofday_handler(args),
+        nr::CLOCK_SETTIME => syscalls::clock_settime_handler(args),
+        nr::SETTIMEOFDAY => syscalls::settimeofday_handler(args),
+        nr::ADJTIMEX => syscalls::adjtimex_handler(args),
+        nr::CLOCK_ADJTIME => syscalls::clock_adjtime_handler(args),
         nr::UMASK => syscalls::umask_handler(args),
         nr::UTIMENSAT => syscalls::utimensat_handler(args),
         nr::GETRANDOM => syscalls::getrandom_handler(args),
         nr::UNAME => syscalls::uname_handler(args),
         // Credentials and exits
         nr::GETPID => syscalls::getpid_handler(args),
-        nr::GETUID => syscalls::getuid_handler(args),
-        nr::GETGID => syscalls::getgid_handler(args),
-        nr::GETEUID => syscalls::geteuid_handler(args),
-        nr::GETEGID => syscalls::getegid_handler(args),
+        nr::GETUID => crate::userland::credentials::getuid_handler(args),
+        nr::GETGID => crate::userland::credentials::getgid_handler(args),
+        nr::GETEUID => crate::userland::credentials::geteuid_handler(args),
+        nr::GETEGID => crate::userland::credentials::getegid_handler(args),
+        nr::SETUID => crate::userland::credentials::setuid_handler(args),
+        nr::SETGID => crate::userland::credentials::setgid_handler(args),
+        nr::SETREUID => crate::userland::credentials::setreuid_handler(args),
+        nr::SETREGID => crate::userland::credentials::setregid_handler(args),
+        nr::GETGROUPS => crate::userland::credentials::getgroups_handler(args),
+        nr::SETGROUPS => crate::userland::credentials::setgroups_handler(args),
+        nr::SETRESUID => crate::userland::credentials::setresuid_handler(args),
+        nr::GETRESUID => crate::userland::credentials::getresuid_handler(args),
+        nr::SETRESGID => crate::userland::credentials::setresgid_handler(args),
+        nr::GETRESGID => crate::userland::credentials::getresgid_handler(args),
+        nr::SETFSUID => crate::userland::credentials::setfsuid_handler(args),
+        nr::SETFSGID => crate::userland::credentials::setfsgid_handler(args),
         nr::GETPPID => syscalls::getppid_handler(args),
+        nr::SETPGID => crate::userland::job_control::setpgid_handler(args),
+        nr::GETPGRP => crate::userland::job_control::getpgrp_handler(args),
+        nr::SETSID => crate::userland::job_control::setsid_handler(args),
+        nr::GETPGID => crate::userland::job_control::getpgid_handler(args),
+        nr::GETSID => crate::userland::job_control::getsid_handler(args),
+        nr::PTRACE => crate::userland::ptrace::ptrace_handler(args),
         nr::EXIT => syscalls::exit_thread_handler(args),
         nr::EXIT_GROUP => syscalls::exit_group_handler(args),
         // Phase 4 PR-C: process management. Stubs return -ENOSYS for
@@ -526,6 +655,10 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         nr::UNLINK => syscalls::unlink_handler(args),
         nr::CHMOD => syscalls::chmod_handler(args),
         nr::FCHMOD => syscalls::fchmod_handler(args),
+        nr::CHOWN => syscalls::chown_handler(args),
+        nr::FCHOWN => syscalls::fchown_handler(args),
+        nr::LCHOWN => syscalls::lchown_handler(args),
+        nr::FCHOWNAT => syscalls::fchownat_handler(args),
         nr::UNLINKAT => syscalls::unlinkat_handler(args),
         nr::RENAME => syscalls::rename_handler(args),
         nr::RENAMEAT => syscalls::renameat_handler(args),
@@ -540,6 +673,13 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         nr::FDATASYNC => syscalls::fdatasync_handler(args),
         nr::SYNC => syscalls::sync_handler(args),
         nr::SYNCFS => syscalls::syncfs_handler(args),
+        nr::MOUNT => crate::userland::mount::mount_handler(args),
+        nr::UMOUNT2 => crate::userland::mount::umount2_handler(args),
+        nr::PIVOT_ROOT => crate::userland::namespace::pivot_root_handler(args),
+        nr::CHROOT => crate::userland::namespace::chroot_handler(args),
+        nr::UNSHARE => crate::userland::namespace::unshare_handler(args),
+        nr::STATFS => crate::userland::statfs::statfs_handler(args),
+        nr::FSTATFS => crate::userland::statfs::fstatfs_handler(args),
         nr::PREAD64 => syscalls::pread64_handler(args),
         nr::PWRITE64 => syscalls::pwrite64_handler(args),
         nr::SENDFILE => syscalls::sendfile_handler(args),
@@ -547,11 +687,20 @@ pub fn syscall_dispatch(args: &mut SyscallArgs) -> i64 {
         nr::MREMAP => syscalls::mremap_handler(args),
         _ => unhandled_syscall(args),
     };
+    finish_syscall(args, result)
+}
+
+/// Syscall tail, for handled and filtered syscalls alike.
+fn finish_syscall(args: &mut SyscallArgs, result: i64) -> i64 {
+    use crate::userland::syscalls;
 
     // Phase 5 PR-B2: deliver a pending signal if one is queued. This
     // diverges (iretq into the handler) — control never returns
     // here. If no signal is pending, return the syscall result
     // normally and let the SYSCALL stub iretq back to the caller.
+    // A tracee first reports the syscall exit and any pending signal.
+    crate::userland::ptrace::syscall_exit_stop(args, result);
+    crate::userland::ptrace::signal_stop_after_syscall(args, result);
     let _ = syscalls::maybe_deliver_signal(args, result);
     result
 }
@@ -33,6 +33,9 @@ pub struct AddressSpace {
     vmas: crate::userland::vm::VmaSet,
     vma_generation: u64,
     shadow_generation: u64,
+    /// Auxiliary vector handed to the program at exec, kept for the
+    /// `NT_AUXV` note of a core dump.
+    saved_auxv: alloc::vec::Vec<u64>,
 }
 
 impl AddressSpace {
@@ -75,6 +78,7 @@ impl AddressSpace {
                 vmas: crate::userland::vm::VmaSet::new(),
                 vma_generation: 1,
                 shadow_generation,
+                saved_auxv: alloc::vec::Vec::new(),
             })
         });
 
@@ -111,6 +115,14 @@ impl AddressSpace {
         self.shadow_generation
     }
 
+    pub fn saved_auxv(&self) -> &[u64] {
+        &self.saved_auxv
+    }
+
+    pub fn set_saved_auxv(&mut self, auxv: &[u64]) {
+        self.saved_auxv = auxv.to_vec();
+    }
+
     pub fn publish_owner(&mut self, tgid: u32) {
         crate::diagnostics::shadow::address_space::publish_owner(
             self.shadow_generation,
@@ -173,9 +185,12 @@ impl AddressSpace {
 
     /// Clone page-table structure while sharing resident leaves. Writable
     /// private leaves become read-only COW mappings in both processes;
-    /// nonresident VMAs allocate no leaves.
+    /// leaves inside `MAP_SHARED` VMAs of `parent_vmas` keep their write
+    /// access so both processes keep storing to the same frame.
+    /// Nonresident VMAs allocate no leaves.
     pub fn clone_for_child(
         parent_l4_frame: PhysFrame<Size4KiB>,
+        parent_vmas: &crate::userland::vm::VmaSet,
     ) -> Result<Self, AddressSpaceError> {
         // Build the child like a fresh AddressSpace — kernel half copied
         // from the kernel L4, PML4[0] empty.
@@ -215,6 +230,7 @@ impl AddressSpace {
                     child_table[slot].set_addr(child_pdpt_frame.start_address(), parent_flags);
                     clone_pdpt(
                         mapper,
+                        parent_vmas,
                         phys_offset,
                         parent_pdpt_pa,
                         child_pdpt_frame.start_address().as_u64(),
@@ -223,7 +239,11 @@ impl AddressSpace {
                         (slot as u64) << 39,
                     )?;
                 }
+                // The parent's writable leaves are now read-only COW
+                // shares; sibling threads must not keep writing through
+                // cached entries.
                 x86_64::instructions::tlb::flush_all();
+                mapper.note_stale_translations(parent_l4_frame);
             }
             Ok(())
         });
@@ -245,6 +265,7 @@ impl AddressSpace {
     /// kernel L4. Every code page in the kernel binary, the heap, and
     /// any kernel stack satisfies that.
     pub unsafe fn activate(&self) {
+        crate::arch::x86_64::percpu::set_active_user_l4(self.l4_frame.start_address().as_u64());
         Cr3::write(self.l4_frame, Cr3Flags::empty());
         crate::diagnostics::shadow::address_space::activate(
             self.shadow_generation,
@@ -254,8 +275,10 @@ impl AddressSpace {
 }
 
 /// Recursively clone page-table structure while sharing leaf frames.
+#[allow(clippy::too_many_arguments)]
 unsafe fn clone_pdpt(
     mapper: &mut crate::mm::paging::MemoryMapper,
+    vmas: &crate::userland::vm::VmaSet,
     phys_offset: u64,
     parent_pa: u64,
     child_pa: u64,
@@ -278,6 +301,7 @@ unsafe fn clone_pdpt(
         child[i].set_addr(new_frame.start_address(), flags);
         clone_pd(
             mapper,
+            vmas,
             phys_offset,
             p_pa,
             new_frame.start_address().as_u64(),
@@ -289,8 +313,10 @@ unsafe fn clone_pdpt(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 unsafe fn clone_pd(
     mapper: &mut crate::mm::paging::MemoryMapper,
+    vmas: &crate::userland::vm::VmaSet,
     phys_offset: u64,
     parent_pa: u64,
     child_pa: u64,
@@ -313,6 +339,7 @@ unsafe fn clone_pd(
         child[i].set_addr(new_frame.start_address(), flags);
         clone_pt(
             mapper,
+            vmas,
             phys_offset,
             p_pa,
             new_frame.start_address().as_u64(),
@@ -324,8 +351,10 @@ unsafe fn clone_pd(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 unsafe fn clone_pt(
     mapper: &mut crate::mm::paging::MemoryMapper,
+    vmas: &crate::userland::vm::VmaSet,
     phys_offset: u64,
     parent_pa: u64,
     child_pa: u64,
@@ -343,14 +372,15 @@ unsafe fn clone_pt(
         if !mapper.retain_leaf_frame(frame) {
             return Err(AddressSpaceError::OutOfFrames);
         }
+        let virtual_page = virtual_base | ((i as u64) << 12);
+        let shared = vmas.find(virtual_page).is_some_and(|vma| !vma.private);
         let mut flags = parent[i].flags();
-        if flags.contains(PageTableFlags::WRITABLE) {
+        if !shared && flags.contains(PageTableFlags::WRITABLE) {
             flags.remove(PageTableFlags::WRITABLE);
             flags.insert(PageTableFlags::BIT_9);
             parent[i].set_flags(flags);
         }
         child[i].set_addr(frame.start_address(), flags);
-        let virtual_page = virtual_base | ((i as u64) << 12);
         crate::diagnostics::shadow::memory::update_leaf_flags(
             parent_generation,
             virtual_page,
@@ -384,6 +414,16 @@ impl Drop for AddressSpace {
                 crate::mm::paging::activate_kernel_l4();
             }
         }
+        // A thread of an exited group may still be switching off another
+        // CPU; its page walks must finish before the tables are freed.
+        crate::arch::x86_64::shootdown::retire_address_space(
+            self.l4_frame.start_address().as_u64(),
+        );
+        // Shared file pages stored to through this address space must be
+        // written back when their object goes.
+        for vma in self.vmas.as_slice() {
+            crate::userland::shared_memory::harvest_dirty(self.l4_frame, vma, vma.start, vma.end);
+        }
         crate::diagnostics::shadow::address_space::begin_destroy(self.shadow_generation);
         let destroyed = with_memory_mapper(|mapper| {
             let _ = mapper.audit_user_address_space(self.l4_frame);
@@ -0,0 +1,669 @@
+//! ELF core dumps for ring-3 processes killed by a fault.
+//!
+//! [`capture`] runs on the fault path, before teardown unmaps anything, and
+//! builds the whole core file in memory: an `ET_CORE` header, one `PT_NOTE`
+//! with `NT_PRSTATUS`, `NT_PRFPREG`, `NT_PRPSINFO` and `NT_AUXV`, and one
+//! `PT_LOAD` per VMA. Only private writable VMAs carry file contents; text
+//! and read-only data come from the executable, as with Linux's default
+//! `coredump_filter`, and shared mappings are skipped.
+//!
+//! The fault path must not start file I/O, so page contents come straight
+//! from the page tables: a page that is not resident is written as zeros,
+//! and a segment's file image stops at its last resident page. For the same
+//! reason the finished image is queued and the process service writes it to
+//! `<core_dir>/core.<comm>.<pid>` once it runs; the exit path's
+//! `notify_process_exit` is what wakes it.
+//!
+//! A non-dumpable process (see [`crate::userland::credentials`]) leaves no
+//! core. `RLIMIT_CORE` caps the file size, and so does [`MAX_CORE_SIZE`]
+//! since the image lives in the kernel heap until it is written. A limit too
+//! small for the headers and notes writes nothing; otherwise the image is
+//! truncated at the limit, like a Linux core that hit its limit mid-dump.
+//! A queue slot is claimed before anything is allocated, so at most
+//! [`MAX_PENDING_CORES`] images exist at once. Only the faulting thread gets
+//! an `NT_PRSTATUS`, and a hardware fault supplies just RIP, RSP and RFLAGS,
+//! so the other general-purpose registers read as zero in that case.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::arch::x86_64::interrupt_guard::InterruptMutex;
+use crate::userland::lifecycle::AbnormalExit;
+use crate::userland::user_state::{UserRegs, UserState};
+use crate::userland::vm::{VmProt, VmaBacking};
+
+const PAGE_SIZE: u64 = 4096;
+
+const ET_CORE: u16 = 4;
+const EM_X86_64: u16 = 62;
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+/// Program headers are counted in a `u16`; `PN_XNUM` and above need a
+/// section header, which these files do not have.
+const MAX_SEGMENTS: usize = 0xfffe;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRFPREG: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+
+/// `sizeof(struct elf_prstatus)` on x86-64.
+const PRSTATUS_SIZE: usize = 336;
+/// Offset of `pr_reg` (a `user_regs_struct`) inside `elf_prstatus`.
+const PRSTATUS_REG_OFFSET: usize = 112;
+/// `sizeof(struct elf_prpsinfo)` on x86-64.
+const PRPSINFO_SIZE: usize = 136;
+/// `sizeof(struct user_fxsr_struct)`: the FXSAVE area.
+const FPREGS_SIZE: usize = 512;
+
+/// Cores being built or waiting for the process service. Dumps past this
+/// are dropped rather than letting a crash loop pin unbounded kernel memory.
+const MAX_PENDING_CORES: usize = 4;
+
+/// Largest image held in the heap. A bigger core is truncated here, as if
+/// `RLIMIT_CORE` were this size.
+const MAX_CORE_SIZE: u64 = 32 << 20;
+
+struct PendingCore {
+    name: String,
+    image: Vec<u8>,
+    _slot: Slot,
+}
+
+static PENDING: InterruptMutex<VecDeque<PendingCore>> = InterruptMutex::new(VecDeque::new());
+
+/// Slots claimed by cores from capture until written.
+static SLOTS: AtomicUsize = AtomicUsize::new(0);
+
+/// One of the [`MAX_PENDING_CORES`] slots, given back on drop.
+struct Slot;
+
+impl Slot {
+    fn claim() -> Option<Slot> {
+        SLOTS
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                (used < MAX_PENDING_CORES).then_some(used + 1)
+            })
+            .ok()
+            .map(|_| Slot)
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        SLOTS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Process state recorded in the notes.
+struct CoreInfo {
+    signo: i32,
+    /// Faulting thread.
+    tid: u32,
+    tgid: u32,
+    ppid: u32,
+    pgrp: u32,
+    sid: u32,
+    utime_ticks: u64,
+    sigpend: u64,
+    sighold: u64,
+    regs: UserState,
+    fs_base: u64,
+    fpregs: [u8; FPREGS_SIZE],
+    comm: String,
+    psargs: String,
+    auxv: Vec<u64>,
+}
+
+/// One `PT_LOAD`. The file image covers `[vaddr, vaddr + filesz)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    vaddr: u64,
+    memsz: u64,
+    filesz: u64,
+    flags: u32,
+}
+
+/// Build a core image for the current process and queue it for writing.
+/// Called by `cleanup_user_process` while the address space is intact.
+pub fn capture(reason: &AbnormalExit, signo: i32) {
+    if !crate::system_control::core_dumps_enabled() {
+        return;
+    }
+    let (limit, dumpable) = crate::userland::lifecycle::with_current_group(|p| {
+        (
+            p.rlimits.cur(crate::userland::rlimit::RLIMIT_CORE),
+            p.credentials.dumpable,
+        )
+    });
+    if limit == 0 || !dumpable {
+        return;
+    }
+    let Some(slot) = Slot::claim() else {
+        crate::debug_warn!("coredump: queue full, dropping signal {} core", signo);
+        return;
+    };
+    let limit = limit.min(MAX_CORE_SIZE);
+    let Some((info, l4, regions)) = snapshot(reason, signo) else {
+        return;
+    };
+    let segments = plan_segments(l4, &regions);
+    let notes = build_notes(&info);
+    let data_offset = align_up(
+        (EHDR_SIZE + PHDR_SIZE * (segments.len() + 1) + notes.len()) as u64,
+        PAGE_SIZE,
+    );
+    let total = segments.iter().fold(data_offset, |sum, segment| {
+        sum.saturating_add(segment.filesz)
+    });
+    if limit < data_offset {
+        crate::debug_warn!(
+            "coredump: pid={} RLIMIT_CORE {} is below the {}-byte header, not dumped",
+            info.tgid,
+            limit,
+            data_offset
+        );
+        return;
+    }
+    let size = total.min(limit) as usize;
+    let mut image = Vec::new();
+    if image.try_reserve_exact(size).is_err() {
+        crate::debug_warn!(
+            "coredump: pid={} needs {} bytes, out of memory",
+            info.tgid,
+            size
+        );
+        return;
+    }
+    image.resize(size, 0);
+    write_headers(&mut image, &segments, notes.len() as u64, data_offset);
+    image[EHDR_SIZE + PHDR_SIZE * (segments.len() + 1)..][..notes.len()].copy_from_slice(&notes);
+    copy_segments(l4, &segments, &mut image[data_offset as usize..]);
+
+    let name = format!("core.{}.{}", info.comm.replace('/', "_"), info.tgid);
+    crate::debug_info!(
+        "coredump: pid={} signal {} queued {} ({} bytes)",
+        info.tgid,
+        signo,
+        name,
+        image.len()
+    );
+    PENDING.lock().push_back(PendingCore {
+        name,
+        image,
+        _slot: slot,
+    });
+}
+
+/// The parts of a VMA the dump needs.
+struct Region {
+    start: u64,
+    end: u64,
+    prot: VmProt,
+    grow_down: bool,
+    /// Private and writable: its contents go in the file.
+    dumped: bool,
+}
+
+fn snapshot(
+    reason: &AbnormalExit,
+    signo: i32,
+) -> Option<(CoreInfo, x86_64::structures::paging::PhysFrame, Vec<Region>)> {
+    use crate::userland::lifecycle::{with_current_group, with_current_process};
+
+    let tid = crate::userland::lifecycle::current_user_pid()?;
+    // The kernel never touches XMM, so the live FPU registers are still the
+    // faulting thread's. FXSAVE needs the aligned buffer in the Process.
+    let (fs_base, fpregs, utime_ticks, sigpend, sighold) = with_current_process(|p| {
+        crate::userland::lifecycle::save_user_cpu_state(p);
+        (
+            p.fs_base,
+            *p.fpu_state.bytes(),
+            p.utime_ticks,
+            p.signal_state.pending,
+            p.signal_state.blocked,
+        )
+    });
+    with_current_group(|p| {
+        let space = p.address_space.as_ref()?;
+        let regions = space
+            .vmas()
+            .as_slice()
+            .iter()
+            .map(|vma| Region {
+                start: vma.start,
+                end: vma.end,
+                prot: vma.prot,
+                grow_down: vma.grow_down,
+                dumped: vma.prot.contains(VmProt::WRITE)
+                    && !matches!(vma.backing, VmaBacking::Shared { .. }),
+            })
+            .take(MAX_SEGMENTS)
+            .collect();
+        let info = CoreInfo {
+            signo,
+            tid,
+            tgid: p.pid,
+            ppid: p.parent_pid,
+            pgrp: p.job.pgid,
+            sid: p.job.sid,
+            utime_ticks,
+            sigpend,
+            sighold,
+            regs: reason.user_regs,
+            fs_base,
+            fpregs,
+            comm: crate::userland::procfs::comm_of(&p.exe_path, &p.cmdline),
+            psargs: p.cmdline.join(" "),
+            auxv: space.saved_auxv().to_vec(),
+        };
+        Some((info, space.l4_frame(), regions))
+    })
+}
+
+/// Lay out one `PT_LOAD` per region. A dumped region's file image runs
+/// to its last resident page; a grow-down stack also starts at its lowest
+/// resident page, since the rest of its reservation was never touched.
+fn plan_segments(l4: x86_64::structures::paging::PhysFrame, regions: &[Region]) -> Vec<Segment> {
+    let resident = |start: u64, end: u64| -> Option<(u64, u64)> {
+        crate::mm::memory::with_memory_mapper(|mapper| {
+            let present = |page: u64| mapper.leaf_info(l4, x86_64::VirtAddr::new(page)).is_some();
+            let mut pages = (start..end).step_by(PAGE_SIZE as usize);
+            let first = pages.find(|&page| present(page))?;
+            let last = pages.filter(|&page| present(page)).last().unwrap_or(first);
+            Some((first, last + PAGE_SIZE))
+        })
+        .flatten()
+    };
+    regions
+        .iter()
+        .map(|region| {
+            let mut segment = Segment {
+                vaddr: region.start,
+                memsz: region.end - region.start,
+                filesz: 0,
+                flags: segment_flags(region.prot),
+            };
+            if region.dumped {
+                if let Some((first, last)) = resident(region.start, region.end) {
+                    if region.grow_down {
+                        segment.vaddr = first;
+                        segment.memsz = region.end - first;
+                    }
+                    segment.filesz = last - segment.vaddr;
+                }
+            }
+            segment
+        })
+        .collect()
+}
+
+fn segment_flags(prot: VmProt) -> u32 {
+    let mut flags = 0;
+    if prot.contains(VmProt::READ) {
+        flags |= PF_R;
+    }
+    if prot.contains(VmProt::WRITE) {
+        flags |= PF_W;
+    }
+    if prot.contains(VmProt::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+/// Copy resident pages into `data`, which holds the segment file images
+/// back to back and may be cut short by `RLIMIT_CORE`. Leaves are looked up
+/// again here so a page unmapped since planning reads as zeros.
+fn copy_segments(l4: x86_64::structures::paging::PhysFrame, segments: &[Segment], data: &mut [u8]) {
+    let _ = crate::mm::memory::with_memory_mapper(|mapper| {
+        let phys_offset = mapper.physical_memory_offset().as_u64();
+        let mut offset = 0usize;
+        for segment in segments {
+            for page in (segment.vaddr..segment.vaddr + segment.filesz).step_by(PAGE_SIZE as usize)
+            {
+                if offset >= data.len() {
+                    return;
+                }
+                let len = (data.len() - offset).min(PAGE_SIZE as usize);
+                if let Some((frame, _)) = mapper.leaf_info(l4, x86_64::VirtAddr::new(page)) {
+                    let source = (phys_offset + frame.start_address().as_u64()) as *const u8;
+                    // SAFETY: the frame is mapped in the dying process's
+                    // page tables, and the mapper lock held here keeps it
+                    // from being unmapped and freed during the copy. The
+                    // offset map covers all physical memory.
+                    unsafe {
+                        core::ptr::copy_nonoverlapping(source, data[offset..].as_mut_ptr(), len);
+                    }
+                }
+                offset += len;
+            }
+        }
+    });
+}
+
+fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+    buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+fn align_up(value: u64, align: u64) -> u64 {
+    value.div_ceil(align) * align
+}
+
+/// Write the ELF header and program headers at the start of `image`.
+fn write_headers(image: &mut [u8], segments: &[Segment], notes_len: u64, data_offset: u64) {
+    let phnum = segments.len() + 1;
+    put(image, 0, &[0x7f, b'E', b'L', b'F', 2, 1, 1]);
+    put(image, 16, &ET_CORE.to_le_bytes());
+    put(image, 18, &EM_X86_64.to_le_bytes());
+    put(image, 20, &1u32.to_le_bytes());
+    put(image, 32, &(EHDR_SIZE as u64).to_le_bytes());
+    put(image, 52, &(EHDR_SIZE as u16).to_le_bytes());
+    put(image, 54, &(PHDR_SIZE as u16).to_le_bytes());
+    put(image, 56, &(phnum as u16).to_le_bytes());
+
+    let notes_offset = (EHDR_SIZE + PHDR_SIZE * phnum) as u64;
+    let mut phdr = |index: usize, kind: u32, flags: u32, fields: [u64; 6]| {
+        let base = EHDR_SIZE + PHDR_SIZE * index;
+        put(image, base, &kind.to_le_bytes());
+        put(image, base + 4, &flags.to_le_bytes());
+        for (i, field) in fields.iter().enumerate() {
+            put(image, base + 8 + i * 8, &field.to_le_bytes());
+        }
+    };
+    // p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_align
+    phdr(0, PT_NOTE, 0, [notes_offset, 0, 0, notes_len, 0, 4]);
+    let mut offset = data_offset;
+    for (i, segment) in segments.iter().enumerate() {
+        phdr(
+            i + 1,
+            PT_LOAD,
+            segment.flags,
+            [
+                offset,
+                segment.vaddr,
+                0,
+                segment.filesz,
+                segment.memsz,
+                PAGE_SIZE,
+            ],
+        );
+        offset += segment.filesz;
+    }
+}
+
+/// Append one `CORE` note; name and descriptor are padded to 4 bytes.
+fn push_note(out: &mut Vec<u8>, kind: u32, desc: &[u8]) {
+    const NAME: &[u8] = b"CORE\0";
+    out.extend_from_slice(&(NAME.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    out.extend_from_slice(&kind.to_le_bytes());
+    out.extend_from_slice(NAME);
+    out.resize(align_up(out.len() as u64, 4) as usize, 0);
+    out.extend_from_slice(desc);
+    out.resize(align_up(out.len() as u64, 4) as usize, 0);
+}
+
+fn build_notes(info: &CoreInfo) -> Vec<u8> {
+    let mut notes = Vec::new();
+    push_note(&mut notes, NT_PRSTATUS, &prstatus(info));
+    push_note(&mut notes, NT_PRPSINFO, &prpsinfo(info));
+    let auxv: Vec<u8> = info
+        .auxv
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+    push_note(&mut notes, NT_AUXV, &auxv);
+    push_note(&mut notes, NT_PRFPREG, &info.fpregs);
+    notes
+}
+
+/// `struct elf_prstatus` for the faulting thread.
+fn prstatus(info: &CoreInfo) -> [u8; PRSTATUS_SIZE] {
+    let mut out = [0u8; PRSTATUS_SIZE];
+    put(&mut out, 0, &info.signo.to_le_bytes());
+    put(&mut out, 12, &(info.signo as i16).to_le_bytes());
+    put(&mut out, 16, &info.sigpend.to_le_bytes());
+    put(&mut out, 24, &info.sighold.to_le_bytes());
+    put(&mut out, 32, &info.tid.to_le_bytes());
+    put(&mut out, 36, &info.ppid.to_le_bytes());
+    put(&mut out, 40, &info.pgrp.to_le_bytes());
+    put(&mut out, 44, &info.sid.to_le_bytes());
+    let (sec, usec) = crate::userland::syscalls::ticks_to_timeval(info.utime_ticks);
+    put(&mut out, 48, &sec.to_le_bytes());
+    put(&mut out, 56, &usec.to_le_bytes());
+
+    // orig_rax is -1: no syscall to restart.
+    let regs = UserRegs::from_state(&info.regs, u64::MAX, info.fs_base);
+    put(&mut out, PRSTATUS_REG_OFFSET, regs.as_bytes());
+    // pr_fpvalid: NT_PRFPREG follows.
+    put(
+        &mut out,
+        PRSTATUS_REG_OFFSET + core::mem::size_of::<UserRegs>(),
+        &1i32.to_le_bytes(),
+    );
+    out
+}
+
+/// `struct elf_prpsinfo` for the process.
+fn prpsinfo(info: &CoreInfo) -> [u8; PRPSINFO_SIZE] {
+    let mut out = [0u8; PRPSINFO_SIZE];
+    out[1] = b'R';
+    put(&mut out, 24, &info.tgid.to_le_bytes());
+    put(&mut out, 28, &info.ppid.to_le_bytes());
+    put(&mut out, 32, &info.pgrp.to_le_bytes());
+    put(&mut out, 36, &info.sid.to_le_bytes());
+    // fname[16] and psargs[80] are NUL-terminated when they fit.
+    let fname = info.comm.as_bytes();
+    put(&mut out, 40, &fname[..fname.len().min(15)]);
+    let psargs = info.psargs.as_bytes();
+    put(&mut out, 56, &psargs[..psargs.len().min(79)]);
+    out
+}
+
+/// Write one queued core. Returns whether a core was taken off the queue,
+/// so the process service can drain it.
+pub fn write_one_pending() -> bool {
+    let core = { PENDING.lock().pop_front() };
+    let Some(core) = core else {
+        return false;
+    };
+    // The directory is read now, not at capture: the fault path may not
+    // take the settings lock.
+    let Some(dir) = crate::system_control::core_dump_dir() else {
+        return true;
+    };
+    match crate::fs::vfs::vfs_mkdir(&dir) {
+        Ok(()) | Err(crate::fs::filesystem::FilesystemError::AlreadyExists) => {}
+        Err(error) => {
+            crate::debug_warn!("coredump: cannot create {}: {:?}", dir, error);
+            return true;
+        }
+    }
+    let path = format!("{}/{}", dir.trim_end_matches('/'), core.name);
+    let result = (|| {
+        let file = crate::fs::File::create(&path)?;
+        if file.write(&core.image)? != core.image.len() {
+            return Err(crate::fs::file_handle::FileError::IoError);
+        }
+        file.close()
+    })();
+    match result {
+        Ok(()) => crate::debug_info!("coredump: wrote {} ({} bytes)", path, core.image.len()),
+        Err(error) => crate::debug_warn!("coredump: writing {} failed: {:?}", path, error),
+    }
+    true
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn sample_info() -> CoreInfo {
+        CoreInfo {
+            signo: 11,
+            tid: 42,
+            tgid: 40,
+            ppid: 1,
+            pgrp: 40,
+            sid: 1,
+            utime_ticks: 150,
+            sigpend: 0,
+            sighold: 1 << 1,
+            regs: UserState {
+                rip: 0x40_1000,
+                rsp: 0x7fff_0000,
+                rflags: 0x246,
+                rax: 7,
+                ..Default::default()
+            },
+            fs_base: 0x5000,
+            fpregs: [0xab; FPREGS_SIZE],
+            comm: String::from("crashme"),
+            psargs: String::from("crashme --now"),
+            auxv: alloc::vec![6, 4096, 0, 0],
+        }
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn test_prstatus_layout() {
+        let info = sample_info();
+        let status = prstatus(&info);
+        assert_eq!(u32_at(&status, 0), 11);
+        assert_eq!(u16::from_le_bytes([status[12], status[13]]), 11);
+        assert_eq!(u64_at(&status, 24), 2);
+        assert_eq!(u32_at(&status, 32), 42);
+        assert_eq!(u32_at(&status, 40), 40);
+        // 150 ticks of the 100 Hz PIT.
+        assert_eq!(u64_at(&status, 48), 1);
+        assert_eq!(u64_at(&status, 56), 500_000);
+        let reg = |index: usize| u64_at(&status, PRSTATUS_REG_OFFSET + index * 8);
+        assert_eq!(reg(10), 7); // rax
+        assert_eq!(reg(15), u64::MAX); // orig_rax
+        assert_eq!(reg(16), 0x40_1000); // rip
+        assert_eq!(reg(18), 0x246); // eflags
+        assert_eq!(reg(19), 0x7fff_0000); // rsp
+        assert_eq!(reg(21), 0x5000); // fs_base
+        assert_eq!(u32_at(&status, 328), 1);
+    }
+
+    fn test_prpsinfo_truncates_names() {
+        let mut info = sample_info();
+        info.comm = String::from("a-very-long-command-name");
+        info.psargs = "x".repeat(100);
+        let psinfo = prpsinfo(&info);
+        assert_eq!(psinfo[1], b'R');
+        assert_eq!(u32_at(&psinfo, 24), 40);
+        assert_eq!(&psinfo[40..55], b"a-very-long-com");
+        assert_eq!(psinfo[55], 0);
+        assert!(psinfo[56..135].iter().all(|&b| b == b'x'));
+        assert_eq!(psinfo[135], 0);
+    }
+
+    fn test_notes_are_aligned_and_ordered() {
+        let notes = build_notes(&sample_info());
+        let mut offset = 0;
+        let mut kinds = Vec::new();
+        while offset < notes.len() {
+            let namesz = u32_at(&notes, offset) as usize;
+            let descsz = u32_at(&notes, offset + 4) as usize;
+            kinds.push(u32_at(&notes, offset + 8));
+            assert_eq!(&notes[offset + 12..offset + 12 + namesz], b"CORE\0");
+            offset +=
+                12 + align_up(namesz as u64, 4) as usize + align_up(descsz as u64, 4) as usize;
+        }
+        assert_eq!(offset, notes.len());
+        assert_eq!(kinds, [NT_PRSTATUS, NT_PRPSINFO, NT_AUXV, NT_PRFPREG]);
+    }
+
+    fn test_headers_describe_segments() {
+        let segments = [
+            Segment {
+                vaddr: 0x40_0000,
+                memsz: 0x2000,
+                filesz: 0,
+                flags: PF_R | PF_X,
+            },
+            Segment {
+                vaddr: 0x60_0000,
+                memsz: 0x3000,
+                filesz: 0x1000,
+                flags: PF_R | PF_W,
+            },
+        ];
+        let mut image = alloc::vec![0u8; 0x2000];
+        write_headers(&mut image, &segments, 100, 0x1000);
+        assert_eq!(&image[..4], b"\x7fELF");
+        assert_eq!(u16::from_le_bytes([image[16], image[17]]), ET_CORE);
+        assert_eq!(u16::from_le_bytes([image[18], image[19]]), EM_X86_64);
+        assert_eq!(u16::from_le_bytes([image[56], image[57]]), 3);
+        let phdr = |index: usize| EHDR_SIZE + PHDR_SIZE * index;
+        assert_eq!(u32_at(&image, phdr(0)), PT_NOTE);
+        assert_eq!(
+            u64_at(&image, phdr(0) + 8),
+            (EHDR_SIZE + 3 * PHDR_SIZE) as u64
+        );
+        assert_eq!(u64_at(&image, phdr(0) + 32), 100);
+        assert_eq!(u32_at(&image, phdr(2)), PT_LOAD);
+        assert_eq!(u32_at(&image, phdr(2) + 4), PF_R | PF_W);
+        // The read-only segment has no file bytes, so the writable one
+        // starts right at the data offset.
+        assert_eq!(u64_at(&image, phdr(2) + 8), 0x1000);
+        assert_eq!(u64_at(&image, phdr(2) + 16), 0x60_0000);
+        assert_eq!(u64_at(&image, phdr(2) + 32), 0x1000);
+        assert_eq!(u64_at(&image, phdr(2) + 40), 0x3000);
+    }
+
+    fn test_segment_flags() {
+        assert_eq!(segment_flags(VmProt::READ), PF_R);
+        assert_eq!(
+            segment_flags(VmProt::READ.union(VmProt::WRITE).union(VmProt::EXEC)),
+            PF_R | PF_W | PF_X
+        );
+        assert_eq!(segment_flags(VmProt::NONE), 0);
+    }
+
+    fn test_slots_bound_pending_cores() {
+        let mut claimed = Vec::new();
+        while let Some(slot) = Slot::claim() {
+            claimed.push(slot);
+        }
+        assert!(!claimed.is_empty() && claimed.len() <= MAX_PENDING_CORES);
+        assert_eq!(SLOTS.load(Ordering::Acquire), MAX_PENDING_CORES);
+        claimed.pop();
+        assert!(
+            Slot::claim().is_some(),
+            "a released slot is claimable again"
+        );
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_prstatus_layout,
+            &test_prpsinfo_truncates_names,
+            &test_notes_are_aligned_and_ordered,
+            &test_headers_describe_segments,
+            &test_segment_flags,
+            &test_slots_bound_pending_cores,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as coredump_tests;
@@ -0,0 +1,797 @@
+//! Process credentials and filesystem permission checks.
+//!
+//! Each thread group carries real, effective, saved and filesystem user and
+//! group ids plus a supplementary group list, authoritative on its leader.
+//! `fork` and thread creation copy them; `execve` keeps them, except that a
+//! set-user-ID or set-group-ID regular file switches the effective ids to
+//! its owner, and the saved ids then follow the effective ones. Every
+//! process starts as root.
+//!
+//! An `execve` that leaves the effective ids different from the real ones
+//! makes the process non-dumpable, as does any later change to its
+//! effective or filesystem ids; only root may then trace it, and it leaves
+//! no core file. A traced `execve` ignores the set-id bits.
+//!
+//! Privilege is "effective uid 0" for the `set*id` family and "filesystem
+//! uid 0" for file access, `chmod` and `chown`; there are no capabilities.
+//! Unprivileged callers get the usual owner/group/other mode-bit checks,
+//! search permission on every ancestor directory and the sticky-directory
+//! deletion rule. Root bypasses all of them, including the execute bit:
+//! the FAT lower layer and older persisted overlays carry no reliable
+//! execute bits, and root has always been able to run those files here.
+//! Paths without filesystem metadata (the synthetic `/bin`, `/proc` and
+//! `/dev` namespaces, or a missing leaf) pass the checks and leave the
+//! error, if any, to the operation itself.
+
+use alloc::vec::Vec;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::fs::filesystem::UnixMetadata;
+use crate::userland::abi::{EACCES, EFAULT, EINVAL, EPERM, ESRCH};
+
+pub const R_OK: u32 = 4;
+pub const W_OK: u32 = 2;
+pub const X_OK: u32 = 1;
+
+pub const S_ISUID: u32 = 0o4000;
+pub const S_ISGID: u32 = 0o2000;
+pub const S_ISVTX: u32 = 0o1000;
+const S_IFMT: u32 = 0o170000;
+const S_IFREG: u32 = 0o100000;
+const S_IXGRP: u32 = 0o010;
+
+/// Linux `NGROUPS_MAX`.
+pub const NGROUPS_MAX: usize = 65536;
+
+/// `(uid_t)-1`: leave the id unchanged.
+pub const KEEP: u32 = u32::MAX;
+
+/// One id family (user or group): real, effective, saved and filesystem.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ids {
+    pub real: u32,
+    pub effective: u32,
+    pub saved: u32,
+    pub fs: u32,
+}
+
+impl Ids {
+    pub const fn all(id: u32) -> Self {
+        Self {
+            real: id,
+            effective: id,
+            saved: id,
+            fs: id,
+        }
+    }
+
+    /// `[real, effective, saved, fs]`, the `/proc/<pid>/status` order.
+    pub const fn to_array(self) -> [u32; 4] {
+        [self.real, self.effective, self.saved, self.fs]
+    }
+
+    /// Whether an unprivileged caller may switch one of its ids to `id`.
+    fn holds(&self, id: u32) -> bool {
+        id == self.real || id == self.effective || id == self.saved
+    }
+
+    /// `setuid(2)`/`setgid(2)`.
+    fn set(&mut self, id: u32, privileged: bool) -> Result<(), i64> {
+        if id == KEEP {
+            return Err(EINVAL);
+        }
+        if privileged {
+            *self = Self::all(id);
+        } else if id == self.real || id == self.saved {
+            self.effective = id;
+            self.fs = id;
+        } else {
+            return Err(EPERM);
+        }
+        Ok(())
+    }
+
+    /// `setreuid(2)`/`setregid(2)`. Setting the real id, or an effective
+    /// id other than the old real one, also moves the saved id.
+    fn set_real_effective(
+        &mut self,
+        real: u32,
+        effective: u32,
+        privileged: bool,
+    ) -> Result<(), i64> {
+        if !privileged
+            && ((real != KEEP && real != self.real && real != self.effective)
+                || (effective != KEEP && !self.holds(effective)))
+        {
+            return Err(EPERM);
+        }
+        let old_real = self.real;
+        if real != KEEP {
+            self.real = real;
+        }
+        if effective != KEEP {
+            self.effective = effective;
+        }
+        if real != KEEP || (effective != KEEP && effective != old_real) {
+            self.saved = self.effective;
+        }
+        self.fs = self.effective;
+        Ok(())
+    }
+
+    /// `setresuid(2)`/`setresgid(2)`.
+    fn set_all(
+        &mut self,
+        real: u32,
+        effective: u32,
+        saved: u32,
+        privileged: bool,
+    ) -> Result<(), i64> {
+        if !privileged
+            && [real, effective, saved]
+                .iter()
+                .any(|&id| id != KEEP && !self.holds(id))
+        {
+            return Err(EPERM);
+        }
+        if real != KEEP {
+            self.real = real;
+        }
+        if effective != KEEP {
+            self.effective = effective;
+        }
+        if saved != KEEP {
+            self.saved = saved;
+        }
+        self.fs = self.effective;
+        Ok(())
+    }
+
+    /// `setfsuid(2)`/`setfsgid(2)`: always returns the previous value and
+    /// silently ignores a change the caller may not make.
+    fn set_fs(&mut self, id: u32, privileged: bool) -> u32 {
+        let old = self.fs;
+        if id != KEEP && (privileged || self.holds(id) || id == self.fs) {
+            self.fs = id;
+        }
+        old
+    }
+}
+
+/// One thread group's credentials. The default is root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub user: Ids,
+    pub group: Ids,
+    pub groups: Vec<u32>,
+    /// Whether an unprivileged process with matching ids may trace this
+    /// one (Linux's `PR_GET_DUMPABLE`).
+    pub dumpable: bool,
+}
+
+impl Default for Credentials {
+    fn default() -> Self {
+        Self {
+            user: Ids::default(),
+            group: Ids::default(),
+            groups: Vec::new(),
+            dumpable: true,
+        }
+    }
+}
+
+impl Credentials {
+    /// Privilege for the `set*id` family.
+    pub fn privileged(&self) -> bool {
+        self.user.effective == 0
+    }
+
+    /// Privilege for file access, `chmod` and `chown`.
+    pub fn fs_privileged(&self) -> bool {
+        self.user.fs == 0
+    }
+
+    pub fn in_group(&self, gid: u32) -> bool {
+        gid == self.group.fs || self.groups.contains(&gid)
+    }
+
+    /// Whether the filesystem ids grant every bit of `want`
+    /// (`R_OK | W_OK | X_OK`) on an object with metadata `meta`.
+    pub fn permits(&self, meta: &UnixMetadata, want: u32) -> bool {
+        if self.fs_privileged() {
+            return true;
+        }
+        let class = if self.user.fs == meta.uid {
+            meta.mode >> 6
+        } else if self.in_group(meta.gid) {
+            meta.mode >> 3
+        } else {
+            meta.mode
+        };
+        class & want & 7 == want & 7
+    }
+
+    /// The same credentials with the filesystem ids replaced by the real
+    /// ones, as `access(2)` checks.
+    pub fn as_real(&self) -> Self {
+        let mut real = self.clone();
+        real.user.fs = real.user.real;
+        real.group.fs = real.group.real;
+        real
+    }
+
+    /// `execve` of a file with metadata `meta` (`None` for images that
+    /// carry none, such as the `/bin` namespace).
+    pub fn exec(&mut self, meta: Option<&UnixMetadata>) {
+        if let Some(meta) = meta.filter(|meta| meta.mode & S_IFMT == S_IFREG) {
+            if meta.mode & S_ISUID != 0 {
+                self.user.effective = meta.uid;
+            }
+            if meta.mode & (S_ISGID | S_IXGRP) == S_ISGID | S_IXGRP {
+                self.group.effective = meta.gid;
+            }
+        }
+        self.user.saved = self.user.effective;
+        self.user.fs = self.user.effective;
+        self.group.saved = self.group.effective;
+        self.group.fs = self.group.effective;
+        self.dumpable =
+            self.user.effective == self.user.real && self.group.effective == self.group.real;
+    }
+
+    /// Clear `dumpable` if the effective or filesystem ids differ from
+    /// `old`'s, as after a `set*id` call that changed the identity.
+    fn commit(&mut self, old: &Credentials) {
+        let identity =
+            |c: &Credentials| (c.user.effective, c.user.fs, c.group.effective, c.group.fs);
+        if identity(self) != identity(old) {
+            self.dumpable = false;
+        }
+    }
+
+    /// Validate `chmod(meta, mode)` and return the bits to store. Only the
+    /// owner may change the mode; a caller outside the file's group
+    /// cannot set its set-group-ID bit.
+    pub fn chmod_mode(&self, meta: &UnixMetadata, mode: u32) -> Result<u32, i64> {
+        let mut mode = mode & 0o7777;
+        if !self.fs_privileged() {
+            if self.user.fs != meta.uid {
+                return Err(EPERM);
+            }
+            if !self.in_group(meta.gid) {
+                mode &= !S_ISGID;
+            }
+        }
+        Ok(mode)
+    }
+
+    /// Validate `chown(meta, uid, gid)`. The owner may only hand the file
+    /// to one of its own groups; any other change needs privilege.
+    pub fn may_chown(&self, meta: &UnixMetadata, uid: u32, gid: u32) -> bool {
+        if self.fs_privileged() {
+            return true;
+        }
+        let owner = self.user.fs == meta.uid;
+        let uid_ok = uid == KEEP || (owner && uid == meta.uid);
+        let gid_ok = gid == KEEP || (owner && (gid == meta.gid || self.in_group(gid)));
+        uid_ok && gid_ok
+    }
+
+    /// Whether the caller may remove `victim` from directory `dir`, which
+    /// it may already write: the sticky bit restricts removal to the
+    /// owners of the entry or of the directory.
+    pub fn may_delete(&self, dir: &UnixMetadata, victim: &UnixMetadata) -> bool {
+        self.fs_privileged()
+            || dir.mode & S_ISVTX == 0
+            || self.user.fs == dir.uid
+            || self.user.fs == victim.uid
+    }
+
+    /// Whether a process with these credentials may signal one with
+    /// `target`: root, or a real or effective uid matching the target's
+    /// real or saved uid.
+    pub fn may_signal(&self, target: &Credentials) -> bool {
+        self.privileged()
+            || [self.user.real, self.user.effective]
+                .iter()
+                .any(|&uid| uid == target.user.real || uid == target.user.saved)
+    }
+
+    /// Whether a process with these credentials may trace one with
+    /// `target`: root, or a dumpable target whose real, effective and saved
+    /// ids all equal the caller's real ones. Stricter than
+    /// [`Self::may_signal`], which would let a user trace a set-user-ID
+    /// program it started.
+    pub fn may_ptrace(&self, target: &Credentials) -> bool {
+        self.privileged()
+            || (target.dumpable
+                && target.user.to_array()[..3]
+                    .iter()
+                    .all(|&uid| uid == self.user.real)
+                && target.group.to_array()[..3]
+                    .iter()
+                    .all(|&gid| gid == self.group.real))
+    }
+}
+
+/// Mode bits a `chown` leaves on a regular file: changing ownership drops
+/// set-user-ID and an effective set-group-ID.
+pub fn chown_mode(meta: &UnixMetadata) -> Option<u32> {
+    if meta.mode & S_IFMT != S_IFREG {
+        return None;
+    }
+    let mut mode = meta.mode & 0o7777;
+    mode &= !S_ISUID;
+    if mode & S_IXGRP != 0 {
+        mode &= !S_ISGID;
+    }
+    (mode != meta.mode & 0o7777).then_some(mode)
+}
+
+/// The caller's credentials.
+pub fn current() -> Credentials {
+    crate::userland::lifecycle::with_current_group(|p| p.credentials.clone())
+}
+
+/// The credentials of thread group `tgid`.
+pub fn of_group(tgid: u32) -> Option<Credentials> {
+    crate::userland::lifecycle::with_group(tgid, |p| p.credentials.clone())
+}
+
+fn parent_of(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(index) => &path[..index],
+    }
+}
+
+/// Search permission on every directory above `path`.
+fn check_search(creds: &Credentials, path: &str) -> Result<(), i64> {
+    for (index, _) in path.match_indices('/') {
+        let dir = if index == 0 { "/" } else { &path[..index] };
+        if let Ok(meta) = crate::fs::vfs::vfs_unix_metadata(dir) {
+            if !creds.permits(&meta, X_OK) {
+                return Err(EACCES);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check `want` on `path` for `creds`, plus search permission on its
+/// ancestors.
+pub fn check_path_as(creds: &Credentials, path: &str, want: u32) -> Result<(), i64> {
+    if creds.fs_privileged() {
+        return Ok(());
+    }
+    check_search(creds, path)?;
+    match crate::fs::vfs::vfs_unix_metadata(path) {
+        Ok(meta) if !creds.permits(&meta, want) => Err(EACCES),
+        _ => Ok(()),
+    }
+}
+
+/// Check `want` on `path` for the caller.
+pub fn check_path(path: &str, want: u32) -> Result<(), i64> {
+    check_path_as(&current(), path, want)
+}
+
+/// Check that the caller may add an entry at `path`: write and search
+/// permission on its parent directory.
+pub fn check_create(path: &str) -> Result<(), i64> {
+    check_path(parent_of(path), W_OK | X_OK)
+}
+
+/// Check that the caller may remove the entry at `path`: write and search
+/// permission on its parent, and the sticky-directory rule.
+pub fn check_delete(path: &str) -> Result<(), i64> {
+    let creds = current();
+    if creds.fs_privileged() {
+        return Ok(());
+    }
+    let parent = parent_of(path);
+    check_path_as(&creds, parent, W_OK | X_OK)?;
+    let (Ok(dir), Ok(victim)) = (
+        crate::fs::vfs::vfs_unix_metadata(parent),
+        crate::fs::vfs::vfs_symlink_metadata(path),
+    ) else {
+        return Ok(());
+    };
+    if creds.may_delete(&dir, &victim) {
+        Ok(())
+    } else {
+        Err(EPERM)
+    }
+}
+
+/// Give a freshly created `path` the requested permission bits less the
+/// caller's umask, and the caller's filesystem ids. Filesystems without
+/// permission metadata keep their defaults.
+pub fn init_new_node(path: &str, mode: u32) {
+    let (creds, umask) =
+        crate::userland::lifecycle::with_current_group(|p| (p.credentials.clone(), p.umask));
+    let owner =
+        (creds.user.fs != 0 || creds.group.fs != 0).then_some((creds.user.fs, creds.group.fs));
+    crate::fs::vfs::vfs_init_attributes(path, mode & !umask & 0o7777, owner);
+}
+
+// ---------- syscalls ----------
+
+fn update(f: impl FnOnce(&mut Credentials) -> Result<(), i64>) -> i64 {
+    crate::userland::lifecycle::with_current_group(|p| {
+        let old = p.credentials.clone();
+        f(&mut p.credentials)?;
+        p.credentials.commit(&old);
+        Ok(())
+    })
+    .map_or_else(|e| e, |_| 0)
+}
+
+fn write_ids(ptrs: [u64; 3], ids: [u32; 3]) -> i64 {
+    for (ptr, id) in ptrs.into_iter().zip(ids) {
+        if let Err(e) = crate::userland::usercopy::write_unaligned(ptr, &id) {
+            return e;
+        }
+    }
+    0
+}
+
+/// `getuid() -> uid_t`
+pub fn getuid_handler(_: &mut SyscallArgs) -> i64 {
+    current().user.real as i64
+}
+
+/// `geteuid() -> uid_t`
+pub fn geteuid_handler(_: &mut SyscallArgs) -> i64 {
+    current().user.effective as i64
+}
+
+/// `getgid() -> gid_t`
+pub fn getgid_handler(_: &mut SyscallArgs) -> i64 {
+    current().group.real as i64
+}
+
+/// `getegid() -> gid_t`
+pub fn getegid_handler(_: &mut SyscallArgs) -> i64 {
+    current().group.effective as i64
+}
+
+/// `getresuid(*ruid, *euid, *suid) -> int`
+pub fn getresuid_handler(args: &mut SyscallArgs) -> i64 {
+    let ids = current().user;
+    write_ids(
+        [args.rdi, args.rsi, args.rdx],
+        [ids.real, ids.effective, ids.saved],
+    )
+}
+
+/// `getresgid(*rgid, *egid, *sgid) -> int`
+pub fn getresgid_handler(args: &mut SyscallArgs) -> i64 {
+    let ids = current().group;
+    write_ids(
+        [args.rdi, args.rsi, args.rdx],
+        [ids.real, ids.effective, ids.saved],
+    )
+}
+
+/// `setuid(uid) -> int`
+pub fn setuid_handler(args: &mut SyscallArgs) -> i64 {
+    update(|c| {
+        let privileged = c.privileged();
+        c.user.set(args.rdi as u32, privileged)
+    })
+}
+
+/// `setgid(gid) -> int`
+pub fn setgid_handler(args: &mut SyscallArgs) -> i64 {
+    update(|c| {
+        let privileged = c.privileged();
+        c.group.set(args.rdi as u32, privileged)
+    })
+}
+
+/// `setreuid(ruid, euid) -> int`
+pub fn setreuid_handler(args: &mut SyscallArgs) -> i64 {
+    update(|c| {
+        let privileged = c.privileged();
+        c.user
+            .set_real_effective(args.rdi as u32, args.rsi as u32, privileged)
+    })
+}
+
+/// `setregid(rgid, egid) -> int`
+pub fn setregid_handler(args: &mut SyscallArgs) -> i64 {
+    update(|c| {
+        let privileged = c.privileged();
+        c.group
+            .set_real_effective(args.rdi as u32, args.rsi as u32, privileged)
+    })
+}
+
+/// `setresuid(ruid, euid, suid) -> int`
+pub fn setresuid_handler(args: &mut SyscallArgs) -> i64 {
+    update(|c| {
+        let privileged = c.privileged();
+        c.user.set_all(
+            args.rdi as u32,
+            args.rsi as u32,
+            args.rdx as u32,
+            privileged,
+        )
+    })
+}
+
+/// `setresgid(rgid, egid, sgid) -> int`
+pub fn setresgid_handler(args: &mut SyscallArgs) -> i64 {
+    update(|c| {
+        let privileged = c.privileged();
+        c.group.set_all(
+            args.rdi as u32,
+            args.rsi as u32,
+            args.rdx as u32,
+            privileged,
+        )
+    })
+}
+
+/// `setfsuid(fsuid) -> old_fsuid`
+pub fn setfsuid_handler(args: &mut SyscallArgs) -> i64 {
+    crate::userland::lifecycle::with_current_group(|p| {
+        let old = p.credentials.clone();
+        let privileged = p.credentials.privileged();
+        let previous = p.credentials.user.set_fs(args.rdi as u32, privileged);
+        p.credentials.commit(&old);
+        previous as i64
+    })
+}
+
+/// `setfsgid(fsgid) -> old_fsgid`
+pub fn setfsgid_handler(args: &mut SyscallArgs) -> i64 {
+    crate::userland::lifecycle::with_current_group(|p| {
+        let old = p.credentials.clone();
+        let privileged = p.credentials.privileged();
+        let previous = p.credentials.group.set_fs(args.rdi as u32, privileged);
+        p.credentials.commit(&old);
+        previous as i64
+    })
+}
+
+/// `getgroups(size, *list) -> int`. `size == 0` only counts.
+pub fn getgroups_handler(args: &mut SyscallArgs) -> i64 {
+    let size = args.rdi as i32;
+    if size < 0 {
+        return EINVAL;
+    }
+    let groups = current().groups;
+    if size == 0 {
+        return groups.len() as i64;
+    }
+    if (size as usize) < groups.len() {
+        return EINVAL;
+    }
+    let bytes: Vec<u8> = groups.iter().flat_map(|gid| gid.to_le_bytes()).collect();
+    match crate::userland::usercopy::copy_to_user(args.rsi, &bytes) {
+        Ok(()) => groups.len() as i64,
+        Err(e) => e,
+    }
+}
+
+/// `setgroups(size, *list) -> int`. Needs privilege.
+pub fn setgroups_handler(args: &mut SyscallArgs) -> i64 {
+    let size = args.rdi as usize;
+    if size > NGROUPS_MAX {
+        return EINVAL;
+    }
+    if !current().privileged() {
+        return EPERM;
+    }
+    let mut bytes = alloc::vec![0u8; size * 4];
+    if size != 0 && crate::userland::usercopy::copy_from_user(&mut bytes, args.rsi).is_err() {
+        return EFAULT;
+    }
+    let mut groups: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+    update(|c| {
+        c.groups = groups;
+        Ok(())
+    })
+}
+
+/// Permission for the caller to signal thread group `tgid`.
+pub fn check_signal(tgid: u32) -> Result<(), i64> {
+    let target = of_group(tgid).ok_or(ESRCH)?;
+    if current().may_signal(&target) {
+        Ok(())
+    } else {
+        Err(EPERM)
+    }
+}
+
+/// Permission for the caller to trace thread group `tgid`.
+pub fn check_ptrace(tgid: u32) -> Result<(), i64> {
+    let target = of_group(tgid).ok_or(ESRCH)?;
+    if current().may_ptrace(&target) {
+        Ok(())
+    } else {
+        Err(EPERM)
+    }
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn meta(mode: u32, uid: u32, gid: u32) -> UnixMetadata {
+        UnixMetadata {
+            inode: 0,
+            mode,
+            uid,
+            gid,
+            links: 1,
+            size: 0,
+            blocks_512: 0,
+            block_size: 512,
+            accessed: crate::fs::filesystem::UnixTimestamp::ZERO,
+            modified: crate::fs::filesystem::UnixTimestamp::ZERO,
+            changed: crate::fs::filesystem::UnixTimestamp::ZERO,
+        }
+    }
+
+    fn user(uid: u32, gid: u32) -> Credentials {
+        Credentials {
+            user: Ids::all(uid),
+            group: Ids::all(gid),
+            groups: Vec::new(),
+            dumpable: true,
+        }
+    }
+
+    fn test_setuid_rules() {
+        let mut root = Credentials::default();
+        assert_eq!(root.user.set(1000, root.privileged()), Ok(()));
+        assert_eq!(root.user, Ids::all(1000));
+        // Once every id is unprivileged there is no way back.
+        assert_eq!(root.user.set(0, root.privileged()), Err(EPERM));
+
+        // A set-user-ID program may swap between its real and saved ids.
+        let mut suid = user(1000, 1000);
+        suid.user.effective = 0;
+        suid.user.saved = 0;
+        assert_eq!(suid.user.set_real_effective(KEEP, 1000, true), Ok(()));
+        assert_eq!(suid.user.saved, 0, "effective == old real keeps saved");
+        assert!(!suid.privileged());
+        assert_eq!(suid.user.set_real_effective(KEEP, 0, false), Ok(()));
+        assert!(suid.privileged());
+        assert_eq!(suid.user.set_all(KEEP, 2000, KEEP, false), Err(EPERM));
+        assert_eq!(suid.user.set_all(1000, 1000, 1000, false), Ok(()));
+        assert_eq!(suid.user, Ids::all(1000));
+    }
+
+    fn test_setfsuid_reports_old_value() {
+        let mut creds = user(1000, 1000);
+        assert_eq!(creds.user.set_fs(0, false), 1000);
+        assert_eq!(creds.user.fs, 1000, "unprivileged change ignored");
+        assert_eq!(creds.user.set_fs(KEEP, false), 1000);
+        let mut root = Credentials::default();
+        assert_eq!(root.user.set_fs(1000, true), 0);
+        assert!(!root.fs_privileged());
+        assert!(root.privileged());
+    }
+
+    fn test_permission_classes() {
+        let mut creds = user(1000, 1000);
+        let file = meta(0o100640, 1000, 50);
+        assert!(creds.permits(&file, R_OK | W_OK));
+        assert!(!creds.permits(&file, X_OK));
+        // The owner class applies even when it is stricter than group.
+        let locked = meta(0o100074, 1000, 1000);
+        assert!(!creds.permits(&locked, R_OK));
+        let shared = meta(0o100640, 0, 50);
+        assert!(!creds.permits(&shared, R_OK));
+        creds.groups.push(50);
+        assert!(creds.permits(&shared, R_OK));
+        assert!(!creds.permits(&shared, W_OK));
+        assert!(Credentials::default().permits(&meta(0o100000, 5, 5), R_OK | W_OK | X_OK));
+    }
+
+    fn test_exec_applies_set_id_bits() {
+        let mut creds = user(1000, 1000);
+        creds.exec(Some(&meta(0o104755, 0, 0)));
+        assert_eq!(creds.user.effective, 0);
+        assert_eq!(creds.user.saved, 0);
+        assert_eq!(creds.user.real, 1000);
+        assert!(!creds.dumpable);
+        // Set-group-ID without group execute is mandatory locking, not setgid.
+        creds.exec(Some(&meta(0o102744, 0, 7)));
+        assert_eq!(creds.group.effective, 1000);
+        creds.exec(Some(&meta(0o102755, 0, 7)));
+        assert_eq!(creds.group.effective, 7);
+        // Directories never change credentials.
+        let mut plain = user(1000, 1000);
+        plain.exec(Some(&meta(0o044755, 0, 0)));
+        assert_eq!(plain, user(1000, 1000));
+    }
+
+    fn test_chmod_chown_rules() {
+        let creds = user(1000, 1000);
+        let own = meta(0o100644, 1000, 50);
+        assert_eq!(creds.chmod_mode(&own, 0o2755), Ok(0o755));
+        assert_eq!(creds.chmod_mode(&meta(0o100644, 0, 0), 0o777), Err(EPERM));
+        assert!(creds.may_chown(&own, KEEP, 1000));
+        assert!(creds.may_chown(&own, 1000, 50));
+        assert!(!creds.may_chown(&own, 0, KEEP));
+        assert!(!creds.may_chown(&own, KEEP, 60));
+        assert_eq!(chown_mode(&meta(0o106755, 0, 0)), Some(0o755));
+        assert_eq!(chown_mode(&meta(0o102755, 0, 0)), Some(0o755));
+        // Without group execute the set-group-ID bit survives.
+        assert_eq!(chown_mode(&meta(0o102745, 0, 0)), None);
+        assert_eq!(chown_mode(&meta(0o040755, 0, 0)), None);
+    }
+
+    fn test_sticky_and_signal_rules() {
+        let creds = user(1000, 1000);
+        let tmp = meta(0o041777, 0, 0);
+        assert!(creds.may_delete(&tmp, &meta(0o100644, 1000, 1000)));
+        assert!(!creds.may_delete(&tmp, &meta(0o100644, 2000, 2000)));
+        assert!(creds.may_delete(&meta(0o040777, 0, 0), &meta(0o100644, 2000, 2000)));
+        assert!(creds.may_signal(&user(1000, 5)));
+        assert!(!creds.may_signal(&Credentials::default()));
+        assert!(Credentials::default().may_signal(&creds));
+    }
+
+    fn test_ptrace_needs_every_id_and_dumpable() {
+        let creds = user(1000, 1000);
+        assert!(creds.may_ptrace(&user(1000, 1000)));
+        assert!(!creds.may_ptrace(&user(1000, 5)));
+        // A set-user-ID root program the user started: signallable, not
+        // traceable.
+        let mut suid = user(1000, 1000);
+        suid.exec(Some(&meta(0o104755, 0, 0)));
+        assert!(creds.may_signal(&suid));
+        assert!(!creds.may_ptrace(&suid));
+        // Dropping back to the real ids does not make it dumpable again.
+        suid.user = Ids::all(1000);
+        assert!(!creds.may_ptrace(&suid));
+        assert!(Credentials::default().may_ptrace(&suid));
+        // A plain exec restores dumpability.
+        suid.exec(Some(&meta(0o100755, 0, 0)));
+        assert!(creds.may_ptrace(&suid));
+    }
+
+    fn test_identity_change_clears_dumpable() {
+        let mut root = Credentials::default();
+        let old = root.clone();
+        root.groups.push(5);
+        root.commit(&old);
+        assert!(root.dumpable, "supplementary groups are not the identity");
+        let old = root.clone();
+        assert_eq!(root.user.set(1000, true), Ok(()));
+        root.commit(&old);
+        assert!(!root.dumpable);
+        let mut fs = user(1000, 1000);
+        let old = fs.clone();
+        fs.group.set_fs(1000, false);
+        fs.commit(&old);
+        assert!(fs.dumpable, "an unchanged fsgid keeps it dumpable");
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_setuid_rules,
+            &test_setfsuid_reports_old_value,
+            &test_permission_classes,
+            &test_exec_applies_set_id_bits,
+            &test_chmod_chown_rules,
+            &test_sticky_and_signal_rules,
+            &test_ptrace_needs_every_id_and_dumpable,
+            &test_identity_change_clears_dumpable,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as credentials_tests;
@@ -8,6 +8,14 @@ pub enum DeviceNode {
     /// which opens `/dev/null` O_RDWR unconditionally at startup, and by
     /// ordinary shell `> /dev/null` redirection.
     Null,
+    /// Pseudo-terminal multiplexer: each open allocates a new pty.
+    Ptmx,
+    /// The caller's controlling terminal.
+    Tty,
+    /// Directory of the live pty slaves.
+    PtsDirectory,
+    /// Slave end of the pty with this index.
+    Pts(u32),
 }
 
 pub fn classify(path: &str) -> Option<DeviceNode> {
@@ -15,10 +23,40 @@ pub fn classify(path: &str) -> Option<DeviceNode> {
         "/dev" | "/dev/" => Some(DeviceNode::Directory),
         "/dev/urandom" => Some(DeviceNode::Urandom),
         "/dev/null" => Some(DeviceNode::Null),
-        _ => None,
+        "/dev/ptmx" => Some(DeviceNode::Ptmx),
+        "/dev/tty" => Some(DeviceNode::Tty),
+        "/dev/pts" | "/dev/pts/" => Some(DeviceNode::PtsDirectory),
+        _ => path
+            .strip_prefix("/dev/pts/")
+            .and_then(pts_index)
+            .filter(|&index| {
+                crate::terminal::pty::is_active_for_terminal(crate::terminal::pty::TerminalId(
+                    index,
+                ))
+            })
+            .map(DeviceNode::Pts),
     }
 }
 
+/// Parse a `/dev/pts` entry name, which is written without leading zeros.
+fn pts_index(name: &str) -> Option<u32> {
+    let canonical = !name.is_empty()
+        && name.bytes().all(|b| b.is_ascii_digit())
+        && (name == "0" || !name.starts_with('0'));
+    canonical.then(|| name.parse().ok()).flatten()
+}
+
+/// Mount point of the RAM-only tmpfs behind POSIX shared memory
+/// (`shm_open`) and `memfd_create`.
+pub const SHM_DIR: &str = "/dev/shm";
+
+/// True for `/dev/shm` and everything below it. That subtree is an ordinary
+/// writable mount, not part of the synthetic namespace.
+pub fn is_shm_path(path: &str) -> bool {
+    path.strip_prefix(SHM_DIR)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
 pub fn is_dev_path(path: &str) -> bool {
-    path == "/dev" || path == "/dev/" || path.starts_with("/dev/")
+    (path == "/dev" || path == "/dev/" || path.starts_with("/dev/")) && !is_shm_path(path)
 }
@@ -154,6 +154,8 @@ pub fn epoll_ctl_handler(args: &mut SyscallArgs) -> i64 {
             } else {
                 let generation = match &target {
                     FdSlot::EventFd { handle, .. } => handle.generation(),
+                    FdSlot::TimerFd { handle, .. } => handle.generation(),
+                    FdSlot::Inotify { handle, .. } => handle.generation(),
                     _ => 0,
                 };
                 registrations.insert(
@@ -163,8 +165,9 @@ pub fn epoll_ctl_handler(args: &mut SyscallArgs) -> i64 {
                         events: event.events,
                         data: event.data,
                         last_ready: 0,
-                        // A currently-ready eventfd must be reported once
-                        // after ADD, so start one generation behind.
+                        // A currently-ready eventfd, timerfd or inotify must be
+                        // reported once after ADD, so start one generation
+                        // behind.
                         last_generation: generation.saturating_sub(1),
                         revision: epoll.revision(),
                     },
@@ -258,6 +261,8 @@ fn wait_common(args: &SyscallArgs, reject_nonnull_mask: bool) -> i64 {
         let edge = registration.events & EPOLLET != 0;
         let generation = match &registration.slot {
             FdSlot::EventFd { handle, .. } => Some(handle.generation()),
+            FdSlot::TimerFd { handle, .. } => Some(handle.generation()),
+            FdSlot::Inotify { handle, .. } => Some(handle.generation()),
             _ => None,
         };
         let deliver = if !edge {
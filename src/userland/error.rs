@@ -17,7 +17,7 @@ pub enum LoaderError {
     /// `e_ident[EI_CLASS] != ELFCLASS64`, `e_ident[EI_DATA] != ELFDATA2LSB`,
     /// or `e_machine != EM_X86_64`.
     WrongArch,
-    /// `e_type != ET_EXEC` (we accept only static non-PIE executables, D3).
+    /// `e_type` is neither `ET_EXEC` nor `ET_DYN`.
     WrongType,
     /// File ended before a header field, segment payload, or section the
     /// loader needed could be read. Also covers `e_phoff + phnum * phentsize`
@@ -40,8 +40,14 @@ pub enum LoaderError {
     UnresolvedImport,
     /// PT_TLS encountered. Rejected explicitly rather than silently ignored.
     TlsUnsupported,
-    /// PT_INTERP encountered (we do not run a dynamic linker).
+    /// PT_INTERP is malformed, or was met on the eager (non-file-backed)
+    /// load path.
     InterpUnsupported,
+    /// The PT_INTERP path could not be opened or read.
+    InterpMissing,
+    /// The PT_INTERP interpreter is not a loadable x86-64 ELF image, or
+    /// itself has a PT_INTERP.
+    InterpBad,
     /// `p_align != 0x1000` or `p_offset % align != p_vaddr % align`.
     AlignmentBad,
     /// Frame allocator exhausted while mapping a PT_LOAD or stack page.
@@ -1,8 +1,9 @@
 //! Kernel-managed runtime configuration under `/etc`.
 //!
 //! The root overlay is available before this module is initialized. Static
-//! account/hosts files and the shipped zsh configuration are recreated on
-//! every boot, while `resolv.conf` is published later from the active DHCP
+//! account/hosts files, the shipped zsh configuration and the zoneinfo
+//! database are recreated on every boot, `localtime` follows the time zone
+//! setting, and `resolv.conf` is published later from the active DHCP
 //! lease. Userland mutation syscalls treat the entire namespace as managed;
 //! kernel VFS calls intentionally bypass that policy.
 
@@ -16,6 +17,9 @@ pub const RESOLV_CONF_TEMP_PATH: &str = "/etc/.resolv.conf.new";
 pub const THEME_PATH: &str = "/etc/theme";
 pub const CA_CERT_PATH: &str = "/etc/ssl/cert.pem";
 const CA_CERT_TEMP_PATH: &str = "/etc/ssl/.cert.pem.new";
+const LOCALTIME_PATH: &str = "/etc/localtime";
+const LOCALTIME_TEMP_PATH: &str = "/etc/.localtime.new";
+const ZONEINFO_DIR: &str = "/etc/zoneinfo";
 
 const PASSWD_PATH: &str = "/etc/passwd";
 const GROUP_PATH: &str = "/etc/group";
@@ -31,9 +35,14 @@ const ZSHRC_SOURCE_PATH: &str = "/host/etc/zshrc";
 const ZSH_THEME_SOURCE_PATH: &str = "/host/etc/zsh/agnoster.zsh-theme";
 const ZSH_FUNCTIONS_SOURCE_DIR: &str = "/host/etc/zsh/functions";
 const ZSH_FUNCTIONS_MANIFEST_PATH: &str = "/host/etc/zsh/functions.manifest";
+const ZONE_LIST_PATH: &str = "/etc/zoneinfo/zone.list";
+const ZONEINFO_SOURCE_DIR: &str = "/host/etc/zoneinfo";
+const ZONEINFO_MANIFEST_PATH: &str = "/host/etc/zoneinfo.manifest";
+const TZIF_MAGIC: &[u8] = b"TZif";
 
-const PASSWD_CONTENT: &[u8] = b"root:x:0:0::/root:/bin/zsh\n";
-const GROUP_CONTENT: &[u8] = b"root:x:0:\n";
+const PASSWD_CONTENT: &[u8] =
+    b"root:x:0:0::/root:/bin/zsh\nnobody:x:65534:65534:nobody:/nonexistent:/bin/false\n";
+const GROUP_CONTENT: &[u8] = b"root:x:0:\nnogroup:x:65534:\n";
 
 const GITCONFIG_PATH: &str = "/etc/gitconfig";
 /// System git defaults. Everything runs as uid 0 with no per-user
@@ -94,6 +103,15 @@ pub fn init() {
     write_file(GITCONFIG_PATH, GITCONFIG_CONTENT);
     seed_zsh_config();
     seed_ca_certificates();
+    seed_zoneinfo();
+
+    // Without a usable zone file readers fall back to UTC, so a stale copy
+    // from an earlier boot must not survive.
+    let zone = crate::system_control::time_zone();
+    if !publish_time_zone(&zone) {
+        crate::debug_warn!("time zone {} unavailable; local time is UTC", zone);
+        remove_if_present(LOCALTIME_PATH);
+    }
 }
 
 /// Publish the boot-selected frame/control theme as `/etc/theme` so ring-3
@@ -235,6 +253,94 @@ fn seed_ca_certificates() {
     }
 }
 
+fn seed_zoneinfo() {
+    match crate::fs::vfs::vfs_mkdir(ZONEINFO_DIR) {
+        Ok(()) | Err(FilesystemError::AlreadyExists) => {}
+        Err(error) => {
+            crate::debug_warn!("failed to create managed {}: {:?}", ZONEINFO_DIR, error);
+            return;
+        }
+    }
+
+    let manifest = match File::open_read(ZONEINFO_MANIFEST_PATH).and_then(|file| file.read_to_vec())
+    {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            crate::debug_warn!(
+                "failed to read staged zoneinfo manifest {}: {:?}",
+                ZONEINFO_MANIFEST_PATH,
+                error
+            );
+            return;
+        }
+    };
+    let manifest = match core::str::from_utf8(&manifest) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            crate::debug_warn!("invalid staged zoneinfo manifest: {:?}", error);
+            return;
+        }
+    };
+
+    // The published list names only the zones that actually arrived, so the
+    // Control Center never offers one that cannot be selected.
+    let mut zone_list = alloc::string::String::new();
+    for name in manifest.lines() {
+        if !crate::system_control::valid_time_zone_name(name) {
+            crate::debug_warn!("ignored invalid staged zone name: {}", name);
+            continue;
+        }
+        for (end, _) in name.match_indices('/') {
+            let directory = format!("{}/{}", ZONEINFO_DIR, &name[..end]);
+            match crate::fs::vfs::vfs_mkdir(&directory) {
+                Ok(()) | Err(FilesystemError::AlreadyExists) => {}
+                Err(error) => {
+                    crate::debug_warn!("failed to create managed {}: {:?}", directory, error);
+                }
+            }
+        }
+        let source = format!("{ZONEINFO_SOURCE_DIR}/{name}");
+        let destination = format!("{ZONEINFO_DIR}/{name}");
+        let result = File::open_read(&source)
+            .and_then(|file| file.read_to_vec())
+            .and_then(|contents| write_file_result(&destination, &contents));
+        match result {
+            Ok(()) => {
+                zone_list.push_str(name);
+                zone_list.push('\n');
+            }
+            Err(error) => crate::debug_warn!("failed to import zone {}: {:?}", name, error),
+        }
+    }
+    write_file(ZONE_LIST_PATH, zone_list.as_bytes());
+}
+
+/// Publish `/etc/zoneinfo/<name>` as `/etc/localtime`, where musl and the GUI
+/// library read the local time zone. Returns `false`, leaving the current
+/// file in place, when the zone is missing or is not a TZif file.
+pub fn publish_time_zone(name: &str) -> bool {
+    let source = format!("{ZONEINFO_DIR}/{name}");
+    let contents = match File::open_read(&source).and_then(|file| file.read_to_vec()) {
+        Ok(contents) if contents.starts_with(TZIF_MAGIC) => contents,
+        Ok(_) => {
+            crate::debug_warn!("{} is not a TZif file", source);
+            return false;
+        }
+        Err(_) => return false,
+    };
+    if let Err(error) = write_file_result(LOCALTIME_TEMP_PATH, &contents) {
+        crate::debug_warn!("failed to seed {}: {:?}", LOCALTIME_TEMP_PATH, error);
+        remove_if_present(LOCALTIME_TEMP_PATH);
+        return false;
+    }
+    if let Err(error) = crate::fs::vfs::vfs_rename(LOCALTIME_TEMP_PATH, LOCALTIME_PATH) {
+        crate::debug_warn!("failed to publish {}: {:?}", LOCALTIME_PATH, error);
+        remove_if_present(LOCALTIME_TEMP_PATH);
+        return false;
+    }
+    true
+}
+
 fn copy_file(source: &str, destination: &str) {
     let result = File::open_read(source)
         .and_then(|file| file.read_to_vec())
@@ -308,11 +414,50 @@ mod tests {
         assert!(!crate::fs::exists(CA_CERT_TEMP_PATH));
     }
 
+    fn read_localtime() -> alloc::vec::Vec<u8> {
+        File::open_read(LOCALTIME_PATH)
+            .and_then(|file| file.read_to_vec())
+            .expect("published localtime readable")
+    }
+
+    fn test_zoneinfo_is_imported_and_listed() {
+        let list = File::open_read(ZONE_LIST_PATH)
+            .and_then(|file| file.read_to_vec())
+            .expect("zone list readable");
+        let list = core::str::from_utf8(&list).expect("zone list is UTF-8");
+        assert!(list.lines().any(|zone| zone == "UTC"));
+        assert!(list
+            .lines()
+            .any(|zone| zone == "America/Argentina/Buenos_Aires"));
+        for zone in list.lines() {
+            let contents = File::open_read(&format!("{ZONEINFO_DIR}/{zone}"))
+                .and_then(|file| file.read_to_vec())
+                .expect("listed zone readable");
+            assert!(contents.starts_with(TZIF_MAGIC), "{zone} is TZif");
+        }
+    }
+
+    fn test_publish_time_zone_replaces_localtime() {
+        assert!(publish_time_zone("Europe/London"));
+        let london = File::open_read("/etc/zoneinfo/Europe/London")
+            .and_then(|file| file.read_to_vec())
+            .expect("imported zone readable");
+        assert_eq!(read_localtime(), london);
+        assert!(!publish_time_zone("Nowhere/Atlantis"));
+        assert!(!publish_time_zone("zone.list"));
+        assert_eq!(read_localtime(), london);
+        assert!(!crate::fs::exists(LOCALTIME_TEMP_PATH));
+        // Leave the file matching the configured zone, as boot does.
+        assert!(publish_time_zone(&crate::system_control::time_zone()));
+    }
+
     pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
         &[
             &test_managed_path_is_component_bounded,
             &test_publish_theme_writes_theme_name,
             &test_ca_bundle_is_published_with_test_root,
+            &test_zoneinfo_is_imported_and_listed,
+            &test_publish_time_zone_replaces_localtime,
         ]
     }
 }
@@ -14,8 +14,12 @@ use crate::lib::arc::Arc;
 use crate::net::socket::SocketHandle;
 use crate::userland::epoll::EpollInstance;
 use crate::userland::eventfd::EventFd;
-use crate::userland::local_stream::LocalStreamEndpoint;
+use crate::userland::inotify::Inotify;
 use crate::userland::pipe::{PipeReadHandle, PipeWriteHandle};
+use crate::userland::pts::PtsHandle;
+use crate::userland::signalfd::SignalFd;
+use crate::userland::timerfd::TimerFd;
+use crate::userland::unix_socket::UnixSocket;
 use core::sync::atomic::{AtomicBool, Ordering};
 
 /// Maximum file descriptors per process. Bounded to keep the table size
@@ -123,7 +127,8 @@ pub enum FdSlot {
         cursor: usize,
         cloexec: bool,
     },
-    /// Synthetic `/dev` directory. Its entries are `null` and `urandom`.
+    /// Synthetic `/dev` directory: `null`, `ptmx`, `pts`, `shm`, `tty` and
+    /// `urandom`.
     VirtualDevDir {
         cursor: usize,
         cloexec: bool,
@@ -146,24 +151,46 @@ pub enum FdSlot {
         handle: Arc<EventFd>,
         cloexec: bool,
     },
+    /// A timer whose expirations are read as a count.
+    TimerFd {
+        handle: Arc<TimerFd>,
+        cloexec: bool,
+    },
+    /// Reads take the reader's pending signals in the handle's mask.
+    SignalFd {
+        handle: Arc<SignalFd>,
+        cloexec: bool,
+    },
+    /// Queue of filesystem change events for a set of watched paths.
+    Inotify {
+        handle: Arc<Inotify>,
+        cloexec: bool,
+    },
     /// Bounded epoll interest set. The instance is an open-file description:
     /// dup/fork share registrations, while close-on-exec stays per fd.
     Epoll {
         handle: Arc<EpollInstance>,
         cloexec: bool,
     },
-    LocalStream {
-        handle: Arc<LocalStreamEndpoint>,
+    /// Linux AF_UNIX socket (stream, datagram or seqpacket).
+    UnixSocket {
+        handle: Arc<UnixSocket>,
         cloexec: bool,
     },
     /// The master end of a pty, owned by a ring-3 terminal emulator
-    /// (`TERMINAL.ELF`). Reads drain the slave's output; writes push into the
-    /// slave's input. The slave side is reached by the child process through
-    /// its inherited `terminal_id` (the existing sentinel-stdio model).
+    /// (`TERMINAL.ELF`) or opened from `/dev/ptmx`. Reads drain the slave's
+    /// output; writes push into the slave's input. The emulator's child
+    /// reaches the slave side through its inherited `terminal_id` (the
+    /// existing sentinel-stdio model).
     PtyMaster {
         master: crate::terminal::pty::PtyMaster,
         cloexec: bool,
     },
+    /// A pty slave opened as `/dev/pts/N` or `/dev/tty`.
+    PtySlave {
+        handle: Arc<PtsHandle>,
+        cloexec: bool,
+    },
 }
 
 impl FdSlot {
@@ -183,9 +210,13 @@ impl FdSlot {
             | Self::DevNull { cloexec }
             | Self::GuiEvents { cloexec, .. }
             | Self::EventFd { cloexec, .. }
+            | Self::TimerFd { cloexec, .. }
+            | Self::SignalFd { cloexec, .. }
+            | Self::Inotify { cloexec, .. }
             | Self::Epoll { cloexec, .. }
-            | Self::LocalStream { cloexec, .. }
-            | Self::PtyMaster { cloexec, .. } => *cloexec,
+            | Self::UnixSocket { cloexec, .. }
+            | Self::PtyMaster { cloexec, .. }
+            | Self::PtySlave { cloexec, .. } => *cloexec,
             Self::PipeRead(_, cloexec) | Self::PipeWrite(_, cloexec) => *cloexec,
         }
     }
@@ -207,9 +238,13 @@ impl FdSlot {
             | Self::DevNull { cloexec }
             | Self::GuiEvents { cloexec, .. }
             | Self::EventFd { cloexec, .. }
+            | Self::TimerFd { cloexec, .. }
+            | Self::SignalFd { cloexec, .. }
+            | Self::Inotify { cloexec, .. }
             | Self::Epoll { cloexec, .. }
-            | Self::LocalStream { cloexec, .. }
-            | Self::PtyMaster { cloexec, .. } => *cloexec = value,
+            | Self::UnixSocket { cloexec, .. }
+            | Self::PtyMaster { cloexec, .. }
+            | Self::PtySlave { cloexec, .. } => *cloexec = value,
             Self::PipeRead(_, cloexec) | Self::PipeWrite(_, cloexec) => *cloexec = value,
         }
     }
@@ -251,15 +286,27 @@ impl FdSlot {
             (Self::EventFd { handle: left, .. }, Self::EventFd { handle: right, .. }) => {
                 Arc::ptr_eq(left, right)
             }
+            (Self::TimerFd { handle: left, .. }, Self::TimerFd { handle: right, .. }) => {
+                Arc::ptr_eq(left, right)
+            }
+            (Self::SignalFd { handle: left, .. }, Self::SignalFd { handle: right, .. }) => {
+                Arc::ptr_eq(left, right)
+            }
+            (Self::Inotify { handle: left, .. }, Self::Inotify { handle: right, .. }) => {
+                Arc::ptr_eq(left, right)
+            }
             (Self::Epoll { handle: left, .. }, Self::Epoll { handle: right, .. }) => {
                 Arc::ptr_eq(left, right)
             }
-            (Self::LocalStream { handle: left, .. }, Self::LocalStream { handle: right, .. }) => {
+            (Self::UnixSocket { handle: left, .. }, Self::UnixSocket { handle: right, .. }) => {
                 Arc::ptr_eq(left, right)
             }
             (Self::PtyMaster { master: left, .. }, Self::PtyMaster { master: right, .. }) => {
                 left.same_master(right)
             }
+            (Self::PtySlave { handle: left, .. }, Self::PtySlave { handle: right, .. }) => {
+                Arc::ptr_eq(left, right)
+            }
             (Self::VirtualBinDir { .. }, Self::VirtualBinDir { .. })
             | (Self::VirtualDevDir { .. }, Self::VirtualDevDir { .. }) => false,
             _ => false,
@@ -270,6 +317,10 @@ impl FdSlot {
 #[derive(Clone)]
 pub struct FdTable {
     slots: [Option<FdSlot>; FD_TABLE_SIZE],
+    /// One past the highest descriptor a new slot may take: the group's
+    /// `RLIMIT_NOFILE` soft limit, capped at the table size. Descriptors
+    /// already open above it stay valid.
+    limit: usize,
 }
 
 impl FdTable {
@@ -277,9 +328,20 @@ impl FdTable {
         const NONE: Option<FdSlot> = None;
         Self {
             slots: [NONE; FD_TABLE_SIZE],
+            limit: FD_TABLE_SIZE,
         }
     }
 
+    /// Apply an `RLIMIT_NOFILE` soft limit.
+    pub fn set_limit(&mut self, rlim_cur: u64) {
+        self.limit = rlim_cur.min(FD_TABLE_SIZE as u64) as usize;
+    }
+
+    /// One past the highest descriptor a new slot may take.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
     /// Pin slots 0/1/2 to the standard streams. Called by
     /// `enter_user_mode_with` before iretq so the binary's libc finds
     /// fd 0/1/2 already allocated.
@@ -330,9 +392,9 @@ impl FdTable {
     }
 
     /// Insert `slot` at the lowest available index ≥ 3 and return the
-    /// fd. Returns `None` when the table is full.
+    /// fd. Returns `None` when the table is full up to the limit.
     pub fn alloc(&mut self, slot: FdSlot) -> Option<i32> {
-        for i in 3..FD_TABLE_SIZE {
+        for i in 3..self.limit {
             if self.slots[i].is_none() {
                 self.slots[i] = Some(slot);
                 return Some(i as i32);
@@ -346,6 +408,7 @@ impl FdTable {
     /// intentionally left closed in the child.
     pub fn fork_clone(&self) -> Self {
         let mut cloned = Self::new();
+        cloned.limit = self.limit;
         for (index, slot) in self.slots.iter().enumerate() {
             cloned.slots[index] = match slot {
                 Some(FdSlot::GuiEvents { .. }) => None,
@@ -400,7 +463,7 @@ impl FdTable {
     /// `dup2(oldfd, newfd)` — close newfd if open, then place a clone
     /// of oldfd's slot at newfd. Returns the new fd.
     pub fn dup2(&mut self, oldfd: i32, newfd: i32) -> Option<i32> {
-        if newfd < 0 || (newfd as usize) >= FD_TABLE_SIZE {
+        if newfd < 0 || (newfd as usize) >= self.limit {
             return None;
         }
         if oldfd == newfd {
@@ -417,12 +480,12 @@ impl FdTable {
     /// `fcntl(F_DUPFD*)`: duplicate `fd` into the lowest free descriptor at
     /// or above `minimum`, choosing the new descriptor's FD_CLOEXEC value.
     pub fn dup_from(&mut self, fd: i32, minimum: i32, cloexec: bool) -> Option<i32> {
-        if minimum < 0 || minimum as usize >= FD_TABLE_SIZE {
+        if minimum < 0 || minimum as usize >= self.limit {
             return None;
         }
         let mut slot = self.get(fd)?.clone();
         slot.set_cloexec(cloexec);
-        for index in minimum as usize..FD_TABLE_SIZE {
+        for index in minimum as usize..self.limit {
             if self.slots[index].is_none() {
                 self.slots[index] = Some(slot);
                 return Some(index as i32);
@@ -459,7 +522,10 @@ impl FdTable {
             FdSlot::DevNull { cloexec } => *cloexec,
             FdSlot::GuiEvents { cloexec, .. } => *cloexec,
             FdSlot::EventFd { cloexec, .. } | FdSlot::Epoll { cloexec, .. } => *cloexec,
-            FdSlot::LocalStream { cloexec, .. } => *cloexec,
+            FdSlot::TimerFd { cloexec, .. } | FdSlot::SignalFd { cloexec, .. } => *cloexec,
+            FdSlot::Inotify { cloexec, .. } => *cloexec,
+            FdSlot::UnixSocket { cloexec, .. } => *cloexec,
+            FdSlot::PtyMaster { cloexec, .. } | FdSlot::PtySlave { cloexec, .. } => *cloexec,
             _ => false,
         })
     }
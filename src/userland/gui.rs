@@ -342,11 +342,9 @@ pub fn cleanup_process(pid: u32) {
 /// — including when the emulator crashed without killing its child.
 /// No-op for windows without a pty.
 pub fn release_window_pty(surface_id: WindowId) {
-    if !crate::terminal::pty::is_active_for_terminal(surface_id) {
-        return;
+    if let Some(terminal_id) = crate::terminal::pty::clear_for_window(surface_id) {
+        crate::userland::lifecycle::wake_ring3_blocked_on_input(Some(terminal_id));
     }
-    crate::terminal::pty::clear_for_terminal(surface_id);
-    crate::userland::lifecycle::wake_ring3_blocked_on_input(Some(surface_id));
 }
 
 pub fn encode_window_event(handle: u32, event: &Event) -> Option<GuiEvent> {
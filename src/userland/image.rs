@@ -98,6 +98,15 @@ pub struct UserImage {
     /// `e_phnum` from the ELF header — paired with `phdr_bytes` for
     /// `AT_PHNUM` and to size the on-stack phdr copy.
     pub e_phnum: u16,
+    /// Mapped VA of the program-header table, when a PT_LOAD covers it.
+    /// Dynamic and PIE images report this as `AT_PHDR` so the dynamic
+    /// linker can derive the load bias; `None` keeps the on-stack copy.
+    pub phdr_va: Option<u64>,
+    /// The program's own entry point, reported as `AT_ENTRY`. Equal to
+    /// `entry` unless a dynamic linker was mapped.
+    pub program_entry: VirtAddr,
+    /// Load base of the `PT_INTERP` dynamic linker, reported as `AT_BASE`.
+    pub interp_base: Option<u64>,
     /// Demand-grown stack — VA of the lowest initially-committed stack
     /// page. The loader maps `USER_STACK_INITIAL_PAGES` pages at this
     /// address and stops; growth is driven from the page-fault handler.
@@ -131,6 +140,9 @@ impl UserImage {
             tls_fs_base: None,
             phdr_bytes: Vec::new(),
             e_phnum: 0,
+            phdr_va: None,
+            program_entry: entry,
+            interp_base: None,
             stack_initial_bottom: 0,
             stack_max_growth_floor: 0,
             mappings: Vec::new(),
@@ -162,6 +174,18 @@ impl UserImage {
         self.e_phnum = e_phnum;
     }
 
+    /// Record the mapped program-header table VA for AT_PHDR.
+    pub fn set_phdr_va(&mut self, phdr_va: u64) {
+        self.phdr_va = Some(phdr_va);
+    }
+
+    /// Enter ring 3 at the dynamic linker instead of the program. The
+    /// program entry is kept for `AT_ENTRY`.
+    pub fn set_interpreter(&mut self, entry: VirtAddr, base: u64) {
+        self.entry = entry;
+        self.interp_base = Some(base);
+    }
+
     /// Record a mapping that has just been installed via `map_user_region`.
     /// The drop path replays this list in reverse to call `unmap_user_region`
     /// for each.
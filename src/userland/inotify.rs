@@ -0,0 +1,670 @@
+//! Linux inotify open-file description.
+//!
+//! An instance holds a set of watches, each naming a path, and a queue of
+//! events. The VFS mutation paths report changes here by path after the
+//! filesystem operation succeeds, so every mounted filesystem reports the
+//! same way. A watch on a directory sees events for its entries (with the
+//! entry name); a watch on any object sees its own changes (without one).
+//!
+//! Watches are keyed by path rather than inode: a change made through
+//! another hard link is reported under the name it was made through, and an
+//! unlinked file's watch ends at once instead of at the last link.
+//! `IN_ACCESS`, `IN_OPEN` and the close events are accepted in a mask but
+//! never generated.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::lib::arc::{Arc, Weak};
+use crate::userland::abi::{EAGAIN, EBADF, EEXIST, EINVAL, EMFILE, ENOSPC, ENOTDIR};
+use crate::userland::fdtable::FdSlot;
+
+pub const IN_NONBLOCK: u32 = 0x800;
+pub const IN_CLOEXEC: u32 = 0x80000;
+
+const IN_MODIFY: u32 = 0x2;
+const IN_ATTRIB: u32 = 0x4;
+const IN_MOVED_FROM: u32 = 0x40;
+const IN_MOVED_TO: u32 = 0x80;
+const IN_CREATE: u32 = 0x100;
+const IN_DELETE: u32 = 0x200;
+const IN_DELETE_SELF: u32 = 0x400;
+const IN_MOVE_SELF: u32 = 0x800;
+const IN_ALL_EVENTS: u32 = 0xfff;
+const IN_Q_OVERFLOW: u32 = 0x4000;
+const IN_IGNORED: u32 = 0x8000;
+const IN_ONLYDIR: u32 = 0x0100_0000;
+const IN_DONT_FOLLOW: u32 = 0x0200_0000;
+const IN_EXCL_UNLINK: u32 = 0x0400_0000;
+const IN_MASK_CREATE: u32 = 0x1000_0000;
+const IN_MASK_ADD: u32 = 0x2000_0000;
+const IN_ISDIR: u32 = 0x4000_0000;
+const IN_ONESHOT: u32 = 0x8000_0000;
+
+const VALID_WATCH_FLAGS: u32 = IN_ALL_EVENTS
+    | IN_ONLYDIR
+    | IN_DONT_FOLLOW
+    | IN_EXCL_UNLINK
+    | IN_MASK_CREATE
+    | IN_MASK_ADD
+    | IN_ONESHOT;
+
+/// Linux `max_queued_events` and `max_user_watches` defaults (the latter
+/// applied per instance).
+const MAX_QUEUED_EVENTS: usize = 16384;
+const MAX_WATCHES: usize = 8192;
+
+/// Fixed part of `struct inotify_event`; names are NUL-padded to a multiple
+/// of it.
+const EVENT_HEADER: usize = 16;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Event {
+    wd: i32,
+    mask: u32,
+    cookie: u32,
+    name: String,
+}
+
+impl Event {
+    fn name_len(&self) -> usize {
+        if self.name.is_empty() {
+            0
+        } else {
+            (self.name.len() + 1).next_multiple_of(EVENT_HEADER)
+        }
+    }
+
+    fn record_len(&self) -> usize {
+        EVENT_HEADER + self.name_len()
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        let name_len = self.name_len();
+        out.extend_from_slice(&self.wd.to_ne_bytes());
+        out.extend_from_slice(&self.mask.to_ne_bytes());
+        out.extend_from_slice(&self.cookie.to_ne_bytes());
+        out.extend_from_slice(&(name_len as u32).to_ne_bytes());
+        out.extend_from_slice(self.name.as_bytes());
+        out.resize(out.len() + name_len - self.name.len(), 0);
+    }
+}
+
+struct Watch {
+    path: String,
+    mask: u32,
+}
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Event>,
+    watches: BTreeMap<i32, Watch>,
+    next_wd: i32,
+}
+
+impl State {
+    fn push(&mut self, event: Event) {
+        if self.queue.back() == Some(&event) {
+            return;
+        }
+        if self.queue.len() >= MAX_QUEUED_EVENTS {
+            if self
+                .queue
+                .back()
+                .is_some_and(|tail| tail.mask != IN_Q_OVERFLOW)
+            {
+                self.queue.push_back(Event {
+                    wd: -1,
+                    mask: IN_Q_OVERFLOW,
+                    cookie: 0,
+                    name: String::new(),
+                });
+            }
+            return;
+        }
+        self.queue.push_back(event);
+    }
+
+    /// Drop watch `wd` and queue its `IN_IGNORED`.
+    fn remove(&mut self, wd: i32) {
+        if self.watches.remove(&wd).is_some() {
+            WATCH_COUNT.fetch_sub(1, Ordering::AcqRel);
+            self.push(Event {
+                wd,
+                mask: IN_IGNORED,
+                cookie: 0,
+                name: String::new(),
+            });
+        }
+    }
+
+    /// Queue `mask` on every watch whose events include it, for a change
+    /// to `path`. Parent-directory watches get the entry name.
+    fn report(&mut self, path: &str, mask: u32, is_dir: bool, cookie: u32) {
+        let (parent, name) = split(path);
+        let flags = if is_dir { IN_ISDIR } else { 0 };
+        let mut oneshot = Vec::new();
+        let mut matched = Vec::new();
+        for (&wd, watch) in &self.watches {
+            if watch.mask & mask == 0 {
+                continue;
+            }
+            let name = if watch.path == parent && !name.is_empty() && mask != IN_MOVE_SELF {
+                name
+            } else if watch.path == path && mask & (IN_MODIFY | IN_ATTRIB | IN_MOVE_SELF) != 0 {
+                ""
+            } else {
+                continue;
+            };
+            matched.push(Event {
+                wd,
+                mask: mask | flags,
+                cookie,
+                name: String::from(name),
+            });
+            if watch.mask & IN_ONESHOT != 0 {
+                oneshot.push(wd);
+            }
+        }
+        for event in matched {
+            self.push(event);
+        }
+        for wd in oneshot {
+            self.remove(wd);
+        }
+    }
+
+    /// The object at `path` is gone: its own watch ends.
+    fn delete_self(&mut self, path: &str) {
+        let gone: Vec<i32> = self
+            .watches
+            .iter()
+            .filter(|(_, watch)| watch.path == path)
+            .map(|(&wd, _)| wd)
+            .collect();
+        for wd in gone {
+            if self.watches[&wd].mask & IN_DELETE_SELF != 0 {
+                self.push(Event {
+                    wd,
+                    mask: IN_DELETE_SELF,
+                    cookie: 0,
+                    name: String::new(),
+                });
+            }
+            self.remove(wd);
+        }
+    }
+
+    /// Follow a rename: watches on `old` and anything below it now name
+    /// the same objects under `new`.
+    fn rebase(&mut self, old: &str, new: &str) {
+        for watch in self.watches.values_mut() {
+            if watch.path == old {
+                watch.path = String::from(new);
+            } else if let Some(rest) = watch.path.strip_prefix(old) {
+                if rest.starts_with('/') {
+                    watch.path = alloc::format!("{new}{rest}");
+                }
+            }
+        }
+    }
+}
+
+/// Watches across all instances, so the VFS hooks return at once when
+/// nothing is watched.
+static WATCH_COUNT: AtomicUsize = AtomicUsize::new(0);
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+static NEXT_COOKIE: AtomicU32 = AtomicU32::new(1);
+
+/// Live instances by id, for the VFS hooks.
+static INSTANCES: Mutex<BTreeMap<u32, Weak<Inotify>>> = Mutex::new(BTreeMap::new());
+
+pub struct Inotify {
+    id: u32,
+    state: Mutex<State>,
+    nonblocking: AtomicBool,
+    generation: AtomicU64,
+}
+
+/// Split an absolute path into its parent directory and final component.
+fn split(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(0) => ("/", &path[1..]),
+        Some(index) => (&path[..index], &path[index + 1..]),
+        None => ("", path),
+    }
+}
+
+fn is_directory(path: &str) -> bool {
+    crate::fs::vfs::vfs_stat(path)
+        .is_ok_and(|entry| entry.file_type == crate::fs::filesystem::FileType::Directory)
+}
+
+impl Inotify {
+    fn new(flags: u32) -> Arc<Self> {
+        let instance = Arc::new(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            state: Mutex::new(State {
+                next_wd: 1,
+                ..State::default()
+            }),
+            nonblocking: AtomicBool::new(flags & IN_NONBLOCK != 0),
+            generation: AtomicU64::new(0),
+        });
+        INSTANCES
+            .lock()
+            .insert(instance.id, Arc::downgrade(&instance));
+        instance
+    }
+
+    pub fn nonblocking(&self) -> bool {
+        self.nonblocking.load(Ordering::Acquire)
+    }
+
+    pub fn set_nonblocking(&self, value: bool) {
+        self.nonblocking.store(value, Ordering::Release);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn readable(&self) -> bool {
+        !self.state.lock().queue.is_empty()
+    }
+
+    /// Run `change` against the instance state, publishing readiness if it
+    /// queued anything.
+    fn update(&self, change: impl FnOnce(&mut State)) -> bool {
+        let mut state = self.state.lock();
+        let before = state.queue.len();
+        change(&mut state);
+        let queued = state.queue.len() != before;
+        drop(state);
+        if queued {
+            self.generation.fetch_add(1, Ordering::AcqRel);
+        }
+        queued
+    }
+
+    fn add_watch(&self, path: String, mask: u32) -> i64 {
+        let mut state = self.state.lock();
+        let existing = state
+            .watches
+            .iter()
+            .find(|(_, watch)| watch.path == path)
+            .map(|(&wd, _)| wd);
+        if let Some(wd) = existing {
+            if mask & IN_MASK_CREATE != 0 {
+                return EEXIST;
+            }
+            let watch = state.watches.get_mut(&wd).expect("watch found above");
+            if mask & IN_MASK_ADD != 0 {
+                watch.mask |= mask & !IN_MASK_ADD;
+            } else {
+                watch.mask = mask;
+            }
+            return i64::from(wd);
+        }
+        if state.watches.len() >= MAX_WATCHES {
+            return ENOSPC;
+        }
+        let wd = state.next_wd;
+        state.next_wd += 1;
+        state.watches.insert(
+            wd,
+            Watch {
+                path,
+                mask: mask & !IN_MASK_ADD,
+            },
+        );
+        WATCH_COUNT.fetch_add(1, Ordering::AcqRel);
+        i64::from(wd)
+    }
+}
+
+impl Drop for Inotify {
+    fn drop(&mut self) {
+        INSTANCES.lock().remove(&self.id);
+        let watches = self.state.get_mut().watches.len();
+        WATCH_COUNT.fetch_sub(watches, Ordering::AcqRel);
+    }
+}
+
+/// Apply `change` to every live instance and wake readers if any queued an
+/// event.
+fn broadcast(change: impl Fn(&mut State)) {
+    if WATCH_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    // Collect first: dropping the last reference unregisters the instance.
+    let instances: Vec<Arc<Inotify>> = INSTANCES
+        .lock()
+        .values()
+        .filter_map(Weak::upgrade)
+        .collect();
+    let mut queued = false;
+    for instance in &instances {
+        queued |= instance.update(&change);
+    }
+    drop(instances);
+    if queued {
+        crate::userland::readiness::notify_changed();
+    }
+}
+
+/// `path` was created.
+pub fn created(path: &str, is_dir: bool) {
+    broadcast(|state| state.report(path, IN_CREATE, is_dir, 0));
+}
+
+/// The directory entry `path` was removed.
+pub fn deleted(path: &str, is_dir: bool) {
+    broadcast(|state| {
+        state.delete_self(path);
+        state.report(path, IN_DELETE, is_dir, 0);
+    });
+}
+
+/// The contents of the file at `path` changed.
+pub fn modified(path: &str) {
+    broadcast(|state| state.report(path, IN_MODIFY, false, 0));
+}
+
+/// Metadata of `path` changed.
+pub fn attrib(path: &str) {
+    if WATCH_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let is_dir = is_directory(path);
+    broadcast(|state| state.report(path, IN_ATTRIB, is_dir, 0));
+}
+
+/// `old` was renamed to `new`, replacing anything that was there.
+pub fn moved(old: &str, new: &str) {
+    if old == new || WATCH_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let is_dir = is_directory(new);
+    let cookie = NEXT_COOKIE.fetch_add(1, Ordering::Relaxed);
+    broadcast(|state| {
+        state.report(old, IN_MOVED_FROM, is_dir, cookie);
+        state.report(new, IN_MOVED_TO, is_dir, cookie);
+        state.delete_self(new);
+        state.report(old, IN_MOVE_SELF, is_dir, 0);
+        state.rebase(old, new);
+    });
+}
+
+/// `inotify_init() -> fd`
+pub fn inotify_init_handler(_args: &mut SyscallArgs) -> i64 {
+    inotify_init(0)
+}
+
+/// `inotify_init1(flags) -> fd`
+pub fn inotify_init1_handler(args: &mut SyscallArgs) -> i64 {
+    inotify_init(args.rdi as u32)
+}
+
+fn inotify_init(flags: u32) -> i64 {
+    if flags & !(IN_NONBLOCK | IN_CLOEXEC) != 0 {
+        return EINVAL;
+    }
+    let slot = FdSlot::Inotify {
+        handle: Inotify::new(flags),
+        cloexec: flags & IN_CLOEXEC != 0,
+    };
+    crate::userland::lifecycle::with_current_group(|process| process.fd_table.alloc(slot))
+        .map_or(EMFILE, i64::from)
+}
+
+fn instance(fd: i32) -> Result<Arc<Inotify>, i64> {
+    crate::userland::lifecycle::with_current_group(|process| match process.fd_table.get(fd) {
+        Some(FdSlot::Inotify { handle, .. }) => Ok(handle.clone()),
+        Some(_) => Err(EINVAL),
+        None => Err(EBADF),
+    })
+}
+
+/// `inotify_add_watch(fd, path, mask) -> wd`
+pub fn inotify_add_watch_handler(args: &mut SyscallArgs) -> i64 {
+    let mask = args.rdx as u32;
+    if mask & IN_ALL_EVENTS == 0
+        || mask & !VALID_WATCH_FLAGS != 0
+        || mask & (IN_MASK_CREATE | IN_MASK_ADD) == IN_MASK_CREATE | IN_MASK_ADD
+    {
+        return EINVAL;
+    }
+    let instance = match instance(args.rdi as i32) {
+        Ok(instance) => instance,
+        Err(error) => return error,
+    };
+    let path = match crate::userland::syscalls::resolve_user_path(args.rsi) {
+        Ok(path) => path,
+        Err(error) => return error,
+    };
+    let entry = match crate::fs::vfs::vfs_stat(&path) {
+        Ok(entry) => entry,
+        Err(ref error) => return crate::userland::syscalls::map_filesystem_err(error),
+    };
+    if mask & IN_ONLYDIR != 0 && entry.file_type != crate::fs::filesystem::FileType::Directory {
+        return ENOTDIR;
+    }
+    instance.add_watch(path, mask)
+}
+
+/// `inotify_rm_watch(fd, wd) -> 0`
+pub fn inotify_rm_watch_handler(args: &mut SyscallArgs) -> i64 {
+    let instance = match instance(args.rdi as i32) {
+        Ok(instance) => instance,
+        Err(error) => return error,
+    };
+    let wd = args.rsi as i32;
+    if !instance.state.lock().watches.contains_key(&wd) {
+        return EINVAL;
+    }
+    if instance.update(|state| state.remove(wd)) {
+        crate::userland::readiness::notify_changed();
+    }
+    0
+}
+
+pub fn read(args: &SyscallArgs, handle: &Arc<Inotify>, pointer: u64, len: u64) -> i64 {
+    let observed = crate::userland::readiness::sequence();
+    let mut out = Vec::new();
+    {
+        let mut state = handle.state.lock();
+        let mut count = 0;
+        let mut total = 0;
+        for event in &state.queue {
+            if (total + event.record_len()) as u64 > len {
+                break;
+            }
+            total += event.record_len();
+            count += 1;
+        }
+        if count == 0 && !state.queue.is_empty() {
+            return EINVAL;
+        }
+        // Check the whole span first: a taken event cannot be put back.
+        if let Err(error) =
+            crate::userland::usercopy::ensure_user_range(pointer, total as u64, true)
+        {
+            return error;
+        }
+        for event in state.queue.drain(..count) {
+            event.encode(&mut out);
+        }
+    }
+    if out.is_empty() {
+        if handle.nonblocking() {
+            return EAGAIN;
+        }
+        let identity = Arc::as_ptr(handle) as usize as u64;
+        return crate::userland::readiness::block(args, identity, None, observed);
+    }
+    match crate::userland::usercopy::copy_to_user(pointer, &out) {
+        Ok(()) => out.len() as i64,
+        Err(error) => error,
+    }
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn state_with(watches: &[(&str, u32)]) -> State {
+        let mut state = State {
+            next_wd: 1,
+            ..State::default()
+        };
+        for (index, &(path, mask)) in watches.iter().enumerate() {
+            state.watches.insert(
+                index as i32 + 1,
+                Watch {
+                    path: String::from(path),
+                    mask,
+                },
+            );
+        }
+        state
+    }
+
+    fn drain(state: &mut State) -> Vec<(i32, u32, String)> {
+        state
+            .queue
+            .drain(..)
+            .map(|event| (event.wd, event.mask, event.name))
+            .collect()
+    }
+
+    fn test_split_path() {
+        assert_eq!(split("/tmp/a"), ("/tmp", "a"));
+        assert_eq!(split("/a"), ("/", "a"));
+        assert_eq!(split("/"), ("/", ""));
+    }
+
+    fn test_event_record_padding() {
+        let event = Event {
+            wd: 3,
+            mask: IN_CREATE,
+            cookie: 0,
+            name: String::from("file"),
+        };
+        let mut out = Vec::new();
+        event.encode(&mut out);
+        assert_eq!(out.len(), 32);
+        assert_eq!(out[12..16], 16u32.to_ne_bytes());
+        assert_eq!(&out[16..20], b"file");
+        assert!(out[20..].iter().all(|&byte| byte == 0));
+        let bare = Event {
+            name: String::new(),
+            ..event
+        };
+        assert_eq!(bare.record_len(), EVENT_HEADER);
+    }
+
+    fn test_parent_and_self_watches() {
+        let mut state = state_with(&[("/tmp", IN_ALL_EVENTS), ("/tmp/f", IN_ALL_EVENTS)]);
+        state.report("/tmp/f", IN_MODIFY, false, 0);
+        state.report("/tmp/d", IN_CREATE, true, 0);
+        assert_eq!(
+            drain(&mut state),
+            [
+                (1, IN_MODIFY, String::from("f")),
+                (2, IN_MODIFY, String::new()),
+                (1, IN_CREATE | IN_ISDIR, String::from("d")),
+            ]
+        );
+    }
+
+    fn test_identical_events_coalesce() {
+        let mut state = state_with(&[("/tmp", IN_MODIFY)]);
+        state.report("/tmp/f", IN_MODIFY, false, 0);
+        state.report("/tmp/f", IN_MODIFY, false, 0);
+        state.report("/tmp/f", IN_CREATE, false, 0);
+        assert_eq!(drain(&mut state).len(), 1);
+    }
+
+    fn test_delete_ends_watch() {
+        let mut state = state_with(&[("/tmp", IN_DELETE), ("/tmp/f", IN_ALL_EVENTS)]);
+        WATCH_COUNT.fetch_add(2, Ordering::AcqRel);
+        state.delete_self("/tmp/f");
+        state.report("/tmp/f", IN_DELETE, false, 0);
+        assert_eq!(
+            drain(&mut state),
+            [
+                (2, IN_DELETE_SELF, String::new()),
+                (2, IN_IGNORED, String::new()),
+                (1, IN_DELETE, String::from("f")),
+            ]
+        );
+        assert!(!state.watches.contains_key(&2));
+        state.remove(1);
+        drain(&mut state);
+    }
+
+    fn test_oneshot_removes_watch() {
+        let mut state = state_with(&[("/tmp", IN_CREATE | IN_ONESHOT)]);
+        WATCH_COUNT.fetch_add(1, Ordering::AcqRel);
+        state.report("/tmp/a", IN_CREATE, false, 0);
+        state.report("/tmp/b", IN_CREATE, false, 0);
+        assert_eq!(
+            drain(&mut state),
+            [
+                (1, IN_CREATE, String::from("a")),
+                (1, IN_IGNORED, String::new()),
+            ]
+        );
+    }
+
+    fn test_rename_rebases_watches() {
+        let mut state = state_with(&[
+            ("/a/b", IN_ALL_EVENTS),
+            ("/a/b/c", IN_ALL_EVENTS),
+            ("/a/bc", IN_ALL_EVENTS),
+        ]);
+        state.rebase("/a/b", "/x");
+        assert_eq!(state.watches[&1].path, "/x");
+        assert_eq!(state.watches[&2].path, "/x/c");
+        assert_eq!(state.watches[&3].path, "/a/bc");
+    }
+
+    fn test_queue_overflow() {
+        let mut state = state_with(&[]);
+        for index in 0..MAX_QUEUED_EVENTS + 2 {
+            state.push(Event {
+                wd: 1,
+                mask: IN_MODIFY,
+                cookie: index as u32,
+                name: String::new(),
+            });
+        }
+        assert_eq!(state.queue.len(), MAX_QUEUED_EVENTS + 1);
+        assert_eq!(
+            state.queue.back().map(|event| event.mask),
+            Some(IN_Q_OVERFLOW)
+        );
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_split_path,
+            &test_event_record_padding,
+            &test_parent_and_self_watches,
+            &test_identical_events_coalesce,
+            &test_delete_ends_watch,
+            &test_oneshot_removes_watch,
+            &test_rename_rebases_watches,
+            &test_queue_overflow,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as inotify_tests;
@@ -0,0 +1,688 @@
+//! Process groups, sessions and job control.
+//!
+//! Every process belongs to a process group and a session, recorded in
+//! [`JobState`] on its thread-group leader. A process launched by the kernel
+//! leads a new session and group; `fork` inherits both and `execve` keeps
+//! them. `setpgid` and `setsid` move processes between groups the way POSIX
+//! allows.
+//!
+//! A pty becomes the controlling terminal of a session on the first
+//! `TIOCGPGRP`, `TIOCSPGRP` or `TIOCSCTTY` from a process bound to it while
+//! it is unclaimed (or its session has died), with the caller's group in
+//! the foreground. A session leader without a controlling terminal also
+//! acquires a `/dev/pts` terminal it opens without `O_NOCTTY`. Keyboard
+//! signals and SIGWINCH then go to the foreground group, and a background
+//! group that reads the terminal gets SIGTTIN.
+//!
+//! A stop signal with its default disposition stops the whole thread
+//! group: the leader's [`JobState`] records it, every member parks as
+//! [`Ring3BlockReason::Stopped`] at its next kernel exit, and the parent
+//! gets one SIGCHLD and one report for `wait4(WUNTRACED)`. SIGCONT resumes
+//! every member, whatever its disposition, and leaves a report for
+//! `wait4(WCONTINUED)`.
+
+use alloc::vec::Vec;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::terminal::pty::{PtySlave, TerminalId};
+use crate::userland::abi::{EINTR, EINVAL, EIO, ENOTTY, EPERM, ESRCH};
+use crate::userland::lifecycle::{ExitKind, ProcessTable, Ring3BlockReason, KERNEL_PID};
+use crate::userland::signal::{SIGCHLD, SIGCONT, SIGTSTP, SIGTTIN, SIGTTOU};
+
+/// `SA_NOCLDSTOP`: no SIGCHLD when a child stops or continues.
+const SA_NOCLDSTOP: u64 = 0x1;
+
+/// A stop or continue not yet collected by the parent's `wait4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobReport {
+    Stopped(i32),
+    Continued,
+}
+
+impl JobReport {
+    /// The `wait4` status word: `WIFSTOPPED` with `WSTOPSIG`, or
+    /// `WIFCONTINUED`.
+    pub fn status(self) -> u32 {
+        match self {
+            JobReport::Stopped(sig) => (((sig as u32) & 0xff) << 8) | 0x7f,
+            JobReport::Continued => 0xffff,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobState {
+    pub pgid: u32,
+    pub sid: u32,
+    pub stopped: bool,
+    pub report: Option<JobReport>,
+}
+
+impl JobState {
+    /// Leader of a new session and process group.
+    pub const fn leader(pid: u32) -> Self {
+        Self {
+            pgid: pid,
+            sid: pid,
+            stopped: false,
+            report: None,
+        }
+    }
+
+    /// State of a new child: same group and session, running.
+    pub fn fork_child(&self) -> Self {
+        Self {
+            pgid: self.pgid,
+            sid: self.sid,
+            stopped: false,
+            report: None,
+        }
+    }
+}
+
+/// Whether a child `pid` in group `pgid` is selected by the `wait4` target
+/// `target` of a caller in group `caller_pgid`.
+pub fn wait_target_matches(target: i32, pid: u32, pgid: u32, caller_pgid: u32) -> bool {
+    match target {
+        -1 => true,
+        0 => pgid == caller_pgid,
+        t if t > 0 => pid == t as u32,
+        t => pgid == t.unsigned_abs(),
+    }
+}
+
+/// Live thread-group leaders other than the kernel sentinel.
+fn live_leaders(g: &ProcessTable) -> impl Iterator<Item = &crate::userland::lifecycle::Process> {
+    g.by_pid.values().filter(move |p| {
+        p.pid != KERNEL_PID
+            && p.exit_kind == ExitKind::None
+            && g.thread_groups
+                .get(&p.pid)
+                .is_none_or(|&tgid| tgid == p.pid)
+    })
+}
+
+fn group_exists_in_session(g: &ProcessTable, pgid: u32, sid: u32) -> bool {
+    live_leaders(g).any(|p| p.job.pgid == pgid && p.job.sid == sid)
+}
+
+fn session_alive(g: &ProcessTable, sid: u32) -> bool {
+    live_leaders(g).any(|p| p.job.sid == sid)
+}
+
+fn current_job() -> JobState {
+    crate::userland::lifecycle::with_current_group(|p| p.job)
+}
+
+// ---------- syscalls ----------
+
+/// `setpgid(pid, pgid) -> int`. `0` in either argument means the caller
+/// (for `pid`) or the target itself (for `pgid`). The target must be the
+/// caller or one of its children in the same session, and must not be a
+/// session leader; an existing `pgid` must belong to the caller's session.
+pub fn setpgid_handler(args: &mut SyscallArgs) -> i64 {
+    let pid = args.rdi as i32;
+    let pgid = args.rsi as i32;
+    if pgid < 0 {
+        return EINVAL;
+    }
+    if pid < 0 {
+        return ESRCH;
+    }
+    let caller = crate::userland::lifecycle::current_pid();
+    let target = if pid == 0 { caller } else { pid as u32 };
+    let pgid = if pgid == 0 { target } else { pgid as u32 };
+
+    let mut g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+    let Some(caller_sid) = g.by_pid.get(&caller).map(|p| p.job.sid) else {
+        return ESRCH;
+    };
+    let Some(job) = live_leaders(&g)
+        .find(|p| p.pid == target && (target == caller || p.parent_pid == caller))
+        .map(|p| p.job)
+    else {
+        return ESRCH;
+    };
+    if job.sid != caller_sid || job.sid == target {
+        return EPERM;
+    }
+    if pgid != target && !group_exists_in_session(&g, pgid, caller_sid) {
+        return EPERM;
+    }
+    if let Some(p) = g.by_pid.get_mut(&target) {
+        p.job.pgid = pgid;
+    }
+    0
+}
+
+fn lookup_job(pid: i32) -> Result<JobState, i64> {
+    if pid < 0 {
+        return Err(ESRCH);
+    }
+    if pid == 0 {
+        return Ok(current_job());
+    }
+    let g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+    let tgid = g
+        .thread_groups
+        .get(&(pid as u32))
+        .copied()
+        .unwrap_or(pid as u32);
+    let job = live_leaders(&g).find(|p| p.pid == tgid).map(|p| p.job);
+    job.ok_or(ESRCH)
+}
+
+/// `getpgid(pid) -> pid_t`
+pub fn getpgid_handler(args: &mut SyscallArgs) -> i64 {
+    lookup_job(args.rdi as i32).map_or_else(|e| e, |job| job.pgid as i64)
+}
+
+/// `getpgrp() -> pid_t`
+pub fn getpgrp_handler(_: &mut SyscallArgs) -> i64 {
+    current_job().pgid as i64
+}
+
+/// `getsid(pid) -> pid_t`
+pub fn getsid_handler(args: &mut SyscallArgs) -> i64 {
+    lookup_job(args.rdi as i32).map_or_else(|e| e, |job| job.sid as i64)
+}
+
+/// `setsid() -> pid_t`. Fails with `EPERM` when the caller already leads
+/// a process group, which is why shells fork before calling it.
+pub fn setsid_handler(_: &mut SyscallArgs) -> i64 {
+    let caller = crate::userland::lifecycle::current_pid();
+    let mut g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+    if live_leaders(&g).any(|p| p.job.pgid == caller) {
+        return EPERM;
+    }
+    let Some(p) = g.by_pid.get_mut(&caller) else {
+        return ESRCH;
+    };
+    p.job.sid = caller;
+    p.job.pgid = caller;
+    caller as i64
+}
+
+// ---------- controlling terminal ----------
+
+/// The caller's pty and job state, if the pty is the controlling terminal
+/// of the caller's session. With `claim`, an unclaimed pty (or one whose
+/// session has died) becomes it, with the caller's group in the
+/// foreground.
+fn controlling_terminal(claim: bool) -> Result<(PtySlave, JobState), i64> {
+    let (terminal_id, job) =
+        crate::userland::lifecycle::with_current_group(|p| (p.terminal_id, p.job));
+    let slave = terminal_id
+        .and_then(crate::terminal::pty::slave_for_terminal)
+        .ok_or(ENOTTY)?;
+    match slave.with(|p| p.session()) {
+        Some(sid) if sid == job.sid => return Ok((slave, job)),
+        Some(sid) => {
+            let alive = session_alive(&crate::userland::lifecycle::PROCESS_TABLE.lock(), sid);
+            if alive || !claim {
+                return Err(ENOTTY);
+            }
+        }
+        None if !claim => return Err(ENOTTY),
+        None => {}
+    }
+    slave.with(|p| p.set_controlling(job.sid, job.pgid));
+    Ok((slave, job))
+}
+
+/// `ioctl(TIOCGPGRP)`: write the foreground process group.
+pub fn tiocgpgrp(arg: u64) -> i64 {
+    let (slave, job) = match controlling_terminal(true) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let pgrp = slave.with(|p| p.foreground_pgrp()).unwrap_or(job.pgid) as i32;
+    crate::userland::usercopy::write_unaligned(arg, &pgrp).map_or_else(|e| e, |_| 0)
+}
+
+/// `ioctl(TIOCSPGRP)`: put a group of the caller's session in the
+/// foreground. A background caller gets SIGTTOU unless it ignores or
+/// blocks it.
+pub fn tiocspgrp(args: &SyscallArgs, arg: u64) -> i64 {
+    let (slave, job) = match controlling_terminal(true) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let pgrp: i32 = match crate::userland::usercopy::read_unaligned(arg) {
+        Ok(value) => value,
+        Err(e) => return e,
+    };
+    if pgrp < 0 {
+        return EINVAL;
+    }
+    let foreground = slave.with(|p| p.foreground_pgrp());
+    if foreground.is_some_and(|fg| fg != job.pgid)
+        && !crate::userland::lifecycle::with_current_process(|p| p.signal_state.discards(SIGTTOU))
+    {
+        return background_tty_signal(args, job.pgid, SIGTTOU);
+    }
+    {
+        let g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+        if !group_exists_in_session(&g, pgrp as u32, job.sid) {
+            return if live_leaders(&g).any(|p| p.job.pgid == pgrp as u32) {
+                EPERM
+            } else {
+                ESRCH
+            };
+        }
+    }
+    slave.with(|p| p.set_foreground_pgrp(pgrp as u32));
+    0
+}
+
+/// `ioctl(TIOCSCTTY)`: make `terminal` the controlling terminal of the
+/// caller's session and the caller's terminal. Only a session leader may;
+/// stealing a terminal from another live session needs `arg == 1`.
+pub fn tiocsctty(terminal: Option<TerminalId>, arg: u64) -> i64 {
+    let caller = crate::userland::lifecycle::current_pid();
+    let job = crate::userland::lifecycle::with_current_group(|p| p.job);
+    if job.sid != caller {
+        return EPERM;
+    }
+    let Some(slave) = terminal.and_then(crate::terminal::pty::slave_for_terminal) else {
+        return ENOTTY;
+    };
+    match slave.with(|p| p.session()) {
+        Some(sid) if sid == job.sid => {}
+        Some(sid)
+            if arg != 1
+                && session_alive(&crate::userland::lifecycle::PROCESS_TABLE.lock(), sid) =>
+        {
+            return EPERM;
+        }
+        _ => slave.with(|p| p.set_controlling(job.sid, job.pgid)),
+    }
+    crate::userland::lifecycle::with_current_group(|p| p.terminal_id = terminal);
+    0
+}
+
+/// Opening a terminal without `O_NOCTTY` makes it the controlling terminal
+/// when the caller leads a session that has none and no live session
+/// holds the terminal.
+pub fn acquire_on_open(terminal: TerminalId) {
+    let caller = crate::userland::lifecycle::current_pid();
+    let job = crate::userland::lifecycle::with_current_group(|p| p.job);
+    if job.sid != caller || controlling_terminal(false).is_ok() {
+        return;
+    }
+    let Some(slave) = crate::terminal::pty::slave_for_terminal(terminal) else {
+        return;
+    };
+    if let Some(sid) = slave.with(|p| p.session()) {
+        if session_alive(&crate::userland::lifecycle::PROCESS_TABLE.lock(), sid) {
+            return;
+        }
+    }
+    slave.with(|p| p.set_controlling(job.sid, job.pgid));
+    crate::userland::lifecycle::with_current_group(|p| p.terminal_id = Some(terminal));
+}
+
+/// `ioctl(TIOCGSID)`: write the session of the controlling terminal.
+pub fn tiocgsid(arg: u64) -> i64 {
+    let (_, job) = match controlling_terminal(false) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let sid = job.sid as i32;
+    crate::userland::usercopy::write_unaligned(arg, &sid).map_or_else(|e| e, |_| 0)
+}
+
+/// Gate a read of the caller's terminal. A background group gets SIGTTIN
+/// (stopping it by default) or, if it ignores or blocks SIGTTIN, `-EIO`.
+pub fn check_terminal_read(args: &SyscallArgs) -> Result<(), i64> {
+    let Ok((slave, job)) = controlling_terminal(false) else {
+        return Ok(());
+    };
+    if slave
+        .with(|p| p.foreground_pgrp())
+        .is_none_or(|fg| fg == job.pgid)
+    {
+        return Ok(());
+    }
+    if crate::userland::lifecycle::with_current_process(|p| p.signal_state.discards(SIGTTIN)) {
+        return Err(EIO);
+    }
+    Err(background_tty_signal(args, job.pgid, SIGTTIN))
+}
+
+/// Signal the caller's background group for touching the terminal. If the
+/// default stop applies, the caller stops here and retries the syscall once
+/// continued; otherwise the syscall fails with `EINTR` and the signal is
+/// delivered on the way out.
+fn background_tty_signal(args: &SyscallArgs, pgid: u32, sig: i32) -> i64 {
+    signal_group(pgid, sig);
+    stop_if_pending_before_syscall(args);
+    EINTR
+}
+
+// ---------- signal delivery ----------
+
+/// Raise `sig` on `pid` and wake it if needed. SIGCONT also resumes a
+/// stopped process. Returns false when `pid` does not exist.
+pub fn send_signal(pid: u32, sig: i32) -> bool {
+    let raised = crate::userland::lifecycle::with_process(pid, |target| {
+        if sig != 0 {
+            target.signal_state.raise(sig);
+        }
+    });
+    if raised.is_none() {
+        return false;
+    }
+    if sig == SIGCONT {
+        continue_process(pid);
+    }
+    if sig != 0 && pid != crate::userland::lifecycle::current_pid() {
+        // If the target is parked in a blocking syscall, unblock it so
+        // the pending signal is examined at its next dispatcher entry.
+        crate::userland::lifecycle::wake_ring3_for_signal(pid);
+    }
+    true
+}
+
+/// Processes selected by the `kill` target `target` from `caller`: a PID,
+/// `0` for the caller's group, `-1` for every process but the caller, or
+/// `-pgid`.
+pub fn kill_targets(target: i32, caller: u32) -> Vec<u32> {
+    let g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+    match target {
+        t if t > 0 => g
+            .by_pid
+            .contains_key(&(t as u32))
+            .then_some(t as u32)
+            .into_iter()
+            .collect(),
+        -1 => live_leaders(&g)
+            .filter(|p| p.pid != caller)
+            .map(|p| p.pid)
+            .collect(),
+        t => {
+            let pgid = if t == 0 {
+                g.by_pid.get(&caller).map_or(caller, |p| p.job.pgid)
+            } else {
+                t.unsigned_abs()
+            };
+            live_leaders(&g)
+                .filter(|p| p.job.pgid == pgid)
+                .map(|p| p.pid)
+                .collect()
+        }
+    }
+}
+
+/// Send `sig` to every process in group `pgid`. False when the group is
+/// empty.
+pub fn signal_group(pgid: u32, sig: i32) -> bool {
+    let members = kill_targets(-(pgid as i32), KERNEL_PID);
+    for &pid in &members {
+        send_signal(pid, sig);
+    }
+    !members.is_empty()
+}
+
+/// Deliver a terminal-generated signal (ISIG keys, SIGWINCH) for the pty
+/// of `terminal_id`. Once a session has claimed the pty, it goes to the
+/// foreground group; before that, to every process on the terminal, as
+/// if the shell were the only job. An unclaimed terminal drops SIGTSTP,
+/// since nothing could resume the stopped processes.
+pub fn signal_terminal(terminal_id: TerminalId, foreground: Option<u32>, sig: i32) {
+    match foreground {
+        Some(pgid) => {
+            signal_group(pgid, sig);
+        }
+        None if sig == SIGTSTP => {}
+        None => crate::userland::lifecycle::raise_signal_on_terminal(terminal_id, sig),
+    }
+}
+
+// ---------- stop and continue ----------
+
+/// Take a pending default-action stop signal on the current task and stop
+/// its thread group: mark the leader stopped, notify the parent once and
+/// kick the other members so they park at their next kernel exit. A task
+/// whose group is already stopped joins that stop without a second report.
+/// Returns the signal.
+pub(crate) fn take_stop() -> Option<i32> {
+    let tid = current_user_task()?;
+    let (tgid, parent, sig, others) = {
+        let mut g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+        let tgid = crate::userland::lifecycle::tgid_locked(&g, tid);
+        let sig = g.by_pid.get_mut(&tid)?.signal_state.take_stop_default()?;
+        let leader = g.by_pid.get_mut(&tgid)?;
+        if leader.job.stopped {
+            return Some(sig);
+        }
+        leader.job.stopped = true;
+        leader.job.report = Some(JobReport::Stopped(sig));
+        let parent = leader.parent_pid;
+        let others: Vec<u32> = g
+            .thread_groups
+            .iter()
+            .filter_map(|(&member, &group)| (group == tgid && member != tid).then_some(member))
+            .collect();
+        (tgid, parent, sig, others)
+    };
+    crate::debug_info!("USERLAND: pid={} stopped by signal {}", tgid, sig);
+    for member in others {
+        crate::userland::lifecycle::wake_ring3_for_signal(member);
+    }
+    notify_parent(tgid, parent);
+    Some(sig)
+}
+
+/// True when the current task belongs to a stopped thread group and must
+/// park even without a stop signal of its own.
+fn group_stopped() -> bool {
+    let Some(tid) = current_user_task() else {
+        return false;
+    };
+    let g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+    let tgid = crate::userland::lifecycle::tgid_locked(&g, tid);
+    g.by_pid.get(&tgid).is_some_and(|leader| leader.job.stopped)
+}
+
+/// Whether the current task must park as stopped: it took a stop signal,
+/// or another member stopped its group.
+pub(crate) fn must_stop() -> bool {
+    take_stop().is_some() || group_stopped()
+}
+
+fn current_user_task() -> Option<u32> {
+    crate::userland::lifecycle::current_user_pid().filter(|&pid| pid != KERNEL_PID)
+}
+
+/// Stop the current process on the way out of a syscall that returned
+/// `ret`, if a stop signal is pending or its group is stopped. Once
+/// continued, the syscall returns `ret`.
+pub fn stop_if_pending_after_syscall(args: &SyscallArgs, ret: i64) {
+    if must_stop() {
+        unsafe { crate::userland::switch::stop_current_ring3_after_syscall(args, ret) }
+    }
+}
+
+/// Stop the current process before it runs the syscall in `args`, if a
+/// stop signal is pending or its group is stopped. Once continued, the
+/// syscall runs from the start.
+pub fn stop_if_pending_before_syscall(args: &SyscallArgs) {
+    if must_stop() {
+        unsafe {
+            crate::userland::switch::block_current_ring3_and_yield(args, Ring3BlockReason::Stopped)
+        }
+    }
+}
+
+/// Stop the current process from the timer interrupt, whose user state
+/// has already been saved, if a stop signal is pending or its group is
+/// stopped.
+pub fn stop_if_pending_preempted() {
+    if must_stop() {
+        unsafe { crate::userland::switch::stop_current_ring3_preempted(Ring3BlockReason::Stopped) }
+    }
+}
+
+/// Resume the thread group of `pid` if it is stopped, waking every parked
+/// member and leaving one `WCONTINUED` report for its parent.
+pub fn continue_process(pid: u32) {
+    let (tgid, parent, woken) = {
+        let mut g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+        let tgid = crate::userland::lifecycle::tgid_locked(&g, pid);
+        let Some(leader) = g.by_pid.get_mut(&tgid) else {
+            return;
+        };
+        if !leader.job.stopped {
+            return;
+        }
+        leader.job.stopped = false;
+        leader.job.report = Some(JobReport::Continued);
+        let parent = leader.parent_pid;
+        // Members not yet parked: the stopping CPU's recheck in
+        // `reconcile_readiness_after_block` sees `stopped` clear.
+        let woken: Vec<u32> = g
+            .thread_groups
+            .iter()
+            .filter_map(|(&member, &group)| (group == tgid).then_some(member))
+            .filter(|member| g.ring3_blocked.get(member) == Some(&Ring3BlockReason::Stopped))
+            .collect();
+        for member in &woken {
+            g.ring3_blocked.remove(member);
+        }
+        (tgid, parent, woken)
+    };
+    for member in woken {
+        crate::userland::lifecycle::mark_ring3_ready(member);
+    }
+    crate::debug_info!("USERLAND: pid={} continued", tgid);
+    notify_parent(tgid, parent);
+}
+
+/// Tell `parent` that `child` stopped or continued: SIGCHLD unless the
+/// parent set `SA_NOCLDSTOP`, and a wake for a matching `wait4`.
+pub(crate) fn notify_parent(child: u32, parent: u32) {
+    if parent == KERNEL_PID {
+        return;
+    }
+    let _ = crate::userland::lifecycle::with_process(parent, |p| {
+        let flags = p.signal_state.action(SIGCHLD).unwrap_or_default().sa_flags;
+        if flags & SA_NOCLDSTOP == 0 {
+            p.signal_state.raise(SIGCHLD);
+        }
+    });
+    crate::userland::lifecycle::wake_ring3_blocked_on_child(parent, child);
+    crate::userland::lifecycle::wake_ring3_for_signal(parent);
+}
+
+/// Collect a pending stop (`stopped`) or continue (`continued`) report from
+/// a child of `parent` selected by the `wait4` target `target`.
+pub fn take_report(
+    parent: u32,
+    target: i32,
+    stopped: bool,
+    continued: bool,
+) -> Option<(u32, JobReport)> {
+    let mut g = crate::userland::lifecycle::PROCESS_TABLE.lock();
+    let parent_pgid = g.by_pid.get(&parent).map_or(0, |p| p.job.pgid);
+    let pid = live_leaders(&g)
+        .find(|p| {
+            p.parent_pid == parent
+                && wait_target_matches(target, p.pid, p.job.pgid, parent_pgid)
+                && match p.job.report {
+                    Some(JobReport::Stopped(_)) => stopped,
+                    Some(JobReport::Continued) => continued,
+                    None => false,
+                }
+        })?
+        .pid;
+    let report = g.by_pid.get_mut(&pid)?.job.report.take()?;
+    Some((pid, report))
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+    use crate::userland::signal::{SignalState, SIGINT, SIGKILL, SIGSTOP};
+
+    fn test_wait_target_matches() {
+        assert!(wait_target_matches(-1, 7, 3, 1));
+        assert!(wait_target_matches(7, 7, 3, 1));
+        assert!(!wait_target_matches(8, 7, 3, 1));
+        // 0 selects the caller's group; -pgid selects group pgid.
+        assert!(wait_target_matches(0, 7, 3, 3));
+        assert!(!wait_target_matches(0, 7, 3, 1));
+        assert!(wait_target_matches(-3, 7, 3, 1));
+        assert!(!wait_target_matches(-4, 7, 3, 1));
+    }
+
+    fn test_report_status_words() {
+        // WIFSTOPPED: low byte 0x7f, WSTOPSIG in the next byte.
+        assert_eq!(JobReport::Stopped(SIGTSTP).status(), 0x147f);
+        assert_eq!(JobReport::Stopped(SIGSTOP).status(), 0x137f);
+        assert_eq!(JobReport::Continued.status(), 0xffff);
+    }
+
+    fn test_fork_child_keeps_group_and_session() {
+        let mut parent = JobState::leader(5);
+        parent.pgid = 9;
+        parent.stopped = true;
+        parent.report = Some(JobReport::Continued);
+        let child = parent.fork_child();
+        assert_eq!((child.pgid, child.sid), (9, 5));
+        assert!(!child.stopped);
+        assert_eq!(child.report, None);
+    }
+
+    fn test_sigcont_and_stop_discard_each_other() {
+        let mut state = SignalState::new();
+        state.raise(SIGTSTP);
+        state.raise(SIGCONT);
+        assert!(!state.is_pending(SIGTSTP));
+        assert!(state.is_pending(SIGCONT));
+        state.raise(SIGSTOP);
+        assert!(!state.is_pending(SIGCONT));
+        assert!(state.is_pending(SIGSTOP));
+    }
+
+    fn test_take_stop_default() {
+        let mut state = SignalState::new();
+        state.raise(SIGTSTP);
+        state.raise(SIGINT);
+        // Stops are not fatal; SIGINT is.
+        assert_eq!(state.take_fatal_default(), Some(SIGINT));
+        assert_eq!(state.take_stop_default(), Some(SIGTSTP));
+        assert_eq!(state.take_stop_default(), None);
+
+        // A blocked SIGTSTP waits; SIGSTOP cannot be blocked.
+        state.blocked = (1u64 << (SIGTSTP - 1)) | (1u64 << (SIGSTOP - 1));
+        state.raise(SIGTSTP);
+        assert_eq!(state.take_stop_default(), None);
+        state.raise(SIGSTOP);
+        assert_eq!(state.take_stop_default(), Some(SIGSTOP));
+        assert!(state.is_pending(SIGTSTP));
+        assert!(!state.is_pending(SIGKILL));
+    }
+
+    fn test_discards() {
+        let mut state = SignalState::new();
+        assert!(!state.discards(SIGTTIN));
+        assert!(state.discards(SIGCHLD));
+        state.blocked = 1u64 << (SIGTTIN - 1);
+        assert!(state.discards(SIGTTIN));
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_wait_target_matches,
+            &test_report_status_words,
+            &test_fork_child_keeps_group_and_session,
+            &test_sigcont_and_stop_discard_each_other,
+            &test_take_stop_default,
+            &test_discards,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as job_control_tests;
@@ -108,7 +108,7 @@ pub(crate) fn prepare_user_binary_unstarted(
     path: &str,
     argv: &[&str],
     envp: &[&str],
-    terminal_id: Option<crate::window::WindowId>,
+    terminal_id: Option<crate::terminal::pty::TerminalId>,
 ) -> Result<u32, String> {
     crate::userland::abi::reset_unknown_syscall_trace();
 
@@ -116,6 +116,8 @@ pub(crate) fn prepare_user_binary_unstarted(
     // active CR3, so do the potentially long read before entering the
     // address-space setup transaction.
     let (file, bytes) = read_file_bytes(path)?;
+    let interp = crate::userland::loader::read_interpreter(&bytes, |path| Some(path.into()))
+        .map_err(|e| format!("interpreter for '{path}': {e:?}"))?;
     #[cfg(feature = "test")]
     TEST_SETUP_READS.fetch_add(1, core::sync::atomic::Ordering::AcqRel);
     let mut at_random = [0u8; 16];
@@ -157,7 +159,7 @@ pub(crate) fn prepare_user_binary_unstarted(
         }
     }
 
-    let result = crate::userland::loader::load_elf_file(&bytes, file)
+    let result = crate::userland::loader::load_elf_file(&bytes, file, interp.as_ref())
         .map_err(|e| format!("loader error: {:?}", e))
         .and_then(|image| {
             crate::userland::setup_user_process_unstarted(
@@ -48,6 +48,33 @@ pub struct AbnormalExit {
     pub fault_addr: Option<VirtAddr>,
     /// Saved RIP at the moment of the fault.
     pub fault_rip: VirtAddr,
+    /// User registers at the fault, for the core dump. Exception handlers
+    /// see only the interrupt frame, so just RIP, RSP and RFLAGS are real
+    /// there; signal delivery has the full syscall-entry snapshot.
+    pub user_regs: crate::userland::user_state::UserState,
+}
+
+impl AbnormalExit {
+    /// Exit for an exception taken in ring 3.
+    pub fn from_user_frame(
+        vector: u8,
+        error_code: Option<u64>,
+        fault_addr: Option<VirtAddr>,
+        frame: &x86_64::structures::idt::InterruptStackFrame,
+    ) -> Self {
+        Self {
+            vector,
+            error_code,
+            fault_addr,
+            fault_rip: frame.instruction_pointer,
+            user_regs: crate::userland::user_state::UserState {
+                rip: frame.instruction_pointer.as_u64(),
+                rsp: frame.stack_pointer.as_u64(),
+                rflags: frame.cpu_flags,
+                ..Default::default()
+            },
+        }
+    }
 }
 
 /// The single active per-CPU user-process slot.
@@ -82,6 +109,9 @@ pub struct Process {
     /// `run` command, this is `0` (kernel as parent). For fork-spawned
     /// children (PR-C), this becomes the parent's PID.
     pub parent_pid: u32,
+    /// Process group, session and stop state. Authoritative on the
+    /// thread-group leader; see [`crate::userland::job_control`].
+    pub job: crate::userland::job_control::JobState,
     pub image: Option<UserImage>,
     pub exit_kind: ExitKind,
     pub exit_code: i64,
@@ -97,14 +127,25 @@ pub struct Process {
     /// standard streams; slots 3..N hold `Arc<File>` opened via `openat`.
     pub fd_table: FdTable,
     /// Process file-creation mask. Inherited across fork and retained across
-    /// execve, matching POSIX process state even though filesystem permission
-    /// enforcement remains intentionally minimal.
+    /// execve, and applied to the permission bits of files and directories
+    /// the process creates.
     pub umask: u32,
+    /// Resource limits. Authoritative on the thread-group leader; see
+    /// [`crate::userland::rlimit`].
+    pub rlimits: crate::userland::rlimit::Rlimits,
+    /// User and group ids. Authoritative on the thread-group leader; see
+    /// [`crate::userland::credentials`].
+    pub credentials: crate::userland::credentials::Credentials,
+    /// Syscall filters and `no_new_privs`. Per task, copied to `fork` and
+    /// `clone` children and kept across `execve`; see
+    /// [`crate::userland::seccomp`].
+    pub seccomp: crate::userland::seccomp::Seccomp,
     /// Restart-stable deadline state for a blocking network syscall.
     pub network_wait: Option<NetworkWaitState>,
     /// Linux ITIMER_REAL state, represented against the monotonic 100 Hz PIT.
     pub real_timer: RealTimerState,
-    /// Restart-stable absolute PIT deadline for a blocking `nanosleep`. Set
+    /// Restart-stable absolute monotonic-ns deadline for a blocking
+    /// `nanosleep`. Set
     /// on the first entry, checked on every SYSCALL re-fire, and cleared when
     /// the sleep completes. See [`Ring3BlockReason::Sleeping`].
     pub sleep_deadline: Option<u64>,
@@ -116,6 +157,10 @@ pub struct Process {
     /// paths in `openat(AT_FDCWD, …)`, `stat`, `access`, etc. Always
     /// stored as a normalized absolute path.
     pub cwd: String,
+    /// `chroot` directory, as a kernel path. `/` (or empty, for the
+    /// sentinel and non-leader tasks) means unconfined; see
+    /// [`crate::userland::path::resolve_path`].
+    pub root: String,
     /// Phase 4 PR-B: per-process L4 page table. Owns the L4 frame. The
     /// option is `None` for the kernel-sentinel slot (PID 0); every
     /// real user process has a populated `AddressSpace`.
@@ -153,6 +198,10 @@ pub struct Process {
     /// spent in short syscalls between timer fires are not attributed.
     /// Read by `/proc/<pid>/stat` and `/proc/stat`.
     pub utime_ticks: u64,
+    /// On the thread-group leader, user and system ticks charged to every
+    /// task of the group, including exited ones: the running total
+    /// `RLIMIT_CPU` is checked against. Zero on other tasks.
+    pub group_cpu_ticks: u64,
     /// Demand-grown stack — top of the user stack (exclusive). Constant
     /// per-process; mirrors `image.stack_top` for fast access from the
     /// ring-3 page-fault handler without dereferencing `image`. Zero for
@@ -205,11 +254,13 @@ pub struct Process {
     /// block I/O must resume the in-progress kernel operation exactly where
     /// it parked (filesystem operations may already have partial state).
     pub kernel_continuation: Option<Box<crate::process::CpuContext>>,
-    /// Ring-3 terminal GUI surface whose PTY carries this process's stdio.
-    /// Inherited from the launching kernel thread at install time and from the
-    /// parent across fork. `None` for processes without a controlling PTY,
-    /// whose diagnostic output falls back to the framebuffer.
-    pub terminal_id: Option<crate::window::WindowId>,
+    /// PTY that carries this process's stdio and acts as its controlling
+    /// terminal. Inherited from the launching kernel thread's terminal window
+    /// at install time and from the parent across fork; a session leader
+    /// opening a `/dev/pts` slave switches to it. `None` for processes
+    /// without a controlling PTY, whose diagnostic output falls back to the
+    /// framebuffer.
+    pub terminal_id: Option<crate::terminal::pty::TerminalId>,
 }
 
 /// What ended the user process.
@@ -252,9 +303,6 @@ pub struct ProcessTable {
     pub by_pid: BTreeMap<u32, Process>,
     /// Linux task-id to thread-group-id mapping. Leaders map to themselves.
     pub thread_groups: BTreeMap<u32, u32>,
-    /// Home CPU for a group once it has more than one task. This preserves
-    /// the one-active-CPU-per-address-space invariant until TLB shootdown.
-    pub group_home_cpu: BTreeMap<u32, usize>,
     /// Per-task CLONE_CHILD_CLEARTID / set_tid_address pointer.
     pub clear_child_tid: BTreeMap<u32, u64>,
     /// Per-task robust-list registration. Owner-death walking is deferred,
@@ -273,7 +321,6 @@ impl ProcessTable {
         Self {
             by_pid: BTreeMap::new(),
             thread_groups: BTreeMap::new(),
-            group_home_cpu: BTreeMap::new(),
             clear_child_tid: BTreeMap::new(),
             robust_lists: BTreeMap::new(),
             dead_tasks: BTreeMap::new(),
@@ -350,14 +397,15 @@ pub enum Ring3BlockReason {
     WaitingForFileLock {
         observed_sequence: u64,
     },
-    /// `nanosleep` with a not-yet-elapsed absolute PIT deadline. The shared
-    /// timer service calls [`expire_user_sleep`] once `now >= deadline_tick`;
+    /// `nanosleep` with a not-yet-elapsed absolute monotonic-ns deadline. The
+    /// shared timer service calls [`expire_user_sleep`] once
+    /// `now >= deadline_ns`;
     /// the re-fired SYSCALL then sees its `sleep_deadline` elapsed and returns
     /// 0. A signal can wake it early through [`wake_ring3_for_signal`], which
     /// clears the deadline and delivers `-EINTR` through
     /// `pending_syscall_interrupt`.
     Sleeping {
-        deadline_tick: u64,
+        deadline_ns: u64,
     },
     WaitingForFutex {
         tgid: u32,
@@ -368,6 +416,14 @@ pub enum Ring3BlockReason {
     WaitingForBlockIo {
         token: u64,
     },
+    /// Stopped by SIGSTOP/SIGTSTP/SIGTTIN/SIGTTOU. Only SIGCONT (through
+    /// [`crate::userland::job_control::continue_process`]) or SIGKILL
+    /// makes the process runnable again.
+    Stopped,
+    /// In a ptrace stop (see [`crate::userland::ptrace`]). Only a resuming
+    /// request from the tracer, its detach or exit, or SIGKILL makes the
+    /// task runnable again; SIGCONT does not.
+    Traced,
 }
 
 /// Restart-stable deadline state for a re-firing blocking network
@@ -450,6 +506,7 @@ impl Process {
         Process {
             pid: KERNEL_PID,
             parent_pid: KERNEL_PID,
+            job: crate::userland::job_control::JobState::leader(KERNEL_PID),
             image: None,
             exit_kind: ExitKind::None,
             exit_code: 0,
@@ -458,11 +515,15 @@ impl Process {
             mmap_next: 0,
             fd_table: FdTable::new(),
             umask: 0o022,
+            rlimits: crate::userland::rlimit::Rlimits::default(),
+            credentials: crate::userland::credentials::Credentials::default(),
+            seccomp: crate::userland::seccomp::Seccomp::default(),
             network_wait: None,
             real_timer: RealTimerState::disarmed(),
             sleep_deadline: None,
             pending_syscall_interrupt: false,
             cwd: String::new(),
+            root: String::new(),
             address_space: None,
             signal_state: SignalState::new(),
             signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -471,6 +532,7 @@ impl Process {
             exe_path: None,
             cmdline: alloc::vec::Vec::new(),
             utime_ticks: 0,
+            group_cpu_ticks: 0,
             stack_top: 0,
             stack_bottom: 0,
             stack_mapped_bottom: 0,
@@ -515,7 +577,7 @@ pub fn with_process<R>(pid: u32, f: impl FnOnce(&mut Process) -> R) -> Option<R>
     g.by_pid.get_mut(&pid).map(f)
 }
 
-fn tgid_locked(g: &ProcessTable, tid: u32) -> u32 {
+pub(crate) fn tgid_locked(g: &ProcessTable, tid: u32) -> u32 {
     g.thread_groups.get(&tid).copied().unwrap_or(tid)
 }
 
@@ -532,35 +594,6 @@ pub fn with_current_group<R>(f: impl FnOnce(&mut Process) -> R) -> R {
     f(group)
 }
 
-/// Confirm that the current thread group is either single-task or that every
-/// member is pinned to the group's recorded home CPU. This is the execution
-/// invariant that makes private-expedited membarrier a local full fence until
-/// remote user-address-space execution and TLB shootdown are implemented.
-pub fn current_group_has_single_cpu_execution() -> bool {
-    let (members, home) = {
-        let mut table = PROCESS_TABLE.lock();
-        ensure_sentinel(&mut table);
-        let tid = crate::arch::x86_64::percpu::current_user_pid().unwrap_or(KERNEL_PID);
-        let tgid = tgid_locked(&table, tid);
-        let members = table
-            .thread_groups
-            .iter()
-            .filter_map(|(&member, &group)| (group == tgid).then_some(member))
-            .collect::<alloc::vec::Vec<_>>();
-        (members, table.group_home_cpu.get(&tgid).copied())
-    };
-    if members.len() <= 1 {
-        return true;
-    }
-    let Some(home) = home else {
-        return false;
-    };
-    let scheduler = crate::process::scheduler::SCHEDULER.lock();
-    members.into_iter().all(|member| {
-        scheduler.cpu_affinity(crate::process::entity::EntityId::UserProcess(member)) == Some(home)
-    })
-}
-
 /// Operate on the shared owner for `tgid`.
 pub fn with_group<R>(tgid: u32, f: impl FnOnce(&mut Process) -> R) -> Option<R> {
     let mut g = PROCESS_TABLE.lock();
@@ -568,6 +601,27 @@ pub fn with_group<R>(tgid: u32, f: impl FnOnce(&mut Process) -> R) -> Option<R>
     g.by_pid.get_mut(&tgid).map(f)
 }
 
+/// True when `f` holds for the VMA set of some live address space. `f` runs
+/// under the process-table lock and must not sleep.
+pub fn any_address_space(mut f: impl FnMut(&crate::userland::vm::VmaSet) -> bool) -> bool {
+    PROCESS_TABLE
+        .lock()
+        .by_pid
+        .values()
+        .filter_map(|process| process.address_space.as_ref())
+        .any(|space| f(space.vmas()))
+}
+
+/// True when `f` holds for the working or root directory of some live
+/// process. `f` runs under the process-table lock and must not sleep.
+pub fn any_cwd_or_root(mut f: impl FnMut(&str) -> bool) -> bool {
+    PROCESS_TABLE
+        .lock()
+        .by_pid
+        .values()
+        .any(|process| f(&process.cwd) || (!process.root.is_empty() && f(&process.root)))
+}
+
 /// Compatibility alias for the (small) tail of callsites still using
 /// the pre-PR-C name. New code should use `with_current_process`.
 pub fn with_active_user<R>(f: impl FnOnce(&mut Process) -> R) -> R {
@@ -645,19 +699,23 @@ pub fn reset_task_exec_metadata(tid: u32) {
     g.robust_lists.remove(&tid);
 }
 
+#[cfg(feature = "test")]
+pub fn join_thread_group_for_test(tid: u32, tgid: u32) {
+    PROCESS_TABLE.lock().thread_groups.insert(tid, tgid);
+}
+
 #[cfg(feature = "test")]
 pub fn robust_list(tid: u32) -> Option<(u64, usize)> {
     PROCESS_TABLE.lock().robust_lists.get(&tid).copied()
 }
 
 pub fn register_thread_task(tid: u32, tgid: u32, mut process: Process) -> Result<(), ()> {
-    let cpu = crate::arch::x86_64::percpu::cpu_id();
     process
         .kernel_stack
         .as_mut()
         .expect("ring-3 thread without a kernel stack")
         .publish_owner(tid);
-    let members = {
+    {
         let mut g = PROCESS_TABLE.lock();
         if g.by_pid.len() >= 129 || !g.by_pid.contains_key(&tgid) || g.by_pid.contains_key(&tid) {
             return Err(());
@@ -670,10 +728,6 @@ pub fn register_thread_task(tid: u32, tgid: u32, mut process: Process) -> Result
         if count >= 64 {
             return Err(());
         }
-        let home = *g.group_home_cpu.entry(tgid).or_insert(cpu);
-        if home != cpu {
-            return Err(());
-        }
         let address_space_generation = g
             .by_pid
             .get(&tgid)
@@ -683,25 +737,14 @@ pub fn register_thread_task(tid: u32, tgid: u32, mut process: Process) -> Result
         g.by_pid.insert(tid, process);
         g.thread_groups.insert(tid, tgid);
         crate::diagnostics::shadow::address_space::member_join(address_space_generation, tgid);
-        g.thread_groups
-            .iter()
-            .filter_map(|(&member, &group)| (group == tgid).then_some(member))
-            .collect::<alloc::vec::Vec<_>>()
-    };
-    let mut scheduler = crate::process::scheduler::SCHEDULER.lock();
-    for member in members {
-        if scheduler.register_user(member).is_err()
-            || scheduler
-                .set_cpu_affinity(
-                    crate::process::entity::EntityId::UserProcess(member),
-                    Some(cpu),
-                )
-                .is_err()
-        {
-            drop(scheduler);
-            drop(remove_process(tid));
-            return Err(());
-        }
+    }
+    let registered = crate::process::scheduler::SCHEDULER
+        .lock()
+        .register_user(tid)
+        .is_ok();
+    if !registered {
+        drop(remove_process(tid));
+        return Err(());
     }
     Ok(())
 }
@@ -793,9 +836,6 @@ pub fn remove_process(pid: u32) -> Option<Process> {
             removed = process;
         }
     }
-    if pid == tgid {
-        g.group_home_cpu.remove(&tgid);
-    }
     let removed = removed?;
     drop(g);
     if let Some(generation) = address_space_generation {
@@ -809,6 +849,7 @@ pub fn remove_process(pid: u32) -> Option<Process> {
         crate::arch::x86_64::percpu::set_current_user_pid(None);
     }
     for member in members {
+        crate::fs::vfs::leave_namespace(member);
         crate::process::timer::cancel_entity(crate::process::entity::EntityId::UserProcess(member));
         crate::process::scheduler::SCHEDULER
             .lock()
@@ -826,7 +867,7 @@ pub fn reap_one_dead_thread() -> bool {
         let g = PROCESS_TABLE.lock();
         g.dead_tasks.keys().copied().find(|tid| {
             let tgid = tgid_locked(&g, *tid);
-            *tid != tgid
+            *tid != tgid && !task_on_cpu(*tid)
         })
     };
     let Some(tid) = candidate else { return false };
@@ -906,13 +947,13 @@ pub fn mark_ring3_blocked(pid: u32, reason: Ring3BlockReason) {
     }
     let entity = crate::process::entity::EntityId::UserProcess(pid);
     match reason {
-        Ring3BlockReason::Sleeping { deadline_tick } => {
+        Ring3BlockReason::Sleeping { deadline_ns } => {
             crate::process::timer::arm(
                 crate::process::timer::TimerKey {
                     entity,
                     kind: crate::process::timer::TimerKind::UserSleep,
                 },
-                deadline_tick,
+                deadline_ns,
                 crate::process::timer::TimerAction::UserSleep(pid),
             )
             .expect("timer capacity exceeded while arming nanosleep");
@@ -924,7 +965,7 @@ pub fn mark_ring3_blocked(pid: u32, reason: Ring3BlockReason) {
             deadline_tick: Some(deadline_tick),
             ..
         } => {
-            crate::process::timer::arm(
+            crate::process::timer::arm_at_tick(
                 crate::process::timer::TimerKey {
                     entity,
                     kind: crate::process::timer::TimerKind::UserNetworkTimeout,
@@ -938,7 +979,7 @@ pub fn mark_ring3_blocked(pid: u32, reason: Ring3BlockReason) {
             deadline_tick: Some(deadline_tick),
             ..
         } => {
-            crate::process::timer::arm(
+            crate::process::timer::arm_at_tick(
                 crate::process::timer::TimerKey {
                     entity,
                     kind: crate::process::timer::TimerKind::UserFutex,
@@ -966,7 +1007,9 @@ pub fn mark_ring3_blocked(pid: u32, reason: Ring3BlockReason) {
             deadline_tick: None,
             ..
         }
-        | Ring3BlockReason::WaitingForFileLock { .. } => {}
+        | Ring3BlockReason::WaitingForFileLock { .. }
+        | Ring3BlockReason::Stopped
+        | Ring3BlockReason::Traced => {}
     }
 }
 
@@ -978,6 +1021,43 @@ pub fn mark_ring3_blocked(pid: u32, reason: Ring3BlockReason) {
 /// landed in between, its wake may have found no blocked entry; re-readying
 /// the exact waiter here makes the syscall re-fire and observe the new state.
 pub fn reconcile_readiness_after_block(pid: u32, reason: Ring3BlockReason) {
+    if reason == Ring3BlockReason::Stopped {
+        // SIGCONT may have arrived between recording the stop and
+        // publishing the blocked reason; it cleared `stopped` but found
+        // nothing to wake.
+        let resumed = {
+            let mut g = PROCESS_TABLE.lock();
+            let tgid = tgid_locked(&g, pid);
+            let resumed = g.by_pid.get(&tgid).is_some_and(|p| !p.job.stopped)
+                && g.ring3_blocked.get(&pid) == Some(&Ring3BlockReason::Stopped);
+            if resumed {
+                g.ring3_blocked.remove(&pid);
+            }
+            resumed
+        };
+        if resumed {
+            mark_ring3_ready(pid);
+        }
+        return;
+    }
+    if reason == Ring3BlockReason::Traced {
+        // Likewise for a tracer that resumed the task as soon as it saw
+        // the stop report.
+        if !crate::userland::ptrace::in_stop(pid) {
+            let resumed = {
+                let mut g = PROCESS_TABLE.lock();
+                let resumed = g.ring3_blocked.get(&pid) == Some(&Ring3BlockReason::Traced);
+                if resumed {
+                    g.ring3_blocked.remove(&pid);
+                }
+                resumed
+            };
+            if resumed {
+                mark_ring3_ready(pid);
+            }
+        }
+        return;
+    }
     let observed_sequence = match reason {
         Ring3BlockReason::WaitingForPipeRead { observed_sequence }
         | Ring3BlockReason::WaitingForPipeWrite { observed_sequence }
@@ -1129,7 +1209,14 @@ pub fn wake_ring3_blocked_on_child(parent_pid: u32, child_pid: u32) {
         let mut g = PROCESS_TABLE.lock();
         let should_wake = match g.ring3_blocked.get(&parent_pid) {
             Some(Ring3BlockReason::WaitingForChild { target }) => {
-                *target == -1 || *target == child_pid as i32
+                let child_pgid = g.by_pid.get(&child_pid).map_or(child_pid, |c| c.job.pgid);
+                let parent_pgid = g.by_pid.get(&parent_pid).map_or(0, |p| p.job.pgid);
+                crate::userland::job_control::wait_target_matches(
+                    *target,
+                    child_pid,
+                    child_pgid,
+                    parent_pgid,
+                )
             }
             Some(Ring3BlockReason::WaitingForSignal)
             | Some(Ring3BlockReason::WaitingForInput)
@@ -1142,6 +1229,8 @@ pub fn wake_ring3_blocked_on_child(parent_pid: u32, child_pid: u32) {
             | Some(Ring3BlockReason::Sleeping { .. })
             | Some(Ring3BlockReason::WaitingForFutex { .. })
             | Some(Ring3BlockReason::WaitingForBlockIo { .. })
+            | Some(Ring3BlockReason::Stopped)
+            | Some(Ring3BlockReason::Traced)
             | None => false,
         };
         if should_wake {
@@ -1166,11 +1255,11 @@ pub fn wake_ring3_blocked_on_child(parent_pid: u32, child_pid: u32) {
 /// deterministic.
 ///
 /// `terminal_id == None` matches processes whose own `terminal_id` is
-/// `None` (test / legacy paths that don't model a terminal window).
+/// `None` (test / legacy paths that don't model a terminal).
 ///
 /// Walks the blocked map once per call. The set is small (one entry
 /// per terminal-bound ring-3 process); the walk cost is negligible.
-pub fn wake_ring3_blocked_on_input(terminal_id: Option<crate::window::WindowId>) {
+pub fn wake_ring3_blocked_on_input(terminal_id: Option<crate::terminal::pty::TerminalId>) {
     crate::userland::readiness::notify_changed();
     let Some(mut g) = PROCESS_TABLE.try_lock() else {
         return;
@@ -1215,7 +1304,7 @@ pub fn wake_ring3_blocked_on_input(terminal_id: Option<crate::window::WindowId>)
 /// match. A matching process parked in a blocking syscall is then passed
 /// through [`wake_ring3_for_signal`], which wakes it only when the new signal
 /// has an actionable disposition under its current mask.
-pub fn raise_signal_on_terminal(terminal_id: crate::window::WindowId, sig: i32) {
+pub fn raise_signal_on_terminal(terminal_id: crate::terminal::pty::TerminalId, sig: i32) {
     let Some(mut g) = PROCESS_TABLE.try_lock() else {
         return;
     };
@@ -1404,7 +1493,13 @@ pub fn expire_real_timer(pid: u32, now: u64) {
                 process.signal_state.has_deliverable_handler(SIGALRM),
             )
         };
-        let reason = if should_interrupt {
+        // A stopped process keeps SIGALRM pending until SIGCONT (or,
+        // in a ptrace stop, until its tracer resumes it).
+        let stopped = matches!(
+            g.ring3_blocked.get(&pid),
+            Some(Ring3BlockReason::Stopped | Ring3BlockReason::Traced)
+        );
+        let reason = if should_interrupt && !stopped {
             g.ring3_blocked.remove(&pid)
         } else {
             None
@@ -1432,7 +1527,7 @@ pub fn expire_real_timer(pid: u32, now: u64) {
     };
 
     if let Some(deadline_tick) = next_deadline {
-        crate::process::timer::arm(
+        crate::process::timer::arm_at_tick(
             crate::process::timer::TimerKey {
                 entity: crate::process::entity::EntityId::UserProcess(pid),
                 kind: crate::process::timer::TimerKind::UserRealTimer,
@@ -1442,6 +1537,7 @@ pub fn expire_real_timer(pid: u32, now: u64) {
         )
         .expect("timer capacity exceeded while rearming ITIMER_REAL");
     }
+    crate::userland::signalfd::signal_raised();
     if should_wake {
         mark_ring3_ready(pid);
     }
@@ -1454,7 +1550,7 @@ pub fn sync_real_timer(pid: u32) {
         kind: crate::process::timer::TimerKind::UserRealTimer,
     };
     if let Some(deadline_tick) = deadline {
-        crate::process::timer::arm(
+        crate::process::timer::arm_at_tick(
             key,
             deadline_tick,
             crate::process::timer::TimerAction::UserRealTimer(pid),
@@ -1465,11 +1561,11 @@ pub fn sync_real_timer(pid: u32) {
     }
 }
 
-pub fn expire_user_sleep(pid: u32, now: u64) {
+pub fn expire_user_sleep(pid: u32, now_ns: u64) {
     let should_wake = {
         let mut g = PROCESS_TABLE.lock();
         match g.ring3_blocked.get(&pid).copied() {
-            Some(Ring3BlockReason::Sleeping { deadline_tick }) if now >= deadline_tick => {
+            Some(Ring3BlockReason::Sleeping { deadline_ns }) if now_ns >= deadline_ns => {
                 g.ring3_blocked.remove(&pid);
                 true
             }
@@ -1530,7 +1626,7 @@ pub fn process_due_real_timers() {
     for pid in due {
         expire_real_timer(pid, now);
     }
-    let _ = crate::process::timer::process_due(now);
+    let _ = crate::process::timer::process_due(crate::time::monotonic_ns());
 }
 
 /// Compatibility/test hook for inline launch paths.
@@ -1539,23 +1635,22 @@ pub fn process_due_real_timers() {
     expect(dead_code, reason = "QEMU test compatibility")
 )]
 pub fn process_expired_sleeps() {
-    let now = crate::arch::x86_64::interrupts::get_timer_ticks();
-    let _ = crate::process::timer::process_due(now);
+    let _ = crate::process::timer::process_due(crate::time::monotonic_ns());
 }
 
 /// Restart-stable `nanosleep` state machine for the current ring-3 process.
 ///
-/// `requested_ticks` is the sleep length rounded up to whole PIT ticks (0 ⇒
-/// return immediately). Returns `Some(deadline)` if the caller should block
+/// `requested_ns` is the sleep length in nanoseconds (0 ⇒ return
+/// immediately). Returns `Some(deadline)` if the caller should block
 /// with [`Ring3BlockReason::Sleeping`], or `None` if the sleep is already
 /// satisfied (elapsed, or zero-length) and the handler should return 0.
 ///
 /// The absolute deadline is recorded on the first entry and preserved across
 /// SYSCALL re-fires so a woken-and-re-blocked sleeper cannot extend its own
 /// timeout, mirroring [`prepare_network_wait`].
-pub fn nanosleep_deadline(requested_ticks: u64) -> Option<u64> {
+pub fn nanosleep_deadline(requested_ns: u64) -> Option<u64> {
     with_current_process(|process| {
-        let now = crate::arch::x86_64::interrupts::get_timer_ticks();
+        let now = crate::time::monotonic_ns();
         if let Some(deadline) = process.sleep_deadline {
             if now >= deadline {
                 process.sleep_deadline = None;
@@ -1563,10 +1658,10 @@ pub fn nanosleep_deadline(requested_ticks: u64) -> Option<u64> {
             }
             return Some(deadline);
         }
-        if requested_ticks == 0 {
+        if requested_ns == 0 {
             return None;
         }
-        let deadline = now.saturating_add(requested_ticks);
+        let deadline = now.saturating_add(requested_ns);
         process.sleep_deadline = Some(deadline);
         Some(deadline)
     })
@@ -1629,15 +1724,12 @@ pub fn clear_stale_network_wait(syscall_nr: u64) {
 /// set `pending_syscall_interrupt` so the re-fired syscall enters the
 /// dispatcher as `-EINTR`, and requeue as ready.
 pub fn wake_ring3_for_signal(pid: u32) {
+    crate::userland::signalfd::signal_raised();
     {
         let Some(g) = PROCESS_TABLE.try_lock() else {
             return;
         };
-        let actionable = g
-            .by_pid
-            .get(&pid)
-            .is_some_and(|p| p.signal_state.has_actionable_pending());
-        if !actionable {
+        if !signal_wakes(&g, pid) {
             return;
         }
         // A kernel-managed block-I/O continuation is not an interruptible
@@ -1687,11 +1779,7 @@ pub fn wake_ring3_for_signal(pid: u32) {
     let Some(mut g) = PROCESS_TABLE.try_lock() else {
         return;
     };
-    let actionable = g
-        .by_pid
-        .get(&pid)
-        .is_some_and(|p| p.signal_state.has_actionable_pending());
-    if !actionable {
+    if !signal_wakes(&g, pid) {
         return;
     }
     if let Some(Ring3BlockReason::WaitingForBlockIo { token }) = g.ring3_blocked.get(&pid).copied()
@@ -1731,6 +1819,29 @@ pub fn wake_ring3_for_signal(pid: u32) {
     mark_ring3_ready(pid);
 }
 
+/// Whether `pid`'s pending signals justify waking it: some pending signal
+/// is actionable or its thread group is stopping, and if the process is
+/// stopped (by a signal or for its tracer), that signal is SIGKILL.
+fn signal_wakes(g: &ProcessTable, pid: u32) -> bool {
+    let Some(process) = g.by_pid.get(&pid) else {
+        return false;
+    };
+    if matches!(
+        g.ring3_blocked.get(&pid),
+        Some(Ring3BlockReason::Stopped | Ring3BlockReason::Traced)
+    ) {
+        return process
+            .signal_state
+            .is_pending(crate::userland::signal::SIGKILL);
+    }
+    // A member of a stopped group parks at its next kernel exit.
+    let stopping = g
+        .by_pid
+        .get(&tgid_locked(g, pid))
+        .is_some_and(|leader| leader.job.stopped);
+    stopping || process.signal_state.has_actionable_pending()
+}
+
 /// Housekeeping backstop for best-effort signal wakes that were silently
 /// dropped.
 ///
@@ -1763,10 +1874,7 @@ pub fn retry_dropped_signal_wakes() -> bool {
             if matches!(reason, Ring3BlockReason::WaitingForBlockIo { .. }) {
                 continue;
             }
-            if g.by_pid
-                .get(pid)
-                .is_some_and(|p| p.signal_state.has_actionable_pending())
-            {
+            if signal_wakes(&g, *pid) {
                 candidates.push(*pid);
             }
         }
@@ -1841,17 +1949,38 @@ where
 }
 
 /// Returns true if `pid` has any child currently tracked in
-/// `by_pid` (parent_pid match) OR any unreaped zombie. Used by `wait4`
-/// (U6) to distinguish "no children at all → ECHILD" from "has
-/// children but none zombie yet → block or EAGAIN".
+/// `by_pid` (parent_pid match) OR any unreaped zombie. Distinguishes
+/// "no children at all → ECHILD" from "has children but none zombie
+/// yet → block or EAGAIN"; `wait4` uses [`has_children_matching`].
+#[cfg_attr(not(feature = "test"), expect(dead_code, reason = "QEMU test API"))]
 pub fn has_children(parent_pid: u32) -> bool {
+    has_children_matching(parent_pid, -1)
+}
+
+/// [`has_children`] restricted to children selected by the `wait4`
+/// target `target` (a PID, `-1`, `0` for the caller's process group, or
+/// `-pgid`).
+pub fn has_children_matching(parent_pid: u32, target: i32) -> bool {
     let g = PROCESS_TABLE.lock();
-    let live = g
-        .by_pid
-        .values()
-        .any(|p| p.parent_pid == parent_pid && p.pid != KERNEL_PID);
+    let caller_pgid = g.by_pid.get(&parent_pid).map_or(0, |p| p.job.pgid);
+    let live = g.by_pid.values().any(|p| {
+        p.parent_pid == parent_pid
+            && p.pid != KERNEL_PID
+            && g.thread_groups
+                .get(&p.pid)
+                .is_none_or(|&tgid| tgid == p.pid)
+            && crate::userland::job_control::wait_target_matches(
+                target,
+                p.pid,
+                p.job.pgid,
+                caller_pgid,
+            )
+    });
     drop(g);
-    let zombie = ZOMBIES.lock().values().any(|z| z.parent_pid == parent_pid);
+    let zombie = ZOMBIES.lock().iter().any(|(&pid, z)| {
+        z.parent_pid == parent_pid
+            && crate::userland::job_control::wait_target_matches(target, pid, z.pgid, caller_pgid)
+    });
     live || zombie
 }
 
@@ -2037,6 +2166,7 @@ pub fn install_new_process_opt(
     let mut p = Process {
         pid,
         parent_pid: KERNEL_PID,
+        job: crate::userland::job_control::JobState::leader(pid),
         image: Some(image),
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -2045,11 +2175,15 @@ pub fn install_new_process_opt(
         mmap_next: mmap_base,
         fd_table,
         umask: 0o022,
+        rlimits: crate::userland::rlimit::Rlimits::default(),
+        credentials: crate::userland::credentials::Credentials::default(),
+        seccomp: crate::userland::seccomp::Seccomp::default(),
         network_wait: None,
         real_timer: RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: String::from("/host"),
+        root: String::from("/"),
         address_space,
         signal_state: SignalState::new(),
         signal_alt_stack: crate::userland::signal::SignalAltStack::default(),
@@ -2058,6 +2192,7 @@ pub fn install_new_process_opt(
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -2194,12 +2329,19 @@ pub fn try_grow_user_stack(fault_addr: x86_64::VirtAddr) -> GrowOutcome {
         // the normal fault path. The sentinel is also where test
         // helpers (stage_stack_window) stage synthetic stack windows.
         let cur_pid = current_user_pid().unwrap_or(KERNEL_PID);
+        let addr = fault_addr.as_u64();
+        let tgid = tgid_locked(&guard, cur_pid);
+        let over_limit = match (guard.by_pid.get(&cur_pid), guard.by_pid.get(&tgid)) {
+            (Some(task), Some(leader)) => {
+                crate::userland::rlimit::stack_growth_refused(leader, task.stack_top, addr)
+            }
+            _ => false,
+        };
         let p = guard
             .by_pid
             .get_mut(&cur_pid)
             .expect("sentinel invariant violated");
 
-        let addr = fault_addr.as_u64();
         // No active process (sentinel slot) — stack fields are zero
         // and any compare against them is meaningless. Treat as
         // not-a-stack-grow so the caller routes to its normal path.
@@ -2220,8 +2362,8 @@ pub fn try_grow_user_stack(fault_addr: x86_64::VirtAddr) -> GrowOutcome {
             }
             return GrowOutcome::NotStackGrow;
         }
-        // Below the growth floor — true overflow.
-        if addr < p.stack_max_growth_floor {
+        // Below the growth floor or past RLIMIT_STACK — true overflow.
+        if addr < p.stack_max_growth_floor || over_limit {
             #[cfg(feature = "test")]
             {
                 *LAST_GROW_OUTCOME.lock() = Some(GrowOutcome::Overflow);
@@ -2435,6 +2577,8 @@ pub struct ZombieRecord {
     pub exit_code: i64,
     pub parent_pid: u32,
     pub signal_termination: Option<i32>,
+    /// Process group at exit, for `wait4(0)` and `wait4(-pgid)`.
+    pub pgid: u32,
 }
 
 static ZOMBIES: Mutex<BTreeMap<u32, ZombieRecord>> = Mutex::new(BTreeMap::new());
@@ -2442,12 +2586,14 @@ static ZOMBIES: Mutex<BTreeMap<u32, ZombieRecord>> = Mutex::new(BTreeMap::new())
 /// Mark `pid` as a cooperatively-exited zombie awaiting reap. Used by
 /// `_exit` / `exit_group` when the dying process has a real parent.
 pub fn record_zombie(pid: u32, parent_pid: u32, exit_code: i64) {
+    let pgid = with_process(pid, |p| p.job.pgid).unwrap_or(pid);
     ZOMBIES.lock().insert(
         pid,
         ZombieRecord {
             exit_code,
             parent_pid,
             signal_termination: None,
+            pgid,
         },
     );
 }
@@ -2458,38 +2604,43 @@ pub fn record_zombie(pid: u32, parent_pid: u32, exit_code: i64) {
 /// reads it directly, but `wait4_handler` keys off `signal_termination`
 /// for the POSIX status word.
 pub fn record_zombie_signaled(pid: u32, parent_pid: u32, signum: i32, exit_code: i64) {
+    let pgid = with_process(pid, |p| p.job.pgid).unwrap_or(pid);
     ZOMBIES.lock().insert(
         pid,
         ZombieRecord {
             exit_code,
             parent_pid,
             signal_termination: Some(signum),
+            pgid,
         },
     );
 }
 
 /// Reap a zombie child. If `target_pid` is positive, only that PID
 /// matches; if `target_pid == -1` (any-child semantics), the first
-/// zombie with `parent_pid == reaper` is returned. Returns `(pid,
-/// exit_code, signal_termination)` on success, `None` if no matching
-/// zombie exists.
+/// zombie with `parent_pid == reaper` is returned; `0` and other
+/// negative values select the reaper's own process group or group
+/// `-target_pid`. Returns `(pid, exit_code, signal_termination)` on
+/// success, `None` if no matching zombie exists.
 pub fn reap_zombie(target_pid: i32, reaper: u32) -> Option<(u32, i64, Option<i32>)> {
-    let mut zombies = ZOMBIES.lock();
-    let pid = if target_pid == -1 {
-        zombies
-            .iter()
-            .find(|(_, z)| z.parent_pid == reaper)
-            .map(|(&k, _)| k)?
-    } else if target_pid > 0 {
-        let key = target_pid as u32;
-        let z = zombies.get(&key)?;
-        if z.parent_pid != reaper {
-            return None;
-        }
-        key
+    let reaper_pgid = if target_pid == 0 {
+        with_process(reaper, |p| p.job.pgid).unwrap_or(0)
     } else {
-        return None;
+        0
     };
+    let mut zombies = ZOMBIES.lock();
+    let pid = zombies
+        .iter()
+        .find(|(&pid, z)| {
+            z.parent_pid == reaper
+                && crate::userland::job_control::wait_target_matches(
+                    target_pid,
+                    pid,
+                    z.pgid,
+                    reaper_pgid,
+                )
+        })
+        .map(|(&k, _)| k)?;
     let z = zombies.remove(&pid)?;
     drop(zombies);
 
@@ -2619,6 +2770,8 @@ pub fn cleanup_user_process(reason: AbnormalExit) -> ! {
     // vs not-present vs permission issues from the boot log.
     let walk_target = reason.fault_addr.unwrap_or(reason.fault_rip);
     log_page_table_walk(live_cr3.start_address().as_u64(), walk_target);
+    // Dump core while every mapping is still in place.
+    crate::userland::coredump::capture(&reason, signum_for_vector(reason.vector));
     // Demand-grown stack (U4): release the [stack_mapped_bottom,
     // stack_top) range before UserImage::Drop runs. unmap_user_stack
     // also clears the image's stack_initial_bottom so Drop skips a
@@ -2644,11 +2797,16 @@ pub fn cleanup_user_process(reason: AbnormalExit) -> ! {
     );
 
     let (pid, parent_pid) = with_current_process(|p| (p.pid, p.parent_pid));
+    // A fatal fault takes the whole thread group down, as its signal would.
+    if let Some(tid) = cur_pid {
+        stop_group_siblings(tid, task_tgid(tid).unwrap_or(tid));
+    }
     // Release the crashing process's fds (see `close_group_fds`) before
     // waking the parent, so a parent blocked reading a now-dead child's
     // pipe observes EOF rather than hanging on a write end that would
     // otherwise linger in the retained zombie.
     close_group_fds(task_tgid(pid).unwrap_or(pid));
+    crate::userland::ptrace::exit_group(task_tgid(pid).unwrap_or(pid));
     notify_parent_of_signaled_exit(pid, parent_pid, signum, exit_code);
 
     long_jump_to_run_or_halt();
@@ -2791,6 +2949,7 @@ fn clear_tid_and_wake(tid: u32, tgid: u32) {
 
 fn stop_task(tid: u32) {
     crate::userland::futex::discard_task(tid);
+    crate::userland::ptrace::exit_task(tid);
     PROCESS_TABLE.lock().dead_tasks.insert(tid, ());
     let entity = crate::process::entity::EntityId::UserProcess(tid);
     crate::process::timer::cancel_entity(entity);
@@ -2798,6 +2957,70 @@ fn stop_task(tid: u32) {
     scheduler.unregister_entity(entity);
 }
 
+fn task_on_cpu(tid: u32) -> bool {
+    (0..crate::arch::x86_64::acpi::MAX_CPUS)
+        .any(|cpu| crate::arch::x86_64::percpu::user_pid_on(cpu) == Some(tid))
+}
+
+/// Stop every task of `tgid` except the caller `tid`, then wait until none
+/// of them is still loaded on another CPU. A sibling running ring-3 code
+/// only notices at its next kernel entry (see [`exit_if_stopped`]), so its
+/// CPU is kicked rather than left to its next tick.
+fn stop_group_siblings(tid: u32, tgid: u32) {
+    let members = group_members(tgid);
+    for &member in &members {
+        if member != tid {
+            clear_tid_and_wake(member, tgid);
+            stop_task(member);
+        }
+    }
+    let mut kicked = [false; crate::arch::x86_64::acpi::MAX_CPUS];
+    loop {
+        let mut running = false;
+        for (cpu, kicked) in kicked.iter_mut().enumerate() {
+            let Some(pid) = crate::arch::x86_64::percpu::user_pid_on(cpu) else {
+                continue;
+            };
+            if pid == tid || !members.contains(&pid) {
+                continue;
+            }
+            running = true;
+            if !*kicked {
+                *kicked = true;
+                crate::arch::x86_64::smp::notify_cpu(cpu);
+            }
+        }
+        if !running {
+            return;
+        }
+        crate::arch::x86_64::shootdown::service_pending();
+        core::hint::spin_loop();
+    }
+}
+
+/// Leave the CPU if a sibling's `exit_group` or fatal fault stopped the
+/// current task while it ran here. Checked on every kernel entry a running
+/// task cannot avoid, and once more as `resume_ring3` commits, so a task
+/// selected just before it was stopped never reaches ring 3.
+pub fn exit_if_stopped() {
+    let Some(tid) = current_user_pid() else {
+        return;
+    };
+    if !PROCESS_TABLE.lock().dead_tasks.contains_key(&tid) {
+        return;
+    }
+    crate::diagnostics::shadow::stack::begin_abandon(tid);
+    crate::diagnostics::shadow::cpu::begin_kernel(None);
+    set_current_user_pid(None);
+    crate::diagnostics::shadow::cpu::clear_current_pid();
+    // A resume that raced the stop re-registered the entity.
+    crate::process::scheduler::SCHEDULER
+        .lock()
+        .unregister_entity(crate::process::entity::EntityId::UserProcess(tid));
+    crate::userland::process_service::notify_process_exit(tid);
+    unsafe { crate::userland::switch::dispatch_after_user_stop() }
+}
+
 /// Close every fd the exiting group holds, releasing pipe/socket/eventfd
 /// endpoints at exit time (Linux semantics) rather than deferring until
 /// the parent reaps the zombie.
@@ -2837,6 +3060,7 @@ fn finish_group(tgid: u32, code: i64) {
     // parent blocked reading this process's pipe observes EOF and can
     // proceed to reap.
     close_group_fds(tgid);
+    crate::userland::ptrace::exit_group(tgid);
     // The pipe-endpoint `Drop` inside `close_group_fds` wakes a peer parked on
     // those pipes only through the best-effort `try_lock` variant
     // (`PipeWriteHandle::Drop` -> `wake_ring3_blocked_on_pipe_readable`). Under
@@ -2892,11 +3116,9 @@ pub fn cooperative_thread_exit(code: i64) -> ! {
 pub fn cooperative_group_exit(code: i64) -> ! {
     let tid = current_user_pid().expect("group exit without a current task");
     let tgid = task_tgid(tid).unwrap_or(tid);
-    let members = group_members(tgid);
-    for member in members {
-        clear_tid_and_wake(member, tgid);
-        stop_task(member);
-    }
+    stop_group_siblings(tid, tgid);
+    clear_tid_and_wake(tid, tgid);
+    stop_task(tid);
     with_group(tgid, unmap_user_stack);
     finish_group(tgid, code);
     crate::diagnostics::shadow::stack::begin_abandon(tid);
@@ -1,8 +1,9 @@
 //! ELF64 loader (U6).
 //!
-//! Parses a static non-PIE ELF64 (D3), validates aggressively, then maps
-//! PT_LOAD segments + the user stack via `map_user_region`, walks
-//! `.rela.dyn`/`.rela.plt`, and patches GOT slots to user-trampoline addresses.
+//! Parses an ELF64 executable (static non-PIE per D3, static-PIE, or
+//! dynamically linked), validates aggressively, then maps PT_LOAD segments +
+//! the user stack via `map_user_region`, walks `.rela.dyn`/`.rela.plt` for
+//! static non-PIE images, and patches GOT slots to user-trampoline addresses.
 //!
 //! ## Three phases, in order
 //!
@@ -18,6 +19,18 @@
 //! Failure at any point drops the partial `UserImage`, whose `Drop`
 //! `unmap_user_region`s everything mapped so far (D8).
 //!
+//! ## Dynamic images
+//!
+//! An executable with `PT_INTERP` names its dynamic linker (musl's
+//! `/lib/ld-musl-x86_64.so.1`). The caller reads that file with
+//! [`read_interpreter`] before entering the CR3-sensitive setup transaction,
+//! and the sparse loader maps it at [`USER_INTERP_BASE`] next to the
+//! program. The initial RIP is the interpreter's entry; the auxv carries
+//! `AT_BASE`, `AT_ENTRY` and an in-memory `AT_PHDR` so the linker can find
+//! the program, apply its relocations and set up TLS itself. The kernel
+//! therefore skips relocation and TLS installation for such images.
+//! Position-independent (`ET_DYN`) programs load at [`USER_PIE_LOAD_BASE`].
+//!
 //! ## Hand-rolled parser, ELF64 only
 //!
 //! The parser reads `#[repr(C, packed)]` structs from a `&[u8]` via
@@ -25,6 +38,7 @@
 //! We never trust a length or offset without a `checked_add` against
 //! `bytes.len()` (S5 of the doc-review findings).
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::mem::size_of;
 use x86_64::VirtAddr;
@@ -32,9 +46,9 @@ use x86_64::VirtAddr;
 use crate::fs::File;
 use crate::lib::arc::Arc;
 use crate::mm::paging::{
-    UserPerms, USER_LOAD_BASE, USER_MMAP_BASE, USER_STACK_GUARD_PAGES, USER_STACK_INITIAL_PAGES,
-    USER_STACK_MAX_GROWTH_PAGES, USER_STACK_TOP, USER_TCB_VA, USER_TLS_IMAGE_VA, USER_VA_RANGE_END,
-    USER_VA_RANGE_START,
+    UserPerms, USER_INTERP_BASE, USER_LOAD_BASE, USER_MMAP_BASE, USER_PIE_LOAD_BASE,
+    USER_STACK_GUARD_PAGES, USER_STACK_INITIAL_PAGES, USER_STACK_MAX_GROWTH_PAGES, USER_STACK_TOP,
+    USER_TCB_VA, USER_TLS_IMAGE_VA, USER_VA_RANGE_END, USER_VA_RANGE_START,
 };
 use crate::userland::error::LoaderError;
 use crate::userland::image::UserImage;
@@ -51,12 +65,17 @@ const ELFCLASS64: u8 = 2;
 const ELFDATA2LSB: u8 = 1;
 
 const ET_EXEC: u16 = 2;
+const ET_DYN: u16 = 3;
 const EM_X86_64: u16 = 62;
 
 const PT_LOAD: u32 = 1;
 const PT_INTERP: u32 = 3;
+const PT_PHDR: u32 = 6;
 const PT_TLS: u32 = 7;
 
+/// Longest PT_INTERP path accepted, matching Linux's `PATH_MAX`.
+const MAX_INTERP_PATH: u64 = 4096;
+
 const PF_X: u32 = 1;
 const PF_W: u32 = 2;
 
@@ -223,19 +242,97 @@ struct ParsedPtTls {
 /// and return a transactional `UserImage` handle. On any failure the partial
 /// image is dropped — its `Drop` unmaps anything that was already installed,
 /// so kernel page-table state is unchanged from before the call.
+///
+/// This eager path cannot map an interpreter: a `PT_INTERP` binary fails
+/// with `InterpUnsupported`.
 #[cfg_attr(not(feature = "test"), expect(dead_code, reason = "QEMU test API"))]
 pub fn load_elf(bytes: &[u8]) -> Result<UserImage, LoaderError> {
-    load_elf_impl(bytes, None)
+    load_elf_impl(bytes, None, None)
 }
 
 /// Production sparse loader. Parsing still uses a bounded kernel byte buffer,
 /// while PT_LOAD contents remain backed by the retained file and page in on
 /// first access.
-pub fn load_elf_file(bytes: &[u8], file: Arc<File>) -> Result<UserImage, LoaderError> {
-    load_elf_impl(bytes, Some(file))
+///
+/// `interp` must be the result of [`read_interpreter`] on the same bytes;
+/// a `PT_INTERP` binary without it fails with `InterpUnsupported`.
+pub fn load_elf_file(
+    bytes: &[u8],
+    file: Arc<File>,
+    interp: Option<&Interpreter>,
+) -> Result<UserImage, LoaderError> {
+    load_elf_impl(bytes, Some(file), interp)
+}
+
+/// A `PT_INTERP` dynamic linker, read ahead of address-space setup.
+pub struct Interpreter {
+    pub file: Arc<File>,
+    pub bytes: Vec<u8>,
+}
+
+/// Return the `PT_INTERP` path of `bytes`, or `None` for a static image.
+///
+/// Only the ELF header and program headers are inspected; full validation
+/// happens in the load itself.
+pub fn interp_path(bytes: &[u8]) -> Result<Option<String>, LoaderError> {
+    if bytes.len() < 4 || bytes[..4] != ELF_MAGIC {
+        return Err(LoaderError::BadMagic);
+    }
+    let ehdr: Elf64Ehdr = read_at(bytes, 0)?;
+    validate_ehdr(&ehdr)?;
+    for ph in program_headers(bytes, &ehdr)? {
+        if ph.p_type == PT_INTERP {
+            return parse_pt_interp(&ph, bytes).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Open and read the dynamic linker named by `bytes`' `PT_INTERP`.
+/// `resolve` maps that path to the kernel path to open, so an `execve`
+/// inside a `chroot` finds the linker in its own root.
+///
+/// Storage I/O may sleep, so launchers call this before activating the new
+/// address space, exactly as they read the executable itself.
+pub fn read_interpreter(
+    bytes: &[u8],
+    resolve: impl FnOnce(&str) -> Option<String>,
+) -> Result<Option<Interpreter>, LoaderError> {
+    let Some(path) = interp_path(bytes)? else {
+        return Ok(None);
+    };
+    let path = resolve(&path).ok_or(LoaderError::InterpMissing)?;
+    let file = File::open_read(&path).map_err(|_| LoaderError::InterpMissing)?;
+    let interp_bytes = file.read_to_vec().map_err(|_| LoaderError::InterpMissing)?;
+    check_interpreter(&interp_bytes)?;
+    Ok(Some(Interpreter {
+        file,
+        bytes: interp_bytes,
+    }))
 }
 
-fn load_elf_impl(bytes: &[u8], backing_file: Option<Arc<File>>) -> Result<UserImage, LoaderError> {
+/// Refuse an interpreter that is not an x86-64 ELF image or asks for an
+/// interpreter of its own, while the exec can still fail cleanly.
+fn check_interpreter(bytes: &[u8]) -> Result<(), LoaderError> {
+    if bytes.len() < 4 || bytes[..4] != ELF_MAGIC {
+        return Err(LoaderError::InterpBad);
+    }
+    let ehdr: Elf64Ehdr = read_at(bytes, 0).map_err(|_| LoaderError::InterpBad)?;
+    validate_ehdr(&ehdr).map_err(|_| LoaderError::InterpBad)?;
+    let phdrs = program_headers(bytes, &ehdr).map_err(|_| LoaderError::InterpBad)?;
+    if phdrs.iter().any(|ph| ph.p_type == PT_INTERP) {
+        // An interpreter that asks for another interpreter is refused, as
+        // Linux does.
+        return Err(LoaderError::InterpBad);
+    }
+    Ok(())
+}
+
+fn load_elf_impl(
+    bytes: &[u8],
+    backing_file: Option<Arc<File>>,
+    interp: Option<&Interpreter>,
+) -> Result<UserImage, LoaderError> {
     // ---- Phase 1: validate ----
     // Magic-bytes check first so a 4-byte "XXXX" returns BadMagic instead of
     // the more generic Truncated.
@@ -248,45 +345,65 @@ fn load_elf_impl(bytes: &[u8], backing_file: Option<Arc<File>>) -> Result<UserIm
     let ehdr: Elf64Ehdr = read_at(bytes, 0)?;
     validate_ehdr(&ehdr)?;
 
+    let phdrs = program_headers(bytes, &ehdr)?;
     let phnum = ehdr.e_phnum as u64;
     let phentsize = ehdr.e_phentsize as u64;
     let phoff = ehdr.e_phoff;
-    if phentsize < size_of::<Elf64Phdr>() as u64 {
-        return Err(LoaderError::Truncated);
-    }
-    let pht_end = phoff
-        .checked_add(phnum.checked_mul(phentsize).ok_or(LoaderError::Truncated)?)
-        .ok_or(LoaderError::Truncated)?;
-    if pht_end > bytes.len() as u64 {
-        return Err(LoaderError::Truncated);
-    }
 
-    let mut pt_loads: Vec<ParsedPtLoad> = Vec::new();
-    let mut pt_tls: Option<ParsedPtTls> = None;
-    for i in 0..phnum {
-        let off = phoff + i * phentsize;
-        let ph: Elf64Phdr = read_at(bytes, off)?;
+    let mut raw_loads: Vec<Elf64Phdr> = Vec::new();
+    let mut raw_tls: Option<Elf64Phdr> = None;
+    let mut pt_phdr_vaddr: Option<u64> = None;
+    let mut has_interp = false;
+    for ph in &phdrs {
         match ph.p_type {
             PT_TLS => {
-                if pt_tls.is_some() {
+                if raw_tls.is_some() {
                     // Multiple PT_TLS segments are malformed per the ELF
                     // spec — the dynamic linker only resolves the first.
                     return Err(LoaderError::TlsUnsupported);
                 }
-                pt_tls = Some(parse_pt_tls(&ph, bytes)?);
+                raw_tls = Some(*ph);
             }
-            PT_INTERP => return Err(LoaderError::InterpUnsupported),
-            PT_LOAD => pt_loads.push(parse_pt_load(&ph, bytes)?),
-            _ => { /* PT_DYNAMIC, PT_PHDR, PT_GNU_*, PT_NULL: ignored */ }
+            PT_INTERP => {
+                parse_pt_interp(ph, bytes)?;
+                if backing_file.is_none() || interp.is_none() {
+                    return Err(LoaderError::InterpUnsupported);
+                }
+                has_interp = true;
+            }
+            PT_PHDR => pt_phdr_vaddr = Some(ph.p_vaddr),
+            PT_LOAD => raw_loads.push(*ph),
+            _ => { /* PT_DYNAMIC, PT_GNU_*, PT_NULL: ignored */ }
         }
     }
+    let is_pie = ehdr.e_type == ET_DYN;
+    // The dynamic linker installs TLS itself; only static images need the
+    // kernel-built TLS block (and its single-page limit).
+    let pt_tls = match raw_tls {
+        Some(ph) if !has_interp => Some(parse_pt_tls(&ph, bytes)?),
+        _ => None,
+    };
+
+    let bias = if is_pie {
+        load_bias(&raw_loads, USER_PIE_LOAD_BASE)?
+    } else {
+        0
+    };
+    let pt_loads = raw_loads
+        .iter()
+        .map(|ph| parse_pt_load(ph, bytes, bias))
+        .collect::<Result<Vec<_>, _>>()?;
 
     if pt_loads.is_empty() {
         return Err(LoaderError::EntryNotMapped);
     }
 
     check_no_overlap(&pt_loads)?;
-    check_entry_in_pt_load(ehdr.e_entry, &pt_loads)?;
+    let program_entry = ehdr
+        .e_entry
+        .checked_add(bias)
+        .ok_or(LoaderError::SegmentOverflow)?;
+    check_entry_in_pt_load(program_entry, &pt_loads)?;
 
     // ---- Phase 2: allocate + copy ----
 
@@ -390,7 +507,7 @@ fn load_elf_impl(bytes: &[u8], backing_file: Option<Arc<File>>) -> Result<UserIm
     let _ = USER_MMAP_BASE;
 
     let mut image = UserImage::new(
-        VirtAddr::new(ehdr.e_entry),
+        VirtAddr::new(program_entry),
         VirtAddr::new(USER_STACK_TOP),
         bounds_start,
         bounds_end,
@@ -404,6 +521,15 @@ fn load_elf_impl(bytes: &[u8], backing_file: Option<Arc<File>>) -> Result<UserIm
     let phdrs_offset = phoff as usize;
     let phdr_bytes = bytes[phdrs_offset..phdrs_offset + phdrs_size].to_vec();
     image.set_phdrs(phdr_bytes, ehdr.e_phnum);
+    // A dynamic linker locates the program through AT_PHDR, so the table it
+    // sees must be the mapped one rather than the on-stack copy.
+    if has_interp || is_pie {
+        if let Some(phdr_va) =
+            mapped_phdr_va(pt_phdr_vaddr, bias, phoff, phnum * phentsize, &pt_loads)
+        {
+            image.set_phdr_va(phdr_va);
+        }
+    }
 
     for seg in &pt_loads {
         let perms = perms_for_p_flags(seg.p_flags);
@@ -474,12 +600,130 @@ fn load_elf_impl(bytes: &[u8], backing_file: Option<Arc<File>>) -> Result<UserIm
         install_tls(&mut image, &tls, bytes, tls_image_va, tcb_va)?;
     }
 
+    // ---- Phase 2c: dynamic linker ----
+    if let (true, Some(interp)) = (has_interp, interp) {
+        map_interpreter(&mut image, interp, &pt_loads, stack_max_growth_floor).map_err(
+            |e| match e {
+                LoaderError::OutOfFrames | LoaderError::MappingFailed(_) => e,
+                _ => LoaderError::InterpBad,
+            },
+        )?;
+    }
+
     // ---- Phase 3: relocate ----
-    apply_relocations(bytes, &ehdr, &pt_loads)?;
+    // PIE and dynamically linked programs are relocated in ring 3 by their
+    // own startup code or by the interpreter.
+    if !has_interp && !is_pie {
+        apply_relocations(bytes, &ehdr, &pt_loads)?;
+    }
 
     Ok(image)
 }
 
+/// Map the `PT_INTERP` dynamic linker's PT_LOADs, file-backed, at
+/// `USER_INTERP_BASE` and redirect the image entry to the interpreter.
+fn map_interpreter(
+    image: &mut UserImage,
+    interp: &Interpreter,
+    program_loads: &[ParsedPtLoad],
+    stack_max_growth_floor: u64,
+) -> Result<(), LoaderError> {
+    let bytes = interp.bytes.as_slice();
+    check_interpreter(bytes)?;
+    let ehdr: Elf64Ehdr = read_at(bytes, 0)?;
+    let phdrs = program_headers(bytes, &ehdr)?;
+    let raw_loads: Vec<Elf64Phdr> = phdrs
+        .iter()
+        .filter(|ph| ph.p_type == PT_LOAD)
+        .copied()
+        .collect();
+    let bias = if ehdr.e_type == ET_DYN {
+        load_bias(&raw_loads, USER_INTERP_BASE)?
+    } else {
+        0
+    };
+    let loads = raw_loads
+        .iter()
+        .map(|ph| parse_pt_load(ph, bytes, bias))
+        .collect::<Result<Vec<_>, _>>()?;
+    if loads.is_empty() {
+        return Err(LoaderError::EntryNotMapped);
+    }
+    check_no_overlap(&loads)?;
+    let entry = ehdr
+        .e_entry
+        .checked_add(bias)
+        .ok_or(LoaderError::SegmentOverflow)?;
+    check_entry_in_pt_load(entry, &loads)?;
+    for seg in &loads {
+        let seg_end = seg.page_va + seg.page_count * 0x1000;
+        if seg_end > stack_max_growth_floor {
+            return Err(LoaderError::VaOutOfRange);
+        }
+        if program_loads.iter().any(|other| {
+            seg.page_va < other.page_va + other.page_count * 0x1000 && other.page_va < seg_end
+        }) {
+            return Err(LoaderError::OverlappingPtLoad);
+        }
+    }
+
+    for seg in &loads {
+        let file_offset = seg
+            .p_offset
+            .checked_sub(seg.head_pad)
+            .ok_or(LoaderError::AlignmentBad)?;
+        let file_len = seg
+            .head_pad
+            .checked_add(seg.p_filesz)
+            .ok_or(LoaderError::SegmentOverflow)?;
+        let mapped_len = seg.page_count * 0x1000;
+        image.record_mapping_with_perms(
+            VirtAddr::new(seg.page_va),
+            seg.page_count,
+            perms_for_p_flags(seg.p_flags),
+        );
+        image.record_elf_backing(
+            seg.page_va,
+            interp.file.clone(),
+            file_offset,
+            file_len,
+            mapped_len.saturating_sub(file_len),
+        );
+    }
+    let base = loads.iter().map(|seg| seg.page_va).min().unwrap_or(bias);
+    image.set_interpreter(VirtAddr::new(entry), base);
+    Ok(())
+}
+
+/// Bias that places the lowest PT_LOAD page of an `ET_DYN` object at `base`.
+fn load_bias(loads: &[Elf64Phdr], base: u64) -> Result<u64, LoaderError> {
+    let lowest = loads
+        .iter()
+        .map(|ph| ph.p_vaddr & !0xFFF)
+        .min()
+        .ok_or(LoaderError::EntryNotMapped)?;
+    base.checked_sub(lowest).ok_or(LoaderError::VaOutOfRange)
+}
+
+/// Load-time VA of the program-header table when a PT_LOAD maps it.
+/// `loads` are already biased; a `PT_PHDR` address is not.
+fn mapped_phdr_va(
+    pt_phdr_vaddr: Option<u64>,
+    bias: u64,
+    phoff: u64,
+    table_len: u64,
+    loads: &[ParsedPtLoad],
+) -> Option<u64> {
+    if let Some(vaddr) = pt_phdr_vaddr {
+        return vaddr.checked_add(bias);
+    }
+    loads.iter().find_map(|seg| {
+        let table_end = phoff.checked_add(table_len)?;
+        (seg.p_offset <= phoff && table_end <= seg.p_offset + seg.p_filesz)
+            .then(|| seg.p_vaddr - seg.p_offset + phoff)
+    })
+}
+
 // ---------- PT_TLS parse + install ----------
 
 fn parse_pt_tls(ph: &Elf64Phdr, bytes: &[u8]) -> Result<ParsedPtTls, LoaderError> {
@@ -601,21 +845,71 @@ fn validate_ehdr(ehdr: &Elf64Ehdr) -> Result<(), LoaderError> {
     {
         return Err(LoaderError::WrongArch);
     }
-    if {
-        let t = ehdr.e_type;
-        t
-    } != ET_EXEC
-    {
+    if !matches!(
+        {
+            let t = ehdr.e_type;
+            t
+        },
+        ET_EXEC | ET_DYN
+    ) {
         return Err(LoaderError::WrongType);
     }
     Ok(())
 }
 
-fn parse_pt_load(ph: &Elf64Phdr, bytes: &[u8]) -> Result<ParsedPtLoad, LoaderError> {
+/// Read every program header after checking the table lies inside `bytes`.
+fn program_headers(bytes: &[u8], ehdr: &Elf64Ehdr) -> Result<Vec<Elf64Phdr>, LoaderError> {
+    let phnum = ehdr.e_phnum as u64;
+    let phentsize = ehdr.e_phentsize as u64;
+    let phoff = ehdr.e_phoff;
+    if phentsize < size_of::<Elf64Phdr>() as u64 {
+        return Err(LoaderError::Truncated);
+    }
+    let pht_end = phoff
+        .checked_add(phnum.checked_mul(phentsize).ok_or(LoaderError::Truncated)?)
+        .ok_or(LoaderError::Truncated)?;
+    if pht_end > bytes.len() as u64 {
+        return Err(LoaderError::Truncated);
+    }
+    (0..phnum)
+        .map(|i| read_at(bytes, phoff + i * phentsize))
+        .collect()
+}
+
+/// Extract the NUL-terminated absolute path stored in a PT_INTERP segment.
+fn parse_pt_interp(ph: &Elf64Phdr, bytes: &[u8]) -> Result<String, LoaderError> {
+    let p_offset = ph.p_offset;
+    let p_filesz = ph.p_filesz;
+    if !(2..=MAX_INTERP_PATH).contains(&p_filesz) {
+        return Err(LoaderError::InterpUnsupported);
+    }
+    let end = p_offset
+        .checked_add(p_filesz)
+        .ok_or(LoaderError::SegmentOverflow)?;
+    if end > bytes.len() as u64 {
+        return Err(LoaderError::Truncated);
+    }
+    let raw = &bytes[p_offset as usize..end as usize];
+    let path = match raw.iter().position(|&b| b == 0) {
+        Some(nul) => &raw[..nul],
+        None => return Err(LoaderError::InterpUnsupported),
+    };
+    match core::str::from_utf8(path) {
+        Ok(path) if path.starts_with('/') => Ok(String::from(path)),
+        _ => Err(LoaderError::InterpUnsupported),
+    }
+}
+
+/// Parse one PT_LOAD, relocating its addresses by `bias` (non-zero only for
+/// `ET_DYN` objects).
+fn parse_pt_load(ph: &Elf64Phdr, bytes: &[u8], bias: u64) -> Result<ParsedPtLoad, LoaderError> {
     let p_offset = ph.p_offset;
     let p_filesz = ph.p_filesz;
     let p_memsz = ph.p_memsz;
-    let p_vaddr = ph.p_vaddr;
+    let p_vaddr = ph
+        .p_vaddr
+        .checked_add(bias)
+        .ok_or(LoaderError::SegmentOverflow)?;
     let p_align = ph.p_align;
     let p_flags = ph.p_flags;
 
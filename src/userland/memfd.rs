@@ -0,0 +1,283 @@
+//! `memfd_create` and file sealing.
+//!
+//! A memfd is an anonymous regular file. It is created as a hidden file on
+//! the `/dev/shm` tmpfs, opened read-write, and unlinked at once: tmpfs keeps
+//! an unlinked body alive while a handle is open, so the file lives exactly
+//! as long as some descriptor or mapping refers to it. `mmap(MAP_SHARED)` of
+//! a memfd goes through [`SharedObject`](crate::userland::shared_memory) like
+//! any other file, which is how two processes share its pages after `fork`
+//! or descriptor passing.
+//!
+//! Seals (`fcntl(F_ADD_SEALS)`) restrict later changes to the file. Since a
+//! memfd cannot be reopened by name, its one [`File`] is the whole identity
+//! and the seal table is keyed by that allocation. A memfd created without
+//! `MFD_ALLOW_SEALING` starts with `F_SEAL_SEAL`, so it never accepts seals;
+//! any other file reports `EINVAL` to both seal commands.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use spin::Mutex;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::fs::File;
+use crate::lib::arc::{Arc, Weak};
+use crate::userland::abi::{EBUSY, EINVAL, EMFILE, EPERM};
+use crate::userland::fdtable::FdSlot;
+
+pub const MFD_CLOEXEC: u32 = 0x1;
+pub const MFD_ALLOW_SEALING: u32 = 0x2;
+
+/// Longest accepted name: `NAME_MAX` less the `memfd:` prefix.
+const MFD_NAME_MAX: usize = 249;
+
+/// Status flags of a memfd descriptor (`O_RDWR`).
+const O_RDWR: u32 = 0o2;
+
+pub const F_SEAL_SEAL: u32 = 0x1;
+pub const F_SEAL_SHRINK: u32 = 0x2;
+pub const F_SEAL_GROW: u32 = 0x4;
+pub const F_SEAL_WRITE: u32 = 0x8;
+pub const F_SEAL_FUTURE_WRITE: u32 = 0x10;
+const F_SEAL_ALL: u32 =
+    F_SEAL_SEAL | F_SEAL_SHRINK | F_SEAL_GROW | F_SEAL_WRITE | F_SEAL_FUTURE_WRITE;
+
+struct Memfd {
+    /// Address of the `File`. The `Weak` below keeps the allocation alive,
+    /// so the address cannot be reused while this entry exists.
+    key: usize,
+    file: Weak<File>,
+    name: String,
+    seals: u32,
+}
+
+/// Live memfds. Entries whose file has been dropped are pruned on the next
+/// `memfd_create`.
+static MEMFDS: Mutex<Vec<Memfd>> = Mutex::new(Vec::new());
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn key(file: &Arc<File>) -> usize {
+    Arc::as_ptr(file) as usize
+}
+
+/// `memfd_create(name, flags) -> int`
+pub fn memfd_create_handler(args: &mut SyscallArgs) -> i64 {
+    let name = match crate::userland::path::copy_user_cstr(args.rdi) {
+        Ok(name) => name,
+        Err(e) => return e,
+    };
+    let flags = args.rsi as u32;
+    // MFD_HUGETLB and its size bits are not supported.
+    if flags & !(MFD_CLOEXEC | MFD_ALLOW_SEALING) != 0 || name.len() > MFD_NAME_MAX {
+        return EINVAL;
+    }
+    let path = format!(
+        "{}/.memfd-{}",
+        crate::userland::devfs::SHM_DIR,
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    );
+    let mode = crate::fs::filesystem::FileMode {
+        read: true,
+        write: true,
+        append: false,
+        create: true,
+        truncate: true,
+    };
+    let handle = match File::open(&path, mode) {
+        Ok(handle) => handle,
+        Err(ref e) => return crate::userland::syscalls::map_file_err(e),
+    };
+    if crate::fs::vfs::vfs_unlink(&path).is_err() {
+        crate::debug_warn!("memfd_create: could not unlink {}", path);
+    }
+
+    {
+        let mut memfds = MEMFDS.lock();
+        memfds.retain(|memfd| memfd.file.strong_count() > 0);
+        memfds.push(Memfd {
+            key: key(&handle),
+            file: Arc::downgrade(&handle),
+            name,
+            seals: if flags & MFD_ALLOW_SEALING != 0 {
+                0
+            } else {
+                F_SEAL_SEAL
+            },
+        });
+    }
+
+    let slot = FdSlot::File {
+        handle,
+        status_flags: O_RDWR,
+        cloexec: flags & MFD_CLOEXEC != 0,
+    };
+    crate::userland::lifecycle::with_current_group(|process| process.fd_table.alloc(slot))
+        .map_or(EMFILE, i64::from)
+}
+
+/// Seals on `file`, or `None` when it is not a memfd.
+pub fn seals(file: &Arc<File>) -> Option<u32> {
+    let memfds = MEMFDS.lock();
+    if memfds.is_empty() {
+        return None;
+    }
+    let key = key(file);
+    memfds
+        .iter()
+        .find(|memfd| memfd.key == key)
+        .map(|memfd| memfd.seals)
+}
+
+/// The `/proc/self/fd` link text for a memfd, as Linux prints it.
+pub fn link_name(file: &Arc<File>) -> Option<String> {
+    let key = key(file);
+    MEMFDS
+        .lock()
+        .iter()
+        .find(|memfd| memfd.key == key)
+        .map(|memfd| format!("/memfd:{} (deleted)", memfd.name))
+}
+
+/// `fcntl(F_ADD_SEALS)`. `writable` is whether the descriptor was opened
+/// for writing, which Linux requires.
+pub fn add_seals(file: &Arc<File>, writable: bool, requested: u32) -> Result<(), i64> {
+    let key = key(file);
+    let added = {
+        let mut memfds = MEMFDS.lock();
+        let memfd = memfds
+            .iter_mut()
+            .find(|memfd| memfd.key == key)
+            .ok_or(EINVAL)?;
+        if !writable {
+            return Err(EPERM);
+        }
+        let merged = merge(memfd.seals, requested)?;
+        let added = merged & !memfd.seals;
+        memfd.seals = merged;
+        added
+    };
+    // The seal is published before the scan: mmap and mprotect test seals
+    // under the process lock, so any writable mapping made before the seal
+    // became visible is found here, and any made after is refused.
+    if added & F_SEAL_WRITE != 0 && writably_mapped(file) {
+        let mut memfds = MEMFDS.lock();
+        if let Some(memfd) = memfds.iter_mut().find(|memfd| memfd.key == key) {
+            memfd.seals &= !added;
+        }
+        return Err(EBUSY);
+    }
+    Ok(())
+}
+
+/// True when some address space maps `file` shared and writable.
+fn writably_mapped(file: &Arc<File>) -> bool {
+    crate::userland::lifecycle::any_address_space(|vmas| {
+        vmas.as_slice().iter().any(|vma| {
+            vma.prot.contains(crate::userland::vm::VmProt::WRITE)
+                && vma
+                    .shared_at(vma.start)
+                    .is_some_and(|(object, _)| object.is_backed_by(file))
+        })
+    })
+}
+
+/// Validate `requested` against the current seals and return their union.
+fn merge(current: u32, requested: u32) -> Result<u32, i64> {
+    if requested & !F_SEAL_ALL != 0 {
+        return Err(EINVAL);
+    }
+    if current & F_SEAL_SEAL != 0 {
+        return Err(EPERM);
+    }
+    Ok(current | requested)
+}
+
+fn write_permitted(seals: u32, offset: u64, len: u64, size: u64) -> bool {
+    if seals & (F_SEAL_WRITE | F_SEAL_FUTURE_WRITE) != 0 {
+        return false;
+    }
+    seals & F_SEAL_GROW == 0 || offset.saturating_add(len) <= size
+}
+
+fn resize_permitted(seals: u32, size: u64, new_size: u64) -> bool {
+    !(new_size < size && seals & F_SEAL_SHRINK != 0 || new_size > size && seals & F_SEAL_GROW != 0)
+}
+
+/// Refuse a `len`-byte write at `offset` that the file's seals forbid.
+pub fn check_write(file: &Arc<File>, offset: u64, len: u64) -> Result<(), i64> {
+    match seals(file) {
+        Some(seals) if !write_permitted(seals, offset, len, file.size()) => Err(EPERM),
+        _ => Ok(()),
+    }
+}
+
+/// Refuse a truncate to `new_size` that the file's seals forbid.
+pub fn check_resize(file: &Arc<File>, new_size: u64) -> Result<(), i64> {
+    match seals(file) {
+        Some(seals) if !resize_permitted(seals, file.size(), new_size) => Err(EPERM),
+        _ => Ok(()),
+    }
+}
+
+/// True when the file's seals forbid new writable shared mappings.
+pub fn write_sealed(file: &Arc<File>) -> bool {
+    seals(file).is_some_and(|seals| seals & (F_SEAL_WRITE | F_SEAL_FUTURE_WRITE) != 0)
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn test_merge_accumulates_until_sealed() {
+        assert_eq!(merge(0, F_SEAL_GROW), Ok(F_SEAL_GROW));
+        assert_eq!(
+            merge(F_SEAL_GROW, F_SEAL_SHRINK | F_SEAL_SEAL),
+            Ok(F_SEAL_GROW | F_SEAL_SHRINK | F_SEAL_SEAL)
+        );
+        // Adding nothing is allowed until F_SEAL_SEAL is set.
+        assert_eq!(merge(F_SEAL_WRITE, 0), Ok(F_SEAL_WRITE));
+        assert_eq!(merge(F_SEAL_SEAL, F_SEAL_WRITE), Err(EPERM));
+        assert_eq!(merge(F_SEAL_SEAL, 0), Err(EPERM));
+    }
+
+    fn test_merge_rejects_unknown_bits() {
+        assert_eq!(merge(0, 0x20), Err(EINVAL));
+        // Unknown bits win over an existing F_SEAL_SEAL, as on Linux.
+        assert_eq!(merge(F_SEAL_SEAL, 0x100), Err(EINVAL));
+    }
+
+    fn test_write_seals() {
+        assert!(write_permitted(0, 4096, 100, 0));
+        assert!(!write_permitted(F_SEAL_WRITE, 0, 1, 10));
+        assert!(!write_permitted(F_SEAL_FUTURE_WRITE, 0, 1, 10));
+        // F_SEAL_GROW allows overwriting but not extending.
+        assert!(write_permitted(F_SEAL_GROW, 0, 10, 10));
+        assert!(!write_permitted(F_SEAL_GROW, 5, 6, 10));
+        // F_SEAL_SHRINK alone never limits writes.
+        assert!(write_permitted(F_SEAL_SHRINK, 100, 1, 10));
+    }
+
+    fn test_resize_seals() {
+        assert!(resize_permitted(0, 10, 0));
+        assert!(resize_permitted(F_SEAL_GROW, 10, 5));
+        assert!(!resize_permitted(F_SEAL_GROW, 10, 11));
+        assert!(resize_permitted(F_SEAL_SHRINK, 10, 11));
+        assert!(!resize_permitted(F_SEAL_SHRINK, 10, 9));
+        // Truncating to the current size is a no-op under any seal.
+        assert!(resize_permitted(F_SEAL_SHRINK | F_SEAL_GROW, 10, 10));
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_merge_accumulates_until_sealed,
+            &test_merge_rejects_unknown_bits,
+            &test_write_seals,
+            &test_resize_seals,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as memfd_tests;
@@ -6,6 +6,8 @@
 pub mod abi;
 pub mod address_space;
 pub mod bin_namespace;
+pub mod coredump;
+pub mod credentials;
 pub mod devfs;
 pub mod epoll;
 pub mod error;
@@ -17,28 +19,45 @@ pub mod gui;
 pub mod gui_gl;
 pub mod gui_syscalls;
 pub mod image;
+pub mod inotify;
+pub mod job_control;
 pub mod kernel_stack;
 pub mod launcher;
 pub mod lifecycle;
 pub mod loader;
-pub mod local_stream;
+pub mod memfd;
+pub mod mount;
+pub mod namespace;
 pub mod network_syscalls;
 pub mod path;
 pub mod pipe;
+pub mod priority;
 pub mod process_service;
 pub mod procfs;
+pub mod ptrace;
+pub mod pts;
 pub mod pty_syscalls;
 pub mod readiness;
 pub mod record_lock;
+pub mod rlimit;
+pub mod seccomp;
+pub mod shared_memory;
+pub mod shebang;
 pub mod signal;
+pub mod signalfd;
+pub mod statfs;
 pub mod stdin;
 pub mod switch;
 pub mod syscalls;
+pub mod timerfd;
 pub mod tty;
+pub mod unix_socket;
 pub mod user_state;
 pub mod usercopy;
+pub mod vdso;
 pub mod vm;
 
+use alloc::vec::Vec;
 use core::arch::naked_asm;
 
 use crate::userland::image::UserImage;
@@ -51,10 +70,24 @@ const AT_PHDR: u64 = 3;
 const AT_PHENT: u64 = 4;
 const AT_PHNUM: u64 = 5;
 const AT_PAGESZ: u64 = 6;
+const AT_BASE: u64 = 7;
+const AT_ENTRY: u64 = 9;
 const AT_RANDOM: u64 = 25;
+const AT_SYSINFO_EHDR: u64 = 33;
 
 const ELF64_PHDR_SIZE: u64 = 56;
 
+/// Words in the auxiliary vector: nine `(type, value)` pairs ending with
+/// `AT_NULL`.
+const AUXV_WORDS: usize = 18;
+
+/// Result of [`build_initial_stack`]: the entry RSP and a copy of the auxv
+/// it wrote, which the address space keeps for core dumps.
+pub(crate) struct InitialStack {
+    pub rsp: u64,
+    pub auxv: [u64; AUXV_WORDS],
+}
+
 /// Default argv[0] when the caller doesn't supply one. Tests and the
 /// zero-arg `enter_user_mode(image)` wrapper use this. Real launches via
 /// the `run` shell command pass the file path as argv[0].
@@ -161,7 +194,8 @@ pub fn setup_user_process(
             .current()
             .and_then(|pid| sched.get_process(pid))
             .and_then(|pcb| pcb.terminal_id)
-    };
+    }
+    .and_then(crate::terminal::pty::terminal_for_window);
     let pid = setup_user_process_unstarted(
         image,
         argv,
@@ -185,17 +219,19 @@ pub(crate) fn setup_user_process_unstarted(
     argv: &[&str],
     envp: &[&str],
     mut address_space: Option<crate::userland::address_space::AddressSpace>,
-    terminal_id: Option<crate::window::WindowId>,
+    terminal_id: Option<crate::terminal::pty::TerminalId>,
     at_random: &[u8; 16],
 ) -> Result<u32, EnterError> {
     // This is the single authoritative VMA initialization point for every
     // entry path. Building it exactly once avoids cloning and then dropping
     // every ELF-backed Arc during the CR3-sensitive setup transaction.
+    let mut vdso_base = None;
     if let Some(space) = address_space.as_mut() {
         space
             .initialize_vmas_from_image(&image)
             .map_err(|_| EnterError::InvalidVmLayout)?;
         image.transfer_mapping_ownership();
+        vdso_base = crate::userland::vdso::map_into(space);
     }
     // Capture the bits the initial-stack builder needs before the image
     // moves into the new Process slot.
@@ -205,8 +241,7 @@ pub(crate) fn setup_user_process_unstarted(
         start: image.bounds_start,
         end: image.bounds_end,
     };
-    let phdr_bytes = image.phdr_bytes.clone();
-    let e_phnum = image.e_phnum;
+    let auxv = ImageAuxv::from_image(&image, vdso_base);
 
     // Default argv[0] when the caller didn't supply anything — keeps musl
     // happy without forcing every test path to thread a path string.
@@ -217,8 +252,11 @@ pub(crate) fn setup_user_process_unstarted(
     // of the user stack pages. CR3 is already pointing at the new
     // process's L4 (the caller activated it before calling us), so
     // these writes land in the right address space.
-    let user_rsp =
-        build_initial_stack(stack_top, &phdr_bytes, e_phnum, argv_slice, envp, at_random);
+    let initial = build_initial_stack(stack_top, &auxv, argv_slice, envp, at_random);
+    let user_rsp = initial.rsp;
+    if let Some(space) = address_space.as_mut() {
+        space.set_saved_auxv(&initial.auxv);
+    }
 
     // Install the new Process. U8: don't make it current — just insert
     // and mark ready. The scheduler picks it up.
@@ -295,6 +333,41 @@ pub fn wait_for_ring3_exit(pid: u32) -> (ExitKind, i64) {
         .unwrap_or((ExitKind::None, 0))
 }
 
+/// Image facts the initial-stack builder publishes through auxv, captured
+/// before the `UserImage` moves onto its Process.
+pub(crate) struct ImageAuxv {
+    /// Raw program headers, copied onto the stack when `phdr_va` is `None`.
+    pub phdr_bytes: Vec<u8>,
+    pub e_phnum: u16,
+    /// Mapped program-header table; replaces the on-stack copy for AT_PHDR.
+    pub phdr_va: Option<u64>,
+    /// `AT_ENTRY`: the program's own entry, even when ring 3 starts in ld.so.
+    pub program_entry: u64,
+    /// `AT_BASE`: dynamic linker load base, or 0 for static images.
+    pub interp_base: u64,
+    /// `AT_SYSINFO_EHDR`: the mapped vDSO image, or 0 when the address
+    /// space has none, which libc reads as absent.
+    pub vdso_base: u64,
+}
+
+impl ImageAuxv {
+    pub(crate) fn from_image(image: &UserImage, vdso_base: Option<u64>) -> Self {
+        let phdr_bytes = if image.phdr_va.is_some() {
+            Vec::new()
+        } else {
+            image.phdr_bytes.clone()
+        };
+        Self {
+            phdr_bytes,
+            e_phnum: image.e_phnum,
+            phdr_va: image.phdr_va,
+            program_entry: image.program_entry.as_u64(),
+            interp_base: image.interp_base.unwrap_or(0),
+            vdso_base: vdso_base.unwrap_or(0),
+        }
+    }
+}
+
 /// Build the Linux x86-64 initial stack frame.
 ///
 /// Layout at `_start` (low addr to high), per the System V AMD64 psABI:
@@ -305,9 +378,11 @@ pub fn wait_for_ring3_exit(pid: u32) -> (ExitKind, i64) {
 ///          argv[argc] = NULL                          (1 qword)
 ///          envp[0..envc]                              (envc × 8 bytes)
 ///          envp[envc] = NULL                          (1 qword)
-///          auxv pairs: AT_PHDR / AT_PHENT / AT_PHNUM /
-///                       AT_PAGESZ / AT_RANDOM / AT_NULL  (6 × 16 bytes)
-///          phdr_table (e_phnum × 56 bytes, padded to 16 align)
+///          auxv pairs: AT_PHDR / AT_PHENT / AT_PHNUM / AT_PAGESZ /
+///                      AT_BASE / AT_ENTRY / AT_RANDOM / AT_SYSINFO_EHDR /
+///                      AT_NULL (9 × 16 bytes)
+///          phdr_table (e_phnum × 56 bytes, padded to 16 align; omitted
+///                      when the image maps its own table)
 ///          AT_RANDOM payload (16 bytes)
 ///          string pool: argv strings then envp strings, each NUL-terminated
 ///                       (padded to 16 align for the topmost frame edge)
@@ -322,14 +397,15 @@ pub fn wait_for_ring3_exit(pid: u32) -> (ExitKind, i64) {
 /// e_phoff` because the kernel's hand-rolled fixtures don't include the
 /// program headers inside any PT_LOAD; copying them onto the stack lets
 /// the same code path serve fixtures and real musl-cross-make binaries.
+/// Dynamic and PIE images are the exception: the dynamic linker derives the
+/// load bias from `AT_PHDR`, so it must name the mapped table.
 pub(crate) fn build_initial_stack(
     stack_top: u64,
-    phdr_bytes: &[u8],
-    e_phnum: u16,
+    auxv: &ImageAuxv,
     argv: &[&str],
     envp: &[&str],
     at_random: &[u8; 16],
-) -> u64 {
+) -> InitialStack {
     let argc = argv.len() as u64;
     let envc = envp.len() as u64;
 
@@ -341,9 +417,9 @@ pub(crate) fn build_initial_stack(
     let strings_size: u64 = align_up_16(strings_raw);
 
     let random_size: u64 = 16;
-    let phdr_size: u64 = align_up_16((e_phnum as u64) * ELF64_PHDR_SIZE);
-    // 6 auxv pairs × 16 bytes each.
-    let auxv_size: u64 = 6 * 16;
+    let phdr_bytes = auxv.phdr_bytes.as_slice();
+    let phdr_size: u64 = align_up_16(phdr_bytes.len() as u64);
+    let auxv_size: u64 = AUXV_WORDS as u64 * 8;
     let envp_array_size: u64 = (envc + 1) * 8;
     let argv_array_size: u64 = (argc + 1) * 8;
     let argc_size: u64 = 8;
@@ -384,6 +460,27 @@ pub(crate) fn build_initial_stack(
     p += strings_size;
     debug_assert_eq!(p, stack_top, "stack frame layout drift");
 
+    let auxv_words: [u64; AUXV_WORDS] = [
+        AT_PHDR,
+        auxv.phdr_va.unwrap_or(phdr_at),
+        AT_PHENT,
+        ELF64_PHDR_SIZE,
+        AT_PHNUM,
+        auxv.e_phnum as u64,
+        AT_PAGESZ,
+        0x1000,
+        AT_BASE,
+        auxv.interp_base,
+        AT_ENTRY,
+        auxv.program_entry,
+        AT_RANDOM,
+        random_at,
+        AT_SYSINFO_EHDR,
+        auxv.vdso_base,
+        AT_NULL,
+        0,
+    ];
+
     // SAFETY: the user stack pages [stack_top - 8*0x1000, stack_top) are
     // mapped R+W by the loader, and we are CPL=0 — kernel writes ignore
     // the USER bit and the leaf flags. The frame fits inside the topmost
@@ -418,31 +515,9 @@ pub(crate) fn build_initial_stack(
 
         // auxv pairs (low-to-high in declared order — order doesn't
         // matter to musl as long as AT_NULL terminates the list).
-        let mut a = auxv_at;
-        write_u64_at(a, AT_PHDR);
-        a += 8;
-        write_u64_at(a, phdr_at);
-        a += 8;
-        write_u64_at(a, AT_PHENT);
-        a += 8;
-        write_u64_at(a, ELF64_PHDR_SIZE);
-        a += 8;
-        write_u64_at(a, AT_PHNUM);
-        a += 8;
-        write_u64_at(a, e_phnum as u64);
-        a += 8;
-        write_u64_at(a, AT_PAGESZ);
-        a += 8;
-        write_u64_at(a, 0x1000);
-        a += 8;
-        write_u64_at(a, AT_RANDOM);
-        a += 8;
-        write_u64_at(a, random_at);
-        a += 8;
-        write_u64_at(a, AT_NULL);
-        a += 8;
-        write_u64_at(a, 0);
-        let _ = a;
+        for (i, word) in auxv_words.iter().enumerate() {
+            write_u64_at(auxv_at + (i as u64) * 8, *word);
+        }
 
         // Copy phdr bytes onto the stack.
         let dst = phdr_at as *mut u8;
@@ -453,7 +528,10 @@ pub(crate) fn build_initial_stack(
         core::ptr::copy_nonoverlapping(at_random.as_ptr(), dst, at_random.len());
     }
 
-    argc_at
+    InitialStack {
+        rsp: argc_at,
+        auxv: auxv_words,
+    }
 }
 
 #[inline]
@@ -0,0 +1,388 @@
+//! `mount(2)` and `umount2(2)`.
+//!
+//! Sources name what gets attached:
+//!
+//! - `vfat`, `ext2`, or no type (autodetect): a virtio disk as
+//!   `/dev/vdX`, or one of its MBR partitions as `/dev/vdXN`. Disks are
+//!   lettered in PCI discovery order, the same names `/proc/mounts` shows
+//!   for the boot mounts.
+//! - `tmpfs`: a fresh RAM filesystem; the source is only a label.
+//! - `9p`: the virtio-9p mount tag of a host share.
+//!
+//! Options are `ro`/`rw` (also `MS_RDONLY`), `mode=` for the root of a
+//! tmpfs, and the 9p `trans=virtio`/`version=9p2000.L` pair, which is the
+//! only transport there is. `size=` is accepted but not enforced.
+//! `MS_REMOUNT` flips a mount between `ro` and `rw`. Access-time,
+//! `nosuid`, `nodev` and `noexec` flags are accepted and ignored; bind,
+//! move and propagation changes fail with `EINVAL`.
+//!
+//! Both calls need an effective uid of 0 and act on the caller's mount
+//! namespace. A mount with open files, with a process working or root
+//! directory inside it, or with another mount below it is busy;
+//! `MNT_DETACH` skips the open-file and directory checks: the mount leaves
+//! the tree at once, but its filesystem and disk stay in use until the last
+//! open file closes, and the source cannot be mounted again before then.
+//! An unmounted filesystem is freed together with its device handles, see
+//! [`crate::fs::vfs::unmount`].
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::drivers::block::BlockDevice;
+use crate::fs::filesystem::{FileType, FilesystemError, FilesystemType};
+use crate::userland::abi::{EBUSY, EINVAL, ENODEV, ENOENT, ENOTBLK, ENOTDIR, EPERM};
+use crate::userland::path::copy_user_cstr;
+use crate::userland::syscalls::{map_filesystem_err, resolve_user_path};
+use alloc::boxed::Box;
+use alloc::string::String;
+
+pub const MS_RDONLY: u64 = 1;
+pub const MS_NOSUID: u64 = 2;
+pub const MS_NODEV: u64 = 4;
+pub const MS_NOEXEC: u64 = 8;
+pub const MS_REMOUNT: u64 = 32;
+pub const MS_NOATIME: u64 = 1024;
+pub const MS_NODIRATIME: u64 = 2048;
+pub const MS_SILENT: u64 = 32768;
+pub const MS_RELATIME: u64 = 1 << 21;
+pub const MS_STRICTATIME: u64 = 1 << 24;
+pub const MS_LAZYTIME: u64 = 1 << 25;
+/// Pre-2.4 callers put this magic in the upper 16 bits of the flags.
+const MS_MGC_VAL: u64 = 0xc0ed_0000;
+const MS_MGC_MSK: u64 = 0xffff_0000;
+
+/// Flags that change nothing here beyond what the caller asked for.
+const MS_ACCEPTED: u64 = MS_RDONLY
+    | MS_NOSUID
+    | MS_NODEV
+    | MS_NOEXEC
+    | MS_REMOUNT
+    | MS_NOATIME
+    | MS_NODIRATIME
+    | MS_SILENT
+    | MS_RELATIME
+    | MS_STRICTATIME
+    | MS_LAZYTIME;
+
+pub const MNT_FORCE: u64 = 1;
+pub const MNT_DETACH: u64 = 2;
+pub const UMOUNT_NOFOLLOW: u64 = 8;
+
+/// Parsed `mountflags` plus the comma-separated data string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MountOptions {
+    read_only: bool,
+    remount: bool,
+    /// tmpfs root permission bits.
+    mode: Option<u32>,
+}
+
+impl MountOptions {
+    fn parse(flags: u64, data: &str) -> Result<Self, i64> {
+        let flags = if flags & MS_MGC_MSK == MS_MGC_VAL {
+            flags & !MS_MGC_MSK
+        } else {
+            flags
+        };
+        if flags & !MS_ACCEPTED != 0 {
+            return Err(EINVAL);
+        }
+        let mut options = Self {
+            read_only: flags & MS_RDONLY != 0,
+            remount: flags & MS_REMOUNT != 0,
+            mode: None,
+        };
+        for option in data.split(',').filter(|option| !option.is_empty()) {
+            match option.split_once('=') {
+                None if option == "ro" => options.read_only = true,
+                None if option == "rw" => options.read_only = false,
+                None if option == "defaults" => {}
+                Some(("mode", mode)) => {
+                    let mode = u32::from_str_radix(mode, 8).map_err(|_| EINVAL)?;
+                    if mode > 0o7777 {
+                        return Err(EINVAL);
+                    }
+                    options.mode = Some(mode);
+                }
+                Some(("size", _)) | Some(("trans", "virtio")) | Some(("version", "9p2000.L")) => {}
+                _ => return Err(EINVAL),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Split `/dev/vdX[N]` into a virtio disk index and an optional 1-based
+/// MBR partition number.
+fn parse_block_source(source: &str) -> Option<(usize, Option<usize>)> {
+    let rest = source.strip_prefix("/dev/vd")?;
+    let letter = *rest.as_bytes().first()?;
+    if !letter.is_ascii_lowercase() {
+        return None;
+    }
+    let partition = &rest[1..];
+    if partition.is_empty() {
+        return Some(((letter - b'a') as usize, None));
+    }
+    match partition.parse::<usize>() {
+        Ok(number @ 1..=4) if !partition.starts_with('0') => {
+            Some(((letter - b'a') as usize, Some(number)))
+        }
+        _ => None,
+    }
+}
+
+/// Linux type name for a detected on-disk filesystem.
+fn disk_fstype(detected: FilesystemType) -> Option<&'static str> {
+    match detected {
+        FilesystemType::Fat12 | FilesystemType::Fat16 | FilesystemType::Fat32 => Some("vfat"),
+        FilesystemType::Ext2 => Some("ext2"),
+        _ => None,
+    }
+}
+
+/// Attach a virtio disk or partition. The device handles are leaked only
+/// once the filesystem on them checks out, and are freed with it.
+fn mount_block(
+    source: &str,
+    fstype: Option<&str>,
+    target: &str,
+    read_only: bool,
+) -> Result<(), i64> {
+    use crate::drivers::virtio::block::VirtioBlockDevice;
+    use crate::fs::{detect_filesystem, read_partitions, PartitionBlockDevice};
+
+    let (index, partition) = parse_block_source(source).ok_or(ENOTBLK)?;
+    if crate::fs::vfs::source_in_use(source) {
+        return Err(EBUSY);
+    }
+    let disk = VirtioBlockDevice::by_index(index).ok_or(ENOENT)?;
+    let entry = match partition {
+        None => None,
+        Some(number) => {
+            let partitions = read_partitions(&disk).map_err(|_| EINVAL)?;
+            Some(partitions[number - 1].ok_or(ENOENT)?)
+        }
+    };
+    let detected = match &entry {
+        None => detect_filesystem(&disk),
+        Some(entry) => detect_filesystem(&PartitionBlockDevice::new(&disk, entry)),
+    }
+    .map_err(|e| map_filesystem_err(&e))?;
+    let found = disk_fstype(detected).ok_or(EINVAL)?;
+    if fstype.is_some_and(|wanted| wanted != "auto" && wanted != found) {
+        return Err(EINVAL);
+    }
+
+    let disk: &'static VirtioBlockDevice = Box::leak(Box::new(disk));
+    let partition: Option<&'static PartitionBlockDevice<'static>> = entry
+        .as_ref()
+        .map(|entry| &*Box::leak(Box::new(PartitionBlockDevice::new(disk, entry))));
+    let device: &'static dyn BlockDevice = match partition {
+        None => disk,
+        Some(partition) => partition,
+    };
+    let release: crate::fs::vfs::Release = Box::new(move || {
+        // SAFETY: both were leaked above, and the filesystem that borrowed
+        // them is gone or was never built.
+        unsafe {
+            if let Some(partition) = partition {
+                drop(Box::from_raw(
+                    partition as *const PartitionBlockDevice as *mut PartitionBlockDevice,
+                ));
+            }
+            drop(Box::from_raw(
+                disk as *const VirtioBlockDevice as *mut VirtioBlockDevice,
+            ));
+        }
+    });
+    let mounted = if read_only || device.is_read_only() {
+        crate::fs::vfs::auto_mount(device, source, target)
+    } else {
+        crate::fs::vfs::auto_mount_writable(device, source, target, false)
+    };
+    match mounted {
+        Ok(_) => {
+            crate::fs::vfs::attach_release(source, release);
+            Ok(())
+        }
+        Err(e) => {
+            release();
+            Err(map_filesystem_err(&e))
+        }
+    }
+}
+
+fn mount_share(tag: &str, target: &str, read_only: bool) -> Result<(), i64> {
+    if crate::fs::vfs::source_in_use(tag) {
+        return Err(EBUSY);
+    }
+    let transport = crate::drivers::virtio::p9::P9Transport::discover_by_tag(tag).ok_or(ENOENT)?;
+    let filesystem =
+        crate::fs::p9::P9Filesystem::new(transport).map_err(|e| map_filesystem_err(&e))?;
+    crate::fs::vfs::mount_p9(filesystem, tag, target, read_only).map_err(|e| map_filesystem_err(&e))
+}
+
+fn attach(
+    source: &str,
+    fstype: Option<&str>,
+    target: &str,
+    options: MountOptions,
+) -> Result<(), i64> {
+    match fstype {
+        Some("tmpfs") => {
+            let label = if source.is_empty() { "tmpfs" } else { source };
+            let filesystem = crate::fs::vfs::mount_tmpfs(label, target, options.read_only)
+                .map_err(|e| map_filesystem_err(&e))?;
+            if let Some(mode) = options.mode {
+                let _ = filesystem.set_mode("/", mode);
+            }
+            Ok(())
+        }
+        Some("9p") => mount_share(source, target, options.read_only),
+        None | Some("auto") | Some("vfat") | Some("ext2") => {
+            mount_block(source, fstype, target, options.read_only)
+        }
+        Some(_) => Err(ENODEV),
+    }
+}
+
+fn copy_optional_cstr(ptr: u64) -> Result<Option<String>, i64> {
+    if ptr == 0 {
+        return Ok(None);
+    }
+    copy_user_cstr(ptr).map(Some)
+}
+
+/// `mount(source, target, filesystemtype, mountflags, data)`.
+pub fn mount_handler(args: &mut SyscallArgs) -> i64 {
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    match mount_common(args) {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
+
+fn mount_common(args: &SyscallArgs) -> Result<(), i64> {
+    let target = resolve_user_path(args.rsi)?;
+    let data = copy_optional_cstr(args.r8)?.unwrap_or_default();
+    let options = MountOptions::parse(args.r10, &data)?;
+    if options.remount {
+        return crate::fs::vfs::get_vfs()
+            .remount(&target, options.read_only)
+            .map_err(|e| match e {
+                FilesystemError::InvalidPath => EINVAL,
+                e => map_filesystem_err(&e),
+            });
+    }
+    let source = copy_optional_cstr(args.rdi)?.unwrap_or_default();
+    let fstype = copy_optional_cstr(args.rdx)?;
+    match crate::fs::vfs::vfs_stat(&target) {
+        Ok(entry) if entry.file_type == FileType::Directory => {}
+        Ok(_) => return Err(ENOTDIR),
+        Err(e) => return Err(map_filesystem_err(&e)),
+    }
+    attach(&source, fstype.as_deref(), &target, options)
+}
+
+/// `umount2(target, flags)`.
+pub fn umount2_handler(args: &mut SyscallArgs) -> i64 {
+    let flags = args.rsi;
+    if flags & !(MNT_FORCE | MNT_DETACH | UMOUNT_NOFOLLOW) != 0 {
+        return EINVAL;
+    }
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    let target = match resolve_user_path(args.rdi) {
+        Ok(target) => target,
+        Err(e) => return e,
+    };
+    let detach = flags & MNT_DETACH != 0;
+    if !detach && crate::userland::lifecycle::any_cwd_or_root(|dir| is_within(dir, &target)) {
+        return EBUSY;
+    }
+    match crate::fs::vfs::unmount(&target, detach) {
+        Ok(()) => 0,
+        Err(FilesystemError::InvalidPath) => EINVAL,
+        Err(e) => map_filesystem_err(&e),
+    }
+}
+
+/// True when `path` is `dir` or lies below it.
+pub(crate) fn is_within(path: &str, dir: &str) -> bool {
+    dir == "/"
+        || path == dir
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn test_block_source_names() {
+        assert_eq!(parse_block_source("/dev/vda"), Some((0, None)));
+        assert_eq!(parse_block_source("/dev/vdc2"), Some((2, Some(2))));
+        assert_eq!(parse_block_source("/dev/vdb5"), None);
+        assert_eq!(parse_block_source("/dev/vdb0"), None);
+        assert_eq!(parse_block_source("/dev/vdb01"), None);
+        assert_eq!(parse_block_source("/dev/vdB"), None);
+        assert_eq!(parse_block_source("/dev/sda1"), None);
+        assert_eq!(parse_block_source("agenticos-data"), None);
+    }
+
+    fn test_options_parse() {
+        let plain = MountOptions::parse(0, "").unwrap();
+        assert!(!plain.read_only && !plain.remount && plain.mode.is_none());
+        assert!(MountOptions::parse(MS_RDONLY, "").unwrap().read_only);
+        assert!(MountOptions::parse(0, "defaults,ro").unwrap().read_only);
+        assert!(!MountOptions::parse(MS_RDONLY, "rw").unwrap().read_only);
+        assert_eq!(
+            MountOptions::parse(MS_NOSUID | MS_NODEV, "mode=1777,size=64m")
+                .unwrap()
+                .mode,
+            Some(0o1777)
+        );
+        assert!(
+            MountOptions::parse(MS_REMOUNT | MS_RDONLY, "")
+                .unwrap()
+                .remount
+        );
+        assert_eq!(MountOptions::parse(0, "mode=8"), Err(EINVAL));
+        assert_eq!(MountOptions::parse(0, "uid=0"), Err(EINVAL));
+        assert_eq!(MountOptions::parse(0, "trans=tcp"), Err(EINVAL));
+    }
+
+    fn test_flag_filtering() {
+        const MS_BIND: u64 = 4096;
+        const MS_PRIVATE: u64 = 1 << 18;
+        assert!(
+            MountOptions::parse(MS_MGC_VAL | MS_RDONLY, "")
+                .unwrap()
+                .read_only
+        );
+        assert_eq!(MountOptions::parse(MS_BIND, ""), Err(EINVAL));
+        assert_eq!(MountOptions::parse(MS_PRIVATE, ""), Err(EINVAL));
+    }
+
+    fn test_is_within() {
+        assert!(is_within("/mnt", "/mnt"));
+        assert!(is_within("/mnt/a", "/mnt"));
+        assert!(!is_within("/mnt2", "/mnt"));
+        assert!(is_within("/anything", "/"));
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_block_source_names,
+            &test_options_parse,
+            &test_flag_filtering,
+            &test_is_within,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as mount_tests;
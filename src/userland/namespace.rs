@@ -0,0 +1,229 @@
+//! `chroot(2)`, `pivot_root(2)` and `unshare(2)` for sandboxing.
+//!
+//! A process root is a kernel path kept next to its working directory and
+//! applied by [`crate::userland::path::resolve_path_following`] to every
+//! path the process names; `..` stops there, symlinks are expanded against
+//! it rather than the filesystem's own root, and the synthetic `/bin`,
+//! `/proc` and `/dev` trees stay reachable at their usual place. Unlike Linux, `chroot`
+//! also moves a working directory that lies outside the new root onto it,
+//! so the classic `chroot` without `chdir` is not an escape.
+//!
+//! `unshare(CLONE_NEWNS)` gives the caller a private copy of its mount
+//! table (see [`crate::fs::vfs::unshare_namespace`]); children inherit it,
+//! and later mounts on either side stay invisible to the other. Propagation
+//! is always private. `pivot_root` is only accepted inside such a namespace
+//! so the boot mount table is never rearranged, and only from an
+//! unconfined root.
+//!
+//! All three need an effective uid of 0.
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::fs::filesystem::{FileType, FilesystemError};
+use crate::userland::abi::{EBUSY, EINVAL, ENOTDIR, EPERM};
+use crate::userland::lifecycle::{current_tgid, current_user_pid, with_active_user};
+use crate::userland::mount::is_within;
+use crate::userland::syscalls::{map_filesystem_err, resolve_user_path};
+
+pub const CLONE_FS: u64 = 0x0000_0200;
+pub const CLONE_FILES: u64 = 0x0000_0400;
+pub const CLONE_NEWNS: u64 = 0x0002_0000;
+
+/// Fail with `ENOTDIR`/`ENOENT` unless `path` is a directory on a mount.
+fn require_directory(path: &str) -> Result<(), i64> {
+    if path == "/" {
+        return Ok(());
+    }
+    match crate::fs::vfs::vfs_stat(path) {
+        Ok(entry) if entry.file_type == FileType::Directory => Ok(()),
+        Ok(_) => Err(ENOTDIR),
+        Err(e) => Err(map_filesystem_err(&e)),
+    }
+}
+
+/// `chroot(path)`.
+pub fn chroot_handler(args: &mut SyscallArgs) -> i64 {
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    let root = match resolve_user_path(args.rdi) {
+        Ok(root) => root,
+        Err(e) => return e,
+    };
+    if let Err(e) = require_directory(&root) {
+        return e;
+    }
+    with_active_user(|process| {
+        if !is_within(&process.cwd, &root) {
+            process.cwd = root.clone();
+        }
+        process.root = root;
+    });
+    0
+}
+
+/// `unshare(flags)`. Only `CLONE_NEWNS` does anything: the file table and
+/// filesystem context (`CLONE_FILES`, `CLONE_FS`) are already private to
+/// a single-threaded process, and every other namespace kind is refused.
+pub fn unshare_handler(args: &mut SyscallArgs) -> i64 {
+    let flags = args.rdi;
+    if flags & !(CLONE_FS | CLONE_FILES | CLONE_NEWNS) != 0 {
+        return EINVAL;
+    }
+    let tgid = current_tgid();
+    // Threads share the group's root and working directory, so none of
+    // them can take a private copy.
+    if flags != 0 && crate::userland::lifecycle::group_member_count(tgid) > 1 {
+        return EINVAL;
+    }
+    if flags & CLONE_NEWNS == 0 {
+        return 0;
+    }
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    let Some(tid) = current_user_pid() else {
+        return EINVAL;
+    };
+    crate::fs::vfs::unshare_namespace(tid);
+    0
+}
+
+/// `pivot_root(new_root, put_old)`. `new_root` must be a mount point and
+/// `put_old` a directory strictly below it. Afterwards every mount of the
+/// namespace, and the working and root directories of the processes in
+/// it, are renamed as [`crate::fs::vfs::pivoted_path`] describes.
+pub fn pivot_root_handler(args: &mut SyscallArgs) -> i64 {
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    match pivot_root_common(args) {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
+
+fn pivot_root_common(args: &SyscallArgs) -> Result<(), i64> {
+    let new_root = resolve_user_path(args.rdi)?;
+    let put_old = resolve_user_path(args.rsi)?;
+    let root = with_active_user(|process| process.root.clone());
+    if !root.is_empty() && root != "/" {
+        return Err(EINVAL);
+    }
+    require_directory(&new_root)?;
+    require_directory(&put_old)?;
+    let namespace = {
+        let mut vfs = crate::fs::vfs::get_vfs();
+        if vfs.namespace() == crate::fs::vfs::INITIAL_NAMESPACE {
+            return Err(EINVAL);
+        }
+        vfs.pivot(&new_root, &put_old).map_err(|e| match e {
+            FilesystemError::InvalidPath => EINVAL,
+            FilesystemError::Busy => EBUSY,
+            e => map_filesystem_err(&e),
+        })?;
+        vfs.namespace()
+    };
+    let rename = |path: &str| crate::fs::vfs::pivoted_path(&new_root, &put_old, path);
+    for tid in crate::fs::vfs::namespace_members(namespace) {
+        crate::userland::lifecycle::with_group(tid, |process| {
+            if !process.cwd.is_empty() {
+                process.cwd = rename(&process.cwd);
+            }
+            if !process.root.is_empty() {
+                process.root = rename(&process.root);
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+    use crate::fs::vfs::{pivoted_path, VirtualFilesystem};
+
+    fn test_pivoted_paths() {
+        assert_eq!(pivoted_path("/new", "/new/old", "/new"), "/");
+        assert_eq!(pivoted_path("/new", "/new/old", "/new/usr/lib"), "/usr/lib");
+        assert_eq!(pivoted_path("/new", "/new/old", "/"), "/old");
+        assert_eq!(pivoted_path("/new", "/new/old", "/data/x"), "/old/data/x");
+        assert_eq!(pivoted_path("/new", "/new/old", "/newer"), "/old/newer");
+    }
+
+    fn test_pivot_renames_mounts() {
+        // A private table, so the live mounts stay untouched.
+        let mut table = VirtualFilesystem::new();
+        let filesystem: &'static dyn crate::fs::filesystem::Filesystem =
+            alloc::boxed::Box::leak(alloc::boxed::Box::new(crate::fs::tmpfs::Tmpfs::new()));
+        for path in ["/", "/jail", "/jail/proc-data", "/data"] {
+            table
+                .mount(path, "tmpfs", "tmpfs", filesystem, false)
+                .unwrap();
+        }
+        assert_eq!(
+            table.pivot("/data/none", "/data/none/old"),
+            Err(FilesystemError::InvalidPath)
+        );
+        assert_eq!(
+            table.pivot("/jail", "/jail"),
+            Err(FilesystemError::InvalidPath)
+        );
+        assert_eq!(
+            table.pivot("/jail", "/elsewhere"),
+            Err(FilesystemError::InvalidPath)
+        );
+        table.pivot("/jail", "/jail/old").unwrap();
+        let paths: alloc::vec::Vec<&str> = table
+            .list_mounts()
+            .map(|mount| mount.path.as_str())
+            .collect();
+        assert_eq!(paths, ["/old", "/", "/proc-data", "/old/data"]);
+    }
+
+    fn test_unshare_rejects_other_namespaces() {
+        const CLONE_NEWPID: u64 = 0x2000_0000;
+        let mut args = SyscallArgs {
+            rdi: CLONE_NEWPID,
+            ..SyscallArgs::default()
+        };
+        assert_eq!(unshare_handler(&mut args), EINVAL);
+        args.rdi = 0;
+        assert_eq!(unshare_handler(&mut args), 0);
+    }
+
+    fn test_namespace_membership() {
+        // Synthetic tids well above anything the test kernel allocates.
+        let (parent, child) = (0x7fff_0001, 0x7fff_0002);
+        let id = crate::fs::vfs::unshare_namespace(parent);
+        assert_ne!(id, crate::fs::vfs::INITIAL_NAMESPACE);
+        assert_eq!(crate::fs::vfs::get_vfs_of(Some(parent)).namespace(), id);
+        crate::fs::vfs::inherit_namespace(parent, child);
+        assert_eq!(crate::fs::vfs::namespace_members(id), [parent, child]);
+        let before = crate::fs::vfs::get_vfs().list_mounts().count();
+        assert_eq!(
+            crate::fs::vfs::get_vfs_of(Some(child))
+                .list_mounts()
+                .count(),
+            before
+        );
+        crate::fs::vfs::leave_namespace(parent);
+        crate::fs::vfs::leave_namespace(child);
+        assert!(crate::fs::vfs::namespace_members(id).is_empty());
+        assert_eq!(
+            crate::fs::vfs::get_vfs_of(Some(child)).namespace(),
+            crate::fs::vfs::INITIAL_NAMESPACE
+        );
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_pivoted_paths,
+            &test_pivot_renames_mounts,
+            &test_unshare_rejects_other_namespaces,
+            &test_namespace_membership,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as namespace_tests;
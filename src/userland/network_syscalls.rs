@@ -1,14 +1,20 @@
 //! Linux x86-64 IPv4 socket syscall subset.
+//!
+//! AF_UNIX descriptors are handed to `crate::userland::unix_socket`, which
+//! shares the message-header and option helpers below.
 
 use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::lib::arc::Arc;
 use crate::net::abi::SockAddrV4;
 use crate::net::socket::{self, SocketError, SocketOption, SocketType};
 use crate::userland::abi::*;
 use crate::userland::fdtable::{FdSlot, FdTable, FD_TABLE_SIZE};
+use crate::userland::unix_socket::{self, UnixSocket};
 
+const AF_UNIX: i32 = 1;
 const AF_INET: i32 = 2;
 const SOCK_STREAM: i32 = 1;
 const SOCK_DGRAM: i32 = 2;
@@ -28,7 +34,7 @@ const IPPROTO_IP: i32 = 0;
 const IP_TTL: i32 = 2;
 const TCP_NODELAY: i32 = 1;
 const MSG_DONTWAIT: i32 = 0x40;
-const IO_MAX: usize = 64 * 1024;
+pub(crate) const IO_MAX: usize = 64 * 1024;
 const IOV_MAX: usize = 16;
 
 #[repr(C)]
@@ -42,29 +48,29 @@ struct LinuxSockAddrIn {
 
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
-struct LinuxTimeval {
+pub(crate) struct LinuxTimeval {
     seconds: i64,
     microseconds: i64,
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
-struct LinuxIovec {
-    base: u64,
-    len: u64,
+pub(crate) struct LinuxIovec {
+    pub(crate) base: u64,
+    pub(crate) len: u64,
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
-struct LinuxMsghdr {
-    name: u64,
-    name_len: u32,
+pub(crate) struct LinuxMsghdr {
+    pub(crate) name: u64,
+    pub(crate) name_len: u32,
     _name_pad: u32,
-    iov: u64,
-    iov_len: u64,
-    control: u64,
-    control_len: u64,
-    flags: i32,
+    pub(crate) iov: u64,
+    pub(crate) iov_len: u64,
+    pub(crate) control: u64,
+    pub(crate) control_len: u64,
+    pub(crate) flags: i32,
     _flags_pad: u32,
 }
 
@@ -79,6 +85,13 @@ fn with_fd_table_mut<R>(f: impl FnOnce(&mut FdTable) -> R) -> R {
     crate::userland::lifecycle::with_active_user(|process| f(&mut process.fd_table))
 }
 
+fn local_socket(fd: i32) -> Option<Arc<UnixSocket>> {
+    match with_fd_slot(fd) {
+        Some(FdSlot::UnixSocket { handle, .. }) => Some(handle),
+        _ => None,
+    }
+}
+
 fn socket_id(fd: i32) -> Result<u64, i64> {
     match with_fd_slot(fd) {
         Some(FdSlot::Socket { handle, .. }) => Ok(handle.id()),
@@ -194,6 +207,9 @@ pub fn socket_handler(args: &mut SyscallArgs) -> i64 {
     let domain = args.rdi as i32;
     let raw_type = args.rsi as i32;
     let protocol = args.rdx as i32;
+    if domain == AF_UNIX {
+        return unix_socket::socket(raw_type, protocol);
+    }
     if domain != AF_INET {
         return EAFNOSUPPORT;
     }
@@ -228,6 +244,9 @@ pub fn socket_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn bind_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::bind(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -240,6 +259,9 @@ pub fn bind_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn connect_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::connect(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -271,6 +293,9 @@ pub fn connect_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn listen_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::listen(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -281,6 +306,9 @@ pub fn listen_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 fn accept_common(args: &SyscallArgs, flags: i32) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::accept(args, &handle, flags);
+    }
     if flags & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
         return EINVAL;
     }
@@ -364,6 +392,9 @@ pub fn write_connected(args: &SyscallArgs, id: u64, data: &[u8]) -> i64 {
 }
 
 pub fn sendto_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::sendto(args, &handle);
+    }
     let length = args.rdx as usize;
     if length > IO_MAX {
         return EMSGSIZE;
@@ -427,6 +458,9 @@ pub fn read_connected(args: &SyscallArgs, id: u64, pointer: u64, capacity: usize
 }
 
 pub fn recvfrom_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::recvfrom(args, &handle);
+    }
     if let Err(error) = validate_sockaddr_output(args.r8, args.r9) {
         return error;
     }
@@ -445,7 +479,7 @@ pub fn recvfrom_handler(args: &mut SyscallArgs) -> i64 {
     finish(data.len() as i64)
 }
 
-fn read_iovecs(pointer: u64, count: u64) -> Result<Vec<LinuxIovec>, i64> {
+pub(crate) fn read_iovecs(pointer: u64, count: u64) -> Result<Vec<LinuxIovec>, i64> {
     if count as usize > IOV_MAX {
         return Err(EINVAL);
     }
@@ -465,7 +499,36 @@ fn read_iovecs(pointer: u64, count: u64) -> Result<Vec<LinuxIovec>, i64> {
     Ok(iovecs)
 }
 
+/// Copy the bytes named by `iovecs` in from user memory.
+pub(crate) fn gather_iovecs(iovecs: &[LinuxIovec]) -> Result<Vec<u8>, i64> {
+    let total = iovecs.iter().map(|iov| iov.len as usize).sum();
+    let mut data = Vec::with_capacity(total);
+    for iovec in iovecs {
+        let start = data.len();
+        data.resize(start + iovec.len as usize, 0);
+        crate::userland::usercopy::copy_from_user(&mut data[start..], iovec.base)?;
+    }
+    Ok(data)
+}
+
+/// Copy `data` out across `iovecs`, returning the bytes written.
+pub(crate) fn scatter_iovecs(iovecs: &[LinuxIovec], data: &[u8]) -> Result<usize, i64> {
+    let mut copied = 0usize;
+    for iovec in iovecs {
+        let length = (data.len() - copied).min(iovec.len as usize);
+        if length == 0 {
+            break;
+        }
+        crate::userland::usercopy::copy_to_user(iovec.base, &data[copied..copied + length])?;
+        copied += length;
+    }
+    Ok(copied)
+}
+
 pub fn sendmsg_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::sendmsg(args, &handle);
+    }
     let message = match crate::userland::usercopy::read_unaligned::<LinuxMsghdr>(args.rsi) {
         Ok(value) => value,
         Err(e) => return e,
@@ -477,17 +540,10 @@ pub fn sendmsg_handler(args: &mut SyscallArgs) -> i64 {
         Ok(value) => value,
         Err(e) => return e,
     };
-    let total = iovecs.iter().map(|iov| iov.len as usize).sum();
-    let mut data = Vec::with_capacity(total);
-    for iovec in iovecs {
-        let start = data.len();
-        data.resize(start + iovec.len as usize, 0);
-        if let Err(error) =
-            crate::userland::usercopy::copy_from_user(&mut data[start..], iovec.base)
-        {
-            return error;
-        }
-    }
+    let data = match gather_iovecs(&iovecs) {
+        Ok(value) => value,
+        Err(e) => return e,
+    };
     let destination = if message.name == 0 {
         None
     } else {
@@ -500,6 +556,9 @@ pub fn sendmsg_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn recvmsg_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::recvmsg(args, &handle);
+    }
     let mut message = match crate::userland::usercopy::read_unaligned::<LinuxMsghdr>(args.rsi) {
         Ok(value) => value,
         Err(e) => return e,
@@ -516,19 +575,10 @@ pub fn recvmsg_handler(args: &mut SyscallArgs) -> i64 {
         Ok(value) => value,
         Err(e) => return e,
     };
-    let mut copied = 0usize;
-    for iovec in iovecs {
-        let length = (data.len() - copied).min(iovec.len as usize);
-        if length == 0 {
-            break;
-        }
-        if let Err(error) =
-            crate::userland::usercopy::copy_to_user(iovec.base, &data[copied..copied + length])
-        {
-            return error;
-        }
-        copied += length;
-    }
+    let copied = match scatter_iovecs(&iovecs, &data) {
+        Ok(value) => value,
+        Err(e) => return e,
+    };
     if let Some(source) = source {
         if message.name != 0 {
             let length_pointer = args.rsi + 8;
@@ -546,6 +596,9 @@ pub fn recvmsg_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn getsockname_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::getsockname(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -558,6 +611,9 @@ pub fn getsockname_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn getpeername_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::getpeername(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -570,6 +626,9 @@ pub fn getpeername_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn shutdown_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::shutdown(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -577,7 +636,7 @@ pub fn shutdown_handler(args: &mut SyscallArgs) -> i64 {
     socket::shutdown(id, args.rsi as i32).map_or_else(map_socket_error, |_| finish(0))
 }
 
-fn timeout_ticks(value: LinuxTimeval) -> Result<Option<u64>, i64> {
+pub(crate) fn timeout_ticks(value: LinuxTimeval) -> Result<Option<u64>, i64> {
     if value.seconds < 0 || !(0..1_000_000).contains(&value.microseconds) {
         return Err(EINVAL);
     }
@@ -591,6 +650,9 @@ fn timeout_ticks(value: LinuxTimeval) -> Result<Option<u64>, i64> {
 }
 
 pub fn setsockopt_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::setsockopt(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -641,7 +703,7 @@ pub fn setsockopt_handler(args: &mut SyscallArgs) -> i64 {
     socket::set_option(id, option).map_or_else(map_socket_error, |_| 0)
 }
 
-fn timeval_from_ticks(ticks: Option<u64>) -> LinuxTimeval {
+pub(crate) fn timeval_from_ticks(ticks: Option<u64>) -> LinuxTimeval {
     let milliseconds = ticks.unwrap_or(0).saturating_mul(10);
     LinuxTimeval {
         seconds: (milliseconds / 1000) as i64,
@@ -649,7 +711,11 @@ fn timeval_from_ticks(ticks: Option<u64>) -> LinuxTimeval {
     }
 }
 
-fn write_option<T>(value_pointer: u64, length_pointer: u64, value: &T) -> Result<(), i64> {
+pub(crate) fn write_option<T>(
+    value_pointer: u64,
+    length_pointer: u64,
+    value: &T,
+) -> Result<(), i64> {
     let available = crate::userland::usercopy::read_unaligned::<u32>(length_pointer)? as usize;
     if available < core::mem::size_of::<T>() {
         return Err(EINVAL);
@@ -659,6 +725,9 @@ fn write_option<T>(value_pointer: u64, length_pointer: u64, value: &T) -> Result
 }
 
 pub fn getsockopt_handler(args: &mut SyscallArgs) -> i64 {
+    if let Some(handle) = local_socket(args.rdi as i32) {
+        return unix_socket::getsockopt(args, &handle);
+    }
     let id = match socket_id(args.rdi as i32) {
         Ok(id) => id,
         Err(e) => return e,
@@ -6,8 +6,14 @@
 //! - `normalize_path` — resolve a user-supplied path against the
 //!   process's current working directory. Handles `.` / `..` / repeated
 //!   slashes; the result always begins with `/`.
+//! - `resolve_path` / `view_path` — the same for a `chroot`ed process:
+//!   map its view of the tree onto kernel paths and back. The synthetic
+//!   `/bin`, `/proc` and `/dev` namespaces stay visible at their usual
+//!   place inside every root, as if bind-mounted there.
+//! - `resolve_path_following` — the same with every symlink expanded
+//!   inside the root, so a link cannot lead a confined process out of it.
 
-use crate::userland::abi::{EFAULT, ENAMETOOLONG};
+use crate::userland::abi::{EFAULT, ELOOP, ENAMETOOLONG};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
@@ -19,6 +25,10 @@ pub const PATH_MAX: usize = 4096;
 /// pick a smaller bound that's still comfortable for shells.
 pub const MAX_VECTOR_ENTRIES: usize = 256;
 
+/// Linux's `MAXSYMLINKS`: symlinks one lookup expands before it fails
+/// with `-ELOOP`.
+pub const MAX_SYMLINKS: usize = 40;
+
 /// Copy a NULL-terminated user array of C-string pointers (e.g.
 /// argv, envp) into a `Vec<String>`. Each pointer is read as a
 /// 64-bit word from user memory; the array terminates at the first
@@ -108,6 +118,132 @@ pub fn normalize_path(cwd: &str, path: &str) -> String {
     }
 }
 
+/// True for a root that confines nothing: `/`, or empty for tasks whose
+/// thread-group leader holds the real value.
+fn is_unconfined(root: &str) -> bool {
+    root.is_empty() || root == "/"
+}
+
+/// Kernel-synthesized trees shared by every root.
+fn is_synthetic(path: &str) -> bool {
+    crate::userland::procfs::is_proc_path(path)
+        || crate::userland::devfs::is_dev_path(path)
+        || crate::userland::bin_namespace::is_bin_dir(path)
+        || crate::userland::bin_namespace::apply_bin_rewrite(path).is_some()
+}
+
+/// Resolve `path` for a process confined to `root` whose working directory
+/// is `cwd`, both kernel paths, and return the kernel path it names. `..`
+/// stops at `root`. A working directory outside `root` (one inherited
+/// through `fchdir` on a descriptor opened before `chroot`) anchors
+/// relative paths at `root` instead, so it is no way out either.
+pub fn resolve_path(root: &str, cwd: &str, path: &str) -> String {
+    if is_unconfined(root) {
+        return normalize_path(cwd, path);
+    }
+    let cwd = view_path(root, cwd).unwrap_or_else(|| String::from("/"));
+    kernel_path(root, normalize_path(&cwd, path))
+}
+
+/// Kernel path of `view`, a normalized path as a process confined to
+/// `root` sees it.
+fn kernel_path(root: &str, view: String) -> String {
+    if view == "/" {
+        String::from(root)
+    } else if is_synthetic(&view) {
+        view
+    } else {
+        let mut out = String::with_capacity(root.len() + view.len());
+        out.push_str(root);
+        out.push_str(&view);
+        out
+    }
+}
+
+/// [`resolve_path`] with the symlinks along the way expanded inside
+/// `root`: an absolute target restarts at `root` and `..` in a target
+/// stops there, as in the path itself. Filesystems expand links against
+/// their own root, so left to them `ln -s / x` inside the jail would lead
+/// straight out. The final component is expanded only when `follow` is
+/// set, for the calls that act on the link itself. `read_link` maps a
+/// kernel path to its link target, `None` for anything but a symlink.
+///
+/// Unconfined lookups are returned as `resolve_path` leaves them, to be
+/// expanded by the filesystem as before.
+pub fn resolve_path_following(
+    root: &str,
+    cwd: &str,
+    path: &str,
+    follow: bool,
+    mut read_link: impl FnMut(&str) -> Option<String>,
+) -> Result<String, i64> {
+    if is_unconfined(root) {
+        return Ok(resolve_path(root, cwd, path));
+    }
+    let cwd = view_path(root, cwd).unwrap_or_else(|| String::from("/"));
+    let view = normalize_path(&cwd, path);
+    let mut pending: Vec<String> = view
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .rev()
+        .map(String::from)
+        .collect();
+    let mut resolved = String::new();
+    let mut expanded = 0;
+    while let Some(part) = pending.pop() {
+        match part.as_str() {
+            "" | "." => continue,
+            ".." => {
+                let parent = resolved.rfind('/').unwrap_or(0);
+                resolved.truncate(parent);
+                continue;
+            }
+            _ => {}
+        }
+        let before = resolved.len();
+        resolved.push('/');
+        resolved.push_str(&part);
+        if is_synthetic(&resolved) {
+            // Kernel-synthesized trees hold no filesystem links.
+            for rest in pending.iter().rev() {
+                resolved.push('/');
+                resolved.push_str(rest);
+            }
+            return Ok(kernel_path(root, normalize_path("/", &resolved)));
+        }
+        if pending.is_empty() && !follow {
+            break;
+        }
+        let Some(target) = read_link(&kernel_path(root, resolved.clone())) else {
+            continue;
+        };
+        expanded += 1;
+        if expanded > MAX_SYMLINKS {
+            return Err(ELOOP);
+        }
+        resolved.truncate(if target.starts_with('/') { 0 } else { before });
+        pending.extend(target.split('/').rev().map(String::from));
+    }
+    if resolved.is_empty() {
+        resolved.push('/');
+    }
+    Ok(kernel_path(root, resolved))
+}
+
+/// The inverse of [`resolve_path`]: kernel path `path` as a process
+/// confined to `root` sees it, or `None` when it lies outside that root.
+pub fn view_path(root: &str, path: &str) -> Option<String> {
+    if is_unconfined(root) {
+        return Some(path.to_string());
+    }
+    match path.strip_prefix(root) {
+        Some("") => Some(String::from("/")),
+        Some(rest) if rest.starts_with('/') => Some(rest.to_string()),
+        _ if is_synthetic(path) => Some(path.to_string()),
+        _ => None,
+    }
+}
+
 #[cfg(feature = "test")]
 mod tests_internal {
     use super::*;
@@ -138,6 +274,74 @@ mod tests_internal {
         assert_eq!(normalized, "/etc/shadow");
     }
 
+    pub fn test_resolve_confined_to_root() {
+        assert_eq!(resolve_path("/", "/host", "a"), "/host/a");
+        assert_eq!(resolve_path("/jail", "/jail/home", "x"), "/jail/home/x");
+        assert_eq!(
+            resolve_path("/jail", "/jail", "../../etc/passwd"),
+            "/jail/etc/passwd"
+        );
+        assert_eq!(resolve_path("/jail", "/jail/a", "/.."), "/jail");
+        // A working directory outside the root cannot be used to escape.
+        assert_eq!(resolve_path("/jail", "/host", "../etc"), "/jail/etc");
+    }
+
+    pub fn test_resolve_keeps_synthetic_namespaces() {
+        assert_eq!(
+            resolve_path("/jail", "/jail", "/proc/self/stat"),
+            "/proc/self/stat"
+        );
+        assert_eq!(resolve_path("/jail", "/jail", "/dev/null"), "/dev/null");
+        assert_eq!(resolve_path("/jail", "/jail", "/bin"), "/bin");
+        assert_eq!(resolve_path("/jail", "/proc", "self"), "/proc/self");
+        // /dev/shm is an ordinary mount, so the jail gets its own.
+        assert_eq!(
+            resolve_path("/jail", "/jail", "/dev/shm/x"),
+            "/jail/dev/shm/x"
+        );
+    }
+
+    pub fn test_view_path_round_trips() {
+        assert_eq!(view_path("/jail", "/jail").as_deref(), Some("/"));
+        assert_eq!(view_path("/jail", "/jail/a/b").as_deref(), Some("/a/b"));
+        assert_eq!(view_path("/jail", "/jailbreak"), None);
+        assert_eq!(view_path("/jail", "/host"), None);
+        assert_eq!(view_path("/jail", "/proc/1").as_deref(), Some("/proc/1"));
+        assert_eq!(view_path("", "/host").as_deref(), Some("/host"));
+    }
+
+    pub fn test_resolve_following_keeps_links_inside_root() {
+        let links = |path: &str| match path {
+            "/jail/tmp/root" => Some(String::from("/")),
+            "/jail/tmp/up" => Some(String::from("../../../..")),
+            "/jail/tmp/etc" => Some(String::from("/etc")),
+            "/jail/loop" => Some(String::from("loop")),
+            _ => None,
+        };
+        let resolve = |path: &str, follow: bool| {
+            resolve_path_following("/jail", "/jail", path, follow, links)
+        };
+        assert_eq!(
+            resolve("/tmp/root/etc/shadow", true),
+            Ok("/jail/etc/shadow".into())
+        );
+        assert_eq!(
+            resolve("/tmp/up/etc/shadow", true),
+            Ok("/jail/etc/shadow".into())
+        );
+        assert_eq!(resolve("/tmp/etc", true), Ok("/jail/etc".into()));
+        assert_eq!(resolve("/tmp/root", true), Ok("/jail".into()));
+        // The link itself, for lstat, unlink and readlink.
+        assert_eq!(resolve("/tmp/etc", false), Ok("/jail/tmp/etc".into()));
+        assert_eq!(resolve("/tmp/etc/x", false), Ok("/jail/etc/x".into()));
+        assert_eq!(resolve("/loop", true), Err(ELOOP));
+        // Unconfined lookups leave links to the filesystem.
+        assert_eq!(
+            resolve_path_following("/", "/", "/tmp/root", true, links),
+            Ok("/tmp/root".into())
+        );
+    }
+
     pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
         &[
             &test_normalize_absolute_keeps_path,
@@ -145,6 +349,10 @@ mod tests_internal {
             &test_normalize_collapses_redundancy,
             &test_normalize_root_idempotent,
             &test_etc_paths_normalize_into_runtime_namespace,
+            &test_resolve_confined_to_root,
+            &test_resolve_keeps_synthetic_namespaces,
+            &test_view_path_round_trips,
+            &test_resolve_following_keeps_links_inside_root,
         ]
     }
 }
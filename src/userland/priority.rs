@@ -0,0 +1,429 @@
+//! Nice levels (`getpriority(2)`, `setpriority(2)`) and real-time
+//! scheduling classes (`sched_setscheduler(2)` and friends).
+//!
+//! A task's nice value lives in its scheduler entity, where it selects the
+//! load weight that scales how fast the task accrues virtual runtime (see
+//! [`crate::process::scheduler`]). `PRIO_PROCESS` names one task by tid, as
+//! on Linux; `PRIO_PGRP` and `PRIO_USER` reach every task of the matching
+//! process groups or real uid. `fork` and `clone` inherit the caller's
+//! level and `execve` keeps it.
+//!
+//! x86-64 has no `nice` syscall: `nice(3)` is the C library's wrapper over
+//! these two, which is why `getpriority` returns `20 - nice` (1 to 40)
+//! rather than a possibly negative nice value.
+//!
+//! `SCHED_FIFO` and `SCHED_RR` take priorities 1 to 99 and run ahead of
+//! every `SCHED_OTHER` task, subject to the scheduler's real-time
+//! throttle. Entering a real-time class or raising a real-time priority
+//! takes an effective uid of 0; an owner may lower or leave one.
+//! `SCHED_RESET_ON_FORK` is honoured. `SCHED_BATCH`, `SCHED_IDLE` and
+//! `SCHED_DEADLINE` are refused with `EINVAL`.
+
+use alloc::vec::Vec;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::process::entity::{
+    EntityId, SchedPolicy, NICE_MAX, NICE_MIN, RT_PRIORITY_MAX, RT_PRIORITY_MIN,
+};
+use crate::process::scheduler::{RR_TIMESLICE_TICKS, SCHEDULER};
+use crate::userland::abi::{EACCES, EFAULT, EINVAL, ENOMEM, EPERM, ESRCH};
+use crate::userland::credentials::Credentials;
+use crate::userland::lifecycle::{ExitKind, KERNEL_PID, PROCESS_TABLE};
+
+pub const PRIO_PROCESS: u64 = 0;
+pub const PRIO_PGRP: u64 = 1;
+pub const PRIO_USER: u64 = 2;
+
+pub const SCHED_OTHER: u64 = 0;
+pub const SCHED_FIFO: u64 = 1;
+pub const SCHED_RR: u64 = 2;
+/// Flag or'd into a `sched_setscheduler` policy.
+pub const SCHED_RESET_ON_FORK: u64 = 0x4000_0000;
+
+/// Offset between a nice value and the `getpriority` return value.
+const PRIO_BIAS: i64 = 20;
+
+/// Nanoseconds per scheduler tick.
+const NANOS_PER_TICK: i64 = 10_000_000;
+
+/// Linux `struct sched_param`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SchedParam {
+    sched_priority: i32,
+}
+
+/// Whether `caller` may change `target`'s scheduling at all: root, or an
+/// effective uid matching the target's real or effective uid.
+fn may_reschedule(caller: &Credentials, target: &Credentials) -> bool {
+    caller.privileged()
+        || caller.user.effective == target.user.real
+        || caller.user.effective == target.user.effective
+}
+
+/// Whether `caller` may move `target` from nice `old` to `new`. Touching
+/// another user's task takes a matching effective uid, and lowering the
+/// value (raising priority) takes privilege, as without `CAP_SYS_NICE`.
+fn may_renice(caller: &Credentials, target: &Credentials, old: i8, new: i8) -> Result<(), i64> {
+    if !may_reschedule(caller, target) {
+        return Err(EPERM);
+    }
+    if new < old && !caller.privileged() {
+        return Err(EACCES);
+    }
+    Ok(())
+}
+
+/// The live tasks `which`/`who` selects, with their groups' credentials,
+/// and the caller's credentials.
+fn targets(which: u64, who: u32) -> Result<(Vec<(u32, Credentials)>, Credentials), i64> {
+    let caller_tid = crate::arch::x86_64::percpu::current_user_pid().unwrap_or(KERNEL_PID);
+    let g = PROCESS_TABLE.lock();
+    let leader_of = |tid: u32| {
+        let tgid = g.thread_groups.get(&tid).copied().unwrap_or(tid);
+        g.by_pid.get(&tgid)
+    };
+    let caller = leader_of(caller_tid).ok_or(ESRCH)?;
+    let caller_credentials = caller.credentials.clone();
+    let caller_pgid = caller.job.pgid;
+    let matches: &dyn Fn(u32, &crate::userland::lifecycle::Process) -> bool = match which {
+        PRIO_PROCESS => {
+            let tid = if who == 0 { caller_tid } else { who };
+            &move |task, _| task == tid
+        }
+        PRIO_PGRP => {
+            let pgid = if who == 0 { caller_pgid } else { who };
+            &move |_, leader| leader.job.pgid == pgid
+        }
+        PRIO_USER => {
+            let uid = if who == 0 {
+                caller_credentials.user.real
+            } else {
+                who
+            };
+            &move |_, leader| leader.credentials.user.real == uid
+        }
+        _ => return Err(EINVAL),
+    };
+    let tasks = g
+        .by_pid
+        .iter()
+        .filter(|(&tid, p)| tid != KERNEL_PID && p.exit_kind == ExitKind::None)
+        .filter_map(|(&tid, _)| {
+            let leader = leader_of(tid)?;
+            matches(tid, leader).then(|| (tid, leader.credentials.clone()))
+        })
+        .collect();
+    Ok((tasks, caller_credentials))
+}
+
+/// `getpriority(which, who) -> 20 - nice`, for the lowest nice value
+/// among the selected tasks.
+pub fn getpriority_handler(args: &mut SyscallArgs) -> i64 {
+    let (tasks, _) = match targets(args.rdi, args.rsi as u32) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let scheduler = SCHEDULER.lock();
+    tasks
+        .iter()
+        .map(|(tid, _)| scheduler.nice(EntityId::UserProcess(*tid)).unwrap_or(0))
+        .min()
+        .map_or(ESRCH, |nice| PRIO_BIAS - i64::from(nice))
+}
+
+/// `setpriority(which, who, nice) -> int`. Out-of-range values are
+/// clamped. As on Linux, a refusal for one task doesn't stop the others
+/// and is reported once all have been tried.
+pub fn setpriority_handler(args: &mut SyscallArgs) -> i64 {
+    let (tasks, caller) = match targets(args.rdi, args.rsi as u32) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let nice = (args.rdx as i32).clamp(i32::from(NICE_MIN), i32::from(NICE_MAX)) as i8;
+    let mut scheduler = SCHEDULER.lock();
+    let mut result = ESRCH;
+    for (tid, credentials) in tasks {
+        let id = EntityId::UserProcess(tid);
+        let old = scheduler.nice(id).unwrap_or(0);
+        let outcome = may_renice(&caller, &credentials, old, nice).and_then(|()| {
+            scheduler
+                .register_user(tid)
+                .and_then(|()| scheduler.set_nice(id, nice))
+                .map_err(|()| ENOMEM)
+        });
+        match outcome {
+            Ok(()) if result == ESRCH => result = 0,
+            Ok(()) => {}
+            Err(e) => result = e,
+        }
+    }
+    result
+}
+
+/// Class, real-time priority and reset-on-fork flag of one task.
+type Scheduling = (SchedPolicy, u8, bool);
+
+fn decode_policy(policy: u64) -> Result<(SchedPolicy, bool), i64> {
+    let class = match policy & !SCHED_RESET_ON_FORK {
+        SCHED_OTHER => SchedPolicy::Normal,
+        SCHED_FIFO => SchedPolicy::Fifo,
+        SCHED_RR => SchedPolicy::RoundRobin,
+        _ => return Err(EINVAL),
+    };
+    Ok((class, policy & SCHED_RESET_ON_FORK != 0))
+}
+
+pub fn encode_policy(class: SchedPolicy) -> u64 {
+    match class {
+        SchedPolicy::Normal => SCHED_OTHER,
+        SchedPolicy::Fifo => SCHED_FIFO,
+        SchedPolicy::RoundRobin => SCHED_RR,
+    }
+}
+
+/// Valid `sched_priority` values for `class`.
+fn priority_range(class: SchedPolicy) -> core::ops::RangeInclusive<u8> {
+    if class.is_realtime() {
+        RT_PRIORITY_MIN..=RT_PRIORITY_MAX
+    } else {
+        0..=0
+    }
+}
+
+/// Whether `caller` may take `target` from `old` to `new`. Entering a
+/// real-time class, raising a real-time priority or clearing
+/// reset-on-fork takes privilege, as without `CAP_SYS_NICE` and
+/// `RLIMIT_RTPRIO`.
+fn may_set_scheduling(
+    caller: &Credentials,
+    target: &Credentials,
+    old: Scheduling,
+    new: Scheduling,
+) -> Result<(), i64> {
+    if !may_reschedule(caller, target) {
+        return Err(EPERM);
+    }
+    let raising = new.0.is_realtime() && (!old.0.is_realtime() || new.1 > old.1);
+    if (raising || (old.2 && !new.2)) && !caller.privileged() {
+        return Err(EPERM);
+    }
+    Ok(())
+}
+
+/// The task `pid` names (0 for the caller), its group's credentials and
+/// the caller's credentials.
+fn task(pid: u64) -> Result<(u32, Credentials, Credentials), i64> {
+    let pid = pid as i32;
+    if pid < 0 {
+        return Err(EINVAL);
+    }
+    let (mut tasks, caller) = targets(PRIO_PROCESS, pid as u32)?;
+    let (tid, credentials) = tasks.pop().ok_or(ESRCH)?;
+    Ok((tid, credentials, caller))
+}
+
+fn scheduling_of(tid: u32) -> Scheduling {
+    let id = EntityId::UserProcess(tid);
+    let scheduler = SCHEDULER.lock();
+    let (class, priority) = scheduler.policy(id).unwrap_or((SchedPolicy::Normal, 0));
+    (
+        class,
+        priority,
+        scheduler.reset_on_fork(id).unwrap_or(false),
+    )
+}
+
+/// Shared body of `sched_setscheduler` and `sched_setparam`; `policy` is
+/// `None` for the latter, which keeps the class.
+fn set_scheduling(pid: u64, policy: Option<u64>, param: u64) -> Result<i64, i64> {
+    if param == 0 {
+        return Err(EINVAL);
+    }
+    let param: SchedParam = crate::userland::usercopy::read_unaligned(param).map_err(|_| EFAULT)?;
+    let requested = policy.map(decode_policy).transpose()?;
+    let (tid, target, caller) = task(pid)?;
+    let old = scheduling_of(tid);
+    let (class, reset_on_fork) = requested.unwrap_or((old.0, old.2));
+    let priority = u8::try_from(param.sched_priority)
+        .ok()
+        .filter(|priority| priority_range(class).contains(priority))
+        .ok_or(EINVAL)?;
+    may_set_scheduling(&caller, &target, old, (class, priority, reset_on_fork))?;
+    let id = EntityId::UserProcess(tid);
+    let mut scheduler = SCHEDULER.lock();
+    scheduler
+        .register_user(tid)
+        .and_then(|()| scheduler.set_policy(id, class, priority, reset_on_fork))
+        .map_err(|()| ENOMEM)?;
+    Ok(0)
+}
+
+/// `sched_setscheduler(pid, policy, param) -> int`
+pub fn sched_setscheduler_handler(args: &mut SyscallArgs) -> i64 {
+    set_scheduling(args.rdi, Some(args.rsi), args.rdx).unwrap_or_else(|e| e)
+}
+
+/// `sched_setparam(pid, param) -> int`
+pub fn sched_setparam_handler(args: &mut SyscallArgs) -> i64 {
+    set_scheduling(args.rdi, None, args.rsi).unwrap_or_else(|e| e)
+}
+
+/// `sched_getscheduler(pid) -> policy`, with `SCHED_RESET_ON_FORK` set
+/// when it is.
+pub fn sched_getscheduler_handler(args: &mut SyscallArgs) -> i64 {
+    task(args.rdi).map_or_else(
+        |e| e,
+        |(tid, _, _)| {
+            let (class, _, reset_on_fork) = scheduling_of(tid);
+            let flag = if reset_on_fork {
+                SCHED_RESET_ON_FORK
+            } else {
+                0
+            };
+            (encode_policy(class) | flag) as i64
+        },
+    )
+}
+
+/// `sched_getparam(pid, param) -> int`
+pub fn sched_getparam_handler(args: &mut SyscallArgs) -> i64 {
+    if args.rsi == 0 {
+        return EINVAL;
+    }
+    let (tid, _, _) = match task(args.rdi) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let param = SchedParam {
+        sched_priority: i32::from(scheduling_of(tid).1),
+    };
+    crate::userland::usercopy::write_unaligned(args.rsi, &param).map_or(EFAULT, |_| 0)
+}
+
+/// `sched_get_priority_max(policy) -> int`
+pub fn sched_get_priority_max_handler(args: &mut SyscallArgs) -> i64 {
+    match decode_policy(args.rdi) {
+        Ok((class, false)) => i64::from(*priority_range(class).end()),
+        _ => EINVAL,
+    }
+}
+
+/// `sched_get_priority_min(policy) -> int`
+pub fn sched_get_priority_min_handler(args: &mut SyscallArgs) -> i64 {
+    match decode_policy(args.rdi) {
+        Ok((class, false)) => i64::from(*priority_range(class).start()),
+        _ => EINVAL,
+    }
+}
+
+/// `sched_rr_get_interval(pid, tp) -> int`: the round-robin slice for a
+/// `SCHED_RR` task, zero for any other.
+pub fn sched_rr_get_interval_handler(args: &mut SyscallArgs) -> i64 {
+    let (tid, _, _) = match task(args.rdi) {
+        Ok(found) => found,
+        Err(e) => return e,
+    };
+    let ticks = match scheduling_of(tid).0 {
+        SchedPolicy::RoundRobin => RR_TIMESLICE_TICKS as i64,
+        SchedPolicy::Normal | SchedPolicy::Fifo => 0,
+    };
+    let nanos = ticks * NANOS_PER_TICK;
+    let interval = crate::userland::syscalls::LinuxTimespec {
+        tv_sec: nanos / 1_000_000_000,
+        tv_nsec: nanos % 1_000_000_000,
+    };
+    crate::userland::usercopy::write_unaligned(args.rsi, &interval).map_or(EFAULT, |_| 0)
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn user(uid: u32) -> Credentials {
+        let mut credentials = Credentials::default();
+        credentials.user.real = uid;
+        credentials.user.effective = uid;
+        credentials.user.saved = uid;
+        credentials.user.fs = uid;
+        credentials
+    }
+
+    fn test_unprivileged_may_only_lower_own_priority() {
+        let alice = user(1000);
+        assert_eq!(may_renice(&alice, &alice, 0, 5), Ok(()));
+        assert_eq!(may_renice(&alice, &alice, 5, 5), Ok(()));
+        assert_eq!(may_renice(&alice, &alice, 5, 0), Err(EACCES));
+        assert_eq!(may_renice(&alice, &user(1001), 0, 5), Err(EPERM));
+    }
+
+    fn test_root_may_renice_anyone_either_way() {
+        let root = user(0);
+        assert_eq!(may_renice(&root, &user(1000), 0, -20), Ok(()));
+        assert_eq!(may_renice(&root, &user(1000), -20, 19), Ok(()));
+    }
+
+    fn test_policy_encoding_round_trips() {
+        for policy in [SCHED_OTHER, SCHED_FIFO, SCHED_RR] {
+            let (class, reset) = decode_policy(policy | SCHED_RESET_ON_FORK).unwrap();
+            assert!(reset);
+            assert_eq!(encode_policy(class), policy);
+        }
+        assert_eq!(decode_policy(3), Err(EINVAL)); // SCHED_BATCH
+        assert_eq!(decode_policy(5), Err(EINVAL)); // SCHED_IDLE
+        assert_eq!(priority_range(SchedPolicy::Fifo), 1..=99);
+        assert_eq!(priority_range(SchedPolicy::Normal), 0..=0);
+    }
+
+    fn test_realtime_needs_privilege_to_rise_only() {
+        let alice = user(1000);
+        let normal = (SchedPolicy::Normal, 0, false);
+        let fifo_10 = (SchedPolicy::Fifo, 10, false);
+        let fifo_20 = (SchedPolicy::Fifo, 20, false);
+        assert_eq!(
+            may_set_scheduling(&alice, &alice, normal, fifo_10),
+            Err(EPERM)
+        );
+        assert_eq!(
+            may_set_scheduling(&alice, &alice, fifo_10, fifo_20),
+            Err(EPERM)
+        );
+        assert_eq!(may_set_scheduling(&alice, &alice, fifo_20, fifo_10), Ok(()));
+        assert_eq!(may_set_scheduling(&alice, &alice, fifo_20, normal), Ok(()));
+        assert_eq!(
+            may_set_scheduling(&alice, &alice, (SchedPolicy::Normal, 0, true), normal),
+            Err(EPERM)
+        );
+        assert_eq!(
+            may_set_scheduling(&user(0), &alice, normal, fifo_20),
+            Ok(())
+        );
+        assert_eq!(
+            may_set_scheduling(&alice, &user(1001), fifo_20, normal),
+            Err(EPERM)
+        );
+    }
+
+    fn test_weights_fall_with_nice() {
+        use crate::process::entity::{nice_to_weight, NICE_0_WEIGHT};
+        assert_eq!(nice_to_weight(0), NICE_0_WEIGHT);
+        assert_eq!(nice_to_weight(NICE_MIN - 1), nice_to_weight(NICE_MIN));
+        assert_eq!(nice_to_weight(NICE_MAX + 1), nice_to_weight(NICE_MAX));
+        for nice in NICE_MIN..NICE_MAX {
+            assert!(nice_to_weight(nice) > nice_to_weight(nice + 1));
+        }
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_unprivileged_may_only_lower_own_priority,
+            &test_root_may_renice_anyone_either_way,
+            &test_policy_encoding_round_trips,
+            &test_realtime_needs_privilege_to_rise_only,
+            &test_weights_fall_with_nice,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as priority_tests;
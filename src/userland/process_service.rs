@@ -239,6 +239,7 @@ fn service_main() {
         while adopt_one_orphan() {}
         while crate::userland::lifecycle::reap_one_dead_thread() {}
         while reap_one() {}
+        while crate::userland::coredump::write_one_pending() {}
 
         // Keep the service-state guard out of `process_request`: that path
         // publishes the PID by taking STATE again.
@@ -286,7 +287,9 @@ fn process_request(request: LaunchRequest) {
         &request.path,
         &argv_refs,
         &envp_refs,
-        request.terminal_id,
+        request
+            .terminal_id
+            .and_then(crate::terminal::pty::terminal_for_window),
     );
 
     match prepared {
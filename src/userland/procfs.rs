@@ -9,8 +9,10 @@
 //! Two tiers of files:
 //!
 //! - **Linux-shaped** (`uptime`, `meminfo`, `stat`, `loadavg`,
-//!   `net/dev`, `/proc/<pid>/{stat,status,cmdline,statm}`) — minimal
-//!   but well-formed subsets scoped to what BusyBox `ps`/`top` parse.
+//!   `mounts`, `filesystems`, `net/dev`,
+//!   `/proc/<pid>/{stat,status,cmdline,statm,mounts}`) — minimal but
+//!   well-formed subsets scoped to what BusyBox `ps`/`top`/`mount`/`df`
+//!   parse.
 //!   Only real ring-3 processes appear as `/proc/<pid>`; kernel
 //!   threads never masquerade with fake PIDs.
 //! - **AgenticOS extensions** (`/proc/agenticos/{kthreads,gui,
@@ -55,11 +57,18 @@ pub fn is_proc_path(path: &str) -> bool {
 }
 
 /// Names of the static top-level `/proc` files.
-const TOP_FILES: &[&str] = &["loadavg", "meminfo", "stat", "uptime"];
+const TOP_FILES: &[&str] = &[
+    "filesystems",
+    "loadavg",
+    "meminfo",
+    "mounts",
+    "stat",
+    "uptime",
+];
 /// Names of the `/proc/agenticos` extension files.
 const AGENTICOS_FILES: &[&str] = &["gui", "kthreads", "sockets"];
 /// Per-PID directory entries.
-const PID_FILES: &[&str] = &["cmdline", "stat", "statm", "status"];
+const PID_FILES: &[&str] = &["cmdline", "mounts", "stat", "statm", "status"];
 
 /// Resolve `/proc/self` to the calling process's PID and split `path`
 /// into components after `/proc`. Returns `None` for non-proc paths.
@@ -138,6 +147,8 @@ pub fn open_node(path: &str) -> Option<ProcNode> {
             "meminfo" => Some(ProcNode::File(gen_meminfo())),
             "stat" => Some(ProcNode::File(gen_stat())),
             "loadavg" => Some(ProcNode::File(gen_loadavg())),
+            "mounts" => Some(ProcNode::File(gen_mounts(None))),
+            "filesystems" => Some(ProcNode::File(gen_filesystems())),
             "net" => Some(ProcNode::Dir(alloc::vec![(String::from("dev"), false)])),
             "agenticos" => Some(ProcNode::Dir(
                 AGENTICOS_FILES
@@ -170,6 +181,7 @@ pub fn open_node(path: &str) -> Option<ProcNode> {
                     "status" => gen_pid_status(&snap),
                     "cmdline" => gen_pid_cmdline(&snap),
                     "statm" => gen_pid_statm(&snap),
+                    "mounts" => gen_mounts(Some(pid)),
                     _ => unreachable!(),
                 };
                 Some(ProcNode::File(content))
@@ -213,21 +225,36 @@ fn root_listing() -> Vec<(String, bool)> {
 pub struct Ring3Snapshot {
     pub pid: u32,
     pub ppid: u32,
-    /// Linux state char: `R`unning/runnable, `S`leeping, `Z`ombie.
+    pub pgrp: u32,
+    pub session: u32,
+    /// Linux state char: `R`unning/runnable, `S`leeping, s`T`opped,
+    /// `Z`ombie.
     pub state: char,
     /// Short command name (basename of argv[0], ≤ 15 chars).
     pub comm: String,
     /// Retained argv (possibly truncated at retention time).
     pub cmdline: Vec<String>,
     pub utime_ticks: u64,
+    /// The thread-group leader's nice value.
+    pub nice: i8,
+    /// The leader's `SCHED_*` policy and real-time priority.
+    pub policy: u64,
+    pub rt_priority: u8,
     /// Total VMA span in bytes (VSZ).
     pub vsize_bytes: u64,
     /// Resident 4 KiB pages (RSS).
     pub rss_pages: u64,
     pub threads: usize,
+    /// Real, effective, saved and filesystem uid.
+    pub uids: [u32; 4],
+    /// Real, effective, saved and filesystem gid.
+    pub gids: [u32; 4],
+    pub no_new_privs: bool,
+    /// `PR_GET_SECCOMP` mode.
+    pub seccomp_mode: u64,
 }
 
-fn comm_of(exe_path: &Option<String>, cmdline: &[String]) -> String {
+pub(crate) fn comm_of(exe_path: &Option<String>, cmdline: &[String]) -> String {
     let raw = cmdline
         .first()
         .map(String::as_str)
@@ -243,9 +270,13 @@ fn snapshot_one(
     run_state: Option<crate::process::entity::RunState>,
     threads: usize,
     utime_ticks: u64,
+    scheduling: (i8, crate::process::entity::SchedPolicy, u8),
 ) -> Ring3Snapshot {
+    let (nice, policy, rt_priority) = scheduling;
     let state = if p.exit_kind != ExitKind::None {
         'Z'
+    } else if p.job.stopped {
+        'T'
     } else if matches!(
         run_state,
         Some(crate::process::entity::RunState::Ready | crate::process::entity::RunState::Running)
@@ -276,13 +307,22 @@ fn snapshot_one(
     Ring3Snapshot {
         pid,
         ppid: p.parent_pid,
+        pgrp: p.job.pgid,
+        session: p.job.sid,
         state,
         comm: comm_of(&p.exe_path, &p.cmdline),
         cmdline: p.cmdline.clone(),
         utime_ticks,
+        nice,
+        policy: crate::userland::priority::encode_policy(policy),
+        rt_priority,
         vsize_bytes,
         rss_pages,
         threads,
+        uids: p.credentials.user.to_array(),
+        gids: p.credentials.group.to_array(),
+        no_new_privs: p.seccomp.no_new_privs,
+        seccomp_mode: p.seccomp.mode(),
     }
 }
 
@@ -319,7 +359,19 @@ pub fn ring3_snapshot(pid: u32) -> Option<Ring3Snapshot> {
         .iter()
         .filter_map(|tid| g.by_pid.get(tid))
         .fold(0u64, |sum, task| sum.saturating_add(task.utime_ticks));
-    Some(snapshot_one(pid, p, run_state, members.len(), utime_ticks))
+    let leader = crate::process::entity::EntityId::UserProcess(pid);
+    let (policy, rt_priority) = scheduler
+        .policy(leader)
+        .unwrap_or((crate::process::entity::SchedPolicy::Normal, 0));
+    let scheduling = (scheduler.nice(leader).unwrap_or(0), policy, rt_priority);
+    Some(snapshot_one(
+        pid,
+        p,
+        run_state,
+        members.len(),
+        utime_ticks,
+        scheduling,
+    ))
 }
 
 // ---------------------------------------------------------------
@@ -395,6 +447,66 @@ fn gen_meminfo() -> Vec<u8> {
     out.into_bytes()
 }
 
+/// `/proc/mounts`: `source target fstype options 0 0`, one line per mount
+/// in mount order. Only the VFS mount table appears; the synthetic `/proc`,
+/// `/dev` and `/bin` namespaces are not mounts.
+/// Mounts of `pid`'s namespace (the caller's for `None`) that lie inside
+/// its root, with targets as it sees them.
+fn gen_mounts(pid: Option<u32>) -> Vec<u8> {
+    let (tid, tgid) = match pid {
+        Some(pid) => (
+            Some(pid),
+            crate::userland::lifecycle::task_tgid(pid).unwrap_or(pid),
+        ),
+        None => (
+            crate::userland::lifecycle::current_user_pid(),
+            crate::userland::lifecycle::current_tgid(),
+        ),
+    };
+    let root = crate::userland::lifecycle::with_group(tgid, |p| p.root.clone()).unwrap_or_default();
+    let mut out = String::new();
+    for mount in crate::fs::vfs::get_vfs_of(tid).list_mounts() {
+        if let Some(target) = crate::userland::path::view_path(&root, &mount.path) {
+            out.push_str(&mount_line(
+                &mount.source,
+                &target,
+                mount.fstype,
+                mount.is_read_only(),
+            ));
+        }
+    }
+    out.into_bytes()
+}
+
+pub(crate) fn mount_line(source: &str, target: &str, fstype: &str, read_only: bool) -> String {
+    format!(
+        "{} {} {} {} 0 0\n",
+        escape_mount_field(if source.is_empty() { "none" } else { source }),
+        escape_mount_field(target),
+        fstype,
+        if read_only { "ro" } else { "rw" }
+    )
+}
+
+/// Octal-escape the separators `getmntent(3)` would otherwise split on.
+fn escape_mount_field(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            ' ' | '\t' | '\n' | '\\' => out.push_str(&format!("\\{:03o}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `/proc/filesystems`: the types `mount(2)` accepts, `nodev` marking the
+/// ones that need no block device. `overlay` is listed for the root mount
+/// even though it cannot be mounted by hand.
+fn gen_filesystems() -> Vec<u8> {
+    String::from("nodev\ttmpfs\nnodev\toverlay\nnodev\t9p\n\tvfat\n\text2\n").into_bytes()
+}
+
 fn gen_stat() -> Vec<u8> {
     let cpu_times = cpu_time_snapshots();
     let (user, system, idle) =
@@ -415,6 +527,10 @@ fn gen_stat() -> Vec<u8> {
             .count()
             .saturating_sub(1)
     };
+    let queue_depths: Vec<usize> = cpu_times
+        .iter()
+        .map(|(cpu, _)| crate::process::scheduler::queue_depth(*cpu))
+        .collect();
     let mut out = String::new();
     // Every local scheduling timer runs at 100 Hz, so these counters are
     // USER_HZ jiffies directly. Aggregate fields are derived from this exact
@@ -429,6 +545,13 @@ fn gen_stat() -> Vec<u8> {
             cpu, times.user, times.system, times.idle
         ));
     }
+    // Not in Linux: entities waiting on each CPU's ready queue, in cpuN
+    // row order.
+    out.push_str("runqueue");
+    for depth in queue_depths {
+        out.push_str(&format!(" {depth}"));
+    }
+    out.push('\n');
     out.push_str("btime 0\n");
     out.push_str(&format!("processes {}\n", processes));
     out.push_str("procs_running 1\n");
@@ -521,20 +644,35 @@ fn gen_sockets() -> Vec<u8> {
 fn gen_pid_stat(s: &Ring3Snapshot) -> Vec<u8> {
     // Linux /proc/<pid>/stat: 52 fields. Everything we don't track is
     // zero. Field map (1-based): 1 pid, 2 (comm), 3 state, 4 ppid,
-    // 14 utime, 18 priority, 19 nice, 20 num_threads, 23 vsize,
-    // 24 rss. BusyBox ps sscanf-parses through field 24.
-    let mut out = format!("{} ({}) {} {}", s.pid, s.comm, s.state, s.ppid);
-    // fields 5..=13: pgrp session tty_nr tpgid flags minflt cminflt
-    // majflt cmajflt
-    out.push_str(" 0 0 0 0 0 0 0 0 0");
+    // 5 pgrp, 6 session, 14 utime, 18 priority, 19 nice,
+    // 20 num_threads, 23 vsize, 24 rss, 40 rt_priority, 41 policy.
+    // BusyBox ps sscanf-parses through field 24.
+    let mut out = format!(
+        "{} ({}) {} {} {} {}",
+        s.pid, s.comm, s.state, s.ppid, s.pgrp, s.session
+    );
+    // fields 7..=13: tty_nr tpgid flags minflt cminflt majflt cmajflt
+    out.push_str(" 0 0 0 0 0 0 0");
     // 14 utime, 15 stime, 16 cutime, 17 cstime
     out.push_str(&format!(" {} 0 0 0", s.utime_ticks));
     // 18 priority, 19 nice, 20 num_threads, 21 itrealvalue, 22 starttime
-    out.push_str(&format!(" 20 0 {} 0 0", s.threads));
+    // Real-time tasks report -1 - rt_priority, as Linux does.
+    let priority = if s.rt_priority > 0 {
+        -1 - i32::from(s.rt_priority)
+    } else {
+        20 + i32::from(s.nice)
+    };
+    out.push_str(&format!(" {priority} {} {} 0 0", s.nice, s.threads));
     // 23 vsize (bytes), 24 rss (pages)
     out.push_str(&format!(" {} {}", s.vsize_bytes, s.rss_pages));
-    // 25..=52: rsslim … exit_code
-    for _ in 25..=52 {
+    // 25..=39: rsslim … processor
+    for _ in 25..=39 {
+        out.push_str(" 0");
+    }
+    // 40 rt_priority, 41 policy
+    out.push_str(&format!(" {} {}", s.rt_priority, s.policy));
+    // 42..=52: delayacct_blkio_ticks … exit_code
+    for _ in 42..=52 {
         out.push_str(" 0");
     }
     out.push('\n');
@@ -544,6 +682,7 @@ fn gen_pid_stat(s: &Ring3Snapshot) -> Vec<u8> {
 fn gen_pid_status(s: &Ring3Snapshot) -> Vec<u8> {
     let state_line = match s.state {
         'R' => "R (running)",
+        'T' => "T (stopped)",
         'Z' => "Z (zombie)",
         _ => "S (sleeping)",
     };
@@ -552,11 +691,14 @@ fn gen_pid_status(s: &Ring3Snapshot) -> Vec<u8> {
     out.push_str(&format!("State:\t{}\n", state_line));
     out.push_str(&format!("Pid:\t{}\n", s.pid));
     out.push_str(&format!("PPid:\t{}\n", s.ppid));
-    out.push_str("Uid:\t0\t0\t0\t0\n");
-    out.push_str("Gid:\t0\t0\t0\t0\n");
+    for (label, [real, effective, saved, fs]) in [("Uid", s.uids), ("Gid", s.gids)] {
+        out.push_str(&format!("{label}:\t{real}\t{effective}\t{saved}\t{fs}\n"));
+    }
     out.push_str(&format!("VmSize:\t{:>8} kB\n", s.vsize_bytes / 1024));
     out.push_str(&format!("VmRSS:\t{:>8} kB\n", s.rss_pages * 4));
     out.push_str(&format!("Threads:\t{}\n", s.threads));
+    out.push_str(&format!("NoNewPrivs:\t{}\n", u8::from(s.no_new_privs)));
+    out.push_str(&format!("Seccomp:\t{}\n", s.seccomp_mode));
     out.into_bytes()
 }
 
@@ -0,0 +1,951 @@
+//! A `ptrace` subset for ring-3 debuggers.
+//!
+//! Supported requests: `TRACEME`, `ATTACH`, `SEIZE`, `PEEKTEXT`/`PEEKDATA`,
+//! `POKETEXT`/`POKEDATA`, `GETREGS`, `SETREGS`, `SETOPTIONS` (only
+//! `PTRACE_O_TRACESYSGOOD`), `CONT`, `SYSCALL`, `SINGLESTEP` and `DETACH`.
+//!
+//! Tracing is per task; the tracer is a thread group, named by its leader's
+//! pid. A tracee in a ptrace stop is parked as [`Ring3BlockReason::Traced`]
+//! with its user state in `saved_user_state`, which is what `GETREGS` and
+//! `SETREGS` read and write. It stops:
+//!
+//! - for a signal: an actionable pending signal other than SIGKILL, seen at
+//!   a syscall boundary or a timer tick. Resuming with `data == 0`
+//!   discards it; a nonzero `data` injects that signal, which then takes
+//!   its normal course without stopping again. Signals the tracee ignores
+//!   never stop it.
+//! - at syscall entry and exit under `PTRACE_SYSCALL`. The entry stop is
+//!   taken before dispatch with RIP rewound onto the SYSCALL, so the call
+//!   re-fires with whatever registers the tracer left; `GETREGS` presents
+//!   it as Linux does, with `orig_rax` the syscall number, `rax` `-ENOSYS`
+//!   and `rip` after the instruction.
+//! - after one instruction under `PTRACE_SINGLESTEP`, through the #DB trap.
+//! - at the new image's entry point after `execve`, with SIGTRAP, unless
+//!   it was attached with `SEIZE`.
+//!
+//! The tracer's `wait4` reports each stop once as `WIFSTOPPED`, with
+//! `SIGTRAP | 0x80` for syscall stops under `PTRACE_O_TRACESYSGOOD`,
+//! whether or not the tracee is its child. Group stops, the tracee's exit
+//! and hardware faults are not reported to a tracer that is not also the
+//! parent: a fault still ends the tracee at once. Tracing is not inherited
+//! across `fork`, and a tracer's exit detaches its tracees.
+//!
+//! `ATTACH` and `SEIZE` need
+//! [`Credentials::may_ptrace`](crate::userland::credentials::Credentials::may_ptrace),
+//! which refuses set-user-ID programs to unprivileged tracers.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::arch::x86_64::interrupt_guard::InterruptMutex;
+use crate::arch::x86_64::preemption::InterruptStackFrame;
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::userland::abi::{EINTR, EINVAL, EIO, ENOSYS, EPERM, ESRCH};
+use crate::userland::lifecycle::{Ring3BlockReason, KERNEL_PID, PROCESS_TABLE};
+use crate::userland::signal::{
+    default_action_ignores, SignalState, NSIG, SIGKILL, SIGSTOP, SIGTRAP, SIG_DFL, SIG_IGN,
+};
+use crate::userland::user_state::{UserRegs, UserState};
+
+const PTRACE_TRACEME: u64 = 0;
+const PTRACE_PEEKTEXT: u64 = 1;
+const PTRACE_PEEKDATA: u64 = 2;
+const PTRACE_POKETEXT: u64 = 4;
+const PTRACE_POKEDATA: u64 = 5;
+const PTRACE_CONT: u64 = 7;
+const PTRACE_SINGLESTEP: u64 = 9;
+const PTRACE_GETREGS: u64 = 12;
+const PTRACE_SETREGS: u64 = 13;
+const PTRACE_ATTACH: u64 = 16;
+const PTRACE_DETACH: u64 = 17;
+const PTRACE_SYSCALL: u64 = 24;
+const PTRACE_SETOPTIONS: u64 = 0x4200;
+const PTRACE_SEIZE: u64 = 0x4206;
+
+const PTRACE_O_TRACESYSGOOD: u64 = 0x1;
+
+/// RFLAGS bits a tracer may change: CF, PF, AF, ZF, SF, TF, DF and OF.
+const USER_RFLAGS: u64 = 0xdd5;
+
+/// First address above the canonical lower half.
+const USER_ADDRESS_LIMIT: u64 = 0x0000_8000_0000_0000;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// How the tracer last resumed the tracee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resume {
+    Cont,
+    Syscall,
+    SingleStep,
+}
+
+/// Why a tracee is in a ptrace stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    Signal(i32),
+    SyscallEntry,
+    SyscallExit,
+    SingleStep,
+    Exec,
+}
+
+impl Stop {
+    /// The `wait4` status word for this stop.
+    fn status(self, options: u64) -> u32 {
+        let sig = match self {
+            Stop::Signal(sig) => sig as u32,
+            Stop::SyscallEntry | Stop::SyscallExit if options & PTRACE_O_TRACESYSGOOD != 0 => {
+                SIGTRAP as u32 | 0x80
+            }
+            Stop::SyscallEntry | Stop::SyscallExit | Stop::SingleStep | Stop::Exec => {
+                SIGTRAP as u32
+            }
+        };
+        ((sig & 0xff) << 8) | 0x7f
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tracee {
+    /// Thread group of the traced task, whose address space PEEK and POKE
+    /// use.
+    tgid: u32,
+    tracer: u32,
+    seized: bool,
+    options: u64,
+    resume: Resume,
+    stop: Option<Stop>,
+    /// The saved state of the current stop re-executes the SYSCALL.
+    refires: bool,
+    /// `wait4` has collected the current stop.
+    reported: bool,
+    /// Between a syscall-entry stop and its exit.
+    in_syscall: bool,
+    orig_rax: u64,
+    /// Signals injected by the tracer that must not stop the tracee again.
+    injected: u64,
+}
+
+impl Tracee {
+    fn new(tgid: u32, tracer: u32, seized: bool, options: u64) -> Self {
+        Self {
+            tgid,
+            tracer,
+            seized,
+            options,
+            resume: Resume::Cont,
+            stop: None,
+            refires: false,
+            reported: false,
+            in_syscall: false,
+            orig_rax: 0,
+            injected: 0,
+        }
+    }
+}
+
+/// Traced tasks by tid. Lock order: `TRACEES` before `PROCESS_TABLE`.
+static TRACEES: InterruptMutex<BTreeMap<u32, Tracee>> = InterruptMutex::new(BTreeMap::new());
+
+/// Held by `ATTACH`/`SEIZE` from the permission check to the insert, and by
+/// `execve` from its traced check to the credential change, so a tracer
+/// cannot slip in between and trace a freshly set-user-ID image.
+static CRED_GUARD: InterruptMutex<()> = InterruptMutex::new(());
+
+/// Number of entries in `TRACEES`, so untraced syscalls skip the lock.
+static TRACEE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The current task, if something traces it.
+fn current_tracee() -> Option<u32> {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return None;
+    }
+    let tid = crate::userland::lifecycle::current_user_pid().filter(|&tid| tid != KERNEL_PID)?;
+    TRACEES.lock().contains_key(&tid).then_some(tid)
+}
+
+/// Whether something traces the current task.
+pub fn current_is_traced() -> bool {
+    current_tracee().is_some()
+}
+
+/// Keep tracers from attaching while `execve` decides on and applies a
+/// set-id credential change.
+pub fn cred_guard() -> crate::arch::x86_64::interrupt_guard::InterruptMutexGuard<'static, ()> {
+    CRED_GUARD.lock()
+}
+
+/// Whether task `tid` is in a ptrace stop its tracer has not resumed.
+pub fn in_stop(tid: u32) -> bool {
+    TRACEES
+        .lock()
+        .get(&tid)
+        .is_some_and(|tracee| tracee.stop.is_some())
+}
+
+fn ignored(state: &SignalState, sig: i32) -> bool {
+    let action = state.action(sig).unwrap_or_default();
+    action.sa_handler == SIG_IGN || (action.sa_handler == SIG_DFL && default_action_ignores(sig))
+}
+
+/// The lowest pending signal a tracee stops for: deliverable now, not
+/// SIGKILL, not injected by the tracer and not ignored.
+fn reportable_signal(state: &SignalState, injected: u64) -> Option<i32> {
+    let unblockable = 1u64 << (SIGSTOP - 1);
+    let mut bits =
+        state.pending & (!state.blocked | unblockable) & !injected & !(1u64 << (SIGKILL - 1));
+    while bits != 0 {
+        let sig = bits.trailing_zeros() as i32 + 1;
+        bits &= !(1u64 << (sig - 1));
+        if !ignored(state, sig) {
+            return Some(sig);
+        }
+    }
+    None
+}
+
+/// Take the signal the traced task `tid` should stop for off its pending
+/// set.
+fn take_signal(tid: u32) -> Option<i32> {
+    let mut tracees = TRACEES.lock();
+    let tracee = tracees.get_mut(&tid)?;
+    crate::userland::lifecycle::with_process(tid, |process| {
+        // An injected signal stops being exempt once it has been taken.
+        tracee.injected &= process.signal_state.pending;
+        let sig = reportable_signal(&process.signal_state, tracee.injected)?;
+        process.signal_state.pending &= !(1u64 << (sig - 1));
+        Some(sig)
+    })
+    .flatten()
+}
+
+/// Enter `stop` in the current task `tid`, whose resume state is already
+/// saved, and tell the tracer. Returns only if the task stopped being
+/// traced meanwhile, after putting back a signal it took.
+fn stop(tid: u32, stop: Stop, refires: bool) {
+    let tracer = {
+        let mut tracees = TRACEES.lock();
+        tracees.get_mut(&tid).map(|tracee| {
+            tracee.stop = Some(stop);
+            tracee.refires = refires;
+            tracee.reported = false;
+            tracee.tracer
+        })
+    };
+    let Some(tracer) = tracer else {
+        if let Stop::Signal(sig) = stop {
+            crate::userland::lifecycle::with_current_process(|p| p.signal_state.raise(sig));
+        }
+        return;
+    };
+    crate::debug_trace!("USERLAND: tid={} ptrace stop {:?}", tid, stop);
+    crate::userland::job_control::notify_parent(tid, tracer);
+    unsafe { crate::userland::switch::stop_current_ring3_preempted(Ring3BlockReason::Traced) }
+}
+
+// ---------- stop points ----------
+
+/// Signal-delivery stop on the way out of a syscall that returned `ret`.
+pub fn signal_stop_after_syscall(args: &SyscallArgs, ret: i64) {
+    let Some(tid) = current_tracee() else {
+        return;
+    };
+    let Some(sig) = take_signal(tid) else {
+        return;
+    };
+    unsafe {
+        crate::userland::switch::save_current_ring3(crate::userland::switch::post_syscall_state(
+            args, ret as u64,
+        ));
+    }
+    stop(tid, Stop::Signal(sig), false);
+}
+
+/// Signal-delivery stop for a signal that interrupted a blocking syscall.
+/// Once resumed, the syscall re-fires.
+pub fn signal_stop_before_syscall(args: &SyscallArgs) {
+    let Some(tid) = current_tracee() else {
+        return;
+    };
+    let Some(sig) = take_signal(tid) else {
+        return;
+    };
+    unsafe {
+        crate::userland::switch::save_current_ring3(crate::userland::switch::refire_state(args));
+    }
+    stop(tid, Stop::Signal(sig), true);
+}
+
+/// Signal-delivery stop from the timer, after `save_ring3`.
+pub fn signal_stop_preempted() {
+    let Some(tid) = current_tracee() else {
+        return;
+    };
+    if let Some(sig) = take_signal(tid) {
+        stop(tid, Stop::Signal(sig), false);
+    }
+}
+
+/// Whether the current task's pending fatal signal must wait for its
+/// signal-delivery stop rather than kill it now. SIGKILL never waits.
+pub fn defers_fatal_signal() -> bool {
+    let Some(tid) = current_tracee() else {
+        return false;
+    };
+    let Some(injected) = TRACEES.lock().get(&tid).map(|tracee| tracee.injected) else {
+        return false;
+    };
+    crate::userland::lifecycle::with_process(tid, |process| {
+        !process.signal_state.is_pending(SIGKILL)
+            && reportable_signal(&process.signal_state, injected).is_some()
+    })
+    .unwrap_or(false)
+}
+
+/// Syscall-entry stop under `PTRACE_SYSCALL`. Taken once per syscall: the
+/// re-fire after the stop, or after the syscall blocks, passes through.
+pub fn syscall_entry_stop(args: &SyscallArgs) {
+    let Some(tid) = current_tracee() else {
+        return;
+    };
+    {
+        let mut tracees = TRACEES.lock();
+        let Some(tracee) = tracees.get_mut(&tid) else {
+            return;
+        };
+        if tracee.in_syscall || tracee.resume != Resume::Syscall {
+            return;
+        }
+        tracee.in_syscall = true;
+        tracee.orig_rax = args.rax;
+    }
+    unsafe {
+        crate::userland::switch::save_current_ring3(crate::userland::switch::refire_state(args));
+    }
+    stop(tid, Stop::SyscallEntry, true);
+}
+
+/// Close the current syscall and take its exit stop under
+/// `PTRACE_SYSCALL`. Once resumed, the syscall returns `ret`.
+pub fn syscall_exit_stop(args: &SyscallArgs, ret: i64) {
+    let Some(tid) = current_tracee() else {
+        return;
+    };
+    if !leave(tid) {
+        return;
+    }
+    unsafe {
+        crate::userland::switch::save_current_ring3(crate::userland::switch::post_syscall_state(
+            args, ret as u64,
+        ));
+    }
+    stop(tid, Stop::SyscallExit, false);
+}
+
+/// Close the current syscall without an exit stop, for the paths that
+/// return to ring 3 without passing the dispatcher's tail.
+pub fn leave_syscall() {
+    if let Some(tid) = current_tracee() {
+        leave(tid);
+    }
+}
+
+/// Clear `in_syscall` for `tid`. True when the syscall owes an exit stop.
+fn leave(tid: u32) -> bool {
+    TRACEES.lock().get_mut(&tid).is_some_and(|tracee| {
+        core::mem::take(&mut tracee.in_syscall) && tracee.resume == Resume::Syscall
+    })
+}
+
+/// Stop a tracee that has just replaced its image with `execve`, before
+/// the new program's first instruction at `state`.
+pub fn exec_stop(state: &UserState) {
+    let Some(tid) = current_tracee() else {
+        return;
+    };
+    let seized = TRACEES.lock().get(&tid).is_some_and(|tracee| tracee.seized);
+    let kind = if leave(tid) {
+        Stop::SyscallExit
+    } else if !seized {
+        Stop::Exec
+    } else {
+        return;
+    };
+    unsafe { crate::userland::switch::save_current_ring3(*state) };
+    stop(tid, kind, false);
+}
+
+/// #DB trap from ring 3 with `frame` holding the user registers. Ends a
+/// `PTRACE_SINGLESTEP`; any other trace trap raises SIGTRAP, as on Linux.
+pub(crate) fn single_step_trap(frame: &mut InterruptStackFrame) {
+    frame.rflags &= !crate::arch::x86_64::debug_trap::TRAP_FLAG;
+    let stepping = current_tracee().filter(|tid| {
+        TRACEES
+            .lock()
+            .get(tid)
+            .is_some_and(|tracee| tracee.resume == Resume::SingleStep)
+    });
+    let Some(tid) = stepping else {
+        crate::userland::lifecycle::with_current_process(|p| p.signal_state.raise(SIGTRAP));
+        return;
+    };
+    let saved = crate::userland::lifecycle::with_process(tid, |process| {
+        crate::userland::switch::save_ring3(process, frame);
+    });
+    if saved.is_some() {
+        stop(tid, Stop::SingleStep, false);
+    }
+}
+
+// ---------- wait4 and exit ----------
+
+/// Stopped tracees of `tracer` not yet reported, with their groups.
+fn unreported(tracer: u32) -> Vec<(u32, u32)> {
+    TRACEES
+        .lock()
+        .iter()
+        .filter(|(_, tracee)| tracee.tracer == tracer && tracee.stop.is_some() && !tracee.reported)
+        .map(|(&tid, tracee)| (tid, tracee.tgid))
+        .collect()
+}
+
+fn target_matches(tracer: u32, target: i32, tid: u32, tgid: u32) -> bool {
+    let pgid_of = |pid| crate::userland::lifecycle::with_group(pid, |p| p.job.pgid).unwrap_or(0);
+    crate::userland::job_control::wait_target_matches(target, tid, pgid_of(tgid), pgid_of(tracer))
+}
+
+/// Collect a stop report from a tracee of `tracer` selected by the `wait4`
+/// target `target`: the tracee's tid and the status word.
+pub fn take_report(tracer: u32, target: i32) -> Option<(u32, u32)> {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return None;
+    }
+    for (tid, tgid) in unreported(tracer) {
+        if !target_matches(tracer, target, tid, tgid) {
+            continue;
+        }
+        let mut tracees = TRACEES.lock();
+        let Some(tracee) = tracees.get_mut(&tid) else {
+            continue;
+        };
+        let Some(stop) = tracee.stop.filter(|_| !tracee.reported) else {
+            continue;
+        };
+        tracee.reported = true;
+        return Some((tid, stop.status(tracee.options)));
+    }
+    None
+}
+
+/// Whether `tracer` traces a task selected by the `wait4` target `target`.
+pub fn traces_matching(tracer: u32, target: i32) -> bool {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return false;
+    }
+    let traced: Vec<(u32, u32)> = TRACEES
+        .lock()
+        .iter()
+        .filter(|(_, tracee)| tracee.tracer == tracer)
+        .map(|(&tid, tracee)| (tid, tracee.tgid))
+        .collect();
+    traced
+        .into_iter()
+        .any(|(tid, tgid)| target_matches(tracer, target, tid, tgid))
+}
+
+fn remove(tracees: &mut BTreeMap<u32, Tracee>, tid: u32) -> Option<Tracee> {
+    let removed = tracees.remove(&tid);
+    if removed.is_some() {
+        TRACEE_COUNT.fetch_sub(1, Ordering::Release);
+    }
+    removed
+}
+
+/// Task `tid` is exiting: stop tracing it and let its tracer's `wait4`
+/// look again.
+pub fn exit_task(tid: u32) {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let removed = remove(&mut TRACEES.lock(), tid);
+    if let Some(tracee) = removed {
+        crate::userland::lifecycle::wake_ring3_blocked_on_child(tracee.tracer, tid);
+    }
+}
+
+/// Thread group `tgid` is exiting: stop tracing its tasks, and detach the
+/// tasks it traces, resuming any left in a ptrace stop.
+pub fn exit_group(tgid: u32) {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let (gone, released) = {
+        let mut tracees = TRACEES.lock();
+        let tids: Vec<u32> = tracees
+            .iter()
+            .filter(|(_, tracee)| tracee.tgid == tgid || tracee.tracer == tgid)
+            .map(|(&tid, _)| tid)
+            .collect();
+        let mut gone = Vec::new();
+        let mut released = Vec::new();
+        for tid in tids {
+            let Some(tracee) = remove(&mut tracees, tid) else {
+                continue;
+            };
+            if tracee.tgid == tgid {
+                gone.push((tid, tracee.tracer));
+            } else {
+                released.push((tid, tracee.resume));
+            }
+        }
+        (gone, released)
+    };
+    for (tid, tracer) in gone {
+        crate::userland::lifecycle::wake_ring3_blocked_on_child(tracer, tid);
+    }
+    for (tid, resume) in released {
+        if resume == Resume::SingleStep {
+            set_trap_flag(tid, false);
+        }
+        wake_stopped(tid);
+    }
+}
+
+// ---------- requests ----------
+
+/// `ptrace(request, pid, addr, data) -> long`
+pub fn ptrace_handler(args: &mut SyscallArgs) -> i64 {
+    let request = args.rdi;
+    let pid = args.rsi as i32;
+    let addr = args.rdx;
+    let data = args.r10;
+    let caller = crate::userland::lifecycle::current_pid();
+    match request {
+        PTRACE_TRACEME => return traceme(),
+        PTRACE_ATTACH => return attach(caller, pid, false, 0),
+        PTRACE_SEIZE => return attach(caller, pid, true, data),
+        _ => {}
+    }
+    if pid <= 0 {
+        return ESRCH;
+    }
+    let tid = pid as u32;
+    let tracee = match stopped_tracee(caller, tid) {
+        Ok(tracee) => tracee,
+        Err(e) => return e,
+    };
+    match request {
+        PTRACE_PEEKTEXT | PTRACE_PEEKDATA => peek(tracee.tgid, addr, data),
+        PTRACE_POKETEXT | PTRACE_POKEDATA => poke(tracee.tgid, addr, data),
+        PTRACE_GETREGS => get_regs(tid, &tracee, data),
+        PTRACE_SETREGS => set_regs(tid, &tracee, data),
+        PTRACE_SETOPTIONS => set_options(tid, data),
+        PTRACE_CONT => resume(tid, Resume::Cont, data, false),
+        PTRACE_SYSCALL => resume(tid, Resume::Syscall, data, false),
+        PTRACE_SINGLESTEP => resume(tid, Resume::SingleStep, data, false),
+        PTRACE_DETACH => resume(tid, Resume::Cont, data, true),
+        _ => EIO,
+    }
+}
+
+fn insert(tid: u32, tracee: Tracee) -> i64 {
+    let mut tracees = TRACEES.lock();
+    if tracees.contains_key(&tid) {
+        return EPERM;
+    }
+    tracees.insert(tid, tracee);
+    TRACEE_COUNT.fetch_add(1, Ordering::Release);
+    0
+}
+
+/// `PTRACE_TRACEME`: the caller's parent becomes its tracer.
+fn traceme() -> i64 {
+    let Some(tid) = crate::userland::lifecycle::current_user_pid() else {
+        return EPERM;
+    };
+    let (tgid, parent) = crate::userland::lifecycle::with_current_group(|p| (p.pid, p.parent_pid));
+    if parent == KERNEL_PID {
+        return EPERM;
+    }
+    insert(tid, Tracee::new(tgid, parent, false, 0))
+}
+
+/// `PTRACE_ATTACH` (which also sends SIGSTOP) and `PTRACE_SEIZE`.
+fn attach(caller: u32, pid: i32, seized: bool, options: u64) -> i64 {
+    if options & !PTRACE_O_TRACESYSGOOD != 0 {
+        return EINVAL;
+    }
+    if pid <= 0 {
+        return ESRCH;
+    }
+    let tid = pid as u32;
+    let Some(tgid) = crate::userland::lifecycle::task_tgid(tid) else {
+        return ESRCH;
+    };
+    if tgid == KERNEL_PID {
+        return ESRCH;
+    }
+    if tgid == caller {
+        return EPERM;
+    }
+    let guard = CRED_GUARD.lock();
+    if let Err(e) = crate::userland::credentials::check_ptrace(tgid) {
+        return e;
+    }
+    let result = insert(tid, Tracee::new(tgid, caller, seized, options));
+    drop(guard);
+    if result == 0 && !seized {
+        crate::userland::job_control::send_signal(tid, SIGSTOP);
+    }
+    result
+}
+
+/// The tracee `tid` of `caller`, which must be in a ptrace stop.
+fn stopped_tracee(caller: u32, tid: u32) -> Result<Tracee, i64> {
+    TRACEES
+        .lock()
+        .get(&tid)
+        .filter(|tracee| tracee.tracer == caller && tracee.stop.is_some())
+        .copied()
+        .ok_or(ESRCH)
+}
+
+fn set_options(tid: u32, options: u64) -> i64 {
+    if options & !PTRACE_O_TRACESYSGOOD != 0 {
+        return EINVAL;
+    }
+    if let Some(tracee) = TRACEES.lock().get_mut(&tid) {
+        tracee.options = options;
+    }
+    0
+}
+
+/// Copy between `buf` and the memory of thread group `tgid` at `addr`.
+/// Pages are faulted in as for the tracee's own access; a write to a page
+/// it cannot write (breakpoints in text) goes to a private copy, except in
+/// a read-only shared mapping.
+fn access_memory(tgid: u32, addr: u64, buf: &mut [u8], write: bool) -> Result<(), i64> {
+    use crate::userland::vm::{VmProt, VmaBacking};
+
+    let mut done = 0;
+    while done < buf.len() {
+        let address = addr.checked_add(done as u64).ok_or(EIO)?;
+        let chunk = ((PAGE_SIZE - (address & (PAGE_SIZE - 1))) as usize).min(buf.len() - done);
+        crate::userland::usercopy::ensure_group_page(tgid, address, false).map_err(|_| EIO)?;
+        let (l4, shared, writable) = crate::userland::lifecycle::with_group(tgid, |process| {
+            let space = process.address_space.as_ref()?;
+            let vma = space.vmas().find(address)?;
+            Some((
+                space.l4_frame(),
+                matches!(vma.backing, VmaBacking::Shared { .. }),
+                vma.prot.contains(VmProt::WRITE),
+            ))
+        })
+        .flatten()
+        .ok_or(EIO)?;
+        if write && shared && !writable {
+            return Err(EIO);
+        }
+        let bytes = &mut buf[done..done + chunk];
+        crate::mm::memory::with_memory_mapper(|mapper| {
+            let page = x86_64::VirtAddr::new(address);
+            let frame = if write && !shared {
+                mapper.unshare_leaf(l4, page)?
+            } else {
+                mapper.leaf_info(l4, page)?.0
+            };
+            let target = (mapper.physical_memory_offset().as_u64()
+                + frame.start_address().as_u64()
+                + (address & (PAGE_SIZE - 1))) as *mut u8;
+            // SAFETY: the frame is a present leaf of the tracee's page
+            // tables, mapped in full through the physical-memory offset,
+            // and the mapper lock held here keeps it from being freed.
+            unsafe {
+                if write {
+                    core::ptr::copy_nonoverlapping(bytes.as_ptr(), target, chunk);
+                } else {
+                    core::ptr::copy_nonoverlapping(target, bytes.as_mut_ptr(), chunk);
+                }
+            }
+            Some(())
+        })
+        .flatten()
+        .ok_or(EIO)?;
+        done += chunk;
+    }
+    Ok(())
+}
+
+/// `PTRACE_PEEKDATA`: the raw syscall stores the word at `data`.
+fn peek(tgid: u32, addr: u64, data: u64) -> i64 {
+    let mut word = [0u8; 8];
+    if let Err(e) = access_memory(tgid, addr, &mut word, false) {
+        return e;
+    }
+    crate::userland::usercopy::write_unaligned(data, &u64::from_le_bytes(word))
+        .map_or_else(|e| e, |_| 0)
+}
+
+/// `PTRACE_POKEDATA`: store the word `data` at `addr`.
+fn poke(tgid: u32, addr: u64, data: u64) -> i64 {
+    let mut word = data.to_le_bytes();
+    match access_memory(tgid, addr, &mut word, true) {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
+
+/// Registers of a task stopped in `state`. A stop whose saved state
+/// re-fires its syscall shows the syscall as pending the way Linux does:
+/// `orig_rax` holds the number, `rax` the result it would have if the
+/// tracer skipped it, and `rip` the address after the SYSCALL.
+fn present_regs(state: &UserState, fs_base: u64, tracee: &Tracee) -> UserRegs {
+    let orig_rax = match tracee.stop {
+        Some(Stop::SyscallExit) => tracee.orig_rax,
+        _ if tracee.refires => state.rax,
+        _ => u64::MAX,
+    };
+    let mut regs = UserRegs::from_state(state, orig_rax, fs_base);
+    if tracee.refires {
+        regs.rax = if tracee.stop == Some(Stop::SyscallEntry) {
+            ENOSYS as u64
+        } else {
+            EINTR as u64
+        };
+        regs.rip = state.rip.wrapping_add(2);
+    }
+    regs
+}
+
+/// Inverse of [`present_regs`]: the resume state for `regs`, keeping the
+/// RFLAGS bits user code cannot change from `old_rflags`.
+fn apply_regs(regs: &UserRegs, old_rflags: u64, refires: bool) -> UserState {
+    let rflags = (old_rflags & !USER_RFLAGS) | (regs.eflags & USER_RFLAGS);
+    let mut state = regs.to_state(rflags);
+    if refires {
+        state.rax = regs.orig_rax;
+        state.rip = regs.rip.wrapping_sub(2);
+    }
+    state
+}
+
+fn get_regs(tid: u32, tracee: &Tracee, data: u64) -> i64 {
+    let Some(regs) = crate::userland::lifecycle::with_process(tid, |process| {
+        present_regs(&process.saved_user_state, process.fs_base, tracee)
+    }) else {
+        return ESRCH;
+    };
+    crate::userland::usercopy::write_unaligned(data, &regs).map_or_else(|e| e, |_| 0)
+}
+
+fn set_regs(tid: u32, tracee: &Tracee, data: u64) -> i64 {
+    let regs = match crate::userland::usercopy::read_unaligned::<UserRegs>(data) {
+        Ok(regs) => regs,
+        Err(e) => return e,
+    };
+    // iretq and the FS_BASE write fault on a non-canonical address.
+    if [regs.rip, regs.rsp, regs.fs_base]
+        .iter()
+        .any(|&address| address >= USER_ADDRESS_LIMIT)
+    {
+        return EIO;
+    }
+    crate::userland::lifecycle::with_process(tid, |process| {
+        process.saved_user_state =
+            apply_regs(&regs, process.saved_user_state.rflags, tracee.refires);
+        process.fs_base = regs.fs_base;
+    })
+    .map_or(ESRCH, |_| 0)
+}
+
+fn set_trap_flag(tid: u32, on: bool) {
+    let trap_flag = crate::arch::x86_64::debug_trap::TRAP_FLAG;
+    let _ = crate::userland::lifecycle::with_process(tid, |process| {
+        if on {
+            process.saved_user_state.rflags |= trap_flag;
+        } else {
+            process.saved_user_state.rflags &= !trap_flag;
+        }
+    });
+}
+
+/// Make `tid` runnable if it is parked in a ptrace stop. One that has not
+/// parked yet sees its stop cleared and does not park.
+fn wake_stopped(tid: u32) {
+    let woke = {
+        let mut g = PROCESS_TABLE.lock();
+        let parked = g.ring3_blocked.get(&tid) == Some(&Ring3BlockReason::Traced);
+        if parked {
+            g.ring3_blocked.remove(&tid);
+        }
+        parked
+    };
+    if woke {
+        crate::userland::lifecycle::mark_ring3_ready(tid);
+    }
+}
+
+/// `PTRACE_CONT`, `PTRACE_SYSCALL`, `PTRACE_SINGLESTEP` and, with
+/// `detach`, `PTRACE_DETACH`. A nonzero `sig` is injected when the tracee
+/// is in a signal-delivery stop and ignored otherwise.
+fn resume(tid: u32, mode: Resume, sig: u64, detach: bool) -> i64 {
+    if sig > NSIG as u64 {
+        return EIO;
+    }
+    let (previous, inject, refires) = {
+        let mut tracees = TRACEES.lock();
+        let Some(tracee) = tracees.get_mut(&tid) else {
+            return ESRCH;
+        };
+        let Some(stop) = tracee.stop.take() else {
+            return ESRCH;
+        };
+        let previous = core::mem::replace(&mut tracee.resume, mode);
+        let inject = match stop {
+            Stop::Signal(_) if sig != 0 => Some(sig as i32),
+            _ => None,
+        };
+        if let Some(sig) = inject {
+            tracee.injected |= 1u64 << (sig - 1);
+        }
+        let refires = tracee.refires;
+        if detach {
+            remove(&mut tracees, tid);
+        }
+        (previous, inject, refires)
+    };
+    if let Some(sig) = inject {
+        let _ = crate::userland::lifecycle::with_process(tid, |process| {
+            process.signal_state.raise(sig);
+            // A re-fired syscall must look at the signal before running.
+            if refires {
+                process.pending_syscall_interrupt = true;
+            }
+        });
+    }
+    if mode == Resume::SingleStep {
+        set_trap_flag(tid, true);
+    } else if previous == Resume::SingleStep {
+        set_trap_flag(tid, false);
+    }
+    wake_stopped(tid);
+    0
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+    use crate::userland::signal::{SigAction, SIGCHLD, SIGINT, SIGTERM, SIGUSR1};
+
+    fn test_stop_status_words() {
+        assert_eq!(Stop::Signal(SIGSTOP).status(0), 0x137f);
+        assert_eq!(Stop::SingleStep.status(PTRACE_O_TRACESYSGOOD), 0x57f);
+        assert_eq!(Stop::Exec.status(0), 0x57f);
+        assert_eq!(Stop::SyscallEntry.status(0), 0x57f);
+        assert_eq!(Stop::SyscallExit.status(PTRACE_O_TRACESYSGOOD), 0x857f);
+    }
+
+    fn test_reportable_signal() {
+        let mut state = SignalState::new();
+        // SIGCHLD is ignored by default; SIGKILL never stops a tracee.
+        state.raise(SIGCHLD);
+        state.raise(SIGKILL);
+        assert_eq!(reportable_signal(&state, 0), None);
+        state.raise(SIGTERM);
+        state.raise(SIGINT);
+        assert_eq!(reportable_signal(&state, 0), Some(SIGINT));
+        assert_eq!(
+            reportable_signal(&state, 1u64 << (SIGINT - 1)),
+            Some(SIGTERM)
+        );
+        state.blocked = 1u64 << (SIGINT - 1) | 1u64 << (SIGTERM - 1) | 1u64 << (SIGSTOP - 1);
+        assert_eq!(reportable_signal(&state, 0), None);
+        state.raise(SIGSTOP);
+        assert_eq!(reportable_signal(&state, 0), Some(SIGSTOP));
+
+        let mut state = SignalState::new();
+        state.set_action(
+            SIGUSR1,
+            SigAction {
+                sa_handler: SIG_IGN,
+                ..SigAction::default()
+            },
+        );
+        state.raise(SIGUSR1);
+        assert_eq!(reportable_signal(&state, 0), None);
+    }
+
+    fn test_user_regs_layout() {
+        use core::mem::offset_of;
+        assert_eq!(offset_of!(UserRegs, rax), 10 * 8);
+        assert_eq!(offset_of!(UserRegs, orig_rax), 15 * 8);
+        assert_eq!(offset_of!(UserRegs, rip), 16 * 8);
+        assert_eq!(offset_of!(UserRegs, eflags), 18 * 8);
+        assert_eq!(offset_of!(UserRegs, fs_base), 21 * 8);
+    }
+
+    fn test_syscall_entry_regs_round_trip() {
+        let mut tracee = Tracee::new(7, 3, false, 0);
+        tracee.stop = Some(Stop::SyscallEntry);
+        tracee.refires = true;
+        // Parked on the SYSCALL at 0x1000 for write(2).
+        let state = UserState {
+            rax: 1,
+            rdi: 2,
+            rip: 0x1000,
+            rflags: 0x202,
+            ..UserState::default()
+        };
+        let mut regs = present_regs(&state, 0x7000, &tracee);
+        assert_eq!(regs.orig_rax, 1);
+        assert_eq!(regs.rax, ENOSYS as u64);
+        assert_eq!(regs.rip, 0x1002);
+        assert_eq!(regs.rdi, 2);
+        assert_eq!(regs.fs_base, 0x7000);
+
+        // The tracer turns the call into getpid(2) and tries to clear IF.
+        regs.orig_rax = 39;
+        regs.eflags = 0x100;
+        let applied = apply_regs(&regs, state.rflags, true);
+        assert_eq!(applied.rax, 39);
+        assert_eq!(applied.rip, 0x1000);
+        assert_eq!(applied.rflags, 0x302);
+    }
+
+    fn test_plain_stop_regs_are_verbatim() {
+        let mut tracee = Tracee::new(7, 3, false, 0);
+        tracee.stop = Some(Stop::SingleStep);
+        let state = UserState {
+            rax: 5,
+            rcx: 6,
+            r11: 7,
+            rip: 0x2000,
+            ..UserState::default()
+        };
+        let regs = present_regs(&state, 0, &tracee);
+        assert_eq!((regs.rax, regs.rcx, regs.r11), (5, 6, 7));
+        assert_eq!(regs.orig_rax, u64::MAX);
+        assert_eq!(regs.rip, 0x2000);
+        let applied = apply_regs(&regs, 0x202, false);
+        assert_eq!((applied.rax, applied.rip), (5, 0x2000));
+
+        tracee.stop = Some(Stop::SyscallExit);
+        tracee.orig_rax = 60;
+        assert_eq!(present_regs(&state, 0, &tracee).orig_rax, 60);
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_stop_status_words,
+            &test_reportable_signal,
+            &test_user_regs_layout,
+            &test_syscall_entry_regs_round_trip,
+            &test_plain_stop_regs_are_verbatim,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as ptrace_tests;
@@ -0,0 +1,335 @@
+//! `/dev/ptmx`, `/dev/pts/N` and `/dev/tty` descriptors.
+//!
+//! Opening `/dev/ptmx` allocates a pty and returns its master, with the
+//! slave locked until `TIOCSPTLCK` clears the lock; `TIOCGPTN` names the
+//! slave, which then opens as `/dev/pts/N`. `/dev/tty` reopens the caller's
+//! own terminal. Unlike the standard-stream sentinels, a slave descriptor
+//! names its pty directly, so a process can hold terminals other than its
+//! own — which is how `script`, `tmux` or an ssh server drive a child.
+//!
+//! Once the pty is torn down (the last `/dev/ptmx` descriptor closes or its
+//! terminal window goes away) slave reads return end-of-file and writes
+//! fail with `EIO`.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::lib::arc::Arc;
+use crate::terminal::pty::{PtyMaster, PtySlave, TerminalId, Winsize};
+use crate::userland::abi::{EAGAIN, EIO, ENOENT, ENOSPC, ENOSYS, ENOTTY, ENXIO};
+use crate::userland::fdtable::FdSlot;
+use crate::userland::syscalls::{
+    TCGETS, TCSETS, TCSETSF, TCSETSW, TIOCGPGRP, TIOCGSID, TIOCGWINSZ, TIOCSCTTY, TIOCSPGRP,
+};
+
+const O_NONBLOCK: u32 = 0o4000;
+const O_NOCTTY: u32 = 0o400;
+const O_CLOEXEC: u32 = 0o2000000;
+
+const TIOCSWINSZ: u64 = 0x5414;
+const TIOCGPTN: u64 = 0x8004_5430;
+const TIOCSPTLCK: u64 = 0x4004_5431;
+const TIOCGPTLCK: u64 = 0x8004_5439;
+
+/// Open-file description of a pty slave. dup and fork share it, and with
+/// it the `O_NONBLOCK` status flag.
+pub struct PtsHandle {
+    slave: PtySlave,
+    nonblocking: AtomicBool,
+}
+
+impl PtsHandle {
+    pub fn terminal_id(&self) -> TerminalId {
+        self.slave.terminal_id()
+    }
+
+    pub fn nonblocking(&self) -> bool {
+        self.nonblocking.load(Ordering::Acquire)
+    }
+
+    pub fn set_nonblocking(&self, value: bool) {
+        self.nonblocking.store(value, Ordering::Release);
+    }
+
+    fn hung_up(&self) -> bool {
+        self.slave.with(|p| p.hung_up())
+    }
+
+    /// `(readable, writable, error, hangup)` for poll, select and epoll.
+    pub fn readiness(&self) -> (bool, bool, bool, bool) {
+        let hung_up = self.hung_up();
+        (
+            hung_up || self.slave.readable() != 0,
+            !hung_up && self.slave.output_room() != 0,
+            false,
+            hung_up,
+        )
+    }
+
+    /// Queue `bytes` for the master. `Ok(0)` means the queue is full.
+    pub fn write(&self, bytes: &[u8]) -> Result<usize, i64> {
+        if self.hung_up() {
+            return Err(EIO);
+        }
+        Ok(self.slave.write(bytes))
+    }
+}
+
+// ---------- open ----------
+
+/// Open `/dev/ptmx`: allocate a pty and return its master.
+pub fn open_ptmx(flags: u32) -> Result<FdSlot, i64> {
+    let master = crate::terminal::pty::open_ptmx().ok_or(ENOSPC)?;
+    master.set_nonblocking(flags & O_NONBLOCK != 0);
+    Ok(FdSlot::PtyMaster {
+        master,
+        cloexec: flags & O_CLOEXEC != 0,
+    })
+}
+
+/// Open `/dev/pts/N`. A slave stays unopenable while its master holds the
+/// `TIOCSPTLCK` lock.
+pub fn open_pts(index: u32, flags: u32) -> Result<FdSlot, i64> {
+    let terminal_id = TerminalId(index);
+    let slave = crate::terminal::pty::slave_for_terminal(terminal_id).ok_or(ENOENT)?;
+    if slave.with(|p| p.locked()) {
+        return Err(EIO);
+    }
+    if flags & O_NOCTTY == 0 {
+        crate::userland::job_control::acquire_on_open(terminal_id);
+    }
+    Ok(slave_slot(slave, flags))
+}
+
+/// Open `/dev/tty`, the caller's own terminal.
+pub fn open_tty(flags: u32) -> Result<FdSlot, i64> {
+    let slave = crate::userland::lifecycle::with_current_group(|p| p.terminal_id)
+        .and_then(crate::terminal::pty::slave_for_terminal)
+        .ok_or(ENXIO)?;
+    Ok(slave_slot(slave, flags))
+}
+
+fn slave_slot(slave: PtySlave, flags: u32) -> FdSlot {
+    FdSlot::PtySlave {
+        handle: Arc::new(PtsHandle {
+            slave,
+            nonblocking: AtomicBool::new(flags & O_NONBLOCK != 0),
+        }),
+        cloexec: flags & O_CLOEXEC != 0,
+    }
+}
+
+// ---------- read / write ----------
+
+/// `read` on a slave descriptor. A background group reading its own
+/// controlling terminal is stopped by SIGTTIN, as for standard input.
+pub fn read(args: &SyscallArgs, handle: Arc<PtsHandle>, pointer: u64, len: u64) -> i64 {
+    let observed = crate::userland::readiness::sequence();
+    if is_callers_terminal(handle.terminal_id()) {
+        if let Err(error) = crate::userland::job_control::check_terminal_read(args) {
+            return error;
+        }
+    }
+    let mut staging = alloc::vec![0u8; len as usize];
+    let n = handle.slave.read(&mut staging);
+    if n > 0 {
+        return match crate::userland::usercopy::copy_to_user(pointer, &staging[..n]) {
+            Ok(()) => n as i64,
+            Err(error) => error,
+        };
+    }
+    if handle.slave.take_eof() || handle.hung_up() {
+        return 0;
+    }
+    drop(staging);
+    wait_slave(args, handle, observed)
+}
+
+/// Park a slave reader or writer that found its queue empty or full. The
+/// syscall restarts from the top once the pty changes, so the handle is
+/// dropped first rather than leaked with the abandoned frame.
+pub fn wait_slave(args: &SyscallArgs, handle: Arc<PtsHandle>, observed: u64) -> i64 {
+    if handle.nonblocking() {
+        return EAGAIN;
+    }
+    let identity = Arc::as_ptr(&handle) as usize as u64;
+    drop(handle);
+    crate::userland::readiness::block(args, identity, None, observed)
+}
+
+/// Park a blocking `/dev/ptmx` master whose read found no output or whose
+/// write found the slave's input queue full. A window master always
+/// reports `EAGAIN`.
+pub fn wait_master(args: &SyscallArgs, master: PtyMaster, observed: u64) -> i64 {
+    if master.nonblocking() {
+        return EAGAIN;
+    }
+    let identity = u64::from(master.terminal_id().0);
+    drop(master);
+    crate::userland::readiness::block(args, identity, None, observed)
+}
+
+fn is_callers_terminal(terminal_id: TerminalId) -> bool {
+    crate::userland::lifecycle::with_current_group(|p| p.terminal_id) == Some(terminal_id)
+}
+
+// ---------- ioctl ----------
+
+/// `ioctl` on a master descriptor: the terminal attributes plus the
+/// `/dev/pts` index and slave lock.
+pub fn master_ioctl(master: &PtyMaster, request: u64, arg: u64) -> i64 {
+    match request {
+        TIOCGPTN => {
+            let index = master.terminal_id().0;
+            crate::userland::usercopy::write_unaligned(arg, &index).map_or_else(|e| e, |_| 0)
+        }
+        TIOCSPTLCK => {
+            let lock: i32 = match crate::userland::usercopy::read_unaligned(arg) {
+                Ok(value) => value,
+                Err(error) => return error,
+            };
+            master.with(|p| p.set_locked(lock != 0));
+            0
+        }
+        TIOCGPTLCK => {
+            let lock = i32::from(master.with(|p| p.locked()));
+            crate::userland::usercopy::write_unaligned(arg, &lock).map_or_else(|e| e, |_| 0)
+        }
+        _ => attribute_ioctl(&master.slave(), request, arg).unwrap_or(ENOSYS),
+    }
+}
+
+/// `ioctl` on a slave descriptor. The job-control requests only apply to
+/// the caller's own terminal, which `TIOCSCTTY` can make this one.
+pub fn slave_ioctl(args: &SyscallArgs, handle: &PtsHandle, request: u64, arg: u64) -> i64 {
+    let terminal_id = handle.terminal_id();
+    match request {
+        TIOCSCTTY => crate::userland::job_control::tiocsctty(Some(terminal_id), arg),
+        TIOCGPGRP | TIOCSPGRP | TIOCGSID if !is_callers_terminal(terminal_id) => ENOTTY,
+        TIOCGPGRP => crate::userland::job_control::tiocgpgrp(arg),
+        TIOCSPGRP => crate::userland::job_control::tiocspgrp(args, arg),
+        TIOCGSID => crate::userland::job_control::tiocgsid(arg),
+        _ => attribute_ioctl(&handle.slave, request, arg).unwrap_or(ENOSYS),
+    }
+}
+
+/// termios and window-size requests, shared by both ends. Setting a new
+/// window size raises SIGWINCH on the terminal. `None` for any other
+/// request.
+fn attribute_ioctl(slave: &PtySlave, request: u64, arg: u64) -> Option<i64> {
+    let result = match request {
+        TCGETS => crate::userland::usercopy::write_unaligned(arg, &slave.termios())
+            .map_or_else(|e| e, |_| 0),
+        TCSETS | TCSETSW | TCSETSF => match crate::userland::usercopy::read_unaligned(arg) {
+            Ok(termios) => {
+                slave.set_termios(termios);
+                0
+            }
+            Err(error) => error,
+        },
+        TIOCGWINSZ => crate::userland::usercopy::write_unaligned(arg, &slave.winsize())
+            .map_or_else(|e| e, |_| 0),
+        TIOCSWINSZ => {
+            let winsize: Winsize = match crate::userland::usercopy::read_unaligned(arg) {
+                Ok(value) => value,
+                Err(error) => return Some(error),
+            };
+            let (changed, foreground) = slave.with(|p| {
+                let changed = p.winsize != winsize;
+                p.winsize = winsize;
+                (changed, p.foreground_pgrp())
+            });
+            if changed {
+                crate::userland::job_control::signal_terminal(
+                    slave.terminal_id(),
+                    foreground,
+                    crate::userland::signal::SIGWINCH,
+                );
+            }
+            0
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+    use crate::userland::devfs::{classify, DeviceNode};
+
+    fn open_master() -> PtyMaster {
+        match open_ptmx(0) {
+            Ok(FdSlot::PtyMaster { master, .. }) => master,
+            _ => panic!("/dev/ptmx open failed"),
+        }
+    }
+
+    fn open_slave(master: &PtyMaster) -> Arc<PtsHandle> {
+        match open_pts(master.terminal_id().0, O_NOCTTY) {
+            Ok(FdSlot::PtySlave { handle, .. }) => handle,
+            _ => panic!("/dev/pts open failed"),
+        }
+    }
+
+    fn test_pts_names_only_live_ptys() {
+        let master = open_master();
+        let index = master.terminal_id().0;
+        let name = alloc::format!("/dev/pts/{index}");
+        assert_eq!(classify(&name), Some(DeviceNode::Pts(index)));
+        assert_eq!(classify(&alloc::format!("/dev/pts/0{index}")), None);
+        assert_eq!(classify("/dev/pts/+1"), None);
+        assert_eq!(classify("/dev/pts"), Some(DeviceNode::PtsDirectory));
+        drop(master);
+        assert_eq!(classify(&name), None, "a closed pty leaves /dev/pts");
+    }
+
+    fn test_slave_locked_until_unlocked() {
+        let master = open_master();
+        assert!(matches!(
+            open_pts(master.terminal_id().0, O_NOCTTY),
+            Err(EIO)
+        ));
+        master.with(|p| p.set_locked(false));
+        let slave = open_slave(&master);
+        assert_eq!(slave.terminal_id(), master.terminal_id());
+        assert!(matches!(open_pts(u32::MAX - 1, O_NOCTTY), Err(ENOENT)));
+    }
+
+    fn test_slave_and_master_exchange_bytes() {
+        let master = open_master();
+        master.with(|p| p.set_locked(false));
+        let slave = open_slave(&master);
+        assert_eq!(slave.readiness(), (false, true, false, false));
+        assert_eq!(slave.write(b"hi\n"), Ok(3));
+        let mut out = [0u8; 8];
+        let n = master.read_output(&mut out);
+        assert_eq!(&out[..n], b"hi\r\n", "slave output goes through OPOST");
+        master.with(|p| p.push_slave_input(b"ok\n"));
+        assert!(slave.readiness().0);
+    }
+
+    fn test_last_master_close_hangs_up_slave() {
+        let master = open_master();
+        master.with(|p| p.set_locked(false));
+        let slave = open_slave(&master);
+        let dup = master.clone();
+        drop(master);
+        assert_eq!(slave.readiness(), (false, true, false, false));
+        drop(dup);
+        assert_eq!(slave.readiness(), (true, false, false, true));
+        assert_eq!(slave.write(b"x"), Err(EIO));
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_pts_names_only_live_ptys,
+            &test_slave_locked_until_unlocked,
+            &test_slave_and_master_exchange_bytes,
+            &test_last_master_close_hangs_up_slave,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as pts_tests;
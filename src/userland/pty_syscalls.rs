@@ -5,7 +5,7 @@
 //! shell running on the slave — the split Linux makes between the kernel N_TTY
 //! and a userland xterm.
 //!
-//! `pty_open` keys a pty on the caller's own GUI window (its `surface_id`
+//! `pty_open` binds a pty to the caller's own GUI window (its `surface_id`
 //! `WindowId`), sets the caller's `terminal_id` so a subsequently-`fork`ed
 //! child inherits the slave through the existing sentinel-stdio model, and
 //! returns a `FdSlot::PtyMaster` descriptor. `pty_set_winsize` updates the
@@ -51,14 +51,14 @@ pub fn pty_open_handler(args: &mut SyscallArgs) -> i64 {
         Ok(pid) => pid,
         Err(error) => return error,
     };
-    // Resolve the caller's window to the content-well WindowId we key the pty
-    // on. Ownership is enforced by `window_record` (per-PID map).
+    // Resolve the caller's window to the content-well WindowId we bind the
+    // pty to. Ownership is enforced by `window_record` (per-PID map).
     let Some(record) = crate::userland::gui::window_record(pid, handle) else {
         return EBADF;
     };
-    let terminal_id = record.surface_id;
 
-    let master = pty::install_for_terminal(terminal_id, rows, cols);
+    let master = pty::install_for_window(record.surface_id, rows, cols);
+    let terminal_id = master.terminal_id();
     let slot = FdSlot::PtyMaster {
         master,
         cloexec: flags & O_CLOEXEC != 0,
@@ -76,7 +76,7 @@ pub fn pty_open_handler(args: &mut SyscallArgs) -> i64 {
 /// `pty_set_winsize(master_fd, rows, cols) -> 0 | -errno`.
 ///
 /// Updates the pty winsize and, on an actual change, raises SIGWINCH on the
-/// child bound to this terminal.
+/// foreground process group of this terminal.
 pub fn pty_set_winsize_handler(args: &mut SyscallArgs) -> i64 {
     let fd = args.rdi as i32;
     let rows = args.rsi as u16;
@@ -96,8 +96,9 @@ pub fn pty_set_winsize_handler(args: &mut SyscallArgs) -> i64 {
 
     let changed = master.set_winsize(pty::Winsize::new(rows, cols));
     if changed {
-        crate::userland::lifecycle::raise_signal_on_terminal(
+        crate::userland::job_control::signal_terminal(
             master.terminal_id(),
+            master.with(|p| p.foreground_pgrp()),
             crate::userland::signal::SIGWINCH,
         );
     }
@@ -0,0 +1,503 @@
+//! Per-process resource limits: `getrlimit`, `setrlimit` and `prlimit64`.
+//!
+//! Limits belong to the thread group and are authoritative on its leader.
+//! `fork` and thread creation copy the table; `execve` keeps it. Every
+//! resource starts at `RLIM_INFINITY` for both the soft and the hard limit.
+//! The soft limit may never exceed the hard one, and only root may raise
+//! a hard limit.
+//!
+//! Enforced resources:
+//!
+//! - `RLIMIT_NOFILE` caps descriptor numbers in the fd table.
+//! - `RLIMIT_AS` and `RLIMIT_DATA` fail `mmap`, `mremap` and `brk` growth
+//!   with `ENOMEM`. Data counts private writable mappings; the stack counts
+//!   only its committed part.
+//! - `RLIMIT_STACK` bounds demand stack growth.
+//! - `RLIMIT_NPROC` fails `fork` and `clone` with `EAGAIN` once the real
+//!   uid owns that many tasks. Root is exempt.
+//! - `RLIMIT_CPU` sends `SIGXCPU` at the soft limit and every second after,
+//!   and `SIGKILL` at the hard limit. Timer ticks in ring 3 and in the
+//!   group's syscalls both count, against a running total on the leader.
+//! - `RLIMIT_FSIZE` shortens writes that cross it. A write or truncate
+//!   wholly past it raises `SIGXFSZ` and fails with `EFBIG`.
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::userland::abi::{EAGAIN, EFAULT, EFBIG, EINVAL, ENOMEM, EPERM, ESRCH};
+use crate::userland::lifecycle::{Process, ProcessTable};
+
+pub const RLIMIT_CPU: usize = 0;
+pub const RLIMIT_FSIZE: usize = 1;
+pub const RLIMIT_DATA: usize = 2;
+pub const RLIMIT_STACK: usize = 3;
+pub const RLIMIT_CORE: usize = 4;
+pub const RLIMIT_NPROC: usize = 6;
+pub const RLIMIT_NOFILE: usize = 7;
+pub const RLIMIT_AS: usize = 9;
+/// Number of resources Linux defines (`RLIMIT_RTTIME` is the last, 15).
+pub const RLIM_NLIMITS: usize = 16;
+
+pub const RLIM_INFINITY: u64 = u64::MAX;
+
+/// Linux's default `fs.nr_open`, the ceiling for `RLIMIT_NOFILE`.
+const NR_OPEN: u64 = 1024 * 1024;
+
+/// Linux `struct rlimit` (16 bytes on 64-bit).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimit {
+    pub rlim_cur: u64,
+    pub rlim_max: u64,
+}
+
+impl Rlimit {
+    pub const INFINITY: Self = Self {
+        rlim_cur: RLIM_INFINITY,
+        rlim_max: RLIM_INFINITY,
+    };
+}
+
+/// One thread group's limit table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimits {
+    limits: [Rlimit; RLIM_NLIMITS],
+}
+
+impl Default for Rlimits {
+    fn default() -> Self {
+        Self {
+            limits: [Rlimit::INFINITY; RLIM_NLIMITS],
+        }
+    }
+}
+
+impl Rlimits {
+    /// Limit for `resource`. Callers pass one of the `RLIMIT_*` constants.
+    pub fn get(&self, resource: usize) -> Rlimit {
+        self.limits[resource]
+    }
+
+    /// Soft limit for `resource`.
+    pub fn cur(&self, resource: usize) -> u64 {
+        self.limits[resource].rlim_cur
+    }
+
+    /// Replace the limit for `resource`, refusing a soft limit above the
+    /// hard one.
+    pub fn set(&mut self, resource: usize, limit: Rlimit) -> Result<(), i64> {
+        if resource >= RLIM_NLIMITS || limit.rlim_cur > limit.rlim_max {
+            return Err(EINVAL);
+        }
+        self.limits[resource] = limit;
+        Ok(())
+    }
+}
+
+fn resource_index(raw: u64) -> Result<usize, i64> {
+    let resource = raw as u32 as usize;
+    if resource < RLIM_NLIMITS {
+        Ok(resource)
+    } else {
+        Err(EINVAL)
+    }
+}
+
+/// Refuse replacing `old` with `new` for `resource` unless `privileged`
+/// when that raises the hard limit.
+fn check_raise(resource: usize, old: Rlimit, new: Rlimit, privileged: bool) -> Result<(), i64> {
+    if resource == RLIMIT_NOFILE && new.rlim_max != RLIM_INFINITY && new.rlim_max > NR_OPEN {
+        return Err(EPERM);
+    }
+    if new.rlim_max > old.rlim_max && !privileged {
+        return Err(EPERM);
+    }
+    Ok(())
+}
+
+/// Read the new limit, swap it in for the group `tgid` and return the old
+/// one. `new_ptr == 0` only reads.
+fn exchange(tgid: u32, resource: usize, new_ptr: u64) -> Result<Rlimit, i64> {
+    let new = if new_ptr == 0 {
+        None
+    } else {
+        Some(crate::userland::usercopy::read_unaligned::<Rlimit>(new_ptr).map_err(|_| EFAULT)?)
+    };
+    let privileged = crate::userland::credentials::current().privileged();
+    crate::userland::lifecycle::with_group(tgid, |process| {
+        let old = process.rlimits.get(resource);
+        if let Some(new) = new {
+            check_raise(resource, old, new, privileged)?;
+            process.rlimits.set(resource, new)?;
+            if resource == RLIMIT_NOFILE {
+                process.fd_table.set_limit(new.rlim_cur);
+            }
+        }
+        Ok(old)
+    })
+    .unwrap_or(Err(ESRCH))
+}
+
+/// Whether the caller may change the limits of a process with `target`
+/// credentials: root, or every id of the target equals the caller's real
+/// one.
+fn may_prlimit(
+    caller: &crate::userland::credentials::Credentials,
+    target: &crate::userland::credentials::Credentials,
+) -> bool {
+    caller.privileged()
+        || (target.user.to_array()[..3]
+            .iter()
+            .all(|&uid| uid == caller.user.real)
+            && target.group.to_array()[..3]
+                .iter()
+                .all(|&gid| gid == caller.group.real))
+}
+
+fn exceeds(used: u64, added: u64, limit: u64) -> bool {
+    used.saturating_add(added) > limit
+}
+
+/// Bytes `process` maps in total and as private writable data. The stack
+/// VMA reserves its whole growth window, but only its committed part
+/// counts.
+fn vm_usage(process: &Process) -> (u64, u64) {
+    use crate::userland::vm::{VmProt, VmaBacking};
+    let Some(space) = process.address_space.as_ref() else {
+        return (0, 0);
+    };
+    let (mut total, mut data) = (0u64, 0u64);
+    for vma in space.vmas().as_slice() {
+        if matches!(vma.backing, VmaBacking::Stack { .. }) {
+            total += process.stack_top.saturating_sub(process.stack_bottom);
+            continue;
+        }
+        let len = vma.end - vma.start;
+        total += len;
+        if vma.private && vma.prot.contains(VmProt::WRITE) {
+            data += len;
+        }
+    }
+    (total, data)
+}
+
+/// Refuse mapping `total` more bytes into the group led by `process`,
+/// `data` of them private and writable, past `RLIMIT_AS` or `RLIMIT_DATA`.
+pub fn check_vm_growth(process: &Process, total: u64, data: u64) -> Result<(), i64> {
+    let address_space = process.rlimits.cur(RLIMIT_AS);
+    let data_limit = process.rlimits.cur(RLIMIT_DATA);
+    if address_space == RLIM_INFINITY && data_limit == RLIM_INFINITY {
+        return Ok(());
+    }
+    let (mapped, mapped_data) = vm_usage(process);
+    if exceeds(mapped, total, address_space) || (data > 0 && exceeds(mapped_data, data, data_limit))
+    {
+        Err(ENOMEM)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether growing a stack whose top is `stack_top` down to `address`
+/// breaks the group's `RLIMIT_STACK` or `RLIMIT_AS`. `leader` leads the
+/// group.
+pub fn stack_growth_refused(leader: &Process, stack_top: u64, address: u64) -> bool {
+    let page = address & !0xfff;
+    stack_top.saturating_sub(page) > leader.rlimits.cur(RLIMIT_STACK)
+        || check_vm_growth(leader, 0x1000, 0).is_err()
+}
+
+/// Refuse a new task once the caller's real uid owns `RLIMIT_NPROC` of
+/// them, counting every live thread.
+pub fn check_nproc() -> Result<(), i64> {
+    use crate::userland::lifecycle::{with_current_group, ExitKind, PROCESS_TABLE};
+    let (uid, privileged, limit) = with_current_group(|p| {
+        (
+            p.credentials.user.real,
+            p.credentials.privileged(),
+            p.rlimits.cur(RLIMIT_NPROC),
+        )
+    });
+    if privileged || limit == RLIM_INFINITY {
+        return Ok(());
+    }
+    let table = PROCESS_TABLE.lock();
+    let owned = table
+        .thread_groups
+        .iter()
+        .filter(|(tid, tgid)| {
+            !table.dead_tasks.contains_key(tid)
+                && table.by_pid.get(tgid).is_some_and(|leader| {
+                    leader.exit_kind == ExitKind::None && leader.credentials.user.real == uid
+                })
+        })
+        .count() as u64;
+    if owned >= limit {
+        Err(EAGAIN)
+    } else {
+        Ok(())
+    }
+}
+
+/// The signal a group with CPU `limit` earns on reaching `ticks` of
+/// charged time: `SIGKILL` at the hard limit, `SIGXCPU` at the soft limit
+/// and on every second past it.
+fn cpu_signal(limit: Rlimit, ticks: u64) -> Option<i32> {
+    use crate::userland::signal::{SIGKILL, SIGXCPU};
+    let hz = crate::arch::x86_64::interrupts::TIMER_FREQUENCY_HZ as u64;
+    if ticks >= limit.rlim_max.saturating_mul(hz) {
+        return Some(SIGKILL);
+    }
+    let soft = limit.rlim_cur.saturating_mul(hz);
+    (ticks >= soft && (ticks - soft).is_multiple_of(hz)).then_some(SIGXCPU)
+}
+
+/// Charge one tick of user (`user`) or system time to task `tid` and its
+/// group's running total. Returns the thread group and the signal its
+/// `RLIMIT_CPU` calls for. Runs under the process-table lock; the caller
+/// sends the signal once it is released.
+pub fn charge_cpu_tick(table: &mut ProcessTable, tid: u32, user: bool) -> Option<(u32, i32)> {
+    let task = table.by_pid.get_mut(&tid)?;
+    if user {
+        task.utime_ticks = task.utime_ticks.saturating_add(1);
+    }
+    let tgid = table.thread_groups.get(&tid).copied().unwrap_or(tid);
+    let leader = table.by_pid.get_mut(&tgid)?;
+    leader.group_cpu_ticks = leader.group_cpu_ticks.saturating_add(1);
+    let limit = leader.rlimits.get(RLIMIT_CPU);
+    if limit.rlim_cur == RLIM_INFINITY {
+        return None;
+    }
+    cpu_signal(limit, leader.group_cpu_ticks).map(|sig| (tgid, sig))
+}
+
+/// How many of `len` bytes written at `offset` fit under file-size
+/// `limit`; `None` when the write starts at or past it.
+fn fsize_allowance(limit: u64, offset: u64, len: u64) -> Option<u64> {
+    if limit == RLIM_INFINITY {
+        Some(len)
+    } else if offset >= limit {
+        None
+    } else {
+        Some(len.min(limit - offset))
+    }
+}
+
+/// Clamp a write of `len` bytes at `offset` to the caller's
+/// `RLIMIT_FSIZE`. A write that cannot make progress raises `SIGXFSZ`.
+pub fn clamp_file_write(offset: u64, len: u64) -> Result<u64, i64> {
+    if len == 0 {
+        return Ok(0);
+    }
+    let limit = crate::userland::lifecycle::with_current_group(|p| p.rlimits.cur(RLIMIT_FSIZE));
+    fsize_allowance(limit, offset, len).ok_or_else(raise_xfsz)
+}
+
+/// Refuse growing a file to `size` past the caller's `RLIMIT_FSIZE`.
+pub fn check_file_size(size: u64) -> Result<(), i64> {
+    let limit = crate::userland::lifecycle::with_current_group(|p| p.rlimits.cur(RLIMIT_FSIZE));
+    if limit != RLIM_INFINITY && size > limit {
+        Err(raise_xfsz())
+    } else {
+        Ok(())
+    }
+}
+
+/// Raise `SIGXFSZ` on the writing task, which sees it on its way out of
+/// the syscall.
+fn raise_xfsz() -> i64 {
+    crate::userland::lifecycle::with_current_process(|p| {
+        p.signal_state.raise(crate::userland::signal::SIGXFSZ)
+    });
+    EFBIG
+}
+
+fn write_limit(out_ptr: u64, limit: &Rlimit) -> i64 {
+    crate::userland::usercopy::write_unaligned(out_ptr, limit).map_or_else(|e| e, |_| 0)
+}
+
+/// `getrlimit(resource, *rlim) -> int`
+pub fn getrlimit_handler(args: &mut SyscallArgs) -> i64 {
+    let resource = match resource_index(args.rdi) {
+        Ok(resource) => resource,
+        Err(e) => return e,
+    };
+    let limit = crate::userland::lifecycle::with_current_group(|p| p.rlimits.get(resource));
+    write_limit(args.rsi, &limit)
+}
+
+/// `setrlimit(resource, *rlim) -> int`
+pub fn setrlimit_handler(args: &mut SyscallArgs) -> i64 {
+    let resource = match resource_index(args.rdi) {
+        Ok(resource) => resource,
+        Err(e) => return e,
+    };
+    if args.rsi == 0 {
+        return EFAULT;
+    }
+    let tgid = crate::userland::lifecycle::current_pid();
+    match exchange(tgid, resource, args.rsi) {
+        Ok(_) => 0,
+        Err(e) => e,
+    }
+}
+
+/// `prlimit64(pid, resource, *new_limit, *old_limit) -> int`
+///
+/// `pid == 0` names the caller. Any other pid must be a live thread
+/// group the caller may manage: root, or a target whose ids all equal the
+/// caller's real ids.
+pub fn prlimit64_handler(args: &mut SyscallArgs) -> i64 {
+    let resource = match resource_index(args.rsi) {
+        Ok(resource) => resource,
+        Err(e) => return e,
+    };
+    let pid = args.rdi as i32;
+    let tgid = match pid {
+        0 => crate::userland::lifecycle::current_pid(),
+        pid if pid > 0 => match crate::userland::lifecycle::task_tgid(pid as u32) {
+            Some(tgid) => tgid,
+            None => return ESRCH,
+        },
+        _ => return ESRCH,
+    };
+    if tgid != crate::userland::lifecycle::current_pid() {
+        let Some(target) = crate::userland::credentials::of_group(tgid) else {
+            return ESRCH;
+        };
+        if !may_prlimit(&crate::userland::credentials::current(), &target) {
+            return EPERM;
+        }
+    }
+    let old = match exchange(tgid, resource, args.rdx) {
+        Ok(old) => old,
+        Err(e) => return e,
+    };
+    if args.r10 == 0 {
+        return 0;
+    }
+    write_limit(args.r10, &old)
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn test_defaults_are_infinite() {
+        let limits = Rlimits::default();
+        for resource in 0..RLIM_NLIMITS {
+            assert_eq!(limits.get(resource), Rlimit::INFINITY);
+        }
+        assert_eq!(core::mem::size_of::<Rlimit>(), 16);
+    }
+
+    fn test_set_rejects_soft_above_hard() {
+        let mut limits = Rlimits::default();
+        let core = Rlimit {
+            rlim_cur: 0,
+            rlim_max: 4096,
+        };
+        assert_eq!(limits.set(RLIMIT_CORE, core), Ok(()));
+        assert_eq!(limits.cur(RLIMIT_CORE), 0);
+        let inverted = Rlimit {
+            rlim_cur: 8192,
+            rlim_max: 4096,
+        };
+        assert_eq!(limits.set(RLIMIT_CORE, inverted), Err(EINVAL));
+        assert_eq!(limits.get(RLIMIT_CORE), core);
+        assert_eq!(limits.set(RLIM_NLIMITS, core), Err(EINVAL));
+    }
+
+    fn test_resource_index_bounds() {
+        assert_eq!(resource_index(9), Ok(9)); // RLIMIT_AS
+        assert_eq!(resource_index(RLIM_NLIMITS as u64), Err(EINVAL));
+        // The resource argument is an `int`; upper bits are ignored.
+        assert_eq!(resource_index(0xffff_ffff_0000_0004), Ok(RLIMIT_CORE));
+    }
+
+    fn test_only_privilege_raises_hard_limit() {
+        let old = Rlimit {
+            rlim_cur: 64,
+            rlim_max: 128,
+        };
+        let lower = Rlimit {
+            rlim_cur: 32,
+            rlim_max: 64,
+        };
+        assert_eq!(check_raise(RLIMIT_NOFILE, old, lower, false), Ok(()));
+        assert_eq!(check_raise(RLIMIT_NOFILE, old, old, false), Ok(()));
+        assert_eq!(
+            check_raise(RLIMIT_NOFILE, old, Rlimit::INFINITY, false),
+            Err(EPERM)
+        );
+        assert_eq!(
+            check_raise(RLIMIT_NOFILE, old, Rlimit::INFINITY, true),
+            Ok(())
+        );
+        let past_nr_open = Rlimit {
+            rlim_cur: 64,
+            rlim_max: NR_OPEN + 1,
+        };
+        assert_eq!(
+            check_raise(RLIMIT_NOFILE, old, past_nr_open, true),
+            Err(EPERM)
+        );
+        assert_eq!(check_raise(RLIMIT_CORE, old, past_nr_open, true), Ok(()));
+    }
+
+    fn test_prlimit_needs_matching_ids() {
+        use crate::userland::credentials::{Credentials, Ids};
+        let user = |uid: u32| Credentials {
+            user: Ids::all(uid),
+            group: Ids::all(uid),
+            groups: alloc::vec::Vec::new(),
+            dumpable: true,
+        };
+        assert!(may_prlimit(&user(0), &user(1000)));
+        assert!(may_prlimit(&user(1000), &user(1000)));
+        assert!(!may_prlimit(&user(1000), &user(1001)));
+        let mut setuid = user(1000);
+        setuid.user.saved = 0;
+        assert!(!may_prlimit(&user(1000), &setuid));
+    }
+
+    fn test_cpu_signal_schedule() {
+        use crate::userland::signal::{SIGKILL, SIGXCPU};
+        let hz = crate::arch::x86_64::interrupts::TIMER_FREQUENCY_HZ as u64;
+        let limit = Rlimit {
+            rlim_cur: 2,
+            rlim_max: 4,
+        };
+        assert_eq!(cpu_signal(limit, 2 * hz - 1), None);
+        assert_eq!(cpu_signal(limit, 2 * hz), Some(SIGXCPU));
+        assert_eq!(cpu_signal(limit, 2 * hz + 1), None);
+        assert_eq!(cpu_signal(limit, 3 * hz), Some(SIGXCPU));
+        assert_eq!(cpu_signal(limit, 4 * hz), Some(SIGKILL));
+        let soft_only = Rlimit {
+            rlim_cur: 1,
+            rlim_max: RLIM_INFINITY,
+        };
+        assert_eq!(cpu_signal(soft_only, u64::MAX / 2), None);
+    }
+
+    fn test_fsize_allowance() {
+        assert_eq!(fsize_allowance(RLIM_INFINITY, u64::MAX - 1, 10), Some(10));
+        assert_eq!(fsize_allowance(100, 0, 10), Some(10));
+        assert_eq!(fsize_allowance(100, 95, 10), Some(5));
+        assert_eq!(fsize_allowance(100, 100, 10), None);
+        assert_eq!(fsize_allowance(0, 0, 1), None);
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_defaults_are_infinite,
+            &test_set_rejects_soft_above_hard,
+            &test_resource_index_bounds,
+            &test_only_privilege_raises_hard_limit,
+            &test_prlimit_needs_matching_ids,
+            &test_cpu_signal_schedule,
+            &test_fsize_allowance,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as rlimit_tests;
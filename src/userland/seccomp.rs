@@ -0,0 +1,667 @@
+//! Per-task syscall filters: `prctl(PR_SET_SECCOMP)` and `seccomp(2)`.
+//!
+//! [`check`] runs at the top of [`crate::userland::abi::syscall_dispatch`]
+//! before any handler, so the AgenticOS syscalls (5000 and up) can be
+//! filtered as well as the Linux ones. Policies stack: each one installed
+//! is consulted along with the earlier ones and the most restrictive
+//! verdict wins, as on Linux. `fork` and `clone` copy the caller's stack,
+//! `execve` keeps it, and nothing removes a policy once installed.
+//!
+//! Linux expresses filters as classic BPF. A policy here is a rule table
+//! instead, a [`PolicyHeader`] pointing at an array of [`PolicyRule`]s:
+//! the first rule whose syscall number and optional argument predicate
+//! match decides, otherwise the policy's default action does. Actions use
+//! the Linux `SECCOMP_RET_*` encoding; `KILL_PROCESS`, `KILL_THREAD`,
+//! `ERRNO` and `ALLOW` are supported. A BPF `struct sock_fprog` is
+//! refused with `EINVAL` since it cannot carry the header's magic.
+//! Killed tasks die by `SIGSYS`.
+//!
+//! `SECCOMP_MODE_STRICT` allows `read`, `write`, `exit` and `rt_sigreturn`
+//! only, and kills the process with `SIGKILL` on anything else.
+//!
+//! Installing a policy takes `no_new_privs` or an effective uid of 0.
+//! `no_new_privs` is sticky and inherited like the policies, and makes
+//! `execve` ignore set-user-ID and set-group-ID bits.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::userland::abi::{nr, EACCES, EINVAL, ENOMEM, EOPNOTSUPP, EPERM};
+use crate::userland::lifecycle::with_current_process;
+
+pub const PR_GET_SECCOMP: u64 = 21;
+pub const PR_SET_SECCOMP: u64 = 22;
+pub const PR_SET_NO_NEW_PRIVS: u64 = 38;
+pub const PR_GET_NO_NEW_PRIVS: u64 = 39;
+
+/// Modes reported by `PR_GET_SECCOMP` and accepted by `PR_SET_SECCOMP`.
+pub const SECCOMP_MODE_DISABLED: u64 = 0;
+pub const SECCOMP_MODE_STRICT: u64 = 1;
+pub const SECCOMP_MODE_FILTER: u64 = 2;
+
+/// `seccomp(2)` operations.
+pub const SECCOMP_SET_MODE_STRICT: u64 = 0;
+pub const SECCOMP_SET_MODE_FILTER: u64 = 1;
+pub const SECCOMP_GET_ACTION_AVAIL: u64 = 2;
+
+pub const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+pub const SECCOMP_RET_KILL_THREAD: u32 = 0;
+pub const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+pub const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+const SECCOMP_RET_ACTION_FULL: u32 = 0xffff_0000;
+const SECCOMP_RET_DATA: u32 = 0x0000_ffff;
+const MAX_ERRNO: u32 = 4095;
+
+/// [`PolicyHeader::magic`], `"SCMP"` in memory order.
+pub const POLICY_MAGIC: u32 = u32::from_le_bytes(*b"SCMP");
+/// [`PolicyRule::arg`] for a rule that matches on the syscall number alone.
+pub const ARG_NONE: u32 = u32::MAX;
+
+/// [`PolicyRule::op`] comparisons, numbered like libseccomp's
+/// `SCMP_CMP_*`. All compare the full 64-bit argument, unsigned.
+pub const CMP_NE: u32 = 1;
+pub const CMP_LT: u32 = 2;
+pub const CMP_LE: u32 = 3;
+pub const CMP_EQ: u32 = 4;
+pub const CMP_GE: u32 = 5;
+pub const CMP_GT: u32 = 6;
+/// `(arg & mask) == value`.
+pub const CMP_MASKED_EQ: u32 = 7;
+
+const MAX_POLICY_RULES: u32 = 4096;
+/// Rules across a task's whole stack, each policy's default counting as
+/// one. Linux bounds its filter chain the same way (`MAX_INSNS_PER_PATH`).
+const MAX_TOTAL_RULES: usize = 32768;
+
+const STRICT_ALLOWED: [u64; 4] = [nr::READ, nr::WRITE, nr::EXIT, nr::RT_SIGRETURN];
+
+/// Set once any task installs a policy or enters strict mode, so the
+/// dispatcher skips the process-table lookup until then.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Policy as passed to `seccomp(SECCOMP_SET_MODE_FILTER, 0, &header)`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PolicyHeader {
+    pub magic: u32,
+    /// `SECCOMP_RET_*` for syscalls no rule matches.
+    pub default_action: u32,
+    pub rule_count: u32,
+    /// Must be zero.
+    pub reserved: u32,
+    /// User address of `rule_count` [`PolicyRule`]s.
+    pub rules: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PolicyRule {
+    pub nr: u32,
+    /// `SECCOMP_RET_*` when the rule matches.
+    pub action: u32,
+    /// Argument index 0..=5, or [`ARG_NONE`] (with `op` zero).
+    pub arg: u32,
+    /// One of the `CMP_*` comparisons.
+    pub op: u32,
+    /// Only read by [`CMP_MASKED_EQ`].
+    pub mask: u64,
+    pub value: u64,
+}
+
+/// A decoded `SECCOMP_RET_*` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    KillProcess,
+    KillThread,
+    Errno(u16),
+    Allow,
+}
+
+impl Action {
+    fn decode(raw: u32) -> Option<Self> {
+        match raw & SECCOMP_RET_ACTION_FULL {
+            SECCOMP_RET_KILL_PROCESS => Some(Self::KillProcess),
+            SECCOMP_RET_KILL_THREAD => Some(Self::KillThread),
+            // Linux clamps the errno rather than rejecting it.
+            SECCOMP_RET_ERRNO => Some(Self::Errno((raw & SECCOMP_RET_DATA).min(MAX_ERRNO) as u16)),
+            SECCOMP_RET_ALLOW => Some(Self::Allow),
+            _ => None,
+        }
+    }
+
+    /// Lower is more restrictive.
+    fn precedence(self) -> u8 {
+        match self {
+            Self::KillProcess => 0,
+            Self::KillThread => 1,
+            Self::Errno(_) => 2,
+            Self::Allow => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compare {
+    Ne,
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+    MaskedEq(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Predicate {
+    arg: usize,
+    compare: Compare,
+    value: u64,
+}
+
+impl Predicate {
+    fn holds(&self, args: &[u64; 6]) -> bool {
+        let arg = args[self.arg];
+        match self.compare {
+            Compare::Ne => arg != self.value,
+            Compare::Lt => arg < self.value,
+            Compare::Le => arg <= self.value,
+            Compare::Eq => arg == self.value,
+            Compare::Ge => arg >= self.value,
+            Compare::Gt => arg > self.value,
+            Compare::MaskedEq(mask) => arg & mask == self.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rule {
+    nr: u64,
+    predicate: Option<Predicate>,
+    action: Action,
+}
+
+impl Rule {
+    fn parse(raw: &PolicyRule) -> Result<Self, i64> {
+        let action = Action::decode(raw.action).ok_or(EINVAL)?;
+        let predicate = match (raw.arg, raw.op) {
+            (ARG_NONE, 0) => None,
+            (arg @ 0..=5, op) => {
+                let compare = match op {
+                    CMP_NE => Compare::Ne,
+                    CMP_LT => Compare::Lt,
+                    CMP_LE => Compare::Le,
+                    CMP_EQ => Compare::Eq,
+                    CMP_GE => Compare::Ge,
+                    CMP_GT => Compare::Gt,
+                    CMP_MASKED_EQ => Compare::MaskedEq(raw.mask),
+                    _ => return Err(EINVAL),
+                };
+                Some(Predicate {
+                    arg: arg as usize,
+                    compare,
+                    value: raw.value,
+                })
+            }
+            _ => return Err(EINVAL),
+        };
+        Ok(Self {
+            nr: u64::from(raw.nr),
+            predicate,
+            action,
+        })
+    }
+}
+
+/// One installed rule table.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Policy {
+    rules: Vec<Rule>,
+    default: Action,
+}
+
+impl Policy {
+    pub fn parse(default_action: u32, rules: &[PolicyRule]) -> Result<Self, i64> {
+        Ok(Self {
+            rules: rules.iter().map(Rule::parse).collect::<Result<_, _>>()?,
+            default: Action::decode(default_action).ok_or(EINVAL)?,
+        })
+    }
+
+    fn evaluate(&self, nr: u64, args: &[u64; 6]) -> Action {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.nr == nr
+                    && rule
+                        .predicate
+                        .as_ref()
+                        .is_none_or(|predicate| predicate.holds(args))
+            })
+            .map_or(self.default, |rule| rule.action)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Disabled,
+    Strict,
+    Filter,
+}
+
+/// A task's filter state.
+#[derive(Debug, Clone, Default)]
+pub struct Seccomp {
+    mode: Mode,
+    /// Oldest first. Shared with the tasks that inherited them.
+    policies: Vec<Arc<Policy>>,
+    pub no_new_privs: bool,
+}
+
+impl Seccomp {
+    /// `PR_GET_SECCOMP` value.
+    pub fn mode(&self) -> u64 {
+        match self.mode {
+            Mode::Disabled => SECCOMP_MODE_DISABLED,
+            Mode::Strict => SECCOMP_MODE_STRICT,
+            Mode::Filter => SECCOMP_MODE_FILTER,
+        }
+    }
+
+    /// Verdict for syscall `nr` with arguments `args`. On a tie the most
+    /// recently installed policy decides, which only matters for the
+    /// errno an `ERRNO` verdict carries.
+    pub fn evaluate(&self, nr: u64, args: &[u64; 6]) -> Action {
+        match self.mode {
+            Mode::Disabled => Action::Allow,
+            Mode::Strict if STRICT_ALLOWED.contains(&nr) => Action::Allow,
+            Mode::Strict => Action::KillProcess,
+            Mode::Filter => self
+                .policies
+                .iter()
+                .rev()
+                .map(|policy| policy.evaluate(nr, args))
+                .fold(Action::Allow, |verdict, action| {
+                    if action.precedence() < verdict.precedence() {
+                        action
+                    } else {
+                        verdict
+                    }
+                }),
+        }
+    }
+
+    pub fn enter_strict(&mut self) -> Result<(), i64> {
+        if self.mode == Mode::Filter {
+            return Err(EINVAL);
+        }
+        self.mode = Mode::Strict;
+        ACTIVE.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn install(&mut self, policy: Policy) -> Result<(), i64> {
+        if self.mode == Mode::Strict {
+            return Err(EINVAL);
+        }
+        let installed: usize = self
+            .policies
+            .iter()
+            .map(|policy| policy.rules.len() + 1)
+            .sum();
+        if installed + policy.rules.len() + 1 > MAX_TOTAL_RULES {
+            return Err(ENOMEM);
+        }
+        self.policies.push(Arc::new(policy));
+        self.mode = Mode::Filter;
+        ACTIVE.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+fn syscall_arguments(args: &SyscallArgs) -> [u64; 6] {
+    [args.rdi, args.rsi, args.rdx, args.r10, args.r8, args.r9]
+}
+
+/// Apply the current task's filters to the syscall in `args`. `None` lets
+/// it run; `Some(result)` is returned to the caller in its place. Killing
+/// verdicts do not return while a ring-3 task is loaded.
+pub fn check(args: &SyscallArgs) -> Option<i64> {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return None;
+    }
+    let (action, strict) = with_current_process(|process| {
+        let seccomp = &process.seccomp;
+        (
+            seccomp.evaluate(args.rax, &syscall_arguments(args)),
+            seccomp.mode == Mode::Strict,
+        )
+    });
+    match action {
+        Action::Allow => None,
+        Action::Errno(errno) => Some(-i64::from(errno)),
+        Action::KillThread | Action::KillProcess => {
+            use crate::userland::signal::{SIGKILL, SIGSYS};
+            let sig = if strict { SIGKILL } else { SIGSYS };
+            kill_current(action == Action::KillThread, sig, args.rax);
+            // Synthetic dispatcher tests have no task to kill.
+            Some(EPERM)
+        }
+    }
+}
+
+fn kill_current(thread_only: bool, sig: i32, nr: u64) {
+    use crate::userland::lifecycle::{current_user_pid, KERNEL_PID};
+    let Some(tid) = current_user_pid().filter(|&tid| tid != KERNEL_PID) else {
+        return;
+    };
+    crate::debug_info!("seccomp: pid={} denied syscall nr={}", tid, nr);
+    let tgid = crate::userland::lifecycle::task_tgid(tid).unwrap_or(tid);
+    if thread_only && crate::userland::lifecycle::group_live_member_count(tgid) > 1 {
+        crate::userland::lifecycle::cooperative_thread_exit(128 + sig as i64);
+    }
+    crate::userland::syscalls::terminate_current_by_signal(sig);
+}
+
+fn read_policy(ptr: u64) -> Result<Policy, i64> {
+    use crate::userland::usercopy::read_unaligned;
+    let header: PolicyHeader = read_unaligned(ptr)?;
+    if header.magic != POLICY_MAGIC || header.reserved != 0 {
+        return Err(EINVAL);
+    }
+    if header.rule_count > MAX_POLICY_RULES {
+        return Err(EINVAL);
+    }
+    let rules = (0..u64::from(header.rule_count))
+        .map(|index| {
+            read_unaligned::<PolicyRule>(
+                header.rules + index * core::mem::size_of::<PolicyRule>() as u64,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Policy::parse(header.default_action, &rules)
+}
+
+fn set_strict() -> i64 {
+    match with_current_process(|process| process.seccomp.enter_strict()) {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
+
+fn set_filter(ptr: u64) -> i64 {
+    let privileged = crate::userland::credentials::current().privileged();
+    let policy = match read_policy(ptr) {
+        Ok(policy) => policy,
+        Err(e) => return e,
+    };
+    let installed = with_current_process(|process| {
+        if !process.seccomp.no_new_privs && !privileged {
+            return Err(EACCES);
+        }
+        process.seccomp.install(policy)
+    });
+    match installed {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
+
+/// `prctl(option, arg2, arg3, arg4, arg5)`. Only the seccomp and
+/// `no_new_privs` options are implemented; the rest fail with `EINVAL`.
+pub fn prctl_handler(args: &mut SyscallArgs) -> i64 {
+    let unused_from_arg3 = args.rdx | args.r10 | args.r8;
+    match args.rdi {
+        PR_SET_NO_NEW_PRIVS => {
+            if args.rsi != 1 || unused_from_arg3 != 0 {
+                return EINVAL;
+            }
+            with_current_process(|process| process.seccomp.no_new_privs = true);
+            0
+        }
+        PR_GET_NO_NEW_PRIVS => {
+            if args.rsi | unused_from_arg3 != 0 {
+                return EINVAL;
+            }
+            with_current_process(|process| process.seccomp.no_new_privs) as i64
+        }
+        PR_GET_SECCOMP => with_current_process(|process| process.seccomp.mode()) as i64,
+        PR_SET_SECCOMP => match args.rsi {
+            SECCOMP_MODE_STRICT => set_strict(),
+            SECCOMP_MODE_FILTER => set_filter(args.rdx),
+            _ => EINVAL,
+        },
+        _ => EINVAL,
+    }
+}
+
+/// `seccomp(operation, flags, args)`. No flags are supported.
+pub fn seccomp_handler(args: &mut SyscallArgs) -> i64 {
+    if args.rsi != 0 {
+        return EINVAL;
+    }
+    match args.rdi {
+        SECCOMP_SET_MODE_STRICT if args.rdx == 0 => set_strict(),
+        SECCOMP_SET_MODE_FILTER => set_filter(args.rdx),
+        SECCOMP_GET_ACTION_AVAIL => {
+            match crate::userland::usercopy::read_unaligned::<u32>(args.rdx) {
+                Ok(raw) if raw & SECCOMP_RET_DATA == 0 => {
+                    Action::decode(raw).map_or(EOPNOTSUPP, |_| 0)
+                }
+                Ok(_) => EOPNOTSUPP,
+                Err(e) => e,
+            }
+        }
+        _ => EINVAL,
+    }
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn rule(nr: u64, action: u32) -> PolicyRule {
+        PolicyRule {
+            nr: nr as u32,
+            action,
+            arg: ARG_NONE,
+            ..PolicyRule::default()
+        }
+    }
+
+    fn test_policy_layout() {
+        assert_eq!(core::mem::size_of::<PolicyHeader>(), 24);
+        assert_eq!(core::mem::size_of::<PolicyRule>(), 32);
+        assert_eq!(core::mem::offset_of!(PolicyRule, mask), 16);
+    }
+
+    fn test_first_matching_rule_decides() {
+        let rules = [
+            // write(2, …) is allowed, any other write is refused.
+            PolicyRule {
+                arg: 0,
+                op: CMP_EQ,
+                value: 2,
+                ..rule(nr::WRITE, SECCOMP_RET_ALLOW)
+            },
+            rule(nr::WRITE, SECCOMP_RET_ERRNO | 1),
+            // mmap with PROT_EXEC.
+            PolicyRule {
+                arg: 2,
+                op: CMP_MASKED_EQ,
+                mask: 0x4,
+                value: 0x4,
+                ..rule(nr::MMAP, SECCOMP_RET_ERRNO | 13)
+            },
+            rule(nr::SYSTEM_CONTROL, SECCOMP_RET_KILL_PROCESS),
+        ];
+        let policy = Policy::parse(SECCOMP_RET_ALLOW, &rules).unwrap();
+        let args = |first: u64, third: u64| [first, 0, third, 0, 0, 0];
+        assert_eq!(policy.evaluate(nr::WRITE, &args(2, 0)), Action::Allow);
+        assert_eq!(policy.evaluate(nr::WRITE, &args(1, 0)), Action::Errno(1));
+        assert_eq!(policy.evaluate(nr::MMAP, &args(0, 0x3)), Action::Allow);
+        assert_eq!(policy.evaluate(nr::MMAP, &args(0, 0x5)), Action::Errno(13));
+        assert_eq!(
+            policy.evaluate(nr::SYSTEM_CONTROL, &args(0, 0)),
+            Action::KillProcess
+        );
+        assert_eq!(policy.evaluate(nr::GETPID, &args(0, 0)), Action::Allow);
+
+        let deny_by_default = Policy::parse(
+            SECCOMP_RET_ERRNO | 99_999,
+            &[rule(nr::READ, SECCOMP_RET_ALLOW)],
+        )
+        .unwrap();
+        assert_eq!(
+            deny_by_default.evaluate(nr::READ, &args(0, 0)),
+            Action::Allow
+        );
+        assert_eq!(
+            deny_by_default.evaluate(nr::OPEN, &args(0, 0)),
+            Action::Errno(MAX_ERRNO as u16)
+        );
+    }
+
+    fn test_rejects_malformed_rules() {
+        let bad_action = rule(nr::READ, 0x7ffc_0000);
+        assert_eq!(Policy::parse(SECCOMP_RET_ALLOW, &[bad_action]), Err(EINVAL));
+        assert_eq!(Policy::parse(0x0003_0000, &[]), Err(EINVAL));
+        let bad_arg = PolicyRule {
+            arg: 6,
+            op: CMP_EQ,
+            ..rule(nr::READ, SECCOMP_RET_ALLOW)
+        };
+        assert_eq!(Policy::parse(SECCOMP_RET_ALLOW, &[bad_arg]), Err(EINVAL));
+        let bad_op = PolicyRule {
+            arg: 0,
+            op: 8,
+            ..rule(nr::READ, SECCOMP_RET_ALLOW)
+        };
+        assert_eq!(Policy::parse(SECCOMP_RET_ALLOW, &[bad_op]), Err(EINVAL));
+        let op_without_arg = PolicyRule {
+            op: CMP_EQ,
+            ..rule(nr::READ, SECCOMP_RET_ALLOW)
+        };
+        assert_eq!(
+            Policy::parse(SECCOMP_RET_ALLOW, &[op_without_arg]),
+            Err(EINVAL)
+        );
+    }
+
+    fn test_stacked_policies_most_restrictive_wins() {
+        let mut seccomp = Seccomp::default();
+        let args = [0; 6];
+        seccomp
+            .install(
+                Policy::parse(
+                    SECCOMP_RET_ALLOW,
+                    &[
+                        rule(nr::GETPID, SECCOMP_RET_ERRNO | 1),
+                        rule(nr::GETPPID, SECCOMP_RET_ERRNO | 1),
+                    ],
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        seccomp
+            .install(
+                Policy::parse(
+                    SECCOMP_RET_ALLOW,
+                    &[
+                        rule(nr::GETPID, SECCOMP_RET_KILL_THREAD),
+                        rule(nr::GETPPID, SECCOMP_RET_ERRNO | 13),
+                    ],
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(seccomp.mode(), SECCOMP_MODE_FILTER);
+        assert_eq!(seccomp.evaluate(nr::GETPID, &args), Action::KillThread);
+        assert_eq!(seccomp.evaluate(nr::GETPPID, &args), Action::Errno(13));
+        assert_eq!(seccomp.evaluate(nr::GETTID, &args), Action::Allow);
+        // Children start from a copy and can only add to it.
+        let child = seccomp.clone();
+        assert_eq!(child.evaluate(nr::GETPID, &args), Action::KillThread);
+        assert_eq!(seccomp.enter_strict(), Err(EINVAL));
+    }
+
+    fn test_strict_mode() {
+        let mut seccomp = Seccomp::default();
+        seccomp.enter_strict().unwrap();
+        assert_eq!(seccomp.mode(), SECCOMP_MODE_STRICT);
+        let args = [0; 6];
+        for nr in STRICT_ALLOWED {
+            assert_eq!(seccomp.evaluate(nr, &args), Action::Allow);
+        }
+        assert_eq!(seccomp.evaluate(nr::EXIT_GROUP, &args), Action::KillProcess);
+        assert_eq!(
+            seccomp.install(Policy::parse(SECCOMP_RET_ALLOW, &[]).unwrap()),
+            Err(EINVAL)
+        );
+    }
+
+    fn test_dispatch_applies_current_filters() {
+        let policy = Policy::parse(
+            SECCOMP_RET_ALLOW,
+            &[
+                rule(nr::GETPPID, SECCOMP_RET_ERRNO | 1),
+                rule(nr::SYSTEM_CONTROL, SECCOMP_RET_ERRNO | 13),
+            ],
+        )
+        .unwrap();
+        let saved = with_current_process(|process| process.seccomp.clone());
+        with_current_process(|process| process.seccomp.install(policy)).unwrap();
+        let mut args = SyscallArgs {
+            rax: nr::GETPPID,
+            ..SyscallArgs::default()
+        };
+        assert_eq!(crate::userland::abi::syscall_dispatch(&mut args), EPERM);
+        args.rax = nr::SYSTEM_CONTROL;
+        assert_eq!(crate::userland::abi::syscall_dispatch(&mut args), EACCES);
+        args.rax = nr::PRCTL;
+        args.rdi = PR_GET_SECCOMP;
+        assert_eq!(
+            crate::userland::abi::syscall_dispatch(&mut args),
+            SECCOMP_MODE_FILTER as i64
+        );
+        with_current_process(|process| process.seccomp = saved);
+    }
+
+    fn test_prctl_argument_checks() {
+        let mut args = SyscallArgs {
+            rdi: PR_SET_NO_NEW_PRIVS,
+            rsi: 2,
+            ..SyscallArgs::default()
+        };
+        assert_eq!(prctl_handler(&mut args), EINVAL);
+        args.rdi = 0x5345_5400;
+        assert_eq!(prctl_handler(&mut args), EINVAL);
+        let mut args = SyscallArgs {
+            rdi: SECCOMP_SET_MODE_FILTER,
+            rsi: 1,
+            ..SyscallArgs::default()
+        };
+        assert_eq!(seccomp_handler(&mut args), EINVAL);
+        args.rdi = SECCOMP_SET_MODE_STRICT;
+        args.rsi = 0;
+        args.rdx = 0x1000;
+        assert_eq!(seccomp_handler(&mut args), EINVAL);
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_policy_layout,
+            &test_first_matching_rule_decides,
+            &test_rejects_malformed_rules,
+            &test_stacked_policies_most_restrictive_wins,
+            &test_strict_mode,
+            &test_dispatch_applies_current_filters,
+            &test_prctl_argument_checks,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as seccomp_tests;
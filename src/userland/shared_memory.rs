@@ -0,0 +1,250 @@
+//! Page owners for `MAP_SHARED` mappings.
+//!
+//! A private mapping gets its own frame per page and `fork` shares those
+//! frames copy-on-write. A shared mapping instead names a [`SharedObject`]:
+//! the object owns one frame per resident page, and every leaf that maps
+//! the page — in any address space, before or after `fork` — points at that
+//! same frame and is never marked COW. Stores are therefore visible to all
+//! mappers immediately.
+//!
+//! Anonymous shared memory is an object with no backing file; it lives as
+//! long as some VMA references it. A file object is populated from the file
+//! on first touch and writes its dirty pages back on `msync`, `munmap`, and
+//! when the last mapping goes away. A page is dirty once the hardware dirty
+//! bit of a leaf that maps it has been seen set; [`harvest_dirty`] collects
+//! those bits from one address space before its leaves are synced or torn
+//! down, so stores through another process reach the file when that process
+//! syncs, unmaps or exits. File objects are registered by
+//! [`FileIdentity`], so two independent `mmap` calls of one file share
+//! pages, while a file created at the same path after an unlink or a rename
+//! over it gets an object of its own. Files on a filesystem without inode
+//! numbers (FAT) fall back to their normalized path.
+//!
+//! Pages are not coherent with `read`/`write` on the same file: a `write`
+//! is seen by a mapping only for pages it has not yet faulted in.
+//!
+//! Lock order: the page map is never held across file I/O or while the
+//! memory mapper is locked, because mapper-held code must not allocate.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use spin::Mutex;
+use x86_64::structures::paging::{PageTableFlags, PhysFrame};
+use x86_64::VirtAddr;
+
+use crate::fs::file_handle::FileError;
+use crate::fs::filesystem::FileIdentity;
+use crate::fs::File;
+use crate::lib::arc::{Arc, Weak};
+use crate::userland::vm::Vma;
+
+const PAGE_SIZE: u64 = 0x1000;
+/// Leaves [`harvest_dirty`] scans per memory-mapper lock.
+const HARVEST_BATCH: usize = 64;
+
+struct SharedPage {
+    frame: PhysFrame,
+    /// Stored to since the last successful writeback. Only such pages are
+    /// written back, so read-only sharing never rewrites the file.
+    dirty: bool,
+}
+
+struct FileSource {
+    /// The most capable handle attached so far and whether it was opened
+    /// for writing. Writeback goes through this handle.
+    handle: Mutex<(Arc<File>, bool)>,
+}
+
+pub struct SharedObject {
+    source: Option<FileSource>,
+    pages: Mutex<BTreeMap<u64, SharedPage>>,
+}
+
+/// How [`FILE_OBJECTS`] finds the object of a file.
+#[derive(PartialEq, Eq)]
+enum ObjectKey {
+    File(FileIdentity),
+    Path(String),
+}
+
+/// Live file objects. Dead entries are pruned on the next lookup. Only a
+/// matching entry is upgraded, so no object can reach its final drop (and
+/// writeback I/O) while this lock is held.
+static FILE_OBJECTS: Mutex<Vec<(ObjectKey, Weak<SharedObject>)>> = Mutex::new(Vec::new());
+
+impl SharedObject {
+    /// A fresh zero-filled object for `MAP_SHARED | MAP_ANONYMOUS`.
+    pub fn anonymous() -> Arc<Self> {
+        Arc::new(Self {
+            source: None,
+            pages: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// The object that backs shared mappings of `file`, created on first
+    /// use. `writable` records whether `file` was opened for writing; a
+    /// writable handle replaces a read-only one so later writeback works.
+    pub fn for_file(file: &Arc<File>, writable: bool) -> Arc<Self> {
+        let key = match file.identity() {
+            Some(identity) => ObjectKey::File(identity),
+            None => ObjectKey::Path(file.path()),
+        };
+        let mut objects = FILE_OBJECTS.lock();
+        objects.retain(|(_, object)| object.strong_count() > 0);
+        let existing = objects
+            .iter()
+            .find(|(existing, _)| *existing == key)
+            .and_then(|(_, object)| object.upgrade());
+        if let Some(object) = existing {
+            if let Some(source) = object.source.as_ref() {
+                let mut handle = source.handle.lock();
+                if writable || !handle.1 {
+                    *handle = (file.clone(), writable);
+                }
+            }
+            return object;
+        }
+        let object = Arc::new(Self {
+            source: Some(FileSource {
+                handle: Mutex::new((file.clone(), writable)),
+            }),
+            pages: Mutex::new(BTreeMap::new()),
+        });
+        objects.push((key, Arc::downgrade(&object)));
+        object
+    }
+
+    /// The backing file's current handle, or `None` for anonymous memory.
+    pub fn file(&self) -> Option<Arc<File>> {
+        self.source
+            .as_ref()
+            .map(|source| source.handle.lock().0.clone())
+    }
+
+    /// True when this object holds `file` as its backing handle.
+    pub fn is_backed_by(&self, file: &Arc<File>) -> bool {
+        self.source
+            .as_ref()
+            .is_some_and(|source| Arc::ptr_eq(&source.handle.lock().0, file))
+    }
+
+    /// Resident frame for the page at byte `offset`, if any.
+    pub fn page(&self, offset: u64) -> Option<PhysFrame> {
+        self.pages
+            .lock()
+            .get(&(offset / PAGE_SIZE))
+            .map(|page| page.frame)
+    }
+
+    /// Adopt a populated frame for the page at `offset`. If another fault
+    /// installed the page first, its frame wins and is returned with
+    /// `false`; the caller still owns `frame` and must release it.
+    pub fn install(&self, offset: u64, frame: PhysFrame) -> (PhysFrame, bool) {
+        let mut pages = self.pages.lock();
+        let page = pages.entry(offset / PAGE_SIZE).or_insert(SharedPage {
+            frame,
+            dirty: false,
+        });
+        (page.frame, page.frame == frame)
+    }
+
+    /// Write every dirty page in `[start, end)` back to the file and mark it
+    /// clean. Bytes past the current end of file stay in memory only, as on
+    /// Linux.
+    pub fn writeback(&self, start: u64, end: u64) -> Result<(), FileError> {
+        let Some(file) = self.file() else {
+            return Ok(());
+        };
+        let dirty: Vec<(u64, PhysFrame)> = self
+            .pages
+            .lock()
+            .range(start / PAGE_SIZE..end.div_ceil(PAGE_SIZE))
+            .filter(|(_, page)| page.dirty)
+            .map(|(index, page)| (index * PAGE_SIZE, page.frame))
+            .collect();
+        for (offset, frame) in dirty {
+            let size = file.size();
+            if offset >= size {
+                break;
+            }
+            let len = (size - offset).min(PAGE_SIZE) as usize;
+            let virtual_address = crate::mm::memory::phys_to_virt(frame.start_address().as_u64())
+                .ok_or(FileError::IoError)?;
+            // SAFETY: the object holds a reference to `frame` until it is
+            // dropped, and the physical alias maps every managed frame.
+            let bytes = unsafe { core::slice::from_raw_parts(virtual_address as *const u8, len) };
+            if file.write_at(offset, bytes)? != len {
+                return Err(FileError::IoError);
+            }
+            // A store since the harvest set the leaf's dirty bit again, so
+            // clearing the page here loses nothing.
+            if let Some(page) = self.pages.lock().get_mut(&(offset / PAGE_SIZE)) {
+                page.dirty = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Move the hardware dirty bits of the leaves that map `[start, end)` of
+/// `vma` in the address space `l4` onto its object's pages, clearing them
+/// in the leaves. Anonymous objects have nothing to write back and are
+/// skipped.
+pub fn harvest_dirty(l4: PhysFrame, vma: &Vma, start: u64, end: u64) {
+    let Some((object, _)) = vma.shared_at(vma.start) else {
+        return;
+    };
+    if object.source.is_none() {
+        return;
+    }
+    let mut page = start.max(vma.start);
+    let end = end.min(vma.end);
+    while page < end {
+        let mut dirty = [0u64; HARVEST_BATCH];
+        let mut count = 0;
+        let scanned = crate::mm::memory::with_memory_mapper(|mapper| {
+            while page < end && count < HARVEST_BATCH {
+                if let Some((_, flags)) = mapper.leaf_info(l4, VirtAddr::new(page)) {
+                    if flags.contains(PageTableFlags::DIRTY) {
+                        let _ = mapper.set_leaf_flags(
+                            l4,
+                            VirtAddr::new(page),
+                            flags - PageTableFlags::DIRTY,
+                        );
+                        dirty[count] = page;
+                        count += 1;
+                    }
+                }
+                page += PAGE_SIZE;
+            }
+        });
+        if scanned.is_none() {
+            return;
+        }
+        let mut pages = object.pages.lock();
+        for &address in &dirty[..count] {
+            let offset = vma.shared_at(address).map_or(0, |(_, offset)| offset);
+            if let Some(page) = pages.get_mut(&(offset / PAGE_SIZE)) {
+                page.dirty = true;
+            }
+        }
+    }
+}
+
+impl Drop for SharedObject {
+    fn drop(&mut self) {
+        if self.source.is_some() && self.writeback(0, u64::MAX).is_err() {
+            crate::debug_warn!("shared mapping: final writeback failed");
+        }
+        let frames: Vec<PhysFrame> = core::mem::take(self.pages.get_mut())
+            .into_values()
+            .map(|page| page.frame)
+            .collect();
+        crate::mm::memory::with_memory_mapper(|mapper| {
+            for frame in &frames {
+                mapper.release_shared_frame(*frame);
+            }
+        });
+    }
+}
@@ -0,0 +1,162 @@
+//! `#!` interpreter scripts for `execve`.
+//!
+//! A file whose first bytes are `#!` names the program that should run it.
+//! Linux (`fs/binfmt_script.c`) parses the first line as
+//! `#!interpreter [arg]` — everything after the interpreter, trimmed, is a
+//! single optional argument and is never split further — and rewrites the
+//! argument vector:
+//!
+//! ```text
+//!   execve("./run.sh", ["run.sh", "a", "b"])   with "#!/bin/sh -e"
+//!   becomes
+//!   execve("/bin/sh",  ["/bin/sh", "-e", "./run.sh", "a", "b"])
+//! ```
+//!
+//! The script's `argv[0]` is dropped and the path passed to `execve` takes
+//! its place after the interpreter. The interpreter may itself be a script;
+//! the chain is bounded by [`MAX_DEPTH`] and longer chains fail with
+//! `ELOOP`. Since the interpreter goes back through the normal exec path,
+//! `#!/bin/sh` resolves through the virtual `/bin` namespace to BusyBox.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::userland::abi::ENOEXEC;
+
+/// Interpreter indirections allowed before `ELOOP`, as Linux's
+/// `BINPRM_MAX_RECURSION`.
+pub const MAX_DEPTH: usize = 4;
+
+/// Bytes of the file examined for the `#!` line (Linux `BINPRM_BUF_SIZE`).
+pub const LINE_MAX: usize = 256;
+
+/// Parsed `#!` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shebang {
+    pub interpreter: String,
+    pub arg: Option<String>,
+}
+
+/// Parse the `#!` line at the head of `bytes`.
+///
+/// Returns `Ok(None)` when the file is not a script, and `Err(ENOEXEC)`
+/// for a script whose header names no interpreter or does not fit in
+/// [`LINE_MAX`] bytes.
+pub fn parse(bytes: &[u8]) -> Result<Option<Shebang>, i64> {
+    if !bytes.starts_with(b"#!") {
+        return Ok(None);
+    }
+    let head = &bytes[2..bytes.len().min(LINE_MAX)];
+    let line = match head.iter().position(|&b| b == b'\n') {
+        Some(end) => &head[..end],
+        // Linux refuses a header that runs past its buffer rather than
+        // run a truncated interpreter path; a short file is one line.
+        None if bytes.len() > LINE_MAX => return Err(ENOEXEC),
+        None => head,
+    };
+    let line = core::str::from_utf8(line).map_err(|_| ENOEXEC)?;
+    let line = line.trim_matches(|c| c == ' ' || c == '\t' || c == '\r');
+    let (interpreter, rest) = match line.find([' ', '\t']) {
+        Some(split) => (&line[..split], &line[split..]),
+        None => (line, ""),
+    };
+    if interpreter.is_empty() {
+        return Err(ENOEXEC);
+    }
+    let rest = rest.trim_matches(|c| c == ' ' || c == '\t');
+    Ok(Some(Shebang {
+        interpreter: String::from(interpreter),
+        arg: (!rest.is_empty()).then(|| String::from(rest)),
+    }))
+}
+
+/// Build the interpreter's argv from the script's: the interpreter, its
+/// optional argument, the script path as given to `execve`, then the
+/// script's own arguments after `argv[0]`.
+pub fn rewrite_argv(shebang: &Shebang, script_path: &str, argv: Vec<String>) -> Vec<String> {
+    let mut out = Vec::with_capacity(argv.len() + 2);
+    out.push(shebang.interpreter.clone());
+    if let Some(arg) = &shebang.arg {
+        out.push(arg.clone());
+    }
+    out.push(String::from(script_path));
+    out.extend(argv.into_iter().skip(1));
+    out
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+    use alloc::vec;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| String::from(*s)).collect()
+    }
+
+    fn test_parse_not_a_script() {
+        assert_eq!(parse(b"\x7fELF\x02\x01"), Ok(None));
+        assert_eq!(parse(b"#"), Ok(None));
+        assert_eq!(parse(b""), Ok(None));
+    }
+
+    fn test_parse_interpreter_only() {
+        let got = parse(b"#!/bin/sh\necho hi\n").unwrap().unwrap();
+        assert_eq!(got.interpreter, "/bin/sh");
+        assert_eq!(got.arg, None);
+        // Leading blanks and a CRLF ending are tolerated.
+        let got = parse(b"#! \t/bin/sh\r\n").unwrap().unwrap();
+        assert_eq!(got.interpreter, "/bin/sh");
+        assert_eq!(got.arg, None);
+    }
+
+    fn test_parse_single_unsplit_arg() {
+        let got = parse(b"#!/usr/bin/env  python3 -u \nprint()\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(got.interpreter, "/usr/bin/env");
+        assert_eq!(got.arg.as_deref(), Some("python3 -u"));
+    }
+
+    fn test_parse_rejects_empty_and_overlong() {
+        assert_eq!(parse(b"#!\n"), Err(ENOEXEC));
+        assert_eq!(parse(b"#!   \n"), Err(ENOEXEC));
+        let mut long = vec![b'#', b'!', b'/'];
+        long.resize(LINE_MAX + 8, b'a');
+        assert_eq!(parse(&long), Err(ENOEXEC));
+        // A newline-less script shorter than the buffer is one line.
+        assert_eq!(parse(b"#!/bin/sh").unwrap().unwrap().interpreter, "/bin/sh");
+    }
+
+    fn test_rewrite_argv_matches_linux() {
+        let sb = Shebang {
+            interpreter: String::from("/bin/sh"),
+            arg: Some(String::from("-e")),
+        };
+        assert_eq!(
+            rewrite_argv(&sb, "./run.sh", strings(&["run.sh", "a", "b"])),
+            strings(&["/bin/sh", "-e", "./run.sh", "a", "b"])
+        );
+        let bare = Shebang {
+            interpreter: String::from("/bin/sh"),
+            arg: None,
+        };
+        // An empty argv has no argv[0] to replace.
+        assert_eq!(
+            rewrite_argv(&bare, "/work/x", Vec::new()),
+            strings(&["/bin/sh", "/work/x"])
+        );
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_parse_not_a_script,
+            &test_parse_interpreter_only,
+            &test_parse_single_unsplit_arg,
+            &test_parse_rejects_empty_and_overlong,
+            &test_rewrite_argv_matches_linux,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as shebang_tests;
@@ -25,6 +25,7 @@ pub const SIGHUP: i32 = 1;
 pub const SIGINT: i32 = 2;
 pub const SIGQUIT: i32 = 3;
 pub const SIGILL: i32 = 4;
+pub const SIGTRAP: i32 = 5;
 pub const SIGFPE: i32 = 8;
 pub const SIGKILL: i32 = 9;
 #[cfg_attr(not(feature = "test"), expect(dead_code, reason = "QEMU test API"))]
@@ -39,21 +40,37 @@ pub const SIGCHLD: i32 = 17;
 pub const SIGCONT: i32 = 18;
 pub const SIGBUS: i32 = 7;
 pub const SIGSTOP: i32 = 19;
+pub const SIGTSTP: i32 = 20;
+pub const SIGTTIN: i32 = 21;
+pub const SIGTTOU: i32 = 22;
 pub const SIGURG: i32 = 23;
+pub const SIGXCPU: i32 = 24;
+pub const SIGXFSZ: i32 = 25;
 pub const SIGWINCH: i32 = 28;
+pub const SIGSYS: i32 = 31;
 
-/// Signals whose Linux default disposition terminates the process
-/// (with or without core — we don't dump cores, so the distinction
-/// collapses). Everything not listed here and not in
-/// [`default_action_ignores`] is treated as terminate too, matching
-/// Linux where "ignore by default" is the short list.
+/// Signals whose Linux default disposition is to ignore them.
+/// Everything not listed here and not in [`default_action_stops`]
+/// terminates the process, matching Linux where "ignore by default" is
+/// the short list. Core dumps are written only for ring-3 faults (see
+/// [`crate::userland::coredump`]), so a core-action signal sent with
+/// `kill` terminates without one. SIGCONT's resume happens when it is
+/// sent, whatever its disposition; its delivery default is to ignore.
 pub fn default_action_ignores(sig: i32) -> bool {
-    // SIGSTOP/SIGCONT job control is unimplemented — treating a
-    // pending SIGSTOP/SIGCONT as ignorable (rather than wedging the
-    // pending bit forever) is the least-surprising degradation.
-    matches!(sig, SIGCHLD | SIGCONT | SIGSTOP | SIGURG | SIGWINCH)
+    matches!(sig, SIGCHLD | SIGCONT | SIGURG | SIGWINCH)
 }
 
+/// Signals whose default disposition stops the process until SIGCONT.
+pub fn default_action_stops(sig: i32) -> bool {
+    matches!(sig, SIGSTOP | SIGTSTP | SIGTTIN | SIGTTOU)
+}
+
+/// Pending-mask bits of the four stop signals.
+const STOP_MASK: u64 = (1u64 << (SIGSTOP - 1))
+    | (1u64 << (SIGTSTP - 1))
+    | (1u64 << (SIGTTIN - 1))
+    | (1u64 << (SIGTTOU - 1));
+
 // ---- well-known sa_handler sentinels ----
 pub const SIG_DFL: u64 = 0;
 pub const SIG_IGN: u64 = 1;
@@ -176,13 +193,41 @@ impl SignalState {
     /// Mark `sig` as pending. Silently ignores invalid signal numbers
     /// rather than panicking — kernel-side callers should rarely hit
     /// invalid sig numbers, but defensive treatment matches Linux.
+    ///
+    /// As on Linux, SIGCONT discards any pending stop signal and a stop
+    /// signal discards a pending SIGCONT: only the most recent of the
+    /// two can take effect.
     pub fn raise(&mut self, sig: i32) {
         if sig < 1 || (sig as usize) > NSIG {
             return;
         }
+        if sig == SIGCONT {
+            self.pending &= !STOP_MASK;
+        } else if default_action_stops(sig) {
+            self.pending &= !(1u64 << (SIGCONT - 1));
+        }
         self.pending |= 1u64 << (sig - 1);
     }
 
+    /// Whether `sig` is pending, blocked or not.
+    pub fn is_pending(&self, sig: i32) -> bool {
+        (1..=NSIG as i32).contains(&sig) && self.pending & (1u64 << (sig - 1)) != 0
+    }
+
+    /// Whether raising `sig` would have no effect: blocked, or ignored
+    /// either explicitly or by default. Job control uses this to decide
+    /// between SIGTTIN and `-EIO` for a background terminal read.
+    pub fn discards(&self, sig: i32) -> bool {
+        if sig < 1 || (sig as usize) > NSIG {
+            return true;
+        }
+        if self.blocked & (1u64 << (sig - 1)) != 0 {
+            return true;
+        }
+        let act = self.action(sig).unwrap_or_default();
+        act.sa_handler == SIG_IGN || (act.sa_handler == SIG_DFL && default_action_ignores(sig))
+    }
+
     /// Set the action for `sig`, returning the previous one. SIGKILL
     /// and SIGSTOP cannot have their disposition changed (POSIX
     /// guarantee), so attempts return the existing action unchanged.
@@ -271,7 +316,7 @@ impl SignalState {
             if act.sa_handler != SIG_DFL {
                 continue; // real handler — the delivery path owns it
             }
-            if default_action_ignores(lowest) {
+            if default_action_ignores(lowest) || default_action_stops(lowest) {
                 continue;
             }
             return Some(lowest);
@@ -279,10 +324,29 @@ impl SignalState {
         None
     }
 
+    /// Take the lowest pending stop signal whose disposition is
+    /// `SIG_DFL`, clearing its pending bit. SIGSTOP ignores the blocked
+    /// mask. Callers check [`Self::take_fatal_default`] first, so a
+    /// pending SIGKILL wins over a stop.
+    pub fn take_stop_default(&mut self) -> Option<i32> {
+        let unblockable = 1u64 << (SIGSTOP - 1);
+        let mut bits = self.pending & (!self.blocked | unblockable) & STOP_MASK;
+        while bits != 0 {
+            let lowest = bits.trailing_zeros() as i32 + 1;
+            let mask = 1u64 << (lowest - 1);
+            bits &= !mask;
+            if self.action(lowest).unwrap_or_default().sa_handler == SIG_DFL {
+                self.pending &= !mask;
+                return Some(lowest);
+            }
+        }
+        None
+    }
+
     /// Whether any pending signal would actually do something if this
-    /// process reached the dispatcher now — either a deliverable user
-    /// handler or a fatal default action. Used by `kill(2)` to decide
-    /// whether a blocked target must be woken.
+    /// process reached the dispatcher now — a deliverable user handler,
+    /// or a fatal or stopping default action. Used by `kill(2)` to
+    /// decide whether a blocked target must be woken.
     pub fn has_actionable_pending(&self) -> bool {
         let unblockable = (1u64 << (SIGKILL - 1)) | (1u64 << (SIGSTOP - 1));
         let mut bits = self.pending & (!self.blocked | unblockable);
@@ -297,7 +361,7 @@ impl SignalState {
                 return true; // deliverable user handler
             }
             if !default_action_ignores(lowest) {
-                return true; // fatal default
+                return true; // fatal or stopping default
             }
         }
         false
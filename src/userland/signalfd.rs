@@ -0,0 +1,255 @@
+//! Linux signalfd open-file description.
+//!
+//! A signalfd names a set of signals. Reading it takes the lowest pending
+//! signals in the set off the reader's own pending set, one
+//! `struct signalfd_siginfo` each, instead of having them delivered.
+//! Callers block those signals first, as on Linux; an unblocked one may
+//! still be delivered before a read sees it. The kernel does not record
+//! who sent a signal, so only `ssi_signo` is filled in (`ssi_code` is
+//! `SI_USER`).
+//!
+//! Readiness depends on the reader, so it is sampled against the polling
+//! process. Raising a signal publishes a readiness change while any
+//! signalfd is open, which is what wakes a poller.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::lib::arc::Arc;
+use crate::userland::abi::{EAGAIN, EBADF, EFAULT, EINVAL, EMFILE};
+use crate::userland::fdtable::FdSlot;
+use crate::userland::signal::{SIGKILL, SIGSTOP};
+
+pub const SFD_NONBLOCK: u32 = 0x800;
+pub const SFD_CLOEXEC: u32 = 0x80000;
+
+/// Linux `struct signalfd_siginfo` (128 bytes).
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct SignalfdSiginfo {
+    ssi_signo: u32,
+    ssi_errno: i32,
+    ssi_code: i32,
+    ssi_pid: u32,
+    ssi_uid: u32,
+    ssi_fd: i32,
+    ssi_tid: u32,
+    ssi_band: u32,
+    ssi_overrun: u32,
+    ssi_trapno: u32,
+    ssi_status: i32,
+    ssi_int: i32,
+    ssi_ptr: u64,
+    ssi_utime: u64,
+    ssi_stime: u64,
+    ssi_addr: u64,
+    ssi_addr_lsb: u16,
+    _pad2: u16,
+    ssi_syscall: i32,
+    ssi_call_addr: u64,
+    ssi_arch: u32,
+    _pad: [u8; 28],
+}
+
+const SIGINFO_SIZE: usize = core::mem::size_of::<SignalfdSiginfo>();
+const _: () = assert!(SIGINFO_SIZE == 128);
+
+/// Open signalfds, so raising a signal skips the readiness publication
+/// when there are none.
+static OPEN_SIGNALFDS: AtomicUsize = AtomicUsize::new(0);
+
+pub struct SignalFd {
+    mask: AtomicU64,
+    nonblocking: AtomicBool,
+}
+
+/// SIGKILL and SIGSTOP cannot be read from a signalfd; Linux drops them
+/// from the mask silently.
+fn sanitize(mask: u64) -> u64 {
+    mask & !(1u64 << (SIGKILL - 1)) & !(1u64 << (SIGSTOP - 1))
+}
+
+/// Take up to `max` of the lowest signals in `mask` off `pending`.
+fn take_pending(pending: &mut u64, mask: u64, max: usize) -> Vec<i32> {
+    let mut taken = Vec::new();
+    while taken.len() < max {
+        let ready = *pending & mask;
+        if ready == 0 {
+            break;
+        }
+        let sig = ready.trailing_zeros() as i32 + 1;
+        *pending &= !(1u64 << (sig - 1));
+        taken.push(sig);
+    }
+    taken
+}
+
+impl SignalFd {
+    fn new(mask: u64, flags: u32) -> Arc<Self> {
+        OPEN_SIGNALFDS.fetch_add(1, Ordering::AcqRel);
+        Arc::new(Self {
+            mask: AtomicU64::new(sanitize(mask)),
+            nonblocking: AtomicBool::new(flags & SFD_NONBLOCK != 0),
+        })
+    }
+
+    pub fn nonblocking(&self) -> bool {
+        self.nonblocking.load(Ordering::Acquire)
+    }
+
+    pub fn set_nonblocking(&self, value: bool) {
+        self.nonblocking.store(value, Ordering::Release);
+    }
+
+    fn mask(&self) -> u64 {
+        self.mask.load(Ordering::Acquire)
+    }
+
+    /// Whether the calling process has a signal in the mask pending.
+    pub fn readable(&self) -> bool {
+        let mask = self.mask();
+        crate::userland::lifecycle::with_current_process(|p| p.signal_state.pending & mask != 0)
+    }
+
+    fn take(&self, max: usize) -> Vec<i32> {
+        let mask = self.mask();
+        crate::userland::lifecycle::with_current_process(|p| {
+            take_pending(&mut p.signal_state.pending, mask, max)
+        })
+    }
+}
+
+impl Drop for SignalFd {
+    fn drop(&mut self) {
+        OPEN_SIGNALFDS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A signal was raised on some process: pollers of a signalfd re-sample.
+pub fn signal_raised() {
+    if OPEN_SIGNALFDS.load(Ordering::Acquire) != 0 {
+        crate::userland::readiness::notify_changed();
+    }
+}
+
+/// `signalfd(fd, *mask, sizemask) -> fd`
+pub fn signalfd_handler(args: &mut SyscallArgs) -> i64 {
+    signalfd(args.rdi as i32, args.rsi, args.rdx, 0)
+}
+
+/// `signalfd4(fd, *mask, sizemask, flags) -> fd`
+pub fn signalfd4_handler(args: &mut SyscallArgs) -> i64 {
+    signalfd(args.rdi as i32, args.rsi, args.rdx, args.r10 as u32)
+}
+
+/// Create a signalfd for `fd == -1`, or replace the mask of the signalfd
+/// at `fd`.
+fn signalfd(fd: i32, mask_ptr: u64, sizemask: u64, flags: u32) -> i64 {
+    if flags & !(SFD_NONBLOCK | SFD_CLOEXEC) != 0 || sizemask != 8 {
+        return EINVAL;
+    }
+    let mask = match crate::userland::usercopy::read_unaligned::<u64>(mask_ptr) {
+        Ok(mask) => mask,
+        Err(_) => return EFAULT,
+    };
+    if fd != -1 {
+        return crate::userland::lifecycle::with_current_group(|process| {
+            match process.fd_table.get(fd) {
+                Some(FdSlot::SignalFd { handle, .. }) => {
+                    handle.mask.store(sanitize(mask), Ordering::Release);
+                    i64::from(fd)
+                }
+                Some(_) => EINVAL,
+                None => EBADF,
+            }
+        });
+    }
+    let slot = FdSlot::SignalFd {
+        handle: SignalFd::new(mask, flags),
+        cloexec: flags & SFD_CLOEXEC != 0,
+    };
+    crate::userland::lifecycle::with_current_group(|process| process.fd_table.alloc(slot))
+        .map_or(EMFILE, i64::from)
+}
+
+pub fn read(args: &SyscallArgs, handle: &Arc<SignalFd>, pointer: u64, len: u64) -> i64 {
+    let max = (len / SIGINFO_SIZE as u64) as usize;
+    if max == 0 {
+        return EINVAL;
+    }
+    // Check the whole buffer first: a taken signal cannot be put back.
+    if let Err(error) =
+        crate::userland::usercopy::ensure_user_range(pointer, (max * SIGINFO_SIZE) as u64, true)
+    {
+        return error;
+    }
+    let observed = crate::userland::readiness::sequence();
+    let taken = handle.take(max);
+    if taken.is_empty() {
+        if handle.nonblocking() {
+            return EAGAIN;
+        }
+        let identity = Arc::as_ptr(handle) as usize as u64;
+        return crate::userland::readiness::block(args, identity, None, observed);
+    }
+    for (index, &sig) in taken.iter().enumerate() {
+        let info = SignalfdSiginfo {
+            ssi_signo: sig as u32,
+            ..SignalfdSiginfo::default()
+        };
+        let address = pointer + (index * SIGINFO_SIZE) as u64;
+        if let Err(error) = crate::userland::usercopy::write_unaligned(address, &info) {
+            return error;
+        }
+    }
+    (taken.len() * SIGINFO_SIZE) as i64
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+    use crate::userland::signal::{SIGCHLD, SIGINT, SIGUSR1};
+
+    fn bit(sig: i32) -> u64 {
+        1u64 << (sig - 1)
+    }
+
+    fn test_mask_drops_unreadable_signals() {
+        let mask = sanitize(!0);
+        assert_eq!(mask & bit(SIGKILL), 0);
+        assert_eq!(mask & bit(SIGSTOP), 0);
+        assert_ne!(mask & bit(SIGCHLD), 0);
+    }
+
+    fn test_take_pending_lowest_first() {
+        let mut pending = bit(SIGCHLD) | bit(SIGINT) | bit(SIGUSR1);
+        let mask = bit(SIGCHLD) | bit(SIGINT);
+        assert_eq!(take_pending(&mut pending, mask, 1), [SIGINT]);
+        assert_eq!(pending, bit(SIGCHLD) | bit(SIGUSR1));
+        assert_eq!(take_pending(&mut pending, mask, 4), [SIGCHLD]);
+        // Signals outside the mask stay pending for normal delivery.
+        assert_eq!(pending, bit(SIGUSR1));
+        assert!(take_pending(&mut pending, mask, 4).is_empty());
+    }
+
+    fn test_siginfo_layout() {
+        use core::mem::offset_of;
+        assert_eq!(offset_of!(SignalfdSiginfo, ssi_code), 8);
+        assert_eq!(offset_of!(SignalfdSiginfo, ssi_ptr), 48);
+        assert_eq!(offset_of!(SignalfdSiginfo, ssi_addr_lsb), 80);
+        assert_eq!(offset_of!(SignalfdSiginfo, ssi_call_addr), 88);
+        assert_eq!(offset_of!(SignalfdSiginfo, ssi_arch), 96);
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_mask_drops_unreadable_signals,
+            &test_take_pending_lowest_first,
+            &test_siginfo_layout,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as signalfd_tests;
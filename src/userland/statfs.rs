@@ -0,0 +1,249 @@
+//! `statfs(2)` and `fstatfs(2)`.
+//!
+//! Paths and descriptors on a mount report that mount's
+//! [`Filesystem::stats`](crate::fs::filesystem::Filesystem::stats) under
+//! the Linux magic number for its type, so `df` and free-space checks see
+//! the same figures they would on Linux. The kernel-synthesized
+//! namespaces (`/proc`, `/dev`, `/dev/pts`) and descriptor-only objects
+//! (pipes, sockets, eventfd and friends) report their Linux magic with no
+//! blocks, like Linux's own pseudo filesystems.
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::fs::vfs::MountStats;
+use crate::userland::abi::{EBADF, ENOENT};
+use crate::userland::fdtable::FdSlot;
+use crate::userland::syscalls::{fd_slot, map_filesystem_err, resolve_user_path};
+
+pub const MSDOS_SUPER_MAGIC: i64 = 0x4d44;
+pub const EXT2_SUPER_MAGIC: i64 = 0xef53;
+pub const TMPFS_MAGIC: i64 = 0x0102_1994;
+pub const OVERLAYFS_SUPER_MAGIC: i64 = 0x794c_7630;
+pub const V9FS_MAGIC: i64 = 0x0102_1997;
+pub const PROC_SUPER_MAGIC: i64 = 0x9fa0;
+pub const DEVPTS_SUPER_MAGIC: i64 = 0x1cd1;
+pub const PIPEFS_MAGIC: i64 = 0x5049_5045;
+pub const SOCKFS_MAGIC: i64 = 0x534f_434b;
+pub const ANON_INODE_FS_MAGIC: i64 = 0x0904_1934;
+
+/// `f_flags` bits.
+const ST_RDONLY: i64 = 1;
+/// Tells callers `f_flags` is filled in (always, since Linux 2.6.36).
+const ST_VALID: i64 = 0x20;
+
+const NAME_MAX: i64 = 255;
+/// Block size reported by the pseudo filesystems.
+const PSEUDO_BLOCK_SIZE: i64 = 4096;
+
+/// `struct statfs` as the x86-64 kernel writes it.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinuxStatfs {
+    pub f_type: i64,
+    pub f_bsize: i64,
+    pub f_blocks: u64,
+    pub f_bfree: u64,
+    pub f_bavail: u64,
+    pub f_files: u64,
+    pub f_ffree: u64,
+    pub f_fsid: [i32; 2],
+    pub f_namelen: i64,
+    pub f_frsize: i64,
+    pub f_flags: i64,
+    pub f_spare: [i64; 4],
+}
+
+impl LinuxStatfs {
+    fn pseudo(f_type: i64) -> Self {
+        Self {
+            f_type,
+            f_bsize: PSEUDO_BLOCK_SIZE,
+            f_namelen: NAME_MAX,
+            f_frsize: PSEUDO_BLOCK_SIZE,
+            f_flags: ST_VALID,
+            ..Self::default()
+        }
+    }
+
+    fn from_mount(mount: &MountStats) -> Self {
+        let stats = &mount.stats;
+        Self {
+            f_type: magic(mount.fstype),
+            f_bsize: stats.block_size as i64,
+            f_blocks: stats.total_blocks,
+            f_bfree: stats.free_blocks,
+            f_bavail: stats.available_blocks,
+            f_files: stats.total_inodes,
+            f_ffree: stats.free_inodes,
+            f_namelen: NAME_MAX,
+            f_frsize: stats.block_size as i64,
+            f_flags: if mount.read_only {
+                ST_VALID | ST_RDONLY
+            } else {
+                ST_VALID
+            },
+            ..Self::default()
+        }
+    }
+}
+
+/// Magic number for a mount-table type name.
+fn magic(fstype: &str) -> i64 {
+    match fstype {
+        "vfat" => MSDOS_SUPER_MAGIC,
+        "ext2" => EXT2_SUPER_MAGIC,
+        "tmpfs" => TMPFS_MAGIC,
+        "overlay" => OVERLAYFS_SUPER_MAGIC,
+        "9p" => V9FS_MAGIC,
+        _ => 0,
+    }
+}
+
+fn statfs_mount(path: &str) -> Result<LinuxStatfs, i64> {
+    crate::fs::vfs::vfs_statfs(path)
+        .map(|mount| LinuxStatfs::from_mount(&mount))
+        .map_err(|e| map_filesystem_err(&e))
+}
+
+/// Resolve an absolute path the way `stat` does: `/bin` applets, `/dev`
+/// and `/proc` are synthesized, everything else must exist on a mount.
+fn statfs_path(path: &str) -> Result<LinuxStatfs, i64> {
+    use crate::userland::bin_namespace::{apply_bin_rewrite, is_bin_dir};
+    use crate::userland::devfs::{classify, DeviceNode};
+    if is_bin_dir(path) {
+        return statfs_mount("/");
+    }
+    if let Some((host_path, _)) = apply_bin_rewrite(path) {
+        return statfs_mount(host_path);
+    }
+    match classify(path) {
+        Some(DeviceNode::PtsDirectory | DeviceNode::Pts(_)) => {
+            return Ok(LinuxStatfs::pseudo(DEVPTS_SUPER_MAGIC));
+        }
+        Some(_) => return Ok(LinuxStatfs::pseudo(TMPFS_MAGIC)),
+        None => {}
+    }
+    if crate::userland::procfs::is_proc_path(path) {
+        return match crate::userland::procfs::classify(path) {
+            Some(_) => Ok(LinuxStatfs::pseudo(PROC_SUPER_MAGIC)),
+            None => Err(ENOENT),
+        };
+    }
+    if path != "/" {
+        crate::fs::vfs::vfs_stat(path).map_err(|e| map_filesystem_err(&e))?;
+    }
+    statfs_mount(path)
+}
+
+fn statfs_fd(fd: i32) -> Result<LinuxStatfs, i64> {
+    match fd_slot(fd).ok_or(EBADF)? {
+        // Open files keep answering after an unlink, so go by mount only.
+        FdSlot::File { handle, .. } => statfs_mount(&handle.path()),
+        FdSlot::Directory { handle, .. } => statfs_mount(&handle.path()),
+        FdSlot::VirtualBinDir { .. } => statfs_mount("/"),
+        FdSlot::VirtualFile { .. } | FdSlot::VirtualDir { .. } => {
+            Ok(LinuxStatfs::pseudo(PROC_SUPER_MAGIC))
+        }
+        FdSlot::PtySlave { .. } => Ok(LinuxStatfs::pseudo(DEVPTS_SUPER_MAGIC)),
+        FdSlot::Stdin
+        | FdSlot::Stdout
+        | FdSlot::Stderr
+        | FdSlot::PtyMaster { .. }
+        | FdSlot::VirtualDevDir { .. }
+        | FdSlot::Urandom { .. }
+        | FdSlot::DevNull { .. }
+        | FdSlot::GuiEvents { .. } => Ok(LinuxStatfs::pseudo(TMPFS_MAGIC)),
+        FdSlot::PipeRead(..) | FdSlot::PipeWrite(..) => Ok(LinuxStatfs::pseudo(PIPEFS_MAGIC)),
+        FdSlot::Socket { .. } | FdSlot::UnixSocket { .. } => Ok(LinuxStatfs::pseudo(SOCKFS_MAGIC)),
+        FdSlot::EventFd { .. }
+        | FdSlot::TimerFd { .. }
+        | FdSlot::SignalFd { .. }
+        | FdSlot::Inotify { .. }
+        | FdSlot::Epoll { .. } => Ok(LinuxStatfs::pseudo(ANON_INODE_FS_MAGIC)),
+    }
+}
+
+fn write_statfs(ptr: u64, result: Result<LinuxStatfs, i64>) -> i64 {
+    match result {
+        Ok(statfs) => {
+            crate::userland::usercopy::write_unaligned(ptr, &statfs).map_or_else(|e| e, |_| 0)
+        }
+        Err(e) => e,
+    }
+}
+
+/// `statfs(path, buf)`.
+pub fn statfs_handler(args: &mut SyscallArgs) -> i64 {
+    let result = resolve_user_path(args.rdi).and_then(|path| statfs_path(&path));
+    write_statfs(args.rsi, result)
+}
+
+/// `fstatfs(fd, buf)`.
+pub fn fstatfs_handler(args: &mut SyscallArgs) -> i64 {
+    write_statfs(args.rsi, statfs_fd(args.rdi as i32))
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn test_statfs_layout() {
+        assert_eq!(core::mem::size_of::<LinuxStatfs>(), 120);
+        assert_eq!(core::mem::offset_of!(LinuxStatfs, f_fsid), 56);
+        assert_eq!(core::mem::offset_of!(LinuxStatfs, f_flags), 80);
+    }
+
+    fn test_mount_magic_and_flags() {
+        let mount = MountStats {
+            fstype: "ext2",
+            read_only: true,
+            stats: crate::fs::filesystem::FilesystemStats {
+                total_blocks: 1000,
+                free_blocks: 300,
+                available_blocks: 250,
+                block_size: 1024,
+                total_inodes: 128,
+                free_inodes: 100,
+            },
+        };
+        let statfs = LinuxStatfs::from_mount(&mount);
+        assert_eq!(statfs.f_type, EXT2_SUPER_MAGIC);
+        assert_eq!((statfs.f_bsize, statfs.f_frsize), (1024, 1024));
+        assert_eq!(
+            (statfs.f_blocks, statfs.f_bfree, statfs.f_bavail),
+            (1000, 300, 250)
+        );
+        assert_eq!((statfs.f_files, statfs.f_ffree), (128, 100));
+        assert_eq!(statfs.f_flags, ST_VALID | ST_RDONLY);
+        assert_eq!(magic("vfat"), MSDOS_SUPER_MAGIC);
+        assert_eq!(magic("tmpfs"), TMPFS_MAGIC);
+        assert_eq!(magic("overlay"), OVERLAYFS_SUPER_MAGIC);
+        assert_eq!(magic("9p"), V9FS_MAGIC);
+    }
+
+    fn test_pseudo_paths() {
+        assert_eq!(statfs_path("/proc").unwrap().f_type, PROC_SUPER_MAGIC);
+        assert_eq!(statfs_path("/proc/no-such-entry"), Err(ENOENT));
+        assert_eq!(statfs_path("/dev/null").unwrap().f_type, TMPFS_MAGIC);
+        assert_eq!(statfs_path("/dev/pts").unwrap().f_type, DEVPTS_SUPER_MAGIC);
+    }
+
+    fn test_dev_shm_is_sized_tmpfs() {
+        let statfs = statfs_path(crate::userland::devfs::SHM_DIR).expect("statfs /dev/shm");
+        assert_eq!(statfs.f_type, TMPFS_MAGIC);
+        assert_eq!(statfs.f_bsize, 4096);
+        assert!(statfs.f_blocks > 0 && statfs.f_bfree <= statfs.f_blocks);
+        assert!(statfs.f_files > 0 && statfs.f_ffree < statfs.f_files);
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_statfs_layout,
+            &test_mount_magic_and_flags,
+            &test_pseudo_paths,
+            &test_dev_shm_is_sized_tmpfs,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as statfs_tests;
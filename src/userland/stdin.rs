@@ -7,7 +7,7 @@
 //! fixture.
 //!
 //! Lookup model matches the previous design: the pty registry is keyed
-//! by `Option<WindowId>` (the `None` slot reserved for tests / boot-time
+//! by `Option<TerminalId>` (the `None` slot reserved for tests / boot-time
 //! paths without a terminal). Per-process lookup uses
 //! `Process.terminal_id`.
 
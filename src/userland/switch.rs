@@ -363,6 +363,7 @@ unsafe fn resume_ring3_inner(pid: u32) -> ! {
     // CR3 swap — only if the process has its own address space.
     if let Some((frame, generation)) = l4 {
         use x86_64::registers::control::{Cr3, Cr3Flags};
+        crate::arch::x86_64::percpu::set_active_user_l4(frame.start_address().as_u64());
         Cr3::write(frame, Cr3Flags::empty());
         crate::diagnostics::shadow::address_space::activate(
             generation,
@@ -399,6 +400,9 @@ unsafe fn resume_ring3_inner(pid: u32) -> ! {
     crate::diagnostics::shadow::cpu::set_current_pid(pid);
     crate::process::set_in_spawned_process(true);
     crate::diagnostics::shadow::cpu::commit_user(pid);
+    // Publishing the task above and checking here pairs with the loaded-task
+    // scan in a sibling's exit_group: one of the two always sees the other.
+    crate::userland::lifecycle::exit_if_stopped();
 
     if let Some(context) = kernel_continuation {
         crate::diagnostics::shadow::continuation::dispatch(pid, &context);
@@ -662,6 +666,12 @@ pub unsafe fn block_current_ring3_and_yield(args: &SyscallArgs, reason: Ring3Blo
         p.saved_user_state = snapshot;
         save_user_cpu_state(p);
     }
+    park_saved_ring3(me, reason)
+}
+
+/// Publish the current ring-3 process `me`, whose user state is already
+/// saved, as blocked on `reason` and dispatch another entity. Diverges.
+unsafe fn park_saved_ring3(me: u32, reason: Ring3BlockReason) -> ! {
     // The process kernel stack is still live on this CPU. Keep the saved
     // continuation unpublished until the architecture handoff has moved to
     // the per-CPU kernel stack; otherwise an early pipe/readiness wake can
@@ -677,24 +687,25 @@ pub unsafe fn block_current_ring3_and_yield(args: &SyscallArgs, reason: Ring3Blo
     // but before the blocked reason became visible. This includes ordinary
     // pipe reads/writes as well as poll/select/epoll. Producers increment the
     // sequence before waking, so a post-publication recheck closes the early
-    // side of the lost-wake race for this exact waiter.
+    // side of the lost-wake race for this exact waiter. A stop likewise
+    // rechecks for a SIGCONT (or, in a ptrace stop, a resuming request)
+    // that raced the publication.
     crate::userland::lifecycle::reconcile_readiness_after_block(me, reason);
 
     crate::diagnostics::shadow::stack::begin_abandon(me);
     dispatch_after_user_stop()
 }
 
-/// Save a successful post-SYSCALL continuation, put the current ring-3 entity
-/// back on the ready queue, and dispatch another entity. Unlike a blocking
-/// syscall, RIP is not rewound and RAX is the successful return value zero.
-pub unsafe fn yield_current_ring3(args: &SyscallArgs) -> ! {
+/// User state for resuming just after the SYSCALL in `args` with `rax` as
+/// its return value.
+pub(crate) unsafe fn post_syscall_state(args: &SyscallArgs, rax: u64) -> UserState {
     let raw = args as *const SyscallArgs as *const u64;
     let user_rip = core::ptr::read(raw.add(7));
     let user_rflags = core::ptr::read(raw.add(8));
     let saved = crate::userland::user_state::read_user_callee_saved(args as *const SyscallArgs);
     let user_r12 = crate::userland::user_state::read_user_r12(args as *const SyscallArgs);
-    let snapshot = UserState {
-        rax: 0,
+    UserState {
+        rax,
         rdi: args.rdi,
         rsi: args.rsi,
         rdx: args.rdx,
@@ -712,7 +723,22 @@ pub unsafe fn yield_current_ring3(args: &SyscallArgs) -> ! {
         rflags: user_rflags,
         rcx: 0,
         r11: 0,
-    };
+    }
+}
+
+/// User state that re-executes the SYSCALL in `args` when resumed: the
+/// same snapshot [`block_current_ring3_and_yield`] parks with.
+pub(crate) unsafe fn refire_state(args: &SyscallArgs) -> UserState {
+    let mut state = post_syscall_state(args, args.rax);
+    state.rip = state.rip.wrapping_sub(2);
+    state
+}
+
+/// Save a successful post-SYSCALL continuation, put the current ring-3 entity
+/// back on the ready queue, and dispatch another entity. Unlike a blocking
+/// syscall, RIP is not rewound and RAX is the successful return value zero.
+pub unsafe fn yield_current_ring3(args: &SyscallArgs) -> ! {
+    let snapshot = post_syscall_state(args, 0);
     let me = current_user_pid().expect("yield_current_ring3: no current ring-3 process");
     {
         let mut table = PROCESS_TABLE.lock();
@@ -729,3 +755,34 @@ pub unsafe fn yield_current_ring3(args: &SyscallArgs) -> ! {
     crate::diagnostics::shadow::stack::begin_abandon(me);
     dispatch_after_user_stop()
 }
+
+/// Stop the current ring-3 process after its syscall completed with `ret`.
+/// Once continued it returns to the instruction after the SYSCALL with
+/// `ret` in RAX; the syscall is not replayed.
+pub unsafe fn stop_current_ring3_after_syscall(args: &SyscallArgs, ret: i64) -> ! {
+    save_current_ring3(post_syscall_state(args, ret as u64));
+    stop_current_ring3_preempted(Ring3BlockReason::Stopped)
+}
+
+/// Record `snapshot` (plus FS_BASE and the FPU) as the current ring-3
+/// task's resume state without parking it. A caller that must publish
+/// something about the stop before parking pairs this with
+/// [`stop_current_ring3_preempted`].
+pub unsafe fn save_current_ring3(snapshot: UserState) {
+    let me = current_user_pid().expect("save_current_ring3: no current process");
+    let mut table = PROCESS_TABLE.lock();
+    let process = table
+        .by_pid
+        .get_mut(&me)
+        .expect("save_current_ring3: current task missing");
+    process.saved_user_state = snapshot;
+    save_user_cpu_state(process);
+}
+
+/// Park the current ring-3 process on `reason` once its user state is
+/// saved: by [`save_ring3`] on a trap path (the timer or a debug trap),
+/// or by [`save_current_ring3`].
+pub unsafe fn stop_current_ring3_preempted(reason: Ring3BlockReason) -> ! {
+    let me = current_user_pid().expect("stop_current_ring3_preempted: no current process");
+    park_saved_ring3(me, reason)
+}
@@ -32,11 +32,11 @@
 use crate::arch::x86_64::syscall::SyscallArgs;
 use crate::userland::abi::{
     validate_user_slice, EACCES, EAGAIN, EBADF, EBUSY, EEXIST, EFAULT, EFBIG, EINTR, EINVAL, EIO,
-    EISDIR, EMFILE, ENOENT, ENOMEM, ENOSPC, ENOSYS, ENOTDIR, ENOTEMPTY, ENOTTY, EOPNOTSUPP, EPERM,
-    ERANGE, EROFS, ESPIPE, ESRCH, EXDEV, LAST_EXIT_CODE,
+    EISDIR, ELIBBAD, ELOOP, EMFILE, ENOENT, ENOEXEC, ENOMEM, ENOSPC, ENOSYS, ENOTDIR, ENOTEMPTY,
+    ENOTTY, EOPNOTSUPP, EPERM, ERANGE, EROFS, ESPIPE, ESRCH, EXDEV, LAST_EXIT_CODE,
 };
 use crate::userland::fdtable::{FdSlot, FdTable, FD_TABLE_SIZE};
-use crate::userland::path::{copy_user_cstr, normalize_path};
+use crate::userland::path::{copy_user_cstr, resolve_path_following};
 use alloc::string::String;
 use alloc::vec;
 use x86_64::structures::paging::PageTableFlags;
@@ -72,20 +72,25 @@ const PROT_READ: u64 = 1;
 const PROT_WRITE: u64 = 2;
 const PROT_EXEC: u64 = 4;
 
+const MAP_SHARED: u64 = 0x01;
 const MAP_PRIVATE: u64 = 0x02;
+const MAP_SHARED_VALIDATE: u64 = 0x03;
+const MAP_TYPE: u64 = 0x0f;
 const MAP_FIXED: u64 = 0x10;
 const MAP_ANONYMOUS: u64 = 0x20;
 
 const ARCH_SET_FS: u64 = 0x1002;
 const ARCH_GET_FS: u64 = 0x1003;
 
-const TCGETS: u64 = 0x5401;
-const TCSETS: u64 = 0x5402;
-const TCSETSW: u64 = 0x5403;
-const TCSETSF: u64 = 0x5404;
-const TIOCGPGRP: u64 = 0x540F;
-const TIOCSPGRP: u64 = 0x5410;
-const TIOCGWINSZ: u64 = 0x5413;
+pub(crate) const TCGETS: u64 = 0x5401;
+pub(crate) const TCSETS: u64 = 0x5402;
+pub(crate) const TCSETSW: u64 = 0x5403;
+pub(crate) const TCSETSF: u64 = 0x5404;
+pub(crate) const TIOCSCTTY: u64 = 0x540E;
+pub(crate) const TIOCGPGRP: u64 = 0x540F;
+pub(crate) const TIOCSPGRP: u64 = 0x5410;
+pub(crate) const TIOCGSID: u64 = 0x5429;
+pub(crate) const TIOCGWINSZ: u64 = 0x5413;
 
 const UTIME_NOW: i64 = 0x3fff_ffff;
 const UTIME_OMIT: i64 = 0x3fff_fffe;
@@ -128,6 +133,13 @@ fn write_file_chunked(
     ptr: u64,
     len: u64,
 ) -> i64 {
+    if let Err(e) = crate::userland::memfd::check_write(handle, handle.position(), len) {
+        return e;
+    }
+    let len = match crate::userland::rlimit::clamp_file_write(handle.position(), len) {
+        Ok(len) => len,
+        Err(e) => return e,
+    };
     let mut staging = alloc::vec![0u8; core::cmp::min(len as usize, WRITE_MAX_LEN)];
     let mut written: u64 = 0;
     while written < len {
@@ -159,7 +171,11 @@ fn write_file_chunked(
 /// slave. The pty path is byte-preserving; the framebuffer fallback used by
 /// processes without a terminal remains text-oriented and keeps UTF-8
 /// sequences intact across staging-buffer seams.
-fn write_terminal_chunked(ptr: u64, len: u64, terminal_id: Option<crate::window::WindowId>) -> i64 {
+fn write_terminal_chunked(
+    ptr: u64,
+    len: u64,
+    terminal_id: Option<crate::terminal::pty::TerminalId>,
+) -> i64 {
     let mut staging = alloc::vec![0u8; core::cmp::min(len as usize, WRITE_MAX_LEN)];
     let mut written: u64 = 0;
     while written < len {
@@ -222,13 +238,15 @@ pub fn write_handler(args: &mut SyscallArgs) -> i64 {
         File(crate::lib::arc::Arc<crate::fs::file_handle::File>),
         Socket(u64),
         EventFd(crate::lib::arc::Arc<crate::userland::eventfd::EventFd>),
-        LocalStream(crate::lib::arc::Arc<crate::userland::local_stream::LocalStreamEndpoint>),
+        UnixSocket(crate::lib::arc::Arc<crate::userland::unix_socket::UnixSocket>),
         PtyMaster(crate::terminal::pty::PtyMaster),
+        PtySlave(crate::lib::arc::Arc<crate::userland::pts::PtsHandle>),
     }
     let slot = with_fd_slot(fd);
     let target = match slot {
         Some(FdSlot::Stdout) | Some(FdSlot::Stderr) => Target::StdoutErr,
         Some(FdSlot::PtyMaster { master, .. }) => Target::PtyMaster(master),
+        Some(FdSlot::PtySlave { handle, .. }) => Target::PtySlave(handle),
         Some(FdSlot::File { handle, .. }) => Target::File(handle),
         Some(FdSlot::Directory { .. })
         | Some(FdSlot::VirtualBinDir { .. })
@@ -238,7 +256,7 @@ pub fn write_handler(args: &mut SyscallArgs) -> i64 {
         Some(FdSlot::PipeRead(_, _)) => return EBADF,
         Some(FdSlot::Socket { handle, .. }) => Target::Socket(handle.id()),
         Some(FdSlot::EventFd { handle, .. }) => Target::EventFd(handle),
-        Some(FdSlot::LocalStream { handle, .. }) => Target::LocalStream(handle),
+        Some(FdSlot::UnixSocket { handle, .. }) => Target::UnixSocket(handle),
         // Discard sink: validate the buffer, report it fully written.
         Some(FdSlot::DevNull { .. }) => {
             if let Err(e) = crate::userland::usercopy::ensure_user_range(ptr, len, false) {
@@ -251,6 +269,9 @@ pub fn write_handler(args: &mut SyscallArgs) -> i64 {
         | Some(FdSlot::Urandom { .. })
         | Some(FdSlot::GuiEvents { .. })
         | Some(FdSlot::Epoll { .. }) => return EBADF,
+        Some(FdSlot::TimerFd { .. })
+        | Some(FdSlot::SignalFd { .. })
+        | Some(FdSlot::Inotify { .. }) => return EINVAL,
         Some(FdSlot::Stdin) | None => return EBADF,
     };
 
@@ -324,8 +345,8 @@ pub fn write_handler(args: &mut SyscallArgs) -> i64 {
             crate::userland::network_syscalls::write_connected(args, id, &staging)
         }
         Target::EventFd(handle) => crate::userland::eventfd::write(args, &handle, ptr, len),
-        Target::LocalStream(handle) => {
-            crate::userland::local_stream::LocalStreamEndpoint::write(args, &handle, ptr, len)
+        Target::UnixSocket(handle) => {
+            crate::userland::unix_socket::UnixSocket::write(args, &handle, ptr, len)
         }
         Target::StdoutErr => {
             // Route to THIS process's terminal_id. The pty slave is the
@@ -337,29 +358,52 @@ pub fn write_handler(args: &mut SyscallArgs) -> i64 {
             // The emulator wrote keystrokes to the pty master; run them
             // through the line discipline, then wake a blocked `read(0)`
             // and raise any ISIG signal after the pty lock is released.
+            let observed_sequence = crate::userland::readiness::sequence();
             let take = core::cmp::min(len, WRITE_MAX_LEN as u64) as usize;
             let mut staging = alloc::vec![0u8; take];
             if let Err(e) = crate::userland::usercopy::copy_from_user(&mut staging, ptr) {
                 return e;
             }
-            let (result, terminal_id) =
-                master.with(|p| (p.push_slave_input(&staging), p.terminal_id()));
+            let (result, terminal_id, foreground) = master.with(|p| {
+                (
+                    p.push_slave_input(&staging),
+                    p.terminal_id(),
+                    p.foreground_pgrp(),
+                )
+            });
             if result.wake_reader {
                 crate::userland::lifecycle::wake_ring3_blocked_on_input(Some(terminal_id));
                 // The child may be parked in poll/select on stdin.
                 crate::userland::readiness::notify_changed();
             }
             if let Some(sig) = result.signal {
-                crate::userland::lifecycle::raise_signal_on_terminal(terminal_id, sig);
+                crate::userland::job_control::signal_terminal(terminal_id, foreground, sig);
             }
             if result.consumed == 0 && take > 0 {
-                // Raw mode with a full slave queue: no progress. The
-                // master fd is non-blocking by construction, so report
+                // Raw mode with a full slave queue: no progress. A window
+                // master is non-blocking by construction, so it reports
                 // EAGAIN instead of a POSIX-invalid zero-length write.
-                return EAGAIN;
+                drop(staging);
+                return crate::userland::pts::wait_master(args, master, observed_sequence);
             }
             result.consumed as i64
         }
+        Target::PtySlave(handle) => {
+            let observed_sequence = crate::userland::readiness::sequence();
+            let take = core::cmp::min(len, WRITE_MAX_LEN as u64) as usize;
+            let mut staging = alloc::vec![0u8; take];
+            if let Err(e) = crate::userland::usercopy::copy_from_user(&mut staging, ptr) {
+                return e;
+            }
+            match handle.write(&staging) {
+                Ok(0) => {
+                    drop(staging);
+                    crate::userland::pts::wait_slave(args, handle, observed_sequence)
+                }
+                Ok(n) => n as i64,
+                Err(e) => e,
+            }
+        }
     }
 }
 
@@ -378,14 +422,16 @@ pub fn writev_handler(args: &mut SyscallArgs) -> i64 {
         Pipe(crate::userland::pipe::PipeWriteHandle),
         File(crate::lib::arc::Arc<crate::fs::file_handle::File>),
         Socket(u64),
-        LocalStream(crate::lib::arc::Arc<crate::userland::local_stream::LocalStreamEndpoint>),
+        UnixSocket(crate::lib::arc::Arc<crate::userland::unix_socket::UnixSocket>),
         PtyMaster(crate::terminal::pty::PtyMaster),
+        PtySlave(crate::lib::arc::Arc<crate::userland::pts::PtsHandle>),
         /// `/dev/null`: validated iovecs count as fully written.
         Sink,
     }
     let target = match with_fd_slot(fd) {
         Some(FdSlot::Stdout) | Some(FdSlot::Stderr) => Target::StdoutErr,
         Some(FdSlot::PtyMaster { master, .. }) => Target::PtyMaster(master),
+        Some(FdSlot::PtySlave { handle, .. }) => Target::PtySlave(handle),
         Some(FdSlot::File { handle, .. }) => Target::File(handle),
         Some(FdSlot::Directory { .. })
         | Some(FdSlot::VirtualBinDir { .. })
@@ -395,13 +441,16 @@ pub fn writev_handler(args: &mut SyscallArgs) -> i64 {
         Some(FdSlot::PipeWrite(handle, _)) => Target::Pipe(handle),
         Some(FdSlot::PipeRead(_, _)) => return EBADF,
         Some(FdSlot::Socket { handle, .. }) => Target::Socket(handle.id()),
-        Some(FdSlot::LocalStream { handle, .. }) => Target::LocalStream(handle),
+        Some(FdSlot::UnixSocket { handle, .. }) => Target::UnixSocket(handle),
         // /proc snapshots are read-only.
         Some(FdSlot::VirtualFile { .. })
         | Some(FdSlot::Urandom { .. })
         | Some(FdSlot::GuiEvents { .. })
         | Some(FdSlot::EventFd { .. })
         | Some(FdSlot::Epoll { .. }) => return EBADF,
+        Some(FdSlot::TimerFd { .. })
+        | Some(FdSlot::SignalFd { .. })
+        | Some(FdSlot::Inotify { .. }) => return EINVAL,
         Some(FdSlot::Stdin) | None => return EBADF,
     };
     if iovcnt < 0 || iovcnt as usize > WRITEV_MAX_IOV {
@@ -448,6 +497,7 @@ pub fn writev_handler(args: &mut SyscallArgs) -> i64 {
     // at the staging bound (see the WRITE_MAX_LEN comment).
     let mut written: u64 = 0;
     let mut pipe_block_sequence = None;
+    let mut pty_block_sequence = None;
     for (base, len) in iovecs {
         if len == 0 {
             continue;
@@ -520,24 +570,31 @@ pub fn writev_handler(args: &mut SyscallArgs) -> i64 {
                 }
             }
             Target::PtyMaster(master) => {
+                let observed_sequence = crate::userland::readiness::sequence();
                 let take = core::cmp::min(len, WRITE_MAX_LEN as u64) as usize;
                 let mut bytes = alloc::vec![0u8; take];
                 if let Err(e) = crate::userland::usercopy::copy_from_user(&mut bytes, base) {
                     return if written > 0 { written as i64 } else { e };
                 }
-                let (result, terminal_id) =
-                    master.with(|p| (p.push_slave_input(&bytes), p.terminal_id()));
+                let (result, terminal_id, foreground) = master.with(|p| {
+                    (
+                        p.push_slave_input(&bytes),
+                        p.terminal_id(),
+                        p.foreground_pgrp(),
+                    )
+                });
                 if result.wake_reader {
                     crate::userland::lifecycle::wake_ring3_blocked_on_input(Some(terminal_id));
                     // The child may be parked in poll/select on stdin.
                     crate::userland::readiness::notify_changed();
                 }
                 if let Some(sig) = result.signal {
-                    crate::userland::lifecycle::raise_signal_on_terminal(terminal_id, sig);
+                    crate::userland::job_control::signal_terminal(terminal_id, foreground, sig);
                 }
                 if result.consumed == 0 && take > 0 && written == 0 {
                     // Raw mode, full slave queue, nothing written yet.
-                    return EAGAIN;
+                    pty_block_sequence = Some(observed_sequence);
+                    break;
                 }
                 written += result.consumed as u64;
                 if (result.consumed as u64) < len {
@@ -559,10 +616,9 @@ pub fn writev_handler(args: &mut SyscallArgs) -> i64 {
                     break;
                 }
             }
-            Target::LocalStream(handle) => {
-                let result = crate::userland::local_stream::LocalStreamEndpoint::write(
-                    args, handle, base, len,
-                );
+            Target::UnixSocket(handle) => {
+                let result =
+                    crate::userland::unix_socket::UnixSocket::write(args, handle, base, len);
                 if result < 0 {
                     return if written > 0 { written as i64 } else { result };
                 }
@@ -571,6 +627,39 @@ pub fn writev_handler(args: &mut SyscallArgs) -> i64 {
                     break;
                 }
             }
+            Target::PtySlave(handle) => {
+                let observed_sequence = crate::userland::readiness::sequence();
+                let take = core::cmp::min(len, WRITE_MAX_LEN as u64) as usize;
+                let mut bytes = alloc::vec![0u8; take];
+                if let Err(e) = crate::userland::usercopy::copy_from_user(&mut bytes, base) {
+                    return if written > 0 { written as i64 } else { e };
+                }
+                match handle.write(&bytes) {
+                    Ok(0) if written == 0 => {
+                        pty_block_sequence = Some(observed_sequence);
+                        break;
+                    }
+                    Ok(n) => {
+                        written += n as u64;
+                        if (n as u64) < len {
+                            break;
+                        }
+                    }
+                    Err(e) => return if written > 0 { written as i64 } else { e },
+                }
+            }
+        }
+    }
+    if let Some(observed_sequence) = pty_block_sequence {
+        // Same restart rule as the pipe case below.
+        match target {
+            Target::PtyMaster(master) => {
+                return crate::userland::pts::wait_master(args, master, observed_sequence)
+            }
+            Target::PtySlave(handle) => {
+                return crate::userland::pts::wait_slave(args, handle, observed_sequence)
+            }
+            _ => {}
         }
     }
     if let Some(observed_sequence) = pipe_block_sequence {
@@ -795,20 +884,32 @@ fn read_fd_once(args: &SyscallArgs, fd: i32, ptr: u64, len: u64) -> i64 {
         Some(FdSlot::EventFd { handle, .. }) => {
             crate::userland::eventfd::read(args, &handle, ptr, len)
         }
-        Some(FdSlot::LocalStream { handle, .. }) => {
-            crate::userland::local_stream::LocalStreamEndpoint::read(args, &handle, ptr, len)
+        Some(FdSlot::TimerFd { handle, .. }) => {
+            crate::userland::timerfd::read(args, &handle, ptr, len)
+        }
+        Some(FdSlot::SignalFd { handle, .. }) => {
+            crate::userland::signalfd::read(args, &handle, ptr, len)
+        }
+        Some(FdSlot::Inotify { handle, .. }) => {
+            crate::userland::inotify::read(args, &handle, ptr, len)
+        }
+        Some(FdSlot::UnixSocket { handle, .. }) => {
+            crate::userland::unix_socket::UnixSocket::read(args, &handle, ptr, len)
         }
         Some(FdSlot::Epoll { .. }) => EBADF,
+        Some(FdSlot::PtySlave { handle, .. }) => crate::userland::pts::read(args, handle, ptr, cap),
         Some(FdSlot::PtyMaster { master, .. }) => {
-            // Bounded, non-blocking drain of the slave's output. The emulator
-            // polls the master fd alongside its GUI event fd; an empty queue
-            // reports EAGAIN rather than a spurious EOF. Check emptiness
-            // before allocating so the idle-poll path costs no kernel heap
+            // Bounded drain of the slave's output. The emulator polls its
+            // window master alongside its GUI event fd; an empty queue
+            // reports EAGAIN rather than a spurious EOF (a blocking
+            // `/dev/ptmx` master parks instead). Check emptiness before
+            // allocating so the idle-poll path costs no kernel heap
             // traffic (the queue may shift between check and drain — the
             // drain is bounded either way and a short read is fine).
+            let observed_sequence = crate::userland::readiness::sequence();
             let ready = master.output_ready();
             if ready == 0 {
-                return EAGAIN;
+                return crate::userland::pts::wait_master(args, master, observed_sequence);
             }
             let mut staging = vec![0u8; core::cmp::min(cap as usize, ready)];
             let n = master.read_output(&mut staging);
@@ -846,6 +947,9 @@ fn read_stdin_blocking(args: &SyscallArgs, ptr: u64, cap: u64) -> i64 {
     if !crate::userland::stdin::is_active_for_current_process() {
         return 0;
     }
+    if let Err(e) = crate::userland::job_control::check_terminal_read(args) {
+        return e;
+    }
     let mut staging = alloc::vec![0u8; cap as usize];
     let n = crate::userland::stdin::pop_into_for_current_process(&mut staging);
     if n > 0 {
@@ -877,8 +981,11 @@ fn read_stdin_blocking(args: &SyscallArgs, ptr: u64, cap: u64) -> i64 {
 
 /// `mmap(addr, length, prot, flags, fd, offset) -> void *`
 ///
-/// Creates a metadata-only anonymous-private or file-private VMA. A valid
-/// free hint is honored; otherwise a top-down reusable gap is selected.
+/// Creates a metadata-only anonymous or file VMA. `MAP_PRIVATE` pages are
+/// private copies; `MAP_SHARED` pages belong to a
+/// [`SharedObject`](crate::userland::shared_memory::SharedObject) so every
+/// mapper, including forked children, sees the same bytes. A valid free
+/// hint is honored; otherwise a top-down reusable gap is selected.
 /// Resident pages are allocated only when touched.
 pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
     use crate::userland::vm::{VmProt, Vma, VmaBacking};
@@ -893,9 +1000,11 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
     if length == 0 || length > MMAP_MAX_LEN {
         return EINVAL;
     }
-    if (flags & MAP_PRIVATE) == 0 {
-        return ENOSYS;
-    }
+    let shared = match flags & MAP_TYPE {
+        MAP_PRIVATE => false,
+        MAP_SHARED | MAP_SHARED_VALIDATE => true,
+        _ => return EINVAL,
+    };
     let fixed = flags & MAP_FIXED != 0;
     if fixed && (addr_hint & 0xfff != 0 || addr_hint == 0) {
         // MAP_FIXED demands an exact page-aligned address; musl's
@@ -931,7 +1040,11 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
         }
         crate::userland::lifecycle::with_current_group(|process| {
             match process.fd_table.get(fd as i32) {
-                Some(FdSlot::File { handle, .. }) => Some(handle.clone()),
+                Some(FdSlot::File {
+                    handle,
+                    status_flags,
+                    ..
+                }) => Some((handle.clone(), status_flags & O_ACCMODE)),
                 _ => None,
             }
         })
@@ -944,6 +1057,32 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
     if flags & MAP_ANONYMOUS == 0 && file.is_none() {
         return EBADF;
     }
+    // Stores through a shared file mapping reach the file, so they need a
+    // descriptor opened for both reading and writing, now or after a later
+    // mprotect.
+    let may_write = !shared || file.as_ref().is_none_or(|(_, access)| *access == O_RDWR);
+    if prot & PROT_WRITE != 0 && !may_write {
+        return EACCES;
+    }
+    let backing = match (&file, shared) {
+        (Some((handle, access)), true) => VmaBacking::Shared {
+            object: crate::userland::shared_memory::SharedObject::for_file(
+                handle,
+                *access != O_RDONLY,
+            ),
+            offset,
+        },
+        (Some((handle, _)), false) => VmaBacking::FilePrivate {
+            file: handle.clone(),
+            file_offset: offset,
+            file_size: handle.size(),
+        },
+        (None, true) => VmaBacking::Shared {
+            object: crate::userland::shared_memory::SharedObject::anonymous(),
+            offset: 0,
+        },
+        (None, false) => VmaBacking::Anonymous,
+    };
 
     // `(result, Some(l4))` when a MAP_FIXED insert replaced an existing
     // range and its resident leaves must be torn down after the VMA
@@ -954,9 +1093,35 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
         Option<PhysFrame<Size4KiB>>,
         alloc::vec::Vec<crate::userland::vm::Vma>,
     ) = crate::userland::lifecycle::with_current_group(|process| {
+        // RLIMIT_AS and RLIMIT_DATA, net of any range MAP_FIXED replaces.
+        let replaced = match (fixed, process.address_space.as_ref()) {
+            (true, Some(space)) => addr_hint
+                .checked_add(len)
+                .map_or(0, |end| space.vmas().mapped_bytes(addr_hint, end)),
+            _ => 0,
+        };
+        let added = len.saturating_sub(replaced);
+        let data = if !shared && vm_prot.contains(VmProt::WRITE) {
+            added
+        } else {
+            0
+        };
+        if let Err(e) = crate::userland::rlimit::check_vm_growth(process, added, data) {
+            return (e, None, alloc::vec::Vec::new());
+        }
         let Some(space) = process.address_space.as_mut() else {
             return (ENOMEM, None, alloc::vec::Vec::new());
         };
+        // Checked under the process lock so F_ADD_SEALS(F_SEAL_WRITE) either
+        // sees this mapping or this check sees the seal.
+        if shared
+            && vm_prot.contains(VmProt::WRITE)
+            && file
+                .as_ref()
+                .is_some_and(|(handle, _)| crate::userland::memfd::write_sealed(handle))
+        {
+            return (EPERM, None, alloc::vec::Vec::new());
+        }
         let stack_floor = space
             .vmas()
             .as_slice()
@@ -989,17 +1154,10 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
                 Err(_) => return (ENOMEM, None, alloc::vec::Vec::new()),
             }
         };
-        let backing = match file {
-            Some(ref handle) => VmaBacking::FilePrivate {
-                file: handle.clone(),
-                file_offset: offset,
-                file_size: handle.size(),
-            },
-            None => VmaBacking::Anonymous,
-        };
-        let Ok(vma) = Vma::new(addr, addr + len, vm_prot, backing) else {
+        let Ok(mut vma) = Vma::new(addr, addr + len, vm_prot, backing) else {
             return (ENOMEM, None, removed_vmas);
         };
+        vma.may_write = may_write;
         if space.vmas_mut().insert(vma).is_err() {
             return (ENOMEM, None, removed_vmas);
         }
@@ -1011,6 +1169,7 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
     // exposing the old page contents.
     if let Some(l4) = fixed_l4 {
         let end = result as u64 + len;
+        harvest_dirty(l4, &removed_vmas);
         crate::mm::memory::with_memory_mapper(|mapper| {
             let mut page = result as u64;
             while page < end {
@@ -1021,14 +1180,40 @@ pub fn mmap_handler(args: &mut SyscallArgs) -> i64 {
             }
         });
     }
+    writeback_shared(&removed_vmas);
     drop(removed_vmas);
     result
 }
 
+/// Collect the dirty bits of `l4`'s leaves for shared VMAs about to be
+/// unmapped from it.
+fn harvest_dirty(l4: x86_64::structures::paging::PhysFrame, vmas: &[crate::userland::vm::Vma]) {
+    for vma in vmas {
+        crate::userland::shared_memory::harvest_dirty(l4, vma, vma.start, vma.end);
+    }
+}
+
+/// Write the dirty pages of shared file VMAs back to their files. Callers
+/// pass VMAs already detached from the address space, after releasing the
+/// process lock, since the writes sleep on file I/O.
+fn writeback_shared(vmas: &[crate::userland::vm::Vma]) {
+    for vma in vmas {
+        if let Some((object, offset)) = vma.shared_at(vma.start) {
+            if object
+                .writeback(offset, offset + (vma.end - vma.start))
+                .is_err()
+            {
+                crate::debug_warn!("munmap: shared writeback failed at {:#x}", vma.start);
+            }
+        }
+    }
+}
+
 /// `munmap(addr, length) -> int`
 ///
 /// Splits/trims intersecting VMAs, tolerates holes, and releases every
-/// resident leaf in the range. Subsequent mmap gap search can reuse it.
+/// resident leaf in the range. Shared file pages are written back first.
+/// Subsequent mmap gap search can reuse it.
 pub fn munmap_handler(args: &mut SyscallArgs) -> i64 {
     let addr = args.rdi;
     let length = args.rsi;
@@ -1049,6 +1234,7 @@ pub fn munmap_handler(args: &mut SyscallArgs) -> i64 {
     let Some((l4, removed_vmas)) = removed else {
         return EINVAL;
     };
+    harvest_dirty(l4, &removed_vmas);
     crate::mm::memory::with_memory_mapper(|mapper| {
         let mut page = addr;
         while page < end {
@@ -1058,14 +1244,88 @@ pub fn munmap_handler(args: &mut SyscallArgs) -> i64 {
             page += 0x1000;
         }
     });
+    writeback_shared(&removed_vmas);
     drop(removed_vmas);
     0
 }
 
+/// `msync(addr, length, flags) -> int`
+///
+/// Writes dirty pages of shared file mappings in the range back to their
+/// files; `MS_SYNC` also flushes the file to its device. With no page cache
+/// a plain `read` only sees mapped stores after writeback, so `MS_ASYNC`
+/// writes back too instead of being a no-op. Private and anonymous ranges
+/// have nothing to write. An unmapped hole yields `ENOMEM` after the mapped
+/// parts are synced.
+pub fn msync_handler(args: &mut SyscallArgs) -> i64 {
+    const MS_ASYNC: u64 = 1;
+    const MS_INVALIDATE: u64 = 2;
+    const MS_SYNC: u64 = 4;
+    let addr = args.rdi;
+    let length = args.rsi;
+    let flags = args.rdx;
+    if addr & 0xfff != 0
+        || flags & !(MS_ASYNC | MS_INVALIDATE | MS_SYNC) != 0
+        || flags & (MS_ASYNC | MS_SYNC) == MS_ASYNC | MS_SYNC
+    {
+        return EINVAL;
+    }
+    let Some(end) = addr.checked_add(length.div_ceil(0x1000) * 0x1000) else {
+        return ENOMEM;
+    };
+    if end == addr {
+        return 0;
+    }
+    let range = crate::userland::lifecycle::with_current_group(|process| {
+        let space = process.address_space.as_ref()?;
+        let covered = space
+            .vmas()
+            .covers(addr, end - addr, crate::userland::vm::VmProt::NONE);
+        let shared: alloc::vec::Vec<crate::userland::vm::Vma> = space
+            .vmas()
+            .as_slice()
+            .iter()
+            .filter(|vma| !vma.private && vma.start < end && vma.end > addr)
+            .cloned()
+            .collect();
+        Some((space.l4_frame(), covered, shared))
+    });
+    let Some((l4, covered, shared)) = range else {
+        return ENOMEM;
+    };
+    for vma in &shared {
+        let start = vma.start.max(addr);
+        crate::userland::shared_memory::harvest_dirty(l4, vma, start, end);
+        let Some((object, offset)) = vma.shared_at(start) else {
+            continue;
+        };
+        if object
+            .writeback(offset, offset + (vma.end.min(end) - start))
+            .is_err()
+        {
+            return EIO;
+        }
+        if flags & MS_SYNC != 0 {
+            if let Some(file) = object.file() {
+                if file.sync(true).is_err() {
+                    return EIO;
+                }
+            }
+        }
+    }
+    if covered {
+        0
+    } else {
+        ENOMEM
+    }
+}
+
 /// `mprotect(addr, length, prot) -> int`
 ///
 /// Updates logical VMA protections and hardware flags on resident pages.
 /// COW software state is preserved and writable+executable is rejected.
+/// Shared pages are never COW: granting write makes their leaves writable
+/// directly, since every mapper is meant to store to the same frame.
 pub fn mprotect_handler(args: &mut SyscallArgs) -> i64 {
     use crate::userland::vm::VmProt;
     let addr = args.rdi;
@@ -1090,14 +1350,43 @@ pub fn mprotect_handler(args: &mut SyscallArgs) -> i64 {
     if prot & PROT_EXEC != 0 {
         vm_prot = vm_prot.union(VmProt::EXEC);
     }
-    let l4 = crate::userland::lifecycle::with_current_group(|process| {
-        let space = process.address_space.as_mut()?;
-        space.vmas_mut().protect(addr, end, vm_prot).ok()?;
-        Some(space.l4_frame())
+    let protected = crate::userland::lifecycle::with_current_group(|process| {
+        let space = process.address_space.as_mut().ok_or(ENOMEM)?;
+        // A shared file mapping of a read-only descriptor never becomes
+        // writable, and a write-sealed memfd refuses new writable shared
+        // mappings; see the matching checks in mmap.
+        if vm_prot.contains(VmProt::WRITE)
+            && space.vmas().as_slice().iter().any(|vma| {
+                vma.start < end
+                    && vma.end > addr
+                    && (!vma.may_write
+                        || vma.shared_at(vma.start).is_some_and(|(object, _)| {
+                            object
+                                .file()
+                                .is_some_and(|file| crate::userland::memfd::write_sealed(&file))
+                        }))
+            })
+        {
+            return Err(EACCES);
+        }
+        space
+            .vmas_mut()
+            .protect(addr, end, vm_prot)
+            .map_err(|_| ENOMEM)?;
+        let shared: alloc::vec::Vec<crate::userland::vm::Vma> = space
+            .vmas()
+            .as_slice()
+            .iter()
+            .filter(|vma| !vma.private && vma.start < end && vma.end > addr)
+            .cloned()
+            .collect();
+        Ok((space.l4_frame(), shared))
     });
-    let Some(l4) = l4 else {
-        return ENOMEM;
+    let (l4, shared) = match protected {
+        Ok(protected) => protected,
+        Err(e) => return e,
     };
+    let in_shared = |page: u64| shared.iter().any(|vma| vma.start <= page && page < vma.end);
     crate::mm::memory::with_memory_mapper(|mapper| {
         let mut page = addr;
         while page < end {
@@ -1112,7 +1401,9 @@ pub fn mprotect_handler(args: &mut SyscallArgs) -> i64 {
                     flags.insert(PageTableFlags::NO_EXECUTE);
                 }
                 if vm_prot.contains(VmProt::WRITE) {
-                    if flags.contains(PageTableFlags::BIT_9)
+                    if in_shared(page) {
+                        flags.insert(PageTableFlags::WRITABLE);
+                    } else if flags.contains(PageTableFlags::BIT_9)
                         || mapper.frame_refcount(frame).is_some_and(|count| count > 1)
                     {
                         flags.insert(PageTableFlags::BIT_9);
@@ -1167,6 +1458,8 @@ pub fn brk_handler(args: &mut SyscallArgs) -> i64 {
         }
     }
     let l4 = crate::userland::lifecycle::with_current_group(|process| {
+        let growth = new_page_end.saturating_sub(old_page_end.max(base));
+        crate::userland::rlimit::check_vm_growth(process, growth, growth).ok()?;
         let Some(space) = process.address_space.as_mut() else {
             return None;
         };
@@ -1276,39 +1569,33 @@ pub fn arch_prctl_handler(args: &mut SyscallArgs) -> i64 {
 ///   drain — so all three are equivalent.
 /// - `TIOCGWINSZ`: copy the synthesized winsize (80x24) into the user
 ///   buffer. zsh's `zle` consults this to decide where to wrap.
-/// - `TIOCGPGRP`: U5 — return `-ENOTTY`. zsh's `acquire_pgrp`
-///   (`Src/init.c`) treats this as "no controlling tty" and clears
-///   `opts[MONITOR]`, which disables the entire job-control surface
-///   (setpgid/setsid/tcsetpgrp). This is the cleanest path to
-///   no-job-control: no `+m` argv hack required, no build-time
-///   `--without-tcsetpgrp` reliance. The `--without-tcsetpgrp`
-///   configure flag is also passed by U1's Makefile as
-///   belt-and-suspenders.
-/// - `TIOCSPGRP`: U5 — return `0`. Defensive stub; zsh shouldn't
-///   reach this path with MONITOR cleared, but a silent success
-///   avoids surprises if a configuration somehow does.
+/// - `TIOCGPGRP`/`TIOCSPGRP`/`TIOCSCTTY`/`TIOCGSID`: job control on the
+///   caller's pty, which the first of these claims as the controlling
+///   terminal of the caller's session (see
+///   [`crate::userland::job_control`]). `-ENOTTY` when the caller has no
+///   pty or it belongs to another session.
 ///
-/// Calls on non-tty fds (anything other than stdin/stdout/stderr)
-/// return `-ENOTTY`; libc relies on this to detect "this fd is a file"
-/// and disable line buffering.
+/// pty master and `/dev/pts` descriptors name their pty directly; see
+/// [`crate::userland::pts`] for the requests they add.
 ///
-/// Per the feasibility doc-review finding, the new TIOCGPGRP arm sits
-/// inside the request match alongside TCGETS — NOT relying on the
-/// non-tty fd short-circuit above. Today's tty-fd unknown-request
-/// default is `-ENOSYS`; without an explicit arm, TIOCGPGRP on stdin
-/// would return ENOSYS (not ENOTTY), and zsh's MONITOR-clearing path
-/// is gated specifically on ENOTTY.
+/// Calls on non-tty fds (anything other than stdin/stdout/stderr and
+/// pty descriptors) return `-ENOTTY`; libc relies on this to detect
+/// "this fd is a file" and disable line buffering. Unknown requests on a
+/// tty fd return `-ENOSYS`.
 pub fn ioctl_handler(args: &mut SyscallArgs) -> i64 {
     let fd = args.rdi as i32;
     let request = args.rsi;
     let arg = args.rdx;
 
-    let is_tty = matches!(
-        with_fd_slot(fd),
-        Some(FdSlot::Stdin) | Some(FdSlot::Stdout) | Some(FdSlot::Stderr)
-    );
-    if !is_tty {
-        return ENOTTY;
+    match with_fd_slot(fd) {
+        Some(FdSlot::Stdin) | Some(FdSlot::Stdout) | Some(FdSlot::Stderr) => {}
+        Some(FdSlot::PtyMaster { master, .. }) => {
+            return crate::userland::pts::master_ioctl(&master, request, arg)
+        }
+        Some(FdSlot::PtySlave { handle, .. }) => {
+            return crate::userland::pts::slave_ioctl(args, &handle, request, arg)
+        }
+        _ => return ENOTTY,
     }
 
     match request {
@@ -1328,8 +1615,13 @@ pub fn ioctl_handler(args: &mut SyscallArgs) -> i64 {
             let ws = crate::userland::tty::winsize();
             crate::userland::usercopy::write_unaligned(arg, &ws).map_or_else(|e| e, |_| 0)
         }
-        TIOCGPGRP => ENOTTY,
-        TIOCSPGRP => 0,
+        TIOCGPGRP => crate::userland::job_control::tiocgpgrp(arg),
+        TIOCSPGRP => crate::userland::job_control::tiocspgrp(args, arg),
+        TIOCSCTTY => crate::userland::job_control::tiocsctty(
+            crate::userland::lifecycle::with_current_group(|p| p.terminal_id),
+            arg,
+        ),
+        TIOCGSID => crate::userland::job_control::tiocgsid(arg),
         _ => ENOSYS,
     }
 }
@@ -1509,21 +1801,6 @@ pub fn rt_sigsuspend_handler(args: &mut SyscallArgs) -> i64 {
     }
 }
 
-// ---------- credentials ----------
-
-pub fn getuid_handler(_: &mut SyscallArgs) -> i64 {
-    0
-}
-pub fn getgid_handler(_: &mut SyscallArgs) -> i64 {
-    0
-}
-pub fn geteuid_handler(_: &mut SyscallArgs) -> i64 {
-    0
-}
-pub fn getegid_handler(_: &mut SyscallArgs) -> i64 {
-    0
-}
-
 /// `getpid() -> pid_t`. Phase 4 PR-A returns the real per-process PID
 /// instead of the previous fixed `1`. PIDs are allocated monotonically
 /// starting at `1` by `enter_user_mode_with`, so each successive
@@ -1569,6 +1846,9 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
     if crate::userland::lifecycle::group_member_count(tgid) != 1 {
         return EAGAIN;
     }
+    if let Err(e) = crate::userland::rlimit::check_nproc() {
+        return e;
+    }
 
     // U7: fork now returns immediately to the parent without iretq'ing
     // into the child. The child is inserted into PROCESS_TABLE with a
@@ -1639,10 +1919,10 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
     // 4. Allocate the child PID. Pull parent's L4 frame under one lock,
     //    then build the child Process below.
     let child_pid = alloc_pid();
-    let (parent_l4_frame, parent_vmas) = match with_current_process(|p| {
+    let (parent_l4_frame, parent_vmas, parent_auxv) = match with_current_process(|p| {
         p.address_space
             .as_ref()
-            .map(|a| (a.l4_frame(), a.vmas().clone()))
+            .map(|a| (a.l4_frame(), a.vmas().clone(), a.saved_auxv().to_vec()))
     }) {
         Some(state) => state,
         None => {
@@ -1654,21 +1934,33 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
     // 5. Eagerly clone the parent's address space (fresh L4 + copy of
     //    every leaf page in PML4[0]). Built on the parent's L4 — we
     //    haven't switched CR3 yet, and we don't intend to.
-    let mut child_aspace =
-        match crate::userland::address_space::AddressSpace::clone_for_child(parent_l4_frame) {
-            Ok(a) => a,
-            Err(e) => {
-                crate::debug_error!("fork(): clone_for_child failed: {:?}", e);
-                return -12; // ENOMEM
-            }
-        };
+    let mut child_aspace = match crate::userland::address_space::AddressSpace::clone_for_child(
+        parent_l4_frame,
+        &parent_vmas,
+    ) {
+        Ok(a) => a,
+        Err(e) => {
+            crate::debug_error!("fork(): clone_for_child failed: {:?}", e);
+            return -12; // ENOMEM
+        }
+    };
     *child_aspace.vmas_mut() = parent_vmas;
+    child_aspace.set_saved_auxv(&parent_auxv);
 
     // 6. Build the child Process. State pieces (FD table, cwd, brk,
     //    mmap) are cloned by value; address space ownership transfers.
+    let (job, rlimits, credentials) = crate::userland::lifecycle::with_current_group(|group| {
+        (
+            group.job.fork_child(),
+            group.rlimits,
+            group.credentials.clone(),
+        )
+    });
     let child_process = with_current_process(|parent| crate::userland::lifecycle::Process {
         pid: child_pid,
         parent_pid: parent.pid,
+        // Same process group and session as the parent.
+        job,
         image: None, // child shares parent's image — kept implicitly
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -1677,6 +1969,11 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
         mmap_next: parent.mmap_next,
         fd_table: parent.fd_table.fork_clone(),
         umask: parent.umask,
+        // Resource limits and credentials are inherited across fork.
+        rlimits,
+        credentials,
+        // So are the calling thread's syscall filters.
+        seccomp: parent.seccomp.clone(),
         network_wait: None,
         // POSIX timers are not inherited across fork.
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
@@ -1684,6 +1981,7 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: parent.cwd.clone(),
+        root: parent.root.clone(),
         address_space: Some(child_aspace),
         // Phase 5 PR-B: child inherits parent's signal dispositions
         // and blocked mask. Pending mask resets to empty (POSIX:
@@ -1702,6 +2000,7 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
         // CPU time is per-process, not inherited (POSIX: child's
         // tms_utime starts at zero).
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         // Demand-grown stack: child inherits the parent's exact stack
         // window. The parent's stack pages already copied into the
         // child's L4 via AddressSpace::clone_for_child above.
@@ -1729,6 +2028,16 @@ fn fork_like(args: &mut SyscallArgs, child_rsp_override: Option<u64>) -> i64 {
     // 7. Register child in PROCESS_TABLE and mark ready. The next
     //    scheduling decision (timer preempt, block-and-yield from
     //    parent, or top-level idle) picks the child via `resume_ring3`.
+    if let Some(parent) = crate::userland::lifecycle::current_user_pid() {
+        crate::fs::vfs::inherit_namespace(parent, child_pid);
+        // The child starts at the parent's nice level.
+        crate::process::scheduler::SCHEDULER
+            .lock()
+            .register_user_child(
+                crate::process::entity::EntityId::UserProcess(parent),
+                child_pid,
+            );
+    }
     insert_process(child_process);
     mark_ring3_ready(child_pid);
 
@@ -1822,6 +2131,9 @@ pub fn clone_handler(args: &mut SyscallArgs) -> i64 {
         r11: 0,
     };
 
+    if let Err(e) = crate::userland::rlimit::check_nproc() {
+        return e;
+    }
     let tid = alloc_pid();
     if crate::userland::usercopy::write_unaligned(args.rdx, &tid).is_err() {
         return EFAULT;
@@ -1838,6 +2150,7 @@ pub fn clone_handler(args: &mut SyscallArgs) -> i64 {
     let task = crate::userland::lifecycle::with_current_process(|parent| Process {
         pid: tid,
         parent_pid: parent.parent_pid,
+        job: parent.job.fork_child(),
         image: None,
         exit_kind: ExitKind::None,
         exit_code: 0,
@@ -1846,11 +2159,15 @@ pub fn clone_handler(args: &mut SyscallArgs) -> i64 {
         mmap_next: 0,
         fd_table: FdTable::new(),
         umask: parent.umask,
+        rlimits: parent.rlimits,
+        credentials: parent.credentials.clone(),
+        seccomp: parent.seccomp.clone(),
         network_wait: None,
         real_timer: crate::userland::lifecycle::RealTimerState::disarmed(),
         sleep_deadline: None,
         pending_syscall_interrupt: false,
         cwd: String::new(),
+        root: String::new(),
         address_space: None,
         signal_state: parent.signal_state.fork_clone(),
         // Linux clears an alternate stack for CLONE_VM without CLONE_VFORK.
@@ -1860,6 +2177,7 @@ pub fn clone_handler(args: &mut SyscallArgs) -> i64 {
         exe_path: None,
         cmdline: alloc::vec::Vec::new(),
         utime_ticks: 0,
+        group_cpu_ticks: 0,
         stack_top: 0,
         stack_bottom: 0,
         stack_mapped_bottom: 0,
@@ -1877,15 +2195,36 @@ pub fn clone_handler(args: &mut SyscallArgs) -> i64 {
         let _ = crate::userland::usercopy::write_unaligned(args.rdx, &zero);
         return EAGAIN;
     }
+    if let Some(parent) = crate::userland::lifecycle::current_user_pid() {
+        crate::fs::vfs::inherit_namespace(parent, tid);
+        crate::process::scheduler::SCHEDULER
+            .lock()
+            .register_user_child(crate::process::entity::EntityId::UserProcess(parent), tid);
+    }
     crate::userland::lifecycle::set_clear_child_tid(tid, args.r10);
     crate::userland::lifecycle::mark_ring3_ready(tid);
     tid as i64
 }
 
+/// The `execve` error for an image the loader refused: `ENOENT` for a
+/// missing interpreter, `ELIBBAD` for a broken one, and `ENOEXEC` for a
+/// program that is not a loadable image.
+fn exec_load_errno(error: crate::userland::error::LoaderError) -> i64 {
+    use crate::userland::error::LoaderError;
+    match error {
+        LoaderError::InterpMissing => ENOENT,
+        LoaderError::InterpBad => ELIBBAD,
+        LoaderError::OutOfFrames => ENOMEM,
+        _ => ENOEXEC,
+    }
+}
+
 /// `execve(path, argv, envp)`. Replaces the current process's image
 /// in place: drops user pages from the current address space, builds a
 /// fresh L4, loads the new ELF into it, lays out a new initial stack
 /// with the supplied argv/envp, and `iretq`s into the new entry point.
+/// A `#!` script runs its interpreter instead, with argv rewritten as
+/// Linux does (see `userland::shebang`).
 ///
 /// PID, parent_pid, FD table, cwd, stdin queue, and termios are all
 /// retained — that's the contract of execve. The existing kernel
@@ -1918,25 +2257,7 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         Ok(p) => p,
         Err(e) => return e,
     };
-    // Normalize once before the /bin namespace rewrite. `..` segments must
-    // be collapsed before the prefix check.
-    let normalized_path = crate::userland::lifecycle::with_current_group(|p| {
-        crate::userland::path::normalize_path(&p.cwd, &raw_path)
-    });
-    // Virtual /bin namespace: rewrite the load path to either BB.ELF
-    // (BusyBox applets) or GLAUNCH.ELF (kernel-side GUI apps) AND
-    // override argv[0] so the chosen multicall binary's dispatcher picks
-    // the requested applet. Linux preserves the caller's argv[0]
-    // verbatim; we deviate here because both multicall binaries need
-    // argv[0] to carry the applet name. Documented in
-    // src/userland/bin_namespace.rs.
-    let bin_rewrite = crate::userland::bin_namespace::apply_bin_rewrite(&normalized_path);
-    let bin_applet = bin_rewrite.map(|(_, n)| n);
-    let resolved_path = match bin_rewrite {
-        Some((host_path, _)) => String::from(host_path),
-        None => normalized_path,
-    };
-    let argv_strings: Vec<String> = match copy_user_cstr_array(argv_ptr) {
+    let mut argv_strings: Vec<String> = match copy_user_cstr_array(argv_ptr) {
         Ok(v) => v,
         Err(e) => return e,
     };
@@ -1945,15 +2266,71 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         Err(e) => return e,
     };
 
-    // 2. Read the new binary off the filesystem. Use the existing
-    //    File API; this is the same path the `run` shell command
-    //    takes for top-level launches.
-    let (exec_file, bytes) = match crate::fs::file_handle::File::open_read(&resolved_path) {
-        Ok(file) => match file.read_to_vec() {
-            Ok(v) => (file, v),
+    // 2. Resolve the path and read the new binary off the filesystem,
+    //    following `#!` interpreter scripts. Use the existing File API;
+    //    this is the same path the `run` shell command takes for
+    //    top-level launches.
+    let mut exec_name = raw_path;
+    let mut script_depth = 0;
+    let (bin_applet, resolved_path, exec_file, bytes, exec_meta) = loop {
+        // Normalize once before the /bin namespace rewrite. `..` segments
+        // must be collapsed before the prefix check.
+        let normalized_path = match resolve_current_path(&exec_name, true) {
+            Ok(path) => path,
+            Err(e) => return e,
+        };
+        // Virtual /bin namespace: rewrite the load path to either BB.ELF
+        // (BusyBox applets) or GLAUNCH.ELF (kernel-side GUI apps) AND
+        // override argv[0] so the chosen multicall binary's dispatcher
+        // picks the requested applet. Linux preserves the caller's argv[0]
+        // verbatim; we deviate here because both multicall binaries need
+        // argv[0] to carry the applet name. Documented in
+        // src/userland/bin_namespace.rs.
+        let bin_rewrite = crate::userland::bin_namespace::apply_bin_rewrite(&normalized_path);
+        let bin_applet = bin_rewrite.map(|(_, n)| n);
+        // The /bin namespace is always executable and carries no set-id
+        // bits; every other image needs execute permission.
+        let exec_meta = if bin_rewrite.is_some() {
+            None
+        } else {
+            if let Err(e) = crate::userland::credentials::check_path(
+                &normalized_path,
+                crate::userland::credentials::X_OK,
+            ) {
+                return e;
+            }
+            crate::fs::vfs::vfs_unix_metadata(&normalized_path).ok()
+        };
+        let resolved_path = match bin_rewrite {
+            Some((host_path, _)) => String::from(host_path),
+            None => normalized_path,
+        };
+        let (exec_file, bytes) = match crate::fs::file_handle::File::open_read(&resolved_path) {
+            Ok(file) => match file.read_to_vec() {
+                Ok(v) => (file, v),
+                Err(ref e) => return map_file_err(e),
+            },
             Err(ref e) => return map_file_err(e),
-        },
-        Err(ref e) => return map_file_err(e),
+        };
+        let shebang = match crate::userland::shebang::parse(&bytes) {
+            Ok(Some(shebang)) => shebang,
+            Ok(None) => break (bin_applet, resolved_path, exec_file, bytes, exec_meta),
+            Err(e) => return e,
+        };
+        script_depth += 1;
+        if script_depth > crate::userland::shebang::MAX_DEPTH {
+            return ELOOP;
+        }
+        argv_strings = crate::userland::shebang::rewrite_argv(&shebang, &exec_name, argv_strings);
+        exec_name = shebang.interpreter;
+    };
+    // A dynamic executable's PT_INTERP linker is read here too, before the
+    // CR3 switch, so a missing or broken linker fails the exec cleanly.
+    let interp = match crate::userland::loader::read_interpreter(&bytes, |path| {
+        resolve_current_path(path, true).ok()
+    }) {
+        Ok(interp) => interp,
+        Err(e) => return exec_load_errno(e),
     };
     let mut at_random = [0u8; 16];
     if crate::random::fill_bytes(&mut at_random).is_err() {
@@ -1981,7 +2358,8 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         new_aspace.activate();
     }
 
-    let mut image = match crate::userland::loader::load_elf_file(&bytes, exec_file) {
+    let mut image = match crate::userland::loader::load_elf_file(&bytes, exec_file, interp.as_ref())
+    {
         Ok(i) => i,
         Err(e) => {
             crate::debug_error!("execve(): load_elf failed: {:?}", e);
@@ -1994,7 +2372,7 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
                 p.image = old_image;
                 p.address_space = old_aspace;
             });
-            return EINVAL;
+            return exec_load_errno(e);
         }
     };
 
@@ -2022,8 +2400,8 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         start: image.bounds_start,
         end: image.bounds_end,
     };
-    let phdr_bytes = image.phdr_bytes.clone();
-    let e_phnum = image.e_phnum;
+    let vdso_base = crate::userland::vdso::map_into(&mut new_aspace);
+    let auxv = super::ImageAuxv::from_image(&image, vdso_base);
     // Demand-grown stack (U3): the new image carries its own stack
     // window. The old image's grown stack pages leak with the old
     // AddressSpace (bump allocator never reclaims anyway).
@@ -2056,14 +2434,9 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         };
     }
     let envp_refs: Vec<&str> = envp_strings.iter().map(|s| s.as_str()).collect();
-    let user_rsp = super::build_initial_stack(
-        stack_top,
-        &phdr_bytes,
-        e_phnum,
-        &argv_refs,
-        &envp_refs,
-        &at_random,
-    );
+    let initial = super::build_initial_stack(stack_top, &auxv, &argv_refs, &envp_refs, &at_random);
+    let user_rsp = initial.rsp;
+    new_aspace.set_saved_auxv(&initial.auxv);
 
     // From commit onward the targeted AddressSpace walker is the sole page
     // owner; UserImage remains only executable metadata.
@@ -2110,6 +2483,15 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         );
         closed_on_exec
     });
+    // Set-id bits are ignored under ptrace, as Linux does without
+    // CAP_SYS_PTRACE in the tracer, and under no_new_privs. The guard keeps
+    // a tracer from attaching between the check and the change.
+    let cred_guard = crate::userland::ptrace::cred_guard();
+    let no_new_privs = crate::userland::lifecycle::with_current_process(|p| p.seccomp.no_new_privs);
+    let exec_meta =
+        exec_meta.filter(|_| !crate::userland::ptrace::current_is_traced() && !no_new_privs);
+    crate::userland::lifecycle::with_current_group(|p| p.credentials.exec(exec_meta.as_ref()));
+    drop(cred_guard);
     // Pipe endpoint destruction can wake a parent blocked in the musl/Git
     // successful-exec handshake. Drop only after with_current_group releases
     // PROCESS_TABLE, matching exit-time fd teardown's lock discipline.
@@ -2158,6 +2540,8 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
         rcx: 0,
         r11: 0,
     };
+    // A tracer sees the new image before its first instruction.
+    crate::userland::ptrace::exec_stop(&state);
     unsafe {
         super::iretq_to_user_with_regs(&state as *const _, user_cs, user_ss);
     }
@@ -2165,12 +2549,10 @@ pub fn execve_handler(args: &mut SyscallArgs) -> i64 {
 
 // ---------- Phase 5 PR-B: signals ----------
 
-/// `kill(pid, sig) -> int`. Sets `sig` pending on the target process.
-///
-/// Synchronous-fork model: the only addressable processes are
-/// "self" (`pid == getpid()`) and "any parent that's currently
-/// stashed in `PARENT_STASH`" — the latter being the only other
-/// process that exists. Any other PID returns `-ESRCH`.
+/// `kill(pid, sig) -> int`. Sets `sig` pending on the target process,
+/// or on every process selected by a process-group target: `0` for the
+/// caller's group, `-pgid` for group `pgid`, and `-1` for every process
+/// except the caller. SIGCONT also resumes a stopped target.
 ///
 /// `sig == 0` is the "is the target alive?" probe; we return 0 for
 /// addressable PIDs without setting any pending bit.
@@ -2180,25 +2562,26 @@ pub fn kill_handler(args: &mut SyscallArgs) -> i64 {
     if sig < 0 || (sig as usize) > crate::userland::signal::NSIG {
         return EINVAL;
     }
-    // No process groups: pid must name one live ring-3 process. The
-    // single-user model has no permission checks — any process may
-    // signal any other (this is what lets a ring-3 task manager
-    // implement End Task through the ordinary kill path).
-    if pid <= 0 {
+    // Root may signal anything (this is what lets a ring-3 task manager
+    // implement End Task through the ordinary kill path); other callers
+    // only reach processes whose real or saved uid matches their own.
+    let caller = crate::userland::lifecycle::current_pid();
+    let targets = crate::userland::job_control::kill_targets(pid, caller);
+    if targets.is_empty() {
         return ESRCH;
     }
-    let raised = crate::userland::lifecycle::with_process(pid as u32, |target| {
-        if sig != 0 {
-            target.signal_state.raise(sig);
-        }
-    });
-    if raised.is_none() {
-        return ESRCH;
+    let permitted: alloc::vec::Vec<u32> = targets
+        .into_iter()
+        .filter(|&target| {
+            crate::userland::lifecycle::task_tgid(target)
+                .is_some_and(|tgid| crate::userland::credentials::check_signal(tgid).is_ok())
+        })
+        .collect();
+    if permitted.is_empty() {
+        return EPERM;
     }
-    if sig != 0 && pid != crate::userland::lifecycle::current_pid() as i32 {
-        // If the target is parked in a blocking syscall, unblock it so
-        // the pending signal is examined at its next dispatcher entry.
-        crate::userland::lifecycle::wake_ring3_for_signal(pid as u32);
+    for target in permitted {
+        crate::userland::job_control::send_signal(target, sig);
     }
     0
 }
@@ -2264,6 +2647,7 @@ pub fn rt_sigreturn_handler(_args: &mut SyscallArgs) -> i64 {
     crate::userland::lifecycle::with_current_process(|p| {
         p.signal_state.blocked = saved_blocked;
     });
+    crate::userland::ptrace::leave_syscall();
 
     let user_cs = crate::arch::x86_64::gdt::selectors().user_code.0 as u64;
     let user_ss = crate::arch::x86_64::gdt::selectors().user_data.0 as u64;
@@ -2304,6 +2688,7 @@ unsafe fn deliver_signal(
 ) -> ! {
     use crate::userland::user_state::UserState;
 
+    crate::userland::ptrace::leave_syscall();
     if action.sa_restorer == 0 {
         // No restorer means the handler can't return cleanly via the
         // standard rt_sigreturn trampoline. We still deliver — the
@@ -2388,6 +2773,7 @@ unsafe fn deliver_signal(
                     error_code: Some(0x6),
                     fault_addr: Some(VirtAddr::new(0)),
                     fault_rip: VirtAddr::new(user_rip),
+                    user_regs: saved,
                 },
             );
         }
@@ -2415,6 +2801,7 @@ unsafe fn deliver_signal(
                 error_code: Some(0x6),
                 fault_addr: Some(VirtAddr::new(frame_addr)),
                 fault_rip: VirtAddr::new(user_rip),
+                user_regs: saved,
             },
         );
     }
@@ -2518,6 +2905,10 @@ pub fn maybe_deliver_signal(args: &SyscallArgs, syscall_ret: i64) -> Option<i64>
     // — synthetic dispatcher tests have no scheduler context to yield
     // from).
     maybe_terminate_pending_fatal_signal();
+    // Then default-action stops: the process parks here and, once
+    // continued, returns `syscall_ret` (or runs a handler for a signal
+    // that arrived meanwhile).
+    crate::userland::job_control::stop_if_pending_after_syscall(args, syscall_ret);
     if let Some((sig, action, restore_mask)) = prepare_deliverable_signal() {
         unsafe {
             deliver_signal(sig, action, args, syscall_ret, restore_mask);
@@ -2534,25 +2925,38 @@ pub fn maybe_terminate_pending_fatal_signal() {
         crate::userland::lifecycle::current_user_pid(),
         Some(pid) if pid != crate::userland::lifecycle::KERNEL_PID
     ) {
+        // A sibling's exit_group may have stopped this task on another CPU.
+        crate::userland::lifecycle::exit_if_stopped();
+        // A tracee's fatal signal waits for its signal-delivery stop.
+        if crate::userland::ptrace::defers_fatal_signal() {
+            return;
+        }
         let fatal = crate::userland::lifecycle::with_current_process(|p| {
             p.signal_state.take_fatal_default()
         });
         if let Some(sig) = fatal {
-            let (pid, parent_pid) =
-                crate::userland::lifecycle::with_active_user(|au| (au.pid, au.parent_pid));
-            let code = 128 + sig as i64; // shell convention for the exit code
-            crate::debug_info!(
-                "USERLAND: pid={} killed by signal {} (default action)",
-                pid,
-                sig
-            );
-            *LAST_EXIT_CODE.lock() = Some(code);
-            crate::userland::lifecycle::notify_parent_of_signaled_exit(pid, parent_pid, sig, code);
-            crate::userland::lifecycle::cooperative_exit(code);
+            terminate_current_by_signal(sig);
         }
     }
 }
 
+/// Kill the current ring-3 process as the default action of `sig` would,
+/// reporting the signal to its parent. Callers check that a real ring-3
+/// task is loaded.
+pub fn terminate_current_by_signal(sig: i32) -> ! {
+    let (pid, parent_pid) =
+        crate::userland::lifecycle::with_active_user(|au| (au.pid, au.parent_pid));
+    let code = 128 + sig as i64; // shell convention for the exit code
+    crate::debug_info!(
+        "USERLAND: pid={} killed by signal {} (default action)",
+        pid,
+        sig
+    );
+    *LAST_EXIT_CODE.lock() = Some(code);
+    crate::userland::lifecycle::notify_parent_of_signaled_exit(pid, parent_pid, sig, code);
+    crate::userland::lifecycle::cooperative_exit(code);
+}
+
 // ---------- Phase 5 PR-A: pipes ----------
 
 /// `pipe(int pipefd[2]) -> int`. Equivalent to `pipe2(pipefd, 0)`.
@@ -2630,11 +3034,14 @@ fn pipe2_common(fds_ptr: u64, flags: u32) -> i64 {
 ///     parks. The wake path on the other side (`notify_parent_of_*`)
 ///     is already wired in U3.
 ///
-/// WNOHANG is the only options bit handled today. WUNTRACED / WCONTINUED
-/// require process-state tracking we don't implement.
+/// `pid` may also be `0` (any child in the caller's process group) or
+/// `-pgid`. `WUNTRACED` reports a child that stopped and `WCONTINUED`
+/// one resumed by SIGCONT, each once, after any matching zombie.
 pub fn wait4_handler(args: &mut SyscallArgs) -> i64 {
     use crate::userland::abi::ECHILD;
     const WNOHANG: u64 = 1;
+    const WUNTRACED: u64 = 2;
+    const WCONTINUED: u64 = 8;
 
     let target = args.rdi as i32;
     let status_ptr = args.rsi;
@@ -2664,10 +3071,40 @@ pub fn wait4_handler(args: &mut SyscallArgs) -> i64 {
         return pid as i64;
     }
 
+    // Ptrace stops go to the tracer whatever the options.
+    if let Some((tid, status)) = crate::userland::ptrace::take_report(me, target) {
+        if status_ptr != 0 {
+            if let Err(e) = crate::userland::usercopy::write_unaligned(status_ptr, &status) {
+                return e;
+            }
+        }
+        return tid as i64;
+    }
+
+    if options & (WUNTRACED | WCONTINUED) != 0 {
+        if let Some((pid, report)) = crate::userland::job_control::take_report(
+            me,
+            target,
+            options & WUNTRACED != 0,
+            options & WCONTINUED != 0,
+        ) {
+            if status_ptr != 0 {
+                if let Err(e) =
+                    crate::userland::usercopy::write_unaligned(status_ptr, &report.status())
+                {
+                    return e;
+                }
+            }
+            return pid as i64;
+        }
+    }
+
     // No matching zombie. POSIX distinguishes "no children at all"
     // (-ECHILD) from "has children but none ready" (block, or return
-    // 0 under WNOHANG).
-    if !crate::userland::lifecycle::has_children(me) {
+    // 0 under WNOHANG). A tracer waits on its tracees as on children.
+    if !crate::userland::lifecycle::has_children_matching(me, target)
+        && !crate::userland::ptrace::traces_matching(me, target)
+    {
         return ECHILD;
     }
 
@@ -2803,6 +3240,9 @@ const F_GETLK: i32 = 5;
 const F_SETLK: i32 = 6;
 const F_SETLKW: i32 = 7;
 const F_DUPFD_CLOEXEC: i32 = 1030;
+/// memfd sealing (`crate::userland::memfd`).
+const F_ADD_SEALS: i32 = 1033;
+const F_GET_SEALS: i32 = 1034;
 const FD_CLOEXEC: u64 = 1;
 
 /// `struct flock` lock types (x86-64 Linux).
@@ -2829,9 +3269,10 @@ const _W_OK: u32 = 2;
 const _X_OK: u32 = 1;
 
 /// `clock_gettime` clock IDs we recognize. Realtime is anchored to the boot
-/// RTC snapshot; monotonic remains PIT uptime.
-const CLOCK_REALTIME: i32 = 0;
-const CLOCK_MONOTONIC: i32 = 1;
+/// RTC snapshot until set or disciplined; monotonic is uptime from the TSC
+/// clocksource.
+pub(crate) const CLOCK_REALTIME: i32 = 0;
+pub(crate) const CLOCK_MONOTONIC: i32 = 1;
 
 /// `linux_stat64` (x86-64) — 144 bytes laid out per `arch/x86/include/uapi/asm/stat.h`.
 #[repr(C)]
@@ -2886,16 +3327,12 @@ fn with_fd_table_mut<R>(f: impl FnOnce(&mut FdTable) -> R) -> R {
     crate::userland::lifecycle::with_active_user(|au| f(&mut au.fd_table))
 }
 
-fn with_cwd<R>(f: impl FnOnce(&str) -> R) -> R {
-    crate::userland::lifecycle::with_active_user(|au| f(&au.cwd))
-}
-
 fn set_cwd(new: String) {
     crate::userland::lifecycle::with_active_user(|au| au.cwd = new);
 }
 
 /// Map `crate::fs::filesystem::FilesystemError` onto Linux `-errno`.
-fn map_filesystem_err(err: &crate::fs::filesystem::FilesystemError) -> i64 {
+pub(crate) fn map_filesystem_err(err: &crate::fs::filesystem::FilesystemError) -> i64 {
     use crate::fs::filesystem::FilesystemError as FE;
     match err {
         FE::NotFound => ENOENT,
@@ -2908,6 +3345,7 @@ fn map_filesystem_err(err: &crate::fs::filesystem::FilesystemError) -> i64 {
         FE::NotEmpty => ENOTEMPTY,
         FE::DiskFull => ENOSPC,
         FE::BufferTooSmall => EFBIG,
+        FE::Busy => EBUSY,
         FE::UnsupportedFeature => EOPNOTSUPP,
         FE::UnsupportedOperation => ENOSYS,
         _ => EIO,
@@ -2915,7 +3353,7 @@ fn map_filesystem_err(err: &crate::fs::filesystem::FilesystemError) -> i64 {
 }
 
 /// Map `crate::fs::file_handle::FileError` onto Linux `-errno` values.
-fn map_file_err(err: &crate::fs::file_handle::FileError) -> i64 {
+pub(crate) fn map_file_err(err: &crate::fs::file_handle::FileError) -> i64 {
     use crate::fs::file_handle::FileError as FE;
     match err {
         FE::NotFound => ENOENT,
@@ -2946,33 +3384,65 @@ fn map_fs_err(err: &crate::fs::fs_manager::FsError) -> i64 {
 
 /// Resolve a user path string against the active CWD into a normalized
 /// kernel-side string. Runtime `/etc` lives in the root overlay.
-fn resolve_user_path(ptr: u64) -> Result<String, i64> {
+pub(crate) fn resolve_user_path(ptr: u64) -> Result<String, i64> {
+    let raw = copy_user_cstr(ptr)?;
+    resolve_current_path(&raw, true)
+}
+
+/// [`resolve_user_path`] for the calls that act on a final symlink itself
+/// rather than on its target.
+pub(crate) fn resolve_user_path_nofollow(ptr: u64) -> Result<String, i64> {
     let raw = copy_user_cstr(ptr)?;
-    Ok(with_cwd(|cwd| normalize_path(cwd, &raw)))
+    resolve_current_path(&raw, false)
+}
+
+/// Resolve `raw` against the caller's root and working directory. Inside a
+/// `chroot` symlinks are expanded here, against that root, and a final
+/// one only when `follow` is set.
+pub(crate) fn resolve_current_path(raw: &str, follow: bool) -> Result<String, i64> {
+    let (root, cwd) =
+        crate::userland::lifecycle::with_active_user(|au| (au.root.clone(), au.cwd.clone()));
+    resolve_path_following(&root, &cwd, raw, follow, |path| {
+        let target = crate::fs::vfs::vfs_read_link(path).ok()?;
+        String::from_utf8(target).ok()
+    })
+}
+
+/// Kernel path `path` as the caller sees it from inside its root.
+pub(crate) fn current_view_path(path: String) -> String {
+    let root = crate::userland::lifecycle::with_active_user(|au| au.root.clone());
+    crate::userland::path::view_path(&root, &path).unwrap_or(path)
 }
 
 // ---------- open / openat / close ----------
 
 /// `open(path, flags, mode) -> int`. Equivalent to `openat(AT_FDCWD, …)`.
 pub fn open_handler(args: &mut SyscallArgs) -> i64 {
-    open_common(AT_FDCWD, args.rdi, args.rsi as u32)
+    open_common(AT_FDCWD, args.rdi, args.rsi as u32, args.rdx as u32)
 }
 
 /// `openat(dirfd, path, flags, mode) -> int`. Only `AT_FDCWD` for dirfd
 /// is supported in this milestone — opening a file *relative to a
 /// directory fd* needs the FAT subdir walker (PR-4).
 pub fn openat_handler(args: &mut SyscallArgs) -> i64 {
-    open_common(args.rdi as i32, args.rsi, args.rdx as u32)
+    open_common(args.rdi as i32, args.rsi, args.rdx as u32, args.r10 as u32)
 }
 
-fn open_common(dirfd: i32, path_ptr: u64, flags: u32) -> i64 {
+/// `mode` gives the permission bits of a file that `O_CREAT` creates.
+fn open_common(dirfd: i32, path_ptr: u64, flags: u32, mode: u32) -> i64 {
     if dirfd != AT_FDCWD {
         // openat with a real dirfd is rejected for now. zsh and basic libc
         // overwhelmingly use AT_FDCWD; `man 2 openat` documents this as
         // the common case.
         return ENOSYS;
     }
-    let path = match resolve_user_path(path_ptr) {
+    // `O_CREAT | O_EXCL` refuses a final symlink rather than follow it.
+    let exclusive = flags & (O_CREAT | O_EXCL) == O_CREAT | O_EXCL;
+    let path = match if exclusive {
+        resolve_user_path_nofollow(path_ptr)
+    } else {
+        resolve_user_path(path_ptr)
+    } {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -2981,8 +3451,9 @@ fn open_common(dirfd: i32, path_ptr: u64, flags: u32) -> i64 {
 
     // Resolve the synthetic device nodes before mounted filesystems so no
     // disk entry can shadow them. Any other `/dev/*` path retains its VFS
-    // behavior. `/dev/null` is the one writable node — git opens it O_RDWR
-    // at startup (`sanitize_stdfds`) and shells redirect into it.
+    // behavior. `/dev/null` and the terminals are the writable nodes — git
+    // opens `/dev/null` O_RDWR at startup (`sanitize_stdfds`) and shells
+    // redirect into it.
     if let Some(node) = crate::userland::devfs::classify(&path) {
         let slot = match node {
             crate::userland::devfs::DeviceNode::Directory => {
@@ -2998,6 +3469,39 @@ fn open_common(dirfd: i32, path_ptr: u64, flags: u32) -> i64 {
                 FdSlot::Urandom { cloexec }
             }
             crate::userland::devfs::DeviceNode::Null => FdSlot::DevNull { cloexec },
+            crate::userland::devfs::DeviceNode::PtsDirectory => {
+                if want_write {
+                    return EACCES;
+                }
+                let entries = crate::terminal::pty::terminal_ids()
+                    .into_iter()
+                    .map(|id| (alloc::format!("{}", id.0), false))
+                    .collect();
+                FdSlot::VirtualDir {
+                    entries: crate::lib::arc::Arc::new(entries),
+                    path: crate::lib::arc::Arc::new(path.clone()),
+                    cursor: 0,
+                    cloexec,
+                }
+            }
+            crate::userland::devfs::DeviceNode::Ptmx => {
+                match crate::userland::pts::open_ptmx(flags) {
+                    Ok(slot) => slot,
+                    Err(e) => return e,
+                }
+            }
+            crate::userland::devfs::DeviceNode::Tty => {
+                match crate::userland::pts::open_tty(flags) {
+                    Ok(slot) => slot,
+                    Err(e) => return e,
+                }
+            }
+            crate::userland::devfs::DeviceNode::Pts(index) => {
+                match crate::userland::pts::open_pts(index, flags) {
+                    Ok(slot) => slot,
+                    Err(e) => return e,
+                }
+            }
         };
         return with_fd_table_mut(|t| t.alloc(slot))
             .map(|fd| fd as i64)
@@ -3098,10 +3602,26 @@ fn open_common(dirfd: i32, path_ptr: u64, flags: u32) -> i64 {
         }
     };
 
-    if let Ok(m) = meta_result {
-        if want_create && (flags & O_EXCL) != 0 {
-            return EEXIST;
+    let access = flags & O_ACCMODE;
+    let permission = match meta_result {
+        Ok(_) if want_create && (flags & O_EXCL) != 0 => return EEXIST,
+        Ok(_) => {
+            use crate::userland::credentials::{R_OK, W_OK};
+            let read = if access == O_WRONLY { 0 } else { R_OK };
+            let write = if access != O_RDONLY || (flags & O_TRUNC) != 0 {
+                W_OK
+            } else {
+                0
+            };
+            crate::userland::credentials::check_path(&path, read | write)
         }
+        Err(()) => crate::userland::credentials::check_create(&path),
+    };
+    if let Err(e) = permission {
+        return e;
+    }
+
+    if let Ok(m) = &meta_result {
         if m.file_type == FileType::Directory {
             let dir = match crate::fs::file_handle::Directory::open_names(&path) {
                 Ok(d) => d,
@@ -3120,8 +3640,7 @@ fn open_common(dirfd: i32, path_ptr: u64, flags: u32) -> i64 {
     }
 
     // Map Linux flags to our FileMode.
-    let access = flags & O_ACCMODE;
-    let mode = crate::fs::filesystem::FileMode {
+    let file_mode = crate::fs::filesystem::FileMode {
         read: access == O_RDONLY || access == O_RDWR,
         write: want_write,
         append: (flags & O_APPEND) != 0,
@@ -3129,10 +3648,13 @@ fn open_common(dirfd: i32, path_ptr: u64, flags: u32) -> i64 {
         truncate: (flags & O_TRUNC) != 0,
     };
 
-    let handle = match crate::fs::file_handle::File::open(&path, mode) {
+    let handle = match crate::fs::file_handle::File::open(&path, file_mode) {
         Ok(h) => h,
         Err(ref e) => return map_file_err(e),
     };
+    if meta_result.is_err() {
+        crate::userland::credentials::init_new_node(&path, mode);
+    }
     let status_flags = access | (flags & (O_APPEND | O_NONBLOCK));
     with_fd_table_mut(|t| {
         t.alloc(FdSlot::File {
@@ -3225,7 +3747,7 @@ fn dev_namespace_mutation_check(path: &str) -> Option<i64> {
 }
 
 pub fn mkdir_handler(args: &mut SyscallArgs) -> i64 {
-    let path = match resolve_user_path(args.rdi) {
+    let path = match resolve_user_path_nofollow(args.rdi) {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -3241,8 +3763,14 @@ pub fn mkdir_handler(args: &mut SyscallArgs) -> i64 {
     if let Some(e) = dev_namespace_mutation_check(&path) {
         return e;
     }
+    if let Err(e) = crate::userland::credentials::check_create(&path) {
+        return e;
+    }
     match crate::fs::vfs::vfs_mkdir(&path) {
-        Ok(()) => 0,
+        Ok(()) => {
+            crate::userland::credentials::init_new_node(&path, args.rsi as u32);
+            0
+        }
         Err(ref e) => map_filesystem_err(e),
     }
 }
@@ -3265,7 +3793,7 @@ pub fn mkdirat_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn rmdir_handler(args: &mut SyscallArgs) -> i64 {
-    let path = match resolve_user_path(args.rdi) {
+    let path = match resolve_user_path_nofollow(args.rdi) {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -3284,6 +3812,9 @@ pub fn rmdir_handler(args: &mut SyscallArgs) -> i64 {
     if path == "/" {
         return EBUSY;
     }
+    if let Err(e) = crate::userland::credentials::check_delete(&path) {
+        return e;
+    }
     match crate::fs::vfs::vfs_rmdir(&path) {
         Ok(()) => 0,
         Err(ref e) => map_filesystem_err(e),
@@ -3291,7 +3822,7 @@ pub fn rmdir_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn unlink_handler(args: &mut SyscallArgs) -> i64 {
-    let path = match resolve_user_path(args.rdi) {
+    let path = match resolve_user_path_nofollow(args.rdi) {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -3307,6 +3838,9 @@ pub fn unlink_handler(args: &mut SyscallArgs) -> i64 {
     if let Some(e) = dev_namespace_mutation_check(&path) {
         return e;
     }
+    if let Err(e) = crate::userland::credentials::check_delete(&path) {
+        return e;
+    }
     match crate::fs::vfs::vfs_unlink(&path) {
         Ok(()) => 0,
         Err(ref e) => map_filesystem_err(e),
@@ -3319,7 +3853,7 @@ pub fn unlinkat_handler(args: &mut SyscallArgs) -> i64 {
         return ENOSYS;
     }
     let flags = args.rdx as u32;
-    let path = match resolve_user_path(args.rsi) {
+    let path = match resolve_user_path_nofollow(args.rsi) {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -3335,6 +3869,9 @@ pub fn unlinkat_handler(args: &mut SyscallArgs) -> i64 {
     if let Some(e) = dev_namespace_mutation_check(&path) {
         return e;
     }
+    if let Err(e) = crate::userland::credentials::check_delete(&path) {
+        return e;
+    }
     // AT_REMOVEDIR = 0x200
     if flags & 0x200 != 0 {
         if path == "/" {
@@ -3353,11 +3890,11 @@ pub fn unlinkat_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn rename_handler(args: &mut SyscallArgs) -> i64 {
-    let old = match resolve_user_path(args.rdi) {
+    let old = match resolve_user_path_nofollow(args.rdi) {
         Ok(p) => p,
         Err(e) => return e,
     };
-    let new = match resolve_user_path(args.rsi) {
+    let new = match resolve_user_path_nofollow(args.rsi) {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -3385,6 +3922,9 @@ pub fn rename_handler(args: &mut SyscallArgs) -> i64 {
     if let Some(e) = managed_etc_mutation_check(&new) {
         return e;
     }
+    if let Err(e) = rename_permission(&old, &new) {
+        return e;
+    }
     match crate::fs::vfs::vfs_rename(&old, &new) {
         Ok(()) => 0,
         // UnsupportedOperation from vfs_rename signals cross-mount.
@@ -3393,6 +3933,17 @@ pub fn rename_handler(args: &mut SyscallArgs) -> i64 {
     }
 }
 
+/// Removing `old` from its directory and adding (or replacing) `new`.
+fn rename_permission(old: &str, new: &str) -> Result<(), i64> {
+    use crate::userland::credentials::{check_create, check_delete};
+    check_delete(old)?;
+    if crate::fs::exists(new) {
+        check_delete(new)
+    } else {
+        check_create(new)
+    }
+}
+
 pub fn renameat_handler(args: &mut SyscallArgs) -> i64 {
     let old_dfd = args.rdi as i32;
     let new_dfd = args.rdx as i32;
@@ -3412,11 +3963,11 @@ pub fn renameat_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn link_handler(args: &mut SyscallArgs) -> i64 {
-    let old = match resolve_user_path(args.rdi) {
+    let old = match resolve_user_path_nofollow(args.rdi) {
         Ok(path) => path,
         Err(error) => return error,
     };
-    let new = match resolve_user_path(args.rsi) {
+    let new = match resolve_user_path_nofollow(args.rsi) {
         Ok(path) => path,
         Err(error) => return error,
     };
@@ -3431,6 +3982,9 @@ pub fn link_handler(args: &mut SyscallArgs) -> i64 {
     {
         return error;
     }
+    if let Err(error) = crate::userland::credentials::check_create(&new) {
+        return error;
+    }
     match crate::fs::vfs::vfs_link(&old, &new) {
         Ok(()) => 0,
         Err(crate::fs::filesystem::FilesystemError::UnsupportedOperation) => EXDEV,
@@ -3459,7 +4013,7 @@ pub fn symlink_handler(args: &mut SyscallArgs) -> i64 {
         Ok(target) => target,
         Err(error) => return error,
     };
-    let link_path = match resolve_user_path(args.rsi) {
+    let link_path = match resolve_user_path_nofollow(args.rsi) {
         Ok(path) => path,
         Err(error) => return error,
     };
@@ -3470,6 +4024,9 @@ pub fn symlink_handler(args: &mut SyscallArgs) -> i64 {
     {
         return error;
     }
+    if let Err(error) = crate::userland::credentials::check_create(&link_path) {
+        return error;
+    }
     match crate::fs::vfs::vfs_symlink(&target, &link_path) {
         Ok(()) => 0,
         Err(ref error) => map_filesystem_err(error),
@@ -3497,7 +4054,7 @@ pub fn symlinkat_handler(args: &mut SyscallArgs) -> i64 {
 pub fn creat_handler(args: &mut SyscallArgs) -> i64 {
     // O_WRONLY=1, O_CREAT=0o100, O_TRUNC=0o1000
     let flags = 1u32 | O_CREAT | O_TRUNC;
-    open_common(AT_FDCWD, args.rdi, flags)
+    open_common(AT_FDCWD, args.rdi, flags, args.rsi as u32)
 }
 
 pub fn ftruncate_handler(args: &mut SyscallArgs) -> i64 {
@@ -3514,6 +4071,12 @@ pub fn ftruncate_handler(args: &mut SyscallArgs) -> i64 {
     if crate::userland::etc::is_managed_path(&handle.path()) {
         return EROFS;
     }
+    if let Err(e) = crate::userland::memfd::check_resize(&handle, new_size) {
+        return e;
+    }
+    if let Err(e) = crate::userland::rlimit::check_file_size(new_size) {
+        return e;
+    }
     handle
         .truncate(new_size)
         .map_or_else(|ref error| map_file_err(error), |_| 0)
@@ -3540,6 +4103,11 @@ pub fn truncate_handler(args: &mut SyscallArgs) -> i64 {
     if !crate::fs::vfs::vfs_is_writable(&path) {
         return EROFS;
     }
+    if let Err(e) =
+        crate::userland::credentials::check_path(&path, crate::userland::credentials::W_OK)
+    {
+        return e;
+    }
     let mode = crate::fs::filesystem::FileMode {
         read: false,
         write: true,
@@ -3547,6 +4115,9 @@ pub fn truncate_handler(args: &mut SyscallArgs) -> i64 {
         create: false,
         truncate: false,
     };
+    if let Err(e) = crate::userland::rlimit::check_file_size(new_size) {
+        return e;
+    }
     let handle = match crate::fs::file_handle::File::open(&path, mode) {
         Ok(h) => h,
         Err(ref e) => return map_file_err(e),
@@ -3793,13 +4364,26 @@ pub fn sendfile_handler(args: &mut SyscallArgs) -> i64 {
                     n
                 }
             },
-            Out::File(handle) => match handle.write(&buf[..n]) {
-                Ok(w) => w,
-                Err(ref e) => {
-                    error = Some(map_file_err(e));
-                    0
+            Out::File(handle) => {
+                let allowed =
+                    crate::userland::memfd::check_write(handle, handle.position(), n as u64)
+                        .and_then(|()| {
+                            crate::userland::rlimit::clamp_file_write(handle.position(), n as u64)
+                        });
+                match allowed {
+                    Err(e) => {
+                        error = Some(e);
+                        0
+                    }
+                    Ok(allowed) => match handle.write(&buf[..allowed as usize]) {
+                        Ok(w) => w,
+                        Err(ref e) => {
+                            error = Some(map_file_err(e));
+                            0
+                        }
+                    },
                 }
-            },
+            }
             Out::Pipe(handle) => {
                 if handle.pipe().readers() == 0 {
                     error = Some(crate::userland::abi::EPIPE);
@@ -3963,7 +4547,7 @@ pub fn fcntl_handler(args: &mut SyscallArgs) -> i64 {
             if with_fd_slot(fd).is_none() {
                 return EBADF;
             }
-            if arg > i32::MAX as u64 || arg >= FD_TABLE_SIZE as u64 {
+            if arg > i32::MAX as u64 || arg >= with_fd_table_mut(|t| t.limit()) as u64 {
                 return EINVAL;
             }
             with_fd_table_mut(|t| t.dup_from(fd, arg as i32, cmd == F_DUPFD_CLOEXEC))
@@ -4000,8 +4584,23 @@ pub fn fcntl_handler(args: &mut SyscallArgs) -> i64 {
             Some(FdSlot::EventFd { handle, .. }) => {
                 (O_RDWR | if handle.nonblocking() { O_NONBLOCK } else { 0 }) as i64
             }
+            Some(FdSlot::TimerFd { handle, .. }) => {
+                (O_RDWR | if handle.nonblocking() { O_NONBLOCK } else { 0 }) as i64
+            }
+            Some(FdSlot::SignalFd { handle, .. }) => {
+                (O_RDWR | if handle.nonblocking() { O_NONBLOCK } else { 0 }) as i64
+            }
+            Some(FdSlot::Inotify { handle, .. }) => {
+                (O_RDONLY | if handle.nonblocking() { O_NONBLOCK } else { 0 }) as i64
+            }
             Some(FdSlot::Epoll { .. }) => O_RDONLY as i64,
-            Some(FdSlot::LocalStream { handle, .. }) => {
+            Some(FdSlot::UnixSocket { handle, .. }) => {
+                (O_RDWR | if handle.nonblocking() { O_NONBLOCK } else { 0 }) as i64
+            }
+            Some(FdSlot::PtyMaster { master, .. }) => {
+                (O_RDWR | if master.nonblocking() { O_NONBLOCK } else { 0 }) as i64
+            }
+            Some(FdSlot::PtySlave { handle, .. }) => {
                 (O_RDWR | if handle.nonblocking() { O_NONBLOCK } else { 0 }) as i64
             }
             Some(_) => O_RDONLY as i64,
@@ -4036,8 +4635,28 @@ pub fn fcntl_handler(args: &mut SyscallArgs) -> i64 {
                 handle.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
                 0
             }
+            Some(FdSlot::TimerFd { handle, .. }) => {
+                handle.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
+                0
+            }
+            Some(FdSlot::SignalFd { handle, .. }) => {
+                handle.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
+                0
+            }
+            Some(FdSlot::Inotify { handle, .. }) => {
+                handle.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
+                0
+            }
             Some(FdSlot::Epoll { .. }) => 0,
-            Some(FdSlot::LocalStream { handle, .. }) => {
+            Some(FdSlot::UnixSocket { handle, .. }) => {
+                handle.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
+                0
+            }
+            Some(FdSlot::PtyMaster { master, .. }) => {
+                master.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
+                0
+            }
+            Some(FdSlot::PtySlave { handle, .. }) => {
                 handle.set_nonblocking(arg & O_NONBLOCK as u64 != 0);
                 0
             }
@@ -4045,6 +4664,27 @@ pub fn fcntl_handler(args: &mut SyscallArgs) -> i64 {
             None => EBADF,
         },
         F_GETLK | F_SETLK | F_SETLKW => fcntl_lock(args, fd, cmd, arg),
+        F_ADD_SEALS => match with_fd_slot(fd) {
+            Some(FdSlot::File {
+                handle,
+                status_flags,
+                ..
+            }) => crate::userland::memfd::add_seals(
+                &handle,
+                status_flags & O_ACCMODE != O_RDONLY,
+                arg as u32,
+            )
+            .map_or_else(|e| e, |_| 0),
+            Some(_) => EINVAL,
+            None => EBADF,
+        },
+        F_GET_SEALS => match with_fd_slot(fd) {
+            Some(FdSlot::File { handle, .. }) => {
+                crate::userland::memfd::seals(&handle).map_or(EINVAL, i64::from)
+            }
+            Some(_) => EINVAL,
+            None => EBADF,
+        },
         _ => ENOSYS,
     }
 }
@@ -4248,7 +4888,7 @@ pub fn stat_handler(args: &mut SyscallArgs) -> i64 {
 }
 
 pub fn lstat_handler(args: &mut SyscallArgs) -> i64 {
-    let path = match resolve_user_path(args.rdi) {
+    let path = match resolve_user_path_nofollow(args.rdi) {
         Ok(path) => path,
         Err(error) => return error,
     };
@@ -4311,11 +4951,40 @@ fn stat_virtual_dev(path: &str) -> Option<LinuxStat> {
             // Linux's /dev/null is character device major 1, minor 3.
             st.st_rdev = (1 << 8) | 3;
         }
+        crate::userland::devfs::DeviceNode::Ptmx => {
+            st.st_mode = S_IFCHR | 0o666;
+            st.st_nlink = 1;
+            // Linux's /dev/ptmx is character device major 5, minor 2.
+            st.st_rdev = (5 << 8) | 2;
+        }
+        crate::userland::devfs::DeviceNode::Tty => {
+            st.st_mode = S_IFCHR | 0o666;
+            st.st_nlink = 1;
+            // Linux's /dev/tty is character device major 5, minor 0.
+            st.st_rdev = 5 << 8;
+        }
+        crate::userland::devfs::DeviceNode::PtsDirectory => {
+            st.st_mode = S_IFDIR | PERM_RX_ALL;
+            st.st_nlink = 2;
+        }
+        crate::userland::devfs::DeviceNode::Pts(index) => {
+            st.st_mode = S_IFCHR | 0o620;
+            st.st_nlink = 1;
+            st.st_rdev = pts_rdev(index);
+        }
     }
     st.st_blksize = 4096;
     Some(st)
 }
 
+/// Device number of `/dev/pts/N`: Linux numbers pty slaves from major 136,
+/// 256 minors per major.
+fn pts_rdev(index: u32) -> u64 {
+    let major = 136 + u64::from(index >> 8);
+    let minor = u64::from(index & 0xff);
+    (major << 8) | minor
+}
+
 /// Synthesize a `LinuxStat` for the virtual `/bin` namespace. Returns
 /// `Some(st)` if `path` is `/bin` (a directory) or `/bin/<applet>` (a
 /// regular file shadowing the appropriate multicall binary — `BB.ELF`
@@ -4396,7 +5065,16 @@ pub fn fstat_handler(args: &mut SyscallArgs) -> i64 {
             st.st_blksize = 4096;
             write_stat(out_ptr, &st)
         }
-        Some(FdSlot::LocalStream { .. }) => {
+        Some(FdSlot::PtySlave { handle, .. }) => {
+            let st = LinuxStat {
+                st_mode: S_IFCHR | 0o620,
+                st_rdev: pts_rdev(handle.terminal_id().0),
+                st_blksize: 4096,
+                ..LinuxStat::default()
+            };
+            write_stat(out_ptr, &st)
+        }
+        Some(FdSlot::UnixSocket { .. }) => {
             const S_IFSOCK: u32 = 0o140000;
             let mut st = LinuxStat::default();
             st.st_mode = S_IFSOCK | 0o600;
@@ -4465,7 +5143,11 @@ pub fn fstat_handler(args: &mut SyscallArgs) -> i64 {
             };
             write_stat(out_ptr, &st)
         }
-        Some(FdSlot::EventFd { .. }) | Some(FdSlot::Epoll { .. }) => {
+        Some(FdSlot::EventFd { .. })
+        | Some(FdSlot::TimerFd { .. })
+        | Some(FdSlot::SignalFd { .. })
+        | Some(FdSlot::Inotify { .. })
+        | Some(FdSlot::Epoll { .. }) => {
             let mut st = LinuxStat::default();
             st.st_mode = S_IFREG | 0o600;
             st.st_nlink = 1;
@@ -4490,7 +5172,11 @@ pub fn newfstatat_handler(args: &mut SyscallArgs) -> i64 {
     if flags & !AT_SYMLINK_NOFOLLOW != 0 {
         return EINVAL;
     }
-    let path = match resolve_user_path(path_ptr) {
+    let path = match if flags & AT_SYMLINK_NOFOLLOW != 0 {
+        resolve_user_path_nofollow(path_ptr)
+    } else {
+        resolve_user_path(path_ptr)
+    } {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -4555,26 +5241,33 @@ fn access_common(path_ptr: u64, mode: u32) -> i64 {
         };
     }
     if let Some(node) = crate::userland::devfs::classify(&path) {
-        // /dev/null is the one writable device node.
-        if matches!(node, crate::userland::devfs::DeviceNode::Null) {
+        // /dev/null and the terminals are the writable device nodes.
+        if matches!(
+            node,
+            crate::userland::devfs::DeviceNode::Null
+                | crate::userland::devfs::DeviceNode::Ptmx
+                | crate::userland::devfs::DeviceNode::Tty
+                | crate::userland::devfs::DeviceNode::Pts(_)
+        ) {
             return 0;
         }
         return if mode & _W_OK != 0 { EACCES } else { 0 };
     }
-    if crate::fs::exists(&path) {
-        0
-    } else {
-        ENOENT
+    if !crate::fs::exists(&path) {
+        return ENOENT;
     }
+    // access() answers for the real ids, not the effective ones.
+    let real = crate::userland::credentials::current().as_real();
+    crate::userland::credentials::check_path_as(&real, &path, mode & 7).map_or_else(|e| e, |_| 0)
 }
 
-/// `chmod(path, mode) -> int`. Validated success no-op: FAT and tmpfs
-/// carry no permission bits and `execve` performs no +x check, so the
-/// only observable POSIX behavior is the existence check. TinyCC (and
-/// other toolchains) chmod their output executable after writing it.
+/// `chmod(path, mode) -> int`. Only the owner (or root) may change the
+/// mode. Filesystems without permission bits (FAT) accept the call as a
+/// no-op, since TinyCC and other toolchains chmod their output executable
+/// after writing it.
 pub fn chmod_handler(args: &mut SyscallArgs) -> i64 {
     let path_ptr = args.rdi;
-    let _mode = args.rsi as u32;
+    let mode = args.rsi as u32;
     let path = match resolve_user_path(path_ptr) {
         Ok(p) => p,
         Err(e) => return e,
@@ -4584,24 +5277,124 @@ pub fn chmod_handler(args: &mut SyscallArgs) -> i64 {
     {
         return EPERM;
     }
-    if crate::fs::exists(&path) {
-        0
-    } else {
-        ENOENT
-    }
+    chmod_path(&path, mode)
 }
 
-/// `fchmod(fd, mode) -> int`. Same no-op semantics as `chmod`, keyed
-/// on descriptor validity instead of a path.
+/// `fchmod(fd, mode) -> int`. Same semantics as `chmod` for regular
+/// files; other descriptors carry no mode and succeed unchanged.
 pub fn fchmod_handler(args: &mut SyscallArgs) -> i64 {
     let fd = args.rdi as i32;
-    let _mode = args.rsi as u32;
+    let mode = args.rsi as u32;
     match with_fd_slot(fd) {
+        Some(FdSlot::File { handle, .. }) => chmod_path(&handle.path(), mode),
         Some(_) => 0,
         None => EBADF,
     }
 }
 
+fn chmod_path(path: &str, mode: u32) -> i64 {
+    let meta = match crate::fs::vfs::vfs_unix_metadata(path) {
+        Ok(meta) => meta,
+        Err(ref e) => return map_filesystem_err(e),
+    };
+    let mode = match crate::userland::credentials::current().chmod_mode(&meta, mode) {
+        Ok(mode) => mode,
+        Err(e) => return e,
+    };
+    match crate::fs::vfs::vfs_set_mode(path, mode) {
+        Ok(()) | Err(crate::fs::filesystem::FilesystemError::UnsupportedOperation) => 0,
+        Err(ref e) => map_filesystem_err(e),
+    }
+}
+
+/// `chown(path, uid, gid) -> int`. An id of -1 leaves that id unchanged.
+pub fn chown_handler(args: &mut SyscallArgs) -> i64 {
+    chown_common(args.rdi, args.rsi as u32, args.rdx as u32, true)
+}
+
+/// `lchown(path, uid, gid) -> int`. Symlinks carry no owner of their own
+/// here, so changing one is a validated no-op.
+pub fn lchown_handler(args: &mut SyscallArgs) -> i64 {
+    chown_common(args.rdi, args.rsi as u32, args.rdx as u32, false)
+}
+
+/// `fchownat(dirfd, path, uid, gid, flags) -> int`. Only `AT_FDCWD` is
+/// supported for `dirfd`.
+pub fn fchownat_handler(args: &mut SyscallArgs) -> i64 {
+    const AT_SYMLINK_NOFOLLOW: u64 = 0x100;
+    if args.rdi as i32 != AT_FDCWD {
+        return ENOSYS;
+    }
+    if args.r8 & !AT_SYMLINK_NOFOLLOW != 0 {
+        return EINVAL;
+    }
+    chown_common(
+        args.rsi,
+        args.rdx as u32,
+        args.r10 as u32,
+        args.r8 & AT_SYMLINK_NOFOLLOW == 0,
+    )
+}
+
+/// `fchown(fd, uid, gid) -> int`.
+pub fn fchown_handler(args: &mut SyscallArgs) -> i64 {
+    let fd = args.rdi as i32;
+    match with_fd_slot(fd) {
+        Some(FdSlot::File { handle, .. }) => {
+            chown_path(&handle.path(), args.rsi as u32, args.rdx as u32)
+        }
+        Some(_) => 0,
+        None => EBADF,
+    }
+}
+
+fn chown_common(path_ptr: u64, uid: u32, gid: u32, follow: bool) -> i64 {
+    let path = match if follow {
+        resolve_user_path(path_ptr)
+    } else {
+        resolve_user_path_nofollow(path_ptr)
+    } {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+    if let Some(e) = bin_namespace_mutation_check(&path)
+        .or_else(|| proc_namespace_mutation_check(&path))
+        .or_else(|| dev_namespace_mutation_check(&path))
+    {
+        return e;
+    }
+    if !follow {
+        match crate::fs::vfs::vfs_symlink_metadata(&path) {
+            Ok(meta) if meta.mode & 0o170000 == 0o120000 => return 0,
+            Ok(_) => {}
+            Err(ref e) => return map_filesystem_err(e),
+        }
+    }
+    chown_path(&path, uid, gid)
+}
+
+fn chown_path(path: &str, uid: u32, gid: u32) -> i64 {
+    let meta = match crate::fs::vfs::vfs_unix_metadata(path) {
+        Ok(meta) => meta,
+        Err(ref e) => return map_filesystem_err(e),
+    };
+    if !crate::userland::credentials::current().may_chown(&meta, uid, gid) {
+        return EPERM;
+    }
+    let keep = crate::userland::credentials::KEEP;
+    let uid = (uid != keep).then_some(uid);
+    let gid = (gid != keep).then_some(gid);
+    match crate::fs::vfs::vfs_set_owner(path, uid, gid) {
+        Ok(()) => {}
+        Err(crate::fs::filesystem::FilesystemError::UnsupportedOperation) => return 0,
+        Err(ref e) => return map_filesystem_err(e),
+    }
+    if let Some(mode) = crate::userland::credentials::chown_mode(&meta) {
+        let _ = crate::fs::vfs::vfs_set_mode(path, mode);
+    }
+    0
+}
+
 // ---------- cwd ----------
 
 /// `getcwd(buf, size) -> int`. Returns the byte length on success
@@ -4611,7 +5404,12 @@ pub fn getcwd_handler(args: &mut SyscallArgs) -> i64 {
     let buf = args.rdi;
     let size = args.rsi;
 
-    let cwd = with_cwd(|c| alloc::string::String::from(c));
+    // Like Linux, a working directory outside the caller's root is
+    // reported with an "(unreachable)" prefix.
+    let (root, cwd) =
+        crate::userland::lifecycle::with_active_user(|au| (au.root.clone(), au.cwd.clone()));
+    let cwd = crate::userland::path::view_path(&root, &cwd)
+        .unwrap_or_else(|| alloc::format!("(unreachable){cwd}"));
     let needed = cwd.len() as u64 + 1; // NUL
     if size < needed {
         return ERANGE;
@@ -4678,14 +5476,14 @@ fn chdir_to(path: alloc::string::String) -> i64 {
     }
     if crate::userland::devfs::is_dev_path(&path) {
         return match crate::userland::devfs::classify(&path) {
-            Some(crate::userland::devfs::DeviceNode::Directory) => {
+            Some(
+                crate::userland::devfs::DeviceNode::Directory
+                | crate::userland::devfs::DeviceNode::PtsDirectory,
+            ) => {
                 set_cwd(path);
                 0
             }
-            Some(
-                crate::userland::devfs::DeviceNode::Urandom
-                | crate::userland::devfs::DeviceNode::Null,
-            ) => ENOTDIR,
+            Some(_) => ENOTDIR,
             None => ENOENT,
         };
     }
@@ -4705,13 +5503,13 @@ fn chdir_to(path: alloc::string::String) -> i64 {
 
 #[repr(C)]
 #[derive(Default, Clone, Copy)]
-struct LinuxTimespec {
-    tv_sec: i64,
-    tv_nsec: i64,
+pub(crate) struct LinuxTimespec {
+    pub(crate) tv_sec: i64,
+    pub(crate) tv_nsec: i64,
 }
 
 #[repr(C)]
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 struct LinuxTimeval {
     tv_sec: i64,
     tv_usec: i64,
@@ -4750,9 +5548,205 @@ pub fn gettimeofday_handler(args: &mut SyscallArgs) -> i64 {
     crate::userland::usercopy::write_unaligned(tv_ptr, &tv).map_or_else(|e| e, |_| 0)
 }
 
-/// `umask(mask) -> previous_mask`. Permission enforcement remains minimal,
-/// but the state is process-local, inherited across fork, and retained across
-/// exec so BFD and future toolchains observe normal POSIX behavior.
+/// `clock_settime(clk, *ts) -> int`. Only `CLOCK_REALTIME` is settable;
+/// the new time is also written back to the RTC.
+pub fn clock_settime_handler(args: &mut SyscallArgs) -> i64 {
+    if args.rdi as i32 != CLOCK_REALTIME {
+        return EINVAL;
+    }
+    let ts: LinuxTimespec = match crate::userland::usercopy::read_unaligned(args.rsi) {
+        Ok(ts) => ts,
+        Err(error) => return error,
+    };
+    if !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return EINVAL;
+    }
+    let Some(ns) = realtime_from_parts(ts.tv_sec, ts.tv_nsec) else {
+        return EINVAL;
+    };
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    set_realtime(ns)
+}
+
+/// `settimeofday(*tv, *tz) -> int`. A null `tv` sets nothing; the time
+/// zone argument is ignored as in `gettimeofday`.
+pub fn settimeofday_handler(args: &mut SyscallArgs) -> i64 {
+    if args.rdi == 0 {
+        return 0;
+    }
+    let tv: LinuxTimeval = match crate::userland::usercopy::read_unaligned(args.rdi) {
+        Ok(tv) => tv,
+        Err(error) => return error,
+    };
+    if !(0..1_000_000).contains(&tv.tv_usec) {
+        return EINVAL;
+    }
+    let Some(ns) = realtime_from_parts(tv.tv_sec, tv.tv_usec * 1_000) else {
+        return EINVAL;
+    };
+    if !crate::userland::credentials::current().privileged() {
+        return EPERM;
+    }
+    set_realtime(ns)
+}
+
+/// Unix nanoseconds for a validated `seconds` and sub-second `nanoseconds`,
+/// or `None` before the epoch or past what realtime can hold, where Linux
+/// refuses times past `KTIME_SEC_MAX`.
+fn realtime_from_parts(seconds: i64, nanoseconds: i64) -> Option<u64> {
+    u64::try_from(seconds)
+        .ok()?
+        .checked_mul(1_000_000_000)?
+        .checked_add(nanoseconds as u64)
+}
+
+fn set_realtime(ns: u64) -> i64 {
+    crate::time::set_realtime_ns(ns);
+    crate::time::sync_rtc();
+    0
+}
+
+const ADJ_OFFSET: u32 = 0x0001;
+const ADJ_SETOFFSET: u32 = 0x0100;
+const ADJ_MICRO: u32 = 0x1000;
+const ADJ_NANO: u32 = 0x2000;
+const ADJ_OFFSET_SINGLESHOT: u32 = 0x8001;
+const ADJ_OFFSET_SS_READ: u32 = 0xa001;
+const STA_UNSYNC: i32 = 0x0040;
+const STA_NANO: i32 = 0x2000;
+const TIME_OK: i64 = 0;
+const TIME_ERROR: i64 = 5;
+/// `ADJ_OFFSET` bound, Linux's `MAXPHASE`.
+const MAX_PHASE_NS: i64 = 500_000_000;
+/// `maxerror` of an unsynchronized clock, Linux's `NTP_PHASE_LIMIT`.
+const UNSYNC_MAX_ERROR_US: i64 = 16_000_000;
+
+/// `struct timex` (x86-64), 208 bytes.
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct LinuxTimex {
+    modes: u32,
+    _pad0: u32,
+    offset: i64,
+    freq: i64,
+    maxerror: i64,
+    esterror: i64,
+    status: i32,
+    _pad1: u32,
+    constant: i64,
+    precision: i64,
+    tolerance: i64,
+    time: LinuxTimeval,
+    tick: i64,
+    ppsfreq: i64,
+    jitter: i64,
+    shift: i32,
+    _pad2: u32,
+    stabil: i64,
+    jitcnt: i64,
+    calcnt: i64,
+    errcnt: i64,
+    stbcnt: i64,
+    tai: i32,
+    _reserved: [u32; 11],
+}
+
+const _: [(); 208] = [(); core::mem::size_of::<LinuxTimex>()];
+
+/// `adjtimex(*tx) -> clock state`.
+pub fn adjtimex_handler(args: &mut SyscallArgs) -> i64 {
+    adjust_clock(args.rdi)
+}
+
+/// `clock_adjtime(clk, *tx) -> clock state`, for `CLOCK_REALTIME` only.
+pub fn clock_adjtime_handler(args: &mut SyscallArgs) -> i64 {
+    if args.rdi as i32 != CLOCK_REALTIME {
+        return EINVAL;
+    }
+    adjust_clock(args.rsi)
+}
+
+/// The kernel has no PLL: `ADJ_OFFSET` slews like `adjtime`, clamped to
+/// `MAXPHASE`, and `ADJ_SETOFFSET` steps. Frequency, error, status and
+/// time-constant updates are refused.
+fn adjust_clock(tx_ptr: u64) -> i64 {
+    let mut tx: LinuxTimex = match crate::userland::usercopy::read_unaligned(tx_ptr) {
+        Ok(tx) => tx,
+        Err(error) => return error,
+    };
+    let modes = tx.modes;
+    let singleshot = modes == ADJ_OFFSET_SINGLESHOT || modes == ADJ_OFFSET_SS_READ;
+    let nano = !singleshot && modes & ADJ_NANO != 0;
+    let unit_ns = if nano { 1 } else { 1_000 };
+    let supported = singleshot || modes & !(ADJ_OFFSET | ADJ_SETOFFSET | ADJ_MICRO | ADJ_NANO) == 0;
+    if !supported || modes & (ADJ_MICRO | ADJ_NANO) == ADJ_MICRO | ADJ_NANO {
+        return EINVAL;
+    }
+    if modes != 0
+        && modes != ADJ_OFFSET_SS_READ
+        && !crate::userland::credentials::current().privileged()
+    {
+        return EPERM;
+    }
+    if modes & ADJ_SETOFFSET != 0 {
+        let limit = if nano { 1_000_000_000 } else { 1_000_000 };
+        if !(0..limit).contains(&tx.time.tv_usec) {
+            return EINVAL;
+        }
+        let Some(delta) = tx
+            .time
+            .tv_sec
+            .checked_mul(1_000_000_000)
+            .and_then(|ns| ns.checked_add(tx.time.tv_usec * unit_ns))
+        else {
+            return EINVAL;
+        };
+        crate::time::step_realtime_ns(delta);
+        crate::time::sync_rtc();
+    }
+    let remaining = match modes {
+        ADJ_OFFSET_SINGLESHOT => crate::time::set_slew_ns(tx.offset.saturating_mul(1_000)),
+        ADJ_OFFSET_SS_READ => crate::time::slew_remaining_ns(),
+        _ if modes & ADJ_OFFSET != 0 => {
+            let offset = tx
+                .offset
+                .saturating_mul(unit_ns)
+                .clamp(-MAX_PHASE_NS, MAX_PHASE_NS);
+            crate::time::set_slew_ns(offset);
+            offset
+        }
+        _ => crate::time::slew_remaining_ns(),
+    };
+
+    let synchronized = crate::time::synchronized();
+    let ns = crate::time::realtime_ns();
+    tx.offset = remaining / if singleshot { 1_000 } else { unit_ns };
+    tx.freq = 0;
+    tx.maxerror = if synchronized { 0 } else { UNSYNC_MAX_ERROR_US };
+    tx.esterror = 0;
+    tx.status = if synchronized { 0 } else { STA_UNSYNC } | if nano { STA_NANO } else { 0 };
+    tx.precision = 1;
+    tx.tolerance = 500 << 16;
+    tx.time = LinuxTimeval {
+        tv_sec: (ns / 1_000_000_000) as i64,
+        tv_usec: ((ns % 1_000_000_000) / unit_ns as u64) as i64,
+    };
+    tx.tick = (crate::time::PIT_NANOSECONDS_PER_TICK / 1_000) as i64;
+    if let Err(error) = crate::userland::usercopy::write_unaligned(tx_ptr, &tx) {
+        return error;
+    }
+    if synchronized {
+        TIME_OK
+    } else {
+        TIME_ERROR
+    }
+}
+
+/// `umask(mask) -> previous_mask`. The mask is process-local, inherited
+/// across fork, retained across exec, and applied to the mode of every
+/// file and directory the process creates.
 pub fn umask_handler(args: &mut SyscallArgs) -> i64 {
     crate::userland::lifecycle::with_current_group(|process| {
         let old = process.umask;
@@ -5069,10 +6063,14 @@ fn getdents64_virtual_dev(fd: i32, dirp: u64, cap: usize) -> Option<i64> {
         Some(FdSlot::VirtualDevDir { cursor, .. }) => Some(*cursor),
         _ => None,
     })?;
-    const RECORDS: [(&[u8], u8); 4] = [
+    const RECORDS: [(&[u8], u8); 8] = [
         (b".", DT_DIR),
         (b"..", DT_DIR),
         (b"null", DT_CHR),
+        (b"ptmx", DT_CHR),
+        (b"pts", DT_DIR),
+        (b"shm", DT_DIR),
+        (b"tty", DT_CHR),
         (b"urandom", DT_CHR),
     ];
     if start >= RECORDS.len() {
@@ -5308,9 +6306,18 @@ pub(crate) fn fd_slot_readiness(slot: &FdSlot) -> Result<FdReady, i64> {
         }),
         FdSlot::PtyMaster { master, .. } => Ok(FdReady {
             readable: master.output_ready() != 0,
-            writable: true,
+            writable: master.input_room() != 0,
             ..FdReady::default()
         }),
+        FdSlot::PtySlave { handle, .. } => {
+            let (readable, writable, error, hangup) = handle.readiness();
+            Ok(FdReady {
+                readable,
+                writable,
+                error,
+                hangup,
+            })
+        }
         FdSlot::Stdout | FdSlot::Stderr => Ok(FdReady {
             writable: true,
             ..FdReady::default()
@@ -5375,11 +6382,23 @@ pub(crate) fn fd_slot_readiness(slot: &FdSlot) -> Result<FdReady, i64> {
                 ..FdReady::default()
             })
         }
+        FdSlot::TimerFd { handle, .. } => Ok(FdReady {
+            readable: handle.readable(),
+            ..FdReady::default()
+        }),
+        FdSlot::SignalFd { handle, .. } => Ok(FdReady {
+            readable: handle.readable(),
+            ..FdReady::default()
+        }),
+        FdSlot::Inotify { handle, .. } => Ok(FdReady {
+            readable: handle.readable(),
+            ..FdReady::default()
+        }),
         FdSlot::Epoll { handle, .. } => Ok(FdReady {
             readable: handle.is_ready(),
             ..FdReady::default()
         }),
-        FdSlot::LocalStream { handle, .. } => {
+        FdSlot::UnixSocket { handle, .. } => {
             let (readable, writable, error, hangup) = handle.readiness();
             Ok(FdReady {
                 readable,
@@ -5726,14 +6745,13 @@ pub fn sched_yield_handler(args: &mut SyscallArgs) -> i64 {
     if crate::arch::x86_64::percpu::current_user_pid().is_none() {
         return 0;
     }
+    crate::userland::ptrace::syscall_exit_stop(args, 0);
     unsafe { crate::userland::switch::yield_current_ring3(args) }
 }
 
 /// `sched_getaffinity(pid, cpusetsize, mask)` for the system-wide online CPU
-/// set. Thread groups are currently pinned to one home CPU for execution, but
-/// Linux reports the CPUs a task may be scheduled on; all online CPUs are
-/// eligible at process creation and Git uses this result to size its I/O
-/// preload pool.
+/// set. Every online CPU is eligible to run any task, and Git uses this
+/// result to size its I/O preload pool.
 pub fn sched_getaffinity_handler(args: &mut SyscallArgs) -> i64 {
     const KERNEL_MASK_BYTES: usize = core::mem::size_of::<u64>();
     const MAX_CPUSET_BYTES: usize = 4096;
@@ -5765,6 +6783,20 @@ pub fn sched_getaffinity_handler(args: &mut SyscallArgs) -> i64 {
     }
 }
 
+/// `getcpu(cpu, node, tcache)`: the CPU the caller is running on and node 0.
+/// The vDSO answers this without a system call where RDTSCP exists.
+pub fn getcpu_handler(args: &mut SyscallArgs) -> i64 {
+    let cpu = crate::arch::x86_64::percpu::cpu_id() as u32;
+    for (pointer, value) in [(args.rdi, cpu), (args.rsi, 0u32)] {
+        if pointer != 0 {
+            if let Err(error) = crate::userland::usercopy::write_unaligned(pointer, &value) {
+                return error;
+            }
+        }
+    }
+    0
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 struct LinuxStackT {
@@ -5871,9 +6903,6 @@ pub fn membarrier_handler(args: &mut SyscallArgs) -> i64 {
             (MEMBARRIER_CMD_PRIVATE_EXPEDITED | MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED) as i64
         }
         MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED => {
-            if !crate::userland::lifecycle::current_group_has_single_cpu_execution() {
-                return ENOSYS;
-            }
             crate::userland::lifecycle::with_current_group(|process| {
                 process.membarrier_private_registered = true;
             });
@@ -5881,16 +6910,24 @@ pub fn membarrier_handler(args: &mut SyscallArgs) -> i64 {
             0
         }
         MEMBARRIER_CMD_PRIVATE_EXPEDITED => {
-            if !crate::userland::lifecycle::current_group_has_single_cpu_execution() {
-                return ENOSYS;
-            }
-            let registered = crate::userland::lifecycle::with_current_group(|process| {
-                process.membarrier_private_registered
+            let (registered, l4) = crate::userland::lifecycle::with_current_group(|process| {
+                (
+                    process.membarrier_private_registered,
+                    process
+                        .address_space
+                        .as_ref()
+                        .map(|space| space.l4_frame().start_address().as_u64()),
+                )
             });
             if !registered {
                 return EPERM;
             }
             core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+            // Every other CPU running a sibling takes and acknowledges an
+            // IPI, which is a full barrier on that CPU.
+            if let Some(l4) = l4 {
+                crate::arch::x86_64::shootdown::flush_address_space(l4);
+            }
             0
         }
         _ => EINVAL,
@@ -5937,10 +6974,14 @@ pub fn madvise_handler(args: &mut SyscallArgs) -> i64 {
             let mut cursor = address;
             while cursor < end {
                 let vma = space.vmas().find(cursor)?;
-                if !matches!(
-                    vma.backing,
-                    VmaBacking::Anonymous | VmaBacking::FilePrivate { .. }
-                ) {
+                // Dropping a shared leaf keeps the object's page, so the
+                // next touch sees the same bytes, as Linux DONTNEED does.
+                let allowed = match vma.backing {
+                    VmaBacking::Anonymous | VmaBacking::FilePrivate { .. } => true,
+                    VmaBacking::Shared { .. } => advice == MADV_DONTNEED,
+                    _ => false,
+                };
+                if !allowed {
                     return None;
                 }
                 cursor = vma.end.min(end);
@@ -6032,6 +7073,18 @@ pub fn mremap_handler(args: &mut SyscallArgs) -> i64 {
         Some(end) => end,
         None => return ENOMEM,
     };
+    let growth = new_size - old_size;
+    let data = if allocation.private && allocation.prot.contains(crate::userland::vm::VmProt::WRITE)
+    {
+        growth
+    } else {
+        0
+    };
+    if let Err(e) = crate::userland::lifecycle::with_current_group(|process| {
+        crate::userland::rlimit::check_vm_growth(process, growth, data)
+    }) {
+        return e;
+    }
     let grew_in_place = crate::userland::lifecycle::with_current_group(|process| {
         let Some(space) = process.address_space.as_mut() else {
             return false;
@@ -6176,7 +7229,10 @@ fn readlink_common(path_ptr: u64, buf_ptr: u64, bufsiz: u64) -> i64 {
         Ok(s) => s,
         Err(e) => return e,
     };
-    let path = with_cwd(|cwd| normalize_path(cwd, &raw_path));
+    let path = match resolve_current_path(&raw_path, false) {
+        Ok(path) => path,
+        Err(e) => return e,
+    };
     if path.len() > READLINK_MAX_PATH {
         return ERANGE;
     }
@@ -6218,8 +7274,10 @@ fn resolve_proc_self_fd(fd: i32) -> Option<String> {
     Some(match slot {
         FdSlot::Stdin | FdSlot::Stdout | FdSlot::Stderr => String::from("/dev/tty"),
         FdSlot::PtyMaster { .. } => String::from("/dev/ptmx"),
-        FdSlot::File { handle, .. } => handle.path(),
-        FdSlot::Directory { handle, .. } => handle.path(),
+        FdSlot::PtySlave { handle, .. } => alloc::format!("/dev/pts/{}", handle.terminal_id().0),
+        FdSlot::File { handle, .. } => crate::userland::memfd::link_name(&handle)
+            .unwrap_or_else(|| current_view_path(handle.path())),
+        FdSlot::Directory { handle, .. } => current_view_path(handle.path()),
         FdSlot::PipeRead(_, _) | FdSlot::PipeWrite(_, _) => String::from("pipe:[0]"),
         FdSlot::VirtualBinDir { .. } => String::from("/bin"),
         FdSlot::VirtualDevDir { .. } => String::from("/dev"),
@@ -6228,53 +7286,15 @@ fn resolve_proc_self_fd(fd: i32) -> Option<String> {
         FdSlot::Socket { handle, .. } => alloc::format!("socket:[{}]", handle.id()),
         FdSlot::GuiEvents { .. } => String::from("anon_inode:[agenticos-gui]"),
         FdSlot::EventFd { .. } => String::from("anon_inode:[eventfd]"),
+        FdSlot::TimerFd { .. } => String::from("anon_inode:[timerfd]"),
+        FdSlot::SignalFd { .. } => String::from("anon_inode:[signalfd]"),
+        FdSlot::Inotify { .. } => String::from("anon_inode:inotify"),
         FdSlot::Epoll { .. } => String::from("anon_inode:[eventpoll]"),
-        FdSlot::LocalStream { handle, .. } => alloc::format!("socket:[{}]", handle.id()),
+        FdSlot::UnixSocket { handle, .. } => alloc::format!("socket:[{}]", handle.id()),
         FdSlot::VirtualFile { path, .. } | FdSlot::VirtualDir { path, .. } => String::clone(&path),
     })
 }
 
-/// Linux `struct rlimit` (16 bytes on 64-bit).
-#[repr(C)]
-#[derive(Clone, Copy)]
-struct LinuxRlimit {
-    rlim_cur: u64,
-    rlim_max: u64,
-}
-
-const RLIM_INFINITY: u64 = u64::MAX;
-
-/// `getrlimit(resource: i32, *rlim: *mut rlimit) -> int`
-///
-/// Stub: every resource reports `RLIM_INFINITY` for both `cur` and `max`.
-/// Sufficient for zsh's startup queries on `RLIMIT_STACK`, `RLIMIT_NOFILE`,
-/// `RLIMIT_DATA`, `RLIMIT_AS`. Real per-process limits are out of scope.
-pub fn getrlimit_handler(args: &mut SyscallArgs) -> i64 {
-    let out_ptr = args.rsi;
-    write_rlim_infinity(out_ptr)
-}
-
-/// `prlimit64(pid, resource, *new_limit, *old_limit) -> int`
-///
-/// Stub: ignore `pid` (we have one process from the user's perspective)
-/// and `new_limit` (no enforcement); if `old_limit` is non-null, write
-/// `RLIM_INFINITY` into it. Returns 0.
-pub fn prlimit64_handler(args: &mut SyscallArgs) -> i64 {
-    let old_ptr = args.r10;
-    if old_ptr == 0 {
-        return 0;
-    }
-    write_rlim_infinity(old_ptr)
-}
-
-fn write_rlim_infinity(out_ptr: u64) -> i64 {
-    let r = LinuxRlimit {
-        rlim_cur: RLIM_INFINITY,
-        rlim_max: RLIM_INFINITY,
-    };
-    crate::userland::usercopy::write_unaligned(out_ptr, &r).map_or_else(|e| e, |_| 0)
-}
-
 /// Linux `struct rusage` layout (x86-64): two `timeval` pairs followed by
 /// 14 `long` counters. 144 bytes total. zsh reads it at startup for the
 /// `times` builtin / shell timing init.
@@ -6393,7 +7413,7 @@ fn timeval_to_ticks(seconds: i64, microseconds: i64) -> Result<u64, i64> {
     Ok(total.saturating_add(PIT_MICROSECONDS_PER_TICK - 1) / PIT_MICROSECONDS_PER_TICK)
 }
 
-fn ticks_to_timeval(ticks: u64) -> (i64, i64) {
+pub(crate) fn ticks_to_timeval(ticks: u64) -> (i64, i64) {
     let total = ticks.saturating_mul(PIT_MICROSECONDS_PER_TICK);
     ((total / 1_000_000) as i64, (total % 1_000_000) as i64)
 }
@@ -6470,18 +7490,20 @@ pub fn setitimer_handler(args: &mut SyscallArgs) -> i64 {
 /// `nanosleep(*req: *const timespec, *rem: *mut timespec) -> int`
 ///
 /// Blocks the calling ring-3 process until the requested duration elapses
-/// against the monotonic 100 Hz PIT, then returns 0. The duration is rounded
-/// up to whole ticks so a sub-tick request still yields the CPU rather than
-/// busy-spinning — self-driven ring-3 animation loops (`PAINTING.ELF`,
-/// `TASKMGR.ELF`) and zsh's `sleep`/`usleep` builtins all depend on this.
+/// against the monotonic TSC clock, then returns 0. The deadline fires on
+/// the BSP's clockevent timer, so a 1 ms sleep takes about 1 ms rather than
+/// the next 10 ms PIT tick. Any positive request still blocks and yields
+/// the CPU rather than busy-spinning — self-driven ring-3 animation loops
+/// (`PAINTING.ELF`, `TASKMGR.ELF`) and zsh's `sleep`/`usleep` builtins all
+/// depend on this.
 ///
 /// Restart mechanics: blocking parks the process as
-/// `Ring3BlockReason::Sleeping { deadline_tick }` with RIP rewound so the
+/// `Ring3BlockReason::Sleeping { deadline_ns }` with RIP rewound so the
 /// SYSCALL re-fires on wake. The absolute deadline is restart-stable via
 /// `Process.sleep_deadline` (see [`nanosleep_deadline`]), so a
-/// woken-and-re-blocked sleeper cannot extend its own timeout;
-/// `process_expired_sleeps` (PIT ISR + housekeeping backstops) readies the
-/// process at the deadline.
+/// woken-and-re-blocked sleeper cannot extend its own timeout; the timer
+/// service (`process_expired_sleeps` in tests) readies the process at the
+/// deadline.
 ///
 /// Signals: `wake_ring3_for_signal` / ITIMER expiry unblock the sleeper,
 /// clear `sleep_deadline`, and set `pending_syscall_interrupt` — the
@@ -6493,9 +7515,7 @@ pub fn setitimer_handler(args: &mut SyscallArgs) -> i64 {
 /// Synthetic dispatch (tests, sentinel PID 0) cannot yield — a valid
 /// request from that context returns 0 immediately.
 pub fn nanosleep_handler(args: &mut SyscallArgs) -> i64 {
-    /// PIT period: 100 Hz ⇒ 10 ms ⇒ 10,000,000 ns per tick.
-    const NS_PER_TICK: u64 = 10_000_000;
-    const TICKS_PER_SEC: u64 = 100;
+    const NS_PER_SEC: u64 = 1_000_000_000;
 
     let req_ptr = args.rdi;
     let rem_ptr = args.rsi;
@@ -6509,11 +7529,9 @@ pub fn nanosleep_handler(args: &mut SyscallArgs) -> i64 {
     if req.tv_sec < 0 || req.tv_nsec < 0 || req.tv_nsec >= 1_000_000_000 {
         return EINVAL;
     }
-    // Round the sub-second remainder up so any positive request sleeps
-    // at least one tick.
-    let requested_ticks = (req.tv_sec as u64)
-        .saturating_mul(TICKS_PER_SEC)
-        .saturating_add((req.tv_nsec as u64).div_ceil(NS_PER_TICK));
+    let requested_ns = (req.tv_sec as u64)
+        .saturating_mul(NS_PER_SEC)
+        .saturating_add(req.tv_nsec as u64);
 
     let write_zero_rem = || -> i64 {
         if rem_ptr == 0 {
@@ -6531,12 +7549,12 @@ pub fn nanosleep_handler(args: &mut SyscallArgs) -> i64 {
         return write_zero_rem();
     }
 
-    match crate::userland::lifecycle::nanosleep_deadline(requested_ticks) {
+    match crate::userland::lifecycle::nanosleep_deadline(requested_ns) {
         Some(deadline) => unsafe {
             crate::userland::switch::block_current_ring3_and_yield(
                 args,
                 crate::userland::lifecycle::Ring3BlockReason::Sleeping {
-                    deadline_tick: deadline,
+                    deadline_ns: deadline,
                 },
             )
         },
@@ -0,0 +1,456 @@
+//! Linux timerfd open-file description.
+//!
+//! A timerfd's deadline lives on the shared timer heap like `ITIMER_REAL`,
+//! keyed by the timerfd's id rather than a process, since dup and fork
+//! share it. Expiry counts overruns, bumps the epoll generation and
+//! publishes a readiness change; `read` returns and clears the count.
+//! Readers also catch up on a late timer service themselves.
+//!
+//! Deadlines are kept on the monotonic clock. `CLOCK_REALTIME`,
+//! `CLOCK_MONOTONIC` and `CLOCK_BOOTTIME` advance together apart from clock
+//! steps and slews, so an absolute deadline converts to a monotonic one
+//! once, at `timerfd_settime`, and a later step does not move it.
+//! `TFD_TIMER_CANCEL_ON_SET` is not supported.
+
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use spin::Mutex;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::lib::arc::{Arc, Weak};
+use crate::process::timer::{TimerAction, TimerKey, TimerKind};
+use crate::userland::abi::{EAGAIN, EBADF, EFAULT, EINVAL, EMFILE, ENOMEM};
+use crate::userland::fdtable::FdSlot;
+use crate::userland::syscalls::{LinuxTimespec, CLOCK_MONOTONIC, CLOCK_REALTIME};
+
+pub const TFD_TIMER_ABSTIME: u32 = 0x1;
+pub const TFD_NONBLOCK: u32 = 0x800;
+pub const TFD_CLOEXEC: u32 = 0x80000;
+const CLOCK_BOOTTIME: i32 = 7;
+const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Linux `struct itimerspec`.
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct Itimerspec {
+    it_interval: LinuxTimespec,
+    it_value: LinuxTimespec,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct TimerState {
+    deadline_ns: Option<u64>,
+    interval_ns: u64,
+    expirations: u64,
+}
+
+impl TimerState {
+    /// Count the expirations due by `now` and move the deadline past it.
+    /// Returns whether any were due.
+    fn advance(&mut self, now: u64) -> bool {
+        let Some(deadline) = self.deadline_ns else {
+            return false;
+        };
+        if now < deadline {
+            return false;
+        }
+        let periods = if self.interval_ns == 0 {
+            self.deadline_ns = None;
+            1
+        } else {
+            let periods = (now - deadline) / self.interval_ns + 1;
+            self.deadline_ns =
+                Some(deadline.saturating_add(periods.saturating_mul(self.interval_ns)));
+            periods
+        };
+        self.expirations = self.expirations.saturating_add(periods);
+        true
+    }
+
+    /// `(interval, remaining)` in nanoseconds, as `timerfd_gettime` reports
+    /// them.
+    fn current(&self, now: u64) -> (u64, u64) {
+        let remaining = self
+            .deadline_ns
+            .map_or(0, |deadline| deadline.saturating_sub(now));
+        (self.interval_ns, remaining)
+    }
+}
+
+pub struct TimerFd {
+    id: u32,
+    clock: i32,
+    state: Mutex<TimerState>,
+    nonblocking: AtomicBool,
+    generation: AtomicU64,
+}
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Live timerfds by id, for the timer heap's expiry callback.
+static TIMERFDS: Mutex<BTreeMap<u32, Weak<TimerFd>>> = Mutex::new(BTreeMap::new());
+
+fn monotonic_now() -> u64 {
+    crate::time::monotonic_ns()
+}
+
+impl TimerFd {
+    fn new(clock: i32, flags: u32) -> Arc<Self> {
+        let timer = Arc::new(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            clock,
+            state: Mutex::new(TimerState::default()),
+            nonblocking: AtomicBool::new(flags & TFD_NONBLOCK != 0),
+            generation: AtomicU64::new(0),
+        });
+        TIMERFDS.lock().insert(timer.id, Arc::downgrade(&timer));
+        timer
+    }
+
+    pub fn nonblocking(&self) -> bool {
+        self.nonblocking.load(Ordering::Acquire)
+    }
+
+    pub fn set_nonblocking(&self, value: bool) {
+        self.nonblocking.store(value, Ordering::Release);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Whether a read would return an expiration count now.
+    pub fn readable(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.advance(monotonic_now()) {
+            self.generation.fetch_add(1, Ordering::AcqRel);
+        }
+        state.expirations != 0
+    }
+
+    fn key(&self) -> TimerKey {
+        TimerKey {
+            entity: crate::process::entity::EntityId::UserProcess(self.id),
+            kind: TimerKind::UserTimerFd,
+        }
+    }
+
+    /// Put the heap entry in line with `state`. Called with the state
+    /// locked, so an expiry cannot re-arm a deadline `settime` replaced.
+    fn sync(&self, state: &TimerState) -> Result<(), ()> {
+        match state.deadline_ns {
+            Some(deadline) => {
+                crate::process::timer::arm(self.key(), deadline, TimerAction::UserTimerFd(self.id))
+                    .map(|_| ())
+            }
+            None => {
+                crate::process::timer::cancel(self.key());
+                Ok(())
+            }
+        }
+    }
+
+    fn now_ns(&self) -> u64 {
+        if self.clock == CLOCK_REALTIME {
+            crate::time::realtime_ns()
+        } else {
+            crate::time::monotonic_ns()
+        }
+    }
+
+    /// Replace the setting, clearing unread expirations, and return the
+    /// old `(interval, remaining)`.
+    fn settime(&self, deadline_ns: Option<u64>, interval_ns: u64) -> Result<(u64, u64), i64> {
+        let now = monotonic_now();
+        let mut state = self.state.lock();
+        state.advance(now);
+        let old = state.current(now);
+        *state = TimerState {
+            deadline_ns,
+            interval_ns,
+            expirations: 0,
+        };
+        if self.sync(&state).is_err() {
+            *state = TimerState::default();
+            let _ = self.sync(&state);
+            return Err(ENOMEM);
+        }
+        Ok(old)
+    }
+
+    fn gettime(&self) -> (u64, u64) {
+        let now = monotonic_now();
+        let mut state = self.state.lock();
+        state.advance(now);
+        state.current(now)
+    }
+
+    fn try_read(&self) -> Option<u64> {
+        let mut state = self.state.lock();
+        state.advance(monotonic_now());
+        if state.expirations == 0 {
+            return None;
+        }
+        Some(core::mem::take(&mut state.expirations))
+    }
+}
+
+impl Drop for TimerFd {
+    fn drop(&mut self) {
+        crate::process::timer::cancel(self.key());
+        TIMERFDS.lock().remove(&self.id);
+    }
+}
+
+/// Timer-heap expiry for timerfd `id`.
+pub fn expire(id: u32, now: u64) {
+    let Some(timer) = TIMERFDS.lock().get(&id).and_then(Weak::upgrade) else {
+        return;
+    };
+    let fired = {
+        let mut state = timer.state.lock();
+        let fired = state.advance(now);
+        // A reader may already have advanced past this entry's deadline;
+        // re-arm for whatever is next either way.
+        if timer.sync(&state).is_err() {
+            crate::debug_warn!("timerfd {}: timer heap full, periodic rearm lost", id);
+        }
+        fired
+    };
+    if fired {
+        timer.generation.fetch_add(1, Ordering::AcqRel);
+        crate::userland::readiness::notify_changed();
+    }
+}
+
+fn timespec_ns(value: &LinuxTimespec) -> Result<u64, i64> {
+    if value.tv_sec < 0 || !(0..NANOSECONDS_PER_SECOND as i64).contains(&value.tv_nsec) {
+        return Err(EINVAL);
+    }
+    Ok((value.tv_sec as u64)
+        .saturating_mul(NANOSECONDS_PER_SECOND)
+        .saturating_add(value.tv_nsec as u64))
+}
+
+fn ns_to_timespec(ns: u64) -> LinuxTimespec {
+    LinuxTimespec {
+        tv_sec: (ns / NANOSECONDS_PER_SECOND) as i64,
+        tv_nsec: (ns % NANOSECONDS_PER_SECOND) as i64,
+    }
+}
+
+fn itimerspec((interval, remaining): (u64, u64)) -> Itimerspec {
+    Itimerspec {
+        it_interval: ns_to_timespec(interval),
+        it_value: ns_to_timespec(remaining),
+    }
+}
+
+/// The monotonic deadline for `it_value`, which is relative to `now`, or
+/// absolute on the timer's clock when that reads `clock_now`. A deadline
+/// already passed fires at once.
+fn deadline_for(value_ns: u64, absolute: bool, now: u64, clock_now: u64) -> Option<u64> {
+    if value_ns == 0 {
+        return None;
+    }
+    let delay = if absolute {
+        value_ns.saturating_sub(clock_now)
+    } else {
+        value_ns
+    };
+    Some(now.saturating_add(delay))
+}
+
+fn timer(fd: i32) -> Result<Arc<TimerFd>, i64> {
+    crate::userland::lifecycle::with_current_group(|process| match process.fd_table.get(fd) {
+        Some(FdSlot::TimerFd { handle, .. }) => Ok(handle.clone()),
+        Some(_) => Err(EINVAL),
+        None => Err(EBADF),
+    })
+}
+
+/// `timerfd_create(clockid, flags) -> fd`
+pub fn timerfd_create_handler(args: &mut SyscallArgs) -> i64 {
+    let clock = args.rdi as i32;
+    let flags = args.rsi as u32;
+    if !matches!(clock, CLOCK_REALTIME | CLOCK_MONOTONIC | CLOCK_BOOTTIME) {
+        return EINVAL;
+    }
+    if flags & !(TFD_NONBLOCK | TFD_CLOEXEC) != 0 {
+        return EINVAL;
+    }
+    let slot = FdSlot::TimerFd {
+        handle: TimerFd::new(clock, flags),
+        cloexec: flags & TFD_CLOEXEC != 0,
+    };
+    crate::userland::lifecycle::with_current_group(|process| process.fd_table.alloc(slot))
+        .map_or(EMFILE, i64::from)
+}
+
+/// `timerfd_settime(fd, flags, *new_value, *old_value) -> int`
+pub fn timerfd_settime_handler(args: &mut SyscallArgs) -> i64 {
+    let handle = match timer(args.rdi as i32) {
+        Ok(handle) => handle,
+        Err(error) => return error,
+    };
+    let flags = args.rsi as u32;
+    if flags & !TFD_TIMER_ABSTIME != 0 {
+        return EINVAL;
+    }
+    let new = match crate::userland::usercopy::read_unaligned::<Itimerspec>(args.rdx) {
+        Ok(new) => new,
+        Err(_) => return EFAULT,
+    };
+    let (interval_ns, value_ns) = match (timespec_ns(&new.it_interval), timespec_ns(&new.it_value))
+    {
+        (Ok(interval), Ok(value)) => (interval, value),
+        (Err(error), _) | (_, Err(error)) => return error,
+    };
+    let old_ptr = args.r10;
+    if old_ptr != 0 {
+        if let Err(error) = crate::userland::usercopy::ensure_user_range(
+            old_ptr,
+            core::mem::size_of::<Itimerspec>() as u64,
+            true,
+        ) {
+            return error;
+        }
+    }
+    let deadline = deadline_for(
+        value_ns,
+        flags & TFD_TIMER_ABSTIME != 0,
+        monotonic_now(),
+        handle.now_ns(),
+    );
+    let old = match handle.settime(deadline, interval_ns) {
+        Ok(old) => old,
+        Err(error) => return error,
+    };
+    crate::userland::readiness::notify_changed();
+    if old_ptr == 0 {
+        return 0;
+    }
+    crate::userland::usercopy::write_unaligned(old_ptr, &itimerspec(old))
+        .map_or_else(|error| error, |_| 0)
+}
+
+/// `timerfd_gettime(fd, *curr_value) -> int`
+pub fn timerfd_gettime_handler(args: &mut SyscallArgs) -> i64 {
+    let handle = match timer(args.rdi as i32) {
+        Ok(handle) => handle,
+        Err(error) => return error,
+    };
+    crate::userland::usercopy::write_unaligned(args.rsi, &itimerspec(handle.gettime()))
+        .map_or_else(|error| error, |_| 0)
+}
+
+pub fn read(args: &SyscallArgs, handle: &Arc<TimerFd>, pointer: u64, len: u64) -> i64 {
+    if len < 8 {
+        return EINVAL;
+    }
+    if let Err(error) = crate::userland::usercopy::ensure_user_range(pointer, 8, true) {
+        return error;
+    }
+    let observed = crate::userland::readiness::sequence();
+    if let Some(value) = handle.try_read() {
+        return crate::userland::usercopy::write_unaligned(pointer, &value)
+            .map_or_else(|_| EFAULT, |_| 8);
+    }
+    if handle.nonblocking() {
+        return EAGAIN;
+    }
+    let identity = Arc::as_ptr(handle) as usize as u64;
+    crate::userland::readiness::block(args, identity, None, observed)
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn test_one_shot_expires_once() {
+        let mut state = TimerState {
+            deadline_ns: Some(10),
+            ..TimerState::default()
+        };
+        assert!(!state.advance(9));
+        assert_eq!(state.current(9), (0, 1));
+        assert!(state.advance(25));
+        assert_eq!(state.expirations, 1);
+        assert_eq!(state.deadline_ns, None);
+        assert!(!state.advance(40));
+        assert_eq!(state.current(40), (0, 0));
+    }
+
+    fn test_periodic_counts_overruns() {
+        let mut state = TimerState {
+            deadline_ns: Some(10),
+            interval_ns: 5,
+            expirations: 0,
+        };
+        // Due at 10, 15 and 20; the next is 25.
+        assert!(state.advance(22));
+        assert_eq!(state.expirations, 3);
+        assert_eq!(state.deadline_ns, Some(25));
+        assert_eq!(state.current(22), (5, 3));
+        assert!(state.advance(25));
+        assert_eq!(state.expirations, 4);
+        assert_eq!(state.deadline_ns, Some(30));
+    }
+
+    fn test_deadline_conversion() {
+        // Zero disarms; relative values count from the monotonic reading.
+        assert_eq!(deadline_for(0, false, 100, 0), None);
+        assert_eq!(deadline_for(1, false, 100, 0), Some(101));
+        assert_eq!(
+            deadline_for(2 * NANOSECONDS_PER_SECOND, false, 100, 0),
+            Some(100 + 2 * NANOSECONDS_PER_SECOND)
+        );
+        // Absolute values count from the clock's reading; a past one fires
+        // at once.
+        let clock_now = 5 * NANOSECONDS_PER_SECOND;
+        assert_eq!(
+            deadline_for(clock_now + 1_000, true, 100, clock_now),
+            Some(1_100)
+        );
+        assert_eq!(deadline_for(clock_now - 1, true, 100, clock_now), Some(100));
+    }
+
+    fn test_timespec_validation() {
+        let valid = LinuxTimespec {
+            tv_sec: 2,
+            tv_nsec: 500,
+        };
+        assert_eq!(timespec_ns(&valid), Ok(2 * NANOSECONDS_PER_SECOND + 500));
+        let bad_nsec = LinuxTimespec {
+            tv_sec: 0,
+            tv_nsec: NANOSECONDS_PER_SECOND as i64,
+        };
+        assert_eq!(timespec_ns(&bad_nsec), Err(EINVAL));
+        let negative = LinuxTimespec {
+            tv_sec: -1,
+            tv_nsec: 0,
+        };
+        assert_eq!(timespec_ns(&negative), Err(EINVAL));
+        let spec = itimerspec((1_500_000_000, 30_000_000));
+        assert_eq!(
+            (spec.it_interval.tv_sec, spec.it_interval.tv_nsec),
+            (1, 500_000_000)
+        );
+        assert_eq!(
+            (spec.it_value.tv_sec, spec.it_value.tv_nsec),
+            (0, 30_000_000)
+        );
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_one_shot_expires_once,
+            &test_periodic_counts_overruns,
+            &test_deadline_conversion,
+            &test_timespec_validation,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as timerfd_tests;
@@ -0,0 +1,1609 @@
+//! Linux AF_UNIX sockets: stream, datagram and sequenced-packet.
+//!
+//! Each socket owns its receive queue; a sender pushes straight into the
+//! peer's queue, so a connected pair needs no shared buffer. Messages keep
+//! their boundaries in the queue and carry the sender's credentials and any
+//! `SCM_RIGHTS` descriptors; stream reads join adjacent messages but stop at
+//! one that carries descriptors.
+//!
+//! Bound names live in a kernel-wide registry. Binding a filesystem path
+//! also creates an empty file there, so an existing path is
+//! `EADDRINUSE`, connecting to a missing one is `ENOENT`, and `unlink`
+//! frees the name as on Linux; the node stats as a regular file.
+//! Abstract names (leading NUL) exist only in the registry. Path names are
+//! stored and reported resolved against the binder's cwd.
+//!
+//! A descriptor queued in flight keeps its open-file description alive
+//! until it is received or the queue is dropped. Cycles of sockets queued
+//! in each other are not collected; instead, as Linux's
+//! `too_many_unix_fds` does, each user may have at most `RLIMIT_NOFILE`
+//! descriptors in flight (`ETOOMANYREFS`), and a socket cannot be sent
+//! into its own queue.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::x86_64::syscall::SyscallArgs;
+use crate::lib::arc::{Arc, Weak};
+use crate::userland::abi::{
+    EADDRINUSE, EAGAIN, EBADF, ECONNREFUSED, EFAULT, EINVAL, EISCONN, EMFILE, EMSGSIZE, ENOBUFS,
+    ENOENT, ENOPROTOOPT, ENOTCONN, EOPNOTSUPP, EPERM, EPIPE, EPROTONOSUPPORT, EPROTOTYPE,
+    ETOOMANYREFS,
+};
+use crate::userland::fdtable::FdSlot;
+use crate::userland::network_syscalls::{
+    gather_iovecs, read_iovecs, scatter_iovecs, timeout_ticks, timeval_from_ticks, write_option,
+    LinuxMsghdr, LinuxTimeval, IO_MAX,
+};
+
+const AF_UNSPEC: u16 = 0;
+const AF_UNIX: u16 = 1;
+const SOCK_STREAM: i32 = 1;
+const SOCK_DGRAM: i32 = 2;
+const SOCK_SEQPACKET: i32 = 5;
+const SOCK_NONBLOCK: i32 = 0x800;
+const SOCK_CLOEXEC: i32 = 0x80000;
+const SOCK_TYPE_MASK: i32 = 0xf;
+const SOL_SOCKET: i32 = 1;
+const SCM_RIGHTS: i32 = 1;
+const SCM_CREDENTIALS: i32 = 2;
+const SO_REUSEADDR: i32 = 2;
+const SO_TYPE: i32 = 3;
+const SO_ERROR: i32 = 4;
+const SO_SNDBUF: i32 = 7;
+const SO_RCVBUF: i32 = 8;
+const SO_PASSCRED: i32 = 16;
+const SO_PEERCRED: i32 = 17;
+const SO_RCVTIMEO: i32 = 20;
+const SO_SNDTIMEO: i32 = 21;
+const SO_ACCEPTCONN: i32 = 30;
+const MSG_PEEK: i32 = 0x2;
+const MSG_CTRUNC: i32 = 0x8;
+const MSG_TRUNC: i32 = 0x20;
+const MSG_DONTWAIT: i32 = 0x40;
+const MSG_CMSG_CLOEXEC: i32 = 0x4000_0000;
+const SHUT_RD: i32 = 0;
+const SHUT_WR: i32 = 1;
+const SHUT_RDWR: i32 = 2;
+
+/// `sizeof(struct sockaddr_un)`: family plus a 108-byte `sun_path`.
+const SOCKADDR_UN_LEN: usize = 110;
+/// Receive-queue capacity in bytes, which also bounds one datagram.
+const BUFFER_BYTES: usize = IO_MAX;
+/// Linux `somaxconn` default.
+const MAX_BACKLOG: i32 = 4096;
+/// Linux `SCM_MAX_FD`.
+const MAX_RIGHTS: usize = 253;
+/// Linux `optmem_max` default; larger control buffers are `ENOBUFS`.
+const MAX_CONTROL: u64 = 20480;
+const CMSG_HEADER: usize = 16;
+
+static NEXT_SOCKET_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_AUTOBIND: AtomicU32 = AtomicU32::new(0);
+
+/// Bound names. Entries point at live sockets or at sockets whose name
+/// was later reused; `Drop` removes an entry that no longer resolves.
+static NAMES: Mutex<BTreeMap<Address, Weak<UnixSocket>>> = Mutex::new(BTreeMap::new());
+
+/// Descriptors queued in flight, by the real uid that sent them.
+static IN_FLIGHT: Mutex<BTreeMap<u32, usize>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Stream,
+    Datagram,
+    SeqPacket,
+}
+
+impl Kind {
+    fn from_raw(raw: i32) -> Option<Self> {
+        match raw {
+            SOCK_STREAM => Some(Self::Stream),
+            SOCK_DGRAM => Some(Self::Datagram),
+            SOCK_SEQPACKET => Some(Self::SeqPacket),
+            _ => None,
+        }
+    }
+
+    fn raw(self) -> i32 {
+        match self {
+            Self::Stream => SOCK_STREAM,
+            Self::Datagram => SOCK_DGRAM,
+            Self::SeqPacket => SOCK_SEQPACKET,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+enum Address {
+    #[default]
+    Unnamed,
+    Path(String),
+    Abstract(Vec<u8>),
+}
+
+/// Linux `struct ucred`.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+struct Ucred {
+    pid: i32,
+    uid: u32,
+    gid: u32,
+}
+
+fn current_credentials() -> Ucred {
+    let credentials = crate::userland::credentials::current();
+    Ucred {
+        pid: crate::userland::lifecycle::current_tgid() as i32,
+        uid: credentials.user.effective,
+        gid: credentials.group.effective,
+    }
+}
+
+/// `SCM_RIGHTS` descriptors on their way to a receiver, charged to the
+/// sending user until they are received or dropped.
+#[derive(Default)]
+struct Rights {
+    slots: Vec<FdSlot>,
+    user: u32,
+    charged: usize,
+}
+
+impl Rights {
+    /// Charge `slots` to `user`, refusing to go past `limit` descriptors
+    /// in flight.
+    fn charge(slots: Vec<FdSlot>, user: u32, limit: Option<u64>) -> Result<Self, i64> {
+        let charged = slots.len();
+        if charged > 0 {
+            let mut in_flight = IN_FLIGHT.lock();
+            let count = in_flight.get(&user).copied().unwrap_or(0) + charged;
+            if limit.is_some_and(|limit| count as u64 > limit) {
+                return Err(ETOOMANYREFS);
+            }
+            in_flight.insert(user, count);
+        }
+        Ok(Self {
+            slots,
+            user,
+            charged,
+        })
+    }
+
+    /// Hand the descriptors to a receiver.
+    fn take(&mut self) -> Vec<FdSlot> {
+        self.uncharge();
+        core::mem::take(&mut self.slots)
+    }
+
+    fn uncharge(&mut self) {
+        let charged = core::mem::take(&mut self.charged);
+        if charged == 0 {
+            return;
+        }
+        let mut in_flight = IN_FLIGHT.lock();
+        if let Some(count) = in_flight.get_mut(&self.user) {
+            *count -= charged;
+            if *count == 0 {
+                in_flight.remove(&self.user);
+            }
+        }
+    }
+}
+
+impl core::ops::Deref for Rights {
+    type Target = [FdSlot];
+
+    fn deref(&self) -> &[FdSlot] {
+        &self.slots
+    }
+}
+
+impl Drop for Rights {
+    fn drop(&mut self) {
+        self.uncharge();
+    }
+}
+
+struct Message {
+    data: Vec<u8>,
+    /// Bytes of `data` already read by a stream reader.
+    offset: usize,
+    rights: Rights,
+    credentials: Ucred,
+    source: Address,
+}
+
+#[derive(Default)]
+struct Received {
+    data: Vec<u8>,
+    /// Full message length; differs from `data.len()` when truncated.
+    length: usize,
+    rights: Vec<FdSlot>,
+    credentials: Ucred,
+    source: Address,
+}
+
+enum Connection {
+    Unconnected,
+    Listening {
+        backlog: VecDeque<Arc<UnixSocket>>,
+        limit: usize,
+        credentials: Ucred,
+    },
+    /// For datagram sockets, the default destination.
+    Connected {
+        peer: Weak<UnixSocket>,
+        credentials: Ucred,
+    },
+}
+
+enum Peer {
+    Unconnected,
+    Listening,
+    Connected(Arc<UnixSocket>),
+    Disconnected,
+}
+
+struct State {
+    address: Address,
+    connection: Connection,
+    queue: VecDeque<Message>,
+    queued: usize,
+    pass_credentials: bool,
+    receive_timeout: Option<u64>,
+    send_timeout: Option<u64>,
+}
+
+impl State {
+    fn take_stream(&mut self, capacity: usize, peek: bool) -> Received {
+        let mut received = Received::default();
+        let mut index = 0;
+        while received.data.len() < capacity {
+            let Some(message) = self.queue.get_mut(index) else {
+                break;
+            };
+            if received.data.is_empty() {
+                received.credentials = message.credentials;
+                received.source = message.source.clone();
+                received.rights = if peek {
+                    message.rights.to_vec()
+                } else {
+                    message.rights.take()
+                };
+            } else if !message.rights.is_empty()
+                || (self.pass_credentials && message.credentials != received.credentials)
+            {
+                break;
+            }
+            let available = &message.data[message.offset..];
+            let count = available.len().min(capacity - received.data.len());
+            received.data.extend_from_slice(&available[..count]);
+            if peek {
+                index += 1;
+            } else {
+                message.offset += count;
+                self.queued -= count;
+                if message.offset == message.data.len() {
+                    self.queue.pop_front();
+                }
+            }
+            if !received.rights.is_empty() {
+                break;
+            }
+        }
+        received.length = received.data.len();
+        received
+    }
+
+    fn take_message(&mut self, capacity: usize, peek: bool) -> Received {
+        let received = if peek {
+            self.queue.front().map(|message| Received {
+                data: message.data.clone(),
+                length: 0,
+                rights: message.rights.to_vec(),
+                credentials: message.credentials,
+                source: message.source.clone(),
+            })
+        } else {
+            self.queue.pop_front().map(|mut message| {
+                self.queued -= message.data.len();
+                Received {
+                    rights: message.rights.take(),
+                    data: core::mem::take(&mut message.data),
+                    length: 0,
+                    credentials: message.credentials,
+                    source: core::mem::take(&mut message.source),
+                }
+            })
+        };
+        let Some(mut received) = received else {
+            return Received::default();
+        };
+        received.length = received.data.len();
+        received.data.truncate(capacity);
+        received
+    }
+}
+
+pub struct UnixSocket {
+    id: u64,
+    kind: Kind,
+    state: Mutex<State>,
+    nonblocking: AtomicBool,
+    /// `SHUT_RD` on this end: reads see end-of-file and peers get `EPIPE`.
+    read_shutdown: AtomicBool,
+    /// `SHUT_WR` on this end: the peer reads end-of-file.
+    write_shutdown: AtomicBool,
+}
+
+impl UnixSocket {
+    fn new(kind: Kind, nonblocking: bool) -> Arc<Self> {
+        Arc::new(Self {
+            id: NEXT_SOCKET_ID.fetch_add(1, Ordering::Relaxed),
+            kind,
+            state: Mutex::new(State {
+                address: Address::Unnamed,
+                connection: Connection::Unconnected,
+                queue: VecDeque::new(),
+                queued: 0,
+                pass_credentials: false,
+                receive_timeout: None,
+                send_timeout: None,
+            }),
+            nonblocking: AtomicBool::new(nonblocking),
+            read_shutdown: AtomicBool::new(false),
+            write_shutdown: AtomicBool::new(false),
+        })
+    }
+
+    fn pair(kind: Kind, nonblocking: bool, credentials: Ucred) -> (Arc<Self>, Arc<Self>) {
+        let zero = Self::new(kind, nonblocking);
+        let one = Self::new(kind, nonblocking);
+        zero.state.lock().connection = Connection::Connected {
+            peer: Arc::downgrade(&one),
+            credentials,
+        };
+        one.state.lock().connection = Connection::Connected {
+            peer: Arc::downgrade(&zero),
+            credentials,
+        };
+        (zero, one)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn nonblocking(&self) -> bool {
+        self.nonblocking.load(Ordering::Acquire)
+    }
+
+    pub fn set_nonblocking(&self, value: bool) {
+        self.nonblocking.store(value, Ordering::Release);
+    }
+
+    fn address(&self) -> Address {
+        self.state.lock().address.clone()
+    }
+
+    /// Upgrade the peer outside the state lock: dropping the last reference
+    /// to it runs its `Drop`.
+    fn peer(&self) -> Peer {
+        let peer = match &self.state.lock().connection {
+            Connection::Unconnected => return Peer::Unconnected,
+            Connection::Listening { .. } => return Peer::Listening,
+            Connection::Connected { peer, .. } => peer.clone(),
+        };
+        peer.upgrade().map_or(Peer::Disconnected, Peer::Connected)
+    }
+
+    fn has_room(&self) -> bool {
+        self.state.lock().queued < BUFFER_BYTES
+    }
+
+    /// `(readable, writable, error, hangup)` for poll and epoll.
+    pub fn readiness(&self) -> (bool, bool, bool, bool) {
+        let peer = self.peer();
+        let read_shutdown = self.read_shutdown.load(Ordering::Acquire);
+        let write_shutdown = self.write_shutdown.load(Ordering::Acquire);
+        let (queued, backlog) = {
+            let state = self.state.lock();
+            let backlog = match &state.connection {
+                Connection::Listening { backlog, .. } => !backlog.is_empty(),
+                _ => false,
+            };
+            (!state.queue.is_empty(), backlog)
+        };
+        if self.kind == Kind::Datagram {
+            let writable = match &peer {
+                Peer::Connected(peer) => peer.has_room(),
+                _ => true,
+            };
+            return (
+                queued || read_shutdown,
+                writable && !write_shutdown,
+                false,
+                false,
+            );
+        }
+        match peer {
+            Peer::Listening => (backlog, false, false, false),
+            Peer::Unconnected => (false, true, false, true),
+            Peer::Disconnected => (true, false, false, true),
+            Peer::Connected(peer) => {
+                let eof = read_shutdown || peer.write_shutdown.load(Ordering::Acquire);
+                let writable = !write_shutdown && peer.has_room();
+                (queued || eof, writable, false, eof && write_shutdown)
+            }
+        }
+    }
+
+    /// Queue `message` for this socket's reader. `Ok(None)` means the
+    /// receive buffer has no room for it yet.
+    fn enqueue(&self, mut message: Message) -> Result<Option<usize>, i64> {
+        if self.read_shutdown.load(Ordering::Acquire) {
+            return Err(EPIPE);
+        }
+        let mut state = self.state.lock();
+        let accepted = if self.kind == Kind::Stream {
+            let room = BUFFER_BYTES.saturating_sub(state.queued);
+            if room == 0 {
+                return Ok(None);
+            }
+            message.data.truncate(room);
+            message.data.len()
+        } else {
+            let length = message.data.len();
+            if length > BUFFER_BYTES {
+                return Err(EMSGSIZE);
+            }
+            if !state.queue.is_empty() && state.queued + length > BUFFER_BYTES {
+                return Ok(None);
+            }
+            length
+        };
+        state.queued += accepted;
+        let joins_tail = self.kind == Kind::Stream
+            && message.rights.is_empty()
+            && state.queue.back().is_some_and(|tail| {
+                tail.rights.is_empty()
+                    && tail.credentials == message.credentials
+                    && tail.source == message.source
+            });
+        match state.queue.back_mut() {
+            Some(tail) if joins_tail => tail.data.extend_from_slice(&message.data),
+            _ => state.queue.push_back(message),
+        }
+        drop(state);
+        crate::userland::readiness::notify_changed();
+        Ok(Some(accepted))
+    }
+
+    /// Take up to `capacity` bytes off the receive queue. `Ok(None)` means
+    /// nothing is queued yet; an empty result is end-of-file.
+    fn take(&self, capacity: usize, peek: bool) -> Result<Option<Received>, i64> {
+        let eof = if self.kind == Kind::Datagram {
+            self.read_shutdown.load(Ordering::Acquire)
+        } else {
+            match self.peer() {
+                Peer::Connected(peer) => {
+                    self.read_shutdown.load(Ordering::Acquire)
+                        || peer.write_shutdown.load(Ordering::Acquire)
+                }
+                Peer::Disconnected => true,
+                Peer::Unconnected | Peer::Listening => return Err(EINVAL),
+            }
+        };
+        let mut state = self.state.lock();
+        if state.queue.is_empty() {
+            return Ok(eof.then(Received::default));
+        }
+        let received = if self.kind == Kind::Stream {
+            state.take_stream(capacity, peek)
+        } else {
+            state.take_message(capacity, peek)
+        };
+        drop(state);
+        if !peek {
+            crate::userland::readiness::notify_changed();
+        }
+        Ok(Some(received))
+    }
+
+    /// Park the caller until readiness changes, or fail with `EAGAIN` for
+    /// a nonblocking call or an expired timeout.
+    fn wait(&self, args: &SyscallArgs, flags: i32, timeout: Option<u64>, observed: u64) -> i64 {
+        if self.nonblocking() || flags & MSG_DONTWAIT != 0 {
+            crate::userland::lifecycle::clear_network_wait();
+            return EAGAIN;
+        }
+        let deadline =
+            match crate::userland::lifecycle::prepare_network_wait(args.rax, self.id, timeout) {
+                Ok(deadline) => deadline,
+                Err(()) => return EAGAIN,
+            };
+        unsafe {
+            crate::userland::switch::block_current_ring3_and_yield(
+                args,
+                crate::userland::lifecycle::Ring3BlockReason::WaitingForReadiness {
+                    deadline_tick: deadline,
+                    observed_sequence: observed,
+                },
+            )
+        }
+    }
+
+    fn send_target(&self, destination: Option<Address>) -> Result<Arc<UnixSocket>, i64> {
+        let peer = self.peer();
+        match (self.kind, destination) {
+            (Kind::Datagram, Some(address)) => {
+                let target = lookup(&address)?;
+                if target.kind != Kind::Datagram {
+                    return Err(EPROTOTYPE);
+                }
+                Ok(target)
+            }
+            (Kind::Datagram, None) => match peer {
+                Peer::Connected(peer) => Ok(peer),
+                Peer::Disconnected => Err(ECONNREFUSED),
+                Peer::Unconnected | Peer::Listening => Err(ENOTCONN),
+            },
+            (_, Some(_)) => match peer {
+                Peer::Connected(_) | Peer::Disconnected => Err(EISCONN),
+                Peer::Unconnected | Peer::Listening => Err(EOPNOTSUPP),
+            },
+            (_, None) => match peer {
+                Peer::Connected(peer) => Ok(peer),
+                Peer::Disconnected => Err(EPIPE),
+                Peer::Unconnected | Peer::Listening => Err(ENOTCONN),
+            },
+        }
+    }
+
+    fn send(
+        args: &SyscallArgs,
+        handle: &Arc<Self>,
+        data: Vec<u8>,
+        control: Control,
+        destination: Option<Address>,
+        flags: i32,
+    ) -> i64 {
+        let result = if handle.write_shutdown.load(Ordering::Acquire) {
+            Err(EPIPE)
+        } else {
+            handle.send_target(destination)
+        };
+        let target = match result {
+            Ok(target) => target,
+            Err(error) => {
+                crate::userland::lifecycle::clear_network_wait();
+                return error;
+            }
+        };
+        if handle.kind == Kind::Stream && data.is_empty() {
+            return 0;
+        }
+        // A socket queued in itself could never be received or freed.
+        if control.rights.iter().any(|slot| {
+            matches!(slot, FdSlot::UnixSocket { handle, .. } if Arc::ptr_eq(handle, &target))
+        }) {
+            crate::userland::lifecycle::clear_network_wait();
+            return EINVAL;
+        }
+        let message = Message {
+            data,
+            offset: 0,
+            rights: control.rights,
+            credentials: control.credentials.unwrap_or_else(current_credentials),
+            source: handle.address(),
+        };
+        let observed = crate::userland::readiness::sequence();
+        match target.enqueue(message) {
+            Ok(Some(count)) => {
+                crate::userland::lifecycle::clear_network_wait();
+                count as i64
+            }
+            Ok(None) => {
+                let timeout = handle.state.lock().send_timeout;
+                handle.wait(args, flags, timeout, observed)
+            }
+            Err(error) => {
+                crate::userland::lifecycle::clear_network_wait();
+                error
+            }
+        }
+    }
+
+    fn receive(
+        args: &SyscallArgs,
+        handle: &Arc<Self>,
+        capacity: usize,
+        flags: i32,
+    ) -> Result<Received, i64> {
+        let observed = crate::userland::readiness::sequence();
+        match handle.take(capacity.min(IO_MAX), flags & MSG_PEEK != 0) {
+            Ok(Some(received)) => {
+                crate::userland::lifecycle::clear_network_wait();
+                Ok(received)
+            }
+            Ok(None) => {
+                let timeout = handle.state.lock().receive_timeout;
+                Err(handle.wait(args, flags, timeout, observed))
+            }
+            Err(error) => {
+                crate::userland::lifecycle::clear_network_wait();
+                Err(error)
+            }
+        }
+    }
+
+    pub fn read(args: &SyscallArgs, handle: &Arc<Self>, pointer: u64, len: u64) -> i64 {
+        if let Err(error) =
+            crate::userland::usercopy::ensure_user_range(pointer, len.min(IO_MAX as u64), true)
+        {
+            return error;
+        }
+        match Self::receive(args, handle, len as usize, 0) {
+            Ok(received) => crate::userland::usercopy::copy_to_user(pointer, &received.data)
+                .map_or_else(|error| error, |_| received.data.len() as i64),
+            Err(error) => error,
+        }
+    }
+
+    pub fn write(args: &SyscallArgs, handle: &Arc<Self>, pointer: u64, len: u64) -> i64 {
+        let count = if handle.kind == Kind::Stream {
+            len.min(IO_MAX as u64) as usize
+        } else if len > BUFFER_BYTES as u64 {
+            return EMSGSIZE;
+        } else {
+            len as usize
+        };
+        let mut data = alloc::vec![0u8; count];
+        if let Err(error) = crate::userland::usercopy::copy_from_user(&mut data, pointer) {
+            return error;
+        }
+        Self::send(args, handle, data, Control::default(), None, 0)
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        let address = core::mem::take(&mut self.state.get_mut().address);
+        if address != Address::Unnamed {
+            let mut names = NAMES.lock();
+            if names
+                .get(&address)
+                .is_some_and(|socket| socket.strong_count() == 0)
+            {
+                names.remove(&address);
+            }
+        }
+        crate::userland::readiness::notify_changed();
+    }
+}
+
+/// Find the live socket bound to `address`.
+fn lookup(address: &Address) -> Result<Arc<UnixSocket>, i64> {
+    if let Address::Path(path) = address {
+        if crate::fs::vfs::vfs_stat(path).is_err() {
+            return Err(ENOENT);
+        }
+    }
+    NAMES
+        .lock()
+        .get(address)
+        .and_then(Weak::upgrade)
+        .ok_or(ECONNREFUSED)
+}
+
+/// Parse the `sun_path` bytes of a `sockaddr_un`. `None` asks for an
+/// autobound name.
+fn parse_address(path: &[u8]) -> Result<Option<Address>, i64> {
+    match path.first() {
+        None => Ok(None),
+        Some(0) => Ok(Some(Address::Abstract(path[1..].to_vec()))),
+        Some(_) => {
+            let end = path
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(path.len());
+            core::str::from_utf8(&path[..end])
+                .map(|path| Some(Address::Path(String::from(path))))
+                .map_err(|_| EINVAL)
+        }
+    }
+}
+
+fn encode_address(address: &Address) -> Vec<u8> {
+    let mut encoded = Vec::from(AF_UNIX.to_ne_bytes());
+    match address {
+        Address::Unnamed => {}
+        Address::Path(path) => {
+            encoded.extend_from_slice(path.as_bytes());
+            encoded.push(0);
+        }
+        Address::Abstract(name) => {
+            encoded.push(0);
+            encoded.extend_from_slice(name);
+        }
+    }
+    encoded
+}
+
+/// Read a user `sockaddr_un`. `Ok(None)` is the bare family, which binds
+/// an autobound name; paths are resolved against the cwd.
+fn read_address(pointer: u64, length: u64) -> Result<Option<Address>, i64> {
+    if pointer == 0 || !(2..=SOCKADDR_UN_LEN as u64).contains(&length) {
+        return Err(EINVAL);
+    }
+    let mut raw = alloc::vec![0u8; length as usize];
+    crate::userland::usercopy::copy_from_user(&mut raw, pointer)?;
+    if u16::from_ne_bytes([raw[0], raw[1]]) != AF_UNIX {
+        return Err(EINVAL);
+    }
+    Ok(match parse_address(&raw[2..])? {
+        Some(Address::Path(path)) => Some(Address::Path(
+            crate::userland::syscalls::resolve_current_path(&path, false)?,
+        )),
+        address => address,
+    })
+}
+
+/// Store `address` at `pointer`, truncated to the caller's buffer, and its
+/// full length at `length_pointer`.
+fn write_address(pointer: u64, length_pointer: u64, address: &Address) -> Result<(), i64> {
+    if length_pointer == 0 {
+        return if pointer == 0 { Ok(()) } else { Err(EFAULT) };
+    }
+    let available = crate::userland::usercopy::read_unaligned::<u32>(length_pointer)? as usize;
+    let encoded = match address {
+        Address::Path(path) => encode_address(&Address::Path(
+            crate::userland::syscalls::current_view_path(path.clone()),
+        )),
+        address => encode_address(address),
+    };
+    let count = available.min(encoded.len());
+    if count != 0 {
+        crate::userland::usercopy::copy_to_user(pointer, &encoded[..count])?;
+    }
+    crate::userland::usercopy::write_unaligned(length_pointer, &(encoded.len() as u32))
+}
+
+#[derive(Default)]
+struct Control {
+    rights: Rights,
+    credentials: Option<Ucred>,
+}
+
+/// Ancillary data of a `sendmsg`, before descriptors are looked up.
+#[derive(Default, Debug, PartialEq, Eq)]
+struct ControlRequest {
+    fds: Vec<i32>,
+    credentials: Option<Ucred>,
+}
+
+fn parse_control(bytes: &[u8]) -> Result<ControlRequest, i64> {
+    let mut request = ControlRequest::default();
+    let mut offset = 0;
+    while offset + CMSG_HEADER <= bytes.len() {
+        let header = &bytes[offset..offset + CMSG_HEADER];
+        let length = u64::from_ne_bytes(header[..8].try_into().expect("8-byte slice")) as usize;
+        let level = i32::from_ne_bytes(header[8..12].try_into().expect("4-byte slice"));
+        let kind = i32::from_ne_bytes(header[12..16].try_into().expect("4-byte slice"));
+        if length < CMSG_HEADER || length > bytes.len() - offset || level != SOL_SOCKET {
+            return Err(EINVAL);
+        }
+        let payload = &bytes[offset + CMSG_HEADER..offset + length];
+        match kind {
+            SCM_RIGHTS => {
+                request.fds.extend(
+                    payload
+                        .chunks_exact(4)
+                        .map(|fd| i32::from_ne_bytes(fd.try_into().expect("4-byte chunk"))),
+                );
+                if request.fds.len() > MAX_RIGHTS {
+                    return Err(EINVAL);
+                }
+            }
+            SCM_CREDENTIALS if payload.len() == core::mem::size_of::<Ucred>() => {
+                request.credentials = Some(Ucred {
+                    pid: i32::from_ne_bytes(payload[0..4].try_into().expect("4-byte slice")),
+                    uid: u32::from_ne_bytes(payload[4..8].try_into().expect("4-byte slice")),
+                    gid: u32::from_ne_bytes(payload[8..12].try_into().expect("4-byte slice")),
+                });
+            }
+            _ => return Err(EINVAL),
+        }
+        offset += length.next_multiple_of(8);
+    }
+    Ok(request)
+}
+
+fn read_control(pointer: u64, length: u64) -> Result<Control, i64> {
+    if length == 0 {
+        return Ok(Control::default());
+    }
+    if length > MAX_CONTROL {
+        return Err(ENOBUFS);
+    }
+    let mut bytes = alloc::vec![0u8; length as usize];
+    crate::userland::usercopy::copy_from_user(&mut bytes, pointer)?;
+    let request = parse_control(&bytes)?;
+    if let Some(claimed) = request.credentials {
+        // Only root may send credentials other than its own.
+        let own = crate::userland::credentials::current();
+        let pid = crate::userland::lifecycle::current_tgid() as i32;
+        if !own.privileged()
+            && (claimed.pid != pid
+                || !own.user.to_array()[..3].contains(&claimed.uid)
+                || !own.group.to_array()[..3].contains(&claimed.gid))
+        {
+            return Err(EPERM);
+        }
+    }
+    let (slots, user, limit) = crate::userland::lifecycle::with_current_group(|process| {
+        let slots = request
+            .fds
+            .iter()
+            .map(|&fd| process.fd_table.get(fd).cloned().ok_or(EBADF))
+            .collect::<Result<Vec<_>, i64>>()?;
+        let limit = (!process.credentials.privileged())
+            .then(|| process.rlimits.cur(crate::userland::rlimit::RLIMIT_NOFILE));
+        Ok::<_, i64>((slots, process.credentials.user.real, limit))
+    })?;
+    Ok(Control {
+        rights: Rights::charge(slots, user, limit)?,
+        credentials: request.credentials,
+    })
+}
+
+fn push_cmsg(out: &mut Vec<u8>, kind: i32, payload: &[u8]) {
+    out.extend_from_slice(&((CMSG_HEADER + payload.len()) as u64).to_ne_bytes());
+    out.extend_from_slice(&SOL_SOCKET.to_ne_bytes());
+    out.extend_from_slice(&kind.to_ne_bytes());
+    out.extend_from_slice(payload);
+    out.resize(out.len().next_multiple_of(8), 0);
+}
+
+/// Build the ancillary data for a receive into a `capacity`-byte control
+/// buffer, installing as many passed descriptors as fit. Returns the bytes
+/// and whether anything was cut off.
+fn build_control(
+    capacity: usize,
+    received: &mut Received,
+    pass_credentials: bool,
+    cloexec: bool,
+) -> (Vec<u8>, bool) {
+    let mut out = Vec::new();
+    let mut truncated = false;
+    if pass_credentials {
+        let credentials = received.credentials;
+        let payload = [
+            credentials.pid.to_ne_bytes(),
+            credentials.uid.to_ne_bytes(),
+            credentials.gid.to_ne_bytes(),
+        ]
+        .concat();
+        if capacity >= CMSG_HEADER + payload.len() {
+            push_cmsg(&mut out, SCM_CREDENTIALS, &payload);
+        } else {
+            truncated = true;
+        }
+    }
+    let rights = core::mem::take(&mut received.rights);
+    if !rights.is_empty() {
+        let total = rights.len();
+        let room = capacity
+            .saturating_sub(out.len())
+            .saturating_sub(CMSG_HEADER)
+            / 4;
+        let (fds, leftover) = crate::userland::lifecycle::with_current_group(|process| {
+            let mut fds = Vec::new();
+            let mut rights = rights.into_iter();
+            for slot in rights.by_ref().take(room) {
+                let Some(fd) = process.fd_table.alloc(slot) else {
+                    break;
+                };
+                let _ = process.fd_table.set_cloexec(fd, cloexec);
+                fds.push(fd);
+            }
+            (fds, rights.collect::<Vec<_>>())
+        });
+        truncated |= fds.len() < total;
+        drop(leftover);
+        if !fds.is_empty() {
+            let payload: Vec<u8> = fds.iter().flat_map(|fd| fd.to_ne_bytes()).collect();
+            push_cmsg(&mut out, SCM_RIGHTS, &payload);
+        }
+    }
+    out.truncate(capacity);
+    (out, truncated)
+}
+
+fn alloc_socket(handle: Arc<UnixSocket>, cloexec: bool) -> Option<i32> {
+    crate::userland::lifecycle::with_current_group(|process| {
+        process
+            .fd_table
+            .alloc(FdSlot::UnixSocket { handle, cloexec })
+    })
+}
+
+/// `socket(AF_UNIX, type, protocol) -> fd`
+pub fn socket(raw_type: i32, protocol: i32) -> i64 {
+    if raw_type & !(SOCK_TYPE_MASK | SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+        return EINVAL;
+    }
+    let Some(kind) = Kind::from_raw(raw_type & SOCK_TYPE_MASK) else {
+        return EPROTONOSUPPORT;
+    };
+    if protocol != 0 && protocol != i32::from(AF_UNIX) {
+        return EPROTONOSUPPORT;
+    }
+    let handle = UnixSocket::new(kind, raw_type & SOCK_NONBLOCK != 0);
+    alloc_socket(handle, raw_type & SOCK_CLOEXEC != 0).map_or(EMFILE, i64::from)
+}
+
+pub fn socketpair_handler(args: &mut SyscallArgs) -> i64 {
+    let domain = args.rdi as i32;
+    let socket_type = args.rsi as i32;
+    let protocol = args.rdx as i32;
+    let output = args.r10;
+    if domain != i32::from(AF_UNIX) {
+        return crate::userland::abi::EAFNOSUPPORT;
+    }
+    let Some(kind) = Kind::from_raw(socket_type & SOCK_TYPE_MASK) else {
+        return EPROTONOSUPPORT;
+    };
+    if socket_type & !(SOCK_TYPE_MASK | SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 || protocol != 0 {
+        return EINVAL;
+    }
+    if crate::userland::usercopy::ensure_user_range(output, 8, true).is_err() {
+        return EFAULT;
+    }
+
+    let nonblocking = socket_type & SOCK_NONBLOCK != 0;
+    let cloexec = socket_type & SOCK_CLOEXEC != 0;
+    let (zero, one) = UnixSocket::pair(kind, nonblocking, current_credentials());
+    let allocated = crate::userland::lifecycle::with_current_group(|process| {
+        let first = process.fd_table.alloc(FdSlot::UnixSocket {
+            handle: zero,
+            cloexec,
+        })?;
+        let second = match process.fd_table.alloc(FdSlot::UnixSocket {
+            handle: one,
+            cloexec,
+        }) {
+            Some(fd) => fd,
+            None => {
+                let _ = process.fd_table.close(first);
+                return None;
+            }
+        };
+        Some((first, second))
+    });
+    let Some((first, second)) = allocated else {
+        return EMFILE;
+    };
+    let pair = [first, second];
+    if crate::userland::usercopy::copy_to_user(output, unsafe {
+        core::slice::from_raw_parts(pair.as_ptr().cast::<u8>(), 8)
+    })
+    .is_err()
+    {
+        crate::userland::lifecycle::with_current_group(|process| {
+            let _ = process.fd_table.close(first);
+            let _ = process.fd_table.close(second);
+        });
+        return EFAULT;
+    }
+    crate::userland::readiness::notify_changed();
+    0
+}
+
+/// `bind(fd, addr, addrlen)`
+pub fn bind(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let address = match read_address(args.rsi, args.rdx) {
+        Ok(address) => address,
+        Err(error) => return error,
+    };
+    if handle.address() != Address::Unnamed {
+        return EINVAL;
+    }
+    let address = match address {
+        Some(address) => address,
+        None => loop {
+            let serial = NEXT_AUTOBIND.fetch_add(1, Ordering::Relaxed) & 0xfffff;
+            let name = Address::Abstract(alloc::format!("{serial:05x}").into_bytes());
+            if !NAMES.lock().contains_key(&name) {
+                break name;
+            }
+        },
+    };
+    if let Address::Path(path) = &address {
+        if crate::fs::vfs::vfs_stat(path).is_ok() {
+            return EADDRINUSE;
+        }
+        if let Err(error) = crate::fs::file_handle::File::create(path) {
+            return crate::userland::syscalls::map_file_err(&error);
+        }
+    }
+    {
+        let mut names = NAMES.lock();
+        if matches!(address, Address::Abstract(_))
+            && names
+                .get(&address)
+                .is_some_and(|socket| socket.strong_count() != 0)
+        {
+            return EADDRINUSE;
+        }
+        names.insert(address.clone(), Arc::downgrade(handle));
+    }
+    handle.state.lock().address = address;
+    0
+}
+
+/// `connect(fd, addr, addrlen)`
+pub fn connect(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    if handle.kind == Kind::Datagram && args.rsi != 0 && args.rdx >= 2 {
+        let family = crate::userland::usercopy::read_unaligned::<u16>(args.rsi);
+        if family == Ok(AF_UNSPEC) {
+            handle.state.lock().connection = Connection::Unconnected;
+            return 0;
+        }
+    }
+    let address = match read_address(args.rsi, args.rdx) {
+        Ok(Some(address)) => address,
+        Ok(None) => return EINVAL,
+        Err(error) => return error,
+    };
+    let target = match lookup(&address) {
+        Ok(target) => target,
+        Err(error) => {
+            crate::userland::lifecycle::clear_network_wait();
+            return error;
+        }
+    };
+    if target.kind != handle.kind {
+        return EPROTOTYPE;
+    }
+    if handle.kind == Kind::Datagram {
+        handle.state.lock().connection = Connection::Connected {
+            peer: Arc::downgrade(&target),
+            credentials: Ucred::default(),
+        };
+        return 0;
+    }
+    if Arc::ptr_eq(&target, handle) {
+        return ECONNREFUSED;
+    }
+    let credentials = current_credentials();
+    let observed = crate::userland::readiness::sequence();
+    // Lock order is connector before listener; a listening socket never
+    // takes another socket's lock while holding its own.
+    let mut state = handle.state.lock();
+    match state.connection {
+        Connection::Unconnected => {}
+        Connection::Connected { .. } => return EISCONN,
+        Connection::Listening { .. } => return EINVAL,
+    }
+    let mut listener = target.state.lock();
+    let address = listener.address.clone();
+    let Connection::Listening {
+        backlog,
+        limit,
+        credentials: listener_credentials,
+    } = &mut listener.connection
+    else {
+        crate::userland::lifecycle::clear_network_wait();
+        return ECONNREFUSED;
+    };
+    if backlog.len() > *limit {
+        drop(listener);
+        let timeout = state.send_timeout;
+        drop(state);
+        return handle.wait(args, 0, timeout, observed);
+    }
+    let server = UnixSocket::new(handle.kind, false);
+    {
+        let mut server_state = server.state.lock();
+        server_state.address = address;
+        server_state.connection = Connection::Connected {
+            peer: Arc::downgrade(handle),
+            credentials,
+        };
+    }
+    state.connection = Connection::Connected {
+        peer: Arc::downgrade(&server),
+        credentials: *listener_credentials,
+    };
+    backlog.push_back(server);
+    drop(listener);
+    drop(state);
+    crate::userland::lifecycle::clear_network_wait();
+    crate::userland::readiness::notify_changed();
+    0
+}
+
+/// `listen(fd, backlog)`
+pub fn listen(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    if handle.kind == Kind::Datagram {
+        return EOPNOTSUPP;
+    }
+    let limit = (args.rsi as i32).clamp(0, MAX_BACKLOG) as usize;
+    let credentials = current_credentials();
+    let mut state = handle.state.lock();
+    if state.address == Address::Unnamed {
+        return EINVAL;
+    }
+    match &mut state.connection {
+        Connection::Listening { limit: current, .. } => *current = limit,
+        Connection::Connected { .. } => return EINVAL,
+        Connection::Unconnected => {
+            state.connection = Connection::Listening {
+                backlog: VecDeque::new(),
+                limit,
+                credentials,
+            }
+        }
+    }
+    0
+}
+
+/// `accept4(fd, addr, addrlen, flags)`
+pub fn accept(args: &SyscallArgs, handle: &Arc<UnixSocket>, flags: i32) -> i64 {
+    if flags & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+        return EINVAL;
+    }
+    let observed = crate::userland::readiness::sequence();
+    let server = match &mut handle.state.lock().connection {
+        Connection::Listening { backlog, .. } => backlog.pop_front(),
+        _ => return EINVAL,
+    };
+    let Some(server) = server else {
+        let timeout = handle.state.lock().receive_timeout;
+        return handle.wait(args, 0, timeout, observed);
+    };
+    crate::userland::lifecycle::clear_network_wait();
+    server.set_nonblocking(flags & SOCK_NONBLOCK != 0);
+    let peer_address = match server.peer() {
+        Peer::Connected(client) => client.address(),
+        _ => Address::Unnamed,
+    };
+    let Some(fd) = alloc_socket(server.clone(), flags & SOCK_CLOEXEC != 0) else {
+        if let Connection::Listening { backlog, .. } = &mut handle.state.lock().connection {
+            backlog.push_front(server);
+        }
+        return EMFILE;
+    };
+    crate::userland::readiness::notify_changed();
+    if let Err(error) = write_address(args.rsi, args.rdx, &peer_address) {
+        crate::userland::lifecycle::with_current_group(|process| {
+            let _ = process.fd_table.close(fd);
+        });
+        return error;
+    }
+    i64::from(fd)
+}
+
+/// `sendto(fd, buf, len, flags, addr, addrlen)`
+pub fn sendto(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let length = if handle.kind == Kind::Stream {
+        (args.rdx as usize).min(IO_MAX)
+    } else if args.rdx > BUFFER_BYTES as u64 {
+        return EMSGSIZE;
+    } else {
+        args.rdx as usize
+    };
+    let mut data = alloc::vec![0u8; length];
+    if let Err(error) = crate::userland::usercopy::copy_from_user(&mut data, args.rsi) {
+        return error;
+    }
+    let destination = if args.r8 == 0 {
+        None
+    } else {
+        match read_address(args.r8, args.r9) {
+            Ok(Some(address)) => Some(address),
+            Ok(None) => return EINVAL,
+            Err(error) => return error,
+        }
+    };
+    UnixSocket::send(
+        args,
+        handle,
+        data,
+        Control::default(),
+        destination,
+        args.r10 as i32,
+    )
+}
+
+/// `recvfrom(fd, buf, len, flags, addr, addrlen)`
+pub fn recvfrom(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let flags = args.r10 as i32;
+    if let Err(error) =
+        crate::userland::usercopy::ensure_user_range(args.rsi, args.rdx.min(IO_MAX as u64), true)
+    {
+        return error;
+    }
+    let received = match UnixSocket::receive(args, handle, args.rdx as usize, flags) {
+        Ok(received) => received,
+        Err(error) => return error,
+    };
+    if let Err(error) = crate::userland::usercopy::copy_to_user(args.rsi, &received.data) {
+        return error;
+    }
+    if args.r8 != 0 {
+        if let Err(error) = write_address(args.r8, args.r9, &received.source) {
+            return error;
+        }
+    }
+    if flags & MSG_TRUNC != 0 && handle.kind != Kind::Stream {
+        received.length as i64
+    } else {
+        received.data.len() as i64
+    }
+}
+
+/// `sendmsg(fd, msg, flags)`
+pub fn sendmsg(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let message = match crate::userland::usercopy::read_unaligned::<LinuxMsghdr>(args.rsi) {
+        Ok(message) => message,
+        Err(error) => return error,
+    };
+    let data =
+        match read_iovecs(message.iov, message.iov_len).and_then(|iovecs| gather_iovecs(&iovecs)) {
+            Ok(data) => data,
+            Err(error) => return error,
+        };
+    if handle.kind != Kind::Stream && data.len() > BUFFER_BYTES {
+        return EMSGSIZE;
+    }
+    let destination = if message.name == 0 || message.name_len == 0 {
+        None
+    } else {
+        match read_address(message.name, u64::from(message.name_len)) {
+            Ok(Some(address)) => Some(address),
+            Ok(None) => return EINVAL,
+            Err(error) => return error,
+        }
+    };
+    let control = match read_control(message.control, message.control_len) {
+        Ok(control) => control,
+        Err(error) => return error,
+    };
+    UnixSocket::send(args, handle, data, control, destination, args.rdx as i32)
+}
+
+/// `recvmsg(fd, msg, flags)`
+pub fn recvmsg(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let flags = args.rdx as i32;
+    let mut message = match crate::userland::usercopy::read_unaligned::<LinuxMsghdr>(args.rsi) {
+        Ok(message) => message,
+        Err(error) => return error,
+    };
+    let iovecs = match read_iovecs(message.iov, message.iov_len) {
+        Ok(iovecs) => iovecs,
+        Err(error) => return error,
+    };
+    let capacity = iovecs.iter().map(|iovec| iovec.len as usize).sum();
+    let mut received = match UnixSocket::receive(args, handle, capacity, flags) {
+        Ok(received) => received,
+        Err(error) => return error,
+    };
+    if let Err(error) = scatter_iovecs(&iovecs, &received.data) {
+        return error;
+    }
+    let mut message_flags = 0;
+    if received.length > received.data.len() {
+        message_flags |= MSG_TRUNC;
+    }
+    if message.name != 0 {
+        let encoded = encode_address(&received.source);
+        let count = (message.name_len as usize).min(encoded.len());
+        if let Err(error) = crate::userland::usercopy::copy_to_user(message.name, &encoded[..count])
+        {
+            return error;
+        }
+        message.name_len = encoded.len() as u32;
+    }
+    let pass_credentials = handle.state.lock().pass_credentials;
+    let capacity = if message.control == 0 {
+        0
+    } else {
+        message.control_len as usize
+    };
+    let (control, truncated) = build_control(
+        capacity,
+        &mut received,
+        pass_credentials,
+        flags & MSG_CMSG_CLOEXEC != 0,
+    );
+    if truncated {
+        message_flags |= MSG_CTRUNC;
+    }
+    if !control.is_empty() {
+        if let Err(error) = crate::userland::usercopy::copy_to_user(message.control, &control) {
+            return error;
+        }
+    }
+    message.control_len = control.len() as u64;
+    message.flags = message_flags;
+    if let Err(error) = crate::userland::usercopy::write_unaligned(args.rsi, &message) {
+        return error;
+    }
+    if flags & MSG_TRUNC != 0 && handle.kind != Kind::Stream {
+        received.length as i64
+    } else {
+        received.data.len() as i64
+    }
+}
+
+/// `getsockname(fd, addr, addrlen)`
+pub fn getsockname(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    write_address(args.rsi, args.rdx, &handle.address()).map_or_else(|error| error, |_| 0)
+}
+
+/// `getpeername(fd, addr, addrlen)`
+pub fn getpeername(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let Peer::Connected(peer) = handle.peer() else {
+        return ENOTCONN;
+    };
+    write_address(args.rsi, args.rdx, &peer.address()).map_or_else(|error| error, |_| 0)
+}
+
+/// `shutdown(fd, how)`
+pub fn shutdown(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let how = args.rsi as i32;
+    if !matches!(how, SHUT_RD | SHUT_WR | SHUT_RDWR) {
+        return EINVAL;
+    }
+    if handle.kind != Kind::Datagram && matches!(handle.peer(), Peer::Unconnected | Peer::Listening)
+    {
+        return ENOTCONN;
+    }
+    if how != SHUT_WR {
+        handle.read_shutdown.store(true, Ordering::Release);
+    }
+    if how != SHUT_RD {
+        handle.write_shutdown.store(true, Ordering::Release);
+    }
+    crate::userland::readiness::notify_changed();
+    0
+}
+
+/// `setsockopt(fd, SOL_SOCKET, name, value, length)`
+pub fn setsockopt(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    let name = args.rdx as i32;
+    let pointer = args.r10;
+    let length = args.r8 as usize;
+    if args.rsi as i32 != SOL_SOCKET {
+        return ENOPROTOOPT;
+    }
+    match name {
+        SO_PASSCRED if length >= 4 => {
+            match crate::userland::usercopy::read_unaligned::<i32>(pointer) {
+                Ok(value) => handle.state.lock().pass_credentials = value != 0,
+                Err(error) => return error,
+            }
+        }
+        SO_RCVTIMEO | SO_SNDTIMEO if length >= core::mem::size_of::<LinuxTimeval>() => {
+            let timeout =
+                match crate::userland::usercopy::read_unaligned(pointer).and_then(timeout_ticks) {
+                    Ok(timeout) => timeout,
+                    Err(error) => return error,
+                };
+            let mut state = handle.state.lock();
+            if name == SO_RCVTIMEO {
+                state.receive_timeout = timeout;
+            } else {
+                state.send_timeout = timeout;
+            }
+        }
+        // Accepted for compatibility; the buffer size is fixed.
+        SO_REUSEADDR | SO_SNDBUF | SO_RCVBUF if length >= 4 => {}
+        _ => return ENOPROTOOPT,
+    }
+    0
+}
+
+/// `getsockopt(fd, SOL_SOCKET, name, value, length)`
+pub fn getsockopt(args: &SyscallArgs, handle: &Arc<UnixSocket>) -> i64 {
+    if args.rsi as i32 != SOL_SOCKET {
+        return ENOPROTOOPT;
+    }
+    let (value, length) = (args.r10, args.r8);
+    let result = match args.rdx as i32 {
+        SO_TYPE => write_option(value, length, &handle.kind.raw()),
+        SO_ERROR => write_option(value, length, &0i32),
+        SO_PASSCRED => {
+            let pass = handle.state.lock().pass_credentials;
+            write_option(value, length, &i32::from(pass))
+        }
+        SO_PEERCRED => {
+            let credentials = match &handle.state.lock().connection {
+                Connection::Connected { credentials, .. } if handle.kind != Kind::Datagram => {
+                    Some(*credentials)
+                }
+                _ => None,
+            };
+            match credentials {
+                Some(credentials) => write_option(value, length, &credentials),
+                None => Err(ENOTCONN),
+            }
+        }
+        SO_RCVTIMEO | SO_SNDTIMEO => {
+            let state = handle.state.lock();
+            let ticks = if args.rdx as i32 == SO_RCVTIMEO {
+                state.receive_timeout
+            } else {
+                state.send_timeout
+            };
+            drop(state);
+            write_option(value, length, &timeval_from_ticks(ticks))
+        }
+        SO_SNDBUF | SO_RCVBUF => write_option(value, length, &(BUFFER_BYTES as i32)),
+        SO_ACCEPTCONN => {
+            let listening = matches!(handle.peer(), Peer::Listening);
+            write_option(value, length, &i32::from(listening))
+        }
+        _ => Err(ENOPROTOOPT),
+    };
+    result.map_or_else(|error| error, |_| 0)
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn message(data: &[u8]) -> Message {
+        Message {
+            data: Vec::from(data),
+            offset: 0,
+            rights: Rights::default(),
+            credentials: Ucred::default(),
+            source: Address::Unnamed,
+        }
+    }
+
+    fn test_address_round_trip() {
+        assert_eq!(parse_address(b""), Ok(None));
+        assert_eq!(
+            parse_address(b"/run/s\0junk"),
+            Ok(Some(Address::Path(String::from("/run/s"))))
+        );
+        let abstract_name = Address::Abstract(Vec::from(&b"a\0b"[..]));
+        assert_eq!(parse_address(b"\0a\0b"), Ok(Some(abstract_name.clone())));
+        assert_eq!(encode_address(&abstract_name)[2..], b"\0a\0b"[..]);
+        assert_eq!(encode_address(&Address::Unnamed).len(), 2);
+        assert_eq!(
+            encode_address(&Address::Path(String::from("/s")))[2..],
+            b"/s\0"[..]
+        );
+    }
+
+    fn test_stream_joins_and_splits_writes() {
+        let (zero, one) = UnixSocket::pair(Kind::Stream, true, Ucred::default());
+        assert_eq!(one.enqueue(message(b"hello ")), Ok(Some(6)));
+        assert_eq!(one.enqueue(message(b"world")), Ok(Some(5)));
+        let first = one.take(8, false).unwrap().unwrap();
+        assert_eq!(first.data, b"hello wo");
+        let rest = one.take(64, true).unwrap().unwrap();
+        assert_eq!(rest.data, b"rld");
+        assert_eq!(one.take(64, false).unwrap().unwrap().data, b"rld");
+        assert!(one.take(64, false).unwrap().is_none());
+        // Closing the peer turns an empty queue into end-of-file.
+        drop(zero);
+        assert_eq!(one.take(64, false).unwrap().unwrap().data.len(), 0);
+    }
+
+    fn test_stream_read_stops_after_rights() {
+        let (_zero, one) = UnixSocket::pair(Kind::Stream, true, Ucred::default());
+        let (carried, _other) = UnixSocket::pair(Kind::Stream, true, Ucred::default());
+        let mut with_rights = message(b"ab");
+        with_rights.rights.slots.push(FdSlot::UnixSocket {
+            handle: carried,
+            cloexec: false,
+        });
+        one.enqueue(message(b"xy")).unwrap();
+        one.enqueue(with_rights).unwrap();
+        one.enqueue(message(b"cd")).unwrap();
+        let plain = one.take(64, false).unwrap().unwrap();
+        assert_eq!((plain.data.as_slice(), plain.rights.len()), (&b"xy"[..], 0));
+        let carrying = one.take(64, false).unwrap().unwrap();
+        assert_eq!(
+            (carrying.data.as_slice(), carrying.rights.len()),
+            (&b"ab"[..], 1)
+        );
+        assert_eq!(one.take(64, false).unwrap().unwrap().data, b"cd");
+    }
+
+    fn test_datagram_boundaries_and_truncation() {
+        let (_zero, one) = UnixSocket::pair(Kind::Datagram, true, Ucred::default());
+        one.enqueue(message(b"first")).unwrap();
+        one.enqueue(message(b"second")).unwrap();
+        let truncated = one.take(3, false).unwrap().unwrap();
+        assert_eq!(
+            (truncated.data.as_slice(), truncated.length),
+            (&b"fir"[..], 5)
+        );
+        assert_eq!(one.take(64, false).unwrap().unwrap().data, b"second");
+        assert_eq!(
+            one.enqueue(message(&alloc::vec![0; BUFFER_BYTES + 1])),
+            Err(EMSGSIZE)
+        );
+    }
+
+    fn test_full_buffer_refuses() {
+        let (_zero, one) = UnixSocket::pair(Kind::Stream, true, Ucred::default());
+        let big = alloc::vec![7u8; BUFFER_BYTES + 10];
+        assert_eq!(one.enqueue(message(&big)), Ok(Some(BUFFER_BYTES)));
+        assert_eq!(one.enqueue(message(b"x")), Ok(None));
+        let (_a, seq) = UnixSocket::pair(Kind::SeqPacket, true, Ucred::default());
+        seq.enqueue(message(&alloc::vec![0; BUFFER_BYTES - 4]))
+            .unwrap();
+        assert_eq!(seq.enqueue(message(b"too long")), Ok(None));
+    }
+
+    fn test_in_flight_rights_are_capped_per_user() {
+        const USER: u32 = 4242;
+        let slot = || FdSlot::UnixSocket {
+            handle: UnixSocket::new(Kind::Stream, true),
+            cloexec: false,
+        };
+        let in_flight = || IN_FLIGHT.lock().get(&USER).copied().unwrap_or(0);
+        let mut first = Rights::charge(alloc::vec![slot(), slot()], USER, Some(3)).unwrap();
+        assert_eq!(in_flight(), 2);
+        assert_eq!(
+            Rights::charge(alloc::vec![slot(), slot()], USER, Some(3)).err(),
+            Some(ETOOMANYREFS)
+        );
+        let second = Rights::charge(alloc::vec![slot()], USER, Some(3)).unwrap();
+        assert_eq!(first.take().len(), 2, "receiving releases the charge");
+        assert_eq!(in_flight(), 1);
+        drop(second);
+        assert_eq!(in_flight(), 0, "dropping a queued message releases it");
+    }
+
+    fn test_parse_control_messages() {
+        let mut bytes = Vec::new();
+        push_cmsg(
+            &mut bytes,
+            SCM_RIGHTS,
+            &[3i32.to_ne_bytes(), 7i32.to_ne_bytes()].concat(),
+        );
+        let credentials = [5i32.to_ne_bytes(), 0u32.to_ne_bytes(), 0u32.to_ne_bytes()].concat();
+        push_cmsg(&mut bytes, SCM_CREDENTIALS, &credentials);
+        assert_eq!(
+            parse_control(&bytes),
+            Ok(ControlRequest {
+                fds: alloc::vec![3, 7],
+                credentials: Some(Ucred {
+                    pid: 5,
+                    uid: 0,
+                    gid: 0
+                }),
+            })
+        );
+        bytes[8] = 0; // level != SOL_SOCKET
+        assert_eq!(parse_control(&bytes), Err(EINVAL));
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_address_round_trip,
+            &test_stream_joins_and_splits_writes,
+            &test_stream_read_stops_after_rights,
+            &test_datagram_boundaries_and_truncation,
+            &test_full_buffer_refuses,
+            &test_in_flight_rights_are_capped_per_user,
+            &test_parse_control_messages,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as unix_socket_tests;
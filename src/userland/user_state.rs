@@ -123,3 +123,108 @@ pub unsafe fn read_user_rsp() -> u64 {
     );
     rsp
 }
+
+/// Linux `struct user_regs_struct`: the register set `PTRACE_GETREGS`
+/// returns and `NT_PRSTATUS` records.
+#[repr(C)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserRegs {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+const _USER_REGS_SIZE_CHECK: () = assert!(core::mem::size_of::<UserRegs>() == 27 * 8);
+
+impl UserRegs {
+    /// Registers of a task stopped in `state` with FS base `fs_base`.
+    /// `orig_rax` is the syscall being made, or `u64::MAX` for none.
+    pub fn from_state(state: &UserState, orig_rax: u64, fs_base: u64) -> Self {
+        let (cs, ss) = crate::arch::x86_64::gdt::user_selectors();
+        Self {
+            r15: state.r15,
+            r14: state.r14,
+            r13: state.r13,
+            r12: state.r12,
+            rbp: state.rbp,
+            rbx: state.rbx,
+            r11: state.r11,
+            r10: state.r10,
+            r9: state.r9,
+            r8: state.r8,
+            rax: state.rax,
+            rcx: state.rcx,
+            rdx: state.rdx,
+            rsi: state.rsi,
+            rdi: state.rdi,
+            orig_rax,
+            rip: state.rip,
+            cs,
+            eflags: state.rflags,
+            rsp: state.rsp,
+            ss,
+            fs_base,
+            ..Self::default()
+        }
+    }
+
+    /// The general-purpose registers, RIP and RSP as a [`UserState`] with
+    /// `rflags`. Segment registers, `orig_rax` and the FS base are the
+    /// caller's to apply.
+    pub fn to_state(self, rflags: u64) -> UserState {
+        UserState {
+            rax: self.rax,
+            rdi: self.rdi,
+            rsi: self.rsi,
+            rdx: self.rdx,
+            r10: self.r10,
+            r8: self.r8,
+            r9: self.r9,
+            rbx: self.rbx,
+            rbp: self.rbp,
+            rsp: self.rsp,
+            r12: self.r12,
+            r13: self.r13,
+            r14: self.r14,
+            r15: self.r15,
+            rip: self.rip,
+            rflags,
+            rcx: self.rcx,
+            r11: self.r11,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: `repr(C)` with only `u64` fields, so no padding.
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
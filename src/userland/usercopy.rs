@@ -36,15 +36,23 @@ struct PageInFailure {
 }
 
 pub fn ensure_user_page(address: u64, write: bool) -> Result<(), i64> {
+    ensure_group_page(crate::userland::lifecycle::current_tgid(), address, write)
+}
+
+/// [`ensure_user_page`] for the address space of thread group `tgid`,
+/// which need not be the caller's. `ptrace` uses it to reach a tracee's
+/// memory.
+pub fn ensure_group_page(tgid: u32, address: u64, write: bool) -> Result<(), i64> {
     let page = address & !0xfff;
-    let context = crate::userland::lifecycle::with_current_group(|process| {
+    let context = crate::userland::lifecycle::with_group(tgid, |process| {
         let space = process.address_space.as_ref()?;
         Some((
             space.l4_frame(),
             space.vma_generation(),
             space.vmas().find(address)?.clone(),
         ))
-    });
+    })
+    .flatten();
     // Kernel-only syscall tests intentionally use host stack buffers plus the
     // legacy explicit bounds hook. Real processes always have an AddressSpace.
     let Some((l4, vma_generation, vma)) = context else {
@@ -63,7 +71,7 @@ pub fn ensure_user_page(address: u64, write: bool) -> Result<(), i64> {
             )
         })
         .flatten();
-    let outcome = ensure_user_page_inner(page, write, l4, &vma, pager);
+    let outcome = ensure_user_page_inner(tgid, page, write, l4, &vma, pager);
     let (reason, requested, actual) = match outcome {
         Ok((requested, actual)) => (PageInTerminalReason::PresentCommitted, requested, actual),
         Err(failure) => (failure.reason, failure.requested, failure.actual),
@@ -87,6 +95,7 @@ pub fn ensure_user_page(address: u64, write: bool) -> Result<(), i64> {
 }
 
 fn ensure_user_page_inner(
+    tgid: u32,
     page: u64,
     write: bool,
     l4: x86_64::structures::paging::PhysFrame,
@@ -102,6 +111,9 @@ fn ensure_user_page_inner(
     if !vma.prot.contains(required) {
         return Err(fail(PageInTerminalReason::PermissionDenied));
     }
+    if matches!(vma.backing, VmaBacking::Shared { .. }) {
+        return ensure_shared_page(tgid, page, l4, vma, pager);
+    }
 
     let private_frame = crate::mm::memory::with_memory_mapper(|mapper| {
         if let Some((_frame, flags)) = mapper.leaf_info(l4, VirtAddr::new(page)) {
@@ -202,6 +214,7 @@ fn ensure_user_page_inner(
         | VmaBacking::Stack { .. }
         | VmaBacking::Heap
         | VmaBacking::Anonymous => Ok((0, 0)),
+        VmaBacking::Shared { .. } => unreachable!("shared pages use ensure_shared_page"),
     })();
     let (requested, actual) = match populate {
         Ok(counts) => counts,
@@ -225,7 +238,7 @@ fn ensure_user_page_inner(
     // unchanged: pthread creation legitimately mmaps unrelated stacks while
     // this read sleeps. Exact target bounds/protection/backing plus the stable
     // anonymous mapping identity reject destructive changes to this VMA.
-    let unchanged = crate::userland::lifecycle::with_current_group(|process| {
+    let unchanged = crate::userland::lifecycle::with_group(tgid, |process| {
         let Some(space) = process.address_space.as_ref() else {
             return false;
         };
@@ -234,7 +247,8 @@ fn ensure_user_page_inner(
                 .vmas()
                 .find(page)
                 .is_some_and(|current| same_vma(current, vma))
-    });
+    })
+    .unwrap_or(false);
     if !unchanged {
         release_private(frame);
         return Err(PageInFailure {
@@ -305,6 +319,168 @@ fn ensure_user_page_inner(
     }
 }
 
+/// Demand-page one `MAP_SHARED` page. The frame belongs to the VMA's shared
+/// object and this address space only adds a leaf for it, so a page that
+/// another mapper already populated is mapped without I/O. The leaf is
+/// writable whenever the VMA is; shared pages never take the COW path.
+fn ensure_shared_page(
+    tgid: u32,
+    page: u64,
+    l4: x86_64::structures::paging::PhysFrame,
+    vma: &Vma,
+    pager: Option<crate::diagnostics::shadow::pager::Handle>,
+) -> Result<(usize, usize), PageInFailure> {
+    let fail = |reason| PageInFailure {
+        reason,
+        requested: 0,
+        actual: 0,
+    };
+    let Some((object, offset)) = vma.shared_at(page) else {
+        return Err(fail(PageInTerminalReason::NoVma));
+    };
+    let present = crate::mm::memory::with_memory_mapper(|mapper| {
+        mapper.leaf_info(l4, VirtAddr::new(page)).is_some()
+    })
+    .ok_or_else(|| fail(PageInTerminalReason::MapperUnavailable))?;
+    if present {
+        if let Some(handle) = pager {
+            crate::diagnostics::shadow::pager::observe_present(handle);
+        }
+        return Ok((0, 0));
+    }
+
+    let (frame, populated) = match object.page(offset) {
+        Some(frame) => (frame, None),
+        None => {
+            let frame = crate::mm::memory::with_memory_mapper(|mapper| {
+                mapper.allocate_private_zeroed_frame()
+            })
+            .ok_or_else(|| fail(PageInTerminalReason::MapperUnavailable))?
+            .ok_or_else(|| fail(PageInTerminalReason::FrameAllocationFailed))?;
+            if let Some(handle) = pager {
+                crate::diagnostics::shadow::pager::reserve_frame(
+                    handle,
+                    frame.start_address().as_u64(),
+                );
+            }
+            let Some(virtual_address) =
+                crate::mm::memory::phys_to_virt(frame.start_address().as_u64())
+            else {
+                release_private(frame);
+                return Err(fail(PageInTerminalReason::PhysicalAliasFailed));
+            };
+            let destination =
+                unsafe { core::slice::from_raw_parts_mut(virtual_address as *mut u8, 0x1000) };
+            let counts = match object.file() {
+                Some(file) => {
+                    let size = file.size();
+                    if offset >= size {
+                        release_private(frame);
+                        return Err(fail(PageInTerminalReason::FileOffsetInvalid));
+                    }
+                    let available = (size - offset).min(0x1000) as usize;
+                    let actual = match file.read_at(offset, &mut destination[..available]) {
+                        Ok(actual) => actual,
+                        Err(_) => {
+                            release_private(frame);
+                            return Err(PageInFailure {
+                                reason: PageInTerminalReason::IoCompletionError,
+                                requested: available,
+                                actual: 0,
+                            });
+                        }
+                    };
+                    if actual != available {
+                        release_private(frame);
+                        return Err(PageInFailure {
+                            reason: PageInTerminalReason::ShortRead,
+                            requested: available,
+                            actual,
+                        });
+                    }
+                    (available, actual)
+                }
+                None => (0, 0),
+            };
+            if let Some(handle) = pager {
+                crate::diagnostics::shadow::pager::populated(
+                    handle,
+                    counts.0,
+                    counts.1,
+                    crate::diagnostics::wire::fnv1a64(destination),
+                );
+            }
+            // As for private pages, the read slept without VM locks.
+            let unchanged = crate::userland::lifecycle::with_group(tgid, |process| {
+                process.address_space.as_ref().is_some_and(|space| {
+                    space.l4_frame() == l4
+                        && space
+                            .vmas()
+                            .find(page)
+                            .is_some_and(|current| same_vma(current, vma))
+                })
+            })
+            .unwrap_or(false);
+            if !unchanged {
+                release_private(frame);
+                return Err(PageInFailure {
+                    reason: PageInTerminalReason::VmaChangedDuringIo,
+                    requested: counts.0,
+                    actual: counts.1,
+                });
+            }
+            // A fault through another mapping may have installed the page
+            // while this one read it; the first frame stays authoritative.
+            let (winner, adopted) = object.install(offset, frame);
+            if !adopted {
+                release_private(frame);
+            }
+            (winner, Some(counts))
+        }
+    };
+    let (requested, actual) = populated.unwrap_or((0, 0));
+
+    let writable = vma.prot.contains(VmProt::WRITE);
+    let mut flags = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+    if writable {
+        flags.insert(PageTableFlags::WRITABLE);
+    }
+    if !vma.prot.contains(VmProt::EXEC) {
+        flags.insert(PageTableFlags::NO_EXECUTE);
+    }
+    let committed = crate::mm::memory::with_memory_mapper(|mapper| {
+        if mapper.leaf_info(l4, VirtAddr::new(page)).is_some() {
+            return Err(PageInTerminalReason::LeafCollision);
+        }
+        mapper
+            .map_shared_frame_into(l4, VirtAddr::new(page), frame, flags)
+            .map_err(|_| PageInTerminalReason::MapFailed)
+    });
+    match committed {
+        Some(Ok(())) | Some(Err(PageInTerminalReason::LeafCollision)) => {
+            if let Some(handle) = pager {
+                if populated.is_some() {
+                    crate::diagnostics::shadow::pager::commit(handle);
+                } else {
+                    crate::diagnostics::shadow::pager::observe_present(handle);
+                }
+            }
+            x86_64::instructions::tlb::flush(VirtAddr::new(page));
+            Ok((requested, actual))
+        }
+        Some(Err(reason)) => Err(PageInFailure {
+            reason,
+            requested,
+            actual,
+        }),
+        None => Err(PageInFailure {
+            reason: PageInTerminalReason::MapperUnavailable,
+            requested,
+            actual,
+        }),
+    }
+}
+
 fn release_private(frame: x86_64::structures::paging::PhysFrame) {
     let _ = crate::mm::memory::with_memory_mapper(|mapper| mapper.release_private_frame(frame));
 }
@@ -369,6 +545,16 @@ fn same_vma(current: &Vma, original: &Vma) -> bool {
                 && left_offset == right_offset
                 && left_size == right_size
         }
+        (
+            VmaBacking::Shared {
+                object: left_object,
+                offset: left_offset,
+            },
+            VmaBacking::Shared {
+                object: right_object,
+                offset: right_offset,
+            },
+        ) => crate::lib::arc::Arc::ptr_eq(left_object, right_object) && left_offset == right_offset,
         _ => false,
     }
 }
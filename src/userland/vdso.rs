@@ -0,0 +1,656 @@
+//! The vDSO: `clock_gettime`, `gettimeofday` and `getcpu` without a
+//! SYSCALL.
+//!
+//! Boot assembles a small ELF shared object into one frame and keeps the
+//! clock data it reads, the vvar page, in a second frame. The kernel
+//! rewrites the vvar page under a sequence count whenever the TSC scale or
+//! the realtime anchor changes. Every new address space maps the vvar
+//! page read-only at [`USER_VVAR_BASE`] and the image read-execute in the
+//! page above, and the initial stack names the image in `AT_SYSINFO_EHDR`.
+//! musl finds the `__vdso_*` functions through the image's DT_HASH table.
+//!
+//! The clock functions read the TSC against the vvar scale exactly as
+//! [`crate::time`] does, and fall back to the system call for any other
+//! clock or while the TSC is uncalibrated. `getcpu` reads the CPU number
+//! that RDTSCP returns from IA32_TSC_AUX.
+//!
+//! Both mappings are ordinary private pages: `fork` shares them, and a
+//! store made possible by `mprotect` copies the page instead of reaching
+//! other processes.
+
+use core::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+
+use spin::Once;
+use x86_64::structures::paging::{PageTableFlags, PhysFrame};
+use x86_64::VirtAddr;
+
+use crate::arch::x86_64::interrupt_guard::InterruptMutex;
+use crate::mm::paging::{USER_VDSO_BASE, USER_VVAR_BASE};
+use crate::userland::abi::nr;
+use crate::userland::address_space::AddressSpace;
+use crate::userland::vm::{VmProt, Vma, VmaBacking};
+
+const PAGE_SIZE: usize = 0x1000;
+const VDSO_END: u64 = USER_VDSO_BASE + PAGE_SIZE as u64;
+
+const CLOCK_REALTIME: u32 = 0;
+const CLOCK_MONOTONIC: u32 = 1;
+
+/// `VvarData::flags`: IA32_TSC_AUX holds the CPU number on every CPU.
+const VVAR_CPU_NUMBER: u32 = 1;
+
+/// The vvar page as the kernel writes it and the image reads it.
+#[repr(C)]
+struct VvarData {
+    /// Odd while an update is in progress.
+    seq: AtomicU32,
+    flags: AtomicU32,
+    base_tsc: AtomicU64,
+    base_ns: AtomicU64,
+    /// 0 until the TSC is calibrated.
+    ns_per_cycle: AtomicU64,
+    /// [`crate::time::RealtimeAnchor`]: realtime is `realtime_base_ns` plus
+    /// the monotonic time since `realtime_anchor_ns` scaled by
+    /// `realtime_rate`.
+    realtime_anchor_ns: AtomicU64,
+    realtime_base_ns: AtomicU64,
+    realtime_rate: AtomicU64,
+}
+
+// ---------- image layout ----------
+
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+const SHDR_SIZE: usize = 64;
+const DYN_SIZE: usize = 16;
+const SYM_SIZE: usize = 24;
+
+const PHDR_OFFSET: usize = EHDR_SIZE;
+const PHDR_COUNT: usize = 2;
+const SHDR_OFFSET: usize = PHDR_OFFSET + PHDR_COUNT * PHDR_SIZE;
+/// A null section and `.text`, which the exported symbols name.
+const SHDR_COUNT: usize = 2;
+const TEXT_SECTION: u16 = 1;
+const DYNAMIC_OFFSET: usize = SHDR_OFFSET + SHDR_COUNT * SHDR_SIZE;
+const DYNAMIC_COUNT: usize = 6;
+const HASH_OFFSET: usize = DYNAMIC_OFFSET + DYNAMIC_COUNT * DYN_SIZE;
+const SYMBOL_COUNT: usize = EXPORT_NAMES.len() + 1;
+/// nbucket, nchain, one bucket and a chain entry per symbol.
+const HASH_WORDS: usize = 3 + SYMBOL_COUNT;
+const SYMTAB_OFFSET: usize = (HASH_OFFSET + HASH_WORDS * 4 + 7) & !7;
+const STRTAB_OFFSET: usize = SYMTAB_OFFSET + SYMBOL_COUNT * SYM_SIZE;
+const TEXT_OFFSET: usize = 0x400;
+/// How far below the copied code the vvar page starts.
+const VVAR_FROM_TEXT: usize = PAGE_SIZE + TEXT_OFFSET;
+
+const EXPORT_NAMES: [&str; 3] = [
+    "__vdso_clock_gettime",
+    "__vdso_gettimeofday",
+    "__vdso_getcpu",
+];
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PF_X: u32 = 1;
+const PF_R: u32 = 4;
+const SHT_PROGBITS: u32 = 1;
+const SHF_ALLOC: u64 = 2;
+const SHF_EXECINSTR: u64 = 4;
+const DT_NULL: u64 = 0;
+const DT_HASH: u64 = 4;
+const DT_STRTAB: u64 = 5;
+const DT_SYMTAB: u64 = 6;
+const DT_STRSZ: u64 = 10;
+const DT_SYMENT: u64 = 11;
+const STB_GLOBAL: u8 = 1;
+const STT_FUNC: u8 = 2;
+
+// The functions are position-independent and self-contained: the vvar page
+// is found RIP-relative from the code, which is copied into the image page
+// as one block. `read_ns` returns the clock in RAX, realtime when ECX is
+// nonzero, or sets CF when the TSC is uncalibrated.
+core::arch::global_asm!(
+    r#"
+    .pushsection .text.vdso, "ax"
+    .balign 16
+    .global vdso_text_start
+vdso_text_start:
+.Lvdso_read_ns:
+    lea r8, [rip + vdso_text_start - {vvar_from_text}]
+.Lvdso_read_retry:
+    mov r9d, dword ptr [r8 + {seq}]
+    test r9d, 1
+    jnz .Lvdso_read_busy
+    mov r10, qword ptr [r8 + {ns_per_cycle}]
+    test r10, r10
+    jz .Lvdso_read_unavailable
+    lfence
+    rdtsc
+    shl rdx, 32
+    or rax, rdx
+    sub rax, qword ptr [r8 + {base_tsc}]
+    jae .Lvdso_read_scale
+    xor eax, eax
+.Lvdso_read_scale:
+    mul r10
+    shrd rax, rdx, 32
+    add rax, qword ptr [r8 + {base_ns}]
+    test ecx, ecx
+    jz .Lvdso_read_check
+    sub rax, qword ptr [r8 + {realtime_anchor_ns}]
+    jae .Lvdso_read_rate
+    xor eax, eax
+.Lvdso_read_rate:
+    mul qword ptr [r8 + {realtime_rate}]
+    shrd rax, rdx, 32
+    add rax, qword ptr [r8 + {realtime_base_ns}]
+.Lvdso_read_check:
+    cmp r9d, dword ptr [r8 + {seq}]
+    jne .Lvdso_read_retry
+    clc
+    ret
+.Lvdso_read_busy:
+    pause
+    jmp .Lvdso_read_retry
+.Lvdso_read_unavailable:
+    stc
+    ret
+
+    .global vdso_clock_gettime
+vdso_clock_gettime:
+    xor ecx, ecx
+    cmp edi, {clock_monotonic}
+    je .Lvdso_cgt_read
+    mov ecx, 1
+    cmp edi, {clock_realtime}
+    jne .Lvdso_cgt_syscall
+.Lvdso_cgt_read:
+    call .Lvdso_read_ns
+    jc .Lvdso_cgt_syscall
+    xor edx, edx
+    mov r8d, 1000000000
+    div r8
+    mov qword ptr [rsi], rax
+    mov qword ptr [rsi + 8], rdx
+    xor eax, eax
+    ret
+.Lvdso_cgt_syscall:
+    mov eax, {sys_clock_gettime}
+    syscall
+    ret
+
+    .global vdso_gettimeofday
+vdso_gettimeofday:
+    test rdi, rdi
+    jz .Lvdso_gtod_done
+    mov ecx, 1
+    call .Lvdso_read_ns
+    jc .Lvdso_gtod_syscall
+    xor edx, edx
+    mov r8d, 1000000000
+    div r8
+    mov qword ptr [rdi], rax
+    mov eax, edx
+    xor edx, edx
+    mov r8d, 1000
+    div r8
+    mov qword ptr [rdi + 8], rax
+.Lvdso_gtod_done:
+    xor eax, eax
+    ret
+.Lvdso_gtod_syscall:
+    mov eax, {sys_gettimeofday}
+    syscall
+    ret
+
+    .global vdso_getcpu
+vdso_getcpu:
+    lea r8, [rip + vdso_text_start - {vvar_from_text}]
+    test dword ptr [r8 + {flags}], {cpu_number}
+    jz .Lvdso_getcpu_syscall
+    rdtscp
+    test rdi, rdi
+    jz .Lvdso_getcpu_node
+    mov eax, ecx
+    and eax, 0xfff
+    mov dword ptr [rdi], eax
+.Lvdso_getcpu_node:
+    test rsi, rsi
+    jz .Lvdso_getcpu_done
+    shr ecx, 12
+    mov dword ptr [rsi], ecx
+.Lvdso_getcpu_done:
+    xor eax, eax
+    ret
+.Lvdso_getcpu_syscall:
+    mov eax, {sys_getcpu}
+    syscall
+    ret
+
+    .global vdso_text_end
+vdso_text_end:
+    .popsection
+"#,
+    vvar_from_text = const VVAR_FROM_TEXT,
+    seq = const core::mem::offset_of!(VvarData, seq),
+    flags = const core::mem::offset_of!(VvarData, flags),
+    base_tsc = const core::mem::offset_of!(VvarData, base_tsc),
+    base_ns = const core::mem::offset_of!(VvarData, base_ns),
+    ns_per_cycle = const core::mem::offset_of!(VvarData, ns_per_cycle),
+    realtime_anchor_ns = const core::mem::offset_of!(VvarData, realtime_anchor_ns),
+    realtime_base_ns = const core::mem::offset_of!(VvarData, realtime_base_ns),
+    realtime_rate = const core::mem::offset_of!(VvarData, realtime_rate),
+    clock_realtime = const CLOCK_REALTIME,
+    clock_monotonic = const CLOCK_MONOTONIC,
+    cpu_number = const VVAR_CPU_NUMBER,
+    sys_clock_gettime = const nr::CLOCK_GETTIME,
+    sys_gettimeofday = const nr::GETTIMEOFDAY,
+    sys_getcpu = const nr::GETCPU,
+);
+
+unsafe extern "C" {
+    static vdso_text_start: u8;
+    static vdso_text_end: u8;
+    static vdso_clock_gettime: u8;
+    static vdso_gettimeofday: u8;
+    static vdso_getcpu: u8;
+}
+
+struct Pages {
+    vvar: PhysFrame,
+    image: PhysFrame,
+}
+
+static PAGES: Once<Pages> = Once::new();
+/// Serializes vvar updates; readers only ever see even sequence counts
+/// around a consistent snapshot.
+static PUBLISH: InterruptMutex<()> = InterruptMutex::new(());
+
+/// Allocate the vvar page and the image, then publish the clock. Runs after
+/// [`crate::time::init`]; without the frames every process simply makes
+/// the system calls.
+pub fn init() {
+    let frames = crate::mm::memory::with_memory_mapper(|mapper| {
+        let vvar = mapper.allocate_private_zeroed_frame()?;
+        let Some(image) = mapper.allocate_private_zeroed_frame() else {
+            mapper.release_private_frame(vvar);
+            return None;
+        };
+        Some(Pages { vvar, image })
+    })
+    .flatten();
+    let Some(pages) = frames else {
+        crate::debug_warn!("vdso: no frames; clocks stay on system calls");
+        return;
+    };
+    let Some(image) = frame_bytes(pages.image) else {
+        return;
+    };
+    build_image(image);
+    let pages = PAGES.call_once(|| pages);
+    if crate::arch::x86_64::tsc::rdtscp_supported() {
+        if let Some(data) = vvar_data(pages.vvar) {
+            data.flags.store(VVAR_CPU_NUMBER, Ordering::Relaxed);
+        }
+    }
+    publish();
+    crate::debug_info!("vdso: mapped at {:#x}", USER_VDSO_BASE);
+}
+
+/// Copy the current TSC scale and realtime anchor into the vvar page.
+pub(crate) fn publish() {
+    let Some(data) = PAGES.get().and_then(|pages| vvar_data(pages.vvar)) else {
+        return;
+    };
+    let _writer = PUBLISH.lock();
+    let scale = crate::arch::x86_64::tsc::scale();
+    let anchor = crate::time::realtime_anchor();
+    let seq = data.seq.load(Ordering::Relaxed);
+    data.seq.store(seq.wrapping_add(1), Ordering::Relaxed);
+    fence(Ordering::Release);
+    data.base_tsc
+        .store(scale.map_or(0, |scale| scale.base_tsc), Ordering::Relaxed);
+    data.base_ns
+        .store(scale.map_or(0, |scale| scale.base_ns), Ordering::Relaxed);
+    data.ns_per_cycle.store(
+        scale.map_or(0, |scale| scale.ns_per_cycle),
+        Ordering::Relaxed,
+    );
+    data.realtime_anchor_ns
+        .store(anchor.monotonic_ns, Ordering::Relaxed);
+    data.realtime_base_ns
+        .store(anchor.realtime_ns, Ordering::Relaxed);
+    data.realtime_rate.store(anchor.rate, Ordering::Relaxed);
+    data.seq.store(seq.wrapping_add(2), Ordering::Release);
+}
+
+/// Map the vvar page and the image into a fresh address space. Returns the
+/// image base for `AT_SYSINFO_EHDR`, or `None` when boot built no vDSO or
+/// the program already occupies the range.
+pub(crate) fn map_into(space: &mut AddressSpace) -> Option<u64> {
+    let pages = PAGES.get()?;
+    if !space.vmas().is_free(USER_VVAR_BASE, VDSO_END) {
+        return None;
+    }
+    let vvar = Vma::new(
+        USER_VVAR_BASE,
+        USER_VDSO_BASE,
+        VmProt::READ,
+        VmaBacking::ElfResident,
+    )
+    .ok()?;
+    let image = Vma::new(
+        USER_VDSO_BASE,
+        VDSO_END,
+        VmProt::READ.union(VmProt::EXEC),
+        VmaBacking::ElfResident,
+    )
+    .ok()?;
+    let l4 = space.l4_frame();
+    let user = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+    let mapped = crate::mm::memory::with_memory_mapper(|mapper| {
+        mapper.map_shared_frame_into(
+            l4,
+            VirtAddr::new(USER_VVAR_BASE),
+            pages.vvar,
+            user | PageTableFlags::NO_EXECUTE,
+        )?;
+        let result =
+            mapper.map_shared_frame_into(l4, VirtAddr::new(USER_VDSO_BASE), pages.image, user);
+        if result.is_err() {
+            let _ = mapper.unmap_page_from(l4, VirtAddr::new(USER_VVAR_BASE));
+        }
+        result
+    });
+    if !matches!(mapped, Some(Ok(()))) {
+        return None;
+    }
+    // The range was free, so neither insert can overlap.
+    let vmas = space.vmas_mut();
+    vmas.insert(vvar).ok()?;
+    vmas.insert(image).ok()?;
+    Some(USER_VDSO_BASE)
+}
+
+fn frame_bytes(frame: PhysFrame) -> Option<&'static mut [u8; PAGE_SIZE]> {
+    let virt = crate::mm::memory::phys_to_virt(frame.start_address().as_u64())?;
+    // SAFETY: the frame is allocator-owned and kept by PAGES forever; the
+    // physical-memory window maps it writable for the kernel.
+    Some(unsafe { &mut *(virt as *mut [u8; PAGE_SIZE]) })
+}
+
+fn vvar_data(frame: PhysFrame) -> Option<&'static VvarData> {
+    let virt = crate::mm::memory::phys_to_virt(frame.start_address().as_u64())?;
+    // SAFETY: as for `frame_bytes`; every field is atomic.
+    Some(unsafe { &*(virt as *const VvarData) })
+}
+
+/// Offsets of the exported functions from the start of the copied code.
+fn export_offsets() -> [usize; EXPORT_NAMES.len()] {
+    let start = core::ptr::addr_of!(vdso_text_start) as usize;
+    [
+        core::ptr::addr_of!(vdso_clock_gettime) as usize - start,
+        core::ptr::addr_of!(vdso_gettimeofday) as usize - start,
+        core::ptr::addr_of!(vdso_getcpu) as usize - start,
+    ]
+}
+
+fn text() -> &'static [u8] {
+    let start = core::ptr::addr_of!(vdso_text_start);
+    let end = core::ptr::addr_of!(vdso_text_end);
+    // SAFETY: both labels delimit one block in the kernel's text.
+    unsafe { core::slice::from_raw_parts(start, end as usize - start as usize) }
+}
+
+/// Lay out the ELF image: header, program and section headers, the dynamic
+/// section, a one-bucket DT_HASH table, the symbols and strings, then the
+/// code at [`TEXT_OFFSET`]. Addresses are relative to the image base.
+fn build_image(page: &mut [u8; PAGE_SIZE]) {
+    let text = text();
+    let mut strtab = alloc::vec![0u8];
+    let mut name_offsets = [0u32; EXPORT_NAMES.len()];
+    for (offset, name) in name_offsets.iter_mut().zip(EXPORT_NAMES) {
+        *offset = strtab.len() as u32;
+        strtab.extend_from_slice(name.as_bytes());
+        strtab.push(0);
+    }
+    assert!(STRTAB_OFFSET + strtab.len() <= TEXT_OFFSET);
+    assert!(TEXT_OFFSET + text.len() <= PAGE_SIZE);
+
+    // ELF header.
+    page[..4].copy_from_slice(b"\x7fELF");
+    page[4] = 2; // ELFCLASS64
+    page[5] = 1; // ELFDATA2LSB
+    page[6] = 1; // EV_CURRENT
+    put_u16(page, 16, 3); // ET_DYN
+    put_u16(page, 18, 62); // EM_X86_64
+    put_u32(page, 20, 1);
+    put_u64(page, 32, PHDR_OFFSET as u64);
+    put_u64(page, 40, SHDR_OFFSET as u64);
+    put_u16(page, 52, EHDR_SIZE as u16);
+    put_u16(page, 54, PHDR_SIZE as u16);
+    put_u16(page, 56, PHDR_COUNT as u16);
+    put_u16(page, 58, SHDR_SIZE as u16);
+    put_u16(page, 60, SHDR_COUNT as u16);
+
+    put_phdr(page, 0, PT_LOAD, PF_R | PF_X, 0, PAGE_SIZE, PAGE_SIZE);
+    put_phdr(
+        page,
+        1,
+        PT_DYNAMIC,
+        PF_R,
+        DYNAMIC_OFFSET,
+        DYNAMIC_COUNT * DYN_SIZE,
+        8,
+    );
+
+    // `.text`; the null section before it stays zero.
+    let shdr = SHDR_OFFSET + usize::from(TEXT_SECTION) * SHDR_SIZE;
+    put_u32(page, shdr + 4, SHT_PROGBITS);
+    put_u64(page, shdr + 8, SHF_ALLOC | SHF_EXECINSTR);
+    put_u64(page, shdr + 16, TEXT_OFFSET as u64);
+    put_u64(page, shdr + 24, TEXT_OFFSET as u64);
+    put_u64(page, shdr + 32, text.len() as u64);
+    put_u64(page, shdr + 48, 16);
+
+    let dynamic = [
+        (DT_HASH, HASH_OFFSET as u64),
+        (DT_STRTAB, STRTAB_OFFSET as u64),
+        (DT_SYMTAB, SYMTAB_OFFSET as u64),
+        (DT_STRSZ, strtab.len() as u64),
+        (DT_SYMENT, SYM_SIZE as u64),
+        (DT_NULL, 0),
+    ];
+    for (index, (tag, value)) in dynamic.into_iter().enumerate() {
+        put_u64(page, DYNAMIC_OFFSET + index * DYN_SIZE, tag);
+        put_u64(page, DYNAMIC_OFFSET + index * DYN_SIZE + 8, value);
+    }
+
+    // With one bucket every name hashes to it, so the bucket names the last
+    // symbol and each chain entry steps to the one before.
+    put_u32(page, HASH_OFFSET, 1);
+    put_u32(page, HASH_OFFSET + 4, SYMBOL_COUNT as u32);
+    put_u32(page, HASH_OFFSET + 8, (SYMBOL_COUNT - 1) as u32);
+    for symbol in 1..SYMBOL_COUNT {
+        put_u32(page, HASH_OFFSET + 12 + symbol * 4, (symbol - 1) as u32);
+    }
+
+    for (index, (name, offset)) in name_offsets.into_iter().zip(export_offsets()).enumerate() {
+        let sym = SYMTAB_OFFSET + (index + 1) * SYM_SIZE;
+        put_u32(page, sym, name);
+        page[sym + 4] = (STB_GLOBAL << 4) | STT_FUNC;
+        put_u16(page, sym + 6, TEXT_SECTION);
+        put_u64(page, sym + 8, (TEXT_OFFSET + offset) as u64);
+    }
+    page[STRTAB_OFFSET..STRTAB_OFFSET + strtab.len()].copy_from_slice(&strtab);
+    page[TEXT_OFFSET..TEXT_OFFSET + text.len()].copy_from_slice(text);
+}
+
+fn put_phdr(
+    page: &mut [u8],
+    index: usize,
+    p_type: u32,
+    p_flags: u32,
+    offset: usize,
+    size: usize,
+    align: usize,
+) {
+    let phdr = PHDR_OFFSET + index * PHDR_SIZE;
+    put_u32(page, phdr, p_type);
+    put_u32(page, phdr + 4, p_flags);
+    put_u64(page, phdr + 8, offset as u64);
+    put_u64(page, phdr + 16, offset as u64);
+    put_u64(page, phdr + 24, offset as u64);
+    put_u64(page, phdr + 32, size as u64);
+    put_u64(page, phdr + 40, size as u64);
+    put_u64(page, phdr + 48, align as u64);
+}
+
+fn put_u16(page: &mut [u8], offset: usize, value: u16) {
+    page[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(page: &mut [u8], offset: usize, value: u32) {
+    page[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(page: &mut [u8], offset: usize, value: u64) {
+    page[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(feature = "test")]
+mod tests_internal {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    /// Resolve `name` the way musl's `__vdsosym` does: PT_DYNAMIC, then
+    /// every symbol DT_HASH counts.
+    fn lookup(image: &[u8], name: &str) -> Option<(usize, u16)> {
+        let phoff = u64_at(image, 32) as usize;
+        let phnum = u16::from_le_bytes([image[56], image[57]]) as usize;
+        let dynamic = (0..phnum)
+            .map(|index| phoff + index * PHDR_SIZE)
+            .find(|&phdr| u32_at(image, phdr) == PT_DYNAMIC)
+            .map(|phdr| u64_at(image, phdr + 8) as usize)?;
+        let (mut hash, mut symtab, mut strtab) = (0, 0, 0);
+        let mut entry = dynamic;
+        while u64_at(image, entry) != DT_NULL {
+            let value = u64_at(image, entry + 8) as usize;
+            match u64_at(image, entry) {
+                DT_HASH => hash = value,
+                DT_SYMTAB => symtab = value,
+                DT_STRTAB => strtab = value,
+                _ => {}
+            }
+            entry += DYN_SIZE;
+        }
+        let count = u32_at(image, hash + 4) as usize;
+        (0..count).find_map(|index| {
+            let sym = symtab + index * SYM_SIZE;
+            let shndx = u16::from_le_bytes([image[sym + 6], image[sym + 7]]);
+            let start = strtab + u32_at(image, sym) as usize;
+            let len = image[start..].iter().position(|&byte| byte == 0)?;
+            (shndx != 0 && &image[start..start + len] == name.as_bytes())
+                .then(|| (u64_at(image, sym + 8) as usize, shndx))
+        })
+    }
+
+    fn test_image_exports_time_functions() {
+        let pages = PAGES.get().expect("boot built the vDSO");
+        let image = frame_bytes(pages.image).expect("image frame");
+        assert_eq!(&image[..4], b"\x7fELF");
+        for (name, offset) in EXPORT_NAMES.into_iter().zip(export_offsets()) {
+            let (value, shndx) = lookup(image, name).expect("exported symbol");
+            assert_eq!(shndx, TEXT_SECTION);
+            assert_eq!(value, TEXT_OFFSET + offset);
+        }
+        assert_eq!(
+            &image[TEXT_OFFSET..TEXT_OFFSET + text().len()],
+            text(),
+            "the image carries the kernel's copy of the code"
+        );
+        assert!(lookup(image, "__vdso_missing").is_none());
+    }
+
+    fn test_vvar_matches_kernel_clock() {
+        let pages = PAGES.get().expect("boot built the vDSO");
+        let data = vvar_data(pages.vvar).expect("vvar frame");
+        publish();
+        assert_eq!(data.seq.load(Ordering::Acquire) % 2, 0);
+        let scale = crate::arch::x86_64::tsc::scale();
+        assert_eq!(
+            data.ns_per_cycle.load(Ordering::Relaxed),
+            scale.map_or(0, |scale| scale.ns_per_cycle)
+        );
+        assert_eq!(
+            data.base_tsc.load(Ordering::Relaxed),
+            scale.map_or(0, |scale| scale.base_tsc)
+        );
+        assert_eq!(
+            data.base_ns.load(Ordering::Relaxed),
+            scale.map_or(0, |scale| scale.base_ns)
+        );
+        let anchor = crate::time::RealtimeAnchor {
+            monotonic_ns: data.realtime_anchor_ns.load(Ordering::Relaxed),
+            realtime_ns: data.realtime_base_ns.load(Ordering::Relaxed),
+            rate: data.realtime_rate.load(Ordering::Relaxed),
+        };
+        assert_eq!(anchor, crate::time::realtime_anchor());
+        let realtime = crate::time::realtime_ns();
+        let rebuilt = anchor.realtime_at(crate::time::monotonic_ns());
+        assert!(rebuilt >= realtime && rebuilt - realtime < 1_000_000_000);
+    }
+
+    fn test_every_address_space_maps_the_same_pages() {
+        let pages = PAGES.get().expect("boot built the vDSO");
+        let mut space = AddressSpace::new().expect("AddressSpace::new");
+        assert_eq!(map_into(&mut space), Some(USER_VDSO_BASE));
+        assert_eq!(map_into(&mut space), None, "the range is taken");
+
+        let l4 = space.l4_frame();
+        let (vvar, image) = crate::mm::memory::with_memory_mapper(|mapper| {
+            (
+                mapper.leaf_info(l4, VirtAddr::new(USER_VVAR_BASE)),
+                mapper.leaf_info(l4, VirtAddr::new(USER_VDSO_BASE)),
+            )
+        })
+        .expect("mapper");
+        let (frame, flags) = vvar.expect("vvar leaf");
+        assert_eq!(frame, pages.vvar);
+        assert!(!flags.contains(PageTableFlags::WRITABLE));
+        assert!(flags.contains(PageTableFlags::NO_EXECUTE));
+        let (frame, flags) = image.expect("image leaf");
+        assert_eq!(frame, pages.image);
+        assert!(!flags.contains(PageTableFlags::WRITABLE));
+        assert!(!flags.contains(PageTableFlags::NO_EXECUTE));
+
+        let vma = space.vmas().find(USER_VDSO_BASE).expect("image VMA");
+        assert_eq!(vma.prot, VmProt::READ.union(VmProt::EXEC));
+        let vma = space.vmas().find(USER_VVAR_BASE).expect("vvar VMA");
+        assert_eq!(vma.prot, VmProt::READ);
+        drop(space);
+
+        let refcount =
+            crate::mm::memory::with_memory_mapper(|mapper| mapper.frame_refcount(pages.image))
+                .flatten();
+        assert_eq!(refcount, Some(1), "teardown drops only the leaf reference");
+    }
+
+    pub fn get_tests() -> &'static [&'static dyn crate::lib::test_utils::Testable] {
+        &[
+            &test_image_exports_time_functions,
+            &test_vvar_matches_kernel_clock,
+            &test_every_address_space_maps_the_same_pages,
+        ]
+    }
+}
+
+#[cfg(feature = "test")]
+pub use tests_internal::get_tests as vdso_tests;
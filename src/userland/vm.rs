@@ -7,6 +7,7 @@ use core::sync::atomic::{AtomicU64, Ordering};
 use crate::fs::File;
 use crate::lib::arc::Arc;
 use crate::mm::paging::{is_kernel_reserved_slot, USER_CANONICAL_END, USER_LOAD_BASE};
+use crate::userland::shared_memory::SharedObject;
 
 pub const PAGE_SIZE: u64 = 0x1000;
 static NEXT_ANONYMOUS_MAPPING_ID: AtomicU64 = AtomicU64::new(1);
@@ -71,6 +72,12 @@ pub enum VmaBacking {
         file_offset: u64,
         file_size: u64,
     },
+    /// `MAP_SHARED` memory. `offset` is the object byte offset mapped at
+    /// the VMA start; for a file object it is the file offset.
+    Shared {
+        object: Arc<SharedObject>,
+        offset: u64,
+    },
 }
 
 impl fmt::Debug for VmaBacking {
@@ -105,6 +112,11 @@ impl fmt::Debug for VmaBacking {
                 .field("file_offset", file_offset)
                 .field("file_size", file_size)
                 .finish(),
+            Self::Shared { object, offset } => f
+                .debug_struct("Shared")
+                .field("file", &object.file().is_some())
+                .field("offset", offset)
+                .finish(),
         }
     }
 }
@@ -117,6 +129,10 @@ pub struct Vma {
     pub private: bool,
     pub grow_down: bool,
     pub backing: VmaBacking,
+    /// Whether `mprotect` may add write access (Linux's `VM_MAYWRITE`).
+    /// False for a shared file mapping of a descriptor not opened for
+    /// writing.
+    pub may_write: bool,
     /// Stable identity of one mmap-created anonymous allocation. Splits keep
     /// the value, while independently-created adjacent mappings remain
     /// distinguishable for mremap.
@@ -135,17 +151,33 @@ impl Vma {
             start,
             end,
             prot,
-            private: true,
+            private: !matches!(backing, VmaBacking::Shared { .. }),
             grow_down: matches!(backing, VmaBacking::Stack { .. }),
             backing,
+            may_write: true,
             mapping_id,
         })
     }
 
+    /// For a `MAP_SHARED` VMA, its object and the object offset mapped at
+    /// `address`.
+    pub fn shared_at(&self, address: u64) -> Option<(&Arc<SharedObject>, u64)> {
+        match &self.backing {
+            VmaBacking::Shared { object, offset } => {
+                Some((object, offset + (address - self.start)))
+            }
+            _ => None,
+        }
+    }
+
     fn split_right(&self, start: u64) -> Self {
         let mut right = self.clone();
-        if let VmaBacking::FilePrivate { file_offset, .. } | VmaBacking::Elf { file_offset, .. } =
-            &mut right.backing
+        if let VmaBacking::FilePrivate { file_offset, .. }
+        | VmaBacking::Elf { file_offset, .. }
+        | VmaBacking::Shared {
+            offset: file_offset,
+            ..
+        } = &mut right.backing
         {
             *file_offset += start - self.start;
         }
@@ -263,6 +295,15 @@ impl VmaSet {
         self.entries.get(index).is_none_or(|vma| vma.start >= end)
     }
 
+    /// Bytes of `[start, end)` that some VMA covers.
+    pub fn mapped_bytes(&self, start: u64, end: u64) -> u64 {
+        self.entries
+            .iter()
+            .filter(|vma| vma.start < end && vma.end > start)
+            .map(|vma| vma.end.min(end) - vma.start.max(start))
+            .sum()
+    }
+
     pub fn insert(&mut self, vma: Vma) -> Result<(), VmError> {
         validate_range(vma.start, vma.end)?;
         let index = self.entries.partition_point(|old| old.start < vma.start);
@@ -419,6 +460,7 @@ fn mergeable(left: &Vma, right: &Vma) -> bool {
         || left.prot != right.prot
         || left.private != right.private
         || left.grow_down != right.grow_down
+        || left.may_write != right.may_write
     {
         return false;
     }
@@ -442,6 +484,19 @@ fn mergeable(left: &Vma, right: &Vma) -> bool {
             Arc::ptr_eq(left_file, right_file)
                 && *right_offset == *left_offset + (left.end - left.start)
         }
+        (
+            VmaBacking::Shared {
+                object: left_object,
+                offset: left_offset,
+            },
+            VmaBacking::Shared {
+                object: right_object,
+                offset: right_offset,
+            },
+        ) => {
+            Arc::ptr_eq(left_object, right_object)
+                && *right_offset == *left_offset + (left.end - left.start)
+        }
         _ => false,
     }
 }
@@ -135,6 +135,7 @@ export REPO_ROOT HOST_SHARE_STAGE
 # into its managed runtime /etc after mounting the host share.
 stage_zsh_config || exit 1
 stage_ca_certificates || exit 1
+stage_zoneinfo || exit 1
 # Test fixtures remain mandatory even with --skip-userland; optional apps and
 # prebuilt-managed interactive programs retain soft-fail staging semantics.
 stage_userland test "$SKIP_USERLAND" || {
@@ -21,22 +21,26 @@ const ACCENT: u32 = 0x3478E5;
 const ACCENT_SOFT: u32 = 0xE6F0FF;
 const SUCCESS: u32 = 0x218739;
 const WARNING: u32 = 0xA76500;
+const ZONE_ROW_HEIGHT: i32 = 28;
+const CLOCK_REALTIME: i32 = 0;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Page {
     Home,
     Appearance,
     Desktop,
+    DateTime,
     System,
     Network,
     About,
 }
 
 impl Page {
-    const ALL: [Page; 6] = [
+    const ALL: [Page; 7] = [
         Page::Home,
         Page::Appearance,
         Page::Desktop,
+        Page::DateTime,
         Page::System,
         Page::Network,
         Page::About,
@@ -47,6 +51,7 @@ impl Page {
             Self::Home => "Home",
             Self::Appearance => "Appearance",
             Self::Desktop => "Desktop",
+            Self::DateTime => "Date & time",
             Self::System => "System",
             Self::Network => "Network",
             Self::About => "About",
@@ -58,6 +63,7 @@ impl Page {
             Self::Home => "Your AgenticOS at a glance",
             Self::Appearance => "Choose how AgenticOS looks",
             Self::Desktop => "Personalize the desktop background",
+            Self::DateTime => "Time zone for the clock and file times",
             Self::System => "Display, renderer, and memory information",
             Self::Network => "Interface activity and resolver configuration",
             Self::About => "About this AgenticOS development build",
@@ -86,6 +92,10 @@ struct ControlCenter {
     memory: String,
     network: String,
     resolver: String,
+    time_zone: String,
+    local_time: String,
+    zones: Vec<String>,
+    zone_scroll: usize,
     banner: String,
     banner_warning: bool,
     modal: Option<ActiveModal>,
@@ -105,6 +115,10 @@ impl ControlCenter {
             memory: String::from("Unavailable"),
             network: String::from("Unavailable"),
             resolver: String::from("Unavailable"),
+            time_zone: String::from("UTC"),
+            local_time: String::from("Unavailable"),
+            zones: Vec::new(),
+            zone_scroll: 0,
             banner: String::new(),
             banner_warning: false,
             modal: None,
@@ -135,6 +149,18 @@ impl ControlCenter {
         self.memory = memory_summary();
         self.network = network_summary();
         self.resolver = resolver_summary();
+        let mut zone = [0u8; 64];
+        if let Ok(count) = runtime::system_control_time_zone(&mut zone) {
+            if let Ok(value) = core::str::from_utf8(&zone[..count]) {
+                self.time_zone = value.to_string();
+            }
+        }
+        self.local_time = local_time_summary();
+        self.zones = read_text(b"/etc/zoneinfo/zone.list\0")
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
     }
 
     fn filtered_pages(&self) -> Vec<Page> {
@@ -210,6 +236,7 @@ impl ControlCenter {
             Page::Home => self.render_home(),
             Page::Appearance => self.render_appearance(),
             Page::Desktop => self.render_desktop(),
+            Page::DateTime => self.render_date_time(),
             Page::System => self.render_system(),
             Page::Network => self.render_network(),
             Page::About => self.render_about(),
@@ -414,6 +441,84 @@ impl ControlCenter {
         }
     }
 
+    /// The zone list's origin, width and number of visible rows.
+    fn zone_list_rect(&self) -> (i32, i32, u32, usize) {
+        let (x, y, w) = self.content_rect();
+        let list_y = y + 104 + 48;
+        let bottom = self.window.canvas().height() as i32 - 72;
+        let rows = ((bottom - list_y) / ZONE_ROW_HEIGHT).max(1) as usize;
+        (x, list_y, w, rows)
+    }
+
+    fn render_date_time(&mut self) {
+        let (x, y, w) = self.content_rect();
+        let (_, list_y, _, rows) = self.zone_list_rect();
+        self.zone_scroll = self.zone_scroll.min(self.zones.len().saturating_sub(rows));
+        let canvas = self.window.canvas_mut();
+        card(canvas, x, y, w, 88);
+        canvas.draw_text(x + 18, y + 16, "Local time", TEXT);
+        canvas.draw_text(x + 18, y + 42, &self.local_time, TEXT);
+        canvas.draw_text(
+            x + (w as i32 / 2),
+            y + 42,
+            &format!("Time zone: {}", self.time_zone),
+            MUTED,
+        );
+
+        let list_h = (rows as i32 * ZONE_ROW_HEIGHT + 56) as u32;
+        card(canvas, x, y + 104, w, list_h);
+        canvas.draw_text(x + 18, y + 120, "Time zone", TEXT);
+        if self.zones.is_empty() {
+            canvas.draw_text(
+                x + 18,
+                list_y,
+                "No zoneinfo installed; times are UTC.",
+                WARNING,
+            );
+            return;
+        }
+        if self.zones.len() > rows {
+            canvas.draw_text(
+                x + (w as i32 / 2),
+                y + 120,
+                &format!(
+                    "{}-{} of {}; scroll for more",
+                    self.zone_scroll + 1,
+                    (self.zone_scroll + rows).min(self.zones.len()),
+                    self.zones.len()
+                ),
+                MUTED,
+            );
+        }
+        for (row, zone) in self
+            .zones
+            .iter()
+            .skip(self.zone_scroll)
+            .take(rows)
+            .enumerate()
+        {
+            let ry = list_y + row as i32 * ZONE_ROW_HEIGHT;
+            let selected = *zone == self.time_zone;
+            if selected {
+                rounded_fill(
+                    canvas,
+                    x + 10,
+                    ry,
+                    w.saturating_sub(20),
+                    ZONE_ROW_HEIGHT as u32 - 4,
+                    8,
+                    ACCENT_SOFT,
+                );
+            }
+            canvas.draw_text(
+                x + 18,
+                ry + 5,
+                &zone.replace('_', " "),
+                if selected { ACCENT } else { TEXT },
+            );
+        }
+    }
+
     fn render_system(&mut self) {
         let (x, y, w) = self.content_rect();
         let rows = [
@@ -484,6 +589,15 @@ impl ControlCenter {
         self.search_focused = false;
         self.banner.clear();
         self.refresh();
+        if page == Page::DateTime {
+            // Open with the configured zone near the top of the list.
+            let index = self
+                .zones
+                .iter()
+                .position(|zone| *zone == self.time_zone)
+                .unwrap_or(0);
+            self.zone_scroll = index.saturating_sub(2);
+        }
     }
 
     fn apply_theme(&mut self, theme: u32) {
@@ -509,6 +623,21 @@ impl ControlCenter {
         self.refresh();
     }
 
+    fn apply_time_zone(&mut self, zone: String) {
+        match runtime::system_control_set_time_zone(&zone) {
+            Ok(result) => {
+                self.banner = if result == ApplyResult::Persisted {
+                    format!("Time zone set to {zone}")
+                } else {
+                    format!("Time zone set to {zone} for this session")
+                };
+                self.banner_warning = result == ApplyResult::SessionOnly;
+                self.refresh();
+            }
+            Err(_) => self.show_error("Could not change the time zone."),
+        }
+    }
+
     fn choose_wallpaper(&mut self) {
         match FileDialog::open("/host") {
             Ok(dialog) => {
@@ -618,6 +747,16 @@ impl ControlCenter {
                     .cursor_icon_at(x, y)
                     .unwrap_or(gui::CursorIcon::Arrow);
                 let _ = self.window.set_cursor(cursor);
+                if event.payload[3] == runtime::GUI_MOUSE_SCROLL && self.page == Page::DateTime {
+                    let delta = event.payload[5] as i32;
+                    self.zone_scroll = if delta < 0 {
+                        self.zone_scroll.saturating_sub(3)
+                    } else {
+                        self.zone_scroll + 3
+                    };
+                    self.render();
+                    return false;
+                }
                 if event.payload[3] != runtime::GUI_MOUSE_DOWN {
                     return false;
                 }
@@ -721,6 +860,17 @@ impl ControlCenter {
                     self.reset_wallpaper();
                 }
             }
+            Page::DateTime => {
+                let (lx, ly, lw, rows) = self.zone_list_rect();
+                if point_in(x, y, lx, ly, lw, (rows as i32 * ZONE_ROW_HEIGHT) as u32) {
+                    let index = self.zone_scroll + ((y - ly) / ZONE_ROW_HEIGHT) as usize;
+                    if let Some(zone) = self.zones.get(index).cloned() {
+                        if zone != self.time_zone {
+                            self.apply_time_zone(zone);
+                        }
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -801,6 +951,11 @@ fn draw_page_icon(canvas: &mut Canvas, x: i32, y: i32, page: Page, color: u32) {
             canvas.rect(x, y + 2, 15, 11, color);
             canvas.horizontal_line(x + 4, y + 16, 8, color);
         }
+        Page::DateTime => {
+            canvas.rect(x + 1, y + 2, 14, 14, color);
+            canvas.vertical_line(x + 8, y + 5, 5, color);
+            canvas.horizontal_line(x + 8, y + 9, 4, color);
+        }
         Page::System => {
             canvas.rect(x + 2, y + 2, 11, 14, color);
             canvas.horizontal_line(x + 5, y + 6, 5, color);
@@ -964,6 +1119,31 @@ fn uptime_summary() -> String {
     }
 }
 
+fn local_time_summary() -> String {
+    let mut now = runtime::Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if runtime::clock_gettime(CLOCK_REALTIME, &mut now) < 0 {
+        return String::from("Unavailable");
+    }
+    gui::localtime::with_local_time(now.tv_sec, |time| {
+        let offset = time.offset.unsigned_abs() / 60;
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02} {} (UTC{}{:02}:{:02})",
+            time.year,
+            time.month,
+            time.day,
+            time.hour,
+            time.minute,
+            time.abbreviation,
+            if time.offset < 0 { '-' } else { '+' },
+            offset / 60,
+            offset % 60
+        )
+    })
+}
+
 fn keyed_kb(text: &str, key: &str) -> Option<u64> {
     text.lines()
         .find(|line| line.starts_with(key))
@@ -574,6 +574,10 @@ impl Shell {
 
     fn handle_event(&mut self, event: gui::GuiEvent) {
         if event.kind == GUI_EVENT_THEME_CHANGED || event.kind == gui::GUI_EVENT_SETTINGS_CHANGED {
+            // The time zone may have changed; re-render the clock now rather
+            // than at the next minute.
+            self.last_minute = -1;
+            self.update_clock();
             self.dirty = true;
             if let Some(menu) = self.menu.as_mut() {
                 menu.redraw();
@@ -975,32 +979,14 @@ fn clip_label(label: &str, max_chars: usize) -> String {
     out
 }
 
-/// Format `epoch_secs` (UTC) as `HH:MM UTC  YYYY-MM-DD`.
+/// Format `epoch_secs` in the system zone as `HH:MM ZONE  YYYY-MM-DD`.
 fn format_clock(epoch_secs: i64) -> String {
-    let secs = epoch_secs.rem_euclid(86_400);
-    let days = epoch_secs.div_euclid(86_400);
-    let hours = secs / 3600;
-    let minutes = (secs % 3600) / 60;
-    let (year, month, day) = civil_from_days(days);
-    format!(
-        "{:02}:{:02} UTC  {:04}-{:02}-{:02}",
-        hours, minutes, year, month, day
-    )
-}
-
-/// Convert days-since-Unix-epoch to a proleptic-Gregorian `(year, month, day)`.
-/// Howard Hinnant's `civil_from_days` algorithm.
-fn civil_from_days(days: i64) -> (i64, u32, u32) {
-    let z = days + 719_468;
-    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
-    let doe = z - era * 146_097;
-    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
-    let year = yoe + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
-    let mp = (5 * doy + 2) / 153;
-    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
-    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
-    (if month <= 2 { year + 1 } else { year }, month, day)
+    gui::localtime::with_local_time(epoch_secs, |time| {
+        format!(
+            "{:02}:{:02} {}  {:04}-{:02}-{:02}",
+            time.hour, time.minute, time.abbreviation, time.year, time.month, time.day
+        )
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -1,7 +1,8 @@
 //! Task Manager — standalone ring-3 GUI application.
 //!
 //! Three tabs over a 1 Hz /proc sampler: Processes (sortable list,
-//! End Task via `kill(2)`), Performance (CPU/memory history graphs +
+//! End Task via `kill(2)`, priority classes via `setpriority(2)`),
+//! Performance (CPU/memory history graphs +
 //! stat tiles), and Network (RX/TX throughput + socket table). The
 //! loop drains GUI events with `GUI_NONBLOCK`, samples once per
 //! second, repaints only when dirty, and `nanosleep`s 100 ms between
@@ -42,14 +43,27 @@ const TAB_NETWORK: usize = 2;
 /// Key-space tag for kernel-thread rows (never collides with PIDs).
 const KTHREAD_KEY: u64 = 1 << 32;
 
+/// Priority classes the Raise/Lower buttons step through, as nice values.
+/// A process between two classes shows as the lower-priority one.
+const PRIORITY_CLASSES: [(i8, &str); 5] = [
+    (-10, "High"),
+    (-5, "Above normal"),
+    (0, "Normal"),
+    (5, "Below normal"),
+    (19, "Low"),
+];
+
 enum ModalPurpose {
     ConfirmEnd(u32),
     ConfirmForce(u32),
+    Notice,
 }
 
 struct CpuHistory {
     id: u32,
     pct10: u64,
+    /// Entities waiting on this CPU's run queue.
+    queued: u64,
     graph: TimeSeriesGraph,
 }
 
@@ -58,6 +72,8 @@ struct TaskMgr {
     tabs: TabBar,
     proc_list: ColumnListView,
     end_task: Button,
+    raise_priority: Button,
+    lower_priority: Button,
     cpu_graphs: Vec<CpuHistory>,
     mem_graph: TimeSeriesGraph,
     net_graph: TimeSeriesGraph,
@@ -128,6 +144,29 @@ fn fmt_rate(bps: u64) -> String {
     }
 }
 
+fn priority_label(nice: i8) -> &'static str {
+    PRIORITY_CLASSES
+        .iter()
+        .find(|(class, _)| nice <= *class)
+        .map_or("Low", |(_, label)| label)
+}
+
+/// The class one step from `nice`: towards higher priority when `raise`.
+fn step_priority(nice: i8, raise: bool) -> Option<i8> {
+    if raise {
+        PRIORITY_CLASSES
+            .iter()
+            .rev()
+            .map(|(class, _)| *class)
+            .find(|class| *class < nice)
+    } else {
+        PRIORITY_CLASSES
+            .iter()
+            .map(|(class, _)| *class)
+            .find(|class| *class > nice)
+    }
+}
+
 fn state_label(state: char) -> &'static str {
     match state {
         'R' => "Running",
@@ -142,8 +181,9 @@ impl TaskMgr {
         let tabs = TabBar::new(0, 0, 640, &["Processes", "Performance", "Network"]);
         let proc_columns = alloc::vec![
             Column::numeric("PID", 56),
-            Column::new("Name", 152),
-            Column::new("State", 88),
+            Column::new("Name", 136),
+            Column::new("State", 72),
+            Column::new("Priority", 96),
             Column::numeric("CPU %", 64),
             Column::numeric("Time", 72),
             Column::numeric("Mem", 88),
@@ -160,6 +200,8 @@ impl TaskMgr {
             tabs,
             proc_list: ColumnListView::new(0, 0, 10, 10, proc_columns),
             end_task: Button::new("End Task", 0, 0, 96, 24),
+            raise_priority: Button::new("Raise Priority", 0, 0, 120, 24),
+            lower_priority: Button::new("Lower Priority", 0, 0, 120, 24),
             cpu_graphs: Vec::new(),
             mem_graph: TimeSeriesGraph::new(0, 0, 10, 10, 120, None),
             net_graph: TimeSeriesGraph::new(0, 0, 10, 10, 120, None),
@@ -177,7 +219,7 @@ impl TaskMgr {
             exit: false,
         };
         // Default: busiest processes first.
-        app.proc_list.sort_col = 3;
+        app.proc_list.sort_col = 4;
         app.proc_list.sort_desc = true;
         // Memory graph pins to MemTotal once known (first sample).
         app.layout();
@@ -202,6 +244,10 @@ impl TaskMgr {
         self.proc_list.h = content_h.saturating_sub(16 + action_h);
         self.end_task.x = w as i32 - 8 - self.end_task.w as i32;
         self.end_task.y = content_y + content_h as i32 - action_h as i32 + 4;
+        self.lower_priority.x = self.end_task.x - 8 - self.lower_priority.w as i32;
+        self.lower_priority.y = self.end_task.y;
+        self.raise_priority.x = self.lower_priority.x - 8 - self.raise_priority.w as i32;
+        self.raise_priority.y = self.end_task.y;
 
         // Performance tab: a maximum-two-row CPU grid, memory, then tiles.
         let tiles_h = 76;
@@ -271,15 +317,17 @@ impl TaskMgr {
             &snap.cpu_total,
             self.prev.as_ref().map(|prev| &prev.cpu_total),
         );
-        let cpu_rates: Vec<(u32, u64)> = snap
+        let cpu_rates: Vec<(u32, u64, u64)> = snap
             .cpus
             .iter()
-            .map(|cpu| {
+            .enumerate()
+            .map(|(row, cpu)| {
                 let before = self
                     .prev
                     .as_ref()
                     .and_then(|prev| prev.cpus.iter().find(|old| old.id == cpu.id));
-                (cpu.id, cpu_delta_pct10(cpu, before))
+                let queued = snap.run_queues.get(row).copied().unwrap_or(0);
+                (cpu.id, cpu_delta_pct10(cpu, before), queued)
             })
             .collect();
 
@@ -326,6 +374,11 @@ impl TaskMgr {
                     format!("{}", p.pid),
                     p.comm.clone(),
                     String::from(state_label(p.state)),
+                    String::from(if p.realtime {
+                        "Realtime"
+                    } else {
+                        priority_label(p.nice)
+                    }),
                     fmt_pct10(row_pct10(&prev_proc, p.pid, p.utime_ticks)),
                     fmt_mmss(p.utime_ticks),
                     fmt_kb(p.rss_pages * 4),
@@ -345,6 +398,7 @@ impl TaskMgr {
                         }
                         s
                     },
+                    String::new(),
                     fmt_pct10(row_pct10(&prev_kthread, k.tid, k.runtime_ticks)),
                     fmt_mmss(k.runtime_ticks),
                     fmt_kb(k.stack_bytes / 1024),
@@ -357,14 +411,20 @@ impl TaskMgr {
 
         // Graphs. Histories are keyed by logical CPU ID so layout rebuilds or
         // a malformed sample do not wipe unaffected processors.
-        for (id, pct10) in cpu_rates {
+        for (id, pct10, queued) in cpu_rates {
             if let Some(history) = self.cpu_graphs.iter_mut().find(|cpu| cpu.id == id) {
                 history.pct10 = pct10;
+                history.queued = queued;
                 history.graph.push(pct10 as f32 / 10.0, None);
             } else {
                 let mut graph = TimeSeriesGraph::new(0, 0, 10, 10, 120, Some(100.0));
                 graph.push(pct10 as f32 / 10.0, None);
-                self.cpu_graphs.push(CpuHistory { id, pct10, graph });
+                self.cpu_graphs.push(CpuHistory {
+                    id,
+                    pct10,
+                    queued,
+                    graph,
+                });
             }
         }
         self.cpu_graphs.sort_by_key(|cpu| cpu.id);
@@ -450,6 +510,46 @@ impl TaskMgr {
         }
     }
 
+    /// Move the selected process one priority class up or down. Raising
+    /// takes root, so the kernel's refusal is shown rather than hidden.
+    fn change_priority(&mut self, raise: bool) {
+        if self.modal.is_some() {
+            return;
+        }
+        let Some((pid, name)) = self.selected_target() else {
+            return;
+        };
+        let Some(nice) = self
+            .snap
+            .procs
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.nice)
+        else {
+            return;
+        };
+        let Some(target) = step_priority(nice, raise) else {
+            return;
+        };
+        let result = runtime::setpriority(runtime::PRIO_PROCESS, pid, i32::from(target));
+        if result < 0 {
+            let reason = match result {
+                -1 | -13 => "permission denied",
+                -3 => "no such process",
+                _ => "error",
+            };
+            let text =
+                format!("Cannot change the priority of {name} (PID {pid}): {reason} ({result}).");
+            if let Ok(msgbox) = MessageBox::error(&text) {
+                self.modal = Some((msgbox, ModalPurpose::Notice));
+            }
+        } else {
+            // Resample now so the Priority column reflects the change.
+            self.iters = ITERS_PER_SAMPLE;
+        }
+        self.dirty = true;
+    }
+
     fn on_modal_done(&mut self, purpose: ModalPurpose, choice: Option<MessageChoice>) {
         let confirmed = matches!(choice, Some(MessageChoice::Yes));
         match purpose {
@@ -478,6 +578,9 @@ impl TaskMgr {
             runtime::KEY_DELETE if self.tabs.active == TAB_PROCESSES => {
                 self.request_end_task();
             }
+            _ if self.tabs.active == TAB_PROCESSES && matches!(input.character, '+' | '-') => {
+                self.change_priority(input.character == '+');
+            }
             _ => {
                 let list = match self.tabs.active {
                     TAB_PROCESSES => &mut self.proc_list,
@@ -521,6 +624,23 @@ impl TaskMgr {
                 self.dirty |= response.repaint;
                 return;
             }
+            for raise in [true, false] {
+                let button = if raise {
+                    &mut self.raise_priority
+                } else {
+                    &mut self.lower_priority
+                };
+                button.set_enabled(enabled);
+                let response = button.handle_pointer(input);
+                if response.action == Some(gui::ButtonAction::Activated) {
+                    self.change_priority(raise);
+                    return;
+                }
+                if response.consumed {
+                    self.dirty |= response.repaint;
+                    return;
+                }
+            }
         }
         let response = match self.tabs.active {
             TAB_PROCESSES => self.proc_list.handle_pointer(input),
@@ -610,11 +730,15 @@ impl TaskMgr {
                     .unwrap_or(false);
                 self.end_task.set_enabled(enabled);
                 self.end_task.draw_control(canvas, false);
+                for button in [&mut self.raise_priority, &mut self.lower_priority] {
+                    button.set_enabled(enabled);
+                    button.draw_control(canvas, false);
+                }
                 if !enabled {
                     canvas.draw_text(
                         self.proc_list.x,
                         self.end_task.y + 8,
-                        "Select a process to end it",
+                        "Select a process",
                         palette.disabled_text,
                     );
                 }
@@ -622,7 +746,7 @@ impl TaskMgr {
             TAB_PERFORMANCE => {
                 for cpu in &self.cpu_graphs {
                     let title = format!("CPU {}", cpu.id);
-                    let label = format!("{}%", fmt_pct10(cpu.pct10));
+                    let label = format!("{}%, {} queued", fmt_pct10(cpu.pct10), cpu.queued);
                     cpu.graph.draw(canvas, &title, &label);
                 }
                 let used_mb = self.snap.mem_total_kb.saturating_sub(self.snap.mem_free_kb) / 1024;
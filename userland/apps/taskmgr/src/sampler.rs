@@ -12,6 +12,9 @@ pub struct ProcRow {
     pub pid: u32,
     pub comm: String,
     pub state: char,
+    pub nice: i8,
+    /// `SCHED_FIFO` or `SCHED_RR`.
+    pub realtime: bool,
     pub utime_ticks: u64,
     pub rss_pages: u64,
 }
@@ -49,6 +52,8 @@ pub struct Snapshot {
     pub uptime_ticks: u64,
     pub cpu_total: CpuTimes,
     pub cpus: Vec<CpuTimes>,
+    /// Entities waiting on each CPU's run queue, in `cpus` order.
+    pub run_queues: Vec<u64>,
     pub mem_total_kb: u64,
     pub mem_free_kb: u64,
     pub heap_total_kb: u64,
@@ -111,7 +116,8 @@ fn parse_uptime(text: &str) -> u64 {
 }
 
 fn parse_pid_stat(text: &str) -> Option<ProcRow> {
-    // "<pid> (<comm>) <state> <ppid> ... utime=field14 ... vsize=23 rss=24"
+    // "<pid> (<comm>) <state> <ppid> ... utime=field14 ... nice=19 ...
+    // vsize=23 rss=24 ... policy=41"
     let open = text.find('(')?;
     let close = text.rfind(')')?;
     let pid = parse_u64(&text[..open]) as u32;
@@ -122,6 +128,8 @@ fn parse_pid_stat(text: &str) -> Option<ProcRow> {
         pid,
         comm,
         state,
+        nice: rest.get(16).and_then(|s| s.parse().ok()).unwrap_or(0),
+        realtime: rest.get(38).is_some_and(|s| parse_u64(s) != 0),
         utime_ticks: rest.get(11).map(|s| parse_u64(s)).unwrap_or(0),
         rss_pages: rest.get(21).map(|s| parse_u64(s)).unwrap_or(0),
     })
@@ -191,6 +199,10 @@ fn parse_cpu_stat(snap: &mut Snapshot, text: &str) {
         let Some(label) = fields.next() else {
             continue;
         };
+        if label == "runqueue" {
+            snap.run_queues = fields.map(parse_u64).collect();
+            continue;
+        }
         if label != "cpu"
             && !label
                 .strip_prefix("cpu")
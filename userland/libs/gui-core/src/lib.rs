@@ -7,6 +7,7 @@ pub mod focus;
 pub mod input;
 pub mod scroll;
 pub mod text_edit;
+pub mod tz;
 
 pub use geometry::Rect;
 pub use focus::{FocusManager, WidgetId};
@@ -18,3 +19,4 @@ pub use scroll::{
     layout_scrollbars, Axis, ScrollState, ScrollbarGeometry, ScrollbarPolicy, ScrollbarsLayout,
 };
 pub use text_edit::TextEdit;
+pub use tz::{LocalTime, TimeZone};
@@ -0,0 +1,664 @@
+//! TZif zone files (RFC 8536) and UTC to local time conversion.
+//!
+//! Times inside the file's transition table use it directly; later times
+//! follow the POSIX TZ rule in the version 2+ footer, so a zone stays right
+//! past the last transition `zic` wrote out.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const HEADER_LEN: usize = 44;
+/// Rule transitions happen at 02:00 local time unless the rule says otherwise.
+const DEFAULT_RULE_TIME: i32 = 2 * 3600;
+
+/// A calendar time in some zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime<'a> {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Seconds east of UTC.
+    pub offset: i32,
+    pub is_dst: bool,
+    /// Zone abbreviation such as `BST` or `-03`.
+    pub abbreviation: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ZoneType {
+    offset: i32,
+    is_dst: bool,
+    abbreviation: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDate {
+    /// `Jn`: day 1..=365, never counting February 29.
+    Julian(u16),
+    /// `n`: zero-based day of the year, counting February 29.
+    DayOfYear(u16),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (5 = last) of month `m`.
+    MonthWeekDay { month: u8, week: u8, weekday: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RuleTransition {
+    date: RuleDate,
+    /// Local wall-clock seconds after midnight; may be negative or past 24h.
+    time: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DstRule {
+    zone: ZoneType,
+    start: RuleTransition,
+    end: RuleTransition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PosixRule {
+    std: ZoneType,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    /// UTC seconds of each transition, ascending.
+    transitions: Vec<i64>,
+    /// Index into `types` in effect from the matching transition on.
+    transition_types: Vec<u8>,
+    types: Vec<ZoneType>,
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    pub fn utc() -> Self {
+        Self {
+            transitions: Vec::new(),
+            transition_types: Vec::new(),
+            types: alloc::vec![ZoneType {
+                offset: 0,
+                is_dst: false,
+                abbreviation: String::from("UTC"),
+            }],
+            rule: None,
+        }
+    }
+
+    /// Parse a TZif file such as `/etc/localtime`. Leap-second records are
+    /// skipped, not applied.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let header = Header::parse(data)?;
+        if header.version == 0 {
+            return Self::parse_block(data.get(HEADER_LEN..)?, &header, 4).map(|(zone, _)| zone);
+        }
+        // Version 2+ repeats the data with 64-bit times after the v1 block.
+        let rest = data.get(HEADER_LEN + header.block_len(4)..)?;
+        let header = Header::parse(rest)?;
+        let (mut zone, footer) = Self::parse_block(rest.get(HEADER_LEN..)?, &header, 8)?;
+        let footer = footer.strip_prefix(b"\n")?;
+        let end = footer.iter().position(|&byte| byte == b'\n')?;
+        if end > 0 {
+            zone.rule = core::str::from_utf8(&footer[..end])
+                .ok()
+                .and_then(PosixRule::parse);
+        }
+        Some(zone)
+    }
+
+    fn parse_block<'a>(
+        data: &'a [u8],
+        header: &Header,
+        time_len: usize,
+    ) -> Option<(Self, &'a [u8])> {
+        let mut reader = Reader { data };
+        let mut transitions = Vec::with_capacity(header.time_count);
+        for _ in 0..header.time_count {
+            transitions.push(reader.time(time_len)?);
+        }
+        let transition_types = reader.take(header.time_count)?.to_vec();
+        let mut raw_types = Vec::with_capacity(header.type_count);
+        for _ in 0..header.type_count {
+            let offset = reader.time(4)? as i32;
+            let is_dst = reader.take(1)?[0] != 0;
+            let abbreviation_index = usize::from(reader.take(1)?[0]);
+            raw_types.push((offset, is_dst, abbreviation_index));
+        }
+        let abbreviations = reader.take(header.char_count)?;
+        reader.take(header.leap_count * (time_len + 4) + header.std_count + header.ut_count)?;
+
+        let mut types = Vec::with_capacity(raw_types.len());
+        for (offset, is_dst, index) in raw_types {
+            let tail = abbreviations.get(index..)?;
+            let end = tail.iter().position(|&byte| byte == 0)?;
+            types.push(ZoneType {
+                offset,
+                is_dst,
+                abbreviation: String::from(core::str::from_utf8(&tail[..end]).ok()?),
+            });
+        }
+        if types.is_empty()
+            || transition_types
+                .iter()
+                .any(|&index| usize::from(index) >= types.len())
+            || transitions.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return None;
+        }
+        Some((
+            Self {
+                transitions,
+                transition_types,
+                types,
+                rule: None,
+            },
+            reader.data,
+        ))
+    }
+
+    /// Convert seconds since the Unix epoch to local time in this zone.
+    pub fn to_local(&self, seconds: i64) -> LocalTime<'_> {
+        let zone = self.zone_at(seconds);
+        let local = seconds.saturating_add(i64::from(zone.offset));
+        let (year, month, day) = civil_from_days(local.div_euclid(SECONDS_PER_DAY));
+        let second_of_day = local.rem_euclid(SECONDS_PER_DAY);
+        LocalTime {
+            year,
+            month,
+            day,
+            hour: (second_of_day / 3600) as u8,
+            minute: (second_of_day / 60 % 60) as u8,
+            second: (second_of_day % 60) as u8,
+            offset: zone.offset,
+            is_dst: zone.is_dst,
+            abbreviation: &zone.abbreviation,
+        }
+    }
+
+    fn zone_at(&self, seconds: i64) -> &ZoneType {
+        let after = self.transitions.partition_point(|&at| at <= seconds);
+        if after == self.transitions.len() {
+            if let Some(rule) = &self.rule {
+                return rule.zone_at(seconds);
+            }
+        }
+        match after.checked_sub(1) {
+            Some(index) => &self.types[usize::from(self.transition_types[index])],
+            None => &self.types[0],
+        }
+    }
+}
+
+struct Header {
+    version: u8,
+    ut_count: usize,
+    std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let header = data.get(..HEADER_LEN)?;
+        if &header[..4] != b"TZif" {
+            return None;
+        }
+        let version = match header[4] {
+            0 => 0,
+            byte @ b'2'..=b'9' => byte - b'0',
+            _ => return None,
+        };
+        let count = |index: usize| {
+            let start = 20 + index * 4;
+            u32::from_be_bytes([
+                header[start],
+                header[start + 1],
+                header[start + 2],
+                header[start + 3],
+            ]) as usize
+        };
+        Some(Self {
+            version,
+            ut_count: count(0),
+            std_count: count(1),
+            leap_count: count(2),
+            time_count: count(3),
+            type_count: count(4),
+            char_count: count(5),
+        })
+    }
+
+    fn block_len(&self, time_len: usize) -> usize {
+        self.time_count * (time_len + 1)
+            + self.type_count * 6
+            + self.char_count
+            + self.leap_count * (time_len + 4)
+            + self.std_count
+            + self.ut_count
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Some(head)
+    }
+
+    /// A big-endian signed 32- or 64-bit value.
+    fn time(&mut self, len: usize) -> Option<i64> {
+        let bytes = self.take(len)?;
+        Some(if len == 4 {
+            i64::from(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        } else {
+            let mut value = [0u8; 8];
+            value.copy_from_slice(bytes);
+            i64::from_be_bytes(value)
+        })
+    }
+}
+
+impl PosixRule {
+    /// Parse a POSIX TZ string such as `GMT0BST,M3.5.0/1,M10.5.0`.
+    fn parse(text: &str) -> Option<Self> {
+        let mut cursor = Cursor {
+            text: text.as_bytes(),
+        };
+        let std_name = cursor.name()?;
+        let std_offset = -cursor.offset()?;
+        let std = ZoneType {
+            offset: std_offset,
+            is_dst: false,
+            abbreviation: String::from(std_name),
+        };
+        if cursor.text.is_empty() {
+            return Some(Self { std, dst: None });
+        }
+        let dst_name = cursor.name()?;
+        let dst_offset = if cursor.peek() == Some(b',') {
+            std_offset + 3600
+        } else {
+            -cursor.offset()?
+        };
+        cursor.expect(b',')?;
+        let start = cursor.transition()?;
+        cursor.expect(b',')?;
+        let end = cursor.transition()?;
+        if !cursor.text.is_empty() {
+            return None;
+        }
+        Some(Self {
+            std,
+            dst: Some(DstRule {
+                zone: ZoneType {
+                    offset: dst_offset,
+                    is_dst: true,
+                    abbreviation: String::from(dst_name),
+                },
+                start,
+                end,
+            }),
+        })
+    }
+
+    fn zone_at(&self, seconds: i64) -> &ZoneType {
+        let Some(dst) = &self.dst else {
+            return &self.std;
+        };
+        let std_offset = i64::from(self.std.offset);
+        let dst_offset = i64::from(dst.zone.offset);
+        let (year, _, _) = civil_from_days(
+            seconds
+                .saturating_add(std_offset)
+                .div_euclid(SECONDS_PER_DAY),
+        );
+        // The start is given in standard time and the end in daylight time.
+        let start = dst.start.local_seconds(year) - std_offset;
+        let end = dst.end.local_seconds(year) - dst_offset;
+        let in_dst = if start < end {
+            start <= seconds && seconds < end
+        } else {
+            !(end <= seconds && seconds < start)
+        };
+        if in_dst {
+            &dst.zone
+        } else {
+            &self.std
+        }
+    }
+}
+
+impl RuleTransition {
+    /// Local seconds since the epoch at which this transition happens in
+    /// `year`.
+    fn local_seconds(self, year: i64) -> i64 {
+        let january_first = days_from_civil(year, 1, 1);
+        let day = match self.date {
+            RuleDate::Julian(day) => {
+                let day = i64::from(day) - 1;
+                january_first + day + i64::from(is_leap_year(year) && day >= 59)
+            }
+            RuleDate::DayOfYear(day) => january_first + i64::from(day),
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                // 1970-01-01 was a Thursday.
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first
+                    + (i64::from(weekday) - first_weekday).rem_euclid(7)
+                    + 7 * (i64::from(week) - 1);
+                let month_end = first + i64::from(days_in_month(year, month));
+                while day >= month_end {
+                    day -= 7;
+                }
+                day
+            }
+        };
+        day * SECONDS_PER_DAY + i64::from(self.time)
+    }
+}
+
+struct Cursor<'a> {
+    text: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.first().copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.peek()? == byte).then(|| self.text = &self.text[1..])
+    }
+
+    fn take_while(&mut self, accept: impl Fn(u8) -> bool) -> &'a [u8] {
+        let len = self
+            .text
+            .iter()
+            .position(|&byte| !accept(byte))
+            .unwrap_or(self.text.len());
+        let (head, tail) = self.text.split_at(len);
+        self.text = tail;
+        head
+    }
+
+    /// An alphabetic abbreviation, or a quoted one such as `<-03>`.
+    fn name(&mut self) -> Option<&'a str> {
+        let name = if self.peek()? == b'<' {
+            self.text = &self.text[1..];
+            let name = self.take_while(|byte| byte != b'>');
+            self.expect(b'>')?;
+            name
+        } else {
+            self.take_while(|byte| byte.is_ascii_alphabetic())
+        };
+        if name.len() < 3 {
+            return None;
+        }
+        core::str::from_utf8(name).ok()
+    }
+
+    fn number(&mut self) -> Option<i32> {
+        let digits = self.take_while(|byte| byte.is_ascii_digit());
+        if digits.is_empty() || digits.len() > 3 {
+            return None;
+        }
+        Some(
+            digits
+                .iter()
+                .fold(0, |value, &digit| value * 10 + i32::from(digit - b'0')),
+        )
+    }
+
+    /// `[+-]hh[:mm[:ss]]` as seconds.
+    fn offset(&mut self) -> Option<i32> {
+        let sign = match self.peek()? {
+            b'-' => {
+                self.text = &self.text[1..];
+                -1
+            }
+            b'+' => {
+                self.text = &self.text[1..];
+                1
+            }
+            _ => 1,
+        };
+        let mut seconds = self.number()? * 3600;
+        for scale in [60, 1] {
+            if self.peek() != Some(b':') {
+                break;
+            }
+            self.text = &self.text[1..];
+            seconds += self.number()? * scale;
+        }
+        Some(sign * seconds)
+    }
+
+    /// `date[/time]`.
+    fn transition(&mut self) -> Option<RuleTransition> {
+        let date = match self.peek()? {
+            b'J' => {
+                self.text = &self.text[1..];
+                let day = self.number()?;
+                if !(1..=365).contains(&day) {
+                    return None;
+                }
+                RuleDate::Julian(day as u16)
+            }
+            b'M' => {
+                self.text = &self.text[1..];
+                let month = self.number()?;
+                self.expect(b'.')?;
+                let week = self.number()?;
+                self.expect(b'.')?;
+                let weekday = self.number()?;
+                if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                    return None;
+                }
+                RuleDate::MonthWeekDay {
+                    month: month as u8,
+                    week: week as u8,
+                    weekday: weekday as u8,
+                }
+            }
+            _ => {
+                let day = self.number()?;
+                if day > 365 {
+                    return None;
+                }
+                RuleDate::DayOfYear(day as u16)
+            }
+        };
+        let time = if self.peek() == Some(b'/') {
+            self.text = &self.text[1..];
+            self.offset()?
+        } else {
+            DEFAULT_RULE_TIME
+        };
+        Some(RuleTransition { date, time })
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 to `(year, month, day)`, proleptic Gregorian.
+pub fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The inverse of [`civil_from_days`].
+pub fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONDON: &[u8] = include_bytes!("../../../zoneinfo/Europe/London");
+    const NEW_YORK: &[u8] = include_bytes!("../../../zoneinfo/America/New_York");
+    const SYDNEY: &[u8] = include_bytes!("../../../zoneinfo/Australia/Sydney");
+    const KOLKATA: &[u8] = include_bytes!("../../../zoneinfo/Asia/Kolkata");
+    const BUENOS_AIRES: &[u8] = include_bytes!("../../../zoneinfo/America/Argentina/Buenos_Aires");
+
+    fn at(year: i64, month: u8, day: u8, hour: i64, minute: i64) -> i64 {
+        days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60
+    }
+
+    fn clock(time: LocalTime<'_>) -> (i64, u8, u8, u8, u8, &str) {
+        (
+            time.year,
+            time.month,
+            time.day,
+            time.hour,
+            time.minute,
+            time.abbreviation,
+        )
+    }
+
+    #[test]
+    fn civil_conversion_round_trips() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        for days in [-719_468, -1, 0, 59, 10_957, 19_782, 47_482] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn utc_is_identity() {
+        let zone = TimeZone::utc();
+        assert_eq!(
+            clock(zone.to_local(at(2024, 7, 1, 12, 30))),
+            (2024, 7, 1, 12, 30, "UTC")
+        );
+    }
+
+    #[test]
+    fn transitions_switch_at_the_exact_second() {
+        let london = TimeZone::parse(LONDON).expect("London parses");
+        // BST began at 01:00 UTC on 2024-03-31.
+        let start = at(2024, 3, 31, 1, 0);
+        assert_eq!(
+            clock(london.to_local(start - 1)),
+            (2024, 3, 31, 0, 59, "GMT")
+        );
+        let summer = london.to_local(start);
+        assert_eq!(clock(summer), (2024, 3, 31, 2, 0, "BST"));
+        assert_eq!((summer.offset, summer.is_dst), (3600, true));
+
+        let new_york = TimeZone::parse(NEW_YORK).expect("New York parses");
+        // EDT ended at 06:00 UTC on 2024-11-03.
+        let end = at(2024, 11, 3, 6, 0);
+        assert_eq!(
+            clock(new_york.to_local(end - 60)),
+            (2024, 11, 3, 1, 59, "EDT")
+        );
+        assert_eq!(clock(new_york.to_local(end)), (2024, 11, 3, 1, 0, "EST"));
+    }
+
+    #[test]
+    fn footer_rule_covers_times_past_the_table() {
+        let london = TimeZone::parse(LONDON).expect("London parses");
+        assert_eq!(
+            clock(london.to_local(at(2100, 7, 1, 12, 0))),
+            (2100, 7, 1, 13, 0, "BST")
+        );
+        assert_eq!(
+            clock(london.to_local(at(2100, 12, 1, 12, 0))),
+            (2100, 12, 1, 12, 0, "GMT")
+        );
+        // Last Sunday of March 2100 is the 28th; the change is at 01:00 GMT.
+        assert_eq!(clock(london.to_local(at(2100, 3, 28, 1, 0))).5, "BST");
+        assert_eq!(clock(london.to_local(at(2100, 3, 28, 0, 59))).5, "GMT");
+
+        // Southern hemisphere: daylight time spans the new year.
+        let sydney = TimeZone::parse(SYDNEY).expect("Sydney parses");
+        let summer = sydney.to_local(at(2100, 1, 15, 0, 0));
+        assert_eq!((summer.offset, summer.abbreviation), (11 * 3600, "AEDT"));
+        let winter = sydney.to_local(at(2100, 7, 15, 0, 0));
+        assert_eq!((winter.offset, winter.abbreviation), (10 * 3600, "AEST"));
+    }
+
+    #[test]
+    fn fixed_and_quoted_zones() {
+        let kolkata = TimeZone::parse(KOLKATA).expect("Kolkata parses");
+        let time = kolkata.to_local(at(2024, 12, 31, 20, 0));
+        assert_eq!(clock(time), (2025, 1, 1, 1, 30, "IST"));
+        assert_eq!(time.offset, 19_800);
+
+        let buenos_aires = TimeZone::parse(BUENOS_AIRES).expect("Buenos Aires parses");
+        let time = buenos_aires.to_local(at(2090, 1, 1, 2, 0));
+        assert_eq!(clock(time), (2089, 12, 31, 23, 0, "-03"));
+    }
+
+    #[test]
+    fn posix_rules_parse_and_reject() {
+        let rule = PosixRule::parse("<+0330>-3:30").expect("quoted offset");
+        assert_eq!(rule.std.offset, 3 * 3600 + 1800);
+        assert!(rule.dst.is_none());
+        let rule = PosixRule::parse("EST5EDT,M3.2.0,M11.1.0").expect("US rule");
+        let dst = rule.dst.expect("daylight time");
+        assert_eq!(dst.zone.offset, -4 * 3600);
+        assert_eq!(dst.start.time, DEFAULT_RULE_TIME);
+        assert!(PosixRule::parse("EST5EDT").is_none());
+        assert!(PosixRule::parse("X5").is_none());
+        assert!(PosixRule::parse("EST5EDT,M13.1.0,M11.1.0").is_none());
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(TimeZone::parse(b"").is_none());
+        assert!(TimeZone::parse(b"TZif2 not really a zone file").is_none());
+        assert!(TimeZone::parse(&LONDON[..LONDON.len() / 2]).is_none());
+    }
+}
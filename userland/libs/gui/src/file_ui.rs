@@ -622,31 +622,15 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
-/// Format a Unix timestamp as a compact UTC date/time without libc.
+/// Format a Unix timestamp as a compact local date/time without libc.
 pub fn format_modified(seconds: i64) -> String {
     if seconds <= 0 {
         return "--".to_string();
     }
-    let days = seconds.div_euclid(86_400);
-    let day_seconds = seconds.rem_euclid(86_400);
-    // Howard Hinnant's civil-from-days transform, with day zero at the Unix
-    // epoch. It is small, allocation-free until the final formatted String,
-    // and works across Gregorian era boundaries.
-    let z = days + 719_468;
-    let era = if z >= 0 { z } else { z - 146_096 }.div_euclid(146_097);
-    let day_of_era = z - era * 146_097;
-    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
-        - day_of_era / 146_096)
-        .div_euclid(365);
-    let mut year = year_of_era + era * 400;
-    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
-    let month_prime = (5 * day_of_year + 2).div_euclid(153);
-    let day = day_of_year - (153 * month_prime + 2).div_euclid(5) + 1;
-    let month = month_prime + if month_prime < 10 { 3 } else { -9 };
-    if month <= 2 {
-        year += 1;
-    }
-    let hour = day_seconds / 3_600;
-    let minute = day_seconds % 3_600 / 60;
-    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}")
+    crate::localtime::with_local_time(seconds, |time| {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            time.year, time.month, time.day, time.hour, time.minute
+        )
+    })
 }
@@ -6,6 +6,7 @@ mod controls;
 pub mod file_ui;
 mod font;
 mod input;
+pub mod localtime;
 mod menu;
 mod scrollbar;
 mod slider;
@@ -520,6 +521,7 @@ pub fn next_event() -> Result<GuiEvent, i64> {
         Err(result)
     } else {
         theme::apply_system_event(&event);
+        localtime::apply_system_event(&event);
         Ok(event)
     }
 }
@@ -541,6 +543,7 @@ pub fn try_next_event() -> Result<Option<GuiEvent>, i64> {
         Err(result)
     } else {
         theme::apply_system_event(&event);
+        localtime::apply_system_event(&event);
         Ok(Some(event))
     }
 }
@@ -0,0 +1,53 @@
+//! Ring-3 view of the system time zone.
+//!
+//! The kernel publishes the configured zone as `/etc/localtime`; this module
+//! parses it on first use, caches it, and drops the cache when a
+//! settings-changed notification arrives so the next conversion rereads it.
+//! A missing or malformed file means UTC, which is also what musl's
+//! `localtime` falls back to, so the desktop clock, file times and C
+//! programs always agree.
+
+use alloc::vec::Vec;
+
+use gui_core::{LocalTime, TimeZone};
+use spin::Mutex;
+
+/// Zone files larger than this are not real zones.
+const MAX_ZONE_BYTES: usize = 64 * 1024;
+
+static ZONE: Mutex<Option<TimeZone>> = Mutex::new(None);
+
+/// Convert seconds since the Unix epoch to local time and hand it to `f`.
+pub fn with_local_time<R>(seconds: i64, f: impl FnOnce(&LocalTime<'_>) -> R) -> R {
+    let mut zone = ZONE.lock();
+    let zone = zone.get_or_insert_with(load);
+    f(&zone.to_local(seconds))
+}
+
+/// Forget the cached zone when the system settings change.
+pub fn apply_system_event(event: &runtime::GuiEvent) -> bool {
+    if event.kind != runtime::GUI_EVENT_SETTINGS_CHANGED {
+        return false;
+    }
+    *ZONE.lock() = None;
+    true
+}
+
+fn load() -> TimeZone {
+    let fd = runtime::openat(runtime::AT_FDCWD, b"/etc/localtime\0", runtime::O_RDONLY, 0);
+    if fd < 0 {
+        return TimeZone::utc();
+    }
+    let fd = fd as i32;
+    let mut contents = Vec::new();
+    let mut buffer = [0u8; 1024];
+    loop {
+        let count = runtime::read(fd, &mut buffer);
+        if count <= 0 || contents.len() + count as usize > MAX_ZONE_BYTES {
+            break;
+        }
+        contents.extend_from_slice(&buffer[..count as usize]);
+    }
+    let _ = runtime::close(fd);
+    TimeZone::parse(&contents).unwrap_or_else(TimeZone::utc)
+}
@@ -91,6 +91,10 @@ const NR_ACCESS: u64 = 21;
 const NR_NANOSLEEP: u64 = 35;
 const NR_GETPID: u64 = 39;
 const NR_KILL: u64 = 62;
+const NR_GETPRIORITY: u64 = 140;
+const NR_SETPRIORITY: u64 = 141;
+const NR_SCHED_SETSCHEDULER: u64 = 144;
+const NR_SCHED_GETSCHEDULER: u64 = 145;
 const NR_CLOCK_GETTIME: u64 = 228;
 const NR_FORK: u64 = 57;
 const NR_EXECVE: u64 = 59;
@@ -168,6 +172,10 @@ pub const SYSTEM_CONTROL_GET_WALLPAPER_PATH: u64 = 1;
 pub const SYSTEM_CONTROL_SET_THEME: u64 = 2;
 pub const SYSTEM_CONTROL_SET_WALLPAPER_PATH: u64 = 3;
 pub const SYSTEM_CONTROL_RESET_WALLPAPER: u64 = 4;
+pub const SYSTEM_CONTROL_GET_CORE_DIR: u64 = 5;
+pub const SYSTEM_CONTROL_SET_CORE_DIR: u64 = 6;
+pub const SYSTEM_CONTROL_GET_TIME_ZONE: u64 = 7;
+pub const SYSTEM_CONTROL_SET_TIME_ZONE: u64 = 8;
 
 pub const THEME_AUTO: u32 = 0;
 pub const THEME_CLASSIC: u32 = 1;
@@ -400,6 +408,52 @@ pub fn kill(pid: i32, sig: i32) -> i64 {
     unsafe { syscall2(NR_KILL, pid as u64, sig as u64) }
 }
 
+/// `which` for [`getpriority`] and [`setpriority`]: one process.
+pub const PRIO_PROCESS: i32 = 0;
+
+/// Nice value of the selected processes, or a negative errno. The kernel
+/// reports `20 - nice`; this undoes that offset.
+pub fn getpriority(which: i32, who: u32) -> Result<i32, i64> {
+    let raw = unsafe { syscall2(NR_GETPRIORITY, which as u64, who as u64) };
+    if raw < 0 {
+        Err(raw)
+    } else {
+        Ok(20 - raw as i32)
+    }
+}
+
+/// Set the nice value (-20 to 19) of the selected processes. Lowering it
+/// takes root.
+pub fn setpriority(which: i32, who: u32, nice: i32) -> i64 {
+    unsafe { syscall3(NR_SETPRIORITY, which as u64, who as u64, nice as u64) }
+}
+
+/// Scheduling policies for [`sched_setscheduler`].
+pub const SCHED_OTHER: i32 = 0;
+pub const SCHED_FIFO: i32 = 1;
+pub const SCHED_RR: i32 = 2;
+/// Or'd into a policy: children of the caller start as `SCHED_OTHER`.
+pub const SCHED_RESET_ON_FORK: i32 = 0x4000_0000;
+
+/// Set `pid`'s (0 for the caller) scheduling policy. `priority` is 1 to
+/// 99 for `SCHED_FIFO`/`SCHED_RR` and 0 for `SCHED_OTHER`; entering a
+/// real-time policy takes root.
+pub fn sched_setscheduler(pid: i32, policy: i32, priority: i32) -> i64 {
+    let param = priority;
+    unsafe {
+        syscall3(
+            NR_SCHED_SETSCHEDULER,
+            pid as u64,
+            policy as u64,
+            &param as *const i32 as u64,
+        )
+    }
+}
+
+pub fn sched_getscheduler(pid: i32) -> i64 {
+    unsafe { syscall1(NR_SCHED_GETSCHEDULER, pid as u64) }
+}
+
 pub fn nanosleep(request: &Timespec, remaining: Option<&mut Timespec>) -> i64 {
     unsafe {
         syscall2(
@@ -691,6 +745,74 @@ pub fn system_control_reset_wallpaper() -> Result<ApplyResult, i64> {
     decode_apply_result(result)
 }
 
+/// Core-dump directory. An empty result means core dumps are disabled.
+pub fn system_control_core_dir(buffer: &mut [u8]) -> Result<usize, i64> {
+    let result = unsafe {
+        syscall5(
+            NR_SYSTEM_CONTROL,
+            SYSTEM_CONTROL_GET_CORE_DIR,
+            0,
+            buffer.as_mut_ptr() as u64,
+            buffer.len() as u64,
+            0,
+        )
+    };
+    if result < 0 {
+        Err(result)
+    } else {
+        Ok(result as usize)
+    }
+}
+
+/// Set the core-dump directory: an absolute path, `none` or `default`.
+pub fn system_control_set_core_dir(value: &str) -> Result<ApplyResult, i64> {
+    let result = unsafe {
+        syscall5(
+            NR_SYSTEM_CONTROL,
+            SYSTEM_CONTROL_SET_CORE_DIR,
+            0,
+            value.as_ptr() as u64,
+            value.len() as u64,
+            0,
+        )
+    };
+    decode_apply_result(result)
+}
+
+/// Configured time zone name, e.g. `Europe/London`.
+pub fn system_control_time_zone(buffer: &mut [u8]) -> Result<usize, i64> {
+    let result = unsafe {
+        syscall5(
+            NR_SYSTEM_CONTROL,
+            SYSTEM_CONTROL_GET_TIME_ZONE,
+            0,
+            buffer.as_mut_ptr() as u64,
+            buffer.len() as u64,
+            0,
+        )
+    };
+    if result < 0 {
+        Err(result)
+    } else {
+        Ok(result as usize)
+    }
+}
+
+/// Set the time zone to a name listed in `/etc/zoneinfo/zone.list`.
+pub fn system_control_set_time_zone(name: &str) -> Result<ApplyResult, i64> {
+    let result = unsafe {
+        syscall5(
+            NR_SYSTEM_CONTROL,
+            SYSTEM_CONTROL_SET_TIME_ZONE,
+            0,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            0,
+        )
+    };
+    decode_apply_result(result)
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct GlFrameHeader {
@@ -81,6 +81,31 @@ stage_ca_certificates() {
     echo "Staged Mozilla CA bundle: $destination ($(wc -c < "$destination" | tr -d ' ') bytes)"
 }
 
+# Stage the curated IANA zoneinfo snapshot and its manifest. The kernel
+# imports the zones as /etc/zoneinfo and publishes the configured one as
+# /etc/localtime.
+stage_zoneinfo() {
+    local source_dir="$REPO_ROOT/userland/zoneinfo"
+    local etc_dir="$HOST_SHARE_STAGE/ETC"
+    local zone count=0
+
+    [ -f "$source_dir/zone.list" ] || {
+        echo "Missing zoneinfo manifest: $source_dir/zone.list" >&2
+        return 1
+    }
+    while read -r zone; do
+        [ -n "$zone" ] || continue
+        [ "$(head -c 4 "$source_dir/$zone" 2>/dev/null)" = TZif ] || {
+            echo "Missing or invalid zoneinfo file: $source_dir/$zone" >&2
+            return 1
+        }
+        _stage_atomic_copy "$source_dir/$zone" "$etc_dir/ZONEINFO/$zone" || return 1
+        count=$((count + 1))
+    done < "$source_dir/zone.list"
+    _stage_atomic_copy "$source_dir/zone.list" "$etc_dir/ZONEINFO.MANIFEST" || return 1
+    echo "Staged runtime /etc zoneinfo: $count zones"
+}
+
 # Publish only the public test root into test host shares. Server certificates
 # and private keys stay outside the guest-visible tree and are used solely by
 # the host-side guestfwd process.